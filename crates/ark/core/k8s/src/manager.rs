@@ -4,24 +4,102 @@ use std::sync::Arc;
 use anyhow::Result;
 use ark_core::tracer;
 use async_trait::async_trait;
-use futures::StreamExt;
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
 use k8s_openapi::{
     apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition,
     NamespaceResourceScope,
 };
 use kube::{
     api::{Patch, PatchParams, PostParams},
-    runtime::{controller::Action, watcher::Config, Controller},
+    runtime::{
+        controller::Action,
+        reflector::{ObjectRef, Store},
+        watcher::Config,
+        Controller,
+    },
     Api, Client, CustomResourceExt, Error, Resource, ResourceExt,
 };
 use opentelemetry::global;
-use serde::de::DeserializeOwned;
+use schemars::JsonSchema;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::json;
 use tracing::{info, instrument, warn, Level};
 
+mod election;
+
+pub use self::election::LeaderElector;
+
 pub struct Manager<C> {
     pub kube: Client,
     pub ctx: Arc<C>,
+    pub elector: Option<Arc<LeaderElector>>,
+    pub notifier: Option<Arc<dyn Notifier>>,
+}
+
+impl<C> Manager<C>
+where
+    C: Ctx,
+{
+    /// Return whether this replica should reconcile the resource.
+    pub fn is_leader(&self, data: &<C as Ctx>::Data) -> bool {
+        match &self.elector {
+            Some(elector) => elector.is_leader(data.meta().namespace.as_deref()),
+            None => true,
+        }
+    }
+
+    /// Notify the change of the resource to the other controllers, if the notifier is enabled.
+    ///
+    /// NOTE: The notifications are best-effort, as the controllers are requeued anyway.
+    pub async fn notify(&self, data: &<C as Ctx>::Data) {
+        if let Some(notifier) = &self.notifier {
+            let event = ResourceChanged::new(data);
+            if let Err(error) = notifier.notify(&event).await {
+                warn!(
+                    "failed to notify the change of {kind} ({namespace}/{name}): {error}",
+                    kind = &event.kind,
+                    namespace = event.namespace.as_deref().unwrap_or_default(),
+                    name = &event.name,
+                );
+            }
+        }
+    }
+}
+
+/// A notification bus of the resource changes, which is shared by the controllers.
+#[async_trait]
+pub trait Notifier
+where
+    Self: Send + Sync,
+{
+    async fn notify(&self, event: &ResourceChanged) -> Result<()>;
+
+    async fn subscribe(&self, kind: &str) -> Result<BoxStream<'static, ResourceChanged>>;
+}
+
+/// A changed resource, which may be the dependency of the other resources.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub struct ResourceChanged {
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    pub name: String,
+}
+
+impl ResourceChanged {
+    pub fn new<K>(data: &K) -> Self
+    where
+        K: Resource<DynamicType = ()>,
+    {
+        Self {
+            kind: K::kind(&()).into_owned(),
+            namespace: data.namespace(),
+            name: data.name_any(),
+        }
+    }
 }
 
 #[async_trait]
@@ -40,10 +118,28 @@ where
     const FALLBACK: Duration = Duration::from_secs(30 * 60); // 30 minutes
     const FINALIZER_NAME: &'static str = "";
 
+    /// The kinds of the dependencies, whose changes trigger the reconciliation
+    /// of all resources in the same namespace.
+    const DEPENDENCIES: &'static [&'static str] = &[];
+
+    /// Elect a leader among the replicas with the `Lease`s, so that only the leader reconciles.
+    const LEADER_ELECTION: bool = false;
+    /// The number of the leaders, which partition the resources by the namespace hash.
+    /// It takes effect only with the leader election.
+    const SHARDS: u32 = 1;
+    const LEASE_DURATION: Duration = Duration::from_secs(15); // 15 seconds
+
     fn get_subcrds() -> Vec<CustomResourceDefinition> {
         Default::default()
     }
 
+    /// Create the notifier to reconcile the resources as soon as they or their dependencies are changed.
+    ///
+    /// If none, the resources are reconciled only by the watch events and requeues.
+    async fn init_notifier() -> Result<Option<Arc<dyn Notifier>>> {
+        Ok(None)
+    }
+
     async fn spawn()
     where
         Self: Sized,
@@ -101,17 +197,49 @@ where
 
         let client = Client::try_default().await?;
         let ctx = Arc::new(Self::try_default().await?);
+        let notifier = Self::init_notifier().await?;
+        let elector = if <Self as Ctx>::LEADER_ELECTION {
+            let kind = <<Self as Ctx>::Data>::kind(&()).to_lowercase();
+            let elector = Arc::new(LeaderElector::new(
+                client.clone(),
+                format!("{name}-{kind}", name = <Self as Ctx>::NAME),
+                <Self as Ctx>::SHARDS,
+                <Self as Ctx>::LEASE_DURATION,
+            ));
+            ::tokio::spawn(elector.clone().run());
+            Some(elector)
+        } else {
+            None
+        };
         let manager = Arc::new(Manager {
             kube: client.clone(),
             ctx: ctx.clone(),
+            elector,
+            notifier: notifier.clone(),
         });
 
         let api = f_init(client).await?;
+        let controller = Controller::new(api, Config::default());
+        let controller = match notifier {
+            Some(notifier) => {
+                let trigger = Self::subscribe_changes(&*notifier, controller.store()).await?;
+                controller.reconcile_on(trigger)
+            }
+            None => controller,
+        };
 
         // All good. Start controller and return its future.
-        Controller::new(api, Config::default())
+        controller
             .run(
-                |data, manager| Self::reconcile(manager, data),
+                |data, manager| async move {
+                    if manager.is_leader(&data) {
+                        Self::reconcile(manager, data).await
+                    } else {
+                        // NOTE: the resource is reconciled by the other replica,
+                        // unless it loses the leadership
+                        Ok(Action::requeue(<Self as Ctx>::FALLBACK))
+                    }
+                },
                 |data, error, manager| {
                     let kind = <<Self as Ctx>::Data>::kind(&());
                     let name = data.name_any();
@@ -127,6 +255,39 @@ where
         Ok(())
     }
 
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn subscribe_changes(
+        notifier: &dyn Notifier,
+        store: Store<<Self as Ctx>::Data>,
+    ) -> Result<BoxStream<'static, ObjectRef<<Self as Ctx>::Data>>> {
+        let kind = <<Self as Ctx>::Data>::kind(&()).into_owned();
+
+        let mut streams = vec![notifier.subscribe(&kind).await?];
+        for dependency in <Self as Ctx>::DEPENDENCIES {
+            streams.push(notifier.subscribe(dependency).await?);
+        }
+
+        Ok(stream::select_all(streams)
+            .flat_map(move |event| {
+                let targets: Vec<_> = if event.kind == kind {
+                    let target = ObjectRef::new(&event.name);
+                    vec![match &event.namespace {
+                        Some(namespace) => target.within(namespace),
+                        None => target,
+                    }]
+                } else {
+                    store
+                        .state()
+                        .into_iter()
+                        .filter(|data| data.namespace() == event.namespace)
+                        .map(|data| ObjectRef::from_obj(&*data))
+                        .collect()
+                };
+                stream::iter(targets)
+            })
+            .boxed())
+    }
+
     fn init_resource(client: Client) -> Api<<Self as Ctx>::Data> {
         Api::<<Self as Ctx>::Data>::all(client)
     }
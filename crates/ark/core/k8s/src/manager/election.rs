@@ -0,0 +1,177 @@
+use core::time::Duration;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use anyhow::Result;
+use ark_core::env::infer_string;
+use k8s_openapi::{
+    api::coordination::v1::{Lease, LeaseSpec},
+    apimachinery::pkg::apis::meta::v1::MicroTime,
+    chrono::Utc,
+};
+use kube::{api::PostParams, core::ObjectMeta, Api, Client, Error};
+use tokio::time::sleep;
+use tracing::{info, instrument, warn, Level};
+
+/// Elects the leaders of the shards with the `Lease`s, which are shared by the replicas.
+///
+/// The resources are partitioned by the namespace hash, and each shard is reconciled
+/// only by the replica which holds its lease.
+pub struct LeaderElector {
+    api: Api<Lease>,
+    holder: String,
+    lease_duration: Duration,
+    name: String,
+    shards: Vec<AtomicBool>,
+}
+
+impl LeaderElector {
+    pub fn new(kube: Client, name: String, shards: u32, lease_duration: Duration) -> Self {
+        // NOTE: the pod name is unique among the replicas
+        let holder = infer_string("HOSTNAME")
+            .map(|hostname| format!("{hostname}-{pid}", pid = ::std::process::id()))
+            .unwrap_or_else(|_| format!("{name}-{pid}", pid = ::std::process::id()));
+
+        Self {
+            api: Api::default_namespaced(kube),
+            holder,
+            lease_duration,
+            name,
+            shards: (0..shards.max(1)).map(|_| AtomicBool::new(false)).collect(),
+        }
+    }
+
+    /// Return whether the replica leads the shard of the namespace.
+    pub fn is_leader(&self, namespace: Option<&str>) -> bool {
+        let shard = shard_of(namespace.unwrap_or_default(), self.shards.len());
+        self.shards[shard].load(Ordering::SeqCst)
+    }
+
+    /// Acquire and renew the leases forever.
+    pub async fn run(self: Arc<Self>) {
+        // NOTE: renew the leases well before they expire
+        let interval = self.lease_duration / 3;
+        loop {
+            // NOTE: acquire at most one shard per round, so that the shards are spread out
+            // to the replicas which are started together
+            let mut is_acquired = false;
+            for (shard, is_leader) in self.shards.iter().enumerate() {
+                let was_leader = is_leader.load(Ordering::SeqCst);
+                if !was_leader && is_acquired {
+                    continue;
+                }
+
+                let is_leader_now = match self.try_acquire_or_renew(shard, was_leader).await {
+                    Ok(is_leader_now) => is_leader_now,
+                    Err(error) => {
+                        warn!("failed to renew lease: {error}");
+                        false
+                    }
+                };
+                is_leader.store(is_leader_now, Ordering::SeqCst);
+
+                if !was_leader && is_leader_now {
+                    is_acquired = true;
+                    info!("acquired lease: {name}", name = self.lease_name(shard));
+                } else if was_leader && !is_leader_now {
+                    warn!("lost lease: {name}", name = self.lease_name(shard));
+                }
+            }
+            sleep(interval).await;
+        }
+    }
+
+    fn lease_name(&self, shard: usize) -> String {
+        let name = &self.name;
+        if self.shards.len() == 1 {
+            name.clone()
+        } else {
+            format!("{name}-{shard}")
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    async fn try_acquire_or_renew(&self, shard: usize, was_leader: bool) -> Result<bool> {
+        let name = self.lease_name(shard);
+        let now = MicroTime(Utc::now());
+        let pp = PostParams::default();
+
+        let lease = match self.api.get_opt(&name).await? {
+            Some(lease) => lease,
+            None => {
+                let lease = Lease {
+                    metadata: ObjectMeta {
+                        name: Some(name),
+                        ..Default::default()
+                    },
+                    spec: Some(LeaseSpec {
+                        acquire_time: Some(now.clone()),
+                        holder_identity: Some(self.holder.clone()),
+                        lease_duration_seconds: Some(self.lease_duration.as_secs() as i32),
+                        lease_transitions: Some(0),
+                        renew_time: Some(now),
+                        ..Default::default()
+                    }),
+                };
+                return map_conflict(self.api.create(&pp, &lease).await);
+            }
+        };
+
+        let mut spec = lease.spec.clone().unwrap_or_default();
+        let is_holder = spec.holder_identity.as_deref() == Some(self.holder.as_str());
+        if !is_holder {
+            let lease_duration = spec
+                .lease_duration_seconds
+                .and_then(|seconds| seconds.try_into().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(self.lease_duration);
+            let is_expired = spec
+                .renew_time
+                .as_ref()
+                .and_then(|MicroTime(renew_time)| (Utc::now() - *renew_time).to_std().ok())
+                .map(|elapsed| elapsed > lease_duration)
+                .unwrap_or(true);
+            if !is_expired {
+                return Ok(false);
+            }
+
+            spec.acquire_time = Some(now.clone());
+            spec.holder_identity = Some(self.holder.clone());
+            spec.lease_duration_seconds = Some(self.lease_duration.as_secs() as i32);
+            spec.lease_transitions = Some(spec.lease_transitions.unwrap_or_default() + 1);
+        } else if !was_leader {
+            // NOTE: the lease has been held by the previous run of this replica
+            spec.acquire_time = Some(now.clone());
+        }
+        spec.renew_time = Some(now);
+
+        // NOTE: the resource version is kept to detect the concurrent updates
+        let lease = Lease {
+            metadata: lease.metadata,
+            spec: Some(spec),
+        };
+        map_conflict(self.api.replace(&name, &pp, &lease).await)
+    }
+}
+
+fn map_conflict(result: Result<Lease, Error>) -> Result<bool> {
+    match result {
+        Ok(_) => Ok(true),
+        // NOTE: the other replica has just taken the lease
+        Err(Error::Api(error)) if error.code == 409 => Ok(false),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Map the namespace into the shard, with a hash function which is stable across the replicas.
+fn shard_of(namespace: &str, shards: usize) -> usize {
+    // FNV-1a
+    let hash = namespace
+        .bytes()
+        .fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+    (hash % shards as u64) as usize
+}
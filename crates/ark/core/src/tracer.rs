@@ -82,6 +82,11 @@ fn init_once_opentelemetry(export: bool) {
 
         let name = env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| "ark-core".into());
 
+        // propagate the W3C trace context across the services
+        ::opentelemetry::global::set_text_map_propagator(
+            ::opentelemetry_sdk::propagation::TraceContextPropagator::new(),
+        );
+
         otlp::new_pipeline()
             .tracing()
             .with_exporter(init_otlp_pipeline())
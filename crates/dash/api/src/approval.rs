@@ -0,0 +1,210 @@
+use chrono::{DateTime, Utc};
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, CustomResource)]
+#[kube(
+    group = "dash.ulagbulag.io",
+    version = "v1alpha1",
+    kind = "Approval",
+    root = "ApprovalCrd",
+    status = "ApprovalStatus",
+    shortname = "apv",
+    namespaced,
+    printcolumn = r#"{
+        "name": "state",
+        "type": "string",
+        "description": "state of the approval",
+        "jsonPath": ".status.state"
+    }"#,
+    printcolumn = r#"{
+        "name": "operation",
+        "type": "string",
+        "description": "operation to be approved",
+        "jsonPath": ".spec.operation"
+    }"#,
+    printcolumn = r#"{
+        "name": "target",
+        "type": "string",
+        "description": "name of the target resource",
+        "jsonPath": ".spec.target.name"
+    }"#,
+    printcolumn = r#"{
+        "name": "decided-by",
+        "type": "string",
+        "description": "user who has decided the approval",
+        "jsonPath": ".status.decidedBy"
+    }"#,
+    printcolumn = r#"{
+        "name": "expires-at",
+        "type": "date",
+        "description": "expiry time",
+        "jsonPath": ".spec.expiresAt"
+    }"#,
+    printcolumn = r#"{
+        "name": "created-at",
+        "type": "date",
+        "description": "created time",
+        "jsonPath": ".metadata.creationTimestamp"
+    }"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalSpec {
+    pub operation: ApprovalOperation,
+    pub target: ApprovalTargetSpec,
+    /// The reason why the operation is requested.
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// The controller or user who has requested the operation.
+    pub requested_by: String,
+    /// The approval is expired if it is not decided until this time.
+    pub expires_at: DateTime<Utc>,
+    /// The decision of an approver, e.g. `kubectl patch` or the gateway API.
+    #[serde(default)]
+    pub decision: Option<ApprovalDecisionSpec>,
+}
+
+impl ApprovalCrd {
+    pub const LABEL_OPERATION: &'static str = "dash.ulagbulag.io/approval-operation";
+    pub const LABEL_TARGET_NAME: &'static str = "dash.ulagbulag.io/approval-target-name";
+
+    /// The name of the approval, which is unique per the target generation.
+    pub fn name_of(operation: ApprovalOperation, target: &ApprovalTargetSpec) -> String {
+        let operation = operation.to_string().to_lowercase();
+        let kind = target.kind.to_lowercase();
+        let name = &target.name;
+        match target.generation {
+            Some(generation) => format!("{kind}-{name}-{operation}-{generation}"),
+            None => format!("{kind}-{name}-{operation}"),
+        }
+    }
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum ApprovalOperation {
+    /// Deleting a model storage binding with its data.
+    BindingDeletion,
+    /// Migrating the data of a model storage binding into another model storage.
+    StorageMigration,
+    /// Applying a breaking schema change to a model which has stored data.
+    ModelSchemaChange,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalTargetSpec {
+    pub kind: String,
+    pub name: String,
+    #[serde(default)]
+    pub generation: Option<i64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalDecisionSpec {
+    pub approved: bool,
+    /// The name of the approver, which should be listed in the tenant config.
+    pub by: String,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalStatus {
+    #[serde(default)]
+    pub state: ApprovalState,
+    #[serde(default)]
+    pub decided_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub decided_by: Option<String>,
+    #[serde(default)]
+    pub comment: Option<String>,
+    #[serde(default)]
+    pub message: Option<String>,
+    pub last_updated: DateTime<Utc>,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    Default,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum ApprovalState {
+    #[default]
+    Pending,
+    Approved,
+    Rejected,
+    Expired,
+}
+
+impl ApprovalState {
+    pub const fn is_decided(&self) -> bool {
+        !matches!(self, Self::Pending)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalPolicySpec {
+    /// The operations which should be approved before proceeding.
+    #[serde(default)]
+    pub operations: Vec<ApprovalOperation>,
+    /// The users who are allowed to decide the approvals.
+    #[serde(default)]
+    pub approvers: Vec<String>,
+    /// The time to wait for a decision.
+    #[serde(default = "ApprovalPolicySpec::default_ttl_seconds")]
+    pub ttl_seconds: u32,
+}
+
+impl Default for ApprovalPolicySpec {
+    fn default() -> Self {
+        Self {
+            operations: Vec::default(),
+            approvers: Vec::default(),
+            ttl_seconds: Self::default_ttl_seconds(),
+        }
+    }
+}
+
+impl ApprovalPolicySpec {
+    const fn default_ttl_seconds() -> u32 {
+        24 * 60 * 60 // 1 day
+    }
+
+    pub fn is_required(&self, operation: ApprovalOperation) -> bool {
+        self.operations.contains(&operation)
+    }
+
+    pub fn is_approver(&self, user_name: &str) -> bool {
+        self.approvers.iter().any(|name| name == user_name)
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+use crate::{
+    function::FunctionSpec, model::ModelSpec, model_storage_binding::ModelStorageBindingSpec,
+    storage::ModelStorageSpec, task::TaskSpec,
+};
+
+/// A portable bundle of the dash resources of a namespace.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DashBundle {
+    pub version: String,
+    /// The namespace which the resources have been exported from
+    pub namespace: String,
+    pub exported_at: DateTime<Utc>,
+    #[serde(default)]
+    pub bindings: Vec<DashBundleItem<ModelStorageBindingSpec>>,
+    #[serde(default)]
+    pub functions: Vec<DashBundleItem<FunctionSpec>>,
+    #[serde(default)]
+    pub models: Vec<DashBundleItem<ModelSpec>>,
+    #[serde(default)]
+    pub storages: Vec<DashBundleItem<ModelStorageSpec>>,
+    #[serde(default)]
+    pub tasks: Vec<DashBundleItem<TaskSpec>>,
+}
+
+impl DashBundle {
+    pub const VERSION: &'static str = "dash.ulagbulag.io/bundle/v1";
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DashBundleItem<Spec> {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+    pub spec: Spec,
+}
+
+/// What to do when a resource of the same name already exists.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    Default,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum DashBundleConflictStrategy {
+    /// Keep the existing resource
+    #[default]
+    Skip,
+    /// Replace the spec of the existing resource
+    Overwrite,
+    /// Import the resource with a new name, rewriting the references to it
+    Rename,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DashBundleImportReport {
+    pub items: Vec<DashBundleImportItem>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DashBundleImportItem {
+    pub kind: String,
+    /// The name in the bundle
+    pub source_name: String,
+    /// The name in the target namespace
+    pub name: String,
+    pub result: DashBundleImportResult,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum DashBundleImportResult {
+    Created,
+    Overwritten,
+    Renamed,
+    Skipped,
+}
@@ -0,0 +1,70 @@
+use chrono::Utc;
+pub use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
+
+/// The condition whether the resource is ready to be used.
+pub const TYPE_READY: &str = "Ready";
+
+/// The condition whether the resource is placed by the optimizer,
+/// rather than by the local fallback policy.
+pub const TYPE_OPTIMIZED: &str = "Optimized";
+
+/// The condition whether the mutating reconciliation is paused by a maintenance window.
+pub const TYPE_FROZEN: &str = "Frozen";
+
+/// Insert or update the condition of the given type.
+///
+/// The transition time is kept if the status is not changed.
+pub fn set_condition(
+    conditions: &mut Vec<Condition>,
+    type_: &str,
+    status: bool,
+    reason: &str,
+    message: impl ToString,
+    observed_generation: Option<i64>,
+) {
+    let condition = Condition {
+        last_transition_time: Time(Utc::now()),
+        message: message.to_string(),
+        observed_generation,
+        reason: reason.into(),
+        status: if status { "True" } else { "False" }.into(),
+        type_: type_.into(),
+    };
+
+    match conditions.iter_mut().find(|last| last.type_ == type_) {
+        Some(last) => {
+            let last_transition_time = if last.status == condition.status {
+                last.last_transition_time.clone()
+            } else {
+                condition.last_transition_time.clone()
+            };
+            *last = Condition {
+                last_transition_time,
+                ..condition
+            };
+        }
+        None => conditions.push(condition),
+    }
+}
+
+/// Return the updated conditions, starting from the last ones if any.
+pub fn with_condition(
+    last: Option<&[Condition]>,
+    type_: &str,
+    status: bool,
+    reason: &str,
+    message: impl ToString,
+    observed_generation: Option<i64>,
+) -> Vec<Condition> {
+    let mut conditions = last.map(|last| last.to_vec()).unwrap_or_default();
+    set_condition(
+        &mut conditions,
+        type_,
+        status,
+        reason,
+        message,
+        observed_generation,
+    );
+    conditions
+}
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use ark_core_k8s::data::Name;
 use chrono::{DateTime, Utc};
 use kube::CustomResource;
@@ -23,6 +25,12 @@ use crate::model::ModelFieldsNativeSpec;
         "description": "state of the function",
         "jsonPath": ".status.state"
     }"#,
+    printcolumn = r#"{
+        "name": "failed-tests",
+        "type": "integer",
+        "description": "number of the failed golden examples",
+        "jsonPath": ".status.test.failed"
+    }"#,
     printcolumn = r#"{
         "name": "created-at",
         "type": "date",
@@ -45,6 +53,8 @@ pub struct FunctionSpec<Spec = Name, Exec = FunctionExec> {
     #[serde(rename = "type")]
     pub type_: StrawFunctionType,
     pub volatility: FunctionVolatility,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test: Option<FunctionTestSpec>,
 }
 
 impl FunctionCrd {
@@ -55,6 +65,7 @@ impl FunctionCrd {
 #[serde(rename_all = "camelCase")]
 pub enum FunctionExec {
     Placeholder {},
+    Knative(FunctionKnativeSpec),
     Straw(StrawFunction),
 }
 
@@ -64,6 +75,55 @@ impl Default for FunctionExec {
     }
 }
 
+/// A function, which is deployed as a Knative Service and invoked over HTTP.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionKnativeSpec {
+    pub image: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub autoscaling: FunctionAutoscalingSpec,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionAutoscalingSpec {
+    /// The minimum number of the replicas; `0` enables scale-to-zero.
+    #[serde(default)]
+    pub min_scale: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_scale: Option<u32>,
+    /// The target number of the concurrent requests per replica.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_concurrency: Option<u32>,
+    /// How long the last replica is kept after the traffic is gone, e.g. `1m`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale_to_zero_retention_period: Option<String>,
+}
+
+/// The golden examples, which verify the deployed function.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionTestSpec {
+    #[serde(default)]
+    pub examples: Vec<FunctionTestExampleSpec>,
+}
+
+/// A pair of the records, which are stored in the input and output models respectively.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionTestExampleSpec {
+    /// The name of the record in the input model.
+    pub input: String,
+    /// The name of the expected record in the output model.
+    pub output: String,
+}
+
 #[derive(
     Copy,
     Clone,
@@ -102,9 +162,49 @@ pub struct FunctionStatus {
     #[serde(default)]
     pub state: FunctionState,
     pub spec: Option<FunctionSpec<ModelFieldsNativeSpec>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<FunctionEndpointStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test: Option<FunctionTestStatus>,
     pub last_updated: DateTime<Utc>,
 }
 
+/// The status of the deployed function, which is propagated from its backend.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionEndpointStatus {
+    #[serde(default)]
+    pub ready: bool,
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub revision: Option<String>,
+    /// The in-cluster address of the function.
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+/// The results of running the function against its golden examples.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionTestStatus {
+    /// The generation of the function which has been tested.
+    pub generation: i64,
+    pub passed: u32,
+    pub failed: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<FunctionTestFailure>,
+    pub last_tested: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionTestFailure {
+    #[serde(flatten)]
+    pub example: FunctionTestExampleSpec,
+    pub message: String,
+}
+
 #[derive(
     Copy,
     Clone,
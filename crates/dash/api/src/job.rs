@@ -2,7 +2,7 @@ use std::collections::BTreeMap;
 
 use dash_provider_api::TaskChannel;
 use k8s_openapi::chrono::{DateTime, Utc};
-use kube::CustomResource;
+use kube::{CustomResource, ResourceExt};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -23,6 +23,12 @@ use strum::{Display, EnumString};
         "description": "state of the dash job",
         "jsonPath": ".status.state"
     }"#,
+    printcolumn = r#"{
+        "name": "queue-position",
+        "type": "integer",
+        "description": "position in the queue",
+        "jsonPath": ".status.queue.position"
+    }"#,
     printcolumn = r#"{
         "name": "created-at",
         "type": "date",
@@ -44,7 +50,18 @@ impl DashJobCrd {
     pub const LABEL_TARGET_TASK: &'static str = "dash.ulagbulag.io/target-task";
     pub const LABEL_TARGET_TASK_NAMESPACE: &'static str = "dash.ulagbulag.io/target-task-namespace";
 
-    fn preserve_arbitrary(
+    /// The name of the root job of the pipeline, which spawned this job.
+    pub const LABEL_PIPELINE: &'static str = "dash.ulagbulag.io/pipeline";
+
+    /// Return the name of the root job of the pipeline.
+    pub fn pipeline_name(&self) -> String {
+        self.labels()
+            .get(Self::LABEL_PIPELINE)
+            .cloned()
+            .unwrap_or_else(|| self.name_any())
+    }
+
+    pub(crate) fn preserve_arbitrary(
         _gen: &mut ::schemars::gen::SchemaGenerator,
     ) -> ::schemars::schema::Schema {
         let mut obj = ::schemars::schema::SchemaObject::default();
@@ -61,9 +78,83 @@ pub struct DashJobStatus {
     pub channel: Option<TaskChannel>,
     #[serde(default)]
     pub state: DashJobState,
+    /// The rolled-up status of the pipeline, which is only set on the root job.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pipeline: Option<DashJobPipelineStatus>,
+    /// The queue admitting the job, which is kept after the admission.
+    #[serde(default)]
+    pub queue: Option<DashJobQueueStatus>,
     pub last_updated: DateTime<Utc>,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DashJobQueueStatus {
+    pub name: String,
+    pub priority: i32,
+    /// The position in the queue, starting from 1; missing once admitted.
+    #[serde(default)]
+    pub position: Option<u32>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DashJobPipelineStatus {
+    #[serde(default)]
+    pub state: DashJobPipelineState,
+    /// The states of the tasks in the pipeline, by the task names.
+    #[serde(default)]
+    pub tasks: BTreeMap<String, DashJobPipelineTaskState>,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    Default,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum DashJobPipelineState {
+    #[default]
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    Default,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum DashJobPipelineTaskState {
+    #[default]
+    Pending,
+    Running,
+    Completed,
+    Error,
+    Skipped,
+}
+
 #[derive(
     Copy,
     Clone,
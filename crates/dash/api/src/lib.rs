@@ -1,11 +1,21 @@
+pub mod approval;
+pub mod audit;
+pub mod bundle;
+pub mod condition;
 pub mod function;
 pub mod job;
+pub mod lineage;
+pub mod maintenance;
 pub mod model;
 pub mod model_claim;
 pub mod model_storage_binding;
 pub mod model_user;
+pub mod notification;
+pub mod orphaned_artifact;
+pub mod queue;
 pub mod storage;
 pub mod task;
+pub mod tenant;
 
 pub mod consts {
     pub const NAMESPACE: &str = "dash";
@@ -0,0 +1,71 @@
+use std::collections::BTreeSet;
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// A version of the model, which is identified by its generation.
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct LineageModel {
+    pub name: String,
+    /// The generation of the model; missing if unknown.
+    #[serde(default)]
+    pub version: Option<i64>,
+}
+
+/// A derivation of the downstream model from the upstream one, which is performed by a function.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LineageEdge {
+    pub timestamp: DateTime<Utc>,
+    pub namespace: String,
+    pub function: String,
+    #[serde(default)]
+    pub function_version: Option<i64>,
+    /// The id of the function run; missing if derived on the function deployment.
+    #[serde(default)]
+    pub run: Option<String>,
+    pub upstream: LineageModel,
+    pub downstream: LineageModel,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LineageQuery {
+    /// The version of the model; all versions are followed if missing.
+    #[serde(default)]
+    pub version: Option<i64>,
+    /// The maximum number of the derivations to follow.
+    #[serde(default)]
+    pub depth: Option<u32>,
+}
+
+impl LineageQuery {
+    pub const DEFAULT_DEPTH: u32 = 8;
+    pub const MAX_DEPTH: u32 = 32;
+
+    pub fn depth(&self) -> u32 {
+        self.depth
+            .unwrap_or(Self::DEFAULT_DEPTH)
+            .min(Self::MAX_DEPTH)
+    }
+}
+
+/// The models derived from the root model, with the derivations between them.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LineageGraph {
+    pub root: LineageModel,
+    #[serde(default)]
+    pub edges: Vec<LineageEdge>,
+}
+
+impl LineageGraph {
+    /// Return the downstream models, which are affected by the root model.
+    pub fn downstream(&self) -> BTreeSet<&LineageModel> {
+        self.edges.iter().map(|edge| &edge.downstream).collect()
+    }
+}
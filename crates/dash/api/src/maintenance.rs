@@ -0,0 +1,106 @@
+use chrono::{DateTime, Utc};
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, CustomResource)]
+#[kube(
+    group = "dash.ulagbulag.io",
+    version = "v1alpha1",
+    kind = "MaintenanceWindow",
+    root = "MaintenanceWindowCrd",
+    status = "MaintenanceWindowStatus",
+    shortname = "mw",
+    printcolumn = r#"{
+        "name": "state",
+        "type": "string",
+        "description": "state of the maintenance window",
+        "jsonPath": ".status.state"
+    }"#,
+    printcolumn = r#"{
+        "name": "starts-at",
+        "type": "date",
+        "description": "start time of the maintenance window",
+        "jsonPath": ".spec.startsAt"
+    }"#,
+    printcolumn = r#"{
+        "name": "ends-at",
+        "type": "date",
+        "description": "end time of the maintenance window",
+        "jsonPath": ".spec.endsAt"
+    }"#,
+    printcolumn = r#"{
+        "name": "created-at",
+        "type": "date",
+        "description": "created time",
+        "jsonPath": ".metadata.creationTimestamp"
+    }"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceWindowSpec {
+    /// The window is active from the creation if missing.
+    #[serde(default)]
+    pub starts_at: Option<DateTime<Utc>>,
+    /// The window is active until it is deleted if missing.
+    #[serde(default)]
+    pub ends_at: Option<DateTime<Utc>>,
+    /// The reason of the change freeze, e.g. the cluster upgrade.
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// The namespaces to be frozen; all if empty.
+    #[serde(default)]
+    pub namespaces: Vec<String>,
+    /// Whether the KISS jobs are also frozen, e.g. commissioning and upgrading the boxes.
+    #[serde(default)]
+    pub kiss: bool,
+}
+
+impl MaintenanceWindowSpec {
+    pub fn state_at(&self, now: DateTime<Utc>) -> MaintenanceWindowState {
+        if self.starts_at.is_some_and(|starts_at| now < starts_at) {
+            MaintenanceWindowState::Scheduled
+        } else if self.ends_at.is_some_and(|ends_at| now >= ends_at) {
+            MaintenanceWindowState::Closed
+        } else {
+            MaintenanceWindowState::Active
+        }
+    }
+
+    /// Return `true` if the mutating reconciliation of the namespace should be paused.
+    pub fn is_freezing(&self, namespace: &str, now: DateTime<Utc>) -> bool {
+        matches!(self.state_at(now), MaintenanceWindowState::Active)
+            && (self.namespaces.is_empty() || self.namespaces.iter().any(|ns| ns == namespace))
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceWindowStatus {
+    #[serde(default)]
+    pub state: MaintenanceWindowState,
+    pub last_updated: DateTime<Utc>,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    Default,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum MaintenanceWindowState {
+    #[default]
+    Scheduled,
+    Active,
+    Closed,
+}
@@ -1,10 +1,14 @@
+use std::collections::BTreeMap;
+
 use anyhow::{bail, Result};
 use chrono::{DateTime, Utc};
-use kube::CustomResource;
+use kube::{CustomResource, ResourceExt};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use strum::{Display, EnumString};
 
+use crate::condition::Condition;
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, CustomResource)]
 #[kube(
     group = "dash.ulagbulag.io",
@@ -55,6 +59,22 @@ impl Default for ModelSpec {
 impl ModelCrd {
     pub const FINALIZER_NAME: &'static str = "dash.ulagbulag.io/finalizer-models";
 
+    /// Allow the breaking schema changes even if the bound storages have data.
+    pub const ANNOTATION_ALLOW_BREAKING_CHANGES: &'static str =
+        "dash.ulagbulag.io/allow-breaking-changes";
+
+    /// The condition whether the latest schema change is compatible with the stored data.
+    pub const CONDITION_COMPATIBLE: &'static str = "Compatible";
+
+    pub const MAX_VERSION_HISTORY: usize = 16;
+
+    pub fn is_breaking_changes_allowed(&self) -> bool {
+        self.annotations()
+            .get(Self::ANNOTATION_ALLOW_BREAKING_CHANGES)
+            .map(|value| value == "true")
+            .unwrap_or_default()
+    }
+
     pub fn get_fields_unchecked(&self) -> &ModelFieldsNativeSpec {
         self.status
             .as_ref()
@@ -76,10 +96,239 @@ impl ModelCrd {
 pub struct ModelStatus {
     #[serde(default)]
     pub state: ModelState,
+    #[serde(default)]
+    pub conditions: Vec<Condition>,
     pub fields: Option<ModelFieldsSpec<ModelFieldKindNativeSpec>>,
+    #[serde(default)]
+    pub history: Vec<ModelVersion>,
     pub last_updated: DateTime<Utc>,
 }
 
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelVersion {
+    #[serde(default)]
+    pub generation: Option<i64>,
+    #[serde(default)]
+    pub changes: Vec<ModelFieldChange>,
+    #[serde(default)]
+    pub compatibility: ModelCompatibility,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl ModelVersion {
+    pub fn new(
+        generation: Option<i64>,
+        last: Option<&ModelFieldsNativeSpec>,
+        fields: &ModelFieldsNativeSpec,
+    ) -> Self {
+        let changes = last
+            .map(|last| ModelFieldChange::diff(last, fields))
+            .unwrap_or_default();
+
+        Self {
+            generation,
+            compatibility: changes
+                .iter()
+                .map(|change| change.compatibility)
+                .max()
+                .unwrap_or_default(),
+            changes,
+            timestamp: Utc::now(),
+        }
+    }
+}
+
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelFieldChange {
+    pub name: String,
+    pub kind: ModelFieldChangeKind,
+    pub compatibility: ModelCompatibility,
+    #[serde(default)]
+    pub from: Option<ModelFieldKindNativeType>,
+    #[serde(default)]
+    pub to: Option<ModelFieldKindNativeType>,
+}
+
+impl ModelFieldChange {
+    /// Compute the structural diff between the two field sets.
+    pub fn diff(last: &ModelFieldsNativeSpec, fields: &ModelFieldsNativeSpec) -> Vec<Self> {
+        let last: BTreeMap<_, _> = last.iter().map(|field| (&field.name, field)).collect();
+        let fields: BTreeMap<_, _> = fields.iter().map(|field| (&field.name, field)).collect();
+
+        let removed = last
+            .iter()
+            .filter(|(name, _)| !fields.contains_key(*name))
+            .map(|(name, field)| Self {
+                name: name.to_string(),
+                kind: ModelFieldChangeKind::Removed,
+                compatibility: ModelCompatibility::Breaking,
+                from: Some(field.kind.to_type()),
+                to: None,
+            });
+
+        let added_or_changed = fields.iter().filter_map(|(name, field)| {
+            match last.get(name) {
+                // the existing rows have no value for the new field
+                None => Some(Self {
+                    name: name.to_string(),
+                    kind: ModelFieldChangeKind::Added,
+                    compatibility: if field.attribute.optional {
+                        ModelCompatibility::Compatible
+                    } else {
+                        ModelCompatibility::Breaking
+                    },
+                    from: None,
+                    to: Some(field.kind.to_type()),
+                }),
+                Some(last) if last.kind != field.kind || last.attribute != field.attribute => {
+                    Some(Self {
+                        name: name.to_string(),
+                        kind: ModelFieldChangeKind::Changed,
+                        compatibility: ModelCompatibility::classify(last, field),
+                        from: Some(last.kind.to_type()),
+                        to: Some(field.kind.to_type()),
+                    })
+                }
+                Some(_) => None,
+            }
+        });
+
+        removed.chain(added_or_changed).collect()
+    }
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum ModelFieldChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    Default,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum ModelCompatibility {
+    #[default]
+    Compatible,
+    Breaking,
+}
+
+impl ModelCompatibility {
+    /// Classify whether the stored values of the last field are still valid for the given field.
+    fn classify(last: &ModelFieldNativeSpec, field: &ModelFieldNativeSpec) -> Self {
+        fn is_narrowed<T>(last: Option<T>, next: Option<T>, is_less: fn(&T, &T) -> bool) -> bool {
+            match (last, next) {
+                (_, None) => false,
+                (None, Some(_)) => true,
+                (Some(last), Some(next)) => is_less(&last, &next),
+            }
+        }
+
+        if last.attribute.optional && !field.attribute.optional {
+            return Self::Breaking;
+        }
+
+        let is_breaking = match (&last.kind, &field.kind) {
+            (
+                ModelFieldKindNativeSpec::Integer {
+                    minimum: last_minimum,
+                    maximum: last_maximum,
+                    ..
+                },
+                ModelFieldKindNativeSpec::Integer {
+                    minimum, maximum, ..
+                },
+            ) => {
+                is_narrowed(*last_minimum, *minimum, |last, next| last < next)
+                    || is_narrowed(*last_maximum, *maximum, |last, next| last > next)
+            }
+            (
+                ModelFieldKindNativeSpec::Number {
+                    minimum: last_minimum,
+                    maximum: last_maximum,
+                    ..
+                },
+                ModelFieldKindNativeSpec::Number {
+                    minimum, maximum, ..
+                },
+            ) => {
+                is_narrowed(*last_minimum, *minimum, |last, next| last < next)
+                    || is_narrowed(*last_maximum, *maximum, |last, next| last > next)
+            }
+            (
+                ModelFieldKindNativeSpec::String {
+                    kind: last_kind, ..
+                },
+                ModelFieldKindNativeSpec::String { kind, .. },
+            ) => last_kind != kind && *kind != ModelFieldKindStringSpec::Dynamic {},
+            (
+                ModelFieldKindNativeSpec::OneOfStrings {
+                    choices: last_choices,
+                    ..
+                },
+                ModelFieldKindNativeSpec::OneOfStrings { choices, .. },
+            ) => !last_choices.iter().all(|choice| choices.contains(choice)),
+            (
+                ModelFieldKindNativeSpec::Object {
+                    kind: last_kind, ..
+                },
+                ModelFieldKindNativeSpec::Object { kind, .. },
+            ) => match (last_kind, kind) {
+                (
+                    ModelFieldKindObjectSpec::Enumerate {
+                        choices: last_choices,
+                    },
+                    ModelFieldKindObjectSpec::Enumerate { choices },
+                ) => !last_choices.iter().all(|choice| choices.contains(choice)),
+                (last_kind, kind) => {
+                    last_kind != kind && *kind != ModelFieldKindObjectSpec::Dynamic {}
+                }
+            },
+            // NOTE: the children are compared as the separated fields
+            (last_kind, kind) => last_kind.to_type() != kind.to_type(),
+        };
+
+        if is_breaking {
+            Self::Breaking
+        } else {
+            Self::Compatible
+        }
+    }
+}
+
 pub type ModelFieldsSpec<Kind = ModelFieldKindSpec> = Vec<ModelFieldSpec<Kind>>;
 pub type ModelFieldsNativeSpec = ModelFieldsSpec<ModelFieldKindNativeSpec>;
 
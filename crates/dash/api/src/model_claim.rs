@@ -1,11 +1,13 @@
 use chrono::{DateTime, Utc};
-use k8s_openapi::api::core::v1::ResourceRequirements;
+use k8s_openapi::{
+    api::core::v1::ResourceRequirements, apimachinery::pkg::api::resource::Quantity,
+};
 use kube::CustomResource;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use strum::{Display, EnumString};
 
-use crate::storage::ModelStorageKind;
+use crate::{condition::Condition, storage::ModelStorageKind};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, CustomResource)]
 #[kube(
@@ -51,6 +53,8 @@ use crate::storage::ModelStorageKind;
 pub struct ModelClaimSpec {
     #[serde(default)]
     pub affinity: ModelClaimAffinity,
+    #[serde(default)]
+    pub autoscaling: Option<ModelClaimAutoscaling>,
     #[serde(default = "ModelClaimSpec::default_allow_replacement")]
     pub allow_replacement: bool,
     #[serde(default)]
@@ -63,22 +67,32 @@ pub struct ModelClaimSpec {
     pub storage: Option<ModelStorageKind>,
     #[serde(default)]
     pub storage_name: Option<String>,
+    /// The storages to distribute the model across, by their weights.
+    ///
+    /// If it is not empty, `storage` and `storageName` are ignored.
+    #[serde(default)]
+    pub storages: Vec<ModelClaimStorageTarget>,
 }
 
 impl ModelClaimCrd {
     pub const FINALIZER_NAME: &'static str = "dash.ulagbulag.io/finalizer-model-claims";
+
+    /// The maximum number of the resize records kept in the status.
+    pub const MAX_RESIZE_HISTORY: usize = 16;
 }
 
 impl Default for ModelClaimSpec {
     fn default() -> Self {
         Self {
             affinity: ModelClaimAffinity::default(),
+            autoscaling: None,
             allow_replacement: Self::default_allow_replacement(),
             binding_policy: ModelClaimBindingPolicy::default(),
             deletion_policy: ModelClaimDeletionPolicy::default(),
             resources: None,
             storage: None,
             storage_name: None,
+            storages: Vec::default(),
         }
     }
 }
@@ -89,6 +103,50 @@ impl ModelClaimSpec {
     }
 }
 
+/// A model storage to bind a part of the model claim.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelClaimStorageTarget {
+    pub storage_name: String,
+    /// The relative share of the storage quota, e.g. `80` of hot and `20` of cold storages.
+    #[serde(default = "ModelClaimStorageTarget::default_weight")]
+    pub weight: u8,
+}
+
+impl ModelClaimStorageTarget {
+    const fn default_weight() -> u8 {
+        1
+    }
+}
+
+/// Grow the storage quota of the bound models on demand.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelClaimAutoscaling {
+    /// The upper limit of the storage quota.
+    #[serde(default)]
+    pub max_storage: Option<Quantity>,
+    /// The usage ratio of the storage quota to keep, in percent.
+    /// The quota is grown when the usage crosses it.
+    #[serde(default = "ModelClaimAutoscaling::default_target_utilization")]
+    pub target_utilization: u8,
+}
+
+impl Default for ModelClaimAutoscaling {
+    fn default() -> Self {
+        Self {
+            max_storage: None,
+            target_utilization: Self::default_target_utilization(),
+        }
+    }
+}
+
+impl ModelClaimAutoscaling {
+    const fn default_target_utilization() -> u8 {
+        80
+    }
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ModelClaimAffinity {
@@ -168,10 +226,45 @@ pub enum ModelClaimAffinityExpressionSource {
     JsonSchema,
 )]
 pub enum ModelClaimBindingPolicy {
+    /// Prefer the storages with both low usage and low latency.
     Balanced,
+    /// Prefer the fullest storages which can still afford the quota,
+    /// so that the other storages are kept empty.
+    BinPacking,
+    /// Prefer the storages with the most available capacity.
     #[default]
     LowestCopy,
+    /// Prefer the storages whose models have the lowest call latency,
+    /// which is collected from the dash function edges of kubegraph.
     LowestLatency,
+    /// Prefer the storages with the lowest usage ratio.
+    Spread,
+}
+
+/// A scored model storage, which is a candidate to bind the model claim.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelClaimPlacementCandidate {
+    pub storage_name: String,
+    /// The score given by the binding policy; the higher is the better.
+    /// If it is not set, the storage cannot be bound.
+    #[serde(default)]
+    pub score: Option<f64>,
+    /// The available capacity of the storage, in bytes.
+    #[serde(default)]
+    pub available: Option<u128>,
+    /// The total capacity of the storage, in bytes.
+    #[serde(default)]
+    pub capacity: Option<u128>,
+    /// The average call latency of the models in the storage, in milliseconds.
+    #[serde(default)]
+    pub latency_ms: Option<f64>,
+    /// Whether the storage is the default storage of the tenant.
+    #[serde(default)]
+    pub is_default: bool,
+    /// The reason why the storage cannot be bound.
+    #[serde(default)]
+    pub reason: Option<String>,
 }
 
 #[derive(
@@ -202,14 +295,48 @@ pub struct ModelClaimStatus {
     #[serde(default)]
     pub state: ModelClaimState,
     #[serde(default)]
+    pub conditions: Vec<Condition>,
+    #[serde(default)]
+    pub resize_history: Vec<ModelClaimResizeRecord>,
+    #[serde(default)]
     pub resources: Option<ResourceRequirements>,
     #[serde(default)]
     pub storage: Option<ModelStorageKind>,
     #[serde(default)]
     pub storage_name: Option<String>,
+    /// The distribution plan, if the model claim is bound to multiple storages.
+    #[serde(default)]
+    pub distribution: Vec<ModelClaimDistribution>,
     pub last_updated: DateTime<Utc>,
 }
 
+/// A part of the model claim, which is bound to a model storage.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelClaimDistribution {
+    pub binding_name: String,
+    pub storage_name: String,
+    pub weight: u8,
+    /// The storage quota of the part, in bytes.
+    /// If it is not set, the part is unlimited.
+    #[serde(default)]
+    pub quota: Option<u128>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelClaimResizeRecord {
+    pub binding_name: String,
+    pub storage_name: String,
+    /// The storage quota before resizing, in bytes.
+    pub from: u128,
+    /// The storage quota after resizing, in bytes.
+    pub to: u128,
+    /// The measured usage which triggered resizing, in bytes.
+    pub usage: u128,
+    pub timestamp: DateTime<Utc>,
+}
+
 #[derive(
     Copy,
     Clone,
@@ -5,7 +5,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use strum::{Display, EnumString};
 
-use crate::{model::ModelSpec, storage::ModelStorageSpec};
+use crate::{condition::Condition, model::ModelSpec, storage::ModelStorageSpec};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, CustomResource)]
 #[kube(
@@ -53,6 +53,22 @@ pub struct ModelStorageBindingSpec {
 
 impl ModelStorageBindingCrd {
     pub const FINALIZER_NAME: &'static str = "dash.ulagbulag.io/finalizer-model-storage-bindings";
+
+    /// The condition whether the referenced model or model storages have been changed
+    /// since the binding was made.
+    pub const CONDITION_DRIFTED: &'static str = "Drifted";
+
+    /// The condition whether the lifecycle rules of the object storage have been applied to the bucket.
+    pub const CONDITION_LIFECYCLE_APPLIED: &'static str = "LifecycleApplied";
+
+    /// The condition whether the data has been migrated to the re-pointed model storage.
+    pub const CONDITION_MIGRATED: &'static str = "Migrated";
+
+    /// The condition whether the source database is streamed into the target database.
+    pub const CONDITION_REPLICATING: &'static str = "Replicating";
+
+    /// The condition whether the objects have been synced between the source and the target.
+    pub const CONDITION_SYNCED: &'static str = "Synced";
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -163,6 +179,11 @@ pub struct ModelStorageBindingSyncPolicy {
     pub pull: ModelStorageBindingSyncPolicyPull,
     #[serde(default)]
     pub push: ModelStorageBindingSyncPolicyPush,
+    #[serde(default)]
+    pub replication: ModelStorageBindingSyncPolicyReplication,
+    /// How to resolve the conflicts of the bidirectional sync.
+    #[serde(default)]
+    pub conflict: ModelStorageBindingSyncPolicyConflict,
 }
 
 impl ModelStorageBindingSyncPolicy {
@@ -170,6 +191,12 @@ impl ModelStorageBindingSyncPolicy {
         self.pull == ModelStorageBindingSyncPolicyPull::Never
             && self.push == ModelStorageBindingSyncPolicyPush::Never
     }
+
+    /// Return `true` if the changes of both the source and the target are synced continuously.
+    pub fn is_bidirectional(&self) -> bool {
+        self.pull == ModelStorageBindingSyncPolicyPull::Always
+            && self.push == ModelStorageBindingSyncPolicyPush::Always
+    }
 }
 
 #[derive(
@@ -218,6 +245,58 @@ pub enum ModelStorageBindingSyncPolicyPush {
     Never,
 }
 
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    Default,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum ModelStorageBindingSyncPolicyReplication {
+    /// Sync the data in the storage-native way (e.g. bucket replication).
+    #[default]
+    Native,
+    /// Stream the changes of the source database with the PostgreSQL logical replication.
+    ///
+    /// It is supported only for the `Database` storages, and the changes are pulled
+    /// from the source only; the target should be treated as read-only.
+    Logical,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    Default,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum ModelStorageBindingSyncPolicyConflict {
+    /// Keep the latest version of the object, whichever side has written it.
+    #[default]
+    LastWriterWins,
+    /// Prefer the objects of the source; neither the existing objects
+    /// nor the deletions of the target are replicated to the source.
+    SourcePriority,
+}
+
 #[derive(
     Copy,
     Clone,
@@ -246,18 +325,28 @@ pub struct ModelStorageBindingStatus {
     #[serde(default)]
     pub state: ModelStorageBindingState,
     #[serde(default)]
+    pub conditions: Vec<Condition>,
+    #[serde(default)]
     pub deletion_policy: ModelStorageBindingDeletionPolicy,
     #[serde(default)]
     pub model: Option<ModelSpec>,
     #[serde(default)]
+    pub model_generation: Option<i64>,
+    #[serde(default)]
     pub model_name: Option<String>,
     #[serde(default)]
+    pub migration: Option<ModelStorageBindingMigrationStatus>,
+    #[serde(default)]
+    pub replication: Option<ModelStorageBindingReplicationStatus>,
+    #[serde(default)]
     pub resources: Option<ResourceRequirements>,
     #[serde(default)]
     pub storage_source: Option<ModelStorageSpec>,
     #[serde(default)]
     pub storage_source_binding_name: Option<String>,
     #[serde(default)]
+    pub storage_source_generation: Option<i64>,
+    #[serde(default)]
     pub storage_source_name: Option<String>,
     #[serde(default)]
     pub storage_source_uid: Option<String>,
@@ -266,9 +355,13 @@ pub struct ModelStorageBindingStatus {
     #[serde(default)]
     pub storage_target: Option<ModelStorageSpec>,
     #[serde(default)]
+    pub storage_target_generation: Option<i64>,
+    #[serde(default)]
     pub storage_target_name: Option<String>,
     #[serde(default)]
     pub storage_target_uid: Option<String>,
+    #[serde(default)]
+    pub sync: Option<ModelStorageBindingSyncStatus>,
     pub last_updated: DateTime<Utc>,
 }
 
@@ -292,5 +385,79 @@ pub enum ModelStorageBindingState {
     #[default]
     Pending,
     Ready,
+    Migrating,
     Deleting,
 }
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelStorageBindingMigrationStatus {
+    pub job_name: String,
+    #[serde(default)]
+    pub phase: ModelStorageBindingMigrationPhase,
+    /// The number of the failed copy attempts.
+    #[serde(default)]
+    pub failed: i32,
+    pub target: ModelStorageSpec,
+    pub target_name: String,
+    pub started_at: DateTime<Utc>,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    Default,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum ModelStorageBindingMigrationPhase {
+    #[default]
+    Copying,
+    Failed,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelStorageBindingReplicationStatus {
+    /// The name of the publication, the subscription and the replication slot.
+    pub name: String,
+    /// Whether the replication slot is being streamed to the subscriber.
+    #[serde(default)]
+    pub active: bool,
+    /// The size of the WAL which has not been confirmed by the subscriber yet, in bytes.
+    #[serde(default)]
+    pub lag_bytes: Option<i64>,
+    pub last_checked: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelStorageBindingSyncStatus {
+    /// The number of the objects which are waiting to be synced.
+    #[serde(default)]
+    pub pending_objects: u64,
+    /// The size of the objects which are waiting to be synced, in bytes.
+    #[serde(default)]
+    pub pending_bytes: u64,
+    /// The number of the objects which have failed to be synced.
+    #[serde(default)]
+    pub failed_objects: u64,
+    /// The number of the consecutive failures to check the sync.
+    #[serde(default)]
+    pub error_count: u32,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    /// The last time when no objects were pending.
+    #[serde(default)]
+    pub last_synced: Option<DateTime<Utc>>,
+    pub last_checked: DateTime<Utc>,
+}
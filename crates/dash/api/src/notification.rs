@@ -0,0 +1,213 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::condition::Condition;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, CustomResource)]
+#[kube(
+    group = "dash.ulagbulag.io",
+    version = "v1alpha1",
+    kind = "NotificationPolicy",
+    root = "NotificationPolicyCrd",
+    shortname = "dnp",
+    namespaced,
+    printcolumn = r#"{
+        "name": "created-at",
+        "type": "date",
+        "description": "created time",
+        "jsonPath": ".metadata.creationTimestamp"
+    }"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationPolicySpec {
+    /// The transitions to be notified; all the transitions if empty.
+    #[serde(default)]
+    pub events: Vec<NotificationEventSelector>,
+    pub channels: Vec<NotificationChannelSpec>,
+    /// The Tera template of the message, which is given the `event` variable.
+    #[serde(default)]
+    pub template: Option<String>,
+    #[serde(default)]
+    pub rate_limit: NotificationRateLimitSpec,
+}
+
+impl NotificationPolicySpec {
+    pub const DEFAULT_TEMPLATE: &'static str =
+        "[{{ event.kind }}] {{ event.namespace }}/{{ event.name }}: \
+        {% if event.condition %}{{ event.condition.type }}={{ event.condition.status }} \
+        ({{ event.condition.reason }}): {{ event.condition.message }}\
+        {% else %}{{ event.lastState }} -> {{ event.state }}{% endif %}";
+
+    pub fn matches(&self, event: &NotificationEvent) -> bool {
+        self.events.is_empty() || self.events.iter().any(|selector| selector.matches(event))
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationEventSelector {
+    /// The kind of the resources, e.g. `DashJob`.
+    #[serde(default)]
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    /// The new states to be notified; any state if empty.
+    #[serde(default)]
+    pub states: Vec<String>,
+    /// Notify the changes of the condition instead of the state.
+    #[serde(default)]
+    pub condition: Option<NotificationConditionSelector>,
+}
+
+impl NotificationEventSelector {
+    pub fn matches(&self, event: &NotificationEvent) -> bool {
+        if self.kind.as_ref().is_some_and(|kind| *kind != event.kind)
+            || self.name.as_ref().is_some_and(|name| *name != event.name)
+        {
+            return false;
+        }
+
+        match (&self.condition, &event.condition, &event.state) {
+            (Some(selector), Some(condition), _) => selector.matches(condition),
+            (None, None, Some(state)) => self.states.is_empty() || self.states.contains(state),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationConditionSelector {
+    /// The type of the condition, e.g. `QuotaPressure`.
+    #[serde(rename = "type")]
+    pub type_: String,
+    /// The new status of the condition; any status if not given.
+    #[serde(default)]
+    pub status: Option<bool>,
+    /// The reasons to be notified; any reason if empty.
+    #[serde(default)]
+    pub reasons: Vec<String>,
+}
+
+impl NotificationConditionSelector {
+    pub fn matches(&self, condition: &Condition) -> bool {
+        condition.type_ == self.type_
+            && !self
+                .status
+                .is_some_and(|status| (condition.status == "True") != status)
+            && (self.reasons.is_empty() || self.reasons.contains(&condition.reason))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationChannelSpec {
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: NotificationChannelKindSpec,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum NotificationChannelKindSpec {
+    /// Email over an SMTP relay.
+    Email(NotificationEmailSpec),
+    /// Slack incoming webhooks.
+    Slack(NotificationSlackSpec),
+    /// Generic webhooks, which receive the events as JSON.
+    Webhook(NotificationWebhookSpec),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationEmailSpec {
+    pub host: String,
+    #[serde(default = "NotificationEmailSpec::default_port")]
+    pub port: u16,
+    pub from: String,
+    pub to: Vec<String>,
+    /// The credentials of the SMTP relay with the `username` and `password` keys, if required.
+    #[serde(default)]
+    pub secret_name: Option<String>,
+}
+
+impl NotificationEmailSpec {
+    const fn default_port() -> u16 {
+        25
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationSlackSpec {
+    /// The secret storing the incoming webhook URL.
+    pub secret_name: String,
+    #[serde(default = "NotificationSlackSpec::default_secret_key")]
+    pub secret_key: String,
+}
+
+impl NotificationSlackSpec {
+    fn default_secret_key() -> String {
+        "url".into()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationWebhookSpec {
+    pub url: String,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationRateLimitSpec {
+    /// The maximum number of the messages per channel in the window.
+    #[serde(default = "NotificationRateLimitSpec::default_max_messages")]
+    pub max_messages: u32,
+    #[serde(default = "NotificationRateLimitSpec::default_window_seconds")]
+    pub window_seconds: u32,
+}
+
+impl Default for NotificationRateLimitSpec {
+    fn default() -> Self {
+        Self {
+            max_messages: Self::default_max_messages(),
+            window_seconds: Self::default_window_seconds(),
+        }
+    }
+}
+
+impl NotificationRateLimitSpec {
+    const fn default_max_messages() -> u32 {
+        10
+    }
+
+    const fn default_window_seconds() -> u32 {
+        60
+    }
+}
+
+/// A transition of a dash resource.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationEvent {
+    pub timestamp: DateTime<Utc>,
+    pub kind: String,
+    #[serde(default)]
+    pub namespace: Option<String>,
+    pub name: String,
+    /// The new state, if the state has been changed.
+    #[serde(default)]
+    pub state: Option<String>,
+    #[serde(default)]
+    pub last_state: Option<String>,
+    /// The changed condition, if the condition has been changed.
+    #[serde(default)]
+    pub condition: Option<Condition>,
+}
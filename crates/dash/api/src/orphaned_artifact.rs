@@ -0,0 +1,116 @@
+use chrono::{DateTime, Utc};
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, CustomResource)]
+#[kube(
+    group = "dash.ulagbulag.io",
+    version = "v1alpha1",
+    kind = "OrphanedArtifact",
+    root = "OrphanedArtifactCrd",
+    status = "OrphanedArtifactStatus",
+    shortname = "oa",
+    namespaced,
+    printcolumn = r#"{
+        "name": "state",
+        "type": "string",
+        "description": "state of the orphaned artifact",
+        "jsonPath": ".status.state"
+    }"#,
+    printcolumn = r#"{
+        "name": "kind",
+        "type": "string",
+        "description": "kind of the orphaned artifact",
+        "jsonPath": ".spec.kind"
+    }"#,
+    printcolumn = r#"{
+        "name": "storage",
+        "type": "string",
+        "description": "model storage name",
+        "jsonPath": ".spec.storageName"
+    }"#,
+    printcolumn = r#"{
+        "name": "delete-at",
+        "type": "date",
+        "description": "scheduled deletion time",
+        "jsonPath": ".status.deleteAt"
+    }"#,
+    printcolumn = r#"{
+        "name": "created-at",
+        "type": "date",
+        "description": "created time",
+        "jsonPath": ".metadata.creationTimestamp"
+    }"#,
+    printcolumn = r#"{
+        "name": "updated-at",
+        "type": "date",
+        "description": "updated time",
+        "jsonPath": ".status.lastUpdated"
+    }"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanedArtifactSpec {
+    pub kind: OrphanedArtifactKind,
+    pub name: String,
+    pub storage_name: String,
+}
+
+impl OrphanedArtifactCrd {
+    pub const LABEL_STORAGE_NAME: &'static str = "dash.ulagbulag.io/storage-name";
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum OrphanedArtifactKind {
+    Bucket,
+    Table,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanedArtifactStatus {
+    #[serde(default)]
+    pub state: OrphanedArtifactState,
+    /// The time when the artifact will be deleted.
+    /// If it is not set, the artifact is only reported.
+    #[serde(default)]
+    pub delete_at: Option<DateTime<Utc>>,
+    pub last_updated: DateTime<Utc>,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    Default,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum OrphanedArtifactState {
+    #[default]
+    Detected,
+    Deleting,
+}
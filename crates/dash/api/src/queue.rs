@@ -0,0 +1,71 @@
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, CustomResource)]
+#[kube(
+    group = "dash.ulagbulag.io",
+    version = "v1alpha1",
+    kind = "DashQueue",
+    root = "DashQueueCrd",
+    shortname = "dq",
+    printcolumn = r#"{
+        "name": "max-concurrency",
+        "type": "integer",
+        "description": "maximum number of the running jobs",
+        "jsonPath": ".spec.maxConcurrency"
+    }"#,
+    printcolumn = r#"{
+        "name": "preemption",
+        "type": "string",
+        "description": "preemption policy of the pending jobs",
+        "jsonPath": ".spec.preemption"
+    }"#,
+    printcolumn = r#"{
+        "name": "created-at",
+        "type": "date",
+        "description": "created time",
+        "jsonPath": ".metadata.creationTimestamp"
+    }"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct DashQueueSpec {
+    /// The maximum number of the running jobs in the queue, across the cluster.
+    /// Unlimited if not given.
+    #[serde(default)]
+    pub max_concurrency: Option<u32>,
+    /// The maximum number of the running jobs of each namespace in the queue.
+    #[serde(default)]
+    pub max_concurrency_per_namespace: Option<u32>,
+    /// The priority of the jobs whose tasks do not specify one.
+    #[serde(default)]
+    pub default_priority: i32,
+    #[serde(default)]
+    pub preemption: DashQueuePreemptionPolicy,
+}
+
+/// How the pending jobs of the different priorities are ordered.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    Default,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum DashQueuePreemptionPolicy {
+    /// The higher-priority jobs are admitted ahead of the lower-priority pending jobs.
+    #[default]
+    LowerPriority,
+    /// The jobs are admitted in order of arrival, regardless of their priorities.
+    Never,
+}
@@ -0,0 +1,82 @@
+use ark_core_k8s::data::Url;
+use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelStorageFileSystemSpec {
+    #[serde(flatten)]
+    pub source: ModelStorageFileSystemSourceSpec,
+
+    /// The total capacity of the shared filesystem, if known.
+    #[serde(default)]
+    pub capacity: Option<Quantity>,
+
+    #[serde(default)]
+    pub mount_options: Vec<String>,
+
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+impl ModelStorageFileSystemSpec {
+    #[inline]
+    pub(super) fn endpoint(&self) -> Option<Url> {
+        None
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ModelStorageFileSystemSourceSpec {
+    CephFs(ModelStorageFileSystemCephFsSpec),
+    HostPath(ModelStorageFileSystemHostPathSpec),
+    Nfs(ModelStorageFileSystemNfsSpec),
+}
+
+impl ModelStorageFileSystemSourceSpec {
+    /// Return the root directory of the shared filesystem.
+    pub fn path(&self) -> &str {
+        match self {
+            Self::CephFs(spec) => &spec.path,
+            Self::HostPath(spec) => &spec.path,
+            Self::Nfs(spec) => &spec.path,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelStorageFileSystemCephFsSpec {
+    pub monitors: Vec<String>,
+
+    #[serde(default = "ModelStorageFileSystemCephFsSpec::default_path")]
+    pub path: String,
+
+    /// The name of the secret containing the ceph user key.
+    #[serde(default)]
+    pub secret_name: Option<String>,
+
+    #[serde(default)]
+    pub user: Option<String>,
+}
+
+impl ModelStorageFileSystemCephFsSpec {
+    fn default_path() -> String {
+        "/".into()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelStorageFileSystemHostPathSpec {
+    pub path: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelStorageFileSystemNfsSpec {
+    pub server: String,
+    pub path: String,
+}
@@ -1,4 +1,5 @@
 pub mod db;
+pub mod fs;
 pub mod kubernetes;
 pub mod object;
 
@@ -15,6 +16,8 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use strum::{Display, EnumString};
 
+use crate::condition::Condition;
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, CustomResource)]
 #[kube(
     group = "dash.ulagbulag.io",
@@ -55,12 +58,24 @@ impl ModelStorageCrd {
     pub const FINALIZER_NAME: &'static str = "dash.ulagbulag.io/finalizer-model-storages";
 
     pub const LABEL_IS_EXTERNAL: &'static str = "ark.ulagbulag.io/is-external";
+
+    /// The grace period before deleting the orphaned artifacts, e.g. `7d`.
+    /// If it is not set, the orphaned artifacts are only reported.
+    pub const ANNOTATION_GC_GRACE_PERIOD: &'static str = "dash.ulagbulag.io/gc-grace-period";
+
+    /// Whether to benchmark the model storage when it becomes ready, e.g. `true`.
+    /// The benchmark is repeated whenever the model storage is updated.
+    pub const ANNOTATION_BENCHMARK: &'static str = "dash.ulagbulag.io/benchmark";
+
+    /// The condition whether the bound models are approaching the total quota.
+    pub const CONDITION_QUOTA_PRESSURE: &'static str = "QuotaPressure";
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub enum ModelStorageKindSpec {
     Database(#[serde(default)] self::db::ModelStorageDatabaseSpec),
+    FileSystem(self::fs::ModelStorageFileSystemSpec),
     Kubernetes(#[serde(default)] self::kubernetes::ModelStorageKubernetesSpec),
     ObjectStorage(#[serde(default)] self::object::ModelStorageObjectSpec),
 }
@@ -70,6 +85,7 @@ impl ModelStorageKindSpec {
     pub fn endpoint(&self, namespace: &str) -> Option<Url> {
         match self {
             Self::Database(spec) => spec.endpoint(),
+            Self::FileSystem(spec) => spec.endpoint(),
             Self::Kubernetes(spec) => spec.endpoint(),
             Self::ObjectStorage(spec) => spec.endpoint(namespace),
         }
@@ -78,6 +94,7 @@ impl ModelStorageKindSpec {
     pub const fn is_unique(&self) -> bool {
         match self {
             Self::Database(_) => false,
+            Self::FileSystem(_) => false,
             Self::Kubernetes(_) => true,
             Self::ObjectStorage(spec) => spec.is_unique(),
         }
@@ -86,6 +103,7 @@ impl ModelStorageKindSpec {
     pub const fn to_kind(&self) -> ModelStorageKind {
         match self {
             Self::Database(_) => ModelStorageKind::Database,
+            Self::FileSystem(_) => ModelStorageKind::FileSystem,
             Self::Kubernetes(_) => ModelStorageKind::Kubernetes,
             Self::ObjectStorage(_) => ModelStorageKind::ObjectStorage,
         }
@@ -109,6 +127,7 @@ impl ModelStorageKindSpec {
 )]
 pub enum ModelStorageKind {
     Database,
+    FileSystem,
     Kubernetes,
     ObjectStorage,
 }
@@ -118,10 +137,139 @@ pub enum ModelStorageKind {
 pub struct ModelStorageStatus {
     #[serde(default)]
     pub state: ModelStorageState,
+    #[serde(default)]
+    pub benchmark: Option<ModelStorageBenchmarkStatus>,
+    #[serde(default)]
+    pub conditions: Vec<Condition>,
     pub kind: Option<ModelStorageKindSpec>,
     pub last_updated: DateTime<Utc>,
     #[serde(default)]
     pub total_quota: Option<u128>,
+    #[serde(default)]
+    pub usage: Option<ModelStorageUsage>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelStorageUsage {
+    /// The sum of the quotas requested by the bound models.
+    #[serde(default)]
+    pub allocated: u128,
+    /// The sum of the measured sizes of the bound models.
+    #[serde(default)]
+    pub used: u128,
+    #[serde(default)]
+    pub models: BTreeMap<String, ModelStorageModelUsage>,
+}
+
+impl ModelStorageUsage {
+    /// Return the ratio of the allocated or used size, whichever is greater,
+    /// to the given total quota.
+    pub fn ratio(&self, total_quota: u128) -> Option<f64> {
+        if total_quota > 0 {
+            Some(self.allocated.max(self.used) as f64 / total_quota as f64)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelStorageModelUsage {
+    #[serde(default)]
+    pub quota: Option<u128>,
+    #[serde(default)]
+    pub bytes: Option<u128>,
+    #[serde(default)]
+    pub objects: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelStorageBenchmarkStatus {
+    pub state: ModelStorageBenchmarkState,
+    /// The generation of the model storage which is benchmarked
+    #[serde(default)]
+    pub generation: Option<i64>,
+    pub job_name: String,
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub results: Vec<ModelStorageBenchmarkResult>,
+    pub started_timestamp: DateTime<Utc>,
+    #[serde(default)]
+    pub completed_timestamp: Option<DateTime<Utc>>,
+}
+
+impl ModelStorageBenchmarkStatus {
+    /// Return the median latency of the random reads of the smallest objects, in milliseconds.
+    pub fn latency_ms(&self) -> Option<f64> {
+        self.results
+            .iter()
+            .filter(|result| result.operation == ModelStorageBenchmarkOperation::RandomRead)
+            .min_by_key(|result| result.object_size)
+            .map(|result| result.latency_p50_ms)
+    }
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    Default,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum ModelStorageBenchmarkState {
+    #[default]
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelStorageBenchmarkResult {
+    pub operation: ModelStorageBenchmarkOperation,
+    /// The size of each object (or row) in bytes
+    pub object_size: u64,
+    /// The number of the measured operations
+    pub count: u64,
+    pub throughput_bytes_per_second: f64,
+    pub latency_p50_ms: f64,
+    pub latency_p90_ms: f64,
+    pub latency_p99_ms: f64,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum ModelStorageBenchmarkOperation {
+    SequentialRead,
+    SequentialWrite,
+    RandomRead,
+    RandomWrite,
 }
 
 #[derive(
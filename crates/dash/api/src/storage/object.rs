@@ -41,6 +41,15 @@ impl ModelStorageObjectSpec {
             Self::Owned(_) => true,
         }
     }
+
+    /// The lifecycle rules applied to the buckets created on this storage.
+    pub fn lifecycle(&self) -> &[ModelStorageObjectLifecycleRuleSpec] {
+        match self {
+            Self::Borrowed(spec) => &spec.lifecycle,
+            Self::Cloned(spec) => &spec.owned.lifecycle,
+            Self::Owned(spec) => &spec.lifecycle,
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -48,6 +57,9 @@ impl ModelStorageObjectSpec {
 pub struct ModelStorageObjectBorrowedSpec {
     #[serde(default, flatten)]
     pub reference: ModelStorageObjectRefSpec,
+
+    #[serde(default)]
+    pub lifecycle: Vec<ModelStorageObjectLifecycleRuleSpec>,
 }
 
 impl ModelStorageObjectBorrowedSpec {
@@ -77,6 +89,9 @@ impl ModelStorageObjectClonedSpec {
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ModelStorageObjectOwnedSpec {
+    #[serde(default)]
+    pub lifecycle: Vec<ModelStorageObjectLifecycleRuleSpec>,
+
     #[serde(default)]
     pub minio_console_external_service: ModelStorageObjectOwnedExternalServiceSpec,
 
@@ -96,6 +111,7 @@ pub struct ModelStorageObjectOwnedSpec {
 impl Default for ModelStorageObjectOwnedSpec {
     fn default() -> Self {
         Self {
+            lifecycle: Default::default(),
             minio_console_external_service: Default::default(),
             minio_external_service: Default::default(),
             replication: Default::default(),
@@ -178,6 +194,47 @@ impl ModelStorageObjectOwnedReplicationSpec {
     }
 }
 
+/// A lifecycle rule of the buckets, which is applied by the object storage itself.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelStorageObjectLifecycleRuleSpec {
+    /// The unique name of the rule.
+    pub name: String,
+
+    /// Apply the rule only to the objects with the prefix.
+    #[serde(default)]
+    pub prefix: Option<String>,
+
+    /// Move the objects to the cold storage after the given days.
+    #[serde(default)]
+    pub transition: Option<ModelStorageObjectLifecycleTransitionSpec>,
+
+    /// Delete the objects after the given days.
+    #[serde(default)]
+    pub expiration_days: Option<u32>,
+
+    /// Delete the noncurrent versions after they became noncurrent for the given days.
+    #[serde(default)]
+    pub noncurrent_version_expiration_days: Option<u32>,
+}
+
+impl ModelStorageObjectLifecycleRuleSpec {
+    pub const fn is_empty(&self) -> bool {
+        self.transition.is_none()
+            && self.expiration_days.is_none()
+            && self.noncurrent_version_expiration_days.is_none()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelStorageObjectLifecycleTransitionSpec {
+    pub days: u32,
+
+    /// The name of the remote tier (e.g. `COLD`), which should be registered to the object storage.
+    pub storage_class: String,
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ModelStorageObjectOwnedExternalServiceSpec {
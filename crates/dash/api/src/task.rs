@@ -1,11 +1,18 @@
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
 use dash_provider_api::job::TaskActorJobMetadata;
+use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
 use kube::CustomResource;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use strum::{Display, EnumString};
 
-use crate::model::{ModelFieldKindNativeSpec, ModelFieldKindSpec, ModelFieldsSpec};
+use crate::{
+    job::DashJobCrd,
+    model::{ModelFieldKindNativeSpec, ModelFieldKindSpec, ModelFieldsSpec},
+};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, CustomResource)]
 #[kube(
@@ -39,6 +46,15 @@ use crate::model::{ModelFieldKindNativeSpec, ModelFieldKindSpec, ModelFieldsSpec
 pub struct TaskSpec<Kind = ModelFieldKindSpec> {
     pub input: ModelFieldsSpec<Kind>,
     pub actor: TaskActorSpec,
+    /// The upstream tasks which should be finished before running this task.
+    #[serde(default)]
+    pub dependencies: Vec<TaskDependencySpec>,
+    /// Admit the jobs of the task through the queue.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue: Option<TaskQueueSpec>,
+    /// Run the task periodically.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<TaskScheduleSpec>,
 }
 
 impl TaskCrd {
@@ -56,13 +72,118 @@ pub struct TaskStatus {
     #[serde(default)]
     pub state: TaskState,
     pub spec: Option<TaskSpec<ModelFieldKindNativeSpec>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<TaskScheduleStatus>,
     pub last_updated: DateTime<Utc>,
 }
 
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskScheduleStatus {
+    #[serde(default)]
+    pub last_job_name: Option<String>,
+    #[serde(default)]
+    pub last_schedule_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub next_schedule_time: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskQueueSpec {
+    /// The name of the `DashQueue`.
+    pub name: String,
+    /// The jobs with higher priorities are admitted first;
+    /// the default priority of the queue if not given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<i32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskDependencySpec {
+    pub name: String,
+    #[serde(default)]
+    pub on_failure: TaskDependencyFailurePolicy,
+}
+
+/// What to do with the downstream task when the upstream task has failed.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    Default,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum TaskDependencyFailurePolicy {
+    /// Run the downstream task anyway.
+    Continue,
+    /// Skip the downstream task and its descendants.
+    #[default]
+    Skip,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskScheduleSpec {
+    /// The cron expression, e.g. `*/5 * * * *`.
+    /// The optional leading field is parsed as seconds.
+    pub cron: String,
+    /// The IANA time zone of the cron expression, e.g. `Asia/Seoul`; UTC by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<String>,
+    #[serde(default)]
+    pub concurrency_policy: TaskConcurrencyPolicy,
+    /// Stop spawning the scheduled jobs, without affecting the running ones.
+    #[serde(default)]
+    pub suspend: bool,
+    /// The input value of the scheduled jobs.
+    #[serde(default)]
+    #[schemars(schema_with = "DashJobCrd::preserve_arbitrary")]
+    pub value: BTreeMap<String, Value>,
+}
+
+/// What to do with the scheduled job when the previous one is still running.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    Default,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum TaskConcurrencyPolicy {
+    /// Run the jobs concurrently.
+    Allow,
+    /// Skip the scheduled job.
+    #[default]
+    Forbid,
+    /// Cancel the running jobs and run the scheduled job.
+    Replace,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub enum TaskActorSpec {
     Job(TaskActorJobSpec),
+    Wasm(TaskActorWasmSpec),
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -73,6 +194,29 @@ pub struct TaskActorJobSpec {
     pub source: TaskActorSourceSpec,
 }
 
+/// A lightweight function, which is executed inside the dash provider process.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskActorWasmSpec {
+    /// The WebAssembly module, which is stored in the `binaryData` of the ConfigMap.
+    pub source: TaskActorSourceSpec,
+    #[serde(default)]
+    pub limits: TaskActorWasmLimitsSpec,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskActorWasmLimitsSpec {
+    /// The maximum amount of the fuel, which is consumed by the executed instructions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fuel: Option<u64>,
+    /// The maximum size of the linear memory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<Quantity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<u64>,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub enum TaskActorSourceSpec {
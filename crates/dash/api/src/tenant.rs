@@ -0,0 +1,116 @@
+use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{approval::ApprovalPolicySpec, job::DashJobCrd};
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, CustomResource)]
+#[kube(
+    group = "dash.ulagbulag.io",
+    version = "v1alpha1",
+    kind = "TenantConfig",
+    root = "TenantConfigCrd",
+    shortname = "tc",
+    namespaced,
+    printcolumn = r#"{
+        "name": "default-storage",
+        "type": "string",
+        "description": "default model storage name",
+        "jsonPath": ".spec.defaultStorageName"
+    }"#,
+    printcolumn = r#"{
+        "name": "max-claims",
+        "type": "integer",
+        "description": "maximum number of the model claims",
+        "jsonPath": ".spec.quota.maxClaims"
+    }"#,
+    printcolumn = r#"{
+        "name": "max-storage",
+        "type": "string",
+        "description": "maximum storage size of the model claims",
+        "jsonPath": ".spec.quota.maxStorage"
+    }"#,
+    printcolumn = r#"{
+        "name": "created-at",
+        "type": "date",
+        "description": "created time",
+        "jsonPath": ".metadata.creationTimestamp"
+    }"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct TenantConfigSpec {
+    /// The operations which should be approved before the controllers proceed.
+    #[serde(default)]
+    pub approval: ApprovalPolicySpec,
+    /// The model storage to be preferred when binding the model claims.
+    #[serde(default)]
+    pub default_storage_name: Option<String>,
+    /// The telemetry pipelines of the OpenTelemetry Collector,
+    /// which is injected with the `dash.ulagbulag.io/inject-otlp` label.
+    #[serde(default)]
+    pub observability: TenantObservabilitySpec,
+    #[serde(default)]
+    pub quota: TenantQuotaSpec,
+    /// The namespaces which are allowed to bind the model storages of this tenant.
+    #[serde(default)]
+    pub shared_with: Vec<String>,
+}
+
+impl TenantConfigCrd {
+    /// The name of the tenant config, which is unique in each namespace.
+    pub const NAME: &'static str = "default";
+}
+
+impl TenantConfigSpec {
+    pub fn is_shared_with(&self, namespace: &str) -> bool {
+        self.shared_with.iter().any(|name| name == namespace)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TenantQuotaSpec {
+    /// The maximum number of the model claims in the namespace.
+    #[serde(default)]
+    pub max_claims: Option<u32>,
+    /// The maximum sum of the storage quotas of the model claims in the namespace.
+    #[serde(default)]
+    pub max_storage: Option<Quantity>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TenantObservabilitySpec {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logs: Option<TenantTelemetryPipelineSpec>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<TenantTelemetryPipelineSpec>,
+}
+
+impl TenantObservabilitySpec {
+    pub const fn is_empty(&self) -> bool {
+        self.logs.is_none() && self.metrics.is_none()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TenantTelemetryPipelineSpec {
+    pub receivers: Vec<TenantTelemetryComponentSpec>,
+    /// The processors, which are applied in order.
+    #[serde(default)]
+    pub processors: Vec<TenantTelemetryComponentSpec>,
+    pub exporters: Vec<TenantTelemetryComponentSpec>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TenantTelemetryComponentSpec {
+    /// The component ID of the collector, e.g. `otlp` or `prometheus/self`.
+    pub name: String,
+    #[serde(default)]
+    #[schemars(schema_with = "DashJobCrd::preserve_arbitrary")]
+    pub config: Value,
+}
@@ -3,10 +3,21 @@ use std::{error::Error, fmt};
 use anyhow::{anyhow, Result};
 use ark_api::SessionRef;
 use ark_core::result::Result as SessionResult;
-use dash_api::{job::DashJobCrd, model::ModelCrd, task::TaskCrd};
-use dash_provider_api::job::Payload;
+use dash_api::{
+    approval::ApprovalCrd,
+    bundle::{DashBundle, DashBundleConflictStrategy, DashBundleImportReport},
+    job::DashJobCrd,
+    model::{ModelCrd, ModelVersion},
+    model_claim::{ModelClaimPlacementCandidate, ModelClaimSpec},
+    task::TaskCrd,
+};
+use dash_provider_api::{
+    codegen::CodegenOptions,
+    data::{ListOptions, ResourceList, ResourceListOptions},
+    job::Payload,
+};
 use derivative::Derivative;
-use reqwest::{Client, Method, Url};
+use reqwest::{Client, Method, RequestBuilder, Url};
 use schemars::JsonSchema;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value;
@@ -57,9 +68,17 @@ impl DashClient {
     }
 
     #[instrument(level = Level::INFO, err(Display))]
-    pub async fn get_task_list(&self) -> Result<Vec<ObjectRef>> {
+    pub async fn get_task_list(&self) -> Result<ResourceList<Value>> {
         self.get("/task/").await
     }
+
+    #[instrument(level = Level::INFO, err(Display))]
+    pub async fn get_task_list_with(
+        &self,
+        options: &ResourceListOptions,
+    ) -> Result<ResourceList<Value>> {
+        self.get_with_query("/task/", options).await
+    }
 }
 
 impl DashClient {
@@ -105,22 +124,110 @@ impl DashClient {
     }
 }
 
+impl DashClient {
+    #[instrument(level = Level::INFO, err(Display))]
+    pub async fn get_approval_list(&self) -> Result<Vec<ApprovalCrd>> {
+        self.get("/approval/").await
+    }
+
+    #[instrument(level = Level::INFO, err(Display))]
+    pub async fn approve(&self, name: &str, comment: Option<&str>) -> Result<ApprovalCrd> {
+        self.decide_approval(name, "approve", comment).await
+    }
+
+    #[instrument(level = Level::INFO, err(Display))]
+    pub async fn reject(&self, name: &str, comment: Option<&str>) -> Result<ApprovalCrd> {
+        self.decide_approval(name, "reject", comment).await
+    }
+
+    async fn decide_approval(
+        &self,
+        name: &str,
+        decision: &str,
+        comment: Option<&str>,
+    ) -> Result<ApprovalCrd> {
+        let query: Vec<_> = comment
+            .map(|comment| ("comment", comment))
+            .into_iter()
+            .collect();
+        let request = self
+            .client
+            .post(self.get_url(format!("/approval/{name}/{decision}")))
+            .query(&query);
+        self.send(request).await
+    }
+}
+
+impl DashClient {
+    #[instrument(level = Level::INFO, skip(input), err(Display))]
+    pub async fn invoke_function<Input, Output>(&self, name: &str, input: &Input) -> Result<Output>
+    where
+        Input: ?Sized + Serialize,
+        Output: DeserializeOwned,
+    {
+        self.post(format!("/function/{name}/invoke"), Some(input))
+            .await
+    }
+
+    /// Generate the typed client of the function, returning the source code.
+    #[instrument(level = Level::INFO, err(Display))]
+    pub async fn get_function_client(&self, name: &str, options: CodegenOptions) -> Result<String> {
+        self.get_with_query(format!("/function/{name}/client"), &options)
+            .await
+    }
+}
+
+impl DashClient {
+    #[instrument(level = Level::INFO, err(Display))]
+    pub async fn export_bundle(&self) -> Result<DashBundle> {
+        self.get("/bundle/").await
+    }
+
+    #[instrument(level = Level::INFO, skip(bundle), err(Display))]
+    pub async fn import_bundle(
+        &self,
+        bundle: &DashBundle,
+        strategy: DashBundleConflictStrategy,
+    ) -> Result<DashBundleImportReport> {
+        let query = [("strategy", strategy.to_string())];
+        let request = self
+            .client
+            .post(self.get_url("/bundle/"))
+            .query(&query)
+            .json(bundle);
+        self.send(request).await
+    }
+}
+
 impl DashClient {
     #[instrument(level = Level::INFO, err(Display))]
     pub async fn get_model(&self, name: &str) -> Result<ModelCrd> {
         self.get(format!("/model/{name}/")).await
     }
 
+    #[instrument(level = Level::INFO, err(Display))]
+    pub async fn get_model_history(&self, name: &str) -> Result<Vec<ModelVersion>> {
+        self.get(format!("/model/{name}/history/")).await
+    }
+
     #[instrument(level = Level::INFO, err(Display))]
     pub async fn get_model_task_list(&self, name: &str) -> Result<Vec<TaskCrd>> {
         self.get(format!("/model/{name}/task/")).await
     }
 
     #[instrument(level = Level::INFO, err(Display))]
-    pub async fn get_model_list(&self) -> Result<Vec<ObjectRef>> {
+    pub async fn get_model_list(&self) -> Result<ResourceList<Value>> {
         self.get("/model/").await
     }
 
+    #[instrument(level = Level::INFO, err(Display))]
+    pub async fn get_model_list_with(
+        &self,
+        options: &ResourceListOptions,
+    ) -> Result<ResourceList<Value>> {
+        self.get_with_query("/model/", options).await
+    }
+
     #[instrument(level = Level::INFO, err(Display))]
     pub async fn get_model_item(&self, name: &str, item: &str) -> Result<Value> {
         self.get(format!("/model/{name}/item/{item}/")).await
@@ -130,6 +237,26 @@ impl DashClient {
     pub async fn get_model_item_list(&self, name: &str) -> Result<Vec<Value>> {
         self.get(format!("/model/{name}/item/")).await
     }
+
+    #[instrument(level = Level::INFO, err(Display))]
+    pub async fn get_model_item_list_with(
+        &self,
+        name: &str,
+        options: &ListOptions,
+    ) -> Result<Vec<Value>> {
+        self.get_with_query(format!("/model/{name}/item/"), options)
+            .await
+    }
+
+    #[instrument(level = Level::INFO, err(Display))]
+    pub async fn simulate_model_claim(
+        &self,
+        name: &str,
+        spec: &ModelClaimSpec,
+    ) -> Result<Vec<ModelClaimPlacementCandidate>> {
+        self.post(format!("/model/{name}/claim/simulate/"), Some(spec))
+            .await
+    }
 }
 
 impl DashClient {
@@ -183,6 +310,16 @@ impl DashClient {
         self.request::<(), _>(Method::GET, path, None).await
     }
 
+    #[instrument(level = Level::INFO, skip_all, fields(path = %path.as_ref()), err(Display))]
+    async fn get_with_query<Query, Res>(&self, path: impl AsRef<str>, query: &Query) -> Result<Res>
+    where
+        Query: ?Sized + Serialize,
+        Res: DeserializeOwned,
+    {
+        let request = self.client.get(self.get_url(path)).query(query);
+        self.send(request).await
+    }
+
     #[instrument(level = Level::INFO, skip_all, fields(path = %path.as_ref()), err(Display))]
     async fn post<Req, Res>(&self, path: impl AsRef<str>, data: Option<&Req>) -> Result<Res>
     where
@@ -207,6 +344,13 @@ impl DashClient {
         if let Some(data) = data {
             request = request.json(data);
         }
+        self.send(request).await
+    }
+
+    async fn send<Res>(&self, mut request: RequestBuilder) -> Result<Res>
+    where
+        Res: DeserializeOwned,
+    {
         if let Some(namespace) = &self.namespace {
             request = request.header(::ark_api::consts::HEADER_NAMESPACE, namespace);
         }
@@ -32,6 +32,9 @@ macro_rules! init_exporter {
 
                 #[derive(Parser)]
                 pub struct ExporterStorageArgs {
+                    #[command(flatten)]
+                    compaction: ::dash_pipe_provider::storage::StorageCompactionArgs,
+
                     #[arg(long, env = "PIPE_FLUSH", value_name = "MS", default_value_t = 10_000)]
                     flush_ms: u64,
 
@@ -61,6 +64,7 @@ macro_rules! init_exporter {
                                 args.storage_name.clone(),
                                 Some(&args.model_out),
                                 args.flush(),
+                                &args.compaction,
                             ).await?
                         ),
                     )*
@@ -326,6 +330,7 @@ mod impl_trace {
             op,
             type_: match code_namespace {
                 "dash_pipe_provider::messengers::kafka" => MessengerType::Kafka,
+                "dash_pipe_provider::messengers::mqtt" => MessengerType::Mqtt,
                 "dash_pipe_provider::messengers::nats" => MessengerType::Nats,
                 _ => return None,
             },
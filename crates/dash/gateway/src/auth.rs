@@ -0,0 +1,171 @@
+use actix_web::{
+    dev::Payload,
+    error::{ErrorInternalServerError, ErrorUnauthorized},
+    http::header::AUTHORIZATION,
+    web::Data,
+    FromRequest, HttpRequest,
+};
+use anyhow::{anyhow, bail, Result};
+use ark_core::env::infer;
+use futures::future::LocalBoxFuture;
+use k8s_openapi::api::authentication::v1::{
+    TokenReview, TokenReviewSpec, TokenReviewStatus, UserInfo,
+};
+use kube::{api::PostParams, config::AuthInfo, core::ObjectMeta, Api, Client, Config};
+use tracing::{info, instrument, Level};
+
+/// Authenticates the end users of the gateway.
+#[derive(Clone)]
+pub enum DashAuthenticator {
+    /// Call the kubernetes API with the service account of the gateway.
+    Disabled,
+    /// Validate the OpenID Connect bearer tokens (`TokenReview`) against the issuer trusted by
+    /// the kubernetes API server, and impersonate the authenticated users, so that the RBAC
+    /// applies per end user.
+    Oidc {
+        audiences: Option<Vec<String>>,
+        config: Config,
+    },
+}
+
+impl DashAuthenticator {
+    #[instrument(level = Level::INFO, err(Display))]
+    pub async fn try_default() -> Result<Self> {
+        if !infer::<_, bool>("DASH_AUTH_OIDC").unwrap_or_default() {
+            return Ok(Self::Disabled);
+        }
+
+        info!("Enabling OIDC authentication with user impersonation");
+        Ok(Self::Oidc {
+            audiences: infer::<_, String>("DASH_AUTH_OIDC_AUDIENCES")
+                .ok()
+                .map(|audiences| {
+                    audiences
+                        .split(',')
+                        .map(|audience| audience.trim())
+                        .filter(|audience| !audience.is_empty())
+                        .map(Into::into)
+                        .collect()
+                }),
+            config: Config::infer()
+                .await
+                .map_err(|error| anyhow!("failed to infer kubernetes config: {error}"))?,
+        })
+    }
+
+    /// Return a kubernetes client acting on behalf of the user of the request.
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn impersonate(&self, kube: &Client, request: &HttpRequest) -> Result<Client> {
+        let (audiences, config) = match self {
+            Self::Disabled => return Ok(kube.clone()),
+            Self::Oidc { audiences, config } => (audiences, config),
+        };
+
+        let token = request
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| anyhow!("missing bearer token"))?;
+
+        let UserInfo {
+            groups, username, ..
+        } = review_token(kube, audiences.clone(), token).await?;
+        let username = match username {
+            Some(username) if !username.is_empty() => username,
+            _ => bail!("unauthenticated: the token has no username"),
+        };
+
+        let config = Config {
+            auth_info: AuthInfo {
+                impersonate: Some(username),
+                impersonate_groups: groups,
+                ..config.auth_info.clone()
+            },
+            ..config.clone()
+        };
+        Client::try_from(config)
+            .map_err(|error| anyhow!("failed to create impersonated kubernetes client: {error}"))
+    }
+}
+
+async fn review_token(
+    kube: &Client,
+    audiences: Option<Vec<String>>,
+    token: &str,
+) -> Result<UserInfo> {
+    let api = Api::<TokenReview>::all(kube.clone());
+    let pp = PostParams::default();
+    let review = TokenReview {
+        metadata: ObjectMeta::default(),
+        spec: TokenReviewSpec {
+            audiences,
+            token: Some(token.into()),
+        },
+        status: None,
+    };
+    let TokenReviewStatus {
+        authenticated,
+        error,
+        user,
+        ..
+    } = api
+        .create(&pp, &review)
+        .await
+        .map_err(|error| anyhow!("failed to review token: {error}"))?
+        .status
+        .unwrap_or_default();
+
+    match user {
+        Some(user) if authenticated == Some(true) => Ok(user),
+        _ => bail!(
+            "unauthenticated: {error}",
+            error = error.as_deref().unwrap_or("invalid token"),
+        ),
+    }
+}
+
+/// The kubernetes clients of a request.
+///
+/// The service account client is used to look up the user sessions, and the user client
+/// to access the resources on behalf of the user.
+pub struct UserClient {
+    service: Client,
+    user: Client,
+}
+
+impl UserClient {
+    pub fn service(&self) -> &Client {
+        &self.service
+    }
+}
+
+impl AsRef<Client> for UserClient {
+    fn as_ref(&self) -> &Client {
+        &self.user
+    }
+}
+
+impl FromRequest for UserClient {
+    type Error = ::actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(request: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let request = request.clone();
+        Box::pin(async move {
+            let service = request
+                .app_data::<Data<Client>>()
+                .map(|kube| kube.as_ref().clone())
+                .ok_or_else(|| ErrorInternalServerError("kubernetes client is not registered"))?;
+            let authenticator = request
+                .app_data::<Data<DashAuthenticator>>()
+                .ok_or_else(|| ErrorInternalServerError("authenticator is not registered"))?;
+
+            let user = authenticator
+                .impersonate(&service, &request)
+                .await
+                .map_err(ErrorUnauthorized)?;
+            Ok(Self { service, user })
+        })
+    }
+}
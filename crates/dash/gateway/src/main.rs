@@ -1,3 +1,4 @@
+mod auth;
 mod routes;
 
 use std::net::SocketAddr;
@@ -30,6 +31,7 @@ async fn main() {
         let addr =
             infer::<_, SocketAddr>("BIND_ADDR").unwrap_or_else(|_| "0.0.0.0:80".parse().unwrap());
         let client = Data::new(Client::try_default().await?);
+        let authenticator = Data::new(self::auth::DashAuthenticator::try_default().await?);
 
         // Start web server
         HttpServer::new(move || {
@@ -38,10 +40,21 @@ async fn main() {
                 .allow_any_method()
                 .allow_any_origin();
 
-            let app = App::new().app_data(Data::clone(&client));
+            let app = App::new()
+                .app_data(Data::clone(&authenticator))
+                .app_data(Data::clone(&client));
             let app = app
                 .service(index)
                 .service(health)
+                .service(crate::routes::approval::get_list)
+                .service(crate::routes::approval::post_approve)
+                .service(crate::routes::approval::post_reject)
+                .service(crate::routes::audit::get_list)
+                .service(crate::routes::bundle::get)
+                .service(crate::routes::bundle::post)
+                .service(crate::routes::function::get_list)
+                .service(crate::routes::function::get_client)
+                .service(crate::routes::function::post_invoke)
                 .service(crate::routes::task::get)
                 .service(crate::routes::task::get_list)
                 .service(crate::routes::job::batch::post)
@@ -53,10 +66,15 @@ async fn main() {
                 .service(crate::routes::job::single::post)
                 .service(crate::routes::job::single::post_restart)
                 .service(crate::routes::model::get)
+                .service(crate::routes::model::get_history)
+                .service(crate::routes::model::get_lineage)
                 .service(crate::routes::model::get_task_list)
                 .service(crate::routes::model::get_item)
                 .service(crate::routes::model::get_item_list)
-                .service(crate::routes::model::get_list);
+                .service(crate::routes::model::get_list)
+                .service(crate::routes::model::post_claim_simulate)
+                .service(crate::routes::storage::get_list)
+                .service(crate::routes::watch::get);
             let app = ::vine_plugin::register(app);
             app.wrap(cors)
                 .wrap(middleware::NormalizePath::new(
@@ -0,0 +1,112 @@
+use actix_web::{
+    get, post,
+    web::{Path, Query},
+    HttpRequest, HttpResponse, Responder,
+};
+use anyhow::bail;
+use ark_core::result::Result;
+use dash_api::approval::{ApprovalCrd, ApprovalDecisionSpec};
+use dash_provider::storage::KubernetesStorageClient;
+use serde::{Deserialize, Serialize};
+use tracing::{instrument, Level};
+use vine_api::user_session::UserSession;
+use vine_rbac::auth::AuthUserSession;
+
+use crate::auth::UserClient;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalDecisionQuery {
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[get("/approval")]
+pub async fn get_list(request: HttpRequest, kube: UserClient) -> impl Responder {
+    let namespace = match UserSession::from_request(kube.service(), &request).await {
+        Ok(session) => session.namespace,
+        Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
+    };
+    let kube = kube.as_ref();
+
+    let client = KubernetesStorageClient {
+        namespace: &namespace,
+        kube,
+    };
+    let result = client.load_approvals_all().await;
+    HttpResponse::from(Result::from(result))
+}
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[post("/approval/{name}/approve")]
+pub async fn post_approve(
+    request: HttpRequest,
+    kube: UserClient,
+    name: Path<String>,
+    Query(query): Query<ApprovalDecisionQuery>,
+) -> impl Responder {
+    decide(request, kube, name, true, query).await
+}
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[post("/approval/{name}/reject")]
+pub async fn post_reject(
+    request: HttpRequest,
+    kube: UserClient,
+    name: Path<String>,
+    Query(query): Query<ApprovalDecisionQuery>,
+) -> impl Responder {
+    decide(request, kube, name, false, query).await
+}
+
+async fn decide(
+    request: HttpRequest,
+    kube: UserClient,
+    name: Path<String>,
+    approved: bool,
+    ApprovalDecisionQuery { comment }: ApprovalDecisionQuery,
+) -> HttpResponse {
+    let session = match UserSession::from_request(kube.service(), &request).await {
+        Ok(session) => session,
+        Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
+    };
+    let kube = kube.as_ref();
+
+    let client = KubernetesStorageClient {
+        namespace: &session.namespace,
+        kube,
+    };
+    let decision = ApprovalDecisionSpec {
+        approved,
+        by: session.user_name.clone(),
+        comment,
+    };
+    let result = try_decide(client, &name, decision).await;
+    HttpResponse::from(Result::from(result))
+}
+
+async fn try_decide(
+    client: KubernetesStorageClient<'_, '_>,
+    name: &str,
+    decision: ApprovalDecisionSpec,
+) -> ::anyhow::Result<ApprovalCrd> {
+    let policy = client.load_approval_policy().await?;
+    if !policy.is_approver(&decision.by) {
+        bail!("the user is not an approver: {by}", by = &decision.by);
+    }
+
+    match client.load_approval(name).await? {
+        Some(approval)
+            if approval
+                .status
+                .as_ref()
+                .map(|status| status.state.is_decided())
+                .unwrap_or_default() =>
+        {
+            bail!("the approval has already been decided: {name}")
+        }
+        Some(_) => client.decide_approval(name, decision).await,
+        None => bail!("no such approval: {name}"),
+    }
+}
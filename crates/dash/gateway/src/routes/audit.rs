@@ -0,0 +1,29 @@
+use actix_web::{get, web::Query, HttpRequest, HttpResponse, Responder};
+use ark_core::result::Result;
+use dash_api::audit::AuditQuery;
+use dash_provider::audit::AuditClient;
+use tracing::{instrument, Level};
+use vine_api::user_session::UserSession;
+use vine_rbac::auth::AuthUserSession;
+
+use crate::auth::UserClient;
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[get("/audit")]
+pub async fn get_list(
+    request: HttpRequest,
+    kube: UserClient,
+    query: Query<AuditQuery>,
+) -> impl Responder {
+    let namespace = match UserSession::from_request(kube.service(), &request).await {
+        Ok(session) => session.namespace,
+        Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
+    };
+    let kube = kube.as_ref();
+
+    let result = match AuditClient::try_default().await {
+        Ok(client) => client.list(&namespace, &query).await,
+        Err(error) => Err(error),
+    };
+    HttpResponse::from(Result::from(result))
+}
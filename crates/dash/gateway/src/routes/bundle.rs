@@ -0,0 +1,60 @@
+use actix_web::{
+    get, post,
+    web::{Json, Query},
+    HttpRequest, HttpResponse, Responder,
+};
+use ark_core::result::Result;
+use dash_api::bundle::{DashBundle, DashBundleConflictStrategy};
+use dash_provider::bundle::DashBundleClient;
+use serde::{Deserialize, Serialize};
+use tracing::{instrument, Level};
+use vine_api::user_session::UserSession;
+use vine_rbac::auth::AuthUserSession;
+
+use crate::auth::UserClient;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleImportQuery {
+    #[serde(default)]
+    pub strategy: DashBundleConflictStrategy,
+}
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[get("/bundle")]
+pub async fn get(request: HttpRequest, kube: UserClient) -> impl Responder {
+    let namespace = match UserSession::from_request(kube.service(), &request).await {
+        Ok(session) => session.namespace,
+        Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
+    };
+    let kube = kube.as_ref();
+
+    let client = DashBundleClient {
+        namespace: &namespace,
+        kube,
+    };
+    let result = client.export().await;
+    HttpResponse::from(Result::from(result))
+}
+
+#[instrument(level = Level::INFO, skip(request, kube, bundle))]
+#[post("/bundle")]
+pub async fn post(
+    request: HttpRequest,
+    kube: UserClient,
+    Query(query): Query<BundleImportQuery>,
+    Json(bundle): Json<DashBundle>,
+) -> impl Responder {
+    let namespace = match UserSession::from_request(kube.service(), &request).await {
+        Ok(session) => session.namespace,
+        Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
+    };
+    let kube = kube.as_ref();
+
+    let client = DashBundleClient {
+        namespace: &namespace,
+        kube,
+    };
+    let result = client.import(bundle, query.strategy).await;
+    HttpResponse::from(Result::from(result))
+}
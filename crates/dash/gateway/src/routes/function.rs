@@ -0,0 +1,79 @@
+use actix_web::{
+    get, post,
+    web::{Json, Path, Query},
+    HttpRequest, HttpResponse, Responder,
+};
+use ark_core::result::Result;
+use dash_provider::{function::FunctionSession, input::Name, storage::KubernetesStorageClient};
+use dash_provider_api::{codegen::CodegenOptions, data::ResourceListOptions};
+use serde_json::Value;
+use tracing::{instrument, Level};
+use vine_api::user_session::UserSession;
+use vine_rbac::auth::AuthUserSession;
+
+use crate::auth::UserClient;
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[get("/function")]
+pub async fn get_list(
+    request: HttpRequest,
+    kube: UserClient,
+    options: Query<ResourceListOptions>,
+) -> impl Responder {
+    let namespace = match UserSession::from_request(kube.service(), &request).await {
+        Ok(session) => session.namespace,
+        Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
+    };
+    let kube = kube.as_ref();
+
+    let client = KubernetesStorageClient {
+        namespace: &namespace,
+        kube,
+    };
+    let result = client.load_function_page(&options).await;
+    HttpResponse::from(Result::from(result))
+}
+
+#[instrument(level = Level::INFO, skip(request, kube, value))]
+#[post("/function/{name}/invoke")]
+pub async fn post_invoke(
+    request: HttpRequest,
+    kube: UserClient,
+    name: Path<Name>,
+    Json(value): Json<Value>,
+) -> impl Responder {
+    let namespace = match UserSession::from_request(kube.service(), &request).await {
+        Ok(session) => session.namespace,
+        Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
+    };
+    let kube = kube.as_ref();
+
+    let session = FunctionSession {
+        namespace: &namespace,
+        kube,
+    };
+    let result = session.invoke(&name.0, &value).await;
+    HttpResponse::from(Result::from(result))
+}
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[get("/function/{name}/client")]
+pub async fn get_client(
+    request: HttpRequest,
+    kube: UserClient,
+    name: Path<Name>,
+    options: Query<CodegenOptions>,
+) -> impl Responder {
+    let namespace = match UserSession::from_request(kube.service(), &request).await {
+        Ok(session) => session.namespace,
+        Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
+    };
+    let kube = kube.as_ref();
+
+    let session = FunctionSession {
+        namespace: &namespace,
+        kube,
+    };
+    let result = session.generate_client(&name.0, options.0).await;
+    HttpResponse::from(Result::from(result))
+}
@@ -1,33 +1,30 @@
 use std::collections::BTreeMap;
 
-use actix_web::{
-    post,
-    web::{Data, Json},
-    HttpRequest, HttpResponse, Responder,
-};
+use actix_web::{post, web::Json, HttpRequest, HttpResponse, Responder};
 use ark_core::result::Result;
 use dash_api::job::DashJobCrd;
 use dash_provider_api::job::Payload;
 use dash_provider_client::DashProviderClient;
 use futures::{stream::FuturesUnordered, TryStreamExt};
-use kube::Client;
 use serde_json::Value;
 use tracing::{instrument, Level};
 use vine_api::user_session::UserSession;
 use vine_rbac::auth::AuthUserSession;
 
+use crate::auth::UserClient;
+
 #[instrument(level = Level::INFO, skip(request, kube))]
 #[post("/batch/job")]
 pub async fn post(
     request: HttpRequest,
-    kube: Data<Client>,
+    kube: UserClient,
     values: Json<Vec<Payload<BTreeMap<String, Value>>>>,
 ) -> impl Responder {
-    let kube = kube.as_ref().clone();
-    let session = match UserSession::from_request(&kube, &request).await {
+    let session = match UserSession::from_request(kube.service(), &request).await {
         Ok(session) => session,
         Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
     };
+    let kube = kube.as_ref().clone();
 
     let result: ::core::result::Result<Vec<DashJobCrd>, _> = values
         .0
@@ -2,31 +2,32 @@ use std::collections::BTreeMap;
 
 use actix_web::{
     delete, get, post,
-    web::{Data, Json, Path},
+    web::{Json, Path},
     HttpRequest, HttpResponse, Responder,
 };
 use ark_core::result::Result;
 use dash_provider::input::Name;
 use dash_provider_client::DashProviderClient;
-use kube::Client;
 use serde_json::Value;
 use tracing::{instrument, Level};
 use vine_api::user_session::UserSession;
 use vine_rbac::auth::AuthUserSession;
 
+use crate::auth::UserClient;
+
 #[instrument(level = Level::INFO, skip(request, kube))]
 #[delete("/task/{task_name}/job/{job_name}")]
 pub async fn delete(
     request: HttpRequest,
-    kube: Data<Client>,
+    kube: UserClient,
     path: Path<(Name, Name)>,
 ) -> impl Responder {
     let (task_name, job_name) = path.into_inner();
-    let kube = kube.as_ref().clone();
-    let session = match UserSession::from_request(&kube, &request).await {
+    let session = match UserSession::from_request(kube.service(), &request).await {
         Ok(session) => session,
         Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
     };
+    let kube = kube.as_ref().clone();
 
     let client = DashProviderClient::new(kube, &session);
     let result = client.delete(&task_name.0, &job_name.0).await;
@@ -37,15 +38,15 @@ pub async fn delete(
 #[get("/task/{task_name}/job/{job_name}")]
 pub async fn get(
     request: HttpRequest,
-    kube: Data<Client>,
+    kube: UserClient,
     path: Path<(Name, Name)>,
 ) -> impl Responder {
     let (task_name, job_name) = path.into_inner();
-    let kube = kube.as_ref().clone();
-    let session = match UserSession::from_request(&kube, &request).await {
+    let session = match UserSession::from_request(kube.service(), &request).await {
         Ok(session) => session,
         Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
     };
+    let kube = kube.as_ref().clone();
 
     let client = DashProviderClient::new(kube, &session);
     let result = client.get(&task_name.0, &job_name.0).await;
@@ -54,12 +55,12 @@ pub async fn get(
 
 #[instrument(level = Level::INFO, skip(request, kube))]
 #[get("/job")]
-pub async fn get_list(request: HttpRequest, kube: Data<Client>) -> impl Responder {
-    let kube = kube.as_ref().clone();
-    let session = match UserSession::from_request(&kube, &request).await {
+pub async fn get_list(request: HttpRequest, kube: UserClient) -> impl Responder {
+    let session = match UserSession::from_request(kube.service(), &request).await {
         Ok(session) => session,
         Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
     };
+    let kube = kube.as_ref().clone();
 
     let client = DashProviderClient::new(kube, &session);
     let result = client.get_list().await;
@@ -70,14 +71,14 @@ pub async fn get_list(request: HttpRequest, kube: Data<Client>) -> impl Responde
 #[get("/task/{task_name}/job")]
 pub async fn get_list_with_task_name(
     request: HttpRequest,
-    kube: Data<Client>,
+    kube: UserClient,
     task_name: Path<Name>,
 ) -> impl Responder {
-    let kube = kube.as_ref().clone();
-    let session = match UserSession::from_request(&kube, &request).await {
+    let session = match UserSession::from_request(kube.service(), &request).await {
         Ok(session) => session,
         Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
     };
+    let kube = kube.as_ref().clone();
 
     let client = DashProviderClient::new(kube, &session);
     let result = client.get_list_with_task_name(&task_name.0).await;
@@ -88,15 +89,15 @@ pub async fn get_list_with_task_name(
 #[get("/task/{task_name}/job/{job_name}/logs")]
 pub async fn get_stream_logs(
     request: HttpRequest,
-    kube: Data<Client>,
+    kube: UserClient,
     path: Path<(Name, Name)>,
 ) -> impl Responder {
     let (task_name, job_name) = path.into_inner();
-    let kube = kube.as_ref().clone();
-    let session = match UserSession::from_request(&kube, &request).await {
+    let session = match UserSession::from_request(kube.service(), &request).await {
         Ok(session) => session,
         Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
     };
+    let kube = kube.as_ref().clone();
 
     let client = DashProviderClient::new(kube, &session);
     match client
@@ -112,15 +113,15 @@ pub async fn get_stream_logs(
 #[post("/task/{task_name}/job")]
 pub async fn post(
     request: HttpRequest,
-    kube: Data<Client>,
+    kube: UserClient,
     task_name: Path<Name>,
     value: Json<BTreeMap<String, Value>>,
 ) -> impl Responder {
-    let kube = kube.as_ref().clone();
-    let session = match UserSession::from_request(&kube, &request).await {
+    let session = match UserSession::from_request(kube.service(), &request).await {
         Ok(session) => session,
         Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
     };
+    let kube = kube.as_ref().clone();
 
     let client = DashProviderClient::new(kube, &session);
     let result = client.create(&task_name.0, value.0).await;
@@ -131,15 +132,15 @@ pub async fn post(
 #[post("/task/{task_name}/job/{job_name}/restart")]
 pub async fn post_restart(
     request: HttpRequest,
-    kube: Data<Client>,
+    kube: UserClient,
     path: Path<(Name, Name)>,
 ) -> impl Responder {
     let (task_name, job_name) = path.into_inner();
-    let kube = kube.as_ref().clone();
-    let session = match UserSession::from_request(&kube, &request).await {
+    let session = match UserSession::from_request(kube.service(), &request).await {
         Ok(session) => session,
         Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
     };
+    let kube = kube.as_ref().clone();
 
     let client = DashProviderClient::new(kube, &session);
     let result = client.restart(&task_name.0, &job_name.0).await;
@@ -1,3 +1,9 @@
+pub mod approval;
+pub mod audit;
+pub mod bundle;
+pub mod function;
 pub mod job;
 pub mod model;
+pub mod storage;
 pub mod task;
+pub mod watch;
@@ -1,26 +1,33 @@
 use actix_web::{
-    get,
-    web::{Data, Path},
+    get, post,
+    web::{Json, Path, Query},
     HttpRequest, HttpResponse, Responder,
 };
 use ark_core::result::Result;
+use dash_api::{lineage::LineageQuery, model_claim::ModelClaimSpec};
+use dash_operator::{consts::infer_prometheus_url, validator::model_claim::ModelClaimValidator};
 use dash_provider::{
     input::Name,
+    lineage::LineageClient,
     storage::{KubernetesStorageClient, Storage, StorageClient},
 };
+use dash_provider_api::data::{ListOptions, ResourceListOptions};
 use kube::Client;
+use prometheus_http_query::Client as PrometheusClient;
 use tracing::{instrument, Level};
 use vine_api::user_session::UserSession;
 use vine_rbac::auth::AuthUserSession;
 
+use crate::auth::UserClient;
+
 #[instrument(level = Level::INFO, skip(request, kube))]
 #[get("/model/{name}")]
-pub async fn get(request: HttpRequest, kube: Data<Client>, name: Path<Name>) -> impl Responder {
-    let kube = kube.as_ref();
-    let namespace = match UserSession::from_request(&kube, &request).await {
+pub async fn get(request: HttpRequest, kube: UserClient, name: Path<Name>) -> impl Responder {
+    let namespace = match UserSession::from_request(kube.service(), &request).await {
         Ok(session) => session.namespace,
         Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
     };
+    let kube = kube.as_ref();
 
     let client = KubernetesStorageClient {
         namespace: &namespace,
@@ -34,14 +41,14 @@ pub async fn get(request: HttpRequest, kube: Data<Client>, name: Path<Name>) ->
 #[get("/model/{name}/task")]
 pub async fn get_task_list(
     request: HttpRequest,
-    kube: Data<Client>,
+    kube: UserClient,
     name: Path<Name>,
 ) -> impl Responder {
-    let kube = kube.as_ref();
-    let namespace = match UserSession::from_request(&kube, &request).await {
+    let namespace = match UserSession::from_request(kube.service(), &request).await {
         Ok(session) => session.namespace,
         Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
     };
+    let kube = kube.as_ref();
 
     let client = KubernetesStorageClient {
         namespace: &namespace,
@@ -52,19 +59,64 @@ pub async fn get_task_list(
 }
 
 #[instrument(level = Level::INFO, skip(request, kube))]
-#[get("/model")]
-pub async fn get_list(request: HttpRequest, kube: Data<Client>) -> impl Responder {
+#[get("/model/{name}/lineage")]
+pub async fn get_lineage(
+    request: HttpRequest,
+    kube: UserClient,
+    name: Path<Name>,
+    query: Query<LineageQuery>,
+) -> impl Responder {
+    let namespace = match UserSession::from_request(kube.service(), &request).await {
+        Ok(session) => session.namespace,
+        Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
+    };
+
+    let result = match LineageClient::try_default().await {
+        Ok(client) => client.downstream(&namespace, &name.0, &query).await,
+        Err(error) => Err(error),
+    };
+    HttpResponse::from(Result::from(result))
+}
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[get("/model/{name}/history")]
+pub async fn get_history(
+    request: HttpRequest,
+    kube: UserClient,
+    name: Path<Name>,
+) -> impl Responder {
+    let namespace = match UserSession::from_request(kube.service(), &request).await {
+        Ok(session) => session.namespace,
+        Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
+    };
     let kube = kube.as_ref();
-    let namespace = match UserSession::from_request(&kube, &request).await {
+
+    let client = KubernetesStorageClient {
+        namespace: &namespace,
+        kube,
+    };
+    let result = client.load_model_history(&name.0).await;
+    HttpResponse::from(Result::from(result))
+}
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[get("/model")]
+pub async fn get_list(
+    request: HttpRequest,
+    kube: UserClient,
+    options: Query<ResourceListOptions>,
+) -> impl Responder {
+    let namespace = match UserSession::from_request(kube.service(), &request).await {
         Ok(session) => session.namespace,
         Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
     };
+    let kube = kube.as_ref();
 
     let client = KubernetesStorageClient {
         namespace: &namespace,
         kube,
     };
-    let result = client.load_model_all().await;
+    let result = client.load_model_page(&options).await;
     HttpResponse::from(Result::from(result))
 }
 
@@ -72,20 +124,24 @@ pub async fn get_list(request: HttpRequest, kube: Data<Client>) -> impl Responde
 #[get("/model/{name}/item/{item}")]
 pub async fn get_item(
     request: HttpRequest,
-    kube: Data<Client>,
+    kube: UserClient,
     name: Path<(Name, String)>,
+    options: Query<ListOptions>,
 ) -> impl Responder {
-    let kube = kube.as_ref();
-    let namespace = match UserSession::from_request(&kube, &request).await {
+    let namespace = match UserSession::from_request(kube.service(), &request).await {
         Ok(session) => session.namespace,
         Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
     };
+    let kube = kube.as_ref();
 
     let client = StorageClient {
         namespace: &namespace,
         kube,
     };
-    let result = client.get(&name.0 .0, &name.1).await;
+    let result = client
+        .get(&name.0 .0, &name.1)
+        .await
+        .map(|item| options.project(item));
     HttpResponse::from(Result::from(result))
 }
 
@@ -93,19 +149,54 @@ pub async fn get_item(
 #[get("/model/{name}/item")]
 pub async fn get_item_list(
     request: HttpRequest,
-    kube: Data<Client>,
+    kube: UserClient,
     name: Path<Name>,
+    options: Query<ListOptions>,
 ) -> impl Responder {
-    let kube = kube.as_ref();
-    let namespace = match UserSession::from_request(&kube, &request).await {
+    let namespace = match UserSession::from_request(kube.service(), &request).await {
         Ok(session) => session.namespace,
         Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
     };
+    let kube = kube.as_ref();
 
     let client = StorageClient {
         namespace: &namespace,
         kube,
     };
-    let result = client.list(&name.0).await;
+    let result = client.list(&name.0, &options).await;
+    HttpResponse::from(Result::from(result))
+}
+
+#[instrument(level = Level::INFO, skip(request, kube, spec))]
+#[post("/model/{name}/claim/simulate")]
+pub async fn post_claim_simulate(
+    request: HttpRequest,
+    kube: UserClient,
+    name: Path<Name>,
+    Json(spec): Json<ModelClaimSpec>,
+) -> impl Responder {
+    let namespace = match UserSession::from_request(kube.service(), &request).await {
+        Ok(session) => session.namespace,
+        Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
+    };
+    let kube = kube.as_ref();
+
+    let prometheus_url = infer_prometheus_url();
+    let prometheus_client: PrometheusClient = match prometheus_url.parse() {
+        Ok(client) => client,
+        Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
+    };
+
+    let validator = ModelClaimValidator {
+        kubernetes_storage: KubernetesStorageClient {
+            namespace: &namespace,
+            kube,
+        },
+        prometheus_client: &prometheus_client,
+        prometheus_url: &prometheus_url,
+    };
+    let result = validator
+        .simulate_model_claim("dash-gateway", &name.0, &spec)
+        .await;
     HttpResponse::from(Result::from(result))
 }
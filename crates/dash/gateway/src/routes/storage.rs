@@ -0,0 +1,30 @@
+use actix_web::{get, web::Query, HttpRequest, HttpResponse, Responder};
+use ark_core::result::Result;
+use dash_provider::storage::KubernetesStorageClient;
+use dash_provider_api::data::ResourceListOptions;
+use tracing::{instrument, Level};
+use vine_api::user_session::UserSession;
+use vine_rbac::auth::AuthUserSession;
+
+use crate::auth::UserClient;
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[get("/storage")]
+pub async fn get_list(
+    request: HttpRequest,
+    kube: UserClient,
+    options: Query<ResourceListOptions>,
+) -> impl Responder {
+    let namespace = match UserSession::from_request(kube.service(), &request).await {
+        Ok(session) => session.namespace,
+        Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
+    };
+    let kube = kube.as_ref();
+
+    let client = KubernetesStorageClient {
+        namespace: &namespace,
+        kube,
+    };
+    let result = client.load_model_storage_page(&options).await;
+    HttpResponse::from(Result::from(result))
+}
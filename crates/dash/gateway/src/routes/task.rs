@@ -1,23 +1,25 @@
 use actix_web::{
     get,
-    web::{Data, Path},
+    web::{Path, Query},
     HttpRequest, HttpResponse, Responder,
 };
 use ark_core::result::Result;
 use dash_provider::{input::Name, storage::KubernetesStorageClient};
-use kube::Client;
+use dash_provider_api::data::ResourceListOptions;
 use tracing::{instrument, Level};
 use vine_api::user_session::UserSession;
 use vine_rbac::auth::AuthUserSession;
 
+use crate::auth::UserClient;
+
 #[instrument(level = Level::INFO, skip(request, kube))]
 #[get("/task/{name}")]
-pub async fn get(request: HttpRequest, kube: Data<Client>, name: Path<Name>) -> impl Responder {
-    let kube = kube.as_ref();
-    let namespace = match UserSession::from_request(&kube, &request).await {
+pub async fn get(request: HttpRequest, kube: UserClient, name: Path<Name>) -> impl Responder {
+    let namespace = match UserSession::from_request(kube.service(), &request).await {
         Ok(session) => session.namespace,
         Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
     };
+    let kube = kube.as_ref();
 
     let client = KubernetesStorageClient {
         namespace: &namespace,
@@ -29,17 +31,21 @@ pub async fn get(request: HttpRequest, kube: Data<Client>, name: Path<Name>) ->
 
 #[instrument(level = Level::INFO, skip(request, kube))]
 #[get("/task")]
-pub async fn get_list(request: HttpRequest, kube: Data<Client>) -> impl Responder {
-    let kube = kube.as_ref();
-    let namespace = match UserSession::from_request(&kube, &request).await {
+pub async fn get_list(
+    request: HttpRequest,
+    kube: UserClient,
+    options: Query<ResourceListOptions>,
+) -> impl Responder {
+    let namespace = match UserSession::from_request(kube.service(), &request).await {
         Ok(session) => session.namespace,
         Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
     };
+    let kube = kube.as_ref();
 
     let client = KubernetesStorageClient {
         namespace: &namespace,
         kube,
     };
-    let result = client.load_task_all().await;
+    let result = client.load_task_page(&options).await;
     HttpResponse::from(Result::from(result))
 }
@@ -0,0 +1,163 @@
+use std::collections::BTreeMap;
+
+use actix_web::{get, rt, web::Payload, HttpRequest, HttpResponse, Responder};
+use actix_ws::{Message, ProtocolError};
+use ark_core::result::Result;
+use dash_api::{model::ModelCrd, model_storage_binding::ModelStorageBindingCrd, task::TaskCrd};
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use k8s_openapi::NamespaceResourceScope;
+use kube::{
+    runtime::{watcher, WatchStreamExt},
+    Api, Client, Resource, ResourceExt,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::{instrument, warn, Level};
+use vine_api::user_session::UserSession;
+use vine_rbac::auth::AuthUserSession;
+
+use crate::auth::UserClient;
+
+/// A state of the resource, which is pushed to the subscribers whenever it is changed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceStateChanged {
+    pub kind: String,
+    pub name: String,
+    pub namespace: Option<String>,
+    pub state: Option<String>,
+}
+
+/// Stream the state changes of the models, tasks and model storage bindings over a websocket.
+///
+/// The current states are sent first, and then their transitions.
+#[instrument(level = Level::INFO, skip(request, kube, body))]
+#[get("/watch")]
+pub async fn get(request: HttpRequest, kube: UserClient, body: Payload) -> impl Responder {
+    let namespace = match UserSession::from_request(kube.service(), &request).await {
+        Ok(session) => session.namespace,
+        Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
+    };
+    let kube = kube.as_ref();
+
+    let (response, session, messages) = match ::actix_ws::handle(&request, body) {
+        Ok(handle) => handle,
+        Err(error) => return HttpResponse::from(Result::<()>::Err(error.to_string())),
+    };
+
+    let changes = stream::select_all([
+        watch::<ModelCrd>(kube, &namespace, |model| {
+            model.status.as_ref().map(|status| status.state.to_string())
+        }),
+        watch::<TaskCrd>(kube, &namespace, |task| {
+            task.status.as_ref().map(|status| status.state.to_string())
+        }),
+        watch::<ModelStorageBindingCrd>(kube, &namespace, |binding| {
+            binding
+                .status
+                .as_ref()
+                .map(|status| status.state.to_string())
+        }),
+    ]);
+
+    rt::spawn(serve(session, changes, messages));
+    response
+}
+
+fn watch<K>(
+    kube: &Client,
+    namespace: &str,
+    state: fn(&K) -> Option<String>,
+) -> BoxStream<'static, ResourceStateChanged>
+where
+    K: 'static
+        + Clone
+        + ::std::fmt::Debug
+        + Send
+        + DeserializeOwned
+        + Resource<Scope = NamespaceResourceScope>,
+    <K as Resource>::DynamicType: Default,
+{
+    let client = kube.clone();
+    let api = match namespace {
+        "*" => Api::<K>::all(client),
+        namespace => Api::<K>::namespaced(client, namespace),
+    };
+
+    watcher(api, watcher::Config::default())
+        .default_backoff()
+        .applied_objects()
+        .filter_map(move |result| async move {
+            match result {
+                Ok(object) => Some(ResourceStateChanged {
+                    kind: K::kind(&Default::default()).into_owned(),
+                    name: object.name_any(),
+                    namespace: object.namespace(),
+                    state: state(&object),
+                }),
+                Err(error) => {
+                    warn!("failed to watch resources: {error}");
+                    None
+                }
+            }
+        })
+        .boxed()
+}
+
+async fn serve(
+    mut session: ::actix_ws::Session,
+    changes: BoxStream<'static, ResourceStateChanged>,
+    messages: ::actix_ws::MessageStream,
+) {
+    enum Event {
+        Changed(ResourceStateChanged),
+        Message(Result<Message, ProtocolError>),
+    }
+
+    let mut events = stream::select(changes.map(Event::Changed), messages.map(Event::Message));
+    let mut states = BTreeMap::default();
+    while let Some(event) = events.next().await {
+        match event {
+            Event::Changed(change) => {
+                // skip the updates which do not change the state
+                let key = (
+                    change.kind.clone(),
+                    change.namespace.clone(),
+                    change.name.clone(),
+                );
+                if states.get(&key) == Some(&change.state) {
+                    continue;
+                }
+                states.insert(key, change.state.clone());
+
+                let text = match ::serde_json::to_string(&change) {
+                    Ok(text) => text,
+                    Err(error) => {
+                        warn!("failed to serialize resource state: {error}");
+                        continue;
+                    }
+                };
+                if session.text(text).await.is_err() {
+                    return;
+                }
+            }
+            Event::Message(Ok(Message::Ping(bytes))) => {
+                if session.pong(&bytes).await.is_err() {
+                    return;
+                }
+            }
+            Event::Message(Ok(Message::Close(reason))) => {
+                let _ = session.close(reason).await;
+                return;
+            }
+            Event::Message(Ok(_)) => continue,
+            Event::Message(Err(error)) => {
+                warn!("failed to receive websocket message: {error}");
+                break;
+            }
+        }
+    }
+    let _ = session.close(None).await;
+}
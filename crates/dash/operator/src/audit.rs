@@ -0,0 +1,128 @@
+use std::fmt;
+
+use chrono::Utc;
+use dash_api::audit::{AuditOperation, AuditRecord};
+use dash_provider::audit::{hash_state, AuditClient};
+use kube::{
+    api::{DeleteParams, Patch, PatchParams, PostParams},
+    Api, Error, Resource, ResourceExt,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::sync::OnceCell;
+use tracing::{instrument, warn, Level};
+
+/// Load the audit store shared by all controllers.
+///
+/// If the store is not available, the mutations are not audited.
+async fn init_auditor() -> Option<&'static AuditClient> {
+    static AUDITOR: OnceCell<Option<AuditClient>> = OnceCell::const_new();
+
+    AUDITOR
+        .get_or_init(|| async {
+            match AuditClient::try_default().await {
+                Ok(client) => Some(client),
+                Err(error) => {
+                    warn!("failed to init auditor; skipping audit logs: {error}");
+                    None
+                }
+            }
+        })
+        .await
+        .as_ref()
+}
+
+/// Create the resource, recording it into the audit logs.
+#[instrument(level = Level::INFO, skip_all, err(Display))]
+pub(crate) async fn create<K>(api: &Api<K>, pp: &PostParams, data: &K) -> Result<K, Error>
+where
+    K: Clone + fmt::Debug + Serialize + DeserializeOwned + Resource<DynamicType = ()>,
+{
+    let created = api.create(pp, data).await?;
+    record(
+        AuditOperation::Create,
+        pp.field_manager.as_deref(),
+        &created,
+        None,
+        Some(&created),
+    )
+    .await;
+    Ok(created)
+}
+
+/// Patch the status of the resource, recording it into the audit logs
+/// and notifying its transitions.
+#[instrument(level = Level::INFO, skip_all, fields(name = %name), err(Display))]
+pub(crate) async fn patch_status<K, P>(
+    api: &Api<K>,
+    name: &str,
+    pp: &PatchParams,
+    patch: &Patch<P>,
+) -> Result<K, Error>
+where
+    K: Clone + fmt::Debug + Serialize + DeserializeOwned + Resource<DynamicType = ()>,
+    P: fmt::Debug + Serialize,
+{
+    // NOTE: the old one is also compared to notify the transitions
+    let old = api.get_opt(name).await?;
+
+    let new = api.patch_status(name, pp, patch).await?;
+    crate::notification::notify(&api.clone().into_client(), old.as_ref(), &new);
+    record(
+        AuditOperation::PatchStatus,
+        pp.field_manager.as_deref(),
+        &new,
+        old.as_ref(),
+        Some(&new),
+    )
+    .await;
+    Ok(new)
+}
+
+/// Delete the resource, recording it into the audit logs.
+#[instrument(level = Level::INFO, skip_all, fields(name = %name), err(Display))]
+pub(crate) async fn delete<K>(api: &Api<K>, name: &str, dp: &DeleteParams) -> Result<(), Error>
+where
+    K: Clone + fmt::Debug + Serialize + DeserializeOwned + Resource<DynamicType = ()>,
+{
+    let old = match init_auditor().await {
+        Some(_) => api.get_opt(name).await?,
+        None => None,
+    };
+
+    api.delete(name, dp).await?;
+    if let Some(old) = &old {
+        record(AuditOperation::Delete, None, old, Some(old), None).await;
+    }
+    Ok(())
+}
+
+/// Record the mutation as best-effort, so that the controllers are not blocked by the store.
+async fn record<K>(
+    operation: AuditOperation,
+    actor: Option<&str>,
+    data: &K,
+    old: Option<&K>,
+    new: Option<&K>,
+) where
+    K: Serialize + Resource<DynamicType = ()>,
+{
+    let auditor = match init_auditor().await {
+        Some(auditor) => auditor,
+        None => return,
+    };
+
+    let hash = |data: Option<&K>| data.and_then(|data| hash_state(data).ok());
+    let record = AuditRecord {
+        timestamp: Utc::now(),
+        actor: actor.unwrap_or(crate::consts::NAME).into(),
+        operation,
+        kind: K::kind(&()).into_owned(),
+        namespace: data.namespace(),
+        name: data.name_any(),
+        old_hash: hash(old),
+        new_hash: hash(new),
+    };
+    if let Err(error) = auditor.record(record).await {
+        warn!("{error}");
+    }
+}
@@ -0,0 +1,161 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use ark_core_k8s::manager::Manager;
+use async_trait::async_trait;
+use chrono::Utc;
+use dash_api::approval::{ApprovalCrd, ApprovalDecisionSpec, ApprovalState, ApprovalStatus};
+use dash_provider::storage::KubernetesStorageClient;
+use kube::{
+    api::{Patch, PatchParams},
+    runtime::controller::Action,
+    Api, Client, CustomResourceExt, Error, ResourceExt,
+};
+use serde_json::json;
+use tracing::{info, instrument, warn, Level};
+
+#[derive(Default)]
+pub struct Ctx {}
+
+#[async_trait]
+impl ::ark_core_k8s::manager::Ctx for Ctx {
+    type Data = ApprovalCrd;
+
+    const NAME: &'static str = crate::consts::NAME;
+    const NAMESPACE: &'static str = ::dash_api::consts::NAMESPACE;
+    const FALLBACK: Duration = Duration::from_secs(60); // 1 minute
+
+    #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any(), namespace = data.namespace()), err(Display))]
+    async fn reconcile(
+        manager: Arc<Manager<Self>>,
+        data: Arc<<Self as ::ark_core_k8s::manager::Ctx>::Data>,
+    ) -> Result<Action, Error>
+    where
+        Self: Sized,
+    {
+        let name = data.name_any();
+        let namespace = data.namespace().unwrap();
+
+        // NOTE: the decided approvals are immutable
+        let last_status = data.status.as_ref();
+        if last_status
+            .map(|status| status.state.is_decided())
+            .unwrap_or_default()
+        {
+            return Ok(Action::await_change());
+        }
+
+        let kubernetes_storage = KubernetesStorageClient {
+            namespace: &namespace,
+            kube: &manager.kube,
+        };
+        let policy = match kubernetes_storage.load_approval_policy().await {
+            Ok(policy) => policy,
+            Err(e) => {
+                warn!("failed to load approval policy ({namespace}/{name}): {e}");
+                return Ok(Action::requeue(
+                    <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+                ));
+            }
+        };
+
+        let now = Utc::now();
+        let status = match &data.spec.decision {
+            Some(ApprovalDecisionSpec {
+                approved,
+                by,
+                comment,
+            }) if policy.is_approver(by) => ApprovalStatus {
+                state: if *approved {
+                    ApprovalState::Approved
+                } else {
+                    ApprovalState::Rejected
+                },
+                decided_at: Some(now),
+                decided_by: Some(by.clone()),
+                comment: comment.clone(),
+                message: None,
+                last_updated: now,
+            },
+            _ if data.spec.expires_at <= now => ApprovalStatus {
+                state: ApprovalState::Expired,
+                decided_at: None,
+                decided_by: None,
+                comment: None,
+                message: Some("no decision has been made until the expiry".into()),
+                last_updated: now,
+            },
+            Some(ApprovalDecisionSpec { by, .. }) => ApprovalStatus {
+                state: ApprovalState::Pending,
+                decided_at: None,
+                decided_by: None,
+                comment: None,
+                message: Some(format!("the user is not an approver: {by}")),
+                last_updated: now,
+            },
+            None => ApprovalStatus {
+                state: ApprovalState::Pending,
+                decided_at: None,
+                decided_by: None,
+                comment: None,
+                message: None,
+                last_updated: now,
+            },
+        };
+
+        // Skip updating if nothing has been changed
+        let requeue = (data.spec.expires_at - now)
+            .to_std()
+            .unwrap_or_default()
+            .min(<Self as ::ark_core_k8s::manager::Ctx>::FALLBACK);
+        if last_status
+            .map(|last| last.state == status.state && last.message == status.message)
+            .unwrap_or_default()
+        {
+            return Ok(Action::requeue(requeue));
+        }
+
+        let state = status.state;
+        match Self::update_status(&namespace, &manager.kube, &name, status).await {
+            Ok(()) => {
+                info!("approval is {state}: {namespace}/{name}");
+                if state.is_decided() {
+                    Ok(Action::await_change())
+                } else {
+                    Ok(Action::requeue(requeue))
+                }
+            }
+            Err(e) => {
+                warn!("failed to update approval state ({namespace}/{name}): {e}");
+                Ok(Action::requeue(
+                    <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+                ))
+            }
+        }
+    }
+}
+
+impl Ctx {
+    #[instrument(level = Level::INFO, skip(kube, status), err(Display))]
+    async fn update_status(
+        namespace: &str,
+        kube: &Client,
+        name: &str,
+        status: ApprovalStatus,
+    ) -> Result<()> {
+        let api = Api::<<Self as ::ark_core_k8s::manager::Ctx>::Data>::namespaced(
+            kube.clone(),
+            namespace,
+        );
+        let crd = <Self as ::ark_core_k8s::manager::Ctx>::Data::api_resource();
+
+        let patch = Patch::Merge(json!({
+            "apiVersion": crd.api_version,
+            "kind": crd.kind,
+            "status": status,
+        }));
+        let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME);
+        crate::audit::patch_status(&api, name, &pp, &patch).await?;
+        Ok(())
+    }
+}
@@ -1,13 +1,20 @@
 use std::{sync::Arc, time::Duration};
 
 use anyhow::Result;
-use ark_core_k8s::manager::Manager;
+use ark_core_k8s::manager::{Manager, Notifier};
 use async_trait::async_trait;
 use chrono::Utc;
 use dash_api::{
-    function::{FunctionCrd, FunctionSpec, FunctionState, FunctionStatus},
+    function::{
+        FunctionCrd, FunctionEndpointStatus, FunctionSpec, FunctionState, FunctionStatus,
+        FunctionTestStatus,
+    },
     model::ModelFieldsNativeSpec,
 };
+use dash_provider::{
+    function::FunctionSession,
+    lineage::{load_function_edge, LineageClient},
+};
 use kube::{
     api::{Patch, PatchParams},
     runtime::controller::Action,
@@ -30,6 +37,12 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
     const FALLBACK: Duration = Duration::from_secs(30); // 30 seconds
     const FINALIZER_NAME: &'static str =
         <Self as ::ark_core_k8s::manager::Ctx>::Data::FINALIZER_NAME;
+    const DEPENDENCIES: &'static [&'static str] = &["Model", "ModelStorageBinding"];
+    const LEADER_ELECTION: bool = true;
+
+    async fn init_notifier() -> Result<Option<Arc<dyn Notifier>>> {
+        crate::notifier::init_notifier().await
+    }
 
     #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any(), namespace = data.namespace()), err(Display))]
     async fn reconcile(
@@ -42,6 +55,12 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
         let name = data.name_any();
         let namespace = data.namespace().unwrap();
 
+        if let Some(action) =
+            super::pause_for_maintenance::<Self>(&manager.kube, &data, None).await?
+        {
+            return Ok(action);
+        }
+
         if data.metadata.deletion_timestamp.is_some()
             && data
                 .status
@@ -84,8 +103,16 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
             .map(|status| status.state)
             .unwrap_or_default()
         {
-            FunctionState::Pending => match validator.validate_function(data.spec.clone()).await {
+            FunctionState::Pending => match validator
+                .validate_function(
+                    <Self as ::ark_core_k8s::manager::Ctx>::NAME,
+                    &name,
+                    data.spec.clone(),
+                )
+                .await
+            {
                 Ok(spec) => {
+                    Self::record_lineage(&namespace, &manager.kube, &data).await;
                     Self::update_state_or_requeue(
                         &namespace,
                         &manager.kube,
@@ -106,9 +133,60 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
             },
             FunctionState::Ready => {
                 // TODO: implement to finding changes
-                Ok(Action::await_change())
+                match validator.load_endpoint(&name, &data.spec).await {
+                    Ok(Some(endpoint)) => {
+                        if data
+                            .status
+                            .as_ref()
+                            .and_then(|status| status.endpoint.as_ref())
+                            != Some(&endpoint)
+                        {
+                            if let Err(e) = Self::update_endpoint(
+                                &namespace,
+                                &manager.kube,
+                                &name,
+                                endpoint.clone(),
+                            )
+                            .await
+                            {
+                                warn!(
+                                    "failed to update function endpoint ({namespace}/{name}): {e}"
+                                );
+                            }
+                        }
+
+                        // NOTE: test the golden examples once per generation
+                        if endpoint.ready
+                            && data.spec.test.is_some()
+                            && data
+                                .status
+                                .as_ref()
+                                .and_then(|status| status.test.as_ref())
+                                .map(|test| test.generation)
+                                != data.metadata.generation
+                        {
+                            if let Err(e) =
+                                Self::test_function(&namespace, &manager.kube, &name, &data).await
+                            {
+                                warn!("failed to test function ({namespace}/{name}): {e}");
+                            }
+                        }
+
+                        // NOTE: the backend is not watched, so poll it for status propagation
+                        Ok(Action::requeue(
+                            <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+                        ))
+                    }
+                    Ok(None) => Ok(Action::await_change()),
+                    Err(e) => {
+                        warn!("failed to load function endpoint ({namespace}/{name}): {e}");
+                        Ok(Action::requeue(
+                            <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+                        ))
+                    }
+                }
             }
-            FunctionState::Deleting => match validator.delete(&data.spec).await {
+            FunctionState::Deleting => match validator.delete(&name, &data.spec).await {
                 Ok(()) => {
                     <Self as ::ark_core_k8s::manager::Ctx>::remove_finalizer_or_requeue_namespaced(
                         manager.kube.clone(),
@@ -171,11 +249,98 @@ impl Ctx {
             "status": FunctionStatus {
                 state,
                 spec,
+                endpoint: None,
+                test: None,
                 last_updated: Utc::now(),
             },
         }));
         let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME);
-        api.patch_status(name, &pp, &patch).await?;
+        crate::audit::patch_status(&api, name, &pp, &patch).await?;
+        Ok(())
+    }
+
+    /// Record the derivation between the models of the function, as best-effort.
+    async fn record_lineage(
+        namespace: &str,
+        kube: &Client,
+        data: &<Self as ::ark_core_k8s::manager::Ctx>::Data,
+    ) {
+        let lineage = match LineageClient::shared().await {
+            Some(lineage) => lineage,
+            None => return,
+        };
+
+        let name = data.name_any();
+        let result = match load_function_edge(kube, namespace, data, None).await {
+            Ok(edge) => lineage.record(edge).await,
+            Err(error) => Err(error),
+        };
+        if let Err(error) = result {
+            warn!("failed to record function lineage ({namespace}/{name}): {error}");
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip(kube, endpoint), err(Display))]
+    async fn update_endpoint(
+        namespace: &str,
+        kube: &Client,
+        name: &str,
+        endpoint: FunctionEndpointStatus,
+    ) -> Result<()> {
+        let api = Api::<<Self as ::ark_core_k8s::manager::Ctx>::Data>::namespaced(
+            kube.clone(),
+            namespace,
+        );
+        let crd = <Self as ::ark_core_k8s::manager::Ctx>::Data::api_resource();
+
+        let ready = endpoint.ready;
+        let patch = Patch::Merge(json!({
+            "apiVersion": crd.api_version,
+            "kind": crd.kind,
+            "status": {
+                "endpoint": endpoint,
+            },
+        }));
+        let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME);
+        crate::audit::patch_status(&api, name, &pp, &patch).await?;
+        info!("function endpoint is updated ({namespace}/{name}): ready={ready}");
+        Ok(())
+    }
+
+    #[instrument(level = Level::INFO, skip(kube, data), err(Display))]
+    async fn test_function(
+        namespace: &str,
+        kube: &Client,
+        name: &str,
+        data: &<Self as ::ark_core_k8s::manager::Ctx>::Data,
+    ) -> Result<()> {
+        let session = FunctionSession { namespace, kube };
+        let test = match session.test(data).await? {
+            Some(test) => test,
+            None => return Ok(()),
+        };
+
+        let api = Api::<<Self as ::ark_core_k8s::manager::Ctx>::Data>::namespaced(
+            kube.clone(),
+            namespace,
+        );
+        let crd = <Self as ::ark_core_k8s::manager::Ctx>::Data::api_resource();
+
+        let FunctionTestStatus { passed, failed, .. } = test;
+        let patch = Patch::Merge(json!({
+            "apiVersion": crd.api_version,
+            "kind": crd.kind,
+            "status": {
+                "test": test,
+            },
+        }));
+        let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME);
+        crate::audit::patch_status(&api, name, &pp, &patch).await?;
+        if failed == 0 {
+            info!("function is tested ({namespace}/{name}): passed={passed}");
+        } else {
+            warn!("function is tested ({namespace}/{name}): passed={passed}, failed={failed}");
+        }
         Ok(())
     }
 }
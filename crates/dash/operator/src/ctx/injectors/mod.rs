@@ -3,15 +3,17 @@ use std::{marker::PhantomData, sync::Arc, time::Duration};
 use anyhow::Result;
 use ark_core_k8s::manager::{Ctx, Manager};
 use async_trait::async_trait;
+use dash_provider::storage::KubernetesStorageClient;
 use futures::TryFutureExt;
 use k8s_openapi::api::core::v1::Namespace;
 use kube::{runtime::controller::Action, Error, ResourceExt};
+use serde_json::{json, Value};
 use tracing::{debug, info, instrument, warn, Level};
 
-use crate::validator::injector::InjectorValidator;
+use crate::validator::{injector::InjectorValidator, tenant::TenantValidator};
 
 macro_rules! define_injector {
-    [ $( $kind:ident as $name:expr , )* ] => {
+    [ $( $kind:ident as $name:expr $( { $( $item:item )* } )? , )* ] => {
         $(
             pub mod $kind {
                 pub type Ctx = super::InjectionCtx<InjectionCtxParams>;
@@ -23,6 +25,8 @@ macro_rules! define_injector {
                     const CONTENT: &'static str = include_str!(concat!("./", stringify!($kind), ".yaml.j2"));
                     const KIND: &'static str = stringify!($kind);
                     const NAME: &'static str = $name;
+
+                    $( $( $item )* )?
                 }
             }
         )*
@@ -32,7 +36,9 @@ macro_rules! define_injector {
 define_injector![
     kafka as "kafka",
     nats as "nats",
-    otlp as "dash-observability",
+    otlp as "dash-observability" {
+        const WITH_OBSERVABILITY: bool = true;
+    },
 ];
 
 #[derive(Default)]
@@ -47,6 +53,10 @@ where
     const CONTENT: &'static str;
     const KIND: &'static str;
     const NAME: &'static str;
+
+    /// Whether the template is rendered with the tenant's telemetry pipelines.
+    /// The resources are re-applied periodically to roll out the changes.
+    const WITH_OBSERVABILITY: bool = false;
 }
 
 #[async_trait]
@@ -59,6 +69,7 @@ where
     const NAME: &'static str = crate::consts::NAME;
     const NAMESPACE: &'static str = ::dash_api::consts::NAMESPACE;
     const FALLBACK: Duration = Duration::from_secs(30); // 30 seconds
+    const LEADER_ELECTION: bool = true;
 
     #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any()), err(Display))]
     async fn reconcile(
@@ -72,19 +83,68 @@ where
         let kind = <P as InjectionCtxParams>::KIND;
         let label = format!("dash.ulagbulag.io/inject-{kind}");
 
+        let enabled = data
+            .labels()
+            .get(&label)
+            .and_then(|value| value.parse().ok())
+            .unwrap_or_default();
+
+        // NOTE: the collectors are neither created nor deleted during the maintenance windows
+        let kubernetes_storage = KubernetesStorageClient {
+            namespace: &name,
+            kube: &manager.kube,
+        };
+        match kubernetes_storage.load_maintenance_window().await {
+            Ok(None) => (),
+            Ok(Some(window)) => {
+                let window_name = window.name_any();
+                debug!("skipped injecting {kind} collector: {name:?}: frozen by {window_name:?}");
+                return Ok(Action::requeue(
+                    <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+                ));
+            }
+            Err(e) => {
+                warn!("failed to check maintenance windows: {name:?}: {e}");
+                return Ok(Action::requeue(
+                    <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+                ));
+            }
+        }
+
+        let spec = if enabled && <P as InjectionCtxParams>::WITH_OBSERVABILITY {
+            let tenant = TenantValidator {
+                kubernetes_storage: KubernetesStorageClient {
+                    namespace: &name,
+                    kube: &manager.kube,
+                },
+            };
+            match tenant.load_collector_config().await {
+                Ok(collector) => json!({
+                    "observability": {
+                        "collector": collector,
+                    },
+                }),
+                Err(e) => {
+                    warn!("failed to validate {kind} collector config: {name:?}: {e}");
+                    return Ok(Action::requeue(
+                        <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+                    ));
+                }
+            }
+        } else {
+            Value::Null
+        };
+
         let validator = InjectorValidator {
             content: <P as InjectionCtxParams>::CONTENT,
             name: <P as InjectionCtxParams>::NAME,
             namespace: &name,
             kube: &manager.kube,
+            spec: &spec,
         };
 
-        match data
-            .labels()
-            .get(&label)
-            .and_then(|value| value.parse().ok())
-        {
-            Some(true) => match validator
+        match enabled {
+            true => match validator
                 .exists()
                 .and_then(|exists| {
                     let name = name.clone();
@@ -93,6 +153,11 @@ where
                             validator.create().await.map(|()| {
                                 info!("created {kind} collector: {name:?}");
                             })
+                        } else if <P as InjectionCtxParams>::WITH_OBSERVABILITY {
+                            // NOTE: apply the changes of the collector config
+                            validator.create().await.map(|()| {
+                                debug!("applied {kind} collector: {name:?}");
+                            })
                         } else {
                             debug!("skipped creating {kind} collector: {name:?}: already created");
                             Ok(())
@@ -101,6 +166,10 @@ where
                 })
                 .await
             {
+                // NOTE: the tenant config is not watched, so poll it for rollout
+                Ok(()) if <P as InjectionCtxParams>::WITH_OBSERVABILITY => Ok(Action::requeue(
+                    <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+                )),
                 Ok(()) => Ok(Action::await_change()),
                 Err(e) => {
                     warn!("failed to create {kind} collector: {name:?}: {e}");
@@ -109,7 +178,7 @@ where
                     ))
                 }
             },
-            Some(false) | None => match validator
+            false => match validator
                 .exists()
                 .and_then(|exists| {
                     let name = name.clone();
@@ -4,9 +4,14 @@ use anyhow::Result;
 use ark_core_k8s::manager::Manager;
 use async_trait::async_trait;
 use chrono::Utc;
-use dash_api::job::{DashJobCrd, DashJobState, DashJobStatus};
+use dash_api::{
+    job::{DashJobCrd, DashJobQueueStatus, DashJobState, DashJobStatus},
+    notification::NotificationPolicyCrd,
+    queue::DashQueueCrd,
+};
 use dash_provider::storage::KubernetesStorageClient;
 use dash_provider_api::TaskChannel;
+use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
 use kube::{
     api::{DeleteParams, Patch, PatchParams},
     runtime::controller::Action,
@@ -15,7 +20,11 @@ use kube::{
 use serde_json::json;
 use tracing::{info, instrument, warn, Level};
 
-use crate::validator::job::DashJobValidator;
+use crate::validator::{
+    job::DashJobValidator,
+    pipeline::DashPipelineValidator,
+    queue::{DashQueueAdmission, DashQueueValidator},
+};
 
 #[derive(Default)]
 pub struct Ctx {}
@@ -29,6 +38,11 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
     const FALLBACK: Duration = Duration::from_secs(30); // 30 seconds
     const FINALIZER_NAME: &'static str =
         <Self as ::ark_core_k8s::manager::Ctx>::Data::FINALIZER_NAME;
+    const LEADER_ELECTION: bool = true;
+
+    fn get_subcrds() -> Vec<CustomResourceDefinition> {
+        vec![DashQueueCrd::crd(), NotificationPolicyCrd::crd()]
+    }
 
     #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any(), namespace = data.namespace()), err(Display))]
     async fn reconcile(
@@ -41,6 +55,12 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
         let name = data.name_any();
         let namespace = data.namespace().unwrap();
 
+        if let Some(action) =
+            super::pause_for_maintenance::<Self>(&manager.kube, &data, None).await?
+        {
+            return Ok(action);
+        }
+
         let now = Utc::now();
         let completed_job_gc_timeout = ::chrono::Duration::try_minutes(20).unwrap();
 
@@ -61,7 +81,7 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
             return Self::update_spec_or_requeue(
                 &namespace,
                 &manager.kube,
-                &name,
+                &data,
                 data.status
                     .as_ref()
                     .and_then(|status| status.channel.as_ref())
@@ -88,30 +108,54 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
             .map(|status| status.state)
             .unwrap_or_default()
         {
-            DashJobState::Pending => match validator.create(data.as_ref().clone()).await {
-                Ok(channel) => {
-                    Self::update_spec_or_requeue(
-                        &namespace,
-                        &manager.kube,
-                        &name,
-                        Some(channel),
-                        DashJobState::Running,
-                    )
-                    .await
+            DashJobState::Pending => {
+                let queue_validator = DashQueueValidator {
+                    kube: &manager.kube,
+                };
+                match queue_validator.admit(&data).await {
+                    Ok(DashQueueAdmission::Admitted) => (),
+                    Ok(DashQueueAdmission::Queued(queue)) => {
+                        return Self::update_queue_or_requeue(
+                            &namespace,
+                            &manager.kube,
+                            &data,
+                            queue,
+                        )
+                        .await
+                    }
+                    Err(e) => {
+                        warn!("failed to queue dash job ({namespace}/{name}): {e}");
+                        return Ok(Action::requeue(
+                            <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+                        ));
+                    }
                 }
-                Err(e) => {
-                    warn!("failed to spawn dash jobs ({namespace}/{name}): {e}");
-                    Self::update_spec_or_requeue(
-                        &namespace,
-                        &manager.kube,
-                        &name,
-                        None,
-                        DashJobState::Error,
-                    )
-                    .await
-                    .map(|_| Action::await_change())
+
+                match validator.create(data.as_ref().clone()).await {
+                    Ok(channel) => {
+                        Self::update_spec_or_requeue(
+                            &namespace,
+                            &manager.kube,
+                            &data,
+                            Some(channel),
+                            DashJobState::Running,
+                        )
+                        .await
+                    }
+                    Err(e) => {
+                        warn!("failed to spawn dash jobs ({namespace}/{name}): {e}");
+                        Self::update_spec_or_requeue(
+                            &namespace,
+                            &manager.kube,
+                            &data,
+                            None,
+                            DashJobState::Error,
+                        )
+                        .await
+                        .map(|_| Action::await_change())
+                    }
                 }
-            },
+            }
             DashJobState::Running => match validator.is_running(data.as_ref().clone()).await {
                 Ok(true) => Ok(Action::requeue(
                     <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
@@ -120,7 +164,7 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
                     Ok(channel) => Self::update_spec_or_requeue(
                         &namespace,
                         &manager.kube,
-                        &name,
+                        &data,
                         Some(channel),
                         DashJobState::Completed,
                     )
@@ -181,13 +225,37 @@ impl Ctx {
     async fn update_spec_or_requeue(
         namespace: &str,
         kube: &Client,
-        name: &str,
+        data: &DashJobCrd,
         channel: Option<TaskChannel>,
         state: DashJobState,
     ) -> Result<Action, Error> {
-        match Self::update_spec(namespace, kube, name, channel, state).await {
+        let name = data.name_any();
+        let queue = data
+            .status
+            .as_ref()
+            .and_then(|status| status.queue.clone())
+            .map(|queue| DashJobQueueStatus {
+                position: None,
+                ..queue
+            });
+        match Self::update_spec(namespace, kube, &name, channel, state, queue).await {
             Ok(()) => {
                 info!("dash job is {state}: {namespace}/{name}");
+
+                if matches!(
+                    state,
+                    DashJobState::Running | DashJobState::Completed | DashJobState::Error,
+                ) {
+                    let validator = DashPipelineValidator {
+                        kubernetes_storage: KubernetesStorageClient { namespace, kube },
+                    };
+                    if let Err(e) = validator
+                        .schedule(<Self as ::ark_core_k8s::manager::Ctx>::NAME, data, state)
+                        .await
+                    {
+                        warn!("failed to schedule dash pipeline ({namespace}/{name}): {e}");
+                    }
+                }
                 Ok(Action::requeue(
                     <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
                 ))
@@ -201,13 +269,51 @@ impl Ctx {
         }
     }
 
-    #[instrument(level = Level::INFO, skip(kube, channel), err(Display))]
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn update_queue_or_requeue(
+        namespace: &str,
+        kube: &Client,
+        data: &DashJobCrd,
+        queue: DashJobQueueStatus,
+    ) -> Result<Action, Error> {
+        let name = data.name_any();
+        if data
+            .status
+            .as_ref()
+            .and_then(|status| status.queue.as_ref())
+            != Some(&queue)
+        {
+            let position = queue.position.unwrap_or_default();
+            let queue_name = queue.name.clone();
+            match Self::update_spec(
+                namespace,
+                kube,
+                &name,
+                None,
+                DashJobState::Pending,
+                Some(queue),
+            )
+            .await
+            {
+                Ok(()) => {
+                    info!("dash job is queued ({queue_name} #{position}): {namespace}/{name}")
+                }
+                Err(e) => warn!("failed to update dash job queue ({namespace}/{name}): {e}"),
+            }
+        }
+        Ok(Action::requeue(
+            <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+        ))
+    }
+
+    #[instrument(level = Level::INFO, skip(kube, channel, queue), err(Display))]
     async fn update_spec(
         namespace: &str,
         kube: &Client,
         name: &str,
         channel: Option<TaskChannel>,
         state: DashJobState,
+        queue: Option<DashJobQueueStatus>,
     ) -> Result<()> {
         let api = Api::<<Self as ::ark_core_k8s::manager::Ctx>::Data>::namespaced(
             kube.clone(),
@@ -221,11 +327,13 @@ impl Ctx {
             "status": DashJobStatus {
                 channel,
                 state,
+                pipeline: None,
+                queue,
                 last_updated: Utc::now(),
             },
         }));
         let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME);
-        api.patch_status(name, &pp, &patch).await?;
+        crate::audit::patch_status(&api, name, &pp, &patch).await?;
         Ok(())
     }
 
@@ -241,7 +349,7 @@ impl Ctx {
         );
         let dp = DeleteParams::default();
 
-        match api.delete(name, &dp).await {
+        match crate::audit::delete(&api, name, &dp).await {
             Ok(_) => {
                 info!("requested dash job deletion: {namespace}/{name}");
                 Ok(Action::await_change())
@@ -0,0 +1,72 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use ark_core_k8s::manager::Manager;
+use async_trait::async_trait;
+use chrono::Utc;
+use dash_api::maintenance::{
+    MaintenanceWindowCrd, MaintenanceWindowState, MaintenanceWindowStatus,
+};
+use kube::{
+    api::{Patch, PatchParams},
+    runtime::controller::Action,
+    Api, CustomResourceExt, Error, ResourceExt,
+};
+use serde_json::json;
+use tracing::{info, instrument, Level};
+
+#[derive(Default)]
+pub struct Ctx {}
+
+#[async_trait]
+impl ::ark_core_k8s::manager::Ctx for Ctx {
+    type Data = MaintenanceWindowCrd;
+
+    const NAME: &'static str = crate::consts::NAME;
+    const NAMESPACE: &'static str = ::dash_api::consts::NAMESPACE;
+    const FALLBACK: Duration = Duration::from_secs(5 * 60); // 5 minutes
+    const LEADER_ELECTION: bool = true;
+
+    #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any()), err(Display))]
+    async fn reconcile(
+        manager: Arc<Manager<Self>>,
+        data: Arc<<Self as ::ark_core_k8s::manager::Ctx>::Data>,
+    ) -> Result<Action, Error>
+    where
+        Self: Sized,
+    {
+        let name = data.name_any();
+        let now = Utc::now();
+        let state = data.spec.state_at(now);
+
+        if data.status.as_ref().map(|status| status.state) != Some(state) {
+            let api =
+                Api::<<Self as ::ark_core_k8s::manager::Ctx>::Data>::all(manager.kube.clone());
+            let crd = MaintenanceWindowCrd::api_resource();
+            let patch = Patch::Merge(json!({
+                "apiVersion": crd.api_version,
+                "kind": crd.kind,
+                "status": MaintenanceWindowStatus {
+                    state,
+                    last_updated: now,
+                },
+            }));
+            let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME);
+            crate::audit::patch_status(&api, &name, &pp, &patch).await?;
+
+            info!("Maintenance window {name:?} is {state}");
+        }
+
+        // NOTE: the next transition is checked as soon as possible
+        let next = match state {
+            MaintenanceWindowState::Scheduled => data.spec.starts_at,
+            MaintenanceWindowState::Active => data.spec.ends_at,
+            MaintenanceWindowState::Closed => None,
+        };
+        let duration = next.and_then(|next| (next - now).to_std().ok()).map_or(
+            <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+            |duration| duration.min(<Self as ::ark_core_k8s::manager::Ctx>::FALLBACK),
+        );
+        Ok(Action::requeue(duration))
+    }
+}
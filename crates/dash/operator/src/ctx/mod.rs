@@ -1,8 +1,180 @@
+pub mod approval;
 pub mod function;
 pub mod injectors;
 pub mod job;
+pub mod maintenance_window;
 pub mod model;
 pub mod model_claim;
 pub mod model_storage_binding;
+pub mod orphaned_artifact;
 pub mod storage;
 pub mod task;
+
+use ark_core_k8s::manager::Ctx;
+use chrono::Utc;
+use dash_api::condition::{with_condition, Condition, TYPE_FROZEN, TYPE_READY};
+use dash_provider::storage::KubernetesStorageClient;
+use kube::{
+    api::{Patch, PatchParams},
+    runtime::controller::Action,
+    Api, Client, CustomResourceExt, Error, Resource, ResourceExt,
+};
+use serde_json::json;
+use tracing::{info, instrument, warn, Level};
+
+/// Report the failure reason as the `Ready` condition, and requeue the resource.
+#[instrument(level = Level::INFO, skip(kube, data, conditions, state), err(Display))]
+pub(crate) async fn report_not_ready<C>(
+    kube: &Client,
+    data: &<C as Ctx>::Data,
+    conditions: Option<&[Condition]>,
+    state: impl ToString,
+    reason: &str,
+    error: &::anyhow::Error,
+) -> Result<Action, Error>
+where
+    C: Ctx,
+    <C as Ctx>::Data: CustomResourceExt
+        + Resource<Scope = ::k8s_openapi::NamespaceResourceScope>
+        + ::serde::Serialize,
+{
+    let name = data.name_any();
+    let namespace = data.namespace().unwrap();
+
+    let api = Api::<<C as Ctx>::Data>::namespaced(kube.clone(), &namespace);
+    let crd = <C as Ctx>::Data::api_resource();
+
+    let patch = Patch::Merge(json!({
+        "apiVersion": crd.api_version,
+        "kind": crd.kind,
+        "status": {
+            "conditions": with_condition(
+                conditions,
+                TYPE_READY,
+                false,
+                reason,
+                error,
+                data.meta().generation,
+            ),
+            "lastUpdated": Utc::now(),
+            "state": state.to_string(),
+        },
+    }));
+    let pp = PatchParams::apply(<C as Ctx>::NAME);
+    if let Err(e) = crate::audit::patch_status(&api, &name, &pp, &patch).await {
+        warn!("failed to report the condition ({namespace}/{name}): {e}");
+    }
+
+    Ok(Action::requeue(<C as Ctx>::FALLBACK))
+}
+
+/// Pause the mutating reconciliation while a maintenance window freezes the namespace.
+///
+/// The resource is requeued until the window is closed, so that the pending work is resumed then.
+/// The `Frozen` condition is reported if the resource has the conditions.
+#[instrument(level = Level::INFO, skip(kube, data, conditions), err(Display))]
+pub(crate) async fn pause_for_maintenance<C>(
+    kube: &Client,
+    data: &<C as Ctx>::Data,
+    conditions: Option<&[Condition]>,
+) -> Result<Option<Action>, Error>
+where
+    C: Ctx,
+    <C as Ctx>::Data: CustomResourceExt
+        + Resource<Scope = ::k8s_openapi::NamespaceResourceScope>
+        + ::serde::Serialize,
+{
+    let name = data.name_any();
+    let namespace = data.namespace().unwrap();
+
+    let kubernetes_storage = KubernetesStorageClient {
+        namespace: &namespace,
+        kube,
+    };
+    let window = match kubernetes_storage.load_maintenance_window().await {
+        Ok(window) => window,
+        Err(error) => {
+            // NOTE: the mutations are not allowed unless the change freeze is surely lifted
+            warn!("failed to check maintenance windows ({namespace}/{name}): {error}");
+            return Ok(Some(Action::requeue(<C as Ctx>::FALLBACK)));
+        }
+    };
+
+    let is_frozen = conditions
+        .and_then(|conditions| {
+            conditions
+                .iter()
+                .find(|condition| condition.type_ == TYPE_FROZEN)
+        })
+        .is_some_and(|condition| condition.status == "True");
+
+    let (action, status, reason, message) = match &window {
+        Some(window) => {
+            let window_name = window.name_any();
+            let message = match &window.spec.reason {
+                Some(reason) => {
+                    format!("paused by the maintenance window ({window_name}): {reason}")
+                }
+                None => format!("paused by the maintenance window ({window_name})"),
+            };
+
+            // NOTE: the resource is requeued as soon as the window is closed
+            let duration = window
+                .spec
+                .ends_at
+                .and_then(|ends_at| (ends_at - Utc::now()).to_std().ok())
+                .map_or(<C as Ctx>::FALLBACK, |duration| {
+                    duration.min(<C as Ctx>::FALLBACK)
+                });
+            (
+                Some(Action::requeue(duration)),
+                true,
+                "MaintenanceWindow",
+                message,
+            )
+        }
+        None if is_frozen => (
+            None,
+            false,
+            "MaintenanceWindowClosed",
+            "resumed after the maintenance window".into(),
+        ),
+        None => return Ok(None),
+    };
+
+    if let Some(conditions) = conditions {
+        let last_message = conditions
+            .iter()
+            .find(|condition| condition.type_ == TYPE_FROZEN)
+            .map(|condition| condition.message.as_str());
+        if is_frozen != status || last_message != Some(message.as_str()) {
+            let api = Api::<<C as Ctx>::Data>::namespaced(kube.clone(), &namespace);
+            let crd = <C as Ctx>::Data::api_resource();
+
+            let patch = Patch::Merge(json!({
+                "apiVersion": crd.api_version,
+                "kind": crd.kind,
+                "status": {
+                    "conditions": with_condition(
+                        Some(conditions),
+                        TYPE_FROZEN,
+                        status,
+                        reason,
+                        &message,
+                        data.meta().generation,
+                    ),
+                    "lastUpdated": Utc::now(),
+                },
+            }));
+            let pp = PatchParams::apply(<C as Ctx>::NAME);
+            if let Err(e) = crate::audit::patch_status(&api, &name, &pp, &patch).await {
+                warn!("failed to report the condition ({namespace}/{name}): {e}");
+            }
+        }
+    }
+
+    if status {
+        info!("{message}: {namespace}/{name}");
+    }
+    Ok(action)
+}
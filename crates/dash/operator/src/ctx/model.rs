@@ -1,10 +1,13 @@
 use std::{sync::Arc, time::Duration};
 
 use anyhow::Result;
-use ark_core_k8s::manager::Manager;
+use ark_core_k8s::manager::{Manager, Notifier};
 use async_trait::async_trait;
 use chrono::Utc;
-use dash_api::model::{ModelCrd, ModelFieldsNativeSpec, ModelState, ModelStatus};
+use dash_api::{
+    condition::{with_condition, Condition, TYPE_READY},
+    model::{ModelCrd, ModelFieldsNativeSpec, ModelState, ModelStatus, ModelVersion},
+};
 use dash_provider::storage::KubernetesStorageClient;
 use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
 use kube::{
@@ -29,11 +32,16 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
     const FALLBACK: Duration = Duration::from_secs(30); // 30 seconds
     const FINALIZER_NAME: &'static str =
         <Self as ::ark_core_k8s::manager::Ctx>::Data::FINALIZER_NAME;
+    const LEADER_ELECTION: bool = true;
 
     fn get_subcrds() -> Vec<CustomResourceDefinition> {
         vec![::dash_api::model_user::ModelUserCrd::crd()]
     }
 
+    async fn init_notifier() -> Result<Option<Arc<dyn Notifier>>> {
+        crate::notifier::init_notifier().await
+    }
+
     #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any(), namespace = data.namespace()), err(Display))]
     async fn reconcile(
         manager: Arc<Manager<Self>>,
@@ -44,6 +52,21 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
     {
         let name = data.name_any();
         let namespace = data.namespace().unwrap();
+        let conditions = data
+            .status
+            .as_ref()
+            .map(|status| status.conditions.as_slice());
+        let generation = data.metadata.generation;
+
+        if let Some(action) = super::pause_for_maintenance::<Self>(
+            &manager.kube,
+            &data,
+            Some(conditions.unwrap_or_default()),
+        )
+        .await?
+        {
+            return Ok(action);
+        }
 
         if data.metadata.deletion_timestamp.is_some()
             && data
@@ -58,7 +81,18 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
                 &manager.kube,
                 &name,
                 status.and_then(|status| status.fields.clone()),
+                status
+                    .map(|status| status.history.clone())
+                    .unwrap_or_default(),
                 ModelState::Deleting,
+                with_condition(
+                    conditions,
+                    TYPE_READY,
+                    false,
+                    "Deleting",
+                    "the model is being deleted",
+                    generation,
+                ),
             )
             .await;
         } else if !data
@@ -89,25 +123,131 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
         {
             ModelState::Pending => match validator.validate_model(data.spec.clone()).await {
                 Ok(fields) => {
-                    Self::update_fields_or_requeue(
+                    let status = data.status.as_ref();
+                    let version = ModelVersion::new(
+                        generation,
+                        status.and_then(|status| status.fields.as_ref()),
+                        &fields,
+                    );
+                    let action = Self::update_fields_or_requeue(
                         &namespace,
                         &manager.kube,
                         &name,
                         Some(fields),
+                        push_version(status, version),
                         ModelState::Ready,
+                        with_condition(
+                            conditions,
+                            TYPE_READY,
+                            true,
+                            "Validated",
+                            "the model fields are validated",
+                            generation,
+                        ),
                     )
-                    .await
+                    .await;
+                    manager.notify(&data).await;
+                    action
                 }
                 Err(e) => {
                     warn!("failed to validate model: {name:?}: {e}");
-                    Ok(Action::requeue(
-                        <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
-                    ))
+                    super::report_not_ready::<Self>(
+                        &manager.kube,
+                        &data,
+                        conditions,
+                        ModelState::Pending,
+                        "ValidationFailed",
+                        &e,
+                    )
+                    .await
                 }
             },
             ModelState::Ready => {
-                // TODO: implement to finding changes
-                Ok(Action::await_change())
+                let status = data.status.as_ref().unwrap();
+                if status
+                    .history
+                    .last()
+                    .map(|version| version.generation == generation)
+                    .unwrap_or_default()
+                {
+                    return Ok(Action::await_change());
+                }
+
+                let fields = match validator.validate_model(data.spec.clone()).await {
+                    Ok(fields) => fields,
+                    Err(e) => {
+                        warn!("failed to validate model: {name:?}: {e}");
+                        return super::report_not_ready::<Self>(
+                            &manager.kube,
+                            &data,
+                            conditions,
+                            ModelState::Ready,
+                            "ValidationFailed",
+                            &e,
+                        )
+                        .await;
+                    }
+                };
+
+                match validator
+                    .validate_model_update(&data, status.fields.as_ref(), &fields)
+                    .await
+                {
+                    Ok(version) => {
+                        let message = format!(
+                            "the schema change is {compatibility}",
+                            compatibility = version.compatibility,
+                        );
+                        let conditions = with_condition(
+                            conditions,
+                            TYPE_READY,
+                            true,
+                            "Validated",
+                            "the model fields are validated",
+                            generation,
+                        );
+                        let action = Self::update_fields_or_requeue(
+                            &namespace,
+                            &manager.kube,
+                            &name,
+                            Some(fields),
+                            push_version(Some(status), version),
+                            ModelState::Ready,
+                            with_condition(
+                                Some(conditions.as_slice()),
+                                ModelCrd::CONDITION_COMPATIBLE,
+                                true,
+                                "Updated",
+                                message,
+                                generation,
+                            ),
+                        )
+                        .await;
+                        manager.notify(&data).await;
+                        action
+                    }
+                    // NOTE: keep serving the last fields until the change is fixed or allowed
+                    Err(e) => {
+                        warn!("failed to update model: {name:?}: {e}");
+                        Self::update_fields_or_requeue(
+                            &namespace,
+                            &manager.kube,
+                            &name,
+                            status.fields.clone(),
+                            status.history.clone(),
+                            ModelState::Ready,
+                            with_condition(
+                                conditions,
+                                ModelCrd::CONDITION_COMPATIBLE,
+                                false,
+                                "BreakingChange",
+                                e,
+                                generation,
+                            ),
+                        )
+                        .await
+                    }
+                }
             }
             ModelState::Deleting => match validator.delete(&data).await {
                 Ok(()) => {
@@ -136,9 +276,11 @@ impl Ctx {
         kube: &Client,
         name: &str,
         fields: Option<ModelFieldsNativeSpec>,
+        history: Vec<ModelVersion>,
         state: ModelState,
+        conditions: Vec<Condition>,
     ) -> Result<Action, Error> {
-        match Self::update_fields(namespace, kube, name, fields, state).await {
+        match Self::update_fields(namespace, kube, name, fields, history, state, conditions).await {
             Ok(()) => {
                 info!("model is ready: {namespace}/{name}");
                 Ok(Action::requeue(
@@ -154,13 +296,15 @@ impl Ctx {
         }
     }
 
-    #[instrument(level = Level::INFO, skip(kube, fields), err(Display))]
+    #[instrument(level = Level::INFO, skip(kube, fields, history, conditions), err(Display))]
     async fn update_fields(
         namespace: &str,
         kube: &Client,
         name: &str,
         fields: Option<ModelFieldsNativeSpec>,
+        history: Vec<ModelVersion>,
         state: ModelState,
+        conditions: Vec<Condition>,
     ) -> Result<()> {
         let api = Api::<<Self as ::ark_core_k8s::manager::Ctx>::Data>::namespaced(
             kube.clone(),
@@ -173,12 +317,25 @@ impl Ctx {
             "kind": crd.kind,
             "status": ModelStatus {
                 state,
+                conditions,
                 fields,
+                history,
                 last_updated: Utc::now(),
             },
         }));
         let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME);
-        api.patch_status(name, &pp, &patch).await?;
+        crate::audit::patch_status(&api, name, &pp, &patch).await?;
         Ok(())
     }
 }
+
+fn push_version(status: Option<&ModelStatus>, version: ModelVersion) -> Vec<ModelVersion> {
+    let mut history = status
+        .map(|status| status.history.clone())
+        .unwrap_or_default();
+    history.push(version);
+    if history.len() > ModelCrd::MAX_VERSION_HISTORY {
+        history.drain(..history.len() - ModelCrd::MAX_VERSION_HISTORY);
+    }
+    history
+}
@@ -4,8 +4,13 @@ use anyhow::Result;
 use ark_core_k8s::manager::{Manager, TryDefault};
 use async_trait::async_trait;
 use chrono::Utc;
-use dash_api::model_claim::{ModelClaimCrd, ModelClaimState, ModelClaimStatus};
+use dash_api::{
+    condition::{set_condition, with_condition, Condition, TYPE_OPTIMIZED, TYPE_READY},
+    model_claim::{ModelClaimCrd, ModelClaimState, ModelClaimStatus},
+    tenant::TenantConfigCrd,
+};
 use dash_provider::storage::KubernetesStorageClient;
+use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
 use kube::{
     api::{Patch, PatchParams},
     runtime::controller::Action,
@@ -17,6 +22,7 @@ use tracing::{info, instrument, warn, Level};
 
 use crate::{
     consts::infer_prometheus_url,
+    optimizer::model_claim::Placement,
     validator::model_claim::{ModelClaimValidator, UpdateContext},
 };
 
@@ -45,6 +51,11 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
     const FALLBACK: Duration = Duration::from_secs(30); // 30 seconds
     const FINALIZER_NAME: &'static str =
         <Self as ::ark_core_k8s::manager::Ctx>::Data::FINALIZER_NAME;
+    const LEADER_ELECTION: bool = true;
+
+    fn get_subcrds() -> Vec<CustomResourceDefinition> {
+        vec![TenantConfigCrd::crd()]
+    }
 
     #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any(), namespace = data.namespace()), err(Display))]
     async fn reconcile(
@@ -56,6 +67,17 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
     {
         let name = data.name_any();
         let namespace = data.namespace().unwrap();
+        let conditions = data
+            .status
+            .as_ref()
+            .map(|status| status.conditions.as_slice())
+            .unwrap_or_default();
+
+        if let Some(action) =
+            super::pause_for_maintenance::<Self>(&manager.kube, &data, Some(conditions)).await?
+        {
+            return Ok(action);
+        }
 
         if data.metadata.deletion_timestamp.is_some()
             && data
@@ -66,13 +88,21 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
         {
             let status = data.status.as_ref();
             let ctx = UpdateContext {
+                distribution: status
+                    .map(|status| status.distribution.clone())
+                    .unwrap_or_default(),
                 owner_references: None,
+                placement: None,
+                resize_history: status
+                    .map(|status| status.resize_history.clone())
+                    .unwrap_or_default(),
                 resources: status.and_then(|status| status.resources.clone()),
                 state: ModelClaimState::Deleting,
                 storage: status.and_then(|status| status.storage),
                 storage_name: status.and_then(|status| status.storage_name.clone()),
             };
-            return Self::update_fields_or_requeue(&namespace, &manager.kube, &name, ctx).await;
+            return Self::update_fields_or_requeue(&namespace, &manager.kube, &name, &data, ctx)
+                .await;
         } else if !data
             .finalizers()
             .iter()
@@ -106,13 +136,22 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
                 .await
             {
                 Ok(ctx) => {
-                    Self::update_fields_or_requeue(&namespace, &manager.kube, &name, ctx).await
+                    Self::update_fields_or_requeue(&namespace, &manager.kube, &name, &data, ctx)
+                        .await
                 }
                 Err(e) => {
                     warn!("failed to validate model claim: {name:?}: {e}");
-                    Ok(Action::requeue(
-                        <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
-                    ))
+                    super::report_not_ready::<Self>(
+                        &manager.kube,
+                        &data,
+                        data.status
+                            .as_ref()
+                            .map(|status| status.conditions.as_slice()),
+                        ModelClaimState::Pending,
+                        "ValidationFailed",
+                        &e,
+                    )
+                    .await
                 }
             },
             ModelClaimState::Ready => {
@@ -125,8 +164,13 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
                     .await
                 {
                     Ok(Some(ctx)) => {
-                        Self::update_fields_or_requeue(&namespace, &manager.kube, &name, ctx).await
+                        Self::update_fields_or_requeue(&namespace, &manager.kube, &name, &data, ctx)
+                            .await
                     }
+                    // NOTE: the storage usage is not watched, so poll it for autoscaling
+                    Ok(None) if data.spec.autoscaling.is_some() => Ok(Action::requeue(
+                        <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+                    )),
                     Ok(None) => Ok(Action::await_change()),
                     Err(e) => {
                         warn!("failed to update model claim: {name:?}: {e}");
@@ -146,7 +190,8 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
                     .await
                 {
                     Ok(Some(ctx)) => {
-                        Self::update_fields_or_requeue(&namespace, &manager.kube, &name, ctx).await
+                        Self::update_fields_or_requeue(&namespace, &manager.kube, &name, &data, ctx)
+                            .await
                     }
                     Ok(None) => Ok(Action::requeue(
                         <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
@@ -185,10 +230,52 @@ impl Ctx {
         namespace: &str,
         kube: &Client,
         name: &str,
+        data: &ModelClaimCrd,
         ctx: UpdateContext,
     ) -> Result<Action, Error> {
         let state = ctx.state;
-        match Self::update_fields(namespace, kube, name, ctx).await {
+        let (ready, reason, message) = match state {
+            ModelClaimState::Pending => (false, "Pending", "the model claim is not bound yet"),
+            ModelClaimState::Ready if !ctx.distribution.is_empty() => (
+                true,
+                "Distributed",
+                "the model claim is bound to the storages",
+            ),
+            ModelClaimState::Ready => (true, "Bound", "the model claim is bound to the storage"),
+            ModelClaimState::Replacing => (false, "Replacing", "the storage is being replaced"),
+            ModelClaimState::Deleting => (false, "Deleting", "the model claim is being deleted"),
+        };
+        let mut conditions = with_condition(
+            data.status
+                .as_ref()
+                .map(|status| status.conditions.as_slice()),
+            TYPE_READY,
+            ready,
+            reason,
+            message,
+            data.metadata.generation,
+        );
+        match &ctx.placement {
+            Some(Placement::Optimized) => set_condition(
+                &mut conditions,
+                TYPE_OPTIMIZED,
+                true,
+                "Optimized",
+                "the storage is selected by the binding policy",
+                data.metadata.generation,
+            ),
+            Some(Placement::Fallback(message)) => set_condition(
+                &mut conditions,
+                TYPE_OPTIMIZED,
+                false,
+                "Fallback",
+                message,
+                data.metadata.generation,
+            ),
+            None => (),
+        }
+
+        match Self::update_fields(namespace, kube, name, ctx, conditions).await {
             Ok(()) => {
                 info!("model claim is {state}: {namespace}/{name}");
                 Ok(Action::requeue(
@@ -204,18 +291,22 @@ impl Ctx {
         }
     }
 
-    #[instrument(level = Level::INFO, skip(kube, owner_references, resources, state), err(Display))]
+    #[instrument(level = Level::INFO, skip(kube, distribution, owner_references, resize_history, resources, state, conditions), err(Display))]
     async fn update_fields(
         namespace: &str,
         kube: &Client,
         name: &str,
         UpdateContext {
+            distribution,
             owner_references,
+            placement: _,
+            resize_history,
             resources,
             state,
             storage,
             storage_name,
         }: UpdateContext,
+        conditions: Vec<Condition>,
     ) -> Result<()> {
         let api = Api::<<Self as ::ark_core_k8s::manager::Ctx>::Data>::namespaced(
             kube.clone(),
@@ -227,6 +318,9 @@ impl Ctx {
             "apiVersion": crd.api_version,
             "kind": crd.kind,
             "status": ModelClaimStatus {
+                conditions,
+                distribution,
+                resize_history,
                 resources,
                 state,
                 storage,
@@ -235,7 +329,7 @@ impl Ctx {
             },
         }));
         let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME);
-        api.patch_status(name, &pp, &patch).await?;
+        crate::audit::patch_status(&api, name, &pp, &patch).await?;
 
         let patch = Patch::Apply(json!({
             "apiVersion": &crd.api_version,
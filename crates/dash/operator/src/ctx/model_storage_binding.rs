@@ -1,11 +1,14 @@
 use std::{sync::Arc, time::Duration};
 
 use anyhow::Result;
-use ark_core_k8s::manager::{Manager, TryDefault};
+use ark_core_k8s::manager::{Manager, Notifier, TryDefault};
 use async_trait::async_trait;
 use chrono::Utc;
-use dash_api::model_storage_binding::{
-    ModelStorageBindingCrd, ModelStorageBindingState, ModelStorageBindingStatus,
+use dash_api::{
+    condition::{with_condition, TYPE_READY},
+    model_storage_binding::{
+        ModelStorageBindingCrd, ModelStorageBindingState, ModelStorageBindingStatus,
+    },
 };
 use dash_provider::storage::KubernetesStorageClient;
 use kube::{
@@ -47,6 +50,12 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
     const FALLBACK: Duration = Duration::from_secs(10); // 10 seconds
     const FINALIZER_NAME: &'static str =
         <Self as ::ark_core_k8s::manager::Ctx>::Data::FINALIZER_NAME;
+    const DEPENDENCIES: &'static [&'static str] = &["Model", "ModelStorage"];
+    const LEADER_ELECTION: bool = true;
+
+    async fn init_notifier() -> Result<Option<Arc<dyn Notifier>>> {
+        crate::notifier::init_notifier().await
+    }
 
     #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any(), namespace = data.namespace()), err(Display))]
     async fn reconcile(
@@ -58,6 +67,17 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
     {
         let name = data.name_any();
         let namespace = data.namespace().unwrap();
+        let conditions = data
+            .status
+            .as_ref()
+            .map(|status| status.conditions.as_slice())
+            .unwrap_or_default();
+
+        if let Some(action) =
+            super::pause_for_maintenance::<Self>(&manager.kube, &data, Some(conditions)).await?
+        {
+            return Ok(action);
+        }
 
         if data.metadata.deletion_timestamp.is_some()
             && data
@@ -72,12 +92,23 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
                 &manager.kube,
                 &name,
                 UpdateContext {
+                    conditions: with_condition(
+                        status.map(|status| status.conditions.as_slice()),
+                        TYPE_READY,
+                        false,
+                        "Deleting",
+                        "the binding is being deleted",
+                        data.metadata.generation,
+                    ),
                     deletion_policy: status
                         .map(|status| status.deletion_policy)
                         .unwrap_or(data.spec.deletion_policy),
                     model: status.and_then(|status| status.model.clone()),
+                    model_generation: status.and_then(|status| status.model_generation),
                     model_name: status.and_then(|status| status.model_name.clone()),
+                    migration: status.and_then(|status| status.migration.clone()),
                     owner_references: None,
+                    replication: status.and_then(|status| status.replication.clone()),
                     resources: status
                         .map(|status: &ModelStorageBindingStatus| status.resources.clone())
                         .unwrap_or_default(),
@@ -87,14 +118,19 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
                         .cloned(),
                     storage_source_binding_name: status
                         .and_then(|status| status.storage_source_binding_name.clone()),
+                    storage_source_generation: status
+                        .and_then(|status| status.storage_source_generation),
                     storage_source_name: status
                         .and_then(|status| status.storage_source_name.clone()),
                     storage_source_uid: status.and_then(|status| status.storage_source_uid.clone()),
                     storage_sync_policy: status.and_then(|status| status.storage_sync_policy),
                     storage_target: status.and_then(|status| status.storage_target.clone()),
+                    storage_target_generation: status
+                        .and_then(|status| status.storage_target_generation),
                     storage_target_name: status
                         .and_then(|status| status.storage_target_name.clone()),
                     storage_target_uid: status.and_then(|status| status.storage_target_uid.clone()),
+                    sync: status.and_then(|status| status.sync.clone()),
                 },
             )
             .await;
@@ -134,13 +170,25 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
             ModelStorageBindingState::Pending => {
                 match validator.validate_model_storage_binding(&data).await {
                     Ok(ctx) => {
-                        Self::update_state_or_requeue(&namespace, &manager.kube, &name, ctx).await
+                        let action =
+                            Self::update_state_or_requeue(&namespace, &manager.kube, &name, ctx)
+                                .await;
+                        manager.notify(&data).await;
+                        action
                     }
                     Err(e) => {
                         warn!("failed to validate model storage binding: {name:?}: {e}");
-                        Ok(Action::requeue(
-                            <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
-                        ))
+                        super::report_not_ready::<Self>(
+                            &manager.kube,
+                            &data,
+                            data.status
+                                .as_ref()
+                                .map(|status| status.conditions.as_slice()),
+                            ModelStorageBindingState::Pending,
+                            "ValidationFailed",
+                            &e,
+                        )
+                        .await
                     }
                 }
             }
@@ -149,7 +197,11 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
                     Ok(Some(ctx)) => {
                         Self::update_state_or_requeue(&namespace, &manager.kube, &name, ctx).await
                     }
-                    Ok(None) => Ok(Action::await_change()),
+                    // NOTE: the referenced model and model storages are not watched,
+                    // so poll them to detect the drift unless they are notified
+                    Ok(None) => Ok(Action::requeue(
+                        <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+                    )),
                     Err(e) => {
                         warn!("failed to update model storage binding: {name:?}: {e}");
                         Ok(Action::requeue(
@@ -158,7 +210,29 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
                     }
                 }
             }
-            ModelStorageBindingState::Deleting => match validator.delete(&data.spec).await {
+            ModelStorageBindingState::Migrating => {
+                match validator
+                    .migrate(&data, data.status.as_ref().unwrap())
+                    .await
+                {
+                    Ok(Some(ctx)) => {
+                        Self::update_state_or_requeue(&namespace, &manager.kube, &name, ctx).await
+                    }
+                    Ok(None) => Ok(Action::requeue(
+                        <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+                    )),
+                    Err(e) => {
+                        warn!("failed to migrate model storage binding: {name:?}: {e}");
+                        Ok(Action::requeue(
+                            <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+                        ))
+                    }
+                }
+            }
+            ModelStorageBindingState::Deleting => match validator
+                .delete(&data.spec, data.status.as_ref())
+                .await
+            {
                 Ok(()) => {
                     <Self as ::ark_core_k8s::manager::Ctx>::remove_finalizer_or_requeue_namespaced(
                         manager.kube.clone(),
@@ -186,9 +260,10 @@ impl Ctx {
         name: &str,
         ctx: UpdateContext,
     ) -> Result<Action, Error> {
+        let state = ctx.state;
         match Self::update_state(namespace, kube, name, ctx).await {
             Ok(()) => {
-                info!("model storage binding is ready: {namespace}/{name}");
+                info!("model storage binding is {state}: {namespace}/{name}");
                 Ok(Action::requeue(
                     <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
                 ))
@@ -208,20 +283,27 @@ impl Ctx {
         kube: &Client,
         name: &str,
         UpdateContext {
+            conditions,
             deletion_policy,
             model,
+            model_generation,
             model_name,
+            migration,
             owner_references,
+            replication,
             resources,
             state,
             storage_source,
             storage_source_binding_name,
+            storage_source_generation,
             storage_source_name,
             storage_source_uid,
             storage_sync_policy,
             storage_target,
+            storage_target_generation,
             storage_target_name,
             storage_target_uid,
+            sync,
         }: UpdateContext,
     ) -> Result<()> {
         let api = Api::<<Self as ::ark_core_k8s::manager::Ctx>::Data>::namespaced(
@@ -236,23 +318,30 @@ impl Ctx {
                 "kind": crd.kind,
                 "status": ModelStorageBindingStatus {
                     state,
+                    conditions,
                     deletion_policy,
                     model,
+                    model_generation,
                     model_name,
+                    migration,
+                    replication,
                     resources,
                     storage_source,
                     storage_source_binding_name,
+                    storage_source_generation,
                     storage_source_name,
                     storage_source_uid,
                     storage_sync_policy,
                     storage_target,
+                    storage_target_generation,
                     storage_target_name,
                     storage_target_uid,
+                    sync,
                     last_updated: Utc::now(),
                 },
             }));
             let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME);
-            api.patch_status(name, &pp, &patch).await?;
+            crate::audit::patch_status(&api, name, &pp, &patch).await?;
         }
 
         if let Some(owner_references) = owner_references {
@@ -0,0 +1,99 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use ark_core_k8s::manager::{Manager, TryDefault};
+use async_trait::async_trait;
+use dash_api::{
+    orphaned_artifact::OrphanedArtifactCrd,
+    storage::{ModelStorageCrd, ModelStorageState},
+};
+use dash_provider::storage::KubernetesStorageClient;
+use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
+use kube::{runtime::controller::Action, CustomResourceExt, Error, ResourceExt};
+use tracing::{instrument, warn, Level};
+
+use crate::{
+    consts::infer_prometheus_url,
+    validator::{orphaned_artifact::OrphanedArtifactValidator, storage::ModelStorageValidator},
+};
+
+pub struct Ctx {
+    prometheus_url: String,
+}
+
+#[async_trait]
+impl TryDefault for Ctx {
+    async fn try_default() -> Result<Self> {
+        Ok(Self {
+            prometheus_url: infer_prometheus_url(),
+        })
+    }
+}
+
+#[async_trait]
+impl ::ark_core_k8s::manager::Ctx for Ctx {
+    type Data = ModelStorageCrd;
+
+    const NAME: &'static str = crate::consts::NAME;
+    const NAMESPACE: &'static str = ::dash_api::consts::NAMESPACE;
+    const FALLBACK: Duration = Duration::from_secs(10 * 60); // 10 minutes
+    const FINALIZER_NAME: &'static str =
+        <Self as ::ark_core_k8s::manager::Ctx>::Data::FINALIZER_NAME;
+    const LEADER_ELECTION: bool = true;
+
+    fn get_subcrds() -> Vec<CustomResourceDefinition> {
+        vec![OrphanedArtifactCrd::crd()]
+    }
+
+    #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any(), namespace = data.namespace()), err(Display))]
+    async fn reconcile(
+        manager: Arc<Manager<Self>>,
+        data: Arc<<Self as ::ark_core_k8s::manager::Ctx>::Data>,
+    ) -> Result<Action, Error>
+    where
+        Self: Sized,
+    {
+        let name = data.name_any();
+        let namespace = data.namespace().unwrap();
+
+        if let Some(action) =
+            super::pause_for_maintenance::<Self>(&manager.kube, &data, None).await?
+        {
+            return Ok(action);
+        }
+
+        // NOTE: the model storages are managed by the model storage controller
+        if data.metadata.deletion_timestamp.is_some()
+            || data
+                .status
+                .as_ref()
+                .map(|status| status.state != ModelStorageState::Ready)
+                .unwrap_or(true)
+        {
+            return Ok(Action::requeue(
+                <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+            ));
+        }
+
+        let validator = OrphanedArtifactValidator {
+            model_storage: ModelStorageValidator {
+                kubernetes_storage: KubernetesStorageClient {
+                    namespace: &namespace,
+                    kube: &manager.kube,
+                },
+                prometheus_url: &manager.ctx.prometheus_url,
+            },
+        };
+
+        // NOTE: the artifacts in the model storages are not watched, so poll them
+        if let Err(e) = validator
+            .collect(<Self as ::ark_core_k8s::manager::Ctx>::NAME, &data)
+            .await
+        {
+            warn!("failed to collect orphaned artifacts ({namespace}/{name}): {e}");
+        }
+        Ok(Action::requeue(
+            <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+        ))
+    }
+}
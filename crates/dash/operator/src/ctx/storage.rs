@@ -1,17 +1,24 @@
 use std::{sync::Arc, time::Duration};
 
-use anyhow::Result;
-use ark_core_k8s::manager::{Manager, TryDefault};
+use anyhow::{anyhow, Result};
+use ark_core_k8s::manager::{Manager, Notifier, TryDefault};
 use async_trait::async_trait;
 use chrono::Utc;
-use dash_api::storage::{
-    ModelStorageCrd, ModelStorageKindSpec, ModelStorageState, ModelStorageStatus,
+use dash_api::{
+    condition::{with_condition, Condition, TYPE_READY},
+    storage::{
+        ModelStorageBenchmarkStatus, ModelStorageCrd, ModelStorageKindSpec, ModelStorageState,
+        ModelStorageStatus, ModelStorageUsage,
+    },
 };
 use dash_provider::storage::KubernetesStorageClient;
 use kube::{
     api::{Patch, PatchParams},
-    runtime::controller::Action,
-    Api, Client, CustomResourceExt, Error, ResourceExt,
+    runtime::{
+        controller::Action,
+        events::{Event, EventType, Recorder, Reporter},
+    },
+    Api, Client, CustomResourceExt, Error, Resource, ResourceExt,
 };
 use serde_json::json;
 use tracing::{info, instrument, warn, Level};
@@ -40,6 +47,11 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
     const FALLBACK: Duration = Duration::from_secs(30); // 30 seconds
     const FINALIZER_NAME: &'static str =
         <Self as ::ark_core_k8s::manager::Ctx>::Data::FINALIZER_NAME;
+    const LEADER_ELECTION: bool = true;
+
+    async fn init_notifier() -> Result<Option<Arc<dyn Notifier>>> {
+        crate::notifier::init_notifier().await
+    }
 
     #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any(), namespace = data.namespace()), err(Display))]
     async fn reconcile(
@@ -51,6 +63,21 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
     {
         let name = data.name_any();
         let namespace = data.namespace().unwrap();
+        let conditions = data
+            .status
+            .as_ref()
+            .map(|status| status.conditions.as_slice());
+        let generation = data.metadata.generation;
+
+        if let Some(action) = super::pause_for_maintenance::<Self>(
+            &manager.kube,
+            &data,
+            Some(conditions.unwrap_or_default()),
+        )
+        .await?
+        {
+            return Ok(action);
+        }
 
         if data.metadata.deletion_timestamp.is_some()
             && data
@@ -67,6 +94,14 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
                 status.and_then(|status| status.kind.clone()),
                 ModelStorageState::Deleting,
                 None,
+                with_condition(
+                    conditions,
+                    TYPE_READY,
+                    false,
+                    "Deleting",
+                    "the model storage is being deleted",
+                    generation,
+                ),
             )
             .await;
         } else if !data
@@ -102,28 +137,66 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
                     .await
                 {
                     Ok(total_quota) => {
-                        Self::update_state_or_requeue(
+                        let action = Self::update_state_or_requeue(
                             &namespace,
                             &manager.kube,
                             &name,
                             Some(data.spec.kind.clone()),
                             ModelStorageState::Ready,
                             total_quota,
+                            with_condition(
+                                conditions,
+                                TYPE_READY,
+                                true,
+                                "Validated",
+                                "the model storage is reachable",
+                                generation,
+                            ),
                         )
-                        .await
+                        .await;
+                        manager.notify(&data).await;
+                        action
                     }
                     Err(e) => {
                         warn!("failed to validate model storage: {name:?}: {e}");
+                        super::report_not_ready::<Self>(
+                            &manager.kube,
+                            &data,
+                            conditions,
+                            ModelStorageState::Pending,
+                            "ValidationFailed",
+                            &e,
+                        )
+                        .await
+                    }
+                }
+            }
+            // NOTE: the usage of the bound models are not watched, so poll them
+            ModelStorageState::Ready => {
+                match validator.benchmark(&data).await {
+                    Ok(Some(benchmark)) => {
+                        if let Err(e) =
+                            Self::update_benchmark(&manager.kube, &data, benchmark).await
+                        {
+                            warn!("failed to update model storage benchmark ({namespace}/{name}): {e}");
+                        }
+                    }
+                    Ok(None) => (),
+                    Err(e) => {
+                        warn!("failed to benchmark model storage ({namespace}/{name}): {e}");
+                    }
+                }
+
+                match validator.get_usage(&data).await {
+                    Ok(usage) => Self::update_usage_or_requeue(&manager.kube, &data, usage).await,
+                    Err(e) => {
+                        warn!("failed to get model storage usage ({namespace}/{name}): {e}");
                         Ok(Action::requeue(
                             <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
                         ))
                     }
                 }
             }
-            ModelStorageState::Ready => {
-                // TODO: implement to finding changes
-                Ok(Action::await_change())
-            }
             ModelStorageState::Deleting => match validator.delete(&data).await {
                 Ok(()) => {
                     <Self as ::ark_core_k8s::manager::Ctx>::remove_finalizer_or_requeue_namespaced(
@@ -153,8 +226,10 @@ impl Ctx {
         kind: Option<ModelStorageKindSpec>,
         state: ModelStorageState,
         total_quota: Option<u128>,
+        conditions: Vec<Condition>,
     ) -> Result<Action, Error> {
-        match Self::update_state(namespace, kube, name, kind, state, total_quota).await {
+        match Self::update_state(namespace, kube, name, kind, state, total_quota, conditions).await
+        {
             Ok(()) => {
                 info!("model storage is ready: {namespace}/{name}");
                 Ok(Action::requeue(
@@ -170,7 +245,7 @@ impl Ctx {
         }
     }
 
-    #[instrument(level = Level::INFO, skip(kube, kind), err(Display))]
+    #[instrument(level = Level::INFO, skip(kube, kind, conditions), err(Display))]
     async fn update_state(
         namespace: &str,
         kube: &Client,
@@ -178,6 +253,7 @@ impl Ctx {
         kind: Option<ModelStorageKindSpec>,
         state: ModelStorageState,
         total_quota: Option<u128>,
+        conditions: Vec<Condition>,
     ) -> Result<()> {
         let api = Api::<<Self as ::ark_core_k8s::manager::Ctx>::Data>::namespaced(
             kube.clone(),
@@ -190,13 +266,164 @@ impl Ctx {
             "kind": crd.kind,
             "status": ModelStorageStatus {
                 state,
+                benchmark: None,
+                conditions,
                 kind,
                 last_updated: Utc::now(),
                 total_quota,
+                usage: None,
+            },
+        }));
+        let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME);
+        crate::audit::patch_status(&api, name, &pp, &patch).await?;
+        Ok(())
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn update_usage_or_requeue(
+        kube: &Client,
+        data: &ModelStorageCrd,
+        usage: ModelStorageUsage,
+    ) -> Result<Action, Error> {
+        let name = data.name_any();
+        let namespace = data.namespace().unwrap();
+
+        if let Err(e) = Self::update_usage(kube, data, usage).await {
+            warn!("failed to update model storage usage ({namespace}/{name}): {e}");
+        }
+        Ok(Action::requeue(
+            <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+        ))
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn update_usage(
+        kube: &Client,
+        data: &ModelStorageCrd,
+        usage: ModelStorageUsage,
+    ) -> Result<()> {
+        let status = match data.status.as_ref() {
+            Some(status) if status.usage.as_ref() != Some(&usage) => status,
+            Some(_) | None => return Ok(()),
+        };
+
+        let name = data.name_any();
+        let namespace = data.namespace().unwrap();
+
+        let conditions = match status.total_quota {
+            Some(total_quota) => {
+                let last_ratio = status
+                    .usage
+                    .as_ref()
+                    .and_then(|usage| usage.ratio(total_quota))
+                    .unwrap_or_default();
+                let ratio = usage.ratio(total_quota).unwrap_or_default();
+
+                for threshold in QUOTA_THRESHOLDS {
+                    if last_ratio < threshold && threshold <= ratio {
+                        let note = format!(
+                            "the bound models have reached {percent}% of the total quota ({used} of {total_quota} bytes)",
+                            percent = (threshold * 100.0).round(),
+                            used = usage.allocated.max(usage.used),
+                        );
+                        Self::publish_event(kube, data, "QuotaThresholdExceeded", note).await?;
+                    }
+                }
+
+                let pressure = ratio >= QUOTA_THRESHOLDS[0];
+                with_condition(
+                    Some(status.conditions.as_slice()),
+                    ModelStorageCrd::CONDITION_QUOTA_PRESSURE,
+                    pressure,
+                    if pressure {
+                        "QuotaThresholdExceeded"
+                    } else {
+                        "QuotaAvailable"
+                    },
+                    format!(
+                        "{percent:.1}% of the total quota is in use",
+                        percent = ratio * 100.0,
+                    ),
+                    data.metadata.generation,
+                )
+            }
+            None => status.conditions.clone(),
+        };
+
+        let api = Api::<<Self as ::ark_core_k8s::manager::Ctx>::Data>::namespaced(
+            kube.clone(),
+            &namespace,
+        );
+        let crd = <Self as ::ark_core_k8s::manager::Ctx>::Data::api_resource();
+
+        let patch = Patch::Merge(json!({
+            "apiVersion": crd.api_version,
+            "kind": crd.kind,
+            "status": {
+                "conditions": conditions,
+                "lastUpdated": Utc::now(),
+                "usage": usage,
             },
         }));
         let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME);
-        api.patch_status(name, &pp, &patch).await?;
+        crate::audit::patch_status(&api, &name, &pp, &patch).await?;
         Ok(())
     }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn update_benchmark(
+        kube: &Client,
+        data: &ModelStorageCrd,
+        benchmark: ModelStorageBenchmarkStatus,
+    ) -> Result<()> {
+        let name = data.name_any();
+        let namespace = data.namespace().unwrap();
+
+        let api = Api::<<Self as ::ark_core_k8s::manager::Ctx>::Data>::namespaced(
+            kube.clone(),
+            &namespace,
+        );
+        let crd = <Self as ::ark_core_k8s::manager::Ctx>::Data::api_resource();
+
+        let patch = Patch::Merge(json!({
+            "apiVersion": crd.api_version,
+            "kind": crd.kind,
+            "status": {
+                "benchmark": benchmark,
+                "lastUpdated": Utc::now(),
+            },
+        }));
+        let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME);
+        crate::audit::patch_status(&api, &name, &pp, &patch).await?;
+        Ok(())
+    }
+
+    #[instrument(level = Level::INFO, skip(kube, data), err(Display))]
+    async fn publish_event(
+        kube: &Client,
+        data: &ModelStorageCrd,
+        reason: &str,
+        note: String,
+    ) -> Result<()> {
+        let reporter = Reporter {
+            controller: <Self as ::ark_core_k8s::manager::Ctx>::NAME.into(),
+            instance: None,
+        };
+        let recorder = Recorder::new(kube.clone(), reporter);
+
+        let event = Event {
+            type_: EventType::Warning,
+            reason: reason.into(),
+            note: Some(note),
+            action: "ReportUsage".into(),
+            secondary: None,
+        };
+        recorder
+            .publish(&event, &data.object_ref(&()))
+            .await
+            .map_err(|error| anyhow!("failed to publish model storage event: {error}"))
+    }
 }
+
+/// The ratios of the total quota to report when crossed, in ascending order.
+const QUOTA_THRESHOLDS: [f64; 2] = [0.8, 0.95];
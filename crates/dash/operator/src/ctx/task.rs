@@ -6,7 +6,7 @@ use async_trait::async_trait;
 use chrono::Utc;
 use dash_api::{
     model::ModelFieldKindNativeSpec,
-    task::{TaskCrd, TaskSpec, TaskState, TaskStatus},
+    task::{TaskCrd, TaskScheduleStatus, TaskSpec, TaskState, TaskStatus},
 };
 use kube::{
     api::{Patch, PatchParams},
@@ -28,6 +28,7 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
     const NAME: &'static str = crate::consts::NAME;
     const NAMESPACE: &'static str = ::dash_api::consts::NAMESPACE;
     const FALLBACK: Duration = Duration::from_secs(30); // 30 seconds
+    const LEADER_ELECTION: bool = true;
 
     #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any(), namespace = data.namespace()), err(Display))]
     async fn reconcile(
@@ -40,6 +41,12 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
         let name = data.name_any();
         let namespace = data.namespace().unwrap();
 
+        if let Some(action) =
+            super::pause_for_maintenance::<Self>(&manager.kube, &data, None).await?
+        {
+            return Ok(action);
+        }
+
         match data
             .status
             .as_ref()
@@ -51,7 +58,7 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
                     namespace: &namespace,
                     kube: &manager.kube,
                 };
-                match validator.validate_task(data.spec.clone()).await {
+                match validator.validate_task(&name, data.spec.clone()).await {
                     Ok(spec) => {
                         Self::update_spec_or_requeue(&namespace, &manager.kube, &name, spec).await
                     }
@@ -65,7 +72,26 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
             }
             TaskState::Ready => {
                 // TODO: implement to finding changes
-                Ok(Action::await_change())
+                let validator = TaskValidator {
+                    namespace: &namespace,
+                    kube: &manager.kube,
+                };
+                match validator
+                    .schedule(<Self as ::ark_core_k8s::manager::Ctx>::NAME, &data)
+                    .await
+                {
+                    Ok(Some(schedule)) => {
+                        Self::update_schedule_or_requeue(&namespace, &manager.kube, &data, schedule)
+                            .await
+                    }
+                    Ok(None) => Ok(Action::await_change()),
+                    Err(e) => {
+                        warn!("failed to schedule task: {name:?}: {e}");
+                        Ok(Action::requeue(
+                            <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+                        ))
+                    }
+                }
             }
         }
     }
@@ -95,6 +121,66 @@ impl Ctx {
         }
     }
 
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn update_schedule_or_requeue(
+        namespace: &str,
+        kube: &Client,
+        data: &TaskCrd,
+        schedule: TaskScheduleStatus,
+    ) -> Result<Action, Error> {
+        let name = data.name_any();
+        let next = schedule.next_schedule_time;
+
+        if data
+            .status
+            .as_ref()
+            .and_then(|status| status.schedule.as_ref())
+            != Some(&schedule)
+        {
+            if let Err(e) = Self::update_schedule(namespace, kube, &name, schedule).await {
+                warn!("failed to update task schedule ({namespace}/{name}): {e}");
+                return Ok(Action::requeue(
+                    <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+                ));
+            }
+        }
+
+        match next {
+            Some(next) => Ok(Action::requeue(
+                (next - Utc::now())
+                    .to_std()
+                    .unwrap_or_default()
+                    .max(Duration::from_secs(1)),
+            )),
+            None => Ok(Action::await_change()),
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip(kube, schedule), err(Display))]
+    async fn update_schedule(
+        namespace: &str,
+        kube: &Client,
+        name: &str,
+        schedule: TaskScheduleStatus,
+    ) -> Result<()> {
+        let api = Api::<<Self as ::ark_core_k8s::manager::Ctx>::Data>::namespaced(
+            kube.clone(),
+            namespace,
+        );
+        let crd = <Self as ::ark_core_k8s::manager::Ctx>::Data::api_resource();
+
+        let patch = Patch::Merge(json!({
+            "apiVersion": crd.api_version,
+            "kind": crd.kind,
+            "status": {
+                "schedule": schedule,
+            },
+        }));
+        let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME);
+        crate::audit::patch_status(&api, name, &pp, &patch).await?;
+        Ok(())
+    }
+
     #[instrument(level = Level::INFO, skip(kube, spec), err(Display))]
     async fn update_spec(
         namespace: &str,
@@ -114,11 +200,12 @@ impl Ctx {
             "status": TaskStatus {
                 state: TaskState::Ready,
                 spec: Some(spec),
+                schedule: None,
                 last_updated: Utc::now(),
             },
         }));
         let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME);
-        api.patch_status(name, &pp, &patch).await?;
+        crate::audit::patch_status(&api, name, &pp, &patch).await?;
         Ok(())
     }
 }
@@ -0,0 +1,22 @@
+#![recursion_limit = "256"]
+
+mod audit;
+pub mod ctx;
+mod notification;
+mod notifier;
+mod optimizer;
+pub mod validator;
+
+pub mod consts {
+    use ark_core::env::infer_string;
+
+    pub const NAME: &str = "dash-operator";
+
+    const ENV_PROMETHEUS_URL: &str = "PROMETHEUS_URL";
+
+    pub fn infer_prometheus_url() -> String {
+        infer_string(ENV_PROMETHEUS_URL).unwrap_or_else(|_| {
+            "http://kube-prometheus-stack-prometheus.monitoring.svc:9090".into()
+        })
+    }
+}
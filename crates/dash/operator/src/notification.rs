@@ -0,0 +1,419 @@
+use std::{
+    collections::BTreeMap,
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Utc;
+use dash_api::{
+    condition::Condition,
+    notification::{
+        NotificationChannelKindSpec, NotificationChannelSpec, NotificationEmailSpec,
+        NotificationEvent, NotificationPolicyCrd, NotificationPolicySpec,
+        NotificationRateLimitSpec,
+    },
+};
+use k8s_openapi::api::core::v1::Secret;
+use kube::{api::ListParams, Api, Client, Resource, ResourceExt};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tera::{Context, Tera};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader},
+    net::TcpStream,
+    spawn,
+};
+use tracing::{instrument, warn, Level};
+
+/// Notify the transitions of the resource to the channels of the matched policies.
+///
+/// The messages are sent in background as best-effort, so that the controllers are not blocked.
+pub(crate) fn notify<K>(kube: &Client, old: Option<&K>, new: &K)
+where
+    K: Serialize + Resource<DynamicType = ()>,
+{
+    let Some(namespace) = new.namespace() else {
+        return;
+    };
+    let events = collect_events(old, new);
+    if events.is_empty() {
+        return;
+    }
+
+    let kube = kube.clone();
+    spawn(async move {
+        if let Err(error) = dispatch(kube, namespace, events).await {
+            warn!("failed to send notifications: {error}");
+        }
+    });
+}
+
+/// Compare the states and the conditions of the resource.
+fn collect_events<K>(old: Option<&K>, new: &K) -> Vec<NotificationEvent>
+where
+    K: Serialize + Resource<DynamicType = ()>,
+{
+    let status = |data: Option<&K>| {
+        data.and_then(|data| ::serde_json::to_value(data).ok())
+            .and_then(|mut data| data.get_mut("status").map(Value::take))
+            .unwrap_or_default()
+    };
+    let state = |status: &Value| {
+        status
+            .get("state")
+            .and_then(Value::as_str)
+            .map(ToString::to_string)
+    };
+    let conditions = |status: &Value| -> Vec<Condition> {
+        status
+            .get("conditions")
+            .and_then(|conditions| ::serde_json::from_value(conditions.clone()).ok())
+            .unwrap_or_default()
+    };
+
+    let old_status = status(old);
+    let new_status = status(Some(new));
+    let event = NotificationEvent {
+        timestamp: Utc::now(),
+        kind: K::kind(&()).into_owned(),
+        namespace: new.namespace(),
+        name: new.name_any(),
+        state: None,
+        last_state: None,
+        condition: None,
+    };
+
+    let mut events = Vec::default();
+    let (last_state, new_state) = (state(&old_status), state(&new_status));
+    if new_state.is_some() && last_state != new_state {
+        events.push(NotificationEvent {
+            state: new_state,
+            last_state,
+            ..event.clone()
+        });
+    }
+
+    let last_conditions = conditions(&old_status);
+    for condition in conditions(&new_status) {
+        let is_changed = !last_conditions.iter().any(|last| {
+            last.type_ == condition.type_
+                && last.status == condition.status
+                && last.reason == condition.reason
+        });
+        if is_changed {
+            events.push(NotificationEvent {
+                condition: Some(condition),
+                ..event.clone()
+            });
+        }
+    }
+    events
+}
+
+#[instrument(level = Level::INFO, skip(kube, events), err(Display))]
+async fn dispatch(kube: Client, namespace: String, events: Vec<NotificationEvent>) -> Result<()> {
+    let api = Api::<NotificationPolicyCrd>::namespaced(kube.clone(), &namespace);
+    let policies = api
+        .list(&ListParams::default())
+        .await
+        .map_err(|error| anyhow!("failed to list notification policies: {error}"))?;
+
+    for policy in policies.items {
+        let policy_name = policy.name_any();
+        let spec = &policy.spec;
+        for event in events.iter().filter(|event| spec.matches(event)) {
+            let message = match render(spec, event) {
+                Ok(message) => message,
+                Err(error) => {
+                    warn!("failed to render notification ({namespace}/{policy_name}): {error}");
+                    continue;
+                }
+            };
+
+            for channel in &spec.channels {
+                let key = format!("{namespace}/{policy_name}/{name}", name = channel.name);
+                let Some(suppressed) = RATE_LIMITER.acquire(key, spec.rate_limit) else {
+                    continue;
+                };
+                let message = if suppressed > 0 {
+                    format!("{message}\n({suppressed} notifications were suppressed)")
+                } else {
+                    message.clone()
+                };
+
+                if let Err(error) = send(&kube, &namespace, channel, event, &message).await {
+                    warn!(
+                        "failed to send notification ({namespace}/{policy_name}/{name}): {error}",
+                        name = channel.name,
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn render(spec: &NotificationPolicySpec, event: &NotificationEvent) -> Result<String> {
+    let template = spec
+        .template
+        .as_deref()
+        .unwrap_or(NotificationPolicySpec::DEFAULT_TEMPLATE);
+
+    let mut context = Context::new();
+    context.insert("event", event);
+    Tera::one_off(template, &context, false)
+        .map_err(|error| anyhow!("failed to render template: {error}"))
+}
+
+async fn send(
+    kube: &Client,
+    namespace: &str,
+    channel: &NotificationChannelSpec,
+    event: &NotificationEvent,
+    message: &str,
+) -> Result<()> {
+    match &channel.kind {
+        NotificationChannelKindSpec::Email(spec) => {
+            let credentials = match &spec.secret_name {
+                Some(name) => Some((
+                    get_secret(kube, namespace, name, "username").await?,
+                    get_secret(kube, namespace, name, "password").await?,
+                )),
+                None => None,
+            };
+            send_email(spec, credentials, message).await
+        }
+        NotificationChannelKindSpec::Slack(spec) => {
+            let url = get_secret(kube, namespace, &spec.secret_name, &spec.secret_key).await?;
+            post(&url, &Default::default(), &json!({ "text": message })).await
+        }
+        NotificationChannelKindSpec::Webhook(spec) => {
+            let body = json!({
+                "event": event,
+                "message": message,
+            });
+            post(&spec.url, &spec.headers, &body).await
+        }
+    }
+}
+
+async fn get_secret(kube: &Client, namespace: &str, name: &str, key: &str) -> Result<String> {
+    let api = Api::<Secret>::namespaced(kube.clone(), namespace);
+    let secret = api
+        .get(name)
+        .await
+        .map_err(|error| anyhow!("failed to get secret {name}: {error}"))?;
+
+    secret
+        .data
+        .and_then(|mut data| data.remove(key))
+        .ok_or_else(|| anyhow!("no such secret key: {name}/{key}"))
+        .and_then(|value| {
+            String::from_utf8(value.0)
+                .map_err(|error| anyhow!("failed to parse secret {name}/{key}: {error}"))
+        })
+}
+
+async fn post(url: &str, headers: &BTreeMap<String, String>, body: &Value) -> Result<()> {
+    static CLIENT: LazyLock<::reqwest::Client> = LazyLock::new(|| {
+        ::reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default()
+    });
+
+    let mut request = CLIENT.post(url).json(body);
+    for (key, value) in headers {
+        request = request.header(key, value);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|error| anyhow!("failed to post notification: {error}"))?;
+    let status = response.status();
+    if status.is_success() {
+        Ok(())
+    } else {
+        let message = response.text().await.unwrap_or_default();
+        bail!("failed to post notification: [{status}] {message}")
+    }
+}
+
+/// Send the email over a plain SMTP relay, e.g. the one inside of the cluster.
+async fn send_email(
+    spec: &NotificationEmailSpec,
+    credentials: Option<(String, String)>,
+    message: &str,
+) -> Result<()> {
+    let stream = TcpStream::connect((spec.host.as_str(), spec.port))
+        .await
+        .map_err(|error| anyhow!("failed to connect to SMTP relay: {error}"))?;
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+
+    expect_reply(&mut reader, &[220]).await?;
+    let hostname = crate::consts::NAME;
+    smtp_command(
+        &mut reader,
+        &mut writer,
+        &format!("EHLO {hostname}"),
+        &[250],
+    )
+    .await?;
+    if let Some((username, password)) = credentials {
+        let token = STANDARD.encode(format!("\0{username}\0{password}"));
+        smtp_command(
+            &mut reader,
+            &mut writer,
+            &format!("AUTH PLAIN {token}"),
+            &[235],
+        )
+        .await?;
+    }
+
+    let from = &spec.from;
+    smtp_command(
+        &mut reader,
+        &mut writer,
+        &format!("MAIL FROM:<{from}>"),
+        &[250],
+    )
+    .await?;
+    for to in &spec.to {
+        smtp_command(
+            &mut reader,
+            &mut writer,
+            &format!("RCPT TO:<{to}>"),
+            &[250, 251],
+        )
+        .await?;
+    }
+    smtp_command(&mut reader, &mut writer, "DATA", &[354]).await?;
+
+    let subject = message.lines().next().unwrap_or_default();
+    let mut data = format!(
+        "From: <{from}>\r\nTo: {to}\r\nSubject: {subject}\r\nDate: {date}\r\n\
+        Content-Type: text/plain; charset=utf-8\r\n\r\n",
+        to = spec
+            .to
+            .iter()
+            .map(|to| format!("<{to}>"))
+            .collect::<Vec<_>>()
+            .join(", "),
+        date = Utc::now().to_rfc2822(),
+    );
+    for line in message.lines() {
+        // NOTE: escape the lines starting with a dot (RFC 5321 4.5.2)
+        if line.starts_with('.') {
+            data.push('.');
+        }
+        data.push_str(line);
+        data.push_str("\r\n");
+    }
+    data.push_str(".\r\n");
+    write_all(&mut writer, &data).await?;
+    expect_reply(&mut reader, &[250]).await?;
+
+    smtp_command(&mut reader, &mut writer, "QUIT", &[221]).await
+}
+
+async fn smtp_command<R, W>(
+    reader: &mut BufReader<R>,
+    writer: &mut W,
+    command: &str,
+    codes: &[u16],
+) -> Result<()>
+where
+    R: ::tokio::io::AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    write_all(writer, &format!("{command}\r\n")).await?;
+    expect_reply(reader, codes).await
+}
+
+async fn write_all<W>(writer: &mut W, data: &str) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    writer
+        .write_all(data.as_bytes())
+        .await
+        .map_err(|error| anyhow!("failed to write to SMTP relay: {error}"))
+}
+
+/// Read the reply, which may span multiple lines (e.g. `250-...` and `250 ...`).
+async fn expect_reply<R>(reader: &mut BufReader<R>, codes: &[u16]) -> Result<()>
+where
+    R: ::tokio::io::AsyncRead + Unpin,
+{
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader
+            .read_line(&mut line)
+            .await
+            .map_err(|error| anyhow!("failed to read from SMTP relay: {error}"))?
+            == 0
+        {
+            bail!("SMTP relay has closed the connection");
+        }
+        if line.as_bytes().get(3) != Some(&b'-') {
+            break;
+        }
+    }
+
+    let code: u16 = line
+        .get(..3)
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| anyhow!("failed to parse SMTP reply: {line}"))?;
+    if codes.contains(&code) {
+        Ok(())
+    } else {
+        bail!("unexpected SMTP reply: {line}", line = line.trim_end())
+    }
+}
+
+static RATE_LIMITER: LazyLock<RateLimiter> = LazyLock::new(RateLimiter::default);
+
+/// Limits the messages per channel in fixed windows, to avoid alert storms.
+#[derive(Default)]
+struct RateLimiter {
+    windows: Mutex<BTreeMap<String, RateLimitWindow>>,
+}
+
+struct RateLimitWindow {
+    started_at: Instant,
+    sent: u32,
+    suppressed: u32,
+}
+
+impl RateLimiter {
+    /// Return the number of the suppressed messages since the last one,
+    /// or `None` if the message should be suppressed.
+    fn acquire(&self, key: String, spec: NotificationRateLimitSpec) -> Option<u32> {
+        let mut windows = self.windows.lock().ok()?;
+        let now = Instant::now();
+        let length = Duration::from_secs(spec.window_seconds.into());
+
+        let window = windows.entry(key).or_insert(RateLimitWindow {
+            started_at: now,
+            sent: 0,
+            suppressed: 0,
+        });
+        if now.duration_since(window.started_at) >= length {
+            window.started_at = now;
+            window.sent = 0;
+        }
+
+        if window.sent < spec.max_messages {
+            window.sent += 1;
+            Some(::std::mem::take(&mut window.suppressed))
+        } else {
+            window.suppressed += 1;
+            None
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use ark_core_k8s::manager::{Notifier, ResourceChanged};
+use async_trait::async_trait;
+use dash_pipe_provider::{
+    messengers::{Publisher, Subscriber},
+    Name, PipeClient, PipeMessage, PipePublisher,
+};
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use tokio::sync::OnceCell;
+use tracing::{instrument, warn, Level};
+
+/// Load the notifier shared by all controllers.
+///
+/// If the messenger is not available, the controllers fall back to the requeue polling.
+pub(crate) async fn init_notifier() -> Result<Option<Arc<dyn Notifier>>> {
+    static NOTIFIER: OnceCell<Option<Arc<dyn Notifier>>> = OnceCell::const_new();
+
+    Ok(NOTIFIER
+        .get_or_init(|| async {
+            match PipeClient::try_default().await {
+                Ok(client) => Some(Arc::new(PipeNotifier { client }) as Arc<dyn Notifier>),
+                Err(error) => {
+                    warn!("failed to init notifier; falling back to requeue polling: {error}");
+                    None
+                }
+            }
+        })
+        .await
+        .clone())
+}
+
+/// A notifier over the dash pipe messenger, which publishes the changes per kind.
+struct PipeNotifier {
+    client: PipeClient<ResourceChanged>,
+}
+
+impl PipeNotifier {
+    fn topic(kind: &str) -> Result<Name> {
+        format!("dash.notify.{kind}", kind = kind.to_lowercase()).parse()
+    }
+}
+
+#[async_trait]
+impl Notifier for PipeNotifier {
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    async fn notify(&self, event: &ResourceChanged) -> Result<()> {
+        let topic = Self::topic(&event.kind)?;
+        let publisher = self.client.publish(topic).await?;
+        <PipePublisher as Publisher<PipeMessage<ResourceChanged>, PipeMessage>>::send_one(
+            &publisher,
+            PipeMessage::new(event.clone()),
+        )
+        .await
+    }
+
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    async fn subscribe(&self, kind: &str) -> Result<BoxStream<'static, ResourceChanged>> {
+        let topic = Self::topic(kind)?;
+        let subscriber = self.client.subscribe(topic).await?;
+
+        Ok(stream::unfold(subscriber, |mut subscriber| async move {
+            match subscriber.read_one().await {
+                Ok(Some(message)) => Some((message.value, subscriber)),
+                Ok(None) => None,
+                Err(error) => {
+                    // NOTE: the controllers are still requeued without the notifications
+                    warn!("failed to read notification: {error}");
+                    None
+                }
+            }
+        })
+        .boxed())
+    }
+}
@@ -0,0 +1,74 @@
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use tracing::warn;
+
+/// The circuit breaker of the metrics server queried by the optimizer.
+pub(super) static METRICS: CircuitBreaker = CircuitBreaker::new("metrics");
+
+/// Stop calling an unhealthy dependency for a while,
+/// so that the reconciles are not stalled by the repeated timeouts.
+pub(super) struct CircuitBreaker {
+    name: &'static str,
+    failures: AtomicU32,
+    open_until: Mutex<Option<Instant>>,
+}
+
+impl CircuitBreaker {
+    /// The number of consecutive failures to open the circuit.
+    const THRESHOLD: u32 = 3;
+    /// The time to wait before trying the dependency again.
+    const COOLDOWN: Duration = Duration::from_secs(60);
+
+    const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            failures: AtomicU32::new(0),
+            open_until: Mutex::new(None),
+        }
+    }
+
+    /// Return whether the calls should be skipped.
+    ///
+    /// After the cooldown, a trial call is allowed (half-open);
+    /// a single failure then opens the circuit again.
+    pub(super) fn is_open(&self) -> bool {
+        self.open_until
+            .lock()
+            .ok()
+            .and_then(|open_until| *open_until)
+            .map(|open_until| open_until > Instant::now())
+            .unwrap_or_default()
+    }
+
+    /// Record the result of a call, passing it through.
+    pub(super) fn record<T>(&self, result: Result<T>) -> Result<T> {
+        let mut open_until = match self.open_until.lock() {
+            Ok(open_until) => open_until,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        match &result {
+            Ok(_) => {
+                self.failures.store(0, Ordering::SeqCst);
+                *open_until = None;
+            }
+            Err(_) => {
+                let failures = self.failures.fetch_add(1, Ordering::SeqCst) + 1;
+                if failures >= Self::THRESHOLD {
+                    if open_until.is_none() {
+                        let name = self.name;
+                        warn!("circuit is open after {failures} failures: {name}");
+                    }
+                    *open_until = Some(Instant::now() + Self::COOLDOWN);
+                }
+            }
+        }
+        result
+    }
+}
@@ -0,0 +1,22 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use dash_api::storage::fs::ModelStorageFileSystemSpec;
+use dash_provider_api::data::Capacity;
+use kube::Client;
+use tracing::warn;
+
+#[async_trait]
+impl super::GetCapacity for ModelStorageFileSystemSpec {
+    async fn get_capacity_global<'namespace, 'kube>(
+        &self,
+        _kube: &'kube Client,
+        _namespace: &'namespace str,
+        _storage_name: &str,
+    ) -> Result<Option<Capacity>> {
+        warn!("unsupported storage type for fallback optimizer: FileSystem");
+        Ok(None)
+    }
+}
+
+#[async_trait]
+impl super::GetTraffic for ModelStorageFileSystemSpec {}
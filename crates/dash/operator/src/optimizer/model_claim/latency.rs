@@ -0,0 +1,51 @@
+use anyhow::{anyhow, Result};
+use dash_api::storage::ModelStorageCrd;
+use itertools::Itertools;
+use prometheus_http_query::Client as PrometheusClient;
+use tracing::{instrument, Level};
+
+/// Get the average call latency of the models in the storage, in milliseconds.
+///
+/// The latency is collected from the same metrics of which kubegraph
+/// builds the dash function edges.
+#[instrument(level = Level::INFO, skip_all, err(Display))]
+pub(super) async fn get_latency(
+    prometheus_client: &PrometheusClient,
+    namespace: &str,
+    storage: &ModelStorageCrd,
+) -> Result<Option<f64>> {
+    const METRIC: &str = "dash_metrics_duration_milliseconds";
+    const SPAN_NAME: &str = "call_function";
+    const WINDOW: &str = "5m";
+
+    let models = match storage
+        .status
+        .as_ref()
+        .and_then(|status| status.usage.as_ref())
+        .filter(|usage| !usage.models.is_empty())
+    {
+        Some(usage) => usage
+            .models
+            .keys()
+            .map(|name| name.replace('.', "\\\\."))
+            .join("|"),
+        None => return Ok(None),
+    };
+
+    let selector = format!(
+        r#"span_name="{SPAN_NAME}",k8s_namespace_name="{namespace}",data_model=~"{models}""#,
+    );
+    let query = format!(
+        "sum(rate({METRIC}_sum{{{selector}}}[{WINDOW}])) / sum(rate({METRIC}_count{{{selector}}}[{WINDOW}]))",
+    );
+
+    let (data, _stats) = prometheus_client.query(query).get().await?.into_inner();
+    let vectors = data
+        .into_vector()
+        .map_err(|_| anyhow!("failed to get latency: unexpected result type"))?;
+
+    Ok(vectors
+        .first()
+        .map(|vector| vector.sample().value())
+        .filter(|latency_ms| latency_ms.is_finite()))
+}
@@ -1,13 +1,22 @@
+mod breaker;
 mod db;
+mod fs;
 mod kubernetes;
+mod latency;
 mod object;
+mod strategy;
 
-use anyhow::{bail, Result};
+use std::cmp::Ordering;
+
+use anyhow::{anyhow, bail, Result};
 use async_trait::async_trait;
 use byte_unit::Byte;
 use dash_api::{
     model::ModelCrd,
-    model_claim::ModelClaimBindingPolicy,
+    model_claim::{
+        ModelClaimAutoscaling, ModelClaimBindingPolicy, ModelClaimDistribution,
+        ModelClaimPlacementCandidate, ModelClaimStorageTarget,
+    },
     model_storage_binding::{
         ModelStorageBindingCrd, ModelStorageBindingDeletionPolicy, ModelStorageBindingStorageKind,
         ModelStorageBindingStorageKindOwnedSpec,
@@ -19,11 +28,15 @@ use dash_api::{
 use dash_provider::storage::KubernetesStorageClient;
 use dash_provider_api::data::Capacity;
 use futures::{stream::FuturesUnordered, StreamExt};
-use k8s_openapi::api::core::v1::ResourceRequirements;
+use k8s_openapi::{
+    api::core::v1::ResourceRequirements, apimachinery::pkg::api::resource::Quantity,
+};
 use kube::{Client, ResourceExt};
 use prometheus_http_query::Client as PrometheusClient;
 use tracing::{instrument, warn, Level};
 
+use crate::validator::tenant::TenantValidator;
+
 pub struct ModelClaimOptimizer<'namespace, 'kube> {
     binding_policy: ModelClaimBindingPolicy,
     field_manager: &'kube str,
@@ -50,10 +63,157 @@ impl<'namespace, 'kube> ModelClaimOptimizer<'namespace, 'kube> {
     pub async fn optimize_model_storage_binding(
         &self,
         model: &ModelCrd,
+        default_storage_name: Option<&str>,
         storage: Option<ModelStorageKind>,
         resources: Option<ResourceRequirements>,
         deletion_policy: ModelStorageBindingDeletionPolicy,
-    ) -> Result<Option<ModelStorageBindingCrd>> {
+    ) -> Result<Option<(ModelStorageBindingCrd, Placement)>> {
+        let candidates = self
+            .simulate_model_storage_binding(model, default_storage_name, storage, resources.quota())
+            .await?;
+
+        // NOTE: the scores are not reliable without the metrics
+        let (best_storage, placement) = if self::breaker::METRICS.is_open() {
+            match select_fallback(candidates) {
+                Some(candidate) => {
+                    let storage_name = candidate.storage_name;
+                    let policy = if candidate.is_default {
+                        "default"
+                    } else {
+                        "least used"
+                    };
+                    let message = format!(
+                        "the metrics are unavailable; bound to the {policy} storage: {storage_name}",
+                    );
+                    warn!("{message}");
+                    (storage_name, Placement::Fallback(message))
+                }
+                None => return Ok(None),
+            }
+        } else {
+            match candidates
+                .into_iter()
+                .find(|candidate| candidate.score.is_some())
+            {
+                Some(candidate) => (candidate.storage_name, Placement::Optimized),
+                None => return Ok(None),
+            }
+        };
+
+        let storage_binding =
+            ModelStorageBindingStorageKind::Owned(ModelStorageBindingStorageKindOwnedSpec {
+                target: best_storage,
+            });
+
+        self.kubernetes_storage
+            .create_model_storage_binding(
+                self.field_manager,
+                model.name_any(),
+                model.name_any(),
+                storage_binding,
+                resources,
+                deletion_policy,
+            )
+            .await
+            .map(|binding| Some((binding, placement)))
+    }
+
+    /// Bind the model across the given storages, splitting the storage quota by their weights.
+    ///
+    /// The share which overflows a storage is redistributed to the others.
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    pub async fn optimize_model_storage_distribution(
+        &self,
+        model: &ModelCrd,
+        default_storage_name: Option<&str>,
+        targets: &[ModelClaimStorageTarget],
+        resources: Option<ResourceRequirements>,
+        deletion_policy: ModelStorageBindingDeletionPolicy,
+    ) -> Result<Vec<(ModelStorageBindingCrd, ModelClaimDistribution)>> {
+        let candidates = self
+            .simulate_model_storage_binding(model, default_storage_name, None, None)
+            .await?;
+
+        let quota = resources.quota().map(|quota| quota.as_u128());
+        let targets = targets
+            .iter()
+            .map(|target| {
+                let storage_name = &target.storage_name;
+                match candidates
+                    .iter()
+                    .find(|candidate| &candidate.storage_name == storage_name)
+                {
+                    Some(candidate) if candidate.score.is_some() => {
+                        Ok((target, candidate.available))
+                    }
+                    Some(candidate) => bail!(
+                        "cannot bind to the model storage {storage_name:?}: {reason}",
+                        reason = candidate.reason.as_deref().unwrap_or("unknown"),
+                    ),
+                    None => bail!("no such model storage: {storage_name:?}"),
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let plan = plan_distribution(&targets, quota)?;
+
+        let model_name = model.name_any();
+        let mut bindings = Vec::with_capacity(plan.len());
+        for (target, quota) in targets.into_iter().map(|(target, _)| target).zip(plan) {
+            let ModelClaimStorageTarget {
+                storage_name,
+                weight,
+            } = target.clone();
+
+            let mut resources = resources.clone();
+            if let Some(quota) = quota {
+                resources
+                    .get_or_insert_with(Default::default)
+                    .requests
+                    .get_or_insert_with(Default::default)
+                    .insert("storage".into(), Quantity(quota.to_string()));
+            }
+
+            let binding_name = format!("{model_name}-{storage_name}");
+            let storage_binding =
+                ModelStorageBindingStorageKind::Owned(ModelStorageBindingStorageKindOwnedSpec {
+                    target: storage_name.clone(),
+                });
+            let binding = self
+                .kubernetes_storage
+                .create_model_storage_binding(
+                    self.field_manager,
+                    binding_name.clone(),
+                    model_name.clone(),
+                    storage_binding,
+                    resources,
+                    deletion_policy,
+                )
+                .await?;
+
+            bindings.push((
+                binding,
+                ModelClaimDistribution {
+                    binding_name,
+                    storage_name,
+                    weight,
+                    quota,
+                },
+            ));
+        }
+        Ok(bindings)
+    }
+
+    /// Score all model storages by the binding policy, without binding the model.
+    ///
+    /// The candidates are sorted from the best one.
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    pub async fn simulate_model_storage_binding(
+        &self,
+        model: &ModelCrd,
+        default_storage_name: Option<&str>,
+        storage: Option<ModelStorageKind>,
+        quota: Option<Byte>,
+    ) -> Result<Vec<ModelClaimPlacementCandidate>> {
         // Collect all storages
         let crs = self
             .kubernetes_storage
@@ -64,7 +224,11 @@ impl<'namespace, 'kube> ModelClaimOptimizer<'namespace, 'kube> {
             })
             .await?;
 
-        // Collect all metrics
+        // Collect all metrics, unless the metrics server is known to be down
+        let metrics_available = !self::breaker::METRICS.is_open();
+        let tenant = &TenantValidator {
+            kubernetes_storage: self.kubernetes_storage,
+        };
         let storages = crs
             .iter()
             .filter_map(|storage| {
@@ -78,6 +242,9 @@ impl<'namespace, 'kube> ModelClaimOptimizer<'namespace, 'kube> {
                         let storage_name = storage.name_any();
 
                         Storage {
+                            access: tenant
+                                .validate_model_storage_access(&storage_name, &storage.spec)
+                                .await,
                             data: storage,
                             capacity: kind
                                 .get_capacity(kube, namespace, model, &storage_name)
@@ -86,75 +253,290 @@ impl<'namespace, 'kube> ModelClaimOptimizer<'namespace, 'kube> {
                                     warn!("failed to get capacity: {error}");
                                     None
                                 }),
-                            traffic: kind
-                                .get_traffic(
-                                    self.prometheus_client,
-                                    namespace,
-                                    model,
-                                    &storage_name,
-                                )
-                                .await
-                                .unwrap_or_else(|error| {
-                                    warn!("failed to get capacity: {error}");
-                                    TrafficMetrics::default()
-                                }),
+                            latency_ms: if metrics_available {
+                                self::breaker::METRICS
+                                    .record(
+                                        self::latency::get_latency(
+                                            self.prometheus_client,
+                                            namespace,
+                                            storage,
+                                        )
+                                        .await,
+                                    )
+                                    .unwrap_or_else(|error| {
+                                        warn!("failed to get latency: {error}");
+                                        None
+                                    })
+                            } else {
+                                None
+                            },
+                            traffic: if metrics_available {
+                                self::breaker::METRICS
+                                    .record(
+                                        kind.get_traffic(
+                                            self.prometheus_client,
+                                            namespace,
+                                            model,
+                                            &storage_name,
+                                        )
+                                        .await,
+                                    )
+                                    .unwrap_or_else(|error| {
+                                        warn!("failed to get traffic: {error}");
+                                        TrafficMetrics::default()
+                                    })
+                            } else {
+                                TrafficMetrics::default()
+                            },
                         }
                     })
             })
             .collect::<FuturesUnordered<_>>()
             .collect::<Vec<_>>()
-            .await
-            .into_iter();
-
-        // Filter by quota
-        let quota = resources.quota();
-        let affordable_storages = storages.filter(|storage| match (quota, storage.capacity) {
-            (Some(quota), Some(capacity)) => quota <= capacity.available(),
-            (Some(_), None) => false,
-            (None, _) => true,
+            .await;
+
+        // Score by the given binding policy
+        let strategy = self::strategy::load(self.binding_policy);
+        let mut candidates: Vec<_> = storages
+            .into_iter()
+            .map(|storage| {
+                let score = match &storage.access {
+                    Ok(()) => match (quota, storage.capacity) {
+                        (Some(quota), Some(capacity)) if quota > capacity.available() => {
+                            Err("insufficient capacity".into())
+                        }
+                        (Some(_), None) => Err("unknown capacity".into()),
+                        (Some(_), Some(_)) | (None, _) => {
+                            strategy.score(quota, &storage).map_err(ToString::to_string)
+                        }
+                    },
+                    Err(error) => Err(error.to_string()),
+                };
+
+                let storage_name = storage.data.name_any();
+                ModelClaimPlacementCandidate {
+                    is_default: default_storage_name == Some(storage_name.as_str()),
+                    storage_name,
+                    score: score.as_ref().ok().copied(),
+                    available: storage
+                        .capacity
+                        .map(|capacity| capacity.available().as_u128()),
+                    capacity: storage.capacity.map(|capacity| capacity.capacity.as_u128()),
+                    latency_ms: storage.latency_ms,
+                    reason: score.err(),
+                }
+            })
+            .collect();
+
+        // Prefer the tenant's default storage
+        candidates.sort_by(|a, b| {
+            let key = |candidate: &ModelClaimPlacementCandidate| {
+                (candidate.score.is_some(), candidate.is_default)
+            };
+            key(b)
+                .cmp(&key(a))
+                .then_with(|| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal))
         });
+        Ok(candidates)
+    }
+}
 
-        // TODO: optimize by given binding policy (ASAP)
-        // TODO: scrap informantions (from prometheus?)
-        let best_storage = match self.binding_policy {
-            ModelClaimBindingPolicy::Balanced => {
-                bail!("Unimplemented yet ({})!", self.binding_policy)
-            }
-            ModelClaimBindingPolicy::LowestCopy => match affordable_storages
-                .filter(|storage| storage.capacity.is_some())
-                .max_by_key(|storage| storage.capacity.unwrap().available())
-                .map(|storage| storage.data)
-            {
-                Some(storage) => storage,
-                None => return Ok(None),
-            },
-            // TODO: scrap latency informantions (from prometheus?)
-            ModelClaimBindingPolicy::LowestLatency => {
-                bail!("Unimplemented yet ({})!", self.binding_policy)
-            }
+impl<'namespace, 'kube> ModelClaimOptimizer<'namespace, 'kube> {
+    /// Compute the grown storage quota of the bound model,
+    /// so that its usage goes back under the target utilization.
+    ///
+    /// The usage is collected by the model storage controller.
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    pub async fn optimize_model_storage_resources(
+        &self,
+        model: &ModelCrd,
+        storage_name: &str,
+        resources: Option<&ResourceRequirements>,
+        autoscaling: &ModelClaimAutoscaling,
+    ) -> Result<Option<(ResourceRequirements, Resize)>> {
+        let ModelClaimAutoscaling {
+            max_storage,
+            target_utilization,
+        } = autoscaling;
+        if *target_utilization == 0 || *target_utilization > 100 {
+            bail!("autoscaling target utilization should be in 1..=100: {target_utilization}");
+        }
+
+        // the unlimited storages need not be grown
+        let quota = match resources.and_then(|resources| resources.quota()) {
+            Some(quota) => quota.as_u128(),
+            None => return Ok(None),
         };
 
-        let storage_binding =
-            ModelStorageBindingStorageKind::Owned(ModelStorageBindingStorageKindOwnedSpec {
-                target: best_storage.name_any(),
-            });
+        let storage = self
+            .kubernetes_storage
+            .load_model_storage(storage_name)
+            .await?;
+        let model_name = model.name_any();
+        let usage = match storage
+            .status
+            .as_ref()
+            .and_then(|status| status.usage.as_ref())
+            .and_then(|usage| usage.models.get(&model_name))
+            .and_then(|usage| usage.bytes)
+        {
+            Some(usage) => usage,
+            None => return Ok(None),
+        };
 
-        self.kubernetes_storage
-            .create_model_storage_binding(
-                self.field_manager,
-                model.name_any(),
-                storage_binding,
-                resources,
-                deletion_policy,
-            )
-            .await
-            .map(Some)
+        let target_utilization = u128::from(*target_utilization);
+        if usage.saturating_mul(100) <= quota.saturating_mul(target_utilization) {
+            return Ok(None);
+        }
+
+        let mut to = usage.saturating_mul(100).div_ceil(target_utilization);
+        if let Some(max_storage) = max_storage {
+            let max_storage = max_storage
+                .0
+                .parse::<Byte>()
+                .map_err(|error| anyhow!("failed to parse max storage: {error}"))?
+                .as_u128();
+            to = to.min(max_storage);
+        }
+        if to <= quota {
+            warn!("cannot grow the storage quota over the limit: {model_name}");
+            return Ok(None);
+        }
+
+        let mut resources = resources.cloned().unwrap_or_default();
+        resources
+            .requests
+            .get_or_insert_with(Default::default)
+            .insert("storage".into(), Quantity(to.to_string()));
+
+        Ok(Some((
+            resources,
+            Resize {
+                from: quota,
+                to,
+                usage,
+            },
+        )))
+    }
+}
+
+/// How the model storage has been selected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Placement {
+    /// Scored by the binding policy with the collected metrics.
+    Optimized,
+    /// Selected by the local fallback policy, as the metrics are unavailable.
+    Fallback(String),
+}
+
+/// Split the storage quota by the weights of the storages, within their available capacity.
+///
+/// If the quota is not given, all parts are unlimited.
+fn plan_distribution(
+    targets: &[(&ModelClaimStorageTarget, Option<u128>)],
+    quota: Option<u128>,
+) -> Result<Vec<Option<u128>>> {
+    if targets.is_empty() {
+        bail!("no model storages to distribute");
     }
+    if let Some((target, _)) = targets.iter().find(|(target, _)| target.weight == 0) {
+        bail!(
+            "the weight of the model storage should be positive: {:?}",
+            target.storage_name,
+        );
+    }
+
+    let mut remaining = match quota {
+        Some(quota) => quota,
+        None => return Ok(vec![None; targets.len()]),
+    };
+    let available: Vec<_> = targets
+        .iter()
+        .map(|(target, available)| {
+            available.ok_or_else(|| {
+                anyhow!(
+                    "unknown capacity of the model storage: {:?}",
+                    target.storage_name,
+                )
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    // Fill the storages by their weights, until the quota is fully distributed
+    let mut plan = vec![0u128; targets.len()];
+    loop {
+        let open: Vec<_> = (0..targets.len())
+            .filter(|&index| plan[index] < available[index])
+            .collect();
+        let total_weight: u128 = open
+            .iter()
+            .map(|&index| u128::from(targets[index].0.weight))
+            .sum();
+        if remaining == 0 || total_weight == 0 {
+            break;
+        }
+
+        let mut distributed = 0;
+        for (order, &index) in open.iter().enumerate() {
+            let share = if order + 1 == open.len() {
+                // NOTE: the last one takes the rounding errors
+                remaining - distributed
+            } else {
+                remaining * u128::from(targets[index].0.weight) / total_weight
+            };
+            let share = share.min(available[index] - plan[index]);
+            plan[index] += share;
+            distributed += share;
+        }
+        if distributed == 0 {
+            break;
+        }
+        remaining -= distributed;
+    }
+
+    if remaining > 0 {
+        bail!("insufficient capacity to distribute the model: {remaining} bytes are left");
+    }
+    Ok(plan.into_iter().map(Some).collect())
+}
+
+/// Select the default storage, or the least used one by the known capacity.
+fn select_fallback(
+    candidates: Vec<ModelClaimPlacementCandidate>,
+) -> Option<ModelClaimPlacementCandidate> {
+    let usage = |candidate: &ModelClaimPlacementCandidate| match (
+        candidate.available,
+        candidate.capacity,
+    ) {
+        (Some(available), Some(capacity)) if capacity > 0 => {
+            capacity.saturating_sub(available) as f64 / capacity as f64
+        }
+        _ => f64::INFINITY,
+    };
+
+    candidates
+        .into_iter()
+        // the storages which are not accessible or affordable are never bound
+        .filter(|candidate| candidate.score.is_some())
+        .min_by(|a, b| {
+            b.is_default
+                .cmp(&a.is_default)
+                .then_with(|| usage(a).partial_cmp(&usage(b)).unwrap_or(Ordering::Equal))
+        })
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Resize {
+    pub from: u128,
+    pub to: u128,
+    pub usage: u128,
 }
 
 struct Storage<'a> {
+    access: Result<()>,
     capacity: Option<Capacity>,
     data: &'a ModelStorageCrd,
+    latency_ms: Option<f64>,
     traffic: TrafficMetrics,
 }
 
@@ -196,6 +578,11 @@ impl GetCapacity for ModelStorageKindSpec {
                     .get_capacity(kube, namespace, model, storage_name)
                     .await
             }
+            ModelStorageKindSpec::FileSystem(storage) => {
+                storage
+                    .get_capacity(kube, namespace, model, storage_name)
+                    .await
+            }
             ModelStorageKindSpec::Kubernetes(storage) => {
                 storage
                     .get_capacity(kube, namespace, model, storage_name)
@@ -222,6 +609,11 @@ impl GetCapacity for ModelStorageKindSpec {
                     .get_capacity_global(kube, namespace, storage_name)
                     .await
             }
+            ModelStorageKindSpec::FileSystem(storage) => {
+                storage
+                    .get_capacity_global(kube, namespace, storage_name)
+                    .await
+            }
             ModelStorageKindSpec::Kubernetes(storage) => {
                 storage
                     .get_capacity_global(kube, namespace, storage_name)
@@ -270,6 +662,11 @@ impl GetTraffic for ModelStorageKindSpec {
                     .get_traffic(prometheus_client, namespace, model, storage_name)
                     .await
             }
+            ModelStorageKindSpec::FileSystem(storage) => {
+                storage
+                    .get_traffic(prometheus_client, namespace, model, storage_name)
+                    .await
+            }
             ModelStorageKindSpec::Kubernetes(storage) => {
                 storage
                     .get_traffic(prometheus_client, namespace, model, storage_name)
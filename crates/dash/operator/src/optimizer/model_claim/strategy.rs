@@ -0,0 +1,85 @@
+use byte_unit::Byte;
+use dash_api::model_claim::ModelClaimBindingPolicy;
+use dash_provider_api::data::Capacity;
+
+use super::Storage;
+
+pub(super) trait PlacementStrategy {
+    /// Score the affordable storage; the higher is the better.
+    fn score(&self, quota: Option<Byte>, storage: &Storage<'_>) -> Result<f64, &'static str>;
+}
+
+pub(super) fn load(
+    policy: ModelClaimBindingPolicy,
+) -> &'static (dyn PlacementStrategy + Send + Sync) {
+    match policy {
+        ModelClaimBindingPolicy::Balanced => &Balanced,
+        ModelClaimBindingPolicy::BinPacking => &BinPacking,
+        ModelClaimBindingPolicy::LowestCopy => &LowestCopy,
+        ModelClaimBindingPolicy::LowestLatency => &LowestLatency,
+        ModelClaimBindingPolicy::Spread => &Spread,
+    }
+}
+
+struct Balanced;
+
+impl PlacementStrategy for Balanced {
+    fn score(&self, quota: Option<Byte>, storage: &Storage<'_>) -> Result<f64, &'static str> {
+        let spread = Spread.score(quota, storage)?;
+        let latency = LowestLatency.score(quota, storage)?;
+        Ok((spread + latency) / 2.0)
+    }
+}
+
+struct BinPacking;
+
+impl PlacementStrategy for BinPacking {
+    fn score(&self, quota: Option<Byte>, storage: &Storage<'_>) -> Result<f64, &'static str> {
+        let available = load_capacity(storage)?.available().as_u128();
+        let quota = quota.map(|quota| quota.as_u128()).unwrap_or_default();
+
+        // the less space is left after binding, the better
+        Ok(-(available.saturating_sub(quota) as f64))
+    }
+}
+
+struct LowestCopy;
+
+impl PlacementStrategy for LowestCopy {
+    fn score(&self, _quota: Option<Byte>, storage: &Storage<'_>) -> Result<f64, &'static str> {
+        load_capacity(storage).map(|capacity| capacity.available().as_u128() as f64)
+    }
+}
+
+struct LowestLatency;
+
+impl PlacementStrategy for LowestLatency {
+    fn score(&self, _quota: Option<Byte>, storage: &Storage<'_>) -> Result<f64, &'static str> {
+        // NOTE: the storages without traffic fall back to their benchmark results
+        let latency_ms = storage.latency_ms.or_else(|| {
+            storage
+                .data
+                .status
+                .as_ref()
+                .and_then(|status| status.benchmark.as_ref())
+                .and_then(|benchmark| benchmark.latency_ms())
+        });
+
+        // NOTE: the unmeasured storages are the least preferred, but still can be bound
+        Ok(latency_ms
+            .map(|latency_ms| 1.0 / (1.0 + latency_ms.max(0.0)))
+            .unwrap_or_default())
+    }
+}
+
+struct Spread;
+
+impl PlacementStrategy for Spread {
+    fn score(&self, _quota: Option<Byte>, storage: &Storage<'_>) -> Result<f64, &'static str> {
+        load_capacity(storage).map(|capacity| 1.0 - capacity.ratio())
+    }
+}
+
+fn load_capacity(storage: &Storage<'_>) -> Result<Capacity, &'static str> {
+    storage.capacity.ok_or("unknown capacity")
+}
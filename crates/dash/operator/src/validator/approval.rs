@@ -0,0 +1,101 @@
+use anyhow::{bail, Result};
+use chrono::{Duration, Utc};
+use dash_api::approval::{
+    ApprovalCrd, ApprovalOperation, ApprovalSpec, ApprovalState, ApprovalTargetSpec,
+};
+use dash_provider::storage::KubernetesStorageClient;
+use kube::core::ObjectMeta;
+use maplit::btreemap;
+use tracing::{info, instrument, Level};
+
+pub struct ApprovalValidator<'namespace, 'kube> {
+    pub kubernetes_storage: KubernetesStorageClient<'namespace, 'kube>,
+}
+
+impl<'namespace, 'kube> ApprovalValidator<'namespace, 'kube> {
+    /// Check whether the operation can proceed, requesting an approval if it is required.
+    ///
+    /// The expired approvals are deleted so that they can be requested again.
+    #[instrument(level = Level::INFO, skip(self, reason), err(Display))]
+    pub async fn check(
+        &self,
+        requested_by: &str,
+        operation: ApprovalOperation,
+        target: ApprovalTargetSpec,
+        reason: impl Into<String>,
+    ) -> Result<ApprovalCheck> {
+        let policy = self.kubernetes_storage.load_approval_policy().await?;
+        if !policy.is_required(operation) {
+            return Ok(ApprovalCheck::NotRequired);
+        }
+
+        let name = ApprovalCrd::name_of(operation, &target);
+        let approval = match self.kubernetes_storage.load_approval(&name).await? {
+            Some(approval) => approval,
+            None => {
+                let approval = ApprovalCrd {
+                    metadata: ObjectMeta {
+                        name: Some(name.clone()),
+                        namespace: Some(self.kubernetes_storage.namespace.into()),
+                        labels: Some(btreemap! {
+                            ApprovalCrd::LABEL_OPERATION.into() => operation.to_string(),
+                            ApprovalCrd::LABEL_TARGET_NAME.into() => target.name.clone(),
+                        }),
+                        ..Default::default()
+                    },
+                    spec: ApprovalSpec {
+                        operation,
+                        target,
+                        reason: Some(reason.into()),
+                        requested_by: requested_by.into(),
+                        expires_at: Utc::now() + Duration::seconds(policy.ttl_seconds.into()),
+                        decision: None,
+                    },
+                    status: None,
+                };
+                self.kubernetes_storage.create_approval(&approval).await?;
+
+                let namespace = self.kubernetes_storage.namespace;
+                info!("requested approval ({namespace}/{name}): {operation}");
+                return Ok(ApprovalCheck::Pending(name));
+            }
+        };
+
+        match approval
+            .status
+            .as_ref()
+            .map(|status| status.state)
+            .unwrap_or_default()
+        {
+            ApprovalState::Pending => Ok(ApprovalCheck::Pending(name)),
+            ApprovalState::Approved => Ok(ApprovalCheck::Approved),
+            ApprovalState::Rejected => bail!("the operation has been rejected: {name}"),
+            ApprovalState::Expired => {
+                self.kubernetes_storage.delete_approval(&name).await?;
+                Ok(ApprovalCheck::Pending(name))
+            }
+        }
+    }
+
+    /// Fail unless the operation is allowed to proceed.
+    #[instrument(level = Level::INFO, skip(self, reason), err(Display))]
+    pub async fn assert_approved(
+        &self,
+        requested_by: &str,
+        operation: ApprovalOperation,
+        target: ApprovalTargetSpec,
+        reason: impl Into<String>,
+    ) -> Result<()> {
+        match self.check(requested_by, operation, target, reason).await? {
+            ApprovalCheck::NotRequired | ApprovalCheck::Approved => Ok(()),
+            ApprovalCheck::Pending(name) => bail!("waiting for the approval: {name}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ApprovalCheck {
+    NotRequired,
+    Approved,
+    Pending(String),
+}
@@ -1,12 +1,12 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use ark_core_k8s::data::Name;
 use dash_api::{
-    function::{FunctionExec, FunctionSpec},
+    function::{FunctionEndpointStatus, FunctionExec, FunctionKnativeSpec, FunctionSpec},
     model::{
         ModelFieldKindExtendedSpec, ModelFieldKindSpec, ModelFieldSpec, ModelFieldsNativeSpec,
     },
 };
-use dash_provider::storage::KubernetesStorageClient;
+use dash_provider::{function::knative::KnativeServiceClient, storage::KubernetesStorageClient};
 use kube::Client;
 use straw_api::{
     function::{StrawFunction, StrawFunctionType},
@@ -26,6 +26,8 @@ impl<'namespace, 'kube> FunctionValidator<'namespace, 'kube> {
     #[instrument(level = Level::INFO, skip_all, err(Display))]
     pub async fn validate_function(
         &self,
+        field_manager: &str,
+        name: &str,
         spec: FunctionSpec,
     ) -> Result<FunctionSpec<ModelFieldsNativeSpec>> {
         let FunctionSpec {
@@ -34,6 +36,7 @@ impl<'namespace, 'kube> FunctionValidator<'namespace, 'kube> {
             exec,
             type_,
             volatility,
+            test,
         } = spec;
 
         let models = Models { input, output };
@@ -57,21 +60,30 @@ impl<'namespace, 'kube> FunctionValidator<'namespace, 'kube> {
         Ok(FunctionSpec {
             input: validate_model(models.input.clone().into()).await?,
             output: validate_model(models.output.clone().into()).await?,
-            exec: self.validate_exec(type_, exec, models).await?,
+            exec: self
+                .validate_exec(field_manager, name, type_, exec, models)
+                .await?,
             type_,
             volatility,
+            test,
         })
     }
 
     #[instrument(level = Level::INFO, skip_all, err(Display))]
     async fn validate_exec(
         &self,
+        field_manager: &str,
+        name: &str,
         type_: StrawFunctionType,
         exec: FunctionExec,
         models: Models,
     ) -> Result<FunctionExec> {
         match exec {
             FunctionExec::Placeholder {} => Ok(exec),
+            FunctionExec::Knative(spec) => self
+                .validate_exec_knative(field_manager, name, spec)
+                .await
+                .map(FunctionExec::Knative),
             FunctionExec::Straw(function) => self
                 .validate_exec_straw(type_, function, models)
                 .await
@@ -79,6 +91,29 @@ impl<'namespace, 'kube> FunctionValidator<'namespace, 'kube> {
         }
     }
 
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn validate_exec_knative(
+        &self,
+        field_manager: &str,
+        name: &str,
+        spec: FunctionKnativeSpec,
+    ) -> Result<FunctionKnativeSpec> {
+        if let Some(max_scale) = spec.autoscaling.max_scale {
+            if max_scale < spec.autoscaling.min_scale {
+                bail!("autoscaling.maxScale should not be less than autoscaling.minScale");
+            }
+        }
+
+        let client = KnativeServiceClient {
+            namespace: self.namespace,
+            kube: self.kube,
+        };
+        client
+            .apply(field_manager, name, &spec)
+            .await
+            .map(|()| spec)
+    }
+
     #[instrument(level = Level::INFO, skip_all, err(Display))]
     async fn validate_exec_straw(
         &self,
@@ -107,9 +142,34 @@ impl<'namespace, 'kube> FunctionValidator<'namespace, 'kube> {
     }
 
     #[instrument(level = Level::INFO, skip_all, err(Display))]
-    pub async fn delete(&self, spec: &FunctionSpec) -> Result<()> {
+    pub async fn load_endpoint(
+        &self,
+        name: &str,
+        spec: &FunctionSpec,
+    ) -> Result<Option<FunctionEndpointStatus>> {
+        match &spec.exec {
+            FunctionExec::Placeholder {} | FunctionExec::Straw(_) => Ok(None),
+            FunctionExec::Knative(_) => {
+                let client = KnativeServiceClient {
+                    namespace: self.namespace,
+                    kube: self.kube,
+                };
+                client.load_endpoint(name).await.map(Some)
+            }
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    pub async fn delete(&self, name: &str, spec: &FunctionSpec) -> Result<()> {
         match &spec.exec {
             FunctionExec::Placeholder {} => Ok(()),
+            FunctionExec::Knative(_) => {
+                let client = KnativeServiceClient {
+                    namespace: self.namespace,
+                    kube: self.kube,
+                };
+                client.delete(name).await
+            }
             FunctionExec::Straw(function) => self.delete_exec_straw(function).await,
         }
     }
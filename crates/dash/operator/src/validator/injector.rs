@@ -4,17 +4,19 @@ use anyhow::Result;
 use dash_provider::client::job::TaskActorJobClient;
 use dash_provider_api::{job::TaskActorJobMetadata, SessionContext, SessionContextMetadata};
 use kube::Client;
+use serde_json::Value;
 use tracing::{instrument, Level};
 
 #[derive(Copy, Clone)]
-pub struct InjectorValidator<'namespace, 'kube> {
+pub struct InjectorValidator<'namespace, 'kube, 'spec> {
     pub content: &'static str,
     pub name: &'static str,
     pub namespace: &'namespace str,
     pub kube: &'kube Client,
+    pub spec: &'spec Value,
 }
 
-impl<'namespace, 'kube> InjectorValidator<'namespace, 'kube> {
+impl<'namespace, 'kube, 'spec> InjectorValidator<'namespace, 'kube, 'spec> {
     #[instrument(level = Level::INFO, skip_all, err(Display))]
     pub async fn create(&self) -> Result<()> {
         self.execute(|client, input| async move { client.create(&input).await })
@@ -38,7 +40,7 @@ impl<'namespace, 'kube> InjectorValidator<'namespace, 'kube> {
     #[instrument(level = Level::INFO, skip_all, err(Display))]
     async fn execute<F, Fut, R>(&self, f: F) -> Result<R>
     where
-        F: FnOnce(TaskActorJobClient, SessionContext<()>) -> Fut,
+        F: FnOnce(TaskActorJobClient, SessionContext<&'spec Value>) -> Fut,
         Fut: Future<Output = Result<R>>,
     {
         let name = self.name.to_string();
@@ -56,7 +58,7 @@ impl<'namespace, 'kube> InjectorValidator<'namespace, 'kube> {
 
         let input = SessionContext {
             metadata: SessionContextMetadata { name, namespace },
-            spec: (),
+            spec: self.spec,
         };
         f(client, input).await
     }
@@ -1,8 +1,13 @@
+pub mod approval;
 pub mod function;
 pub mod injector;
 pub mod job;
 pub mod model;
 pub mod model_claim;
 pub mod model_storage_binding;
+pub mod orphaned_artifact;
+pub mod pipeline;
+pub mod queue;
 pub mod storage;
 pub mod task;
+pub mod tenant;
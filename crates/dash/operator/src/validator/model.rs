@@ -5,13 +5,16 @@ use std::{
 
 use anyhow::{bail, Result};
 use dash_api::{
+    approval::{ApprovalOperation, ApprovalTargetSpec},
     model::{
-        ModelCrd, ModelCustomResourceDefinitionRefSpec, ModelFieldAttributeSpec,
-        ModelFieldKindExtendedSpec, ModelFieldKindNativeSpec, ModelFieldKindObjectSpec,
-        ModelFieldKindSpec, ModelFieldKindStringSpec, ModelFieldNativeSpec, ModelFieldSpec,
-        ModelFieldsNativeSpec, ModelFieldsSpec, ModelSpec,
+        ModelCompatibility, ModelCrd, ModelCustomResourceDefinitionRefSpec,
+        ModelFieldAttributeSpec, ModelFieldKindExtendedSpec, ModelFieldKindNativeSpec,
+        ModelFieldKindObjectSpec, ModelFieldKindSpec, ModelFieldKindStringSpec,
+        ModelFieldNativeSpec, ModelFieldSpec, ModelFieldsNativeSpec, ModelFieldsSpec, ModelSpec,
+        ModelVersion,
     },
     model_claim::ModelClaimState,
+    storage::ModelStorageModelUsage,
 };
 use dash_provider::{imp::assert_contains, storage::KubernetesStorageClient};
 use dash_provider_api::name;
@@ -20,10 +23,12 @@ use itertools::Itertools;
 use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::{
     CustomResourceDefinitionVersion, JSONSchemaProps,
 };
-use kube::ResourceExt;
+use kube::{Resource, ResourceExt};
 use regex::Regex;
 use tracing::{instrument, warn, Level};
 
+use super::approval::{ApprovalCheck, ApprovalValidator};
+
 pub struct ModelValidator<'namespace, 'kube> {
     pub kubernetes_storage: KubernetesStorageClient<'namespace, 'kube>,
 }
@@ -96,6 +101,101 @@ impl<'namespace, 'kube> ModelValidator<'namespace, 'kube> {
         self.validate_native_fields(parser.finalize()?)
     }
 
+    /// Compute the schema change of the model, and reject the breaking one
+    /// if the bound storages have data, unless it is explicitly allowed or approved.
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    pub async fn validate_model_update(
+        &self,
+        crd: &ModelCrd,
+        last: Option<&ModelFieldsNativeSpec>,
+        fields: &ModelFieldsNativeSpec,
+    ) -> Result<ModelVersion> {
+        let version = ModelVersion::new(crd.metadata.generation, last, fields);
+        if version.compatibility != ModelCompatibility::Breaking
+            || crd.is_breaking_changes_allowed()
+        {
+            return Ok(version);
+        }
+
+        let model_name = crd.name_any();
+        if !self.has_stored_data(&model_name).await? {
+            return Ok(version);
+        }
+
+        let changes = version
+            .changes
+            .iter()
+            .filter(|change| change.compatibility == ModelCompatibility::Breaking)
+            .map(|change| format!("{kind} {name:?}", kind = change.kind, name = change.name))
+            .join(", ");
+
+        let approval = ApprovalValidator {
+            kubernetes_storage: self.kubernetes_storage,
+        };
+        let target = ApprovalTargetSpec {
+            kind: ModelCrd::kind(&()).into(),
+            name: model_name,
+            generation: crd.metadata.generation,
+        };
+        match approval
+            .check(
+                crate::consts::NAME,
+                ApprovalOperation::ModelSchemaChange,
+                target,
+                format!("breaking schema changes: {changes}"),
+            )
+            .await?
+        {
+            ApprovalCheck::Approved => Ok(version),
+            ApprovalCheck::Pending(name) => {
+                bail!("breaking schema changes are waiting for the approval {name:?}: {changes}")
+            }
+            ApprovalCheck::NotRequired => bail!(
+                "breaking schema changes are not allowed while the bound storages have data (set the annotation {key:?} to override): {changes}",
+                key = ModelCrd::ANNOTATION_ALLOW_BREAKING_CHANGES,
+            ),
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    async fn has_stored_data(&self, model_name: &str) -> Result<bool> {
+        let bindings = self
+            .kubernetes_storage
+            .load_model_storage_bindings(model_name)
+            .await?;
+
+        for (_, status) in bindings {
+            let storage_name = match status.storage_target_name {
+                Some(storage_name) => storage_name,
+                None => continue,
+            };
+            let usage = self
+                .kubernetes_storage
+                .load_model_storage(&storage_name)
+                .await?
+                .status
+                .and_then(|status| status.usage)
+                .and_then(|mut usage| usage.models.remove(model_name));
+
+            // NOTE: the unmeasured storages are assumed to have data
+            let has_data = match usage {
+                Some(ModelStorageModelUsage {
+                    bytes: None,
+                    objects: None,
+                    ..
+                })
+                | None => true,
+                Some(ModelStorageModelUsage { bytes, objects, .. }) => {
+                    bytes.unwrap_or_default() > 0 || objects.unwrap_or_default() > 0
+                }
+            };
+            if has_data {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
     #[instrument(level = Level::INFO, skip_all, err(Display))]
     pub async fn delete(&self, crd: &ModelCrd) -> Result<()> {
         let model_name = crd.name_any();
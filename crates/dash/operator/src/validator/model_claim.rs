@@ -1,8 +1,14 @@
 use anyhow::{anyhow, bail, Result};
+use chrono::Utc;
 use dash_api::{
-    model_claim::{ModelClaimCrd, ModelClaimDeletionPolicy, ModelClaimState, ModelClaimStatus},
+    model::{ModelCrd, ModelSpec},
+    model_claim::{
+        ModelClaimCrd, ModelClaimDeletionPolicy, ModelClaimDistribution,
+        ModelClaimPlacementCandidate, ModelClaimResizeRecord, ModelClaimSpec, ModelClaimState,
+        ModelClaimStatus,
+    },
     model_storage_binding::{ModelStorageBindingCrd, ModelStorageBindingDeletionPolicy},
-    storage::ModelStorageKind,
+    storage::{ModelStorageKind, StorageResourceRequirements},
 };
 use dash_provider::storage::KubernetesStorageClient;
 use k8s_openapi::{
@@ -10,9 +16,11 @@ use k8s_openapi::{
 };
 use kube::{api::ObjectMeta, Resource, ResourceExt};
 use prometheus_http_query::Client as PrometheusClient;
-use tracing::{instrument, Level};
+use tracing::{info, instrument, Level};
 
-use crate::optimizer::model_claim::ModelClaimOptimizer;
+use crate::optimizer::model_claim::{ModelClaimOptimizer, Placement, Resize};
+
+use super::tenant::TenantValidator;
 
 pub struct ModelClaimValidator<'namespace, 'kube> {
     pub kubernetes_storage: KubernetesStorageClient<'namespace, 'kube>,
@@ -27,6 +35,12 @@ impl<'namespace, 'kube> ModelClaimValidator<'namespace, 'kube> {
         field_manager: &str,
         crd: &ModelClaimCrd,
     ) -> Result<UpdateContext> {
+        // check tenant quota
+        let tenant = TenantValidator {
+            kubernetes_storage: self.kubernetes_storage,
+        };
+        tenant.validate_model_claim_quota(crd).await?;
+
         // create model
         let model = self
             .kubernetes_storage
@@ -46,7 +60,10 @@ impl<'namespace, 'kube> ModelClaimValidator<'namespace, 'kube> {
                     .map(|(metadata, _)| to_owner_reference(metadata))
                     .collect::<Result<_>>()?;
                 return Ok(UpdateContext {
+                    distribution: load_distribution(crd),
                     owner_references: Some(owner_references),
+                    placement: None,
+                    resize_history: load_resize_history(crd),
                     state: ModelClaimState::Ready,
                     resources: crd.spec.resources.clone(),
                     storage: crd.spec.storage,
@@ -66,26 +83,63 @@ impl<'namespace, 'kube> ModelClaimValidator<'namespace, 'kube> {
             ModelClaimDeletionPolicy::Delete => ModelStorageBindingDeletionPolicy::Delete,
             ModelClaimDeletionPolicy::Retain => ModelStorageBindingDeletionPolicy::Retain,
         };
+        let default_storage_name = tenant.load_default_storage_name().await?;
+
+        // distribute the model across the storages
+        if !crd.spec.storages.is_empty() {
+            let bindings = optimizer
+                .optimize_model_storage_distribution(
+                    &model,
+                    default_storage_name.as_deref(),
+                    &crd.spec.storages,
+                    crd.spec.resources.clone(),
+                    deletion_policy,
+                )
+                .await?;
+
+            let mut owner_references = Vec::with_capacity(bindings.len());
+            let mut distribution = Vec::with_capacity(bindings.len());
+            for (cr, part) in bindings {
+                owner_references.push(to_owner_reference(cr.metadata)?);
+                distribution.push(part);
+            }
+
+            return Ok(UpdateContext {
+                distribution,
+                owner_references: Some(owner_references),
+                placement: Some(Placement::Optimized),
+                resize_history: load_resize_history(crd),
+                resources: crd.spec.resources.clone(),
+                state: ModelClaimState::Ready,
+                storage: None,
+                storage_name: None,
+            });
+        }
+
         let binding = optimizer
             .optimize_model_storage_binding(
                 &model,
+                default_storage_name.as_deref(),
                 crd.spec.storage,
                 crd.spec.resources.clone(),
                 deletion_policy,
             )
             .await?;
 
-        let (owner_references, storage_name) = match binding {
-            Some(cr) => {
+        let (owner_references, storage_name, placement) = match binding {
+            Some((cr, placement)) => {
                 let owner_references = vec![to_owner_reference(cr.metadata.clone())?];
                 let storage_name = cr.spec.storage.target().clone();
-                (owner_references, storage_name)
+                (owner_references, storage_name, placement)
             }
             None => bail!("failed to find suitable model storage"),
         };
 
         Ok(UpdateContext {
+            distribution: Vec::default(),
             owner_references: Some(owner_references),
+            placement: Some(placement),
+            resize_history: load_resize_history(crd),
             resources: crd.spec.resources.clone(),
             state: ModelClaimState::Ready,
             storage: crd.spec.storage,
@@ -93,6 +147,38 @@ impl<'namespace, 'kube> ModelClaimValidator<'namespace, 'kube> {
         })
     }
 
+    /// Score the model storages to bind the model claim, without binding it.
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    pub async fn simulate_model_claim(
+        &self,
+        field_manager: &str,
+        name: &str,
+        spec: &ModelClaimSpec,
+    ) -> Result<Vec<ModelClaimPlacementCandidate>> {
+        let tenant = TenantValidator {
+            kubernetes_storage: self.kubernetes_storage,
+        };
+
+        // NOTE: the model is not created on simulation
+        let mut model = ModelCrd::new(name, ModelSpec::Dynamic {});
+        model.metadata.namespace = Some(self.kubernetes_storage.namespace.into());
+
+        let optimizer = ModelClaimOptimizer::new(
+            field_manager,
+            self.kubernetes_storage,
+            self.prometheus_client,
+            spec.binding_policy,
+        );
+        optimizer
+            .simulate_model_storage_binding(
+                &model,
+                tenant.load_default_storage_name().await?.as_deref(),
+                spec.storage,
+                spec.resources.quota(),
+            )
+            .await
+    }
+
     #[instrument(level = Level::INFO, skip_all, err(Display))]
     pub async fn validate_model_claim_replacement(
         &self,
@@ -143,7 +229,7 @@ impl<'namespace, 'kube> ModelClaimValidator<'namespace, 'kube> {
         last_status: &ModelClaimStatus,
     ) -> Result<Option<UpdateContext>> {
         if !is_changed(crd, last_status) {
-            return Ok(None);
+            return self.autoscale(field_manager, crd, last_status).await;
         }
 
         if !crd.spec.allow_replacement {
@@ -162,8 +248,107 @@ impl<'namespace, 'kube> ModelClaimValidator<'namespace, 'kube> {
     }
 }
 
-pub(crate) struct UpdateContext {
+impl<'namespace, 'kube> ModelClaimValidator<'namespace, 'kube> {
+    /// Grow the storage quota of the bound model storages on demand.
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn autoscale(
+        &self,
+        field_manager: &str,
+        crd: &ModelClaimCrd,
+        last_status: &ModelClaimStatus,
+    ) -> Result<Option<UpdateContext>> {
+        let autoscaling = match crd.spec.autoscaling.as_ref() {
+            Some(autoscaling) => autoscaling,
+            None => return Ok(None),
+        };
+
+        let model = self.kubernetes_storage.load_model(&crd.name_any()).await?;
+        let bindings = self
+            .kubernetes_storage
+            .load_model_storage_bindings(&model.name_any())
+            .await?;
+
+        let optimizer = ModelClaimOptimizer::new(
+            field_manager,
+            self.kubernetes_storage,
+            self.prometheus_client,
+            crd.spec.binding_policy,
+        );
+
+        let mut distribution = last_status.distribution.clone();
+        let mut resize_history = last_status.resize_history.clone();
+        for (metadata, status) in bindings {
+            let binding_name = metadata.name.unwrap_or_default();
+            let storage_name = match status.storage_target_name {
+                Some(storage_name) => storage_name,
+                None => continue,
+            };
+
+            let (resources, Resize { from, to, usage }) = match optimizer
+                .optimize_model_storage_resources(
+                    &model,
+                    &storage_name,
+                    status.resources.as_ref(),
+                    autoscaling,
+                )
+                .await?
+            {
+                Some(resized) => resized,
+                None => continue,
+            };
+
+            self.kubernetes_storage
+                .update_model_storage_binding_resources(field_manager, &binding_name, &resources)
+                .await?;
+            info!("resized model storage binding ({binding_name}): {from} => {to} bytes");
+
+            if let Some(part) = distribution
+                .iter_mut()
+                .find(|part| part.binding_name == binding_name)
+            {
+                part.quota = Some(to);
+            }
+
+            resize_history.push(ModelClaimResizeRecord {
+                binding_name,
+                storage_name,
+                from,
+                to,
+                usage,
+                timestamp: Utc::now(),
+            });
+        }
+
+        if resize_history.len() == last_status.resize_history.len() {
+            return Ok(None);
+        }
+        if let Some(overflow) = resize_history
+            .len()
+            .checked_sub(ModelClaimCrd::MAX_RESIZE_HISTORY)
+        {
+            resize_history.drain(..overflow);
+        }
+
+        Ok(Some(UpdateContext {
+            distribution,
+            owner_references: None,
+            placement: None,
+            resize_history,
+            resources: last_status.resources.clone(),
+            state: last_status.state,
+            storage: last_status.storage,
+            storage_name: last_status.storage_name.clone(),
+        }))
+    }
+}
+
+pub struct UpdateContext {
+    /// The distribution plan, if the model claim is bound to multiple storages.
+    pub(crate) distribution: Vec<ModelClaimDistribution>,
     pub(crate) owner_references: Option<Vec<OwnerReference>>,
+    /// How the storage has been selected, if it is newly bound.
+    pub(crate) placement: Option<Placement>,
+    pub(crate) resize_history: Vec<ModelClaimResizeRecord>,
     pub(crate) resources: Option<ResourceRequirements>,
     pub(crate) state: ModelClaimState,
     pub(crate) storage: Option<ModelStorageKind>,
@@ -178,6 +363,21 @@ struct State<'a> {
 }
 
 fn is_changed(crd: &ModelClaimCrd, last_status: &ModelClaimStatus) -> bool {
+    // Test the distributed storages changed
+    if !crd.spec.storages.is_empty() || !last_status.distribution.is_empty() {
+        let targets = crd
+            .spec
+            .storages
+            .iter()
+            .map(|target| (target.storage_name.as_str(), target.weight));
+        let targets_last = last_status
+            .distribution
+            .iter()
+            .map(|part| (part.storage_name.as_str(), part.weight));
+        return !targets.eq(targets_last)
+            || crd.spec.resources.as_ref() != last_status.resources.as_ref();
+    }
+
     let (before, after) = match (
         last_status.storage_name.as_deref(),
         crd.spec.storage_name.as_deref(),
@@ -199,7 +399,21 @@ fn is_changed(crd: &ModelClaimCrd, last_status: &ModelClaimStatus) -> bool {
         storage: last_status.storage,
         storage_name: before,
     };
-    state_last == state
+    state_last != state
+}
+
+fn load_distribution(crd: &ModelClaimCrd) -> Vec<ModelClaimDistribution> {
+    crd.status
+        .as_ref()
+        .map(|status| status.distribution.clone())
+        .unwrap_or_default()
+}
+
+fn load_resize_history(crd: &ModelClaimCrd) -> Vec<ModelClaimResizeRecord> {
+    crd.status
+        .as_ref()
+        .map(|status| status.resize_history.clone())
+        .unwrap_or_default()
 }
 
 fn to_owner_reference(metadata: ObjectMeta) -> Result<OwnerReference> {
@@ -1,20 +1,32 @@
 use anyhow::{anyhow, bail, Result};
+use byte_unit::Byte;
+use chrono::Utc;
 use dash_api::{
+    approval::{ApprovalOperation, ApprovalTargetSpec},
+    condition::{set_condition, Condition, TYPE_READY},
     model::{ModelCrd, ModelSpec},
     model_storage_binding::{
-        ModelStorageBindingCrd, ModelStorageBindingDeletionPolicy, ModelStorageBindingSpec,
-        ModelStorageBindingState, ModelStorageBindingStatus, ModelStorageBindingStorageSourceSpec,
-        ModelStorageBindingStorageSpec, ModelStorageBindingSyncPolicy,
+        ModelStorageBindingCrd, ModelStorageBindingDeletionPolicy,
+        ModelStorageBindingMigrationPhase, ModelStorageBindingMigrationStatus,
+        ModelStorageBindingReplicationStatus, ModelStorageBindingSpec, ModelStorageBindingState,
+        ModelStorageBindingStatus, ModelStorageBindingStorageKind,
+        ModelStorageBindingStorageSourceSpec, ModelStorageBindingStorageSpec,
+        ModelStorageBindingSyncPolicy, ModelStorageBindingSyncStatus,
+    },
+    storage::{
+        ModelStorageCrd, ModelStorageKindSpec, ModelStorageSpec, StorageResourceRequirements,
     },
-    storage::{ModelStorageCrd, ModelStorageSpec},
 };
 use k8s_openapi::{
     api::core::v1::ResourceRequirements, apimachinery::pkg::apis::meta::v1::OwnerReference,
 };
 use kube::{core::ObjectMeta, Resource, ResourceExt};
-use tracing::{error, instrument, Level};
+use tracing::{error, info, instrument, Level};
 
-use super::{model::ModelValidator, storage::ModelStorageValidator};
+use super::{
+    approval::ApprovalValidator, model::ModelValidator, storage::ModelStorageValidator,
+    tenant::TenantValidator,
+};
 
 pub struct ModelStorageBindingValidator<'namespace, 'kube> {
     pub model: ModelValidator<'namespace, 'kube>,
@@ -24,16 +36,83 @@ pub struct ModelStorageBindingValidator<'namespace, 'kube> {
 }
 
 impl<'namespace, 'kube> ModelStorageBindingValidator<'namespace, 'kube> {
+    fn approval(&self) -> ApprovalValidator<'namespace, 'kube> {
+        ApprovalValidator {
+            kubernetes_storage: self.model_storage.kubernetes_storage,
+        }
+    }
+
+    fn approval_target(&self, generation: Option<i64>) -> ApprovalTargetSpec {
+        ApprovalTargetSpec {
+            kind: ModelStorageBindingCrd::kind(&()).into(),
+            name: self.name.into(),
+            generation,
+        }
+    }
+
     #[instrument(level = Level::INFO, skip_all, err(Display))]
     pub async fn validate_model_storage_binding(
         &self,
         binding: &ModelStorageBindingCrd,
     ) -> Result<UpdateContext> {
+        Self::validate_spec(&binding.spec)?;
         let ctx = self.load_context(&binding.spec).await?;
 
+        let tenant = TenantValidator {
+            kubernetes_storage: self.model_storage.kubernetes_storage,
+        };
+        if let Some(source) = &ctx.state.storage_source {
+            tenant
+                .validate_model_storage_access(source.name, &source.storage)
+                .await?;
+        }
+        tenant
+            .validate_model_storage_access(ctx.state.storage_target_name, &ctx.state.storage_target)
+            .await?;
+
+        let storage_target = self
+            .model_storage
+            .kubernetes_storage
+            .load_model_storage(binding.spec.storage.target())
+            .await?;
+        self.model_storage
+            .validate_model_storage_quota(&storage_target, binding.spec.resources.quota())
+            .await?;
+
         self.validate_model_storage_binding_with(ctx, binding).await
     }
 
+    /// Validate the spec itself, without looking up the referenced resources.
+    pub fn validate_spec(spec: &ModelStorageBindingSpec) -> Result<()> {
+        if spec.model.is_empty() {
+            bail!("spec.model: model name should not be empty");
+        }
+
+        match &spec.storage {
+            ModelStorageBindingStorageKind::Cloned(storage) => {
+                if storage.source.is_empty() {
+                    bail!("spec.storage.cloned.source: model storage name should not be empty");
+                }
+                if storage.target.is_empty() {
+                    bail!("spec.storage.cloned.target: model storage name should not be empty");
+                }
+                if storage.source == storage.target {
+                    bail!(
+                        "spec.storage.cloned.target: should differ from the source: {name}",
+                        name = &storage.target,
+                    );
+                }
+                Ok(())
+            }
+            ModelStorageBindingStorageKind::Owned(storage) => {
+                if storage.target.is_empty() {
+                    bail!("spec.storage.owned.target: model storage name should not be empty");
+                }
+                Ok(())
+            }
+        }
+    }
+
     #[instrument(level = Level::INFO, skip_all, err(Display))]
     async fn validate_model_storage_binding_with(
         &self,
@@ -41,6 +120,7 @@ impl<'namespace, 'kube> ModelStorageBindingValidator<'namespace, 'kube> {
         binding: &ModelStorageBindingCrd,
     ) -> Result<UpdateContext> {
         let Context {
+            generations,
             model,
             state:
                 State {
@@ -100,26 +180,105 @@ impl<'namespace, 'kube> ModelStorageBindingValidator<'namespace, 'kube> {
             })
         }
 
+        let mut conditions = binding
+            .status
+            .as_ref()
+            .map(|status| status.conditions.clone())
+            .unwrap_or_default();
+        let generation = binding.metadata.generation;
+        set_condition(
+            &mut conditions,
+            ModelStorageBindingCrd::CONDITION_DRIFTED,
+            false,
+            "Bound",
+            "the binding is up-to-date",
+            generation,
+        );
+        if let ModelStorageKindSpec::ObjectStorage(spec) = &storage_target.kind {
+            let lifecycle = spec.lifecycle();
+            if lifecycle.is_empty() {
+                set_condition(
+                    &mut conditions,
+                    ModelStorageBindingCrd::CONDITION_LIFECYCLE_APPLIED,
+                    false,
+                    "NotConfigured",
+                    "no lifecycle rules are configured",
+                    generation,
+                );
+            } else {
+                let rules: Vec<_> = lifecycle.iter().map(|rule| rule.name.as_str()).collect();
+                set_condition(
+                    &mut conditions,
+                    ModelStorageBindingCrd::CONDITION_LIFECYCLE_APPLIED,
+                    true,
+                    "Applied",
+                    format!("the lifecycle rules are applied: {}", rules.join(", ")),
+                    generation,
+                );
+            }
+        }
+        set_condition(
+            &mut conditions,
+            TYPE_READY,
+            true,
+            "Bound",
+            "the model is bound to the model storages",
+            generation,
+        );
+
         Ok(UpdateContext {
+            conditions,
             deletion_policy: binding.spec.deletion_policy,
             model: Some(model.spec),
+            model_generation: generations.model,
             model_name: Some(model_name),
+            migration: None,
             owner_references: Some(owner_references),
+            replication: None,
             resources: binding.spec.resources.clone(),
             state: ModelStorageBindingState::Ready,
             storage_source: storage_source.map(|spec| spec.storage),
             storage_source_name,
             storage_source_binding_name,
+            storage_source_generation: generations.storage_source,
             storage_source_uid: storage_source_uid,
             storage_sync_policy,
             storage_target: Some(storage_target),
+            storage_target_generation: generations.storage_target,
             storage_target_name: Some(storage_target_name),
             storage_target_uid: Some(storage_target_uid),
+            sync: None,
         })
     }
 
     #[instrument(level = Level::INFO, skip_all, err(Display))]
-    pub async fn delete(&self, spec: &ModelStorageBindingSpec) -> Result<()> {
+    pub async fn delete(
+        &self,
+        spec: &ModelStorageBindingSpec,
+        last_status: Option<&ModelStorageBindingStatus>,
+    ) -> Result<()> {
+        // Removing the data should be approved
+        let deletion_policy = last_status
+            .map(|status| status.deletion_policy)
+            .unwrap_or(spec.deletion_policy);
+        if deletion_policy == ModelStorageBindingDeletionPolicy::Delete {
+            self.approval()
+                .assert_approved(
+                    crate::consts::NAME,
+                    ApprovalOperation::BindingDeletion,
+                    self.approval_target(None),
+                    "the binding is deleted with its data",
+                )
+                .await?;
+        }
+
+        // Stop the ongoing migration
+        if let Some(migration) = last_status.and_then(|status| status.migration.as_ref()) {
+            self.model_storage
+                .delete_migration_job(&migration.job_name)
+                .await?;
+        }
+
         match self.load_context(spec).await {
             Ok(ctx) => self.delete_with(ctx, spec).await,
             Err(error) => {
@@ -136,6 +295,7 @@ impl<'namespace, 'kube> ModelStorageBindingValidator<'namespace, 'kube> {
     #[instrument(level = Level::INFO, skip_all, err(Display))]
     async fn delete_with(&self, ctx: Context<'_>, spec: &ModelStorageBindingSpec) -> Result<()> {
         let Context {
+            generations: _,
             model,
             state:
                 State {
@@ -160,6 +320,12 @@ impl<'namespace, 'kube> ModelStorageBindingValidator<'namespace, 'kube> {
             .await
     }
 
+    /// Detect the drift of the referenced model and model storages.
+    ///
+    /// On drift, the binding is released and moved back to [`ModelStorageBindingState::Pending`]
+    /// so that it can be recomputed from the changed sources.
+    /// If the target model storage has been re-pointed, the data is migrated instead
+    /// (see [`ModelStorageBindingState::Migrating`]).
     #[instrument(level = Level::INFO, skip_all, err(Display))]
     pub async fn update(
         &self,
@@ -168,56 +334,581 @@ impl<'namespace, 'kube> ModelStorageBindingValidator<'namespace, 'kube> {
     ) -> Result<Option<UpdateContext>> {
         let ctx = self.load_context(&binding.spec).await?;
 
-        // Assert: model should not be changed
-        if last_status.model.as_ref() != Some(&ctx.model.spec) {
-            bail!("model should be immutable")
+        // Test changed
+        let state_last = load_last_state(last_status);
+        let drifts = find_drifts(last_status, &ctx, &state_last);
+        if drifts.is_empty() {
+            return if last_status.resources != binding.spec.resources {
+                self.resize(binding, last_status, ctx).await.map(Some)
+            } else if let ModelStorageKindSpec::ObjectStorage(_) = &ctx.state.storage_target.kind {
+                self.monitor_sync(binding, last_status, ctx).await
+            } else {
+                self.monitor_replication(binding, last_status, ctx).await
+            };
         }
 
-        // Test changed
-        let state_last = State {
-            storage_source: last_status
-                .storage_source
-                .clone()
-                .zip(last_status.storage_source_name.as_deref())
-                .zip(last_status.storage_sync_policy)
-                .map(
-                    |((storage, name), sync_policy)| ModelStorageBindingStorageSourceSpec {
-                        name,
-                        storage,
-                        sync_policy,
-                    },
-                ),
+        let message = drifts.join("; ");
+        {
+            let Self {
+                namespace, name, ..
+            } = self;
+            info!("model storage binding has been drifted ({namespace}/{name}): {message}");
+        }
+
+        // Move the data into the re-pointed model storage, keeping the last one bound
+        if state_last.storage_target_name != ctx.state.storage_target_name
+            && ModelStorageValidator::is_migratable(
+                &state_last.storage_target,
+                &ctx.state.storage_target,
+            )
+        {
+            return self
+                .begin_migration(binding, last_status, ctx, &message)
+                .await
+                .map(Some);
+        }
+
+        // Unbind
+        {
+            let ctx = load_last_context(last_status, state_last);
+            self.delete_with(ctx, &binding.spec).await?;
+        }
+
+        // Wait for (re)binding
+        let mut conditions = last_status.conditions.clone();
+        let generation = binding.metadata.generation;
+        set_condition(
+            &mut conditions,
+            ModelStorageBindingCrd::CONDITION_DRIFTED,
+            true,
+            "SourceChanged",
+            &message,
+            generation,
+        );
+        set_condition(
+            &mut conditions,
+            TYPE_READY,
+            false,
+            "SourceChanged",
+            &message,
+            generation,
+        );
+
+        Ok(Some(UpdateContext {
+            conditions,
+            deletion_policy: last_status.deletion_policy,
+            model: last_status.model.clone(),
+            model_generation: last_status.model_generation,
+            model_name: last_status.model_name.clone(),
+            migration: None,
+            owner_references: None,
+            replication: None,
+            resources: last_status.resources.clone(),
+            state: ModelStorageBindingState::Pending,
+            storage_source: last_status.storage_source.clone(),
             storage_source_binding_name: last_status.storage_source_binding_name.clone(),
+            storage_source_generation: last_status.storage_source_generation,
+            storage_source_name: last_status.storage_source_name.clone(),
             storage_source_uid: last_status.storage_source_uid.clone(),
-            storage_target: last_status.storage_target.clone().unwrap(),
-            storage_target_name: last_status.storage_target_name.as_deref().unwrap(),
-            storage_target_uid: last_status.storage_target_uid.clone().unwrap_or_default(),
+            storage_sync_policy: last_status.storage_sync_policy,
+            storage_target: last_status.storage_target.clone(),
+            storage_target_generation: last_status.storage_target_generation,
+            storage_target_name: last_status.storage_target_name.clone(),
+            storage_target_uid: last_status.storage_target_uid.clone(),
+            sync: None,
+        }))
+    }
+
+    /// Apply the changed resources (e.g. storage quota) to the bound model storage in place.
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn resize(
+        &self,
+        binding: &ModelStorageBindingCrd,
+        last_status: &ModelStorageBindingStatus,
+        ctx: Context<'_>,
+    ) -> Result<UpdateContext> {
+        // NOTE: the last quota is already allocated by this binding
+        let quota_last = last_status.resources.quota().map(|quota| quota.as_u128());
+        let quota = binding.spec.resources.quota().map(|quota| quota.as_u128());
+        let quota_delta = match (quota_last, quota) {
+            (Some(last), Some(quota)) => Byte::from_u128(quota.saturating_sub(last)),
+            (None, Some(quota)) => Byte::from_u128(quota),
+            (_, None) => None,
         };
-        if state_last == ctx.state {
-            return Ok(None);
+
+        let storage_target = self
+            .model_storage
+            .kubernetes_storage
+            .load_model_storage(ctx.state.storage_target_name)
+            .await?;
+        self.model_storage
+            .validate_model_storage_quota(&storage_target, quota_delta)
+            .await?;
+
+        {
+            let Self {
+                namespace, name, ..
+            } = self;
+            info!("resizing model storage binding ({namespace}/{name})");
         }
+        self.validate_model_storage_binding_with(ctx, binding).await
+    }
 
-        // Unbind
+    /// Report the slot and the lag of the logical replication, if any.
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn monitor_replication(
+        &self,
+        binding: &ModelStorageBindingCrd,
+        last_status: &ModelStorageBindingStatus,
+        ctx: Context<'_>,
+    ) -> Result<Option<UpdateContext>> {
+        let storage = ModelStorageBindingStorageSpec {
+            source: ctx
+                .state
+                .storage_source
+                .as_ref()
+                .map(|storage| storage.as_deref()),
+            source_binding_name: ctx.state.storage_source_binding_name.as_deref(),
+            target: &ctx.state.storage_target,
+            target_name: ctx.state.storage_target_name,
+        };
+        let replication = match self
+            .model_storage
+            .get_replication_status(storage, &ctx.model)
+            .await?
         {
-            let ctx = Context {
-                model: ModelCrd {
-                    metadata: ObjectMeta {
-                        name: last_status.model_name.clone(),
-                        ..Default::default()
-                    },
-                    spec: last_status.model.clone().unwrap(),
-                    status: None,
-                },
-                state: state_last,
-            };
+            Some(replication) => replication,
+            None => return Ok(None),
+        };
 
-            self.delete_with(ctx, &binding.spec).await?;
+        // Skip if nothing has been changed
+        if let Some(last) = last_status.replication.as_ref() {
+            if last.active == replication.active && last.lag_bytes == replication.lag_bytes {
+                return Ok(None);
+            }
+        }
+
+        let mut conditions = last_status.conditions.clone();
+        if replication.active {
+            set_condition(
+                &mut conditions,
+                ModelStorageBindingCrd::CONDITION_REPLICATING,
+                true,
+                "Streaming",
+                format!(
+                    "the replication lag is {lag} bytes",
+                    lag = replication.lag_bytes.unwrap_or_default(),
+                ),
+                binding.metadata.generation,
+            );
+        } else {
+            set_condition(
+                &mut conditions,
+                ModelStorageBindingCrd::CONDITION_REPLICATING,
+                false,
+                "SlotInactive",
+                format!(
+                    "the replication slot is not active: {name}",
+                    name = &replication.name,
+                ),
+                binding.metadata.generation,
+            );
         }
 
-        // (Re)bind
-        self.validate_model_storage_binding_with(ctx, binding)
+        Ok(Some(UpdateContext {
+            conditions,
+            replication: Some(replication),
+            ..UpdateContext::from_last_status(last_status)
+        }))
+    }
+
+    /// Report the progress of the bucket replication between the object storages, if any.
+    ///
+    /// If some objects have newly failed to be replicated, the replication rules are re-applied.
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn monitor_sync(
+        &self,
+        binding: &ModelStorageBindingCrd,
+        last_status: &ModelStorageBindingStatus,
+        ctx: Context<'_>,
+    ) -> Result<Option<UpdateContext>> {
+        let storage = ModelStorageBindingStorageSpec {
+            source: ctx
+                .state
+                .storage_source
+                .as_ref()
+                .map(|storage| storage.as_deref()),
+            source_binding_name: ctx.state.storage_source_binding_name.as_deref(),
+            target: &ctx.state.storage_target,
+            target_name: ctx.state.storage_target_name,
+        };
+        let last = last_status.sync.as_ref();
+        let now = Utc::now();
+
+        let sync = match self
+            .model_storage
+            .get_sync_metrics(storage.clone(), &ctx.model)
             .await
-            .map(Some)
+        {
+            Ok(Some(metrics)) => {
+                let failed_objects_last = last.map(|last| last.failed_objects).unwrap_or_default();
+                if metrics.failed_objects > failed_objects_last {
+                    let Self {
+                        namespace, name, ..
+                    } = self;
+                    info!("resyncing model storage binding ({namespace}/{name})");
+                    if let Err(error) = self.model_storage.resync_model(storage, &ctx.model).await {
+                        error!(
+                            "failed to resync model storage binding ({namespace}/{name}): {error}"
+                        );
+                    }
+                }
+
+                ModelStorageBindingSyncStatus {
+                    pending_objects: metrics.pending_objects,
+                    pending_bytes: metrics.pending_bytes,
+                    failed_objects: metrics.failed_objects,
+                    error_count: 0,
+                    last_error: None,
+                    last_synced: if metrics.pending_objects == 0 {
+                        Some(now)
+                    } else {
+                        last.and_then(|last| last.last_synced)
+                    },
+                    last_checked: now,
+                }
+            }
+            Ok(None) => return Ok(None),
+            Err(error) => ModelStorageBindingSyncStatus {
+                error_count: last
+                    .map(|last| last.error_count)
+                    .unwrap_or_default()
+                    .saturating_add(1),
+                last_error: Some(error.to_string()),
+                last_checked: now,
+                ..last.cloned().unwrap_or(ModelStorageBindingSyncStatus {
+                    pending_objects: 0,
+                    pending_bytes: 0,
+                    failed_objects: 0,
+                    error_count: 0,
+                    last_error: None,
+                    last_synced: None,
+                    last_checked: now,
+                })
+            },
+        };
+
+        // Skip if nothing has been changed
+        if let Some(last) = last {
+            if last.pending_objects == sync.pending_objects
+                && last.pending_bytes == sync.pending_bytes
+                && last.failed_objects == sync.failed_objects
+                && last.error_count == sync.error_count
+                && last.last_error == sync.last_error
+                && (sync.pending_objects > 0 || last.last_synced.is_some())
+            {
+                return Ok(None);
+            }
+        }
+
+        let mut conditions = last_status.conditions.clone();
+        let generation = binding.metadata.generation;
+        if let Some(error) = sync.last_error.as_ref() {
+            set_condition(
+                &mut conditions,
+                ModelStorageBindingCrd::CONDITION_SYNCED,
+                false,
+                "CheckFailed",
+                format!("failed to check the replication: {error}"),
+                generation,
+            );
+        } else if sync.failed_objects > 0 {
+            set_condition(
+                &mut conditions,
+                ModelStorageBindingCrd::CONDITION_SYNCED,
+                false,
+                "Failed",
+                format!(
+                    "{failed} objects have failed to be replicated",
+                    failed = sync.failed_objects,
+                ),
+                generation,
+            );
+        } else if sync.pending_objects > 0 {
+            set_condition(
+                &mut conditions,
+                ModelStorageBindingCrd::CONDITION_SYNCED,
+                false,
+                "Pending",
+                format!(
+                    "{pending} objects ({bytes} bytes) are pending",
+                    pending = sync.pending_objects,
+                    bytes = sync.pending_bytes,
+                ),
+                generation,
+            );
+        } else {
+            set_condition(
+                &mut conditions,
+                ModelStorageBindingCrd::CONDITION_SYNCED,
+                true,
+                "InSync",
+                "all objects have been replicated",
+                generation,
+            );
+        }
+
+        Ok(Some(UpdateContext {
+            conditions,
+            sync: Some(sync),
+            ..UpdateContext::from_last_status(last_status)
+        }))
+    }
+
+    /// Bind the model to the re-pointed model storage, and start copying the data into it.
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn begin_migration(
+        &self,
+        binding: &ModelStorageBindingCrd,
+        last_status: &ModelStorageBindingStatus,
+        ctx: Context<'_>,
+        message: &str,
+    ) -> Result<UpdateContext> {
+        self.approval()
+            .assert_approved(
+                crate::consts::NAME,
+                ApprovalOperation::StorageMigration,
+                self.approval_target(binding.metadata.generation),
+                message,
+            )
+            .await?;
+
+        let storage_target = self
+            .model_storage
+            .kubernetes_storage
+            .load_model_storage(ctx.state.storage_target_name)
+            .await?;
+        self.model_storage
+            .validate_model_storage_quota(&storage_target, binding.spec.resources.quota())
+            .await?;
+
+        let storage = ModelStorageBindingStorageSpec {
+            source: ctx
+                .state
+                .storage_source
+                .as_ref()
+                .map(|storage| storage.as_deref()),
+            source_binding_name: ctx.state.storage_source_binding_name.as_deref(),
+            target: &ctx.state.storage_target,
+            target_name: ctx.state.storage_target_name,
+        };
+        self.model_storage
+            .bind_model(binding, storage, &ctx.model)
+            .await?;
+
+        let job_name = format!(
+            "{name}-migrate-{generation}",
+            name = self.name,
+            generation = binding.metadata.generation.unwrap_or_default(),
+        );
+        self.model_storage
+            .migrate_model(
+                &job_name,
+                last_status.storage_target_name.as_deref().unwrap(),
+                last_status.storage_target.as_ref().unwrap(),
+                ctx.state.storage_target_name,
+                &ctx.state.storage_target,
+                &ctx.model,
+            )
+            .await?;
+
+        {
+            let Self {
+                namespace, name, ..
+            } = self;
+            info!("started migrating model storage binding ({namespace}/{name}): {job_name}");
+        }
+
+        let mut conditions = last_status.conditions.clone();
+        let generation = binding.metadata.generation;
+        set_condition(
+            &mut conditions,
+            ModelStorageBindingCrd::CONDITION_DRIFTED,
+            true,
+            "TargetChanged",
+            message,
+            generation,
+        );
+        set_condition(
+            &mut conditions,
+            ModelStorageBindingCrd::CONDITION_MIGRATED,
+            false,
+            "Copying",
+            format!("the data is being copied by the job: {job_name}"),
+            generation,
+        );
+        set_condition(
+            &mut conditions,
+            TYPE_READY,
+            false,
+            "Migrating",
+            message,
+            generation,
+        );
+
+        let migration = ModelStorageBindingMigrationStatus {
+            job_name,
+            phase: ModelStorageBindingMigrationPhase::Copying,
+            failed: 0,
+            target: ctx.state.storage_target,
+            target_name: ctx.state.storage_target_name.into(),
+            started_at: Utc::now(),
+        };
+        Ok(UpdateContext {
+            migration: Some(migration),
+            state: ModelStorageBindingState::Migrating,
+            conditions,
+            ..UpdateContext::from_last_status(last_status)
+        })
+    }
+
+    /// Track the migration job, and finish the migration once the copy has been verified.
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    pub async fn migrate(
+        &self,
+        binding: &ModelStorageBindingCrd,
+        last_status: &ModelStorageBindingStatus,
+    ) -> Result<Option<UpdateContext>> {
+        let migration = last_status
+            .migration
+            .as_ref()
+            .ok_or_else(|| anyhow!("migration status is missing: {name}", name = self.name))?;
+        let ctx = self.load_context(&binding.spec).await?;
+
+        // Re-pointed again while migrating
+        if ctx.state.storage_target_name != migration.target_name {
+            self.model_storage
+                .delete_migration_job(&migration.job_name)
+                .await?;
+
+            // Release the partially-copied model storage
+            {
+                let state = State {
+                    storage_source: ctx.state.storage_source.clone(),
+                    storage_source_binding_name: ctx.state.storage_source_binding_name.clone(),
+                    storage_source_uid: ctx.state.storage_source_uid.clone(),
+                    storage_target: migration.target.clone(),
+                    storage_target_name: &migration.target_name,
+                    storage_target_uid: String::default(),
+                };
+                let ctx = load_last_context(last_status, state);
+                self.delete_with(ctx, &binding.spec).await?;
+            }
+
+            let message = format!(
+                "target model storage has been changed: {name}",
+                name = ctx.state.storage_target_name,
+            );
+            return self
+                .begin_migration(binding, last_status, ctx, &message)
+                .await
+                .map(Some);
+        }
+
+        let job = match self
+            .model_storage
+            .load_migration_job(&migration.job_name)
+            .await?
+        {
+            Some(job) => job,
+            None => {
+                // the job has been deleted (e.g. to retry), so recreate it
+                self.model_storage
+                    .migrate_model(
+                        &migration.job_name,
+                        last_status.storage_target_name.as_deref().unwrap(),
+                        last_status.storage_target.as_ref().unwrap(),
+                        &migration.target_name,
+                        &migration.target,
+                        &ctx.model,
+                    )
+                    .await?;
+                return Ok(None);
+            }
+        };
+        let job_status = job.status.unwrap_or_default();
+
+        if job_status.succeeded.unwrap_or_default() > 0 {
+            // Release the last model storage
+            {
+                let ctx = load_last_context(last_status, load_last_state(last_status));
+                self.delete_with(ctx, &binding.spec).await?;
+            }
+            self.model_storage
+                .delete_migration_job(&migration.job_name)
+                .await?;
+
+            {
+                let Self {
+                    namespace, name, ..
+                } = self;
+                let job_name = &migration.job_name;
+                info!("finished migrating model storage binding ({namespace}/{name}): {job_name}");
+            }
+
+            let mut update = self
+                .validate_model_storage_binding_with(ctx, binding)
+                .await?;
+            set_condition(
+                &mut update.conditions,
+                ModelStorageBindingCrd::CONDITION_MIGRATED,
+                true,
+                "Verified",
+                "the data has been copied and verified",
+                binding.metadata.generation,
+            );
+            return Ok(Some(update));
+        }
+
+        let failed = job_status.failed.unwrap_or_default();
+        let phase = if job_status
+            .conditions
+            .unwrap_or_default()
+            .iter()
+            .any(|condition| condition.type_ == "Failed" && condition.status == "True")
+        {
+            ModelStorageBindingMigrationPhase::Failed
+        } else {
+            ModelStorageBindingMigrationPhase::Copying
+        };
+        if migration.failed == failed && migration.phase == phase {
+            return Ok(None);
+        }
+
+        let mut conditions = last_status.conditions.clone();
+        if phase == ModelStorageBindingMigrationPhase::Failed {
+            set_condition(
+                &mut conditions,
+                ModelStorageBindingCrd::CONDITION_MIGRATED,
+                false,
+                "JobFailed",
+                format!(
+                    "the migration job has failed; delete it to retry: {job_name}",
+                    job_name = &migration.job_name,
+                ),
+                binding.metadata.generation,
+            );
+        }
+
+        Ok(Some(UpdateContext {
+            migration: Some(ModelStorageBindingMigrationStatus {
+                failed,
+                phase,
+                ..migration.clone()
+            }),
+            conditions,
+            ..UpdateContext::from_last_status(last_status)
+        }))
     }
 
     #[instrument(level = Level::INFO, skip_all, err(Display))]
@@ -237,6 +928,9 @@ impl<'namespace, 'kube> ModelStorageBindingValidator<'namespace, 'kube> {
                 .map(Some)?,
             None => None,
         };
+        let storage_source_generation = storage_source
+            .as_ref()
+            .and_then(|cr| cr.metadata.generation);
         let storage_source_uid = storage_source.as_ref().and_then(|cr| cr.uid());
         let storage_source_spec =
             spec.storage
@@ -264,6 +958,11 @@ impl<'namespace, 'kube> ModelStorageBindingValidator<'namespace, 'kube> {
         let storage_target_spec = storage_target.spec;
 
         Ok(Context {
+            generations: Generations {
+                model: model.metadata.generation,
+                storage_source: storage_source_generation,
+                storage_target: storage_target.metadata.generation,
+            },
             model,
             state: State {
                 storage_source: storage_source_spec,
@@ -278,25 +977,69 @@ impl<'namespace, 'kube> ModelStorageBindingValidator<'namespace, 'kube> {
 }
 
 struct Context<'a> {
+    generations: Generations,
     model: ModelCrd,
     state: State<'a>,
 }
 
-pub(crate) struct UpdateContext {
+#[derive(Default)]
+struct Generations {
+    model: Option<i64>,
+    storage_source: Option<i64>,
+    storage_target: Option<i64>,
+}
+
+pub struct UpdateContext {
+    pub(crate) conditions: Vec<Condition>,
     pub(crate) deletion_policy: ModelStorageBindingDeletionPolicy,
     pub(crate) model: Option<ModelSpec>,
+    pub(crate) model_generation: Option<i64>,
     pub(crate) model_name: Option<String>,
+    pub(crate) migration: Option<ModelStorageBindingMigrationStatus>,
     pub(crate) owner_references: Option<Vec<OwnerReference>>,
+    pub(crate) replication: Option<ModelStorageBindingReplicationStatus>,
     pub(crate) resources: Option<ResourceRequirements>,
     pub(crate) state: ModelStorageBindingState,
     pub(crate) storage_source: Option<ModelStorageSpec>,
     pub(crate) storage_source_binding_name: Option<String>,
+    pub(crate) storage_source_generation: Option<i64>,
     pub(crate) storage_source_name: Option<String>,
     pub(crate) storage_source_uid: Option<String>,
     pub(crate) storage_sync_policy: Option<ModelStorageBindingSyncPolicy>,
     pub(crate) storage_target: Option<ModelStorageSpec>,
+    pub(crate) storage_target_generation: Option<i64>,
     pub(crate) storage_target_name: Option<String>,
     pub(crate) storage_target_uid: Option<String>,
+    pub(crate) sync: Option<ModelStorageBindingSyncStatus>,
+}
+
+impl UpdateContext {
+    /// Keep the last status as-is.
+    fn from_last_status(last_status: &ModelStorageBindingStatus) -> Self {
+        Self {
+            conditions: last_status.conditions.clone(),
+            deletion_policy: last_status.deletion_policy,
+            model: last_status.model.clone(),
+            model_generation: last_status.model_generation,
+            model_name: last_status.model_name.clone(),
+            migration: last_status.migration.clone(),
+            owner_references: None,
+            replication: last_status.replication.clone(),
+            resources: last_status.resources.clone(),
+            state: last_status.state,
+            storage_source: last_status.storage_source.clone(),
+            storage_source_binding_name: last_status.storage_source_binding_name.clone(),
+            storage_source_generation: last_status.storage_source_generation,
+            storage_source_name: last_status.storage_source_name.clone(),
+            storage_source_uid: last_status.storage_source_uid.clone(),
+            storage_sync_policy: last_status.storage_sync_policy,
+            storage_target: last_status.storage_target.clone(),
+            storage_target_generation: last_status.storage_target_generation,
+            storage_target_name: last_status.storage_target_name.clone(),
+            storage_target_uid: last_status.storage_target_uid.clone(),
+            sync: last_status.sync.clone(),
+        }
+    }
 }
 
 #[derive(PartialEq)]
@@ -308,3 +1051,93 @@ struct State<'a> {
     storage_target_name: &'a str,
     storage_target_uid: String,
 }
+
+fn load_last_state(last_status: &ModelStorageBindingStatus) -> State<'_> {
+    State {
+        storage_source: last_status
+            .storage_source
+            .clone()
+            .zip(last_status.storage_source_name.as_deref())
+            .zip(last_status.storage_sync_policy)
+            .map(
+                |((storage, name), sync_policy)| ModelStorageBindingStorageSourceSpec {
+                    name,
+                    storage,
+                    sync_policy,
+                },
+            ),
+        storage_source_binding_name: last_status.storage_source_binding_name.clone(),
+        storage_source_uid: last_status.storage_source_uid.clone(),
+        storage_target: last_status.storage_target.clone().unwrap(),
+        storage_target_name: last_status.storage_target_name.as_deref().unwrap(),
+        storage_target_uid: last_status.storage_target_uid.clone().unwrap_or_default(),
+    }
+}
+
+fn load_last_context<'a>(last_status: &ModelStorageBindingStatus, state: State<'a>) -> Context<'a> {
+    Context {
+        generations: Generations::default(),
+        model: ModelCrd {
+            metadata: ObjectMeta {
+                name: last_status.model_name.clone(),
+                ..Default::default()
+            },
+            spec: last_status.model.clone().unwrap(),
+            status: None,
+        },
+        state,
+    }
+}
+
+/// Collect the human-readable reasons of the changes since the last binding.
+fn find_drifts(
+    last_status: &ModelStorageBindingStatus,
+    ctx: &Context<'_>,
+    state_last: &State<'_>,
+) -> Vec<String> {
+    // NOTE: the generations are unknown for the bindings made by the older controllers
+    fn is_generation_changed(last: Option<i64>, current: Option<i64>) -> bool {
+        matches!((last, current), (Some(last), Some(current)) if last != current)
+    }
+
+    let mut drifts = Vec::default();
+
+    let model_name = ctx.model.name_any();
+    if last_status.model_name.as_deref() != Some(model_name.as_str())
+        || last_status.model.as_ref() != Some(&ctx.model.spec)
+        || is_generation_changed(last_status.model_generation, ctx.generations.model)
+    {
+        drifts.push(format!("model has been changed: {model_name}"));
+    }
+
+    if state_last.storage_source != ctx.state.storage_source
+        || state_last.storage_source_binding_name != ctx.state.storage_source_binding_name
+        || state_last.storage_source_uid != ctx.state.storage_source_uid
+        || is_generation_changed(
+            last_status.storage_source_generation,
+            ctx.generations.storage_source,
+        )
+    {
+        let name = ctx
+            .state
+            .storage_source
+            .as_ref()
+            .map(|spec| spec.name)
+            .unwrap_or("(none)");
+        drifts.push(format!("source model storage has been changed: {name}"));
+    }
+
+    if state_last.storage_target != ctx.state.storage_target
+        || state_last.storage_target_name != ctx.state.storage_target_name
+        || state_last.storage_target_uid != ctx.state.storage_target_uid
+        || is_generation_changed(
+            last_status.storage_target_generation,
+            ctx.generations.storage_target,
+        )
+    {
+        let name = ctx.state.storage_target_name;
+        drifts.push(format!("target model storage has been changed: {name}"));
+    }
+
+    drifts
+}
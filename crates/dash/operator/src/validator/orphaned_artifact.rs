@@ -0,0 +1,280 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, Result};
+use chrono::{Duration, Utc};
+use dash_api::{
+    model::{ModelCrd, ModelSpec},
+    model_storage_binding::ModelStorageBindingStorageSpec,
+    orphaned_artifact::{
+        OrphanedArtifactCrd, OrphanedArtifactKind, OrphanedArtifactSpec, OrphanedArtifactState,
+        OrphanedArtifactStatus,
+    },
+    storage::{object::ModelStorageObjectSpec, ModelStorageCrd, ModelStorageKindSpec},
+};
+use dash_provider::storage::{DatabaseStorageClient, ObjectStorageClient};
+use duration_string::DurationString;
+use kube::{
+    api::{DeleteParams, ListParams, ObjectMeta, Patch, PatchParams, PostParams},
+    Api, CustomResourceExt, Resource, ResourceExt,
+};
+use serde_json::json;
+use tracing::{info, instrument, Level};
+
+use super::storage::ModelStorageValidator;
+
+pub struct OrphanedArtifactValidator<'namespace, 'kube> {
+    pub model_storage: ModelStorageValidator<'namespace, 'kube>,
+}
+
+impl<'namespace, 'kube> OrphanedArtifactValidator<'namespace, 'kube> {
+    /// Report the artifacts in the model storage, which are not referenced
+    /// by any models or bindings, and delete them after the grace period.
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    pub async fn collect(&self, field_manager: &str, storage: &ModelStorageCrd) -> Result<()> {
+        let kind = match &storage.spec.kind {
+            ModelStorageKindSpec::Database(_) => OrphanedArtifactKind::Table,
+            ModelStorageKindSpec::FileSystem(_) | ModelStorageKindSpec::Kubernetes(_) => {
+                return Ok(())
+            }
+            ModelStorageKindSpec::ObjectStorage(_) => OrphanedArtifactKind::Bucket,
+        };
+        let grace_period = parse_grace_period(storage)?;
+
+        let storage_name = storage.name_any();
+        let referenced = self.load_referenced_models(&storage_name).await?;
+        let orphans: BTreeSet<_> = self
+            .list_artifacts(storage)
+            .await?
+            .into_iter()
+            .filter(|name| !referenced.contains(name))
+            .collect();
+
+        let kubernetes_storage = &self.model_storage.kubernetes_storage;
+        let api = Api::<OrphanedArtifactCrd>::namespaced(
+            kubernetes_storage.kube.clone(),
+            kubernetes_storage.namespace,
+        );
+        let lp = ListParams::default().labels(&format!(
+            "{key}={storage_name}",
+            key = OrphanedArtifactCrd::LABEL_STORAGE_NAME,
+        ));
+        let reports: BTreeMap<_, _> = api
+            .list(&lp)
+            .await?
+            .items
+            .into_iter()
+            .map(|report| (report.spec.name.clone(), report))
+            .collect();
+
+        // the artifacts are referenced again or have been deleted
+        for (name, report) in &reports {
+            if !orphans.contains(name) {
+                let dp = DeleteParams::default();
+                crate::audit::delete(&api, &report.name_any(), &dp).await?;
+            }
+        }
+
+        let now = Utc::now();
+        for name in orphans {
+            let report = match reports.get(&name) {
+                Some(report) => report.clone(),
+                None => {
+                    let pp = PostParams {
+                        dry_run: false,
+                        field_manager: Some(field_manager.into()),
+                    };
+                    let data = OrphanedArtifactCrd {
+                        metadata: ObjectMeta {
+                            name: Some(format!("{storage_name}-{name}")),
+                            namespace: Some(kubernetes_storage.namespace.into()),
+                            labels: Some(
+                                [(
+                                    OrphanedArtifactCrd::LABEL_STORAGE_NAME.into(),
+                                    storage_name.clone(),
+                                )]
+                                .into(),
+                            ),
+                            owner_references: storage
+                                .controller_owner_ref(&())
+                                .map(|owner_reference| vec![owner_reference]),
+                            ..Default::default()
+                        },
+                        spec: OrphanedArtifactSpec {
+                            kind,
+                            name: name.clone(),
+                            storage_name: storage_name.clone(),
+                        },
+                        status: None,
+                    };
+                    info!("detected an orphaned {kind} in {storage_name:?}: {name}");
+                    crate::audit::create(&api, &pp, &data).await?
+                }
+            };
+
+            let detected_at = report
+                .metadata
+                .creation_timestamp
+                .as_ref()
+                .map(|timestamp| timestamp.0)
+                .unwrap_or(now);
+            let delete_at = grace_period.map(|grace_period| detected_at + grace_period);
+            let state = match delete_at {
+                Some(delete_at) if delete_at <= now => {
+                    // NOTE: the report is removed once the artifact disappears
+                    self.delete_artifact(storage, &name).await?;
+                    OrphanedArtifactState::Deleting
+                }
+                Some(_) | None => OrphanedArtifactState::Detected,
+            };
+
+            let is_changed = report
+                .status
+                .as_ref()
+                .map(|status| status.state != state || status.delete_at != delete_at)
+                .unwrap_or(true);
+            if is_changed {
+                let crd = OrphanedArtifactCrd::api_resource();
+                let patch = Patch::Merge(json!({
+                    "apiVersion": crd.api_version,
+                    "kind": crd.kind,
+                    "status": OrphanedArtifactStatus {
+                        state,
+                        delete_at,
+                        last_updated: now,
+                    },
+                }));
+                let pp = PatchParams::apply(field_manager);
+                crate::audit::patch_status(&api, &report.name_any(), &pp, &patch).await?;
+            }
+        }
+        Ok(())
+    }
+
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    async fn load_referenced_models(&self, storage_name: &str) -> Result<BTreeSet<String>> {
+        let kubernetes_storage = &self.model_storage.kubernetes_storage;
+
+        // NOTE: the models are referenced regardless of their states
+        let api = Api::<ModelCrd>::namespaced(
+            kubernetes_storage.kube.clone(),
+            kubernetes_storage.namespace,
+        );
+        let lp = ListParams::default();
+        let mut models: BTreeSet<_> = api
+            .list(&lp)
+            .await?
+            .items
+            .into_iter()
+            .map(|model| model.name_any())
+            .collect();
+
+        models.extend(
+            kubernetes_storage
+                .load_model_storage_bindings_by_storage(storage_name)
+                .await?
+                .into_iter()
+                .filter_map(|status| status.model_name),
+        );
+        Ok(models)
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn list_artifacts(&self, storage: &ModelStorageCrd) -> Result<Vec<String>> {
+        match &storage.spec.kind {
+            ModelStorageKindSpec::Database(spec) => {
+                DatabaseStorageClient::try_new(spec)
+                    .await?
+                    .list_tables()
+                    .await
+            }
+            ModelStorageKindSpec::FileSystem(_) | ModelStorageKindSpec::Kubernetes(_) => {
+                Ok(Default::default())
+            }
+            ModelStorageKindSpec::ObjectStorage(spec) => {
+                self.load_object_storage_client(storage, spec)
+                    .await?
+                    .target()
+                    .list_buckets()
+                    .await
+            }
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip(self, storage), err(Display))]
+    async fn delete_artifact(&self, storage: &ModelStorageCrd, name: &str) -> Result<()> {
+        let kubernetes_storage = &self.model_storage.kubernetes_storage;
+
+        // NOTE: the artifact name is same as the model name
+        let model = ModelCrd {
+            metadata: ObjectMeta {
+                name: Some(name.into()),
+                namespace: Some(kubernetes_storage.namespace.into()),
+                ..Default::default()
+            },
+            spec: ModelSpec::Dynamic {},
+            status: None,
+        };
+
+        match &storage.spec.kind {
+            ModelStorageKindSpec::Database(spec) => {
+                DatabaseStorageClient::try_new(spec)
+                    .await?
+                    .get_session(&model)
+                    .delete_table()
+                    .await
+            }
+            ModelStorageKindSpec::FileSystem(_) | ModelStorageKindSpec::Kubernetes(_) => Ok(()),
+            ModelStorageKindSpec::ObjectStorage(spec) => {
+                self.load_object_storage_client(storage, spec)
+                    .await?
+                    .get_session(
+                        kubernetes_storage.kube,
+                        kubernetes_storage.namespace,
+                        &model,
+                    )
+                    .delete_bucket()
+                    .await
+            }
+        }
+    }
+
+    async fn load_object_storage_client(
+        &self,
+        storage: &ModelStorageCrd,
+        spec: &ModelStorageObjectSpec,
+    ) -> Result<ObjectStorageClient> {
+        let kubernetes_storage = &self.model_storage.kubernetes_storage;
+        let name = storage.name_any();
+        let target = ModelStorageBindingStorageSpec {
+            source: None,
+            source_binding_name: None,
+            target: spec,
+            target_name: &name,
+        };
+        ObjectStorageClient::try_new(
+            kubernetes_storage.kube,
+            kubernetes_storage.namespace,
+            Some(&storage.metadata),
+            target,
+            Some(self.model_storage.prometheus_url),
+        )
+        .await
+    }
+}
+
+fn parse_grace_period(storage: &ModelStorageCrd) -> Result<Option<Duration>> {
+    let key = ModelStorageCrd::ANNOTATION_GC_GRACE_PERIOD;
+    match storage.annotations().get(key) {
+        Some(value) => DurationString::from_str(value)
+            .map_err(|error| anyhow!("failed to parse the annotation {key:?}: {error}"))
+            .and_then(|duration| {
+                Duration::from_std(duration.into())
+                    .map_err(|error| anyhow!("failed to parse the annotation {key:?}: {error}"))
+            })
+            .map(Some),
+        None => Ok(None),
+    }
+}
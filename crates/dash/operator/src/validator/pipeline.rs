@@ -0,0 +1,368 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use anyhow::{anyhow, Result};
+use dash_api::{
+    job::{
+        DashJobCrd, DashJobPipelineState, DashJobPipelineStatus, DashJobPipelineTaskState,
+        DashJobSpec, DashJobState,
+    },
+    task::{TaskCrd, TaskDependencyFailurePolicy, TaskState},
+};
+use dash_provider::storage::KubernetesStorageClient;
+use kube::{
+    api::{ListParams, Patch, PatchParams, PostParams},
+    core::ObjectMeta,
+    Api, CustomResourceExt, ResourceExt,
+};
+use serde_json::json;
+use tracing::{info, instrument, Level};
+
+pub struct DashPipelineValidator<'namespace, 'kube> {
+    pub kubernetes_storage: KubernetesStorageClient<'namespace, 'kube>,
+}
+
+impl<'namespace, 'kube> DashPipelineValidator<'namespace, 'kube> {
+    /// Spawn the downstream jobs whose upstream jobs are finished,
+    /// and roll up the pipeline status into the root job.
+    #[instrument(level = Level::INFO, skip_all, fields(job.name = %job.name_any(), job.namespace = job.namespace()), err(Display))]
+    pub async fn schedule(
+        &self,
+        field_manager: &str,
+        job: &DashJobCrd,
+        state: DashJobState,
+    ) -> Result<()> {
+        let tasks = self.load_tasks().await?;
+        let pipeline_name = job.pipeline_name();
+        let mut jobs = self.load_jobs(&pipeline_name).await?;
+
+        // NOTE: the new state of the given job may not be applied yet
+        let mut job = job.clone();
+        if let Some(status) = job.status.as_mut() {
+            status.state = state;
+        }
+        jobs.insert(job.spec.task.clone(), job);
+
+        let root_task = match jobs
+            .values()
+            .find(|job| job.name_any() == pipeline_name)
+            .map(|job| job.spec.task.clone())
+        {
+            Some(root_task) => root_task,
+            // the root job has been already cleaned up
+            None => return Ok(()),
+        };
+        let root = &jobs[&root_task];
+        let recorded = root
+            .status
+            .as_ref()
+            .and_then(|status| status.pipeline.as_ref());
+        let pipeline = Pipeline::new(&tasks, &root_task, &jobs, recorded);
+
+        // Spawn the downstream jobs
+        for task_name in pipeline.tasks.iter() {
+            if pipeline.resolve(task_name) == Resolved::Ready {
+                self.create_job(field_manager, &pipeline_name, &tasks[*task_name], &pipeline)
+                    .await?;
+            }
+        }
+
+        // Roll up the pipeline status
+        let status = pipeline.rollup();
+        if recorded != Some(&status) {
+            self.update_pipeline_status(field_manager, &pipeline_name, status)
+                .await?;
+        }
+        Ok(())
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn create_job(
+        &self,
+        field_manager: &str,
+        pipeline_name: &str,
+        task: &TaskCrd,
+        pipeline: &Pipeline<'_>,
+    ) -> Result<()> {
+        let KubernetesStorageClient { namespace, kube } = self.kubernetes_storage;
+        let task_name = task.name_any();
+        let job_name = format!("{pipeline_name}-{task_name}");
+
+        // Pass the outputs of the upstream jobs
+        let value = task
+            .spec
+            .dependencies
+            .iter()
+            .filter_map(|dependency| pipeline.jobs.get(&dependency.name))
+            .flat_map(|job| job.spec.value.clone())
+            .collect();
+
+        let data = DashJobCrd {
+            metadata: ObjectMeta {
+                name: Some(job_name.clone()),
+                namespace: Some(namespace.into()),
+                finalizers: Some(vec![DashJobCrd::FINALIZER_NAME.into()]),
+                labels: Some(
+                    [
+                        (DashJobCrd::LABEL_PIPELINE, pipeline_name.into()),
+                        (DashJobCrd::LABEL_TARGET_TASK, task_name.clone()),
+                        (DashJobCrd::LABEL_TARGET_TASK_NAMESPACE, namespace.into()),
+                    ]
+                    .into_iter()
+                    .map(|(key, value)| (key.to_string(), value))
+                    .collect(),
+                ),
+                ..Default::default()
+            },
+            spec: DashJobSpec {
+                task: task_name.clone(),
+                value,
+            },
+            status: None,
+        };
+
+        let api = Api::<DashJobCrd>::namespaced(kube.clone(), namespace);
+        let pp = PostParams {
+            dry_run: false,
+            field_manager: Some(field_manager.into()),
+        };
+        match crate::audit::create(&api, &pp, &data).await {
+            Ok(_) => {
+                info!("spawned downstream job ({pipeline_name}): {namespace}/{job_name}");
+                Ok(())
+            }
+            // NOTE: the downstream job may be spawned by the other upstream job
+            Err(::kube::Error::Api(error)) if error.code == 409 => Ok(()),
+            Err(error) => Err(anyhow!(
+                "failed to create downstream job ({task_name} => {job_name}): {error}"
+            )),
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn load_jobs(&self, pipeline_name: &str) -> Result<BTreeMap<String, DashJobCrd>> {
+        let KubernetesStorageClient { namespace, kube } = self.kubernetes_storage;
+        let api = Api::<DashJobCrd>::namespaced(kube.clone(), namespace);
+
+        let lp = ListParams::default().labels(&format!(
+            "{key}={pipeline_name}",
+            key = DashJobCrd::LABEL_PIPELINE,
+        ));
+        let mut jobs = api.list(&lp).await?.items;
+        if let Some(root) = api.get_opt(pipeline_name).await? {
+            jobs.push(root);
+        }
+
+        Ok(jobs
+            .into_iter()
+            .map(|job| (job.spec.task.clone(), job))
+            .collect())
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn load_tasks(&self) -> Result<BTreeMap<String, TaskCrd>> {
+        let KubernetesStorageClient { namespace, kube } = self.kubernetes_storage;
+        let api = Api::<TaskCrd>::namespaced(kube.clone(), namespace);
+        let lp = ListParams::default();
+
+        Ok(api
+            .list(&lp)
+            .await?
+            .items
+            .into_iter()
+            .filter(|task| {
+                task.status
+                    .as_ref()
+                    .map(|status| status.state == TaskState::Ready)
+                    .unwrap_or_default()
+            })
+            .map(|task| (task.name_any(), task))
+            .collect())
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn update_pipeline_status(
+        &self,
+        field_manager: &str,
+        pipeline_name: &str,
+        status: DashJobPipelineStatus,
+    ) -> Result<()> {
+        let KubernetesStorageClient { namespace, kube } = self.kubernetes_storage;
+        let api = Api::<DashJobCrd>::namespaced(kube.clone(), namespace);
+        let crd = DashJobCrd::api_resource();
+
+        let state = status.state;
+        let patch = Patch::Merge(json!({
+            "apiVersion": crd.api_version,
+            "kind": crd.kind,
+            "status": {
+                "pipeline": status,
+            },
+        }));
+        let pp = PatchParams::apply(field_manager);
+        crate::audit::patch_status(&api, pipeline_name, &pp, &patch)
+            .await
+            .map(|_| info!("pipeline is {state}: {namespace}/{pipeline_name}"))
+            .map_err(|error| {
+                anyhow!("failed to update pipeline status ({namespace}/{pipeline_name}): {error}")
+            })
+    }
+}
+
+/// A pipeline run, which is the sub-DAG of the tasks downstream of the root task.
+struct Pipeline<'a> {
+    jobs: &'a BTreeMap<String, DashJobCrd>,
+    recorded: Option<&'a DashJobPipelineStatus>,
+    tasks: BTreeSet<&'a String>,
+    tasks_all: &'a BTreeMap<String, TaskCrd>,
+}
+
+impl<'a> Pipeline<'a> {
+    fn new(
+        tasks_all: &'a BTreeMap<String, TaskCrd>,
+        root_task: &'a str,
+        jobs: &'a BTreeMap<String, DashJobCrd>,
+        recorded: Option<&'a DashJobPipelineStatus>,
+    ) -> Self {
+        let mut tasks = BTreeSet::default();
+        let mut queue = VecDeque::from([root_task]);
+        while let Some(upstream) = queue.pop_front() {
+            for (name, task) in tasks_all {
+                if task
+                    .spec
+                    .dependencies
+                    .iter()
+                    .any(|dependency| dependency.name == upstream)
+                    && tasks.insert(name)
+                {
+                    queue.push_back(name.as_str());
+                }
+            }
+        }
+        if let Some((name, _)) = tasks_all.get_key_value(root_task) {
+            tasks.insert(name);
+        }
+
+        Self {
+            jobs,
+            recorded,
+            tasks,
+            tasks_all,
+        }
+    }
+
+    fn resolve(&self, task_name: &str) -> Resolved {
+        self.resolve_with(task_name, &mut BTreeSet::default())
+    }
+
+    fn resolve_with<'t>(&'t self, task_name: &'t str, visited: &mut BTreeSet<&'t str>) -> Resolved {
+        if let Some(job) = self.jobs.get(task_name) {
+            return Resolved::Job(
+                job.status
+                    .as_ref()
+                    .map(|status| status.state)
+                    .unwrap_or_default(),
+            );
+        }
+
+        // NOTE: the finished jobs may be already cleaned up, so never spawn them again
+        match self
+            .recorded
+            .and_then(|recorded| recorded.tasks.get(task_name))
+        {
+            Some(DashJobPipelineTaskState::Completed) => {
+                return Resolved::Job(DashJobState::Completed)
+            }
+            Some(DashJobPipelineTaskState::Running | DashJobPipelineTaskState::Error) => {
+                return Resolved::Job(DashJobState::Error)
+            }
+            Some(DashJobPipelineTaskState::Pending | DashJobPipelineTaskState::Skipped) | None => {
+                ()
+            }
+        }
+
+        if !visited.insert(task_name) {
+            // NOTE: the dependency cycles are rejected by the task validator
+            return Resolved::Skipped;
+        }
+
+        let task = match self.tasks_all.get(task_name) {
+            Some(task) => task,
+            None => return Resolved::Skipped,
+        };
+
+        let mut resolved = Resolved::Ready;
+        for dependency in &task.spec.dependencies {
+            // NOTE: the tasks out of the pipeline are never run by the pipeline
+            if !self.tasks.contains(&dependency.name) {
+                continue;
+            }
+
+            match self.resolve_with(&dependency.name, visited) {
+                Resolved::Job(DashJobState::Completed) => continue,
+                Resolved::Job(DashJobState::Error | DashJobState::Deleting) => {
+                    match dependency.on_failure {
+                        TaskDependencyFailurePolicy::Continue => continue,
+                        TaskDependencyFailurePolicy::Skip => return Resolved::Skipped,
+                    }
+                }
+                Resolved::Skipped => return Resolved::Skipped,
+                Resolved::Job(DashJobState::Pending | DashJobState::Running)
+                | Resolved::Ready
+                | Resolved::Waiting => resolved = Resolved::Waiting,
+            }
+        }
+        resolved
+    }
+
+    fn rollup(&self) -> DashJobPipelineStatus {
+        let tasks: BTreeMap<_, _> = self
+            .tasks
+            .iter()
+            .map(|&name| {
+                let state = match self.resolve(name) {
+                    Resolved::Job(DashJobState::Pending) => DashJobPipelineTaskState::Pending,
+                    Resolved::Job(DashJobState::Running) => DashJobPipelineTaskState::Running,
+                    Resolved::Job(DashJobState::Completed) => DashJobPipelineTaskState::Completed,
+                    Resolved::Job(DashJobState::Error | DashJobState::Deleting) => {
+                        DashJobPipelineTaskState::Error
+                    }
+                    Resolved::Ready | Resolved::Waiting => DashJobPipelineTaskState::Pending,
+                    Resolved::Skipped => DashJobPipelineTaskState::Skipped,
+                };
+                (name.clone(), state)
+            })
+            .collect();
+
+        let state = if tasks.values().any(|state| {
+            matches!(
+                state,
+                DashJobPipelineTaskState::Pending | DashJobPipelineTaskState::Running,
+            )
+        }) {
+            DashJobPipelineState::Running
+        } else if tasks.values().any(|state| {
+            matches!(
+                state,
+                DashJobPipelineTaskState::Error | DashJobPipelineTaskState::Skipped,
+            )
+        }) {
+            DashJobPipelineState::Failed
+        } else {
+            DashJobPipelineState::Completed
+        };
+
+        DashJobPipelineStatus { state, tasks }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Resolved {
+    /// The job has been spawned.
+    Job(DashJobState),
+    /// All upstream jobs are finished.
+    Ready,
+    /// Some upstream jobs are not finished yet.
+    Waiting,
+    /// Some upstream jobs have failed, or have been skipped.
+    Skipped,
+}
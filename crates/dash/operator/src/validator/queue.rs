@@ -0,0 +1,206 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, VecDeque},
+};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use dash_api::{
+    job::{DashJobCrd, DashJobQueueStatus, DashJobState},
+    queue::{DashQueueCrd, DashQueuePreemptionPolicy, DashQueueSpec},
+    task::TaskCrd,
+};
+use kube::{api::ListParams, Api, Client, ResourceExt};
+use tracing::{instrument, Level};
+
+pub struct DashQueueValidator<'kube> {
+    pub kube: &'kube Client,
+}
+
+/// Whether the job can be run now.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DashQueueAdmission {
+    /// The job is not queued, or has been admitted by its queue.
+    Admitted,
+    /// The job should wait for its turn.
+    Queued(DashJobQueueStatus),
+}
+
+impl<'kube> DashQueueValidator<'kube> {
+    #[instrument(level = Level::INFO, skip_all, fields(job.name = %job.name_any(), job.namespace = job.namespace()), err(Display))]
+    pub async fn admit(&self, job: &DashJobCrd) -> Result<DashQueueAdmission> {
+        let namespace = job.namespace().unwrap();
+        let name = job.name_any();
+
+        let current = job.status.as_ref().and_then(|status| status.queue.as_ref());
+        let (queue_name, priority) = match current {
+            Some(status) => (status.name.clone(), Some(status.priority)),
+            None => {
+                let api = Api::<TaskCrd>::namespaced(self.kube.clone(), &namespace);
+                match api
+                    .get_opt(&job.spec.task)
+                    .await?
+                    .and_then(|task| task.spec.queue)
+                {
+                    Some(queue) => (queue.name, queue.priority),
+                    None => return Ok(DashQueueAdmission::Admitted),
+                }
+            }
+        };
+
+        let api = Api::<DashQueueCrd>::all(self.kube.clone());
+        let queue = api
+            .get_opt(&queue_name)
+            .await?
+            .ok_or_else(|| anyhow!("no such queue: {queue_name}"))?;
+        let priority = priority.unwrap_or(queue.spec.default_priority);
+
+        // collect the jobs of the queue across the cluster
+        let api = Api::<DashJobCrd>::all(self.kube.clone());
+        let lp = ListParams::default();
+        let mut running: BTreeMap<String, u32> = BTreeMap::default();
+        let mut pending = vec![QueuedJob {
+            namespace: namespace.clone(),
+            name: name.clone(),
+            priority,
+            created_at: job.creation_timestamp().map(|timestamp| timestamp.0),
+        }];
+        for other in api.list(&lp).await?.items {
+            let other_namespace = other.namespace().unwrap_or_default();
+            let other_name = other.name_any();
+            if other_namespace == namespace && other_name == name {
+                continue;
+            }
+
+            let Some(status) = other.status.as_ref() else {
+                continue;
+            };
+            let Some(other_queue) = status
+                .queue
+                .as_ref()
+                .filter(|status| status.name == queue_name)
+            else {
+                continue;
+            };
+            match status.state {
+                DashJobState::Running => {
+                    *running.entry(other_namespace).or_default() += 1;
+                }
+                DashJobState::Pending if other.metadata.deletion_timestamp.is_none() => pending
+                    .push(QueuedJob {
+                        namespace: other_namespace,
+                        name: other_name,
+                        priority: other_queue.priority,
+                        created_at: other.creation_timestamp().map(|timestamp| timestamp.0),
+                    }),
+                _ => continue,
+            }
+        }
+
+        let (position, is_admitted) = order(&queue.spec, &running, pending)
+            .into_iter()
+            .enumerate()
+            .find(|(_, (job, _))| job.namespace == namespace && job.name == name)
+            .map(|(index, (_, is_admitted))| (index as u32 + 1, is_admitted))
+            .ok_or_else(|| anyhow!("failed to find the job in the queue: {queue_name}"))?;
+
+        // NOTE: the queue is recorded before running, so that the job is counted by the others
+        if is_admitted && current.is_some() {
+            Ok(DashQueueAdmission::Admitted)
+        } else {
+            Ok(DashQueueAdmission::Queued(DashJobQueueStatus {
+                name: queue_name,
+                priority,
+                position: Some(position),
+            }))
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct QueuedJob {
+    namespace: String,
+    name: String,
+    priority: i32,
+    created_at: Option<DateTime<Utc>>,
+}
+
+/// Order the pending jobs, and mark the ones which can be run now.
+///
+/// The higher priorities come first, and the jobs of the same priority are
+/// shared fairly by the namespaces, preferring the ones with less running jobs.
+fn order(
+    spec: &DashQueueSpec,
+    running: &BTreeMap<String, u32>,
+    mut pending: Vec<QueuedJob>,
+) -> Vec<(QueuedJob, bool)> {
+    pending.sort_by(|a, b| {
+        (a.created_at, &a.namespace, &a.name).cmp(&(b.created_at, &b.namespace, &b.name))
+    });
+
+    let mut groups: BTreeMap<_, BTreeMap<_, VecDeque<_>>> = BTreeMap::default();
+    for job in pending {
+        let priority = match spec.preemption {
+            DashQueuePreemptionPolicy::LowerPriority => job.priority,
+            DashQueuePreemptionPolicy::Never => 0,
+        };
+        groups
+            .entry(Reverse(priority))
+            .or_default()
+            .entry(job.namespace.clone())
+            .or_default()
+            .push_back(job);
+    }
+
+    let mut available = spec
+        .max_concurrency
+        .map(|max| max.saturating_sub(running.values().sum()));
+    let mut occupied = running.clone();
+    let mut shares = running.clone();
+
+    let mut ordered = Vec::default();
+    for mut namespaces in groups.into_values() {
+        loop {
+            let Some(namespace) = namespaces
+                .iter()
+                .min_by_key(|(namespace, jobs)| {
+                    (
+                        shares.get(*namespace).copied().unwrap_or_default(),
+                        jobs.front().map(|job| job.created_at),
+                    )
+                })
+                .map(|(namespace, _)| namespace.clone())
+            else {
+                break;
+            };
+            let Some(job) = namespaces
+                .get_mut(&namespace)
+                .and_then(|jobs| jobs.pop_front())
+            else {
+                namespaces.remove(&namespace);
+                continue;
+            };
+            if namespaces
+                .get(&namespace)
+                .is_some_and(|jobs| jobs.is_empty())
+            {
+                namespaces.remove(&namespace);
+            }
+
+            let occupied = occupied.entry(namespace.clone()).or_default();
+            let is_admitted = available != Some(0)
+                && !spec
+                    .max_concurrency_per_namespace
+                    .is_some_and(|max| *occupied >= max);
+            if is_admitted {
+                *occupied += 1;
+                if let Some(available) = available.as_mut() {
+                    *available -= 1;
+                }
+            }
+            *shares.entry(namespace).or_default() += 1;
+            ordered.push((job, is_admitted));
+        }
+    }
+    ordered
+}
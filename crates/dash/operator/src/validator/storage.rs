@@ -1,23 +1,39 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use anyhow::{anyhow, bail, Result};
+use byte_unit::Byte;
+use chrono::Utc;
 use dash_api::{
     model::{ModelCrd, ModelSpec},
     model_storage_binding::{
-        ModelStorageBindingCrd, ModelStorageBindingDeletionPolicy, ModelStorageBindingStorageSpec,
+        ModelStorageBindingCrd, ModelStorageBindingDeletionPolicy,
+        ModelStorageBindingReplicationStatus, ModelStorageBindingStorageSourceSpec,
+        ModelStorageBindingStorageSpec, ModelStorageBindingSyncPolicy,
+        ModelStorageBindingSyncPolicyConflict, ModelStorageBindingSyncPolicyPull,
+        ModelStorageBindingSyncPolicyPush, ModelStorageBindingSyncPolicyReplication,
     },
     storage::{
-        db::ModelStorageDatabaseSpec, kubernetes::ModelStorageKubernetesSpec,
-        object::ModelStorageObjectSpec, ModelStorageCrd, ModelStorageKind, ModelStorageKindSpec,
-        ModelStorageSpec, StorageResourceRequirements,
+        db::ModelStorageDatabaseSpec,
+        fs::{ModelStorageFileSystemSourceSpec, ModelStorageFileSystemSpec},
+        kubernetes::ModelStorageKubernetesSpec,
+        object::{ModelStorageObjectLifecycleRuleSpec, ModelStorageObjectSpec},
+        ModelStorageBenchmarkState, ModelStorageBenchmarkStatus, ModelStorageCrd, ModelStorageKind,
+        ModelStorageKindSpec, ModelStorageModelUsage, ModelStorageSpec, ModelStorageUsage,
+        StorageResourceRequirements,
     },
 };
 use dash_provider::storage::{
-    assert_source_is_none, assert_source_is_same, DatabaseStorageClient, KubernetesStorageClient,
-    ObjectStorageClient,
+    assert_source_is_none, assert_source_is_same, DatabaseStorageClient, FileSystemStorageClient,
+    KubernetesStorageClient, ObjectStorageClient, ObjectStorageSyncMetrics, StorageBenchmarkClient,
 };
+use dash_provider_api::data::Usage;
 use futures::TryFutureExt;
 use itertools::Itertools;
-use k8s_openapi::apimachinery::pkg::apis::meta::v1::OwnerReference;
-use kube::{api::ObjectMeta, Resource, ResourceExt};
+use k8s_openapi::{api::batch::v1::Job, apimachinery::pkg::apis::meta::v1::OwnerReference};
+use kube::{
+    api::{DeleteParams, ObjectMeta},
+    Api, Resource, ResourceExt,
+};
 use tracing::{instrument, Level};
 
 pub struct ModelStorageValidator<'namespace, 'kube> {
@@ -42,6 +58,7 @@ impl<'namespace, 'kube> ModelStorageValidator<'namespace, 'kube> {
             ModelStorageKindSpec::Database(spec) => {
                 self.validate_model_storage_database(spec).await
             }
+            ModelStorageKindSpec::FileSystem(spec) => self.validate_model_storage_file_system(spec),
             ModelStorageKindSpec::Kubernetes(spec) => self.validate_model_storage_kubernetes(spec),
             ModelStorageKindSpec::ObjectStorage(spec) => {
                 self.validate_model_storage_object(name, metadata, spec)
@@ -51,7 +68,7 @@ impl<'namespace, 'kube> ModelStorageValidator<'namespace, 'kube> {
     }
 
     #[instrument(level = Level::INFO, skip_all, err(Display))]
-    async fn validate_model_storage_conflict(
+    pub async fn validate_model_storage_conflict(
         &self,
         name: &str,
         kind: ModelStorageKind,
@@ -79,6 +96,39 @@ impl<'namespace, 'kube> ModelStorageValidator<'namespace, 'kube> {
         DatabaseStorageClient::try_new(storage).await.map(|_| None)
     }
 
+    fn validate_model_storage_file_system(
+        &self,
+        storage: &ModelStorageFileSystemSpec,
+    ) -> Result<Option<u128>> {
+        let path = storage.source.path();
+        if !path.starts_with('/') {
+            bail!("filesystem path should be absolute: {path:?}");
+        }
+
+        match &storage.source {
+            ModelStorageFileSystemSourceSpec::CephFs(spec) => {
+                if spec.monitors.is_empty() {
+                    bail!("cephfs monitors should not be empty");
+                }
+            }
+            ModelStorageFileSystemSourceSpec::HostPath(_) => (),
+            ModelStorageFileSystemSourceSpec::Nfs(spec) => {
+                if spec.server.is_empty() {
+                    bail!("nfs server should not be empty");
+                }
+            }
+        }
+
+        match &storage.capacity {
+            Some(capacity) => capacity
+                .0
+                .parse::<Byte>()
+                .map(|capacity| Some(capacity.as_u128()))
+                .map_err(|error| anyhow!("failed to parse filesystem capacity: {error}")),
+            None => Ok(None),
+        }
+    }
+
     fn validate_model_storage_kubernetes(
         &self,
         storage: &ModelStorageKubernetesSpec,
@@ -94,6 +144,8 @@ impl<'namespace, 'kube> ModelStorageValidator<'namespace, 'kube> {
         metadata: &ObjectMeta,
         storage: &ModelStorageObjectSpec,
     ) -> Result<Option<u128>> {
+        validate_lifecycle(storage.lifecycle())?;
+
         let storage = ModelStorageBindingStorageSpec {
             source: None,
             source_binding_name: None,
@@ -117,6 +169,197 @@ impl<'namespace, 'kube> ModelStorageValidator<'namespace, 'kube> {
         .await
     }
 
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    pub async fn validate_model_storage_quota(
+        &self,
+        storage: &ModelStorageCrd,
+        quota: Option<Byte>,
+    ) -> Result<()> {
+        let total_quota = match storage
+            .status
+            .as_ref()
+            .and_then(|status| status.total_quota)
+        {
+            Some(total_quota) => total_quota,
+            None => return Ok(()),
+        };
+
+        let name = storage.name_any();
+        let allocated: u128 = self
+            .load_model_storage_bound_models(&name)
+            .await?
+            .into_values()
+            .flatten()
+            .sum();
+        let requested = quota.map(|quota| quota.as_u128()).unwrap_or_default();
+
+        if allocated.saturating_add(requested) > total_quota {
+            bail!(
+                "model storage quota exceeded ({name}): requested {requested} bytes, but only {available} of {total_quota} bytes are available",
+                available = total_quota.saturating_sub(allocated),
+            )
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Run the benchmark of the model storage if requested, and collect its results.
+    ///
+    /// Return the updated benchmark status, if changed.
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    pub(crate) async fn benchmark(
+        &self,
+        storage: &ModelStorageCrd,
+    ) -> Result<Option<ModelStorageBenchmarkStatus>> {
+        let KubernetesStorageClient { kube, namespace } = self.kubernetes_storage;
+        let client = StorageBenchmarkClient { namespace, kube };
+
+        let is_enabled = storage
+            .annotations()
+            .get(ModelStorageCrd::ANNOTATION_BENCHMARK)
+            .is_some_and(|value| value == "true");
+        let generation = storage.metadata.generation;
+        let last = storage
+            .status
+            .as_ref()
+            .and_then(|status| status.benchmark.as_ref());
+
+        match last {
+            Some(last) if last.state == ModelStorageBenchmarkState::Running => {
+                let results = match client.load_results(&last.job_name).await? {
+                    Some(results) => results,
+                    None => return Ok(None),
+                };
+                client.delete_job(&last.job_name).await?;
+
+                let (state, message, results) = match results {
+                    Ok(results) => (ModelStorageBenchmarkState::Completed, None, results),
+                    Err(error) => (
+                        ModelStorageBenchmarkState::Failed,
+                        Some(error.to_string()),
+                        Vec::default(),
+                    ),
+                };
+                Ok(Some(ModelStorageBenchmarkStatus {
+                    state,
+                    message,
+                    results,
+                    completed_timestamp: Some(Utc::now()),
+                    ..last.clone()
+                }))
+            }
+            Some(last) if !is_enabled || last.generation == generation => Ok(None),
+            None if !is_enabled => Ok(None),
+            Some(_) | None => {
+                let now = Utc::now();
+                let job_name = format!(
+                    "{name}-benchmark-{timestamp}",
+                    name = storage.name_any(),
+                    timestamp = now.timestamp(),
+                );
+                if !client.create_job(&job_name, storage).await? {
+                    return Ok(None);
+                }
+
+                Ok(Some(ModelStorageBenchmarkStatus {
+                    state: ModelStorageBenchmarkState::Running,
+                    generation,
+                    job_name,
+                    message: None,
+                    results: Vec::default(),
+                    started_timestamp: now,
+                    completed_timestamp: None,
+                }))
+            }
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    pub(crate) async fn get_usage(&self, storage: &ModelStorageCrd) -> Result<ModelStorageUsage> {
+        let name = storage.name_any();
+        let mut models: BTreeMap<_, _> = self
+            .load_model_storage_bound_models(&name)
+            .await?
+            .into_iter()
+            .map(|(model_name, quota)| {
+                let usage = ModelStorageModelUsage {
+                    quota,
+                    bytes: None,
+                    objects: None,
+                };
+                (model_name, usage)
+            })
+            .collect();
+
+        match &storage.spec.kind {
+            ModelStorageKindSpec::Database(spec) => {
+                let client = DatabaseStorageClient::try_new(spec).await?;
+                for (model_name, usage) in &mut models {
+                    let model = self.kubernetes_storage.load_model(model_name).await?;
+                    if let Some(Usage { bytes, objects }) =
+                        client.get_session(&model).get_usage().await?
+                    {
+                        usage.bytes = Some(bytes.as_u128());
+                        usage.objects = objects;
+                    }
+                }
+            }
+            ModelStorageKindSpec::FileSystem(_) | ModelStorageKindSpec::Kubernetes(_) => (),
+            ModelStorageKindSpec::ObjectStorage(spec) => {
+                let target = ModelStorageBindingStorageSpec {
+                    source: None,
+                    source_binding_name: None,
+                    target: spec,
+                    target_name: &name,
+                };
+                let mut buckets = ObjectStorageClient::try_new(
+                    self.kubernetes_storage.kube,
+                    self.kubernetes_storage.namespace,
+                    Some(&storage.metadata),
+                    target,
+                    Some(self.prometheus_url),
+                )
+                .await?
+                .target()
+                .get_usage_buckets()
+                .await?;
+
+                // NOTE: the bucket name is same as the model name
+                for (model_name, usage) in &mut models {
+                    if let Some(Usage { bytes, objects }) = buckets.remove(model_name) {
+                        usage.bytes = Some(bytes.as_u128());
+                        usage.objects = objects;
+                    }
+                }
+            }
+        }
+
+        Ok(ModelStorageUsage {
+            allocated: models.values().filter_map(|usage| usage.quota).sum(),
+            used: models.values().filter_map(|usage| usage.bytes).sum(),
+            models,
+        })
+    }
+
+    /// Return the models bound to the given model storage, with their requested quotas.
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    async fn load_model_storage_bound_models(
+        &self,
+        storage_name: &str,
+    ) -> Result<BTreeMap<String, Option<u128>>> {
+        Ok(self
+            .kubernetes_storage
+            .load_model_storage_bindings_by_storage(storage_name)
+            .await?
+            .into_iter()
+            .filter(|binding| binding.storage_target_name.as_deref() == Some(storage_name))
+            .filter_map(|binding| {
+                let quota = binding.resources.quota().map(|quota| quota.as_u128());
+                binding.model_name.map(|model_name| (model_name, quota))
+            })
+            .collect())
+    }
+
     #[instrument(level = Level::INFO, skip_all, err(Display))]
     pub(crate) async fn bind_model(
         &self,
@@ -124,15 +367,41 @@ impl<'namespace, 'kube> ModelStorageValidator<'namespace, 'kube> {
         storage: ModelStorageBindingStorageSpec<'_, &ModelStorageSpec>,
         model: &ModelCrd,
     ) -> Result<()> {
+        if let Some(source) = &storage.source {
+            if source.sync_policy.replication == ModelStorageBindingSyncPolicyReplication::Logical
+                && !matches!(&storage.target.kind, ModelStorageKindSpec::Database(_))
+            {
+                bail!("Logical replication is supported only for Database")
+            }
+        }
+
         match &storage.target.kind {
             ModelStorageKindSpec::Database(spec) => {
+                let storage =
+                    ModelStorageBindingStorageSpec {
+                        source: assert_source_is_same(storage.source, "Database", |source| {
+                            match &source.kind {
+                                ModelStorageKindSpec::Database(source) => Ok(source),
+                                ModelStorageKindSpec::FileSystem(_) => Err("FileSystem"),
+                                ModelStorageKindSpec::Kubernetes(_) => Err("Kubernetes"),
+                                ModelStorageKindSpec::ObjectStorage(_) => Err("ObjectStorage"),
+                            }
+                        })?,
+                        source_binding_name: storage.source_binding_name,
+                        target: spec,
+                        target_name: storage.target_name,
+                    };
+                self.bind_model_to_database(storage, model).await
+            }
+            ModelStorageKindSpec::FileSystem(spec) => {
                 let storage = ModelStorageBindingStorageSpec {
-                    source: assert_source_is_none(storage.source, "Database")?,
+                    source: assert_source_is_none(storage.source, "FileSystem")?,
                     source_binding_name: storage.source_binding_name,
                     target: spec,
                     target_name: storage.target_name,
                 };
-                self.bind_model_to_database(storage, model).await
+                self.bind_model_to_file_system(binding, storage, model)
+                    .await
             }
             ModelStorageKindSpec::Kubernetes(spec) => {
                 let storage = ModelStorageBindingStorageSpec {
@@ -148,6 +417,7 @@ impl<'namespace, 'kube> ModelStorageValidator<'namespace, 'kube> {
                     source: assert_source_is_same(storage.source, "ObjectStorage", |source| {
                         match &source.kind {
                             ModelStorageKindSpec::Database(_) => Err("Database"),
+                            ModelStorageKindSpec::FileSystem(_) => Err("FileSystem"),
                             ModelStorageKindSpec::Kubernetes(_) => Err("Kubernetes"),
                             ModelStorageKindSpec::ObjectStorage(source) => Ok(source),
                         }
@@ -167,10 +437,56 @@ impl<'namespace, 'kube> ModelStorageValidator<'namespace, 'kube> {
         storage: ModelStorageBindingStorageSpec<'_, &ModelStorageDatabaseSpec>,
         model: &ModelCrd,
     ) -> Result<()> {
-        DatabaseStorageClient::try_new(storage.target)
+        if let Some(source) = &storage.source {
+            match source.sync_policy.replication {
+                ModelStorageBindingSyncPolicyReplication::Native => {
+                    bail!("Sync to Database is supported only with the logical replication")
+                }
+                ModelStorageBindingSyncPolicyReplication::Logical => {
+                    if source.sync_policy.pull != ModelStorageBindingSyncPolicyPull::Always {
+                        bail!("Logical replication requires pulling from the source always")
+                    }
+                    if DatabaseStorageClient::infer_url(source.storage)
+                        == DatabaseStorageClient::infer_url(storage.target)
+                    {
+                        bail!("Logical replication requires another database than the source")
+                    }
+                }
+            }
+        }
+
+        let target = DatabaseStorageClient::try_new(storage.target)
             .await?
+            .get_session(model);
+        target.update_table().await?;
+
+        if let Some(source) = &storage.source {
+            let name = target.get_replication_name(storage.target_name);
+            DatabaseStorageClient::try_new(source.storage)
+                .await?
+                .get_session(model)
+                .create_publication(&name)
+                .await?;
+            target.create_subscription(&name, source.storage).await?;
+        }
+        Ok(())
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn bind_model_to_file_system(
+        &self,
+        binding: &ModelStorageBindingCrd,
+        storage: ModelStorageBindingStorageSpec<'_, &ModelStorageFileSystemSpec>,
+        model: &ModelCrd,
+    ) -> Result<()> {
+        let KubernetesStorageClient { kube, namespace } = self.kubernetes_storage;
+
+        let owner_references = get_owner_references(binding)?;
+        let quota = binding.spec.resources.quota();
+
+        FileSystemStorageClient::new(kube, namespace, storage)
             .get_session(model)
-            .update_table()
+            .create_volume(owner_references, quota)
             .await
     }
 
@@ -195,27 +511,14 @@ impl<'namespace, 'kube> ModelStorageValidator<'namespace, 'kube> {
     ) -> Result<()> {
         let KubernetesStorageClient { kube, namespace } = self.kubernetes_storage;
 
-        let owner_references = {
-            let name = binding.name_any();
-            let uid = binding
-                .uid()
-                .ok_or_else(|| anyhow!("failed to get model storage binding uid: {name}"))?;
-
-            vec![OwnerReference {
-                api_version: ModelStorageBindingCrd::api_version(&()).into(),
-                block_owner_deletion: Some(true),
-                controller: None,
-                kind: ModelStorageBindingCrd::kind(&()).into(),
-                name,
-                uid,
-            }]
-        };
+        let owner_references = get_owner_references(binding)?;
         let quota = binding.spec.resources.quota();
 
+        let lifecycle = storage.target.lifecycle();
         ObjectStorageClient::try_new(kube, namespace, None, storage, Some(self.prometheus_url))
             .await?
             .get_session(kube, namespace, model)
-            .create_bucket(owner_references, quota)
+            .create_bucket(owner_references, quota, lifecycle)
             .await
     }
 
@@ -233,6 +536,7 @@ impl<'namespace, 'kube> ModelStorageValidator<'namespace, 'kube> {
                         source: assert_source_is_same(storage.source, "Database", |source| {
                             match &source.kind {
                                 ModelStorageKindSpec::Database(source) => Ok(source),
+                                ModelStorageKindSpec::FileSystem(_) => Err("FileSystem"),
                                 ModelStorageKindSpec::Kubernetes(_) => Err("Kubernetes"),
                                 ModelStorageKindSpec::ObjectStorage(_) => Err("ObjectStorage"),
                             }
@@ -244,11 +548,29 @@ impl<'namespace, 'kube> ModelStorageValidator<'namespace, 'kube> {
                 self.unbind_model_to_database(storage, model, deletion_policy)
                     .await
             }
+            ModelStorageKindSpec::FileSystem(spec) => {
+                let storage = ModelStorageBindingStorageSpec {
+                    source: assert_source_is_same(storage.source, "FileSystem", |source| {
+                        match &source.kind {
+                            ModelStorageKindSpec::Database(_) => Err("Database"),
+                            ModelStorageKindSpec::FileSystem(source) => Ok(source),
+                            ModelStorageKindSpec::Kubernetes(_) => Err("Kubernetes"),
+                            ModelStorageKindSpec::ObjectStorage(_) => Err("ObjectStorage"),
+                        }
+                    })?,
+                    source_binding_name: storage.source_binding_name,
+                    target: spec,
+                    target_name: storage.target_name,
+                };
+                self.unbind_model_to_file_system(storage, model, deletion_policy)
+                    .await
+            }
             ModelStorageKindSpec::Kubernetes(spec) => {
                 let storage = ModelStorageBindingStorageSpec {
                     source: assert_source_is_same(storage.source, "Kubernetes", |source| {
                         match &source.kind {
                             ModelStorageKindSpec::Database(_) => Err("Database"),
+                            ModelStorageKindSpec::FileSystem(_) => Err("FileSystem"),
                             ModelStorageKindSpec::Kubernetes(source) => Ok(source),
                             ModelStorageKindSpec::ObjectStorage(_) => Err("ObjectStorage"),
                         }
@@ -264,6 +586,7 @@ impl<'namespace, 'kube> ModelStorageValidator<'namespace, 'kube> {
                     source: assert_source_is_same(storage.source, "ObjectStorage", |source| {
                         match &source.kind {
                             ModelStorageKindSpec::Database(_) => Err("Database"),
+                            ModelStorageKindSpec::FileSystem(_) => Err("FileSystem"),
                             ModelStorageKindSpec::Kubernetes(_) => Err("Kubernetes"),
                             ModelStorageKindSpec::ObjectStorage(source) => Ok(source),
                         }
@@ -285,18 +608,120 @@ impl<'namespace, 'kube> ModelStorageValidator<'namespace, 'kube> {
         model: &ModelCrd,
         deletion_policy: ModelStorageBindingDeletionPolicy,
     ) -> Result<()> {
+        let target = DatabaseStorageClient::try_new(storage.target)
+            .await?
+            .get_session(model);
+
+        // NOTE: the replication slot keeps the WAL of the source until the subscription is dropped
+        if let Some(source) = storage.source.as_ref().filter(|source| {
+            source.sync_policy.replication == ModelStorageBindingSyncPolicyReplication::Logical
+        }) {
+            let name = target.get_replication_name(storage.target_name);
+            target.delete_subscription(&name).await?;
+            DatabaseStorageClient::try_new(source.storage)
+                .await?
+                .get_session(model)
+                .delete_publication(&name)
+                .await?;
+        }
+
         match deletion_policy {
-            ModelStorageBindingDeletionPolicy::Delete => {
-                DatabaseStorageClient::try_new(storage.target)
-                    .await?
-                    .get_session(model)
-                    .delete_table()
-                    .await
-            }
+            ModelStorageBindingDeletionPolicy::Delete => target.delete_table().await,
             ModelStorageBindingDeletionPolicy::Retain => Ok(()),
         }
     }
 
+    /// Inspect the logical replication of the bound model, if any.
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    pub(crate) async fn get_replication_status(
+        &self,
+        storage: ModelStorageBindingStorageSpec<'_, &ModelStorageSpec>,
+        model: &ModelCrd,
+    ) -> Result<Option<ModelStorageBindingReplicationStatus>> {
+        let (source, target) = match (storage.source, &storage.target.kind) {
+            (Some(source), ModelStorageKindSpec::Database(target))
+                if source.sync_policy.replication
+                    == ModelStorageBindingSyncPolicyReplication::Logical =>
+            {
+                match &source.storage.kind {
+                    ModelStorageKindSpec::Database(source) => (source, target),
+                    _ => return Ok(None),
+                }
+            }
+            _ => return Ok(None),
+        };
+
+        let name = DatabaseStorageClient::try_new(target)
+            .await?
+            .get_session(model)
+            .get_replication_name(storage.target_name);
+        DatabaseStorageClient::try_new(source)
+            .await?
+            .get_session(model)
+            .get_replication_status(&name)
+            .await
+            .map(Some)
+    }
+
+    /// Inspect the bucket replication between the bound object storages, if any.
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    pub(crate) async fn get_sync_metrics(
+        &self,
+        storage: ModelStorageBindingStorageSpec<'_, &ModelStorageSpec>,
+        model: &ModelCrd,
+    ) -> Result<Option<ObjectStorageSyncMetrics>> {
+        let KubernetesStorageClient { kube, namespace } = self.kubernetes_storage;
+
+        let storage = match select_object_storages(storage) {
+            Some(storage) => storage,
+            None => return Ok(None),
+        };
+        ObjectStorageClient::try_new(kube, namespace, None, storage, Some(self.prometheus_url))
+            .await?
+            .get_session(kube, namespace, model)
+            .get_sync_metrics()
+            .await
+    }
+
+    /// Re-apply the bucket replication rules between the bound object storages.
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    pub(crate) async fn resync_model(
+        &self,
+        storage: ModelStorageBindingStorageSpec<'_, &ModelStorageSpec>,
+        model: &ModelCrd,
+    ) -> Result<()> {
+        let KubernetesStorageClient { kube, namespace } = self.kubernetes_storage;
+
+        let storage = match select_object_storages(storage) {
+            Some(storage) => storage,
+            None => return Ok(()),
+        };
+        ObjectStorageClient::try_new(kube, namespace, None, storage, Some(self.prometheus_url))
+            .await?
+            .get_session(kube, namespace, model)
+            .resync_bucket()
+            .await
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn unbind_model_to_file_system(
+        &self,
+        storage: ModelStorageBindingStorageSpec<'_, &ModelStorageFileSystemSpec>,
+        model: &ModelCrd,
+        deletion_policy: ModelStorageBindingDeletionPolicy,
+    ) -> Result<()> {
+        let KubernetesStorageClient { kube, namespace } = self.kubernetes_storage;
+
+        let delete_data = match deletion_policy {
+            ModelStorageBindingDeletionPolicy::Delete => true,
+            ModelStorageBindingDeletionPolicy::Retain => false,
+        };
+        FileSystemStorageClient::new(kube, namespace, storage)
+            .get_session(model)
+            .delete_volume(delete_data)
+            .await
+    }
+
     fn unbind_model_to_kubernetes(
         &self,
         storage: ModelStorageBindingStorageSpec<'_, &ModelStorageKubernetesSpec>,
@@ -334,6 +759,106 @@ impl<'namespace, 'kube> ModelStorageValidator<'namespace, 'kube> {
         }
     }
 
+    /// Return whether the data of a model can be copied between the given model storages.
+    pub(crate) const fn is_migratable(
+        source: &ModelStorageSpec,
+        target: &ModelStorageSpec,
+    ) -> bool {
+        matches!(
+            (&source.kind, &target.kind),
+            (
+                ModelStorageKindSpec::Database(_),
+                ModelStorageKindSpec::Database(_)
+            ) | (
+                ModelStorageKindSpec::ObjectStorage(_),
+                ModelStorageKindSpec::ObjectStorage(_)
+            )
+        )
+    }
+
+    /// Create a job copying the data of the model from the source into the target model storage.
+    ///
+    /// The job verifies the copied data by itself, so that the succeeded job means the verified copy.
+    #[instrument(level = Level::INFO, skip(self, source, target, model), err(Display))]
+    pub(crate) async fn migrate_model(
+        &self,
+        job_name: &str,
+        source_name: &str,
+        source: &ModelStorageSpec,
+        target_name: &str,
+        target: &ModelStorageSpec,
+        model: &ModelCrd,
+    ) -> Result<()> {
+        let KubernetesStorageClient { kube, namespace } = self.kubernetes_storage;
+
+        match (&source.kind, &target.kind) {
+            (ModelStorageKindSpec::Database(source), ModelStorageKindSpec::Database(target)) => {
+                DatabaseStorageClient::migrate_table(
+                    kube, namespace, job_name, source, target, model,
+                )
+                .await
+            }
+            (
+                ModelStorageKindSpec::ObjectStorage(source),
+                ModelStorageKindSpec::ObjectStorage(target),
+            ) => {
+                let storage = ModelStorageBindingStorageSpec {
+                    source: Some(ModelStorageBindingStorageSourceSpec {
+                        name: source_name,
+                        storage: source,
+                        // the data is copied by the job only once
+                        sync_policy: ModelStorageBindingSyncPolicy {
+                            pull: ModelStorageBindingSyncPolicyPull::Never,
+                            push: ModelStorageBindingSyncPolicyPush::Never,
+                            replication: ModelStorageBindingSyncPolicyReplication::Native,
+                            conflict: ModelStorageBindingSyncPolicyConflict::LastWriterWins,
+                        },
+                    }),
+                    source_binding_name: None,
+                    target,
+                    target_name,
+                };
+                ObjectStorageClient::try_new(
+                    kube,
+                    namespace,
+                    None,
+                    storage,
+                    Some(self.prometheus_url),
+                )
+                .await?
+                .get_session(kube, namespace, model)
+                .migrate_bucket(job_name)
+                .await
+            }
+            (source, target) => bail!(
+                "cannot migrate model storage: {source} => {target}",
+                source = source.to_kind(),
+                target = target.to_kind(),
+            ),
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub(crate) async fn load_migration_job(&self, job_name: &str) -> Result<Option<Job>> {
+        let KubernetesStorageClient { kube, namespace } = self.kubernetes_storage;
+        let api = Api::<Job>::namespaced(kube.clone(), namespace);
+        api.get_opt(job_name)
+            .await
+            .map_err(|error| anyhow!("failed to get migration job ({job_name}): {error}"))
+    }
+
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub(crate) async fn delete_migration_job(&self, job_name: &str) -> Result<()> {
+        let KubernetesStorageClient { kube, namespace } = self.kubernetes_storage;
+        let api = Api::<Job>::namespaced(kube.clone(), namespace);
+        let dp = DeleteParams::background();
+        match crate::audit::delete(&api, job_name, &dp).await {
+            Ok(_) => Ok(()),
+            Err(::kube::Error::Api(error)) if error.code == 404 => Ok(()),
+            Err(error) => bail!("failed to delete migration job ({job_name}): {error}"),
+        }
+    }
+
     #[instrument(level = Level::INFO, skip_all, err(Display))]
     pub async fn delete(&self, crd: &ModelStorageCrd) -> Result<()> {
         let bindings = self
@@ -348,3 +873,80 @@ impl<'namespace, 'kube> ModelStorageValidator<'namespace, 'kube> {
         }
     }
 }
+
+fn get_owner_references(binding: &ModelStorageBindingCrd) -> Result<Vec<OwnerReference>> {
+    let name = binding.name_any();
+    let uid = binding
+        .uid()
+        .ok_or_else(|| anyhow!("failed to get model storage binding uid: {name}"))?;
+
+    Ok(vec![OwnerReference {
+        api_version: ModelStorageBindingCrd::api_version(&()).into(),
+        block_owner_deletion: Some(true),
+        controller: None,
+        kind: ModelStorageBindingCrd::kind(&()).into(),
+        name,
+        uid,
+    }])
+}
+
+fn validate_lifecycle(lifecycle: &[ModelStorageObjectLifecycleRuleSpec]) -> Result<()> {
+    let mut names = BTreeSet::default();
+    for rule in lifecycle {
+        let name = &rule.name;
+        if name.is_empty() {
+            bail!("lifecycle rule name should not be empty");
+        }
+        if !names.insert(name) {
+            bail!("lifecycle rule name is duplicated: {name:?}");
+        }
+        if rule.is_empty() {
+            bail!("lifecycle rule should have at least one action: {name:?}");
+        }
+
+        let days = [
+            rule.transition.as_ref().map(|transition| transition.days),
+            rule.expiration_days,
+            rule.noncurrent_version_expiration_days,
+        ];
+        if days.into_iter().flatten().any(|days| days == 0) {
+            bail!("lifecycle rule days should be positive: {name:?}");
+        }
+
+        if let Some(transition) = &rule.transition {
+            if transition.storage_class.is_empty() {
+                bail!("lifecycle rule transition storage class should not be empty: {name:?}");
+            }
+            if rule
+                .expiration_days
+                .is_some_and(|expiration_days| expiration_days <= transition.days)
+            {
+                bail!("lifecycle rule should expire the objects after the transition: {name:?}");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Select the bound object storages, only if both of them are object storages.
+fn select_object_storages<'name, 'storage>(
+    storage: ModelStorageBindingStorageSpec<'name, &'storage ModelStorageSpec>,
+) -> Option<ModelStorageBindingStorageSpec<'name, &'storage ModelStorageObjectSpec>> {
+    let source = storage.source?;
+    match (&source.storage.kind, &storage.target.kind) {
+        (
+            ModelStorageKindSpec::ObjectStorage(source_storage),
+            ModelStorageKindSpec::ObjectStorage(target),
+        ) => Some(ModelStorageBindingStorageSpec {
+            source: Some(ModelStorageBindingStorageSourceSpec {
+                name: source.name,
+                storage: source_storage,
+                sync_policy: source.sync_policy,
+            }),
+            source_binding_name: storage.source_binding_name,
+            target,
+            target_name: storage.target_name,
+        }),
+        _ => None,
+    }
+}
@@ -1,8 +1,28 @@
-use anyhow::{bail, Result};
-use dash_api::{model::ModelFieldKindNativeSpec, task::TaskSpec};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use cron::Schedule;
+use dash_api::{
+    job::{DashJobCrd, DashJobSpec, DashJobState},
+    model::ModelFieldKindNativeSpec,
+    queue::DashQueueCrd,
+    task::{
+        TaskConcurrencyPolicy, TaskCrd, TaskDependencySpec, TaskScheduleSpec, TaskScheduleStatus,
+        TaskSpec,
+    },
+};
 use dash_provider::{client::TaskActorClient, storage::KubernetesStorageClient};
-use kube::Client;
-use tracing::{instrument, Level};
+use kube::{
+    api::{DeleteParams, ListParams, PostParams},
+    core::ObjectMeta,
+    Api, Client, ResourceExt,
+};
+use tracing::{info, instrument, Level};
 
 use super::model::ModelValidator;
 
@@ -15,6 +35,7 @@ impl<'namespace, 'kube> TaskValidator<'namespace, 'kube> {
     #[instrument(level = Level::INFO, skip_all, err(Display))]
     pub async fn validate_task(
         &self,
+        name: &str,
         spec: TaskSpec,
     ) -> Result<TaskSpec<ModelFieldKindNativeSpec>> {
         let model_validator = ModelValidator {
@@ -30,6 +51,250 @@ impl<'namespace, 'kube> TaskValidator<'namespace, 'kube> {
             bail!("failed to validate task actor: {e}");
         }
 
-        Ok(TaskSpec { input, actor })
+        let dependencies = spec.dependencies;
+        self.validate_dependencies(name, &dependencies).await?;
+
+        let queue = spec.queue;
+        if let Some(queue) = queue.as_ref() {
+            let api = Api::<DashQueueCrd>::all(self.kube.clone());
+            if api.get_opt(&queue.name).await?.is_none() {
+                bail!("no such queue: {}", &queue.name);
+            }
+        }
+
+        let schedule = spec.schedule;
+        if let Some(schedule) = schedule.as_ref() {
+            parse_schedule(schedule)?;
+        }
+
+        Ok(TaskSpec {
+            input,
+            actor,
+            dependencies,
+            queue,
+            schedule,
+        })
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn validate_dependencies(
+        &self,
+        name: &str,
+        dependencies: &[TaskDependencySpec],
+    ) -> Result<()> {
+        if dependencies.is_empty() {
+            return Ok(());
+        }
+        if dependencies
+            .iter()
+            .any(|dependency| dependency.name == name)
+        {
+            bail!("task {name:?} should not depend on itself");
+        }
+
+        let api = Api::<TaskCrd>::namespaced(self.kube.clone(), self.namespace);
+        let lp = ListParams::default();
+        let mut graph: BTreeMap<_, _> = api
+            .list(&lp)
+            .await?
+            .items
+            .into_iter()
+            .map(|task| {
+                (
+                    task.name_any(),
+                    task.spec
+                        .dependencies
+                        .into_iter()
+                        .map(|dependency| dependency.name)
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+        graph.insert(
+            name.into(),
+            dependencies
+                .iter()
+                .map(|dependency| dependency.name.clone())
+                .collect(),
+        );
+
+        // Walk through the upstream tasks to find a dependency cycle
+        let mut visited = BTreeSet::default();
+        let mut stack: Vec<_> = graph[name].iter().collect();
+        while let Some(upstream) = stack.pop() {
+            if upstream == name {
+                bail!("task {name:?} has a dependency cycle");
+            }
+            if visited.insert(upstream) {
+                stack.extend(graph.get(upstream).into_iter().flatten());
+            }
+        }
+        Ok(())
     }
+
+    /// Spawn the scheduled job if it is due, and return the updated schedule status.
+    #[instrument(level = Level::INFO, skip_all, fields(task.name = %task.name_any()), err(Display))]
+    pub async fn schedule(
+        &self,
+        field_manager: &str,
+        task: &TaskCrd,
+    ) -> Result<Option<TaskScheduleStatus>> {
+        let spec = match task.spec.schedule.as_ref() {
+            Some(spec) => spec,
+            None => return Ok(None),
+        };
+        let (schedule, tz) = parse_schedule(spec)?;
+
+        let mut status = task
+            .status
+            .as_ref()
+            .and_then(|status| status.schedule.clone())
+            .unwrap_or_default();
+        if spec.suspend {
+            status.next_schedule_time = None;
+            return Ok(Some(status));
+        }
+
+        let now = Utc::now();
+        let since = status
+            .last_schedule_time
+            .or_else(|| task.creation_timestamp().map(|timestamp| timestamp.0))
+            .unwrap_or(now);
+
+        // NOTE: only the latest one of the missed schedules is run
+        let due = schedule
+            .after(&since.with_timezone(&tz))
+            .take_while(|time| *time <= now)
+            .last()
+            .map(|time| time.with_timezone(&Utc));
+        if let Some(time) = due {
+            status.last_schedule_time = Some(time);
+            if let Some(job_name) = self
+                .spawn_scheduled_job(field_manager, task, spec, time)
+                .await?
+            {
+                status.last_job_name = Some(job_name);
+            }
+        }
+
+        status.next_schedule_time = schedule
+            .after(&now.with_timezone(&tz))
+            .next()
+            .map(|time| time.with_timezone(&Utc));
+        Ok(Some(status))
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn spawn_scheduled_job(
+        &self,
+        field_manager: &str,
+        task: &TaskCrd,
+        spec: &TaskScheduleSpec,
+        time: DateTime<Utc>,
+    ) -> Result<Option<String>> {
+        let namespace = self.namespace;
+        let task_name = task.name_any();
+        let api = Api::<DashJobCrd>::namespaced(self.kube.clone(), namespace);
+
+        let lp = ListParams::default().labels(&format!(
+            "{key}={task_name}",
+            key = DashJobCrd::LABEL_TARGET_TASK,
+        ));
+        let active_jobs: Vec<_> = api
+            .list(&lp)
+            .await?
+            .items
+            .into_iter()
+            .filter(|job| {
+                matches!(
+                    job.status
+                        .as_ref()
+                        .map(|status| status.state)
+                        .unwrap_or_default(),
+                    DashJobState::Pending | DashJobState::Running,
+                )
+            })
+            .collect();
+
+        match spec.concurrency_policy {
+            TaskConcurrencyPolicy::Allow => (),
+            TaskConcurrencyPolicy::Forbid => {
+                if !active_jobs.is_empty() {
+                    info!("skipped scheduled job as the previous one is running: {namespace}/{task_name}");
+                    return Ok(None);
+                }
+            }
+            TaskConcurrencyPolicy::Replace => {
+                let dp = DeleteParams::default();
+                for job in active_jobs {
+                    let job_name = job.name_any();
+                    crate::audit::delete(&api, &job_name, &dp)
+                        .await
+                        .map_err(|error| {
+                            anyhow!(
+                                "failed to replace running job ({namespace}/{job_name}): {error}"
+                            )
+                        })?;
+                }
+            }
+        }
+
+        // NOTE: the job name is derived from the schedule time to dedupe the overlapped runs
+        let job_name = format!("{task_name}-{timestamp}", timestamp = time.timestamp());
+        let data = DashJobCrd {
+            metadata: ObjectMeta {
+                name: Some(job_name.clone()),
+                namespace: Some(namespace.into()),
+                finalizers: Some(vec![DashJobCrd::FINALIZER_NAME.into()]),
+                labels: Some(
+                    [
+                        (DashJobCrd::LABEL_TARGET_TASK, task_name.clone()),
+                        (DashJobCrd::LABEL_TARGET_TASK_NAMESPACE, namespace.into()),
+                    ]
+                    .into_iter()
+                    .map(|(key, value)| (key.to_string(), value))
+                    .collect(),
+                ),
+                ..Default::default()
+            },
+            spec: DashJobSpec {
+                task: task_name.clone(),
+                value: spec.value.clone(),
+            },
+            status: None,
+        };
+
+        let pp = PostParams {
+            dry_run: false,
+            field_manager: Some(field_manager.into()),
+        };
+        match crate::audit::create(&api, &pp, &data).await {
+            Ok(_) => {
+                info!("spawned scheduled job: {namespace}/{job_name}");
+                Ok(Some(job_name))
+            }
+            Err(::kube::Error::Api(error)) if error.code == 409 => Ok(Some(job_name)),
+            Err(error) => Err(anyhow!(
+                "failed to create scheduled job ({namespace}/{job_name}): {error}"
+            )),
+        }
+    }
+}
+
+fn parse_schedule(spec: &TaskScheduleSpec) -> Result<(Schedule, Tz)> {
+    // NOTE: the cron crate requires the seconds field
+    let cron = spec.cron.trim();
+    let schedule = match cron.split_whitespace().count() {
+        5 => Schedule::from_str(&format!("0 {cron}")),
+        _ => Schedule::from_str(cron),
+    }
+    .map_err(|error| anyhow!("failed to parse cron expression {cron:?}: {error}"))?;
+
+    let tz = match spec.time_zone.as_deref() {
+        Some(time_zone) => time_zone
+            .parse()
+            .map_err(|error| anyhow!("failed to parse time zone {time_zone:?}: {error}"))?,
+        None => Tz::UTC,
+    };
+    Ok((schedule, tz))
 }
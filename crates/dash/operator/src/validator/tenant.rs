@@ -0,0 +1,230 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use byte_unit::Byte;
+use dash_api::{
+    model_claim::{ModelClaimCrd, ModelClaimState},
+    storage::{ModelStorageSpec, StorageResourceRequirements},
+    tenant::{
+        TenantConfigCrd, TenantObservabilitySpec, TenantQuotaSpec, TenantTelemetryComponentSpec,
+        TenantTelemetryPipelineSpec,
+    },
+};
+use dash_provider::storage::KubernetesStorageClient;
+use kube::ResourceExt;
+use serde_json::{json, Map, Value};
+use tracing::{instrument, Level};
+
+pub struct TenantValidator<'namespace, 'kube> {
+    pub kubernetes_storage: KubernetesStorageClient<'namespace, 'kube>,
+}
+
+impl<'namespace, 'kube> TenantValidator<'namespace, 'kube> {
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    pub async fn load_default_storage_name(&self) -> Result<Option<String>> {
+        self.kubernetes_storage
+            .load_tenant_config()
+            .await
+            .map(|config| config.and_then(|config| config.spec.default_storage_name))
+    }
+
+    /// Render the OpenTelemetry Collector configuration of the tenant's telemetry pipelines.
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    pub async fn load_collector_config(&self) -> Result<Option<Value>> {
+        match self.kubernetes_storage.load_tenant_config().await? {
+            Some(config) if !config.spec.observability.is_empty() => {
+                render_collector_config(&config.spec.observability).map(Some)
+            }
+            Some(_) | None => Ok(None),
+        }
+    }
+
+    /// Check whether the model claim fits in the quota of the tenant.
+    ///
+    /// Only the admitted model claims are counted,
+    /// so that the pending ones do not block each other.
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    pub async fn validate_model_claim_quota(&self, crd: &ModelClaimCrd) -> Result<()> {
+        let TenantQuotaSpec {
+            max_claims,
+            max_storage,
+        } = match self.kubernetes_storage.load_tenant_config().await? {
+            Some(config) => config.spec.quota,
+            None => return Ok(()),
+        };
+        if max_claims.is_none() && max_storage.is_none() {
+            return Ok(());
+        }
+
+        let name = crd.name_any();
+        let claims: Vec<_> = self
+            .kubernetes_storage
+            .load_model_claims_all()
+            .await?
+            .into_iter()
+            .filter(|claim| claim.name_any() != name)
+            .filter_map(|claim| claim.status)
+            .filter(|status| {
+                matches!(
+                    status.state,
+                    ModelClaimState::Ready | ModelClaimState::Replacing,
+                )
+            })
+            .collect();
+
+        if let Some(max_claims) = max_claims {
+            if claims.len() >= max_claims as usize {
+                bail!(
+                    "tenant quota exceeded: the number of model claims is limited to {max_claims}"
+                );
+            }
+        }
+
+        if let Some(max_storage) = max_storage {
+            let max_storage: Byte = match max_storage.0.parse() {
+                Ok(max_storage) => max_storage,
+                Err(error) => bail!("failed to parse tenant storage quota: {error}"),
+            };
+            let quota = match crd.spec.resources.quota() {
+                Some(quota) => quota,
+                None => bail!(
+                    "spec.resources.requests.storage: should be set as the tenant storage quota is limited"
+                ),
+            };
+
+            let allocated: u128 = claims
+                .iter()
+                .filter_map(|status| status.resources.quota())
+                .map(|quota| quota.as_u128())
+                .sum();
+            if allocated + quota.as_u128() > max_storage.as_u128() {
+                bail!(
+                    "tenant quota exceeded: the storage of model claims is limited to {max_storage} (allocated: {allocated} bytes, requested: {quota})"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Check whether the model storage belongs to this tenant,
+    /// or is shared by the tenant who owns it.
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    pub async fn validate_model_storage_access(
+        &self,
+        storage_name: &str,
+        storage: &ModelStorageSpec,
+    ) -> Result<()> {
+        let KubernetesStorageClient { namespace, kube } = self.kubernetes_storage;
+        let owner = match storage
+            .kind
+            .endpoint(namespace)
+            .and_then(|endpoint| infer_service_namespace(endpoint.host_str()?))
+        {
+            Some(owner) if owner != namespace => owner,
+            Some(_) | None => return Ok(()),
+        };
+
+        let storage_owner = KubernetesStorageClient {
+            namespace: &owner,
+            kube,
+        };
+        match storage_owner.load_tenant_config().await? {
+            Some(config) if config.spec.is_shared_with(namespace) => Ok(()),
+            Some(_) | None => bail!(
+                "model storage {storage_name:?} belongs to the other tenant {owner:?}; it should be shared in {owner}/{config}",
+                config = TenantConfigCrd::NAME,
+            ),
+        }
+    }
+}
+
+fn render_collector_config(spec: &TenantObservabilitySpec) -> Result<Value> {
+    let TenantObservabilitySpec { logs, metrics } = spec;
+
+    let mut components: BTreeMap<&str, Map<String, Value>> = BTreeMap::default();
+    let mut pipelines = Map::default();
+    for (signal, pipeline) in [("logs", logs), ("metrics", metrics)] {
+        let TenantTelemetryPipelineSpec {
+            receivers,
+            processors,
+            exporters,
+        } = match pipeline {
+            Some(pipeline) => pipeline,
+            None => continue,
+        };
+        if receivers.is_empty() {
+            bail!("observability.{signal}.receivers: should not be empty");
+        }
+        if exporters.is_empty() {
+            bail!("observability.{signal}.exporters: should not be empty");
+        }
+
+        let mut names = Map::default();
+        for (kind, items) in [
+            ("receivers", receivers),
+            ("processors", processors),
+            ("exporters", exporters),
+        ] {
+            let registry = components.entry(kind).or_default();
+            let mut ids = Vec::with_capacity(items.len());
+            for TenantTelemetryComponentSpec { name, config } in items {
+                validate_component_id(signal, kind, name)?;
+                if ids.contains(name) {
+                    bail!("observability.{signal}.{kind}: duplicated component: {name:?}");
+                }
+
+                // NOTE: the components can be shared between pipelines, but not redefined
+                match registry.get(name) {
+                    Some(registered) if registered != config => bail!(
+                        "observability.{signal}.{kind}: component {name:?} is defined differently in the other pipeline"
+                    ),
+                    Some(_) => (),
+                    None => {
+                        registry.insert(name.clone(), config.clone());
+                    }
+                }
+                ids.push(name.clone());
+            }
+            names.insert(kind.into(), ids.into());
+        }
+        pipelines.insert(signal.into(), names.into());
+    }
+
+    let mut config = Map::default();
+    for (kind, registry) in components {
+        config.insert(kind.into(), registry.into());
+    }
+    config.insert(
+        "service".into(),
+        json!({
+            "pipelines": pipelines,
+        }),
+    );
+    Ok(config.into())
+}
+
+/// Validate the component ID of the collector, e.g. `otlp` or `prometheus/self`.
+fn validate_component_id(signal: &str, kind: &str, name: &str) -> Result<()> {
+    let mut parts = name.splitn(2, '/');
+    let type_ = parts.next().unwrap_or_default();
+    let is_valid = !type_.is_empty()
+        && type_.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && parts.next().map(|name| !name.is_empty()).unwrap_or(true);
+
+    if is_valid {
+        Ok(())
+    } else {
+        bail!("observability.{signal}.{kind}: invalid component ID: {name:?}")
+    }
+}
+
+/// Infer the namespace of the in-cluster service, e.g. `minio.{namespace}.svc`.
+fn infer_service_namespace(host: &str) -> Option<String> {
+    let mut labels = host.split('.');
+    let _service = labels.next()?;
+    let namespace = labels.next()?;
+    match labels.next() {
+        Some("svc") => Some(namespace.into()),
+        Some(_) | None => None,
+    }
+}
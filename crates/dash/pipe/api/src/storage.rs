@@ -18,6 +18,16 @@ pub struct StorageS3Args {
     #[arg(long, env = "AWS_ENDPOINT_URL", value_name = "URL")]
     pub s3_endpoint: Url,
 
+    /// The secondary endpoints to replicate the objects, in the order of failover.
+    #[arg(
+        long,
+        env = "AWS_ENDPOINT_URL_REPLICAS",
+        value_name = "URL",
+        value_delimiter = ','
+    )]
+    #[serde(default)]
+    pub s3_replicas: Vec<Url>,
+
     #[arg(long, env = "AWS_SECRET_ACCESS_KEY", value_name = "VALUE")]
     pub secret_key: String,
 }
@@ -27,3 +37,21 @@ impl StorageS3Args {
         "us-east-1"
     }
 }
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Parser)]
+pub struct StorageAzureArgs {
+    #[arg(long, env = "AZURE_STORAGE_ACCOUNT_KEY", value_name = "VALUE")]
+    #[serde(default)]
+    pub azure_access_key: Option<String>,
+
+    #[arg(long, env = "AZURE_STORAGE_ACCOUNT_NAME", value_name = "NAME")]
+    #[serde(default)]
+    pub azure_account: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Parser)]
+pub struct StorageGcsArgs {
+    #[arg(long, env = "GOOGLE_SERVICE_ACCOUNT_PATH", value_name = "PATH")]
+    #[serde(default)]
+    pub gcs_service_account_path: Option<String>,
+}
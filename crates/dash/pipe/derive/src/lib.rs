@@ -0,0 +1,242 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Error, Fields, GenericArgument, Ident, LitInt,
+    LitStr, PathArguments, Result, Type,
+};
+
+/// Derive `PipeValue` for a pipe value struct.
+///
+/// A message body struct (`<Name>PipeValue`) is generated along with the schema.
+/// The binary fields (`Bytes` and `Vec<u8>`) are kept inline only if they are small enough,
+/// and the others are carried as the message payloads, which are dumped to the storage.
+///
+/// # Attributes
+///
+/// - `#[pipe(codec = "Cbor")]`: the preferred codec of the messages (default: `Json`)
+/// - `#[pipe(inline_max = 4096)]`: the maximum size of the inline binary fields, in bytes
+/// - `#[pipe(binary)]`, `#[pipe(inline)]`: treat the field as a binary field or not
+#[proc_macro_derive(PipePayload, attributes(pipe))]
+pub fn derive_pipe_payload(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_pipe_payload(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+const CODECS: &[&str] = &["Json", "MessagePack", "Cbor"];
+const DEFAULT_INLINE_MAX: usize = 4096; // 4 KiB
+
+struct ContainerAttrs {
+    codec: Ident,
+    inline_max: usize,
+}
+
+fn parse_container_attrs(attrs: &[Attribute]) -> Result<ContainerAttrs> {
+    let mut codec = None;
+    let mut inline_max = None;
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("pipe")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("codec") {
+                let value: LitStr = meta.value()?.parse()?;
+                if !CODECS.contains(&value.value().as_str()) {
+                    return Err(Error::new(
+                        value.span(),
+                        format!("unsupported codec; expected one of {CODECS:?}"),
+                    ));
+                }
+                codec = Some(Ident::new(&value.value(), value.span()));
+                Ok(())
+            } else if meta.path.is_ident("inline_max") {
+                let value: LitInt = meta.value()?.parse()?;
+                inline_max = Some(value.base10_parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported pipe attribute"))
+            }
+        })?;
+    }
+
+    Ok(ContainerAttrs {
+        codec: codec.unwrap_or_else(|| format_ident!("Json")),
+        inline_max: inline_max.unwrap_or(DEFAULT_INLINE_MAX),
+    })
+}
+
+fn parse_field_is_binary(attrs: &[Attribute], ty: &Type) -> Result<bool> {
+    let mut is_binary = None;
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("pipe")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("binary") {
+                is_binary = Some(true);
+                Ok(())
+            } else if meta.path.is_ident("inline") {
+                is_binary = Some(false);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported pipe field attribute"))
+            }
+        })?;
+    }
+
+    Ok(is_binary.unwrap_or_else(|| is_binary_type(ty)))
+}
+
+/// Return whether the type is `Bytes` or `Vec<u8>`.
+fn is_binary_type(ty: &Type) -> bool {
+    let segment = match ty {
+        Type::Path(ty) if ty.qself.is_none() => match ty.path.segments.last() {
+            Some(segment) => segment,
+            None => return false,
+        },
+        _ => return false,
+    };
+
+    match &segment.arguments {
+        PathArguments::None => segment.ident == "Bytes",
+        PathArguments::AngleBracketed(args) if segment.ident == "Vec" => {
+            args.args.len() == 1
+                && matches!(
+                    args.args.first(),
+                    Some(GenericArgument::Type(Type::Path(arg))) if arg.path.is_ident("u8")
+                )
+        }
+        _ => false,
+    }
+}
+
+fn expand_pipe_payload(input: DeriveInput) -> Result<TokenStream2> {
+    let DeriveInput {
+        attrs,
+        vis,
+        ident,
+        generics,
+        data,
+    } = input;
+
+    if !generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &generics,
+            "generic pipe values are not supported",
+        ));
+    }
+    let fields = match data {
+        Data::Struct(data) => match data.fields {
+            Fields::Named(fields) => fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &ident,
+                    "only the structs with named fields are supported",
+                ))
+            }
+        },
+        _ => return Err(Error::new_spanned(&ident, "only the structs are supported")),
+    };
+
+    let ContainerAttrs { codec, inline_max } = parse_container_attrs(&attrs)?;
+    let value_ident = format_ident!("{ident}PipeValue");
+    let value_doc = format!("The message body of [`{ident}`].");
+
+    // NOTE: the serde attributes are kept to preserve the schema
+    let is_kept = |attr: &&Attribute| attr.path().is_ident("doc") || attr.path().is_ident("serde");
+    let value_attrs = attrs.iter().filter(is_kept);
+
+    let mut value_fields = Vec::default();
+    let mut binary_idents = Vec::default();
+    let mut inline_idents = Vec::default();
+    for field in &fields {
+        let field_attrs = field.attrs.iter().filter(is_kept);
+        let field_ident = field.ident.as_ref().expect("named field");
+        let field_vis = &field.vis;
+
+        if parse_field_is_binary(&field.attrs, &field.ty)? {
+            value_fields.push(quote! {
+                #(#field_attrs)*
+                #[serde(default, skip_serializing_if = "::core::option::Option::is_none")]
+                #field_vis #field_ident: ::core::option::Option<
+                    ::dash_pipe_provider::__private::bytes::Bytes,
+                >
+            });
+            binary_idents.push(field_ident);
+        } else {
+            let field_ty = &field.ty;
+            value_fields.push(quote! {
+                #(#field_attrs)*
+                #field_vis #field_ident: #field_ty
+            });
+            inline_idents.push(field_ident);
+        }
+    }
+    let binary_keys: Vec<_> = binary_idents.iter().map(ToString::to_string).collect();
+
+    Ok(quote! {
+        #[doc = #value_doc]
+        #[derive(
+            ::dash_pipe_provider::__private::serde::Serialize,
+            ::dash_pipe_provider::__private::serde::Deserialize,
+            ::dash_pipe_provider::__private::schemars::JsonSchema,
+        )]
+        #[serde(crate = "::dash_pipe_provider::__private::serde")]
+        #[schemars(crate = "::dash_pipe_provider::__private::schemars")]
+        #(#value_attrs)*
+        #vis struct #value_ident {
+            #(#value_fields,)*
+        }
+
+        impl ::dash_pipe_provider::PipeValue for #ident {
+            type Value = #value_ident;
+
+            const CODEC: ::dash_pipe_provider::Codec = ::dash_pipe_provider::Codec::#codec;
+
+            #[allow(unused_mut)]
+            fn into_message(self) -> ::dash_pipe_provider::PipeMessage<Self::Value> {
+                let Self {
+                    #(#binary_idents,)*
+                    #(#inline_idents,)*
+                } = self;
+
+                let mut message = ::dash_pipe_provider::PipeMessage::new(#value_ident {
+                    #(#binary_idents: ::core::option::Option::None,)*
+                    #(#inline_idents,)*
+                });
+                #(
+                    let #binary_idents = ::dash_pipe_provider::__private::split_payload(
+                        &mut message,
+                        #binary_keys,
+                        #binary_idents.into(),
+                        #inline_max,
+                    );
+                    message.value.#binary_idents = #binary_idents;
+                )*
+                message
+            }
+
+            #[allow(unused_mut)]
+            fn try_from_message(
+                mut message: ::dash_pipe_provider::PipeMessage<Self::Value>,
+            ) -> ::dash_pipe_provider::__private::anyhow::Result<Self> {
+                #(
+                    let #binary_idents = message.value.#binary_idents.take();
+                    let #binary_idents = ::dash_pipe_provider::__private::join_payload(
+                        &message,
+                        #binary_keys,
+                        #binary_idents,
+                    )?;
+                )*
+
+                let #value_ident {
+                    #(#inline_idents,)*
+                    ..
+                } = message.value;
+
+                Ok(Self {
+                    #(#binary_idents: #binary_idents.into(),)*
+                    #(#inline_idents,)*
+                })
+            }
+        }
+    })
+}
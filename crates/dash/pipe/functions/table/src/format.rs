@@ -0,0 +1,81 @@
+use std::{io::Cursor, str::FromStr, sync::Arc};
+
+use anyhow::{anyhow, bail, Error, Result};
+use object_store::{path::Path, ObjectStore};
+use polars::{
+    frame::DataFrame,
+    prelude::{CsvReadOptions, ParquetReader, ParquetWriter, SerReader},
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::schema::TableSchemaArgs;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TableFormat {
+    /// Infer the format from the file extensions
+    #[default]
+    Auto,
+    Csv,
+    Parquet,
+}
+
+impl FromStr for TableFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "csv" => Ok(Self::Csv),
+            "parquet" => Ok(Self::Parquet),
+            _ => bail!("unsupported table format: {s:?}"),
+        }
+    }
+}
+
+impl TableFormat {
+    /// Resolve the format of the file, or `None` if the file is not a table.
+    pub fn resolve(self, path: &Path) -> Option<Self> {
+        let extension = path.extension().map(|extension| extension.to_lowercase());
+        match (self, extension.as_deref()) {
+            (Self::Auto, Some("csv")) | (Self::Csv, Some("csv")) => Some(Self::Csv),
+            (Self::Auto, Some("parquet")) | (Self::Parquet, Some("parquet")) => Some(Self::Parquet),
+            (_, _) => None,
+        }
+    }
+
+    pub fn read(self, args: &TableSchemaArgs, data: Vec<u8>) -> Result<DataFrame> {
+        let data = Cursor::new(data);
+        let df = match self {
+            Self::Auto => bail!("cannot read a table of unknown format"),
+            Self::Csv => CsvReadOptions::default()
+                .with_has_header(true)
+                .with_infer_schema_length(Some(args.infer_schema_length))
+                .into_reader_with_file_handle(data)
+                .finish(),
+            Self::Parquet => ParquetReader::new(data).finish(),
+        }
+        .map_err(|error| anyhow!("failed to read table: {error}"))?;
+        args.coerce(df)
+    }
+}
+
+pub fn write_parquet(df: &mut DataFrame) -> Result<Vec<u8>> {
+    let mut buf = Vec::default();
+    ParquetWriter::new(&mut buf)
+        .finish(df)
+        .map(|_| buf)
+        .map_err(|error| anyhow!("failed to write parquet: {error}"))
+}
+
+/// Open the object store of the URL, e.g. `file:///data/` or `s3://bucket/prefix/`.
+pub fn parse_url(url: &Url) -> Result<(Arc<dyn ObjectStore>, Path)> {
+    let options = ::std::env::vars().filter_map(|(key, value)| {
+        key.strip_prefix("AWS_")
+            .map(|key| (key.to_lowercase(), value))
+    });
+    ::object_store::parse_url_opts(url, options)
+        .map(|(store, prefix)| (Arc::from(store), prefix))
+        .map_err(|error| anyhow!("failed to open table storage ({url}): {error}"))
+}
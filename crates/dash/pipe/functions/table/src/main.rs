@@ -0,0 +1,16 @@
+mod format;
+mod schema;
+mod sink;
+mod source;
+
+use dash_pipe_provider::{connector::Connector, PipeArgs};
+
+fn main() {
+    match ::std::env::var("PIPE_TABLE_MODE").as_deref() {
+        Ok("source") | Err(_) => {
+            PipeArgs::<Connector<self::source::Function>>::from_env().loop_forever()
+        }
+        Ok("sink") => PipeArgs::<self::sink::Function>::from_env().loop_forever(),
+        Ok(mode) => panic!("unsupported table mode; expected source or sink: {mode:?}"),
+    }
+}
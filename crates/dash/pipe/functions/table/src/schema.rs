@@ -0,0 +1,267 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{anyhow, bail, Error, Result};
+use clap::{ArgAction, Parser};
+use dash_pipe_provider::DynValue;
+use polars::{
+    datatypes::{AnyValue, DataType, TimeUnit},
+    frame::DataFrame,
+    lazy::dsl,
+    prelude::{Column, IntoLazy, NamedFrom, PlSmallStr},
+    series::Series,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Parser)]
+pub struct TableSchemaArgs {
+    /// The number of rows to infer the CSV schema; `0` reads all columns as strings
+    #[arg(
+        long,
+        env = "PIPE_TABLE_INFER_SCHEMA_LENGTH",
+        value_name = "ROWS",
+        default_value_t = TableSchemaArgs::default_infer_schema_length(),
+    )]
+    #[serde(default = "TableSchemaArgs::default_infer_schema_length")]
+    pub infer_schema_length: usize,
+
+    /// The column types to coerce into, e.g. `id=i64,score=f64,at=datetime`
+    #[arg(
+        long,
+        env = "PIPE_TABLE_SCHEMA",
+        value_name = "COLUMN=TYPE",
+        value_delimiter = ','
+    )]
+    #[serde(default)]
+    pub schema: Vec<TableColumn>,
+
+    /// Fail on the values which cannot be coerced, rather than nullifying them
+    #[arg(long, env = "PIPE_TABLE_STRICT", action = ArgAction::SetTrue)]
+    #[serde(default)]
+    pub strict: bool,
+}
+
+impl TableSchemaArgs {
+    const fn default_infer_schema_length() -> usize {
+        100
+    }
+
+    /// Cast the columns into the given types.
+    pub fn coerce(&self, df: DataFrame) -> Result<DataFrame> {
+        if self.schema.is_empty() {
+            return Ok(df);
+        }
+
+        let columns: Vec<_> = self
+            .schema
+            .iter()
+            .filter(|column| df.get_column_index(&column.name).is_some())
+            .map(|TableColumn { name, kind }| {
+                let expr = dsl::col(name.as_str());
+                let dtype = kind.to_data_type();
+                if self.strict {
+                    expr.strict_cast(dtype)
+                } else {
+                    expr.cast(dtype)
+                }
+            })
+            .collect();
+
+        df.lazy()
+            .with_columns(columns)
+            .collect()
+            .map_err(|error| anyhow!("failed to coerce the table schema: {error}"))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableColumn {
+    pub name: String,
+    pub kind: TableColumnKind,
+}
+
+impl FromStr for TableColumn {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((name, kind)) => Ok(Self {
+                name: name.trim().into(),
+                kind: kind.trim().parse()?,
+            }),
+            None => bail!("invalid column type; expected COLUMN=TYPE: {s:?}"),
+        }
+    }
+}
+
+impl fmt::Display for TableColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", &self.name, &self.kind)
+    }
+}
+
+impl Serialize for TableColumn {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ::serde::Serializer,
+    {
+        self.to_string().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for TableColumn {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: ::serde::Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(::serde::de::Error::custom)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TableColumnKind {
+    Boolean,
+    Date,
+    DateTime,
+    F64,
+    I64,
+    String,
+}
+
+impl FromStr for TableColumnKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "bool" | "boolean" => Ok(Self::Boolean),
+            "date" => Ok(Self::Date),
+            "datetime" | "timestamp" => Ok(Self::DateTime),
+            "f64" | "float" | "double" => Ok(Self::F64),
+            "i64" | "int" | "integer" => Ok(Self::I64),
+            "str" | "string" => Ok(Self::String),
+            _ => bail!("unsupported column type: {s:?}"),
+        }
+    }
+}
+
+impl fmt::Display for TableColumnKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Boolean => "bool".fmt(f),
+            Self::Date => "date".fmt(f),
+            Self::DateTime => "datetime".fmt(f),
+            Self::F64 => "f64".fmt(f),
+            Self::I64 => "i64".fmt(f),
+            Self::String => "str".fmt(f),
+        }
+    }
+}
+
+impl TableColumnKind {
+    fn to_data_type(self) -> DataType {
+        match self {
+            Self::Boolean => DataType::Boolean,
+            Self::Date => DataType::Date,
+            Self::DateTime => DataType::Datetime(TimeUnit::Milliseconds, None),
+            Self::F64 => DataType::Float64,
+            Self::I64 => DataType::Int64,
+            Self::String => DataType::String,
+        }
+    }
+}
+
+/// Convert the table into the rows of JSON objects.
+pub fn to_rows(df: &DataFrame) -> Result<Vec<DynValue>> {
+    (0..df.height())
+        .map(|index| {
+            df.get_columns()
+                .iter()
+                .map(|column| {
+                    column
+                        .get(index)
+                        .map(|value| (column.name().to_string(), to_json(value)))
+                        .map_err(|error| anyhow!("failed to read table row: {error}"))
+                })
+                .collect::<Result<Map<_, _>>>()
+                .map(Value::Object)
+        })
+        .collect()
+}
+
+/// Convert the rows of JSON objects into a table, keeping the order of the first seen columns.
+pub fn from_rows(rows: &[DynValue]) -> Result<DataFrame> {
+    let mut names: Vec<&str> = vec![];
+    for row in rows {
+        if let Value::Object(row) = row {
+            for name in row.keys() {
+                if !names.contains(&name.as_str()) {
+                    names.push(name);
+                }
+            }
+        }
+    }
+
+    let columns = names
+        .into_iter()
+        .map(|name| {
+            let values: Vec<_> = rows
+                .iter()
+                .map(|row| from_json(row.get(name).unwrap_or(&Value::Null)))
+                .collect();
+            Series::from_any_values(name.into(), &values, false)
+                .map(Column::from)
+                .map_err(|error| anyhow!("failed to build table column {name:?}: {error}"))
+        })
+        .collect::<Result<_>>()?;
+
+    match DataFrame::new(columns) {
+        Ok(df) if df.width() > 0 => Ok(df),
+        Ok(_) => Ok(DataFrame::new(vec![Column::new(
+            PlSmallStr::from_static("value"),
+            rows.iter().map(|row| row.to_string()).collect::<Vec<_>>(),
+        )])?),
+        Err(error) => bail!("failed to build table: {error}"),
+    }
+}
+
+fn to_json(value: AnyValue) -> Value {
+    match value {
+        AnyValue::Null => Value::Null,
+        AnyValue::Boolean(value) => Value::Bool(value),
+        AnyValue::Int8(value) => value.into(),
+        AnyValue::Int16(value) => value.into(),
+        AnyValue::Int32(value) => value.into(),
+        AnyValue::Int64(value) => value.into(),
+        AnyValue::UInt8(value) => value.into(),
+        AnyValue::UInt16(value) => value.into(),
+        AnyValue::UInt32(value) => value.into(),
+        AnyValue::UInt64(value) => value.into(),
+        AnyValue::Float32(value) => Number::from_f64(value.into())
+            .map(Value::Number)
+            .unwrap_or_default(),
+        AnyValue::Float64(value) => Number::from_f64(value)
+            .map(Value::Number)
+            .unwrap_or_default(),
+        AnyValue::String(value) => Value::String(value.into()),
+        AnyValue::StringOwned(value) => Value::String(value.into()),
+        value => Value::String(value.to_string()),
+    }
+}
+
+fn from_json(value: &Value) -> AnyValue<'static> {
+    match value {
+        Value::Null => AnyValue::Null,
+        Value::Bool(value) => AnyValue::Boolean(*value),
+        Value::Number(value) => match value.as_i64() {
+            Some(value) => AnyValue::Int64(value),
+            None => value
+                .as_f64()
+                .map(AnyValue::Float64)
+                .unwrap_or(AnyValue::Null),
+        },
+        Value::String(value) => AnyValue::StringOwned(value.as_str().into()),
+        value => AnyValue::StringOwned(value.to_string().into()),
+    }
+}
@@ -0,0 +1,171 @@
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{SecondsFormat, Utc};
+use clap::Parser;
+use dash_pipe_provider::{storage::StorageIO, DynValue, FunctionContext, PipeMessages};
+use derivative::Derivative;
+use object_store::{path::Path, ObjectStore, PutPayload};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{info, instrument, Level};
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    format::{parse_url, write_parquet},
+    schema::{from_rows, TableSchemaArgs},
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize, Parser)]
+pub struct FunctionArgs {
+    /// The maximum number of rows per a parquet file
+    #[arg(
+        long,
+        env = "PIPE_TABLE_BATCH_SIZE",
+        value_name = "ROWS",
+        default_value_t = FunctionArgs::default_batch_size(),
+    )]
+    #[serde(default = "FunctionArgs::default_batch_size")]
+    batch_size: usize,
+
+    /// The maximum time to buffer the rows before writing them
+    #[arg(
+        long,
+        env = "PIPE_TABLE_FLUSH_INTERVAL_MS",
+        value_name = "MILLISECONDS",
+        default_value_t = FunctionArgs::default_flush_interval_ms(),
+    )]
+    #[serde(default = "FunctionArgs::default_flush_interval_ms")]
+    flush_interval_ms: u64,
+
+    /// The column to partition the files by, as `COLUMN=VALUE` directories
+    #[arg(long, env = "PIPE_TABLE_PARTITION_BY", value_name = "COLUMN")]
+    #[serde(default)]
+    partition_by: Option<String>,
+
+    #[command(flatten)]
+    #[serde(default, flatten)]
+    schema: TableSchemaArgs,
+
+    /// The directory or S3 prefix to write, e.g. `file:///data/` or `s3://bucket/prefix/`
+    #[arg(long, env = "PIPE_TABLE_URL", value_name = "URL")]
+    url: Url,
+}
+
+impl FunctionArgs {
+    const fn default_batch_size() -> usize {
+        10_000
+    }
+
+    const fn default_flush_interval_ms() -> u64 {
+        10_000
+    }
+}
+
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct Function {
+    args: FunctionArgs,
+    #[derivative(Debug = "ignore")]
+    buffer: Vec<DynValue>,
+    last_flushed: Instant,
+    prefix: Path,
+    #[derivative(Debug = "ignore")]
+    store: Arc<dyn ObjectStore>,
+}
+
+#[async_trait]
+impl ::dash_pipe_provider::FunctionBuilder for Function {
+    type Args = FunctionArgs;
+
+    async fn try_new(
+        args: &<Self as ::dash_pipe_provider::FunctionBuilder>::Args,
+        ctx: Option<&mut FunctionContext>,
+        _storage: &Arc<StorageIO>,
+    ) -> Result<Self> {
+        if let Some(ctx) = ctx {
+            ctx.disable_store();
+            ctx.disable_store_metadata();
+        }
+
+        let (store, prefix) = parse_url(&args.url)?;
+        Ok(Self {
+            args: args.clone(),
+            buffer: Vec::default(),
+            last_flushed: Instant::now(),
+            prefix,
+            store,
+        })
+    }
+}
+
+#[async_trait]
+impl ::dash_pipe_provider::Function for Function {
+    type Input = DynValue;
+    type Output = DynValue;
+
+    async fn tick(
+        &mut self,
+        inputs: PipeMessages<<Self as ::dash_pipe_provider::Function>::Input>,
+    ) -> Result<PipeMessages<<Self as ::dash_pipe_provider::Function>::Output>> {
+        self.buffer
+            .extend(inputs.into_vec().into_iter().map(|message| message.value));
+
+        let interval = Duration::from_millis(self.args.flush_interval_ms);
+        if self.buffer.len() >= self.args.batch_size.max(1)
+            || (!self.buffer.is_empty() && self.last_flushed.elapsed() >= interval)
+        {
+            self.flush().await?;
+        }
+        Ok(PipeMessages::None)
+    }
+}
+
+impl Function {
+    /// Write the buffered rows as parquet files, one per partition.
+    #[instrument(level = Level::INFO, skip(self), fields(prefix = %self.prefix), err(Display))]
+    async fn flush(&mut self) -> Result<()> {
+        let rows = ::std::mem::take(&mut self.buffer);
+        self.last_flushed = Instant::now();
+
+        let mut partitions: BTreeMap<Option<String>, Vec<DynValue>> = BTreeMap::default();
+        for row in rows {
+            let partition = self.args.partition_by.as_ref().map(|column| {
+                let value = match row.get(column) {
+                    Some(Value::String(value)) => value.clone(),
+                    Some(Value::Null) | None => "__null__".into(),
+                    Some(value) => value.to_string(),
+                };
+                format!("{column}={value}")
+            });
+            partitions.entry(partition).or_default().push(row);
+        }
+
+        let timestamp = Utc::now()
+            .to_rfc3339_opts(SecondsFormat::Millis, true)
+            .replace(':', "-");
+        for (partition, rows) in partitions {
+            let df = from_rows(&rows)?;
+            let mut df = self.args.schema.coerce(df)?;
+            let data = write_parquet(&mut df)?;
+
+            let name = format!("part-{timestamp}-{id}.parquet", id = Uuid::new_v4());
+            let path = match partition {
+                Some(partition) => self.prefix.child(partition).child(name),
+                None => self.prefix.child(name),
+            };
+            self.store
+                .put(&path, PutPayload::from(data))
+                .await
+                .map_err(|error| anyhow!("failed to write table file ({path}): {error}"))?;
+            info!("wrote table file: {path} ({} rows)", rows.len());
+        }
+        Ok(())
+    }
+}
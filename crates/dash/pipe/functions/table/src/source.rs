@@ -0,0 +1,156 @@
+use std::{
+    collections::{BTreeSet, VecDeque},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use clap::Parser;
+use dash_pipe_provider::{
+    storage::StorageIO, DynValue, FunctionContext, PipeMessage, PipeMessages,
+};
+use derivative::Derivative;
+use futures::TryStreamExt;
+use object_store::{path::Path, ObjectStore};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument, Level};
+use url::Url;
+
+use crate::{
+    format::{parse_url, TableFormat},
+    schema::{to_rows, TableSchemaArgs},
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize, Parser)]
+pub struct FunctionArgs {
+    /// The number of rows per a batch of messages
+    #[arg(
+        long,
+        env = "PIPE_TABLE_BATCH_SIZE",
+        value_name = "ROWS",
+        default_value_t = FunctionArgs::default_batch_size(),
+    )]
+    #[serde(default = "FunctionArgs::default_batch_size")]
+    batch_size: usize,
+
+    #[arg(
+        long,
+        env = "PIPE_TABLE_FORMAT",
+        value_name = "FORMAT",
+        default_value = "auto"
+    )]
+    #[serde(default)]
+    format: TableFormat,
+
+    #[command(flatten)]
+    #[serde(default, flatten)]
+    schema: TableSchemaArgs,
+
+    /// The directory or S3 prefix to tail, e.g. `file:///data/` or `s3://bucket/prefix/`
+    #[arg(long, env = "PIPE_TABLE_URL", value_name = "URL")]
+    url: Url,
+}
+
+impl FunctionArgs {
+    const fn default_batch_size() -> usize {
+        1_000
+    }
+}
+
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct Function {
+    args: FunctionArgs,
+    #[derivative(Debug = "ignore")]
+    pending: VecDeque<DynValue>,
+    prefix: Path,
+    /// The files which have already been emitted
+    seen: BTreeSet<Path>,
+    #[derivative(Debug = "ignore")]
+    store: Arc<dyn ObjectStore>,
+}
+
+#[async_trait]
+impl ::dash_pipe_provider::FunctionBuilder for Function {
+    type Args = FunctionArgs;
+
+    async fn try_new(
+        args: &<Self as ::dash_pipe_provider::FunctionBuilder>::Args,
+        ctx: Option<&mut FunctionContext>,
+        _storage: &Arc<StorageIO>,
+    ) -> Result<Self> {
+        if let Some(ctx) = ctx {
+            ctx.disable_load();
+        }
+
+        let (store, prefix) = parse_url(&args.url)?;
+        Ok(Self {
+            args: args.clone(),
+            pending: VecDeque::default(),
+            prefix,
+            seen: BTreeSet::default(),
+            store,
+        })
+    }
+}
+
+#[async_trait]
+impl ::dash_pipe_provider::Function for Function {
+    type Input = DynValue;
+    type Output = DynValue;
+
+    async fn tick(
+        &mut self,
+        _inputs: PipeMessages<<Self as ::dash_pipe_provider::Function>::Input>,
+    ) -> Result<PipeMessages<<Self as ::dash_pipe_provider::Function>::Output>> {
+        if self.pending.is_empty() {
+            self.load_next().await?;
+        }
+
+        let len = self.pending.len().min(self.args.batch_size.max(1));
+        let messages: Vec<_> = self.pending.drain(..len).map(PipeMessage::new).collect();
+        Ok(match messages.len() {
+            0 => PipeMessages::None,
+            _ => PipeMessages::Batch(messages),
+        })
+    }
+}
+
+impl Function {
+    /// Load the rows of the next new file, in the order of the file names.
+    #[instrument(level = Level::INFO, skip(self), fields(prefix = %self.prefix), err(Display))]
+    async fn load_next(&mut self) -> Result<()> {
+        let files: BTreeSet<_> = self
+            .store
+            .list(Some(&self.prefix))
+            .map_ok(|meta| meta.location)
+            .try_collect()
+            .await
+            .map_err(|error| anyhow!("failed to list table files: {error}"))?;
+
+        for path in files.difference(&self.seen).cloned().collect::<Vec<_>>() {
+            self.seen.insert(path.clone());
+            let format = match self.args.format.resolve(&path) {
+                Some(format) => format,
+                None => continue,
+            };
+
+            let data = self
+                .store
+                .get(&path)
+                .await
+                .map_err(|error| anyhow!("failed to get table file ({path}): {error}"))?
+                .bytes()
+                .await
+                .map_err(|error| anyhow!("failed to read table file ({path}): {error}"))?;
+            let df = format.read(&self.args.schema, data.to_vec())?;
+
+            info!("loaded table file: {path} ({} rows)", df.height());
+            self.pending.extend(to_rows(&df)?);
+            if !self.pending.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,19 +1,51 @@
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use ark_core_k8s::data::Name;
+#[cfg(feature = "deltalake")]
+use async_stream::try_stream;
 use async_trait::async_trait;
-use clap::Parser;
+use bytes::Bytes;
+#[cfg(feature = "deltalake")]
+use chrono::{DateTime, Utc};
+use clap::{ArgAction, Parser};
 use derivative::Derivative;
+#[cfg(feature = "deltalake")]
+use futures::StreamExt;
+use futures::{stream::FuturesOrdered, TryStreamExt};
 use schemars::JsonSchema;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use tracing::{instrument, Level};
+use tokio::{
+    spawn,
+    sync::Mutex,
+    time::{sleep, sleep_until},
+};
+use tracing::{instrument, warn, Level, Span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 
 use crate::{
+    dedup::DedupStore,
+    dlq::DeadLetter,
     message::{Codec, DynValue, PipeMessage},
-    messengers::{init_messenger, Messenger, MessengerArgs, Publisher, Subscriber},
+    messengers::{init_messenger, Messenger, MessengerArgs, Publisher, RawMessage, Subscriber},
+    outbox::{Outbox, OutboxOptions, OutboxRecord, OutboxStore},
+    ratelimit::{RateLimitOptions, RateLimitPermit, RateLimiter},
+    schema::{
+        evolution::SchemaEvolution,
+        registry::{validate, SchemaRegistry, SchemaRegistryArgs, SchemaVersion},
+    },
+    spill::{SpillBuffer, SpillOptions},
     storage::{MetadataStorageArgs, MetadataStorageType, StorageArgs, StorageSet},
 };
+#[cfg(feature = "deltalake")]
+use crate::{
+    function::Function,
+    message::PipeMessages,
+    storage::{MetadataStorage, Stream as StorageStream},
+};
 
 #[derive(Derivative)]
 #[derivative(Debug)]
@@ -23,6 +55,11 @@ pub struct PipeClient<Value = DynValue> {
     #[derivative(Debug = "ignore")]
     messenger: Box<dyn Messenger<Value>>,
 
+    schema_compat: bool,
+
+    #[derivative(Debug = "ignore")]
+    schema_registry: Option<Arc<SchemaRegistry>>,
+
     #[derivative(Debug = "ignore")]
     storage: Arc<StorageSet>,
 }
@@ -59,6 +96,8 @@ impl<Value> PipeClient<Value> {
                 PipeClientExtraArgs {
                     encoder,
                     default_metadata_type,
+                    schema_compat,
+                    schema_registry,
                 },
             messenger,
             storage,
@@ -67,6 +106,10 @@ impl<Value> PipeClient<Value> {
         Ok(Self {
             encoder: encoder.unwrap_or_default(),
             messenger: init_messenger(messenger).await?,
+            schema_compat: *schema_compat,
+            schema_registry: SchemaRegistry::try_new(schema_registry)
+                .await?
+                .map(Arc::new),
             storage: Arc::new(
                 StorageSet::try_new(
                     storage,
@@ -80,11 +123,22 @@ impl<Value> PipeClient<Value> {
     }
 
     #[instrument(level = Level::INFO, skip(self))]
-    pub async fn publish(&self, topic: Name) -> Result<PipePublisher> {
+    pub async fn publish(&self, topic: Name) -> Result<PipePublisher>
+    where
+        Value: JsonSchema,
+    {
+        let schema = match &self.schema_registry {
+            Some(registry) => Some(registry.negotiate::<Value>(&topic).await?),
+            None => None,
+        };
         let inner = self.messenger.publish(topic).await?;
 
         Ok(PipePublisher {
+            batch: None,
             encoder: self.encoder,
+            outbox: None,
+            rate_limit: None,
+            schema,
             topic: inner.topic().clone(),
             inner,
             storage: self.storage.clone(),
@@ -94,11 +148,22 @@ impl<Value> PipeClient<Value> {
     #[instrument(level = Level::INFO, skip(self))]
     pub async fn subscribe(&self, topic: Name) -> Result<PipeSubscriber<Value>>
     where
-        Value: Send + DeserializeOwned,
+        Value: Send + DeserializeOwned + JsonSchema,
     {
+        if let Some(registry) = &self.schema_registry {
+            registry.check::<Value>(&topic).await?;
+        }
+        let evolution = if self.schema_compat {
+            Some(Arc::new(SchemaEvolution::new::<Value>()?))
+        } else {
+            None
+        };
         let inner = self.messenger.subscribe(topic).await?;
 
         Ok(PipeSubscriber {
+            dedup: None,
+            evolution,
+            rate_limit: None,
             topic: inner.topic().clone(),
             inner,
             storage: self.storage.clone(),
@@ -112,6 +177,7 @@ impl<Value> PipeClient<Value> {
         data: PipeMessage,
     ) -> Result<PipeMessage<ValueOut>>
     where
+        Value: JsonSchema,
         ValueOut: Send + DeserializeOwned,
     {
         self.publish(topic).await?.request_one(data).await
@@ -120,11 +186,155 @@ impl<Value> PipeClient<Value> {
     #[instrument(level = Level::INFO, skip(self))]
     pub async fn read(&self, topic: Name) -> Result<Option<PipeMessage<Value>>>
     where
-        Value: Send + DeserializeOwned,
+        Value: Send + DeserializeOwned + JsonSchema,
     {
         self.subscribe(topic).await?.read_one().await
     }
 
+    /// Read the dead letters of the topic, until the queue stays idle for the timeout.
+    #[instrument(level = Level::INFO, skip(self))]
+    pub async fn list_dead_letters(
+        &self,
+        topic: &Name,
+        limit: usize,
+        timeout: Duration,
+    ) -> Result<Vec<DeadLetter>>
+    where
+        Value: Send + DeserializeOwned,
+    {
+        let mut stream = self.messenger.subscribe(DeadLetter::topic(topic)?).await?;
+
+        let mut letters = Vec::default();
+        while letters.len() < limit {
+            let message = match ::tokio::time::timeout(timeout, stream.read_one_raw()).await {
+                Ok(message) => match message? {
+                    Some(message) => message,
+                    None => break,
+                },
+                Err(_) => break,
+            };
+            let letter: PipeMessage<DeadLetter> = message
+                .data
+                .try_into()
+                .map_err(|error| anyhow!("failed to decode dead letter: {error}"))?;
+            letters.push(letter.value);
+        }
+        Ok(letters)
+    }
+
+    /// Publish the dead letter back to its source topic.
+    #[instrument(level = Level::INFO, skip_all, fields(data.model = %letter.model.as_str()), err(Display))]
+    pub async fn replay_dead_letter(&self, letter: &DeadLetter) -> Result<()> {
+        let stream = self.messenger.publish(letter.model.clone()).await?;
+        stream.send_one(letter.payload.clone()).await?;
+        stream.flush().await
+    }
+
+    /// Read the stored messages of the topic in the range of `[from, to)`,
+    /// paced to their original relative timing divided by the `speed`.
+    ///
+    /// NOTE: The infinite `speed` replays the messages as fast as possible.
+    #[cfg(feature = "deltalake")]
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn replay_stream(
+        &self,
+        topic: &Name,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        speed: f64,
+    ) -> Result<StorageStream<PipeMessage<Value>>>
+    where
+        Value: 'static + Send + DeserializeOwned + JsonSchema,
+    {
+        if speed.is_nan() || speed <= 0.0 {
+            bail!("replay speed should be positive: {speed}");
+        }
+
+        let metadata = self.storage.create_deltalake::<Value>(topic, None).await?;
+        let mut list = <_ as MetadataStorage<Value>>::list_metadata_range(&metadata, from, to)
+            .await
+            .map_err(|error| anyhow!("failed to list messages to replay: {error}"))?;
+
+        let storage = self.storage.clone();
+        Ok(try_stream! {
+            // keep the table alive while replaying
+            let _metadata = metadata;
+
+            let mut origin = None;
+            while let Some(message) = list.try_next().await? {
+                let timestamp = message.timestamp();
+                let (started_at, first) = *origin.get_or_insert((::tokio::time::Instant::now(), timestamp));
+
+                let offset = (timestamp - first).to_std().unwrap_or_default();
+                sleep_until(started_at + offset.div_f64(speed)).await;
+
+                yield message.drop_reply().load_payloads(&storage).await?;
+            }
+        }
+        .boxed())
+    }
+
+    /// Republish the stored messages of the topic in the range of `[from, to)`,
+    /// returning the number of the replayed messages.
+    #[cfg(feature = "deltalake")]
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn replay(
+        &self,
+        topic: Name,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        speed: f64,
+    ) -> Result<usize>
+    where
+        Value: 'static + Send + Sync + Clone + Serialize + DeserializeOwned + JsonSchema,
+    {
+        let mut messages = self.replay_stream(&topic, from, to, speed).await?;
+        let stream = self.publish(topic).await?;
+
+        let mut count = 0;
+        while let Some(message) = messages.try_next().await? {
+            <PipePublisher as Publisher<PipeMessage<Value>, PipeMessage>>::send_one(
+                &stream, message,
+            )
+            .await?;
+            count += 1;
+        }
+        <PipePublisher as Publisher<PipeMessage<Value>, PipeMessage>>::flush(&stream).await?;
+        Ok(count)
+    }
+
+    /// Feed the stored messages of the topic in the range of `[from, to)`
+    /// directly into the local function, collecting its outputs.
+    ///
+    /// It is useful for backtesting the new functions against the production traffic.
+    #[cfg(feature = "deltalake")]
+    #[instrument(level = Level::INFO, skip(self, function), err(Display))]
+    pub async fn replay_into<F>(
+        &self,
+        function: &mut F,
+        topic: &Name,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        speed: f64,
+    ) -> Result<Vec<PipeMessage<<F as Function>::Output>>>
+    where
+        F: Send + Function<Input = Value>,
+        Value: 'static + Send + DeserializeOwned + JsonSchema,
+    {
+        let mut messages = self.replay_stream(topic, from, to, speed).await?;
+
+        let mut outputs = Vec::default();
+        while let Some(message) = messages.try_next().await? {
+            outputs.extend(
+                function
+                    .tick(PipeMessages::Single(message))
+                    .await?
+                    .into_vec(),
+            );
+        }
+        Ok(outputs)
+    }
+
     pub const fn storage(&self) -> &Arc<StorageSet> {
         &self.storage
     }
@@ -156,16 +366,109 @@ pub struct PipeClientExtraArgs {
     #[arg(long, env = "PIPE_ENCODER", value_name = "CODEC")]
     #[serde(default)]
     pub encoder: Option<Codec>,
+
+    /// Fill the missing fields with the schema defaults and ignore the unknown ones
+    /// when reading the messages.
+    #[arg(long, env = "PIPE_SCHEMA_COMPAT", action = ArgAction::SetTrue)]
+    #[serde(default)]
+    pub schema_compat: bool,
+
+    #[command(flatten)]
+    #[serde(default)]
+    pub schema_registry: SchemaRegistryArgs,
 }
 
 #[derive(Clone)]
 pub struct PipePublisher {
+    batch: Option<Arc<PipeBatchBuffer>>,
     encoder: Codec,
     inner: Arc<dyn Publisher>,
+    outbox: Option<Arc<Outbox>>,
+    rate_limit: Option<Arc<RateLimiter>>,
+    schema: Option<SchemaVersion>,
     topic: Name,
     storage: Arc<StorageSet>,
 }
 
+impl PipePublisher {
+    /// Aggregate the sending messages, flushing them once any of the thresholds are reached.
+    ///
+    /// NOTE: The remaining messages should be flushed manually before dropping the publisher.
+    pub fn with_batching(mut self, options: PipeBatchOptions) -> Self {
+        self.batch = Some(PipeBatchBuffer::spawn(self.inner.clone(), options));
+        self
+    }
+
+    /// Stage the sending messages into the outbox until published, reaping the incomplete ones.
+    ///
+    /// NOTE: The staged messages bypass the batching, as they are committed once published.
+    pub fn with_outbox<S>(mut self, store: S, options: OutboxOptions) -> Self
+    where
+        S: 'static + OutboxStore,
+    {
+        self.outbox = Some(Outbox::spawn(
+            self.inner.clone(),
+            self.storage.clone(),
+            Arc::new(store),
+            options,
+        ));
+        self
+    }
+
+    /// Limit the sending messages, applying the overflow policy on the exceeding ones.
+    pub fn with_rate_limit(mut self, options: RateLimitOptions) -> Self {
+        self.rate_limit = Some(Arc::new(RateLimiter::new(options)));
+        self
+    }
+
+    /// Spill the unsent messages into the local disk while the messenger is unreachable,
+    /// sending them in order once reconnected.
+    ///
+    /// NOTE: It should be called before enabling the batching or the outbox, which wrap the publisher.
+    pub async fn with_spill(mut self, options: SpillOptions) -> Result<Self> {
+        self.inner = SpillBuffer::try_spawn(self.inner, options).await?;
+        Ok(self)
+    }
+
+    /// Return the negotiated schema of the topic, if the schema registry is enabled.
+    pub const fn schema(&self) -> Option<&SchemaVersion> {
+        self.schema.as_ref()
+    }
+
+    /// Wait for the message to be admitted, returning `None` if it has been dropped.
+    async fn acquire(&self, data: &Bytes) -> Result<Option<Option<RateLimitPermit>>> {
+        match &self.rate_limit {
+            Some(rate_limit) => Ok(rate_limit.acquire(data.len()).await?.map(Some)),
+            None => Ok(Some(None)),
+        }
+    }
+
+    /// Record the version of the negotiated schema, if any.
+    fn stamp<Value>(&self, message: &mut PipeMessage<Value>) {
+        if let Some(SchemaVersion { version, .. }) = &self.schema {
+            message.set_schema_version(*version);
+        }
+    }
+
+    fn validate<Value>(&self, message: &PipeMessage<Value>) -> Result<()>
+    where
+        Value: Serialize,
+    {
+        match &self.schema {
+            Some(SchemaVersion { version, schema }) => {
+                let value = ::serde_json::to_value(&message.value)?;
+                validate(schema, &value).map_err(|error| {
+                    anyhow!(
+                        "failed to validate message with schema of {topic} (version {version}): {error}",
+                        topic = self.topic,
+                    )
+                })
+            }
+            None => Ok(()),
+        }
+    }
+}
+
 #[async_trait]
 impl<Value, ValueOut> Publisher<PipeMessage<Value>, PipeMessage<ValueOut>> for PipePublisher
 where
@@ -189,10 +492,20 @@ where
     where
         Value: 'async_trait,
     {
-        let message = message
+        self.validate(&message)?;
+        let mut message = message
             .dump_payloads(&self.storage, Some(&self.topic), None)
             .await?;
+        message.inject_context();
+        self.stamp(&mut message);
         let data = message.to_bytes(self.encoder)?;
+        let Some(_permit) = self.acquire(&data).await? else {
+            warn!(
+                "dropping reply to {topic}: rate limit exceeded",
+                topic = self.topic
+            );
+            return Ok(());
+        };
         self.inner.reply_one(data, inbox).await
     }
 
@@ -210,10 +523,19 @@ where
         Value: 'async_trait,
         ValueOut: 'async_trait,
     {
-        let message_req = message
+        self.validate(&message)?;
+        let mut message_req = message
             .dump_payloads(&self.storage, Some(&self.topic), None)
             .await?;
+        message_req.inject_context();
+        self.stamp(&mut message_req);
         let data_req = message_req.to_bytes(self.encoder)?;
+        let Some(_permit) = self.acquire(&data_req).await? else {
+            bail!(
+                "dropped request to {topic}: rate limit exceeded",
+                topic = self.topic
+            )
+        };
 
         let data_res = self.inner.request_one(data_req).await?;
         let message_res: PipeMessage<ValueOut> = data_res.try_into()?;
@@ -233,11 +555,99 @@ where
     where
         Value: 'async_trait,
     {
-        let message = message
+        self.validate(&message)?;
+        let mut message = message
             .dump_payloads(&self.storage, Some(&self.topic), None)
             .await?;
+        message.inject_context();
+        self.stamp(&mut message);
         let data = message.to_bytes(self.encoder)?;
-        self.inner.send_one(data).await
+        let Some(_permit) = self.acquire(&data).await? else {
+            warn!(
+                "dropping message to {topic}: rate limit exceeded",
+                topic = self.topic
+            );
+            return Ok(());
+        };
+        match (&self.outbox, &self.batch) {
+            (Some(outbox), _) => {
+                outbox
+                    .send_one(OutboxRecord::staged(&message, &self.topic, data))
+                    .await
+            }
+            (None, Some(batch)) => batch.push(data).await,
+            (None, None) => self.inner.send_one(data).await,
+        }
+    }
+
+    #[instrument(
+        level = Level::INFO,
+        skip_all,
+        fields(
+            message.len = %messages.len(),
+            message.model = %self.topic.as_str(),
+        ),
+        err(Display),
+    )]
+    async fn send_batch(&self, messages: Vec<PipeMessage<Value>>) -> Result<()>
+    where
+        Value: 'async_trait + Send,
+    {
+        let data: Vec<_> = messages
+            .into_iter()
+            .map(|message| async move {
+                self.validate(&message)?;
+                let mut message = message
+                    .dump_payloads(&self.storage, Some(&self.topic), None)
+                    .await?;
+                message.inject_context();
+                self.stamp(&mut message);
+                let data = message.to_bytes(self.encoder)?;
+                let record = self
+                    .outbox
+                    .as_ref()
+                    .map(|_| OutboxRecord::staged(&message, &self.topic, data.clone()));
+                Ok::<_, ::anyhow::Error>((data, record))
+            })
+            .collect::<FuturesOrdered<_>>()
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        // admit the messages in order, holding the in-flight slots until sent
+        let mut permits = Vec::with_capacity(data.len());
+        let mut admitted = Vec::with_capacity(data.len());
+        let mut records = Vec::with_capacity(data.len());
+        for (data, record) in data {
+            match self.acquire(&data).await? {
+                Some(permit) => {
+                    permits.push(permit);
+                    admitted.push(data);
+                    records.extend(record);
+                }
+                None => warn!(
+                    "dropping message to {topic}: rate limit exceeded",
+                    topic = self.topic,
+                ),
+            }
+        }
+        let data = admitted;
+
+        if let Some(outbox) = &self.outbox {
+            for record in records {
+                outbox.send_one(record).await?;
+            }
+            return Ok(());
+        }
+
+        match &self.batch {
+            Some(batch) => {
+                for data in data {
+                    batch.push(data).await?;
+                }
+                Ok(())
+            }
+            None => self.inner.send_batch(data).await,
+        }
     }
 
     #[instrument(
@@ -250,16 +660,151 @@ where
         err(Display),
     )]
     async fn flush(&self) -> Result<()> {
+        if let Some(batch) = &self.batch {
+            batch.flush().await?;
+        }
         self.inner.flush().await
     }
 }
 
+/// The thresholds to flush the buffered messages of a batching publisher.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PipeBatchOptions {
+    pub max_bytes: usize,
+    pub max_latency: Duration,
+    pub max_messages: usize,
+}
+
+impl Default for PipeBatchOptions {
+    fn default() -> Self {
+        Self {
+            max_bytes: 1 << 20,
+            max_latency: Duration::from_millis(10),
+            max_messages: 1_000,
+        }
+    }
+}
+
+struct PipeBatchBuffer {
+    inner: Arc<dyn Publisher>,
+    options: PipeBatchOptions,
+    state: Mutex<PipeBatchState>,
+}
+
+impl PipeBatchBuffer {
+    fn spawn(inner: Arc<dyn Publisher>, options: PipeBatchOptions) -> Arc<Self> {
+        let buffer = Arc::new(Self {
+            inner,
+            options,
+            state: Default::default(),
+        });
+
+        // flush the outdated messages until the publishers are dropped
+        let interval = options.max_latency.max(Duration::from_millis(1));
+        let weak = Arc::downgrade(&buffer);
+        spawn(async move {
+            loop {
+                sleep(interval).await;
+                match weak.upgrade() {
+                    Some(buffer) => {
+                        if let Err(error) = buffer.flush_outdated().await {
+                            warn!("failed to flush batched messages: {error}");
+                        }
+                    }
+                    None => break,
+                }
+            }
+        });
+        buffer
+    }
+
+    async fn push(&self, data: Bytes) -> Result<()> {
+        let mut state = self.state.lock().await;
+        state.len_bytes += data.len();
+        state.data.push(data);
+        state.since.get_or_insert_with(Instant::now);
+
+        if state.data.len() >= self.options.max_messages
+            || state.len_bytes >= self.options.max_bytes
+        {
+            self.inner.send_batch(state.take()).await
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn flush_outdated(&self) -> Result<()> {
+        let mut state = self.state.lock().await;
+        if state
+            .since
+            .map(|since| since.elapsed() >= self.options.max_latency)
+            .unwrap_or_default()
+        {
+            self.inner.send_batch(state.take()).await
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn flush(&self) -> Result<()> {
+        let mut state = self.state.lock().await;
+        if !state.data.is_empty() {
+            self.inner.send_batch(state.take()).await
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Default)]
+struct PipeBatchState {
+    data: Vec<Bytes>,
+    len_bytes: usize,
+    since: Option<Instant>,
+}
+
+impl PipeBatchState {
+    fn take(&mut self) -> Vec<Bytes> {
+        self.len_bytes = 0;
+        self.since = None;
+        ::std::mem::take(&mut self.data)
+    }
+}
+
 pub struct PipeSubscriber<Value> {
+    dedup: Option<Arc<dyn DedupStore>>,
+    evolution: Option<Arc<SchemaEvolution>>,
     inner: Box<dyn Subscriber<Value>>,
+    rate_limit: Option<RateLimiter>,
     topic: Name,
     storage: Arc<StorageSet>,
 }
 
+impl<Value> PipeSubscriber<Value> {
+    /// Skip the messages whose ids have been already delivered within the store's window.
+    pub fn with_dedup<S>(mut self, store: S) -> Self
+    where
+        S: 'static + DedupStore,
+    {
+        self.dedup = Some(Arc::new(store));
+        self
+    }
+
+    /// Fit the receiving messages into the reader schema, tolerating the other schema versions.
+    pub fn with_schema_evolution(mut self, evolution: SchemaEvolution) -> Self {
+        self.evolution = Some(Arc::new(evolution));
+        self
+    }
+
+    /// Limit the receiving messages, applying the overflow policy on the exceeding ones.
+    ///
+    /// NOTE: The in-flight limit is ignored, as the subscribers do not track the processing.
+    pub fn with_rate_limit(mut self, options: RateLimitOptions) -> Self {
+        self.rate_limit = Some(RateLimiter::new(options));
+        self
+    }
+}
+
 #[async_trait]
 impl<Value> Subscriber<Value> for PipeSubscriber<Value>
 where
@@ -279,9 +824,51 @@ where
         err(Display),
     )]
     async fn read_one(&mut self) -> Result<Option<PipeMessage<Value>>> {
-        match self.inner.read_one().await? {
-            Some(msg) => msg.load_payloads(&self.storage).await.map(Some),
-            None => Ok(None),
+        loop {
+            let msg = match (&self.rate_limit, &self.evolution) {
+                (None, None) => self.inner.read_one().await?,
+                (rate_limit, evolution) => match self.inner.read_one_raw().await? {
+                    Some(raw) => {
+                        if let Some(rate_limit) = rate_limit {
+                            if !rate_limit.admit(raw.data.len()).await? {
+                                warn!(
+                                    "dropping message from {topic}: rate limit exceeded",
+                                    topic = self.topic,
+                                );
+                                continue;
+                            }
+                        }
+                        Some(raw.decode_with(evolution.as_deref())?)
+                    }
+                    None => None,
+                },
+            };
+
+            match msg {
+                Some(msg) => {
+                    if let Some(dedup) = &self.dedup {
+                        if !dedup.insert(msg.id(), msg.timestamp()).await? {
+                            continue;
+                        }
+                    }
+                    Span::current().set_parent(msg.extract_context());
+                    break msg.load_payloads(&self.storage).await.map(Some);
+                }
+                None => break Ok(None),
+            }
         }
     }
+
+    #[instrument(
+        level = Level::INFO,
+        skip_all,
+        fields(
+            data.len = %1usize,
+            data.model = %self.topic.as_str(),
+        ),
+        err(Display),
+    )]
+    async fn read_one_raw(&mut self) -> Result<Option<RawMessage>> {
+        self.inner.read_one_raw().await
+    }
 }
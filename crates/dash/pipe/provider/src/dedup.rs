@@ -0,0 +1,194 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    path::PathBuf,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tokio::{
+    fs::{self, File, OpenOptions},
+    io::AsyncWriteExt,
+    sync::Mutex,
+};
+use tracing::{instrument, Level};
+use uuid::Uuid;
+
+/// A sliding window of the delivered message ids.
+#[async_trait]
+pub trait DedupStore
+where
+    Self: Send + Sync,
+{
+    /// Mark the message as delivered, returning `false` if it has been already delivered.
+    async fn insert(&self, id: Uuid, timestamp: DateTime<Utc>) -> Result<bool>;
+}
+
+#[derive(Default)]
+struct DedupWindow {
+    ids: HashSet<Uuid>,
+    queue: VecDeque<(DateTime<Utc>, Uuid)>,
+}
+
+impl DedupWindow {
+    fn insert(&mut self, id: Uuid, timestamp: DateTime<Utc>) -> bool {
+        if self.ids.insert(id) {
+            self.queue.push_back((timestamp, id));
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Evict the ids older than the window, or beyond the capacity.
+    fn evict(&mut self, window: Duration, capacity: usize) -> usize {
+        let deadline = Utc::now() - window;
+
+        let mut num_evicted = 0;
+        while let Some(&(timestamp, id)) = self.queue.front() {
+            if timestamp >= deadline && self.queue.len() <= capacity {
+                break;
+            }
+            self.queue.pop_front();
+            self.ids.remove(&id);
+            num_evicted += 1;
+        }
+        num_evicted
+    }
+}
+
+/// A volatile dedup store, which forgets the window on restart.
+pub struct MemoryDedupStore {
+    capacity: usize,
+    window: Duration,
+    inner: Mutex<DedupWindow>,
+}
+
+impl MemoryDedupStore {
+    pub fn new(window: Duration, capacity: usize) -> Self {
+        Self {
+            capacity,
+            window,
+            inner: Default::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl DedupStore for MemoryDedupStore {
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    async fn insert(&self, id: Uuid, timestamp: DateTime<Utc>) -> Result<bool> {
+        let mut inner = self.inner.lock().await;
+        inner.evict(self.window, self.capacity);
+        Ok(inner.insert(id, timestamp))
+    }
+}
+
+/// A persistent dedup store, which journals the window into a local file.
+pub struct FileDedupStore {
+    capacity: usize,
+    path: PathBuf,
+    window: Duration,
+    inner: Mutex<(DedupWindow, File)>,
+}
+
+impl FileDedupStore {
+    #[instrument(level = Level::INFO, err(Display))]
+    pub async fn try_new(path: PathBuf, window: Duration, capacity: usize) -> Result<Self> {
+        let mut inner = DedupWindow::default();
+        match fs::read_to_string(&path).await {
+            Ok(journal) => {
+                for line in journal.lines() {
+                    if let Some((timestamp, id)) = parse_journal_line(line) {
+                        inner.insert(id, timestamp);
+                    }
+                }
+            }
+            Err(error) if error.kind() == ::std::io::ErrorKind::NotFound => (),
+            Err(error) => {
+                return Err(anyhow!(
+                    "failed to load dedup journal {path}: {error}",
+                    path = path.display(),
+                ))
+            }
+        }
+        inner.evict(window, capacity);
+
+        let file = write_journal(&path, &inner).await?;
+        Ok(Self {
+            capacity,
+            path,
+            window,
+            inner: Mutex::new((inner, file)),
+        })
+    }
+}
+
+#[async_trait]
+impl DedupStore for FileDedupStore {
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    async fn insert(&self, id: Uuid, timestamp: DateTime<Utc>) -> Result<bool> {
+        let mut guard = self.inner.lock().await;
+        let (inner, file) = &mut *guard;
+
+        // Compact the journal once the evicted ids outnumber the live ones
+        let num_evicted = inner.evict(self.window, self.capacity);
+        if num_evicted > 0 && num_evicted >= inner.queue.len() {
+            *file = write_journal(&self.path, inner).await?;
+        }
+
+        if inner.insert(id, timestamp) {
+            file.write_all(format!("{} {id}\n", timestamp.to_rfc3339()).as_bytes())
+                .await
+                .map_err(|error| anyhow!("failed to write dedup journal: {error}"))?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+fn parse_journal_line(line: &str) -> Option<(DateTime<Utc>, Uuid)> {
+    let (timestamp, id) = line.split_once(' ')?;
+    Some((
+        DateTime::parse_from_rfc3339(timestamp).ok()?.into(),
+        id.parse().ok()?,
+    ))
+}
+
+async fn write_journal(path: &PathBuf, window: &DedupWindow) -> Result<File> {
+    let journal: String = window
+        .queue
+        .iter()
+        .map(|(timestamp, id)| format!("{} {id}\n", timestamp.to_rfc3339()))
+        .collect();
+    fs::write(path, journal)
+        .await
+        .map_err(|error| anyhow!("failed to compact dedup journal: {error}"))?;
+
+    OpenOptions::new()
+        .append(true)
+        .open(path)
+        .await
+        .map_err(|error| anyhow!("failed to open dedup journal: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_evicts_by_capacity() {
+        let mut window = DedupWindow::default();
+        let ids: Vec<_> = (0..3).map(|_| Uuid::new_v4()).collect();
+        for &id in &ids {
+            assert!(window.insert(id, Utc::now()));
+        }
+        assert!(!window.insert(ids[0], Utc::now()));
+
+        assert_eq!(window.evict(Duration::from_secs(60), 2), 1);
+        assert!(window.insert(ids[0], Utc::now()));
+        assert!(!window.insert(ids[2], Utc::now()));
+    }
+}
@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Error, Result};
+use ark_core_k8s::data::Name;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{instrument, warn, Level};
+
+use crate::{
+    message::{Codec, PipeMessage},
+    messengers::{Messenger, Publisher},
+};
+
+/// A message which has been given up after the delivery attempts.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetter {
+    /// The number of the failed delivery attempts
+    pub attempts: u32,
+    /// The last error message
+    pub error: String,
+    /// The source topic of the message
+    pub model: Name,
+    /// The raw message, exactly as received
+    pub payload: Bytes,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl DeadLetter {
+    const TOPIC_SUFFIX: &'static str = ".dlq";
+
+    /// Return the dead-letter topic of the given topic.
+    pub fn topic(model: &Name) -> Result<Name> {
+        format!("{model}{suffix}", suffix = Self::TOPIC_SUFFIX)
+            .parse()
+            .map_err(|error| anyhow!("failed to parse dead-letter topic of {model}: {error}"))
+    }
+}
+
+pub(crate) struct DeadLetterQueue {
+    encoder: Codec,
+    max_attempts: u32,
+    model: Name,
+    stream: Arc<dyn Publisher>,
+}
+
+impl DeadLetterQueue {
+    #[instrument(level = Level::INFO, skip(messenger), err(Display))]
+    pub(crate) async fn try_new<Value>(
+        messenger: &dyn Messenger<Value>,
+        encoder: Codec,
+        model: &Name,
+        max_attempts: u32,
+    ) -> Result<Self> {
+        Ok(Self {
+            encoder,
+            max_attempts: max_attempts.max(1),
+            model: model.clone(),
+            stream: messenger.publish(DeadLetter::topic(model)?).await?,
+        })
+    }
+
+    pub(crate) const fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    #[instrument(
+        level = Level::INFO,
+        skip_all,
+        fields(
+            data.len = %payloads.len(),
+            data.model = %self.stream.topic().as_str(),
+        ),
+        err(Display),
+    )]
+    pub(crate) async fn send(
+        &self,
+        payloads: &[Bytes],
+        attempts: u32,
+        error: &Error,
+    ) -> Result<()> {
+        warn!(
+            "moving {len} message(s) to {topic} after {attempts} attempt(s): {error}",
+            len = payloads.len(),
+            topic = self.stream.topic(),
+        );
+
+        for payload in payloads {
+            let letter = DeadLetter {
+                attempts,
+                error: error.to_string(),
+                model: self.model.clone(),
+                payload: payload.clone(),
+                timestamp: Utc::now(),
+            };
+            let data = PipeMessage::<_, Bytes>::new(letter)
+                .to_bytes(self.encoder)
+                .map_err(|error| anyhow!("failed to encode dead letter: {error}"))?;
+            self.stream
+                .send_one(data)
+                .await
+                .map_err(|error| anyhow!("failed to send dead letter: {error}"))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,202 @@
+use std::{any::type_name, fmt, sync::Arc, time::Instant};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use clap::Parser;
+use derivative::Derivative;
+use opentelemetry::{
+    global,
+    metrics::{Counter, Histogram},
+    KeyValue,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{instrument, Level};
+
+use crate::{message::PipeMessages, storage::StorageIO};
+
+use super::{Function, FunctionBuilder, FunctionContext};
+
+/// Compose the pipe functions into a single in-process pipeline.
+///
+/// The intermediate messages are passed in memory, skipping the messengers and the storages.
+/// Each stage is measured as a [`Stage`].
+///
+/// ```ignore
+/// type MyPipeline = dash_pipe_provider::chain!(Parse, Enrich, Filter);
+///
+/// PipeArgs::<MyPipeline>::from_env().loop_forever()
+/// ```
+#[macro_export]
+macro_rules! chain {
+    ( $function:ty $(,)? ) => {
+        $crate::chain::Stage<$function>
+    };
+    ( $function:ty, $( $rest:ty ),+ $(,)? ) => {
+        $crate::chain::Chain<
+            $crate::chain::Stage<$function>,
+            $crate::chain!($( $rest ),+),
+        >
+    };
+}
+
+#[async_trait]
+impl<F1, F2> FunctionBuilder for Chain<F1, F2>
+where
+    F1: Send + FunctionBuilder,
+    F2: Send + FunctionBuilder + Function<Input = <F1 as Function>::Output>,
+    <F1 as FunctionBuilder>::Args: Sync + fmt::Debug,
+    <F2 as FunctionBuilder>::Args: Sync + fmt::Debug,
+{
+    type Args = ChainArgs<<F1 as FunctionBuilder>::Args, <F2 as FunctionBuilder>::Args>;
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn try_new(
+        args: &<Self as FunctionBuilder>::Args,
+        mut ctx: Option<&mut FunctionContext>,
+        storage: &Arc<StorageIO>,
+    ) -> Result<Self> {
+        Ok(Self {
+            first: <F1 as FunctionBuilder>::try_new(&args.first, ctx.as_deref_mut(), storage)
+                .await?,
+            second: <F2 as FunctionBuilder>::try_new(&args.second, ctx, storage).await?,
+        })
+    }
+}
+
+#[async_trait]
+impl<F1, F2> Function for Chain<F1, F2>
+where
+    F1: Send + Function + FunctionBuilder,
+    F2: Send + Function<Input = <F1 as Function>::Output> + FunctionBuilder,
+{
+    type Input = <F1 as Function>::Input;
+    type Output = <F2 as Function>::Output;
+
+    async fn tick(
+        &mut self,
+        inputs: PipeMessages<<Self as Function>::Input>,
+    ) -> Result<PipeMessages<<Self as Function>::Output>> {
+        match self.first.tick(inputs).await? {
+            PipeMessages::None => Ok(PipeMessages::None),
+            outputs => self.second.tick(outputs).await,
+        }
+    }
+}
+
+#[derive(Derivative)]
+#[derivative(Debug(bound = "
+        F1: fmt::Debug,
+        F2: fmt::Debug,
+    "))]
+pub struct Chain<F1, F2>
+where
+    F1: FunctionBuilder,
+    F2: FunctionBuilder,
+{
+    first: F1,
+    second: F2,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Parser)]
+pub struct ChainArgs<F1, F2>
+where
+    F1: ::clap::Args,
+    F2: ::clap::Args,
+{
+    #[command(flatten)]
+    first: F1,
+
+    #[command(flatten)]
+    second: F2,
+}
+
+#[async_trait]
+impl<F> FunctionBuilder for Stage<F>
+where
+    F: Send + FunctionBuilder,
+{
+    type Args = <F as FunctionBuilder>::Args;
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn try_new(
+        args: &<Self as FunctionBuilder>::Args,
+        ctx: Option<&mut FunctionContext>,
+        storage: &Arc<StorageIO>,
+    ) -> Result<Self> {
+        Ok(Self {
+            function: <F as FunctionBuilder>::try_new(args, ctx, storage).await?,
+            metrics: StageMetrics::new(type_name::<F>()),
+        })
+    }
+}
+
+#[async_trait]
+impl<F> Function for Stage<F>
+where
+    F: Send + Function + FunctionBuilder,
+{
+    type Input = <F as Function>::Input;
+    type Output = <F as Function>::Output;
+
+    async fn tick(
+        &mut self,
+        inputs: PipeMessages<<Self as Function>::Input>,
+    ) -> Result<PipeMessages<<Self as Function>::Output>> {
+        let StageMetrics {
+            attributes,
+            duration,
+            errors,
+            messages,
+        } = &self.metrics;
+
+        messages.add(inputs.len() as u64, attributes);
+        let timestamp = Instant::now();
+        let result = self.function.tick(inputs).await;
+        duration.record(timestamp.elapsed().as_secs_f64(), attributes);
+        if result.is_err() {
+            errors.add(1, attributes);
+        }
+        result
+    }
+}
+
+/// A measured stage of the in-process pipeline.
+#[derive(Derivative)]
+#[derivative(Debug(bound = "F: fmt::Debug"))]
+pub struct Stage<F>
+where
+    F: FunctionBuilder,
+{
+    function: F,
+    #[derivative(Debug = "ignore")]
+    metrics: StageMetrics,
+}
+
+struct StageMetrics {
+    attributes: [KeyValue; 1],
+    duration: Histogram<f64>,
+    errors: Counter<u64>,
+    messages: Counter<u64>,
+}
+
+impl StageMetrics {
+    fn new(stage: &'static str) -> Self {
+        let meter = global::meter("dash-pipe");
+        Self {
+            attributes: [KeyValue::new("stage", stage)],
+            duration: meter
+                .f64_histogram("dash_pipe_function_stage_duration")
+                .with_description("The elapsed time of a pipeline stage per tick")
+                .with_unit("s")
+                .build(),
+            errors: meter
+                .u64_counter("dash_pipe_function_stage_errors")
+                .with_description("The number of the failed ticks of a pipeline stage")
+                .build(),
+            messages: meter
+                .u64_counter("dash_pipe_function_stage_messages")
+                .with_description("The number of the input messages of a pipeline stage")
+                .build(),
+        }
+    }
+}
@@ -0,0 +1,276 @@
+//! A bridge between the HTTP services and the pipe functions.
+//!
+//! - [`PipeHttpService`] exposes the pipe functions as HTTP endpoints.
+//! - [`HttpRemoteFunction`] exposes an HTTP endpoint as a pipe function.
+
+use std::{fmt, sync::Arc};
+
+use actix_web::{
+    http::{
+        header::{self, HeaderMap},
+        StatusCode,
+    },
+    web, HttpRequest, HttpResponse, ResponseError,
+};
+use anyhow::{anyhow, Result};
+use ark_core_k8s::data::Name;
+use async_trait::async_trait;
+use bytes::Bytes;
+use serde_json::json;
+use tracing::{instrument, Level};
+use url::Url;
+
+use crate::{
+    client::PipeClient,
+    message::{Codec, DynValue, PipeMessage, PipePayload},
+};
+
+use super::RemoteFunction;
+
+/// The payload key of the HTTP bodies which are not structured.
+pub const BODY_PAYLOAD_KEY: &str = "body";
+
+const CONTENT_TYPE_CBOR: &str = "application/cbor";
+const CONTENT_TYPE_JSON: &str = "application/json";
+const CONTENT_TYPE_MESSAGE_PACK: &str = "application/msgpack";
+const CONTENT_TYPE_OCTET_STREAM: &str = "application/octet-stream";
+
+/// An HTTP service forwarding the requests to the pipe functions.
+///
+/// The structured bodies (JSON, MessagePack and CBOR) become the message
+/// values, and the others become the `body` payload of the messages.
+#[derive(Clone)]
+pub struct PipeHttpService {
+    client: Arc<PipeClient>,
+}
+
+impl PipeHttpService {
+    pub fn new(client: PipeClient) -> Self {
+        Self {
+            client: Arc::new(client),
+        }
+    }
+
+    /// Register `POST /{topic}`, which calls the pipe function of the topic.
+    pub fn configure(self, cfg: &mut web::ServiceConfig) {
+        cfg.app_data(web::Data::new(self))
+            .route("/{topic}", web::post().to(call));
+    }
+}
+
+#[instrument(level = Level::INFO, skip(service, request, body))]
+async fn call(
+    service: web::Data<PipeHttpService>,
+    request: HttpRequest,
+    topic: web::Path<String>,
+    body: Bytes,
+) -> Result<HttpResponse, HttpError> {
+    let topic: Name = topic
+        .parse()
+        .map_err(|error| HttpError::BadRequest(anyhow!("invalid topic: {error}")))?;
+    let accept = negotiate_accept(request.headers())?;
+    let input = decode_request(request.headers(), body)?;
+
+    let output: PipeMessage = service
+        .client
+        .call(topic, input)
+        .await
+        .map_err(HttpError::BadGateway)?;
+    encode_response(accept, output)
+}
+
+/// The response format, negotiated by the `Accept` header.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Accept {
+    Codec(Codec),
+    OctetStream,
+}
+
+fn negotiate_accept(headers: &HeaderMap) -> Result<Accept, HttpError> {
+    let accept = match headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+    {
+        Some(accept) => accept,
+        None => return Ok(Accept::Codec(Codec::default())),
+    };
+
+    accept
+        .split(',')
+        .map(|media_type| media_type.split(';').next().unwrap_or_default().trim())
+        .find_map(|media_type| match media_type {
+            "*/*" | "application/*" => Some(Accept::Codec(Codec::default())),
+            CONTENT_TYPE_OCTET_STREAM => Some(Accept::OctetStream),
+            media_type => codec_from_content_type(media_type).map(Accept::Codec),
+        })
+        .ok_or_else(|| HttpError::NotAcceptable(anyhow!("unsupported media type: {accept}")))
+}
+
+fn decode_request(headers: &HeaderMap, body: Bytes) -> Result<PipeMessage, HttpError> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|value| value.trim())
+        .unwrap_or(CONTENT_TYPE_JSON);
+
+    match codec_from_content_type(content_type) {
+        Some(codec) => decode_value(codec, &body)
+            .map(PipeMessage::new)
+            .map_err(HttpError::BadRequest),
+        None => Ok(PipeMessage::with_payloads(
+            vec![PipePayload::new(BODY_PAYLOAD_KEY.into(), Some(body))],
+            json!({
+                "contentType": content_type,
+            }),
+        )),
+    }
+}
+
+fn encode_response(accept: Accept, message: PipeMessage) -> Result<HttpResponse, HttpError> {
+    match accept {
+        Accept::Codec(codec) => encode_value(codec, &message.value)
+            .map(|body| {
+                HttpResponse::Ok()
+                    .content_type(content_type_from_codec(codec))
+                    .body(body)
+            })
+            .map_err(HttpError::Internal),
+        Accept::OctetStream => message
+            .payloads
+            .into_iter()
+            .find(|payload| payload.key() == BODY_PAYLOAD_KEY)
+            .and_then(|payload| payload.value)
+            .map(|body| {
+                HttpResponse::Ok()
+                    .content_type(CONTENT_TYPE_OCTET_STREAM)
+                    .body(body)
+            })
+            .ok_or_else(|| HttpError::NotAcceptable(anyhow!("no binary body in the response"))),
+    }
+}
+
+fn codec_from_content_type(content_type: &str) -> Option<Codec> {
+    match content_type {
+        CONTENT_TYPE_CBOR => Some(Codec::Cbor),
+        CONTENT_TYPE_JSON => Some(Codec::Json),
+        CONTENT_TYPE_MESSAGE_PACK | "application/x-msgpack" => Some(Codec::MessagePack),
+        _ => None,
+    }
+}
+
+const fn content_type_from_codec(codec: Codec) -> &'static str {
+    match codec {
+        Codec::Cbor => CONTENT_TYPE_CBOR,
+        Codec::Json => CONTENT_TYPE_JSON,
+        Codec::MessagePack => CONTENT_TYPE_MESSAGE_PACK,
+    }
+}
+
+fn decode_value(codec: Codec, data: &[u8]) -> Result<DynValue> {
+    match codec {
+        Codec::Cbor => ::ciborium::from_reader(data).map_err(Into::into),
+        Codec::Json => ::serde_json::from_slice(data).map_err(Into::into),
+        Codec::MessagePack => ::rmp_serde::from_slice(data).map_err(Into::into),
+    }
+}
+
+fn encode_value(codec: Codec, value: &DynValue) -> Result<Vec<u8>> {
+    match codec {
+        Codec::Cbor => {
+            let mut buf = Vec::default();
+            ::ciborium::into_writer(value, &mut buf)
+                .map(|()| buf)
+                .map_err(Into::into)
+        }
+        Codec::Json => ::serde_json::to_vec(value).map_err(Into::into),
+        Codec::MessagePack => ::rmp_serde::to_vec_named(value).map_err(Into::into),
+    }
+}
+
+#[derive(Debug)]
+enum HttpError {
+    BadGateway(::anyhow::Error),
+    BadRequest(::anyhow::Error),
+    Internal(::anyhow::Error),
+    NotAcceptable(::anyhow::Error),
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadGateway(error)
+            | Self::BadRequest(error)
+            | Self::Internal(error)
+            | Self::NotAcceptable(error) => error.fmt(f),
+        }
+    }
+}
+
+impl ResponseError for HttpError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::BadGateway(_) => StatusCode::BAD_GATEWAY,
+            Self::BadRequest(_) => StatusCode::BAD_REQUEST,
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::NotAcceptable(_) => StatusCode::NOT_ACCEPTABLE,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(json!({
+            "error": self.to_string(),
+        }))
+    }
+}
+
+/// A pipe function forwarding the message values to an HTTP endpoint in JSON.
+#[derive(Clone, Debug)]
+pub struct HttpRemoteFunction {
+    client: ::reqwest::Client,
+    endpoint: Url,
+}
+
+impl HttpRemoteFunction {
+    pub fn new(endpoint: Url) -> Self {
+        Self {
+            client: ::reqwest::Client::default(),
+            endpoint,
+        }
+    }
+}
+
+#[async_trait]
+impl RemoteFunction for HttpRemoteFunction {
+    type Input = DynValue;
+    type Output = DynValue;
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn call_one(
+        &self,
+        input: PipeMessage<<Self as RemoteFunction>::Input>,
+    ) -> Result<PipeMessage<<Self as RemoteFunction>::Output>> {
+        let response = self
+            .client
+            .post(self.endpoint.clone())
+            .json(&input.value)
+            .send()
+            .await
+            .map_err(|error| anyhow!("failed to call {}: {error}", &self.endpoint))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let reason = response.text().await.unwrap_or_default();
+            return Err(anyhow!(
+                "failed to call {}: {status}: {reason}",
+                &self.endpoint,
+            ));
+        }
+
+        response
+            .json()
+            .await
+            .map(PipeMessage::new)
+            .map_err(|error| anyhow!("failed to parse response of {}: {error}", &self.endpoint))
+    }
+}
@@ -1,4 +1,8 @@
+pub mod chain;
 pub mod connector;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod window;
 
 use std::{fmt, marker::PhantomData, ops, sync::Arc};
 
@@ -0,0 +1,497 @@
+use std::{collections::BTreeMap, fmt, marker::PhantomData, sync::Arc};
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use derivative::Derivative;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+use tracing::{debug, instrument, Level};
+
+use crate::{
+    message::{DynValue, PipeMessage, PipeMessages},
+    storage::StorageIO,
+};
+
+use super::{Function, FunctionBuilder, FunctionContext};
+
+/// A folding operator over the messages of a window.
+pub trait WindowAggregator<Input>
+where
+    Self: Send + Sync,
+{
+    type State: Send + Sync;
+    type Output;
+
+    fn init(&self) -> <Self as WindowAggregator<Input>>::State;
+
+    fn fold(
+        &self,
+        state: &mut <Self as WindowAggregator<Input>>::State,
+        input: &PipeMessage<Input>,
+    );
+
+    fn finish(
+        &self,
+        state: <Self as WindowAggregator<Input>>::State,
+    ) -> <Self as WindowAggregator<Input>>::Output;
+}
+
+/// A built-in aggregator over a numeric field of the dynamic messages.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldAggregator {
+    aggregate: WindowAggregate,
+    /// The JSON pointer of the field, e.g. `/metrics/cpu`
+    field: Option<String>,
+}
+
+impl FieldAggregator {
+    pub fn try_new(aggregate: WindowAggregate, field: Option<String>) -> Result<Self> {
+        match (aggregate, &field) {
+            (WindowAggregate::Count, _) | (_, Some(_)) => Ok(Self { aggregate, field }),
+            (aggregate, None) => bail!("window field is required for {aggregate} aggregation"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct FieldAggregatorState {
+    count: u64,
+    max: Option<f64>,
+    min: Option<f64>,
+    sum: f64,
+}
+
+impl WindowAggregator<DynValue> for FieldAggregator {
+    type State = FieldAggregatorState;
+    type Output = DynValue;
+
+    fn init(&self) -> <Self as WindowAggregator<DynValue>>::State {
+        FieldAggregatorState::default()
+    }
+
+    fn fold(
+        &self,
+        state: &mut <Self as WindowAggregator<DynValue>>::State,
+        input: &PipeMessage<DynValue>,
+    ) {
+        let value = match &self.field {
+            Some(field) => match input.value.pointer(field).and_then(|value| value.as_f64()) {
+                Some(value) => value,
+                // skip the messages without the numeric field
+                None => return,
+            },
+            None => 0.0,
+        };
+
+        state.count += 1;
+        state.max = Some(state.max.map_or(value, |max| max.max(value)));
+        state.min = Some(state.min.map_or(value, |min| min.min(value)));
+        state.sum += value;
+    }
+
+    fn finish(
+        &self,
+        state: <Self as WindowAggregator<DynValue>>::State,
+    ) -> <Self as WindowAggregator<DynValue>>::Output {
+        match self.aggregate {
+            WindowAggregate::Count => state.count.into(),
+            WindowAggregate::Max => state.max.into(),
+            WindowAggregate::Mean if state.count > 0 => (state.sum / state.count as f64).into(),
+            WindowAggregate::Mean => DynValue::Null,
+            WindowAggregate::Min => state.min.into(),
+            WindowAggregate::Sum => state.sum.into(),
+        }
+    }
+}
+
+/// A custom aggregator folding the messages with the given function.
+pub struct FoldAggregator<Input, State, F> {
+    _input: PhantomData<fn(Input)>,
+    f: F,
+    init: State,
+}
+
+impl<Input, State, F> FoldAggregator<Input, State, F> {
+    pub fn new(init: State, f: F) -> Self {
+        Self {
+            _input: PhantomData,
+            f,
+            init,
+        }
+    }
+}
+
+impl<Input, State, F> WindowAggregator<Input> for FoldAggregator<Input, State, F>
+where
+    State: Send + Sync + Clone,
+    F: Send + Sync + Fn(&mut State, &PipeMessage<Input>),
+{
+    type State = State;
+    type Output = State;
+
+    fn init(&self) -> <Self as WindowAggregator<Input>>::State {
+        self.init.clone()
+    }
+
+    fn fold(
+        &self,
+        state: &mut <Self as WindowAggregator<Input>>::State,
+        input: &PipeMessage<Input>,
+    ) {
+        (self.f)(state, input)
+    }
+
+    fn finish(
+        &self,
+        state: <Self as WindowAggregator<Input>>::State,
+    ) -> <Self as WindowAggregator<Input>>::Output {
+        state
+    }
+}
+
+/// The aggregated result of a closed window.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowOutput<Value = DynValue> {
+    /// The number of the folded messages
+    pub count: u64,
+    /// The exclusive end of the window
+    pub end: DateTime<Utc>,
+    /// The inclusive start of the window
+    pub start: DateTime<Utc>,
+    pub value: Value,
+}
+
+/// Event-time windows, which are closed once the watermark passes over.
+///
+/// The watermark follows the latest event time minus the allowed lateness,
+/// and the messages belonging only to the closed windows are dropped as late.
+pub struct Windows<Input, A>
+where
+    A: WindowAggregator<Input>,
+{
+    aggregator: A,
+    args: WindowArgs,
+    num_late: u64,
+    /// The open windows, indexed by their start (in milliseconds)
+    panes: BTreeMap<i64, (u64, <A as WindowAggregator<Input>>::State)>,
+    watermark: Option<i64>,
+}
+
+impl<Input, A> Windows<Input, A>
+where
+    A: WindowAggregator<Input>,
+{
+    pub fn try_new(args: WindowArgs, aggregator: A) -> Result<Self> {
+        if args.window_size_ms == 0 {
+            bail!("window size should be positive")
+        }
+        if args.slide_ms() == 0 || args.slide_ms() > args.window_size_ms {
+            bail!("window slide should be in (0, window size]")
+        }
+
+        Ok(Self {
+            aggregator,
+            args,
+            num_late: 0,
+            panes: BTreeMap::default(),
+            watermark: None,
+        })
+    }
+
+    /// Return the number of the dropped late messages.
+    pub const fn num_late(&self) -> u64 {
+        self.num_late
+    }
+
+    /// Fold the message into its windows, returning the windows closed by the new watermark.
+    pub fn push(
+        &mut self,
+        input: &PipeMessage<Input>,
+    ) -> Vec<WindowOutput<<A as WindowAggregator<Input>>::Output>> {
+        let timestamp = input.timestamp().timestamp_millis();
+        let size = self.args.window_size_ms as i64;
+        let slide = self.args.slide_ms() as i64;
+
+        let mut is_folded = false;
+        let mut start = timestamp.div_euclid(slide) * slide;
+        while start + size > timestamp {
+            if self
+                .watermark
+                .map_or(true, |watermark| start + size > watermark)
+            {
+                let (count, state) = self
+                    .panes
+                    .entry(start)
+                    .or_insert_with(|| (0, self.aggregator.init()));
+                *count += 1;
+                self.aggregator.fold(state, input);
+                is_folded = true;
+            }
+            start -= slide;
+        }
+        if !is_folded {
+            debug!("dropping late message: {id}", id = input.id());
+            self.num_late += 1;
+        }
+
+        let watermark = (timestamp - self.args.window_lateness_ms as i64)
+            .max(self.watermark.unwrap_or(i64::MIN));
+        self.watermark = Some(watermark);
+        self.close(|end| end <= watermark)
+    }
+
+    /// Close all the open windows regardless of the watermark.
+    pub fn flush(&mut self) -> Vec<WindowOutput<<A as WindowAggregator<Input>>::Output>> {
+        self.close(|_| true)
+    }
+
+    fn close(
+        &mut self,
+        is_closed: impl Fn(i64) -> bool,
+    ) -> Vec<WindowOutput<<A as WindowAggregator<Input>>::Output>> {
+        let size = self.args.window_size_ms as i64;
+
+        let mut outputs = Vec::default();
+        while let Some(entry) = self.panes.first_entry() {
+            let start = *entry.key();
+            if !is_closed(start + size) {
+                break;
+            }
+
+            let (count, state) = entry.remove();
+            match (
+                DateTime::from_timestamp_millis(start),
+                DateTime::from_timestamp_millis(start + size),
+            ) {
+                (Some(start), Some(end)) => outputs.push(WindowOutput {
+                    count,
+                    end,
+                    start,
+                    value: self.aggregator.finish(state),
+                }),
+                _ => debug!("dropping out-of-range window: {start}"),
+            }
+        }
+        outputs
+    }
+}
+
+#[async_trait]
+impl<F> FunctionBuilder for Window<F>
+where
+    F: Send + FunctionBuilder + Function<Input = WindowOutput>,
+    <F as FunctionBuilder>::Args: Sync + fmt::Debug,
+{
+    type Args = Args<<F as FunctionBuilder>::Args>;
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn try_new(
+        args: &<Self as FunctionBuilder>::Args,
+        ctx: Option<&mut FunctionContext>,
+        storage: &Arc<StorageIO>,
+    ) -> Result<Self> {
+        let aggregator = FieldAggregator::try_new(
+            args.window.window_aggregate,
+            args.window.window_field.clone(),
+        )?;
+
+        Ok(Self {
+            function: <F as FunctionBuilder>::try_new(&args.function, ctx, storage).await?,
+            windows: Windows::try_new(args.window.clone(), aggregator)?,
+        })
+    }
+}
+
+#[async_trait]
+impl<F> Function for Window<F>
+where
+    F: Send + Function<Input = WindowOutput> + FunctionBuilder,
+{
+    type Input = DynValue;
+    type Output = <F as Function>::Output;
+
+    async fn tick(
+        &mut self,
+        inputs: PipeMessages<<Self as Function>::Input>,
+    ) -> Result<PipeMessages<<Self as Function>::Output>> {
+        let outputs: Vec<_> = inputs
+            .into_vec()
+            .iter()
+            .flat_map(|input| self.windows.push(input))
+            .map(|output| {
+                let end = output.end;
+                PipeMessage::new(output).with_timestamp(end)
+            })
+            .collect();
+
+        if outputs.is_empty() {
+            Ok(PipeMessages::None)
+        } else {
+            self.function.tick(PipeMessages::Batch(outputs)).await
+        }
+    }
+}
+
+#[derive(Derivative)]
+#[derivative(Debug(bound = "
+        F: fmt::Debug,
+        <F as FunctionBuilder>::Args: fmt::Debug,
+    "))]
+pub struct Window<F>
+where
+    F: FunctionBuilder,
+{
+    function: F,
+    #[derivative(Debug = "ignore")]
+    windows: Windows<DynValue, FieldAggregator>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Parser)]
+pub struct Args<F>
+where
+    F: ::clap::Args,
+{
+    #[command(flatten)]
+    window: WindowArgs,
+
+    #[command(flatten)]
+    function: F,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Parser)]
+pub struct WindowArgs {
+    #[arg(
+        long,
+        env = "PIPE_WINDOW_AGGREGATE",
+        value_name = "AGGREGATE",
+        default_value_t = WindowAggregate::default(),
+    )]
+    #[serde(default)]
+    pub window_aggregate: WindowAggregate,
+
+    /// The JSON pointer of the aggregated field, e.g. `/metrics/cpu`.
+    #[arg(long, env = "PIPE_WINDOW_FIELD", value_name = "POINTER")]
+    #[serde(default)]
+    pub window_field: Option<String>,
+
+    /// The allowed lateness of the messages, delaying the watermark.
+    #[arg(
+        long,
+        env = "PIPE_WINDOW_LATENESS_MS",
+        value_name = "MILLISECONDS",
+        default_value_t = 0
+    )]
+    #[serde(default)]
+    pub window_lateness_ms: u64,
+
+    #[arg(long, env = "PIPE_WINDOW_SIZE_MS", value_name = "MILLISECONDS")]
+    pub window_size_ms: u64,
+
+    /// The slide of the windows; tumbling windows if not given.
+    #[arg(long, env = "PIPE_WINDOW_SLIDE_MS", value_name = "MILLISECONDS")]
+    #[serde(default)]
+    pub window_slide_ms: Option<u64>,
+}
+
+impl WindowArgs {
+    fn slide_ms(&self) -> u64 {
+        self.window_slide_ms.unwrap_or(self.window_size_ms)
+    }
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    Display,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum WindowAggregate {
+    #[default]
+    Count,
+    Max,
+    Mean,
+    Min,
+    Sum,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn message(timestamp_ms: i64, value: f64) -> PipeMessage<DynValue> {
+        PipeMessage::new(json!({ "value": value }))
+            .with_timestamp(DateTime::from_timestamp_millis(timestamp_ms).unwrap())
+    }
+
+    fn args(window_slide_ms: Option<u64>, window_lateness_ms: u64) -> WindowArgs {
+        WindowArgs {
+            window_aggregate: WindowAggregate::Sum,
+            window_field: Some("/value".into()),
+            window_lateness_ms,
+            window_size_ms: 10,
+            window_slide_ms,
+        }
+    }
+
+    #[test]
+    fn tumbling_window_sum() {
+        let aggregator =
+            FieldAggregator::try_new(WindowAggregate::Sum, Some("/value".into())).unwrap();
+        let mut windows = Windows::try_new(args(None, 0), aggregator).unwrap();
+
+        assert!(windows.push(&message(1, 1.0)).is_empty());
+        assert!(windows.push(&message(9, 2.0)).is_empty());
+
+        let outputs = windows.push(&message(10, 4.0));
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].count, 2);
+        assert_eq!(outputs[0].value, json!(3.0));
+
+        // the late message of the closed window is dropped
+        assert!(windows.push(&message(5, 8.0)).is_empty());
+        assert_eq!(windows.num_late(), 1);
+
+        let outputs = windows.flush();
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].value, json!(4.0));
+    }
+
+    #[test]
+    fn sliding_window_with_lateness() {
+        let aggregator = FoldAggregator::new(0u64, |count: &mut u64, _: &PipeMessage<DynValue>| {
+            *count += 1
+        });
+        let mut windows = Windows::try_new(args(Some(5), 5), aggregator).unwrap();
+
+        assert!(windows.push(&message(7, 1.0)).is_empty());
+        assert!(windows.push(&message(12, 1.0)).is_empty());
+        // a late message within the allowed lateness is still folded
+        assert!(windows.push(&message(3, 1.0)).is_empty());
+
+        let outputs = windows.push(&message(15, 1.0));
+        assert_eq!(
+            outputs
+                .iter()
+                .map(|output| (output.start.timestamp_millis(), output.value))
+                .collect::<Vec<_>>(),
+            [(0, 2)],
+        );
+        assert_eq!(windows.num_late(), 0);
+    }
+}
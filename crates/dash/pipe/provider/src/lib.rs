@@ -6,27 +6,60 @@ pub extern crate deltalake;
 #[cfg(feature = "lancedb")]
 pub extern crate lancedb;
 
+// NOTE: the derive macros refer to this crate by name
+extern crate self as dash_pipe_provider;
+
 mod client;
+mod dedup;
+mod dlq;
 mod function;
 mod message;
 pub mod messengers;
+mod outbox;
 mod pipe;
+mod ratelimit;
 pub mod schema;
+mod spill;
 pub mod storage;
+mod value;
 
 pub use ark_core_k8s::data::Name;
+pub use dash_pipe_derive::PipePayload;
 
-pub use self::client::{PipeClient, PipeClientArgs};
+pub use self::client::{
+    PipeBatchOptions, PipeClient, PipeClientArgs, PipePublisher, PipeSubscriber,
+};
+pub use self::dedup::{DedupStore, FileDedupStore, MemoryDedupStore};
+pub use self::dlq::DeadLetter;
 #[cfg(feature = "deltalake")]
 pub use self::function::deltalake::DeltaFunction;
+#[cfg(feature = "http")]
+pub use self::function::http::{HttpRemoteFunction, PipeHttpService};
 pub use self::function::{
-    connector, Function, FunctionBuilder, FunctionContext, FunctionSignalExt,
+    chain, connector, window, Function, FunctionBuilder, FunctionContext, FunctionSignalExt,
     GenericStatelessRemoteFunction, OwnedFunctionBuilder, RemoteFunction, StatelessRemoteFunction,
 };
 #[cfg(feature = "pyo3")]
 pub use self::message::PyPipeMessage;
 pub use self::message::{
-    Codec, DynMap, DynValue, MaybePipeMessage, PipeMessage, PipeMessages, PipePayload,
+    Codec, DynMap, DynValue, MaybePipeMessage, PipeLineage, PipeMessage, PipeMessages, PipePayload,
+    PipePayloadEncryption, PipePriority,
+};
+pub use self::messengers::{ConsumerGroup, ConsumerGroupAssignment, MessengerType};
+pub use self::outbox::{
+    MetadataOutboxStore, OutboxOptions, OutboxPayload, OutboxRecord, OutboxState, OutboxStore,
 };
-pub use self::messengers::MessengerType;
 pub use self::pipe::{DefaultModelIn, PipeArgs};
+pub use self::ratelimit::{RateLimitOptions, RateLimitOverflow};
+pub use self::spill::SpillOptions;
+pub use self::value::PipeValue;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use ::anyhow;
+    pub use ::bytes;
+    pub use ::schemars;
+    pub use ::serde;
+
+    pub use crate::value::{join_payload, split_payload};
+}
@@ -1,21 +1,29 @@
 use std::collections::HashMap;
 
-use anyhow::{bail, Error, Result};
+use anyhow::{anyhow, bail, Error, Result};
 use ark_core_k8s::data::Name;
 use bytes::Bytes;
 use chrono::{DateTime, Utc};
 use futures::{stream::FuturesOrdered, StreamExt, TryStreamExt};
+use opentelemetry::{global, Context};
 use schemars::JsonSchema;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 pub use serde_json::Value as DynValue;
 use strum::{Display, EnumString};
-use tracing::{instrument, Level};
+use tracing::{instrument, Level, Span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 use uuid::Uuid;
 
-use crate::storage::{StorageSet, StorageType};
+use crate::{
+    schema::registry::SchemaVersion,
+    storage::{StorageSet, StorageType},
+};
 
 pub type DynMap = serde_json::Map<String, DynValue>;
 
+/// The message headers, e.g. the W3C trace context (`traceparent`, `tracestate`).
+pub type PipeHeaders = HashMap<String, String>;
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum PipeMessages<Value = DynValue, Payload = Bytes>
@@ -58,6 +66,20 @@ where
         }
     }
 
+    pub(crate) fn as_lineage(&self, model: &Name, version: Option<i64>) -> Vec<PipeLineage> {
+        let lineage = |value: &PipeMessage<Value, Payload>| PipeLineage {
+            model: model.clone(),
+            version,
+            id: value.id(),
+        };
+
+        match self {
+            Self::None => Vec::default(),
+            Self::Single(value) => vec![lineage(value)],
+            Self::Batch(values) => values.iter().map(lineage).collect(),
+        }
+    }
+
     pub fn into_vec(self) -> Vec<PipeMessage<Value, Payload>> {
         match self {
             Self::None => Vec::default(),
@@ -142,9 +164,21 @@ impl<Value> PipeMessages<Value> {
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[::pyo3::pyclass]
 pub struct PyPipeMessage {
+    #[serde(
+        default,
+        rename = "__headers",
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    headers: PipeHeaders,
     id: Uuid,
     #[serde(default, rename = "__payloads")]
     payloads: Vec<PipePayload>,
+    #[serde(
+        default,
+        rename = "__priority",
+        skip_serializing_if = "PipePriority::is_normal"
+    )]
+    priority: PipePriority,
     #[serde(default, flatten, skip_serializing_if = "Option::is_none")]
     reply: Option<PipeReply>,
     #[serde(rename = "__timestamp")]
@@ -157,16 +191,20 @@ pub struct PyPipeMessage {
 impl From<PipeMessage> for PyPipeMessage {
     fn from(
         PipeMessage {
+            headers,
             id,
             payloads,
+            priority,
             timestamp,
             reply,
             value,
         }: PipeMessage,
     ) -> Self {
         Self {
+            headers,
             id,
             payloads,
+            priority,
             timestamp,
             reply,
             value,
@@ -178,16 +216,20 @@ impl From<PipeMessage> for PyPipeMessage {
 impl From<PyPipeMessage> for PipeMessage {
     fn from(
         PyPipeMessage {
+            headers,
             id,
             payloads,
+            priority,
             timestamp,
             reply,
             value,
         }: PyPipeMessage,
     ) -> Self {
         Self {
+            headers,
             id,
             payloads,
+            priority,
             timestamp,
             reply,
             value,
@@ -206,11 +248,13 @@ impl PyPipeMessage {
         reply: Option<(String, Option<String>)>,
     ) -> ::pyo3::PyResult<Self> {
         Ok(Self {
+            headers: PipeHeaders::default(),
             id: Uuid::new_v4(),
             payloads: payloads
                 .into_iter()
                 .map(|(key, value)| PipePayload::new(key, value.map(Into::into)))
                 .collect(),
+            priority: PipePriority::default(),
             reply: reply.map(|(inbox, target)| PipeReply {
                 inbox,
                 target: target.and_then(|target| target.parse().ok()),
@@ -226,6 +270,7 @@ impl PyPipeMessage {
             .iter()
             .map(
                 |PipePayload {
+                     encryption: _,
                      key,
                      model: _,
                      path: _,
@@ -236,6 +281,11 @@ impl PyPipeMessage {
             .collect()
     }
 
+    #[getter]
+    fn get_priority(&self) -> String {
+        self.priority.to_string()
+    }
+
     #[getter]
     fn get_reply(&self) -> Option<(&str, Option<&str>)> {
         self.reply.as_ref().map(|PipeReply { inbox, target }| {
@@ -278,10 +328,23 @@ pub struct MaybePipeMessage<Value = DynValue, Payload = Bytes>
 where
     Payload: JsonSchema,
 {
+    #[serde(
+        default,
+        rename = "__headers",
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    #[schemars(skip)]
+    headers: PipeHeaders,
     #[serde(default, rename = "__id", skip_serializing_if = "Option::is_none")]
     id: Option<Uuid>,
     #[serde(default, rename = "__payloads", skip_serializing_if = "Vec::is_empty")]
     pub payloads: Vec<PipePayload<Payload>>,
+    #[serde(
+        default,
+        rename = "__priority",
+        skip_serializing_if = "PipePriority::is_normal"
+    )]
+    priority: PipePriority,
     #[serde(default, flatten, skip_serializing_if = "Option::is_none")]
     pub(crate) reply: Option<PipeReply>,
     #[serde(
@@ -297,16 +360,20 @@ where
 impl From<MaybePipeMessage> for PipeMessage {
     fn from(message: MaybePipeMessage) -> Self {
         let MaybePipeMessage {
+            headers,
             id,
             payloads,
+            priority,
             reply,
             timestamp,
             value,
         } = message;
 
         Self {
+            headers,
             id: id.unwrap_or_else(Uuid::new_v4),
             payloads,
+            priority,
             reply,
             timestamp: timestamp.unwrap_or_else(Utc::now),
             value,
@@ -319,10 +386,23 @@ pub struct PipeMessage<Value = DynValue, Payload = Bytes>
 where
     Payload: JsonSchema,
 {
+    #[serde(
+        default,
+        rename = "__headers",
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    #[schemars(skip)]
+    headers: PipeHeaders,
     #[serde(rename = "__id")]
     id: Uuid,
     #[serde(rename = "__payloads")]
     pub payloads: Vec<PipePayload<Payload>>,
+    #[serde(
+        default,
+        rename = "__priority",
+        skip_serializing_if = "PipePriority::is_normal"
+    )]
+    priority: PipePriority,
     #[serde(default, flatten, skip_serializing_if = "Option::is_none")]
     pub(crate) reply: Option<PipeReply>,
     #[serde(rename = "__timestamp")]
@@ -339,12 +419,7 @@ where
     type Error = Error;
 
     fn try_from(value: &[u8]) -> Result<Self> {
-        match value.first().copied().map(Into::into) {
-            None | Some(OpCode::AsciiEnd) => ::serde_json::from_slice(value).map_err(Into::into),
-            Some(OpCode::MessagePack) => ::rmp_serde::from_slice(&value[1..]).map_err(Into::into),
-            Some(OpCode::Cbor) => ::ciborium::from_reader(&value[1..]).map_err(Into::into),
-            Some(OpCode::Unsupported) => bail!("cannot infer serde opcode"),
-        }
+        decode(value)
     }
 }
 
@@ -414,8 +489,10 @@ where
 {
     pub fn new(value: Value) -> Self {
         Self {
+            headers: PipeHeaders::default(),
             id: Uuid::new_v4(),
             payloads: Vec::default(),
+            priority: PipePriority::default(),
             timestamp: Utc::now(),
             reply: None,
             value,
@@ -424,8 +501,10 @@ where
 
     pub fn with_payloads(payloads: Vec<PipePayload<Payload>>, value: Value) -> Self {
         Self {
+            headers: PipeHeaders::default(),
             id: Uuid::new_v4(),
             payloads,
+            priority: PipePriority::default(),
             timestamp: Utc::now(),
             reply: None,
             value,
@@ -441,15 +520,17 @@ where
         P: JsonSchema,
     {
         Self {
+            headers: PipeHeaders::default(),
             id: Uuid::new_v4(),
             payloads,
+            // NOTE: the outputs follow the priority of the request
+            priority: request.priority,
             timestamp: Utc::now(),
             reply: request.reply.clone(),
             value,
         }
     }
 
-    #[cfg(feature = "nats")]
     pub(crate) fn with_reply_inbox(mut self, inbox: String) -> Self {
         if !inbox.is_empty() {
             self.reply = Some(PipeReply {
@@ -471,6 +552,11 @@ where
         self
     }
 
+    pub const fn with_priority(mut self, priority: PipePriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
     pub const fn with_timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
         self.timestamp = timestamp;
         self
@@ -495,12 +581,14 @@ where
         P: JsonSchema,
     {
         PipeMessage {
+            headers: self.headers,
             id: self.id,
             payloads: self
                 .payloads
                 .into_iter()
                 .map(|payload| payload.drop())
                 .collect(),
+            priority: self.priority,
             reply: self.reply,
             timestamp: self.timestamp,
             value: self.value,
@@ -513,18 +601,93 @@ where
         Value: Clone,
     {
         PipeMessage {
+            headers: self.headers.clone(),
             id: self.id,
             payloads: self
                 .payloads
                 .iter()
                 .map(|payload| payload.as_dropped())
                 .collect(),
+            priority: self.priority,
             reply: self.reply.clone(),
             timestamp: self.timestamp,
             value: self.value.clone(),
         }
     }
 
+    pub const fn id(&self) -> Uuid {
+        self.id
+    }
+
+    pub const fn headers(&self) -> &PipeHeaders {
+        &self.headers
+    }
+
+    /// Return the version of the writer schema, if recorded.
+    pub fn schema_version(&self) -> Option<u32> {
+        self.headers
+            .get(SchemaVersion::HEADER)
+            .and_then(|version| version.parse().ok())
+    }
+
+    pub(crate) fn set_schema_version(&mut self, version: u32) {
+        self.headers
+            .insert(SchemaVersion::HEADER.into(), version.to_string());
+    }
+
+    /// Return the input messages, which the message has been derived from.
+    pub fn lineage(&self) -> Vec<PipeLineage> {
+        self.headers
+            .get(PipeLineage::HEADER)
+            .and_then(|lineage| ::serde_json::from_str(lineage).ok())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn set_lineage(&mut self, lineage: &[PipeLineage]) {
+        if lineage.is_empty() {
+            return;
+        }
+        if let Ok(lineage) = ::serde_json::to_string(lineage) {
+            self.headers.insert(PipeLineage::HEADER.into(), lineage);
+        }
+    }
+
+    pub(crate) fn try_map_value<T>(
+        self,
+        f: impl FnOnce(Value) -> Result<T>,
+    ) -> Result<PipeMessage<T, Payload>> {
+        Ok(PipeMessage {
+            headers: self.headers,
+            id: self.id,
+            payloads: self.payloads,
+            priority: self.priority,
+            reply: self.reply,
+            timestamp: self.timestamp,
+            value: f(self.value)?,
+        })
+    }
+
+    pub(crate) fn payloads(&self) -> &[PipePayload<Payload>] {
+        &self.payloads
+    }
+
+    /// Inject the context of the current span, so that the receivers can continue the trace.
+    pub fn inject_context(&mut self) {
+        let context = Span::current().context();
+        global::get_text_map_propagator(|propagator| {
+            propagator.inject_context(&context, &mut self.headers)
+        })
+    }
+
+    /// Extract the propagated trace context, if any.
+    pub fn extract_context(&self) -> Context {
+        global::get_text_map_propagator(|propagator| propagator.extract(&self.headers))
+    }
+
+    pub const fn priority(&self) -> PipePriority {
+        self.priority
+    }
+
     pub const fn timestamp(&self) -> DateTime<Utc> {
         self.timestamp
     }
@@ -562,6 +725,7 @@ impl<Value> PipeMessage<Value> {
     #[instrument(level = Level::INFO, skip_all, err(Display))]
     pub(crate) async fn load_payloads(self, storage: &StorageSet) -> Result<Self> {
         Ok(Self {
+            headers: self.headers,
             id: self.id,
             payloads: self
                 .payloads
@@ -570,6 +734,7 @@ impl<Value> PipeMessage<Value> {
                 .collect::<FuturesOrdered<_>>()
                 .try_collect()
                 .await?,
+            priority: self.priority,
             reply: self.reply,
             timestamp: self.timestamp,
             value: self.value,
@@ -584,6 +749,7 @@ impl<Value> PipeMessage<Value> {
         input_payloads: Option<&HashMap<String, PipePayload>>,
     ) -> Result<Self> {
         Ok(Self {
+            headers: self.headers,
             id: self.id,
             payloads: self
                 .payloads
@@ -593,6 +759,7 @@ impl<Value> PipeMessage<Value> {
                 .filter_map(|payload| async { payload.transpose() })
                 .try_collect::<Vec<_>>()
                 .await?,
+            priority: self.priority,
             reply: self.reply,
             timestamp: self.timestamp,
             value: self.value,
@@ -605,6 +772,8 @@ pub struct PipePayload<Value = Bytes>
 where
     Value: JsonSchema,
 {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    encryption: Option<PipePayloadEncryption>,
     key: String,
     #[serde(default)]
     model: Option<Name>,
@@ -621,6 +790,7 @@ where
 {
     pub fn new(key: String, value: Option<Value>) -> Self {
         Self {
+            encryption: None,
             key,
             model: None,
             path: None,
@@ -629,11 +799,24 @@ where
         }
     }
 
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Return the location of the stored value, unless it is passed through.
+    pub(crate) fn location(&self) -> Option<(StorageType, &Name, &str)> {
+        match (self.storage?, self.model.as_ref()?, self.path.as_deref()?) {
+            (StorageType::Passthrough, _, _) => None,
+            (storage_type, model, path) => Some((storage_type, model, path)),
+        }
+    }
+
     fn drop<T>(self) -> PipePayload<T>
     where
         T: JsonSchema,
     {
         let Self {
+            encryption,
             key,
             model,
             path,
@@ -642,6 +825,7 @@ where
         } = self;
 
         PipePayload {
+            encryption,
             key,
             model,
             path,
@@ -655,6 +839,7 @@ where
         T: JsonSchema,
     {
         let Self {
+            encryption,
             key,
             model,
             path,
@@ -663,6 +848,7 @@ where
         } = self;
 
         PipePayload {
+            encryption: encryption.clone(),
             key: key.clone(),
             model: model.clone(),
             path: path.clone(),
@@ -680,6 +866,7 @@ impl PipePayload {
     #[instrument(level = Level::INFO, skip_all, err(Display))]
     async fn load(self, storage: &StorageSet) -> Result<Self> {
         let Self {
+            encryption,
             key,
             model,
             path,
@@ -691,17 +878,26 @@ impl PipePayload {
             key,
             value: match storage_type {
                 Some(StorageType::Passthrough) => value,
-                #[cfg(feature = "s3")]
-                Some(StorageType::S3) => match model.as_ref().zip(path.as_ref()) {
-                    Some((model, path)) => storage
-                        .get(StorageType::S3)
-                        .get(model, path)
-                        .await
-                        .map(Some)?,
+                #[cfg(any(feature = "azure", feature = "gcs", feature = "s3"))]
+                Some(storage_type) => match model.as_ref().zip(path.as_ref()) {
+                    Some((model, path)) => {
+                        let value = storage.get(storage_type).get(model, path).await?;
+                        match &encryption {
+                            #[cfg(feature = "encryption")]
+                            Some(encryption) => match storage.encryption() {
+                                Some(storage) => Some(storage.decrypt(&value, encryption).await?),
+                                None => bail!("storage encryption is not enabled: {key}"),
+                            },
+                            #[cfg(not(feature = "encryption"))]
+                            Some(_) => bail!("storage encryption is not supported: {key}"),
+                            None => Some(value),
+                        }
+                    }
                     None => None,
                 },
                 None => bail!("storage type not defined"),
             },
+            encryption,
             path,
             model,
             storage: storage_type,
@@ -716,6 +912,7 @@ impl PipePayload {
         input_payloads: Option<&HashMap<String, PipePayload>>,
     ) -> Result<Option<Self>> {
         let Self {
+            encryption: last_encryption,
             key,
             model: last_model,
             path: last_path,
@@ -724,6 +921,9 @@ impl PipePayload {
         } = self;
 
         let last = input_payloads.and_then(|map| map.get(&key));
+        let last_encryption = last
+            .and_then(|payload| payload.encryption.clone())
+            .or(last_encryption);
         let last_model = last
             .and_then(|payload| payload.model.clone())
             .or(last_model);
@@ -741,6 +941,7 @@ impl PipePayload {
         if last_model.is_some() && is_storage_same {
             // do not restore the payloads to the same storage
             Ok(Some(Self {
+                encryption: last_encryption,
                 storage: last_storage_type,
                 path: last_path,
                 model: last_model,
@@ -750,22 +951,36 @@ impl PipePayload {
         } else {
             match next_storage_type {
                 StorageType::Passthrough => Ok(Some(Self {
+                    encryption: None,
                     storage: Some(next_storage_type),
                     path: None,
                     model: None,
                     key,
                     value,
                 })),
-                #[cfg(feature = "s3")]
-                StorageType::S3 => match model.or_else(|| next_storage.model()).cloned().zip(value)
-                {
-                    Some((next_model, value)) => Ok(Some(Self {
-                        storage: Some(next_storage_type),
-                        path: Some(next_storage.put(Some(&next_model), &key, value).await?),
-                        model: Some(next_model),
-                        key,
-                        value: None,
-                    })),
+                #[cfg(any(feature = "azure", feature = "gcs", feature = "s3"))]
+                _ => match model.or_else(|| next_storage.model()).cloned().zip(value) {
+                    Some((next_model, value)) => {
+                        #[cfg(feature = "encryption")]
+                        let (value, encryption) = match storage.encryption() {
+                            Some(storage) => storage
+                                .encrypt(&value)
+                                .await
+                                .map(|(value, encryption)| (value, Some(encryption)))?,
+                            None => (value, None),
+                        };
+                        #[cfg(not(feature = "encryption"))]
+                        let encryption = None;
+
+                        Ok(Some(Self {
+                            encryption,
+                            storage: Some(next_storage_type),
+                            path: Some(next_storage.put(Some(&next_model), &key, value).await?),
+                            model: Some(next_model),
+                            key,
+                            value: None,
+                        }))
+                    }
                     None => Ok(None),
                 },
             }
@@ -773,6 +988,35 @@ impl PipePayload {
     }
 }
 
+/// The envelope encryption metadata of a payload at rest.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PipePayloadEncryption {
+    /// The data key, wrapped by the key encryption key
+    pub data_key: Bytes,
+    /// The id of the key encryption key
+    pub key_id: String,
+    pub nonce: Bytes,
+}
+
+/// An input message, which the message has been derived from.
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct PipeLineage {
+    pub model: Name,
+    /// The generation of the input model, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<i64>,
+    pub id: Uuid,
+}
+
+impl PipeLineage {
+    /// The message header recording the input messages.
+    pub const HEADER: &'static str = "lineage";
+}
+
 #[derive(
     Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
 )]
@@ -806,6 +1050,100 @@ pub enum Codec {
     Cbor,
 }
 
+/// The delivery priority of the message.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    EnumString,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum PipePriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+impl PipePriority {
+    /// All priorities, in the order of draining.
+    pub const ALL: [Self; 3] = [Self::High, Self::Normal, Self::Low];
+
+    const fn is_normal(&self) -> bool {
+        matches!(self, Self::Normal)
+    }
+
+    /// Return the number of messages to be drained per round, when all lanes are busy.
+    pub(crate) const fn weight(&self) -> u32 {
+        match self {
+            Self::Low => 1,
+            Self::Normal => 2,
+            Self::High => 4,
+        }
+    }
+
+    /// Return the topic of the priority lane.
+    ///
+    /// NOTE: the normal lane is the topic itself, so that the lanes are compatible with plain subscribers.
+    pub fn topic(&self, model: &Name) -> Result<Name> {
+        match self {
+            Self::Low => format!("{model}.priority-low").parse(),
+            Self::Normal => Ok(model.clone()),
+            Self::High => format!("{model}.priority-high").parse(),
+        }
+        .map_err(|error| anyhow!("failed to parse {self} priority topic of {model}: {error}"))
+    }
+
+    /// Read the priority of the encoded message, falling back to the default one.
+    pub(crate) fn peek(data: &[u8]) -> Self {
+        #[derive(Deserialize)]
+        struct Header {
+            #[serde(default, rename = "__priority")]
+            priority: PipePriority,
+        }
+
+        decode::<Header>(data)
+            .map(|header| header.priority)
+            .unwrap_or_default()
+    }
+}
+
+/// The identity of the encoded message, which is read without decoding the value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize)]
+pub(crate) struct PipeMessageHeader {
+    #[serde(rename = "__id")]
+    pub(crate) id: Uuid,
+    #[serde(rename = "__timestamp")]
+    pub(crate) timestamp: DateTime<Utc>,
+}
+
+impl PipeMessageHeader {
+    pub(crate) fn peek(data: &[u8]) -> Option<Self> {
+        decode(data).ok()
+    }
+}
+
+fn decode<T>(value: &[u8]) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    match value.first().copied().map(Into::into) {
+        None | Some(OpCode::AsciiEnd) => ::serde_json::from_slice(value).map_err(Into::into),
+        Some(OpCode::MessagePack) => ::rmp_serde::from_slice(&value[1..]).map_err(Into::into),
+        Some(OpCode::Cbor) => ::ciborium::from_reader(&value[1..]).map_err(Into::into),
+        Some(OpCode::Unsupported) => bail!("cannot infer serde opcode"),
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 enum OpCode {
     // Special opcodes
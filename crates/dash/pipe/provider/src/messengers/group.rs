@@ -0,0 +1,451 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use ark_core_k8s::data::Name;
+use async_trait::async_trait;
+use chrono::Utc;
+use opentelemetry::{
+    global,
+    metrics::{Counter, Histogram},
+    KeyValue,
+};
+use schemars::JsonSchema;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tokio::{
+    select,
+    sync::watch,
+    task::JoinHandle,
+    time::{interval, Instant, MissedTickBehavior},
+};
+use tracing::{info, instrument, warn, Level};
+
+use crate::message::{Codec, PipeMessage, PipeMessageHeader};
+
+use super::RawMessage;
+
+/// A group of the replicas sharing the messages of a topic,
+/// so that each message is processed by one of them.
+///
+/// The messages are balanced by the messenger if supported (e.g. NATS queue groups, Kafka groups).
+/// Otherwise, the members exchange their heartbeats and take the partitions of the message ids
+/// assigned to them.
+#[derive(Clone, Debug)]
+pub struct ConsumerGroup {
+    heartbeat_interval: Duration,
+    member: String,
+    metrics: Arc<ConsumerGroupMetrics>,
+    name: Name,
+    partitions: u32,
+    state: Arc<watch::Sender<ConsumerGroupAssignment>>,
+}
+
+impl ConsumerGroup {
+    pub const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+    pub const DEFAULT_PARTITIONS: u32 = 64;
+
+    /// Join the group as the member named after the hostname.
+    pub fn try_new(name: Name) -> Result<Self> {
+        let member = ::gethostname::gethostname()
+            .into_string()
+            .map_err(|_| anyhow!("failed to get hostname as consumer group member"))?;
+        Ok(Self::with_member(name, member))
+    }
+
+    pub fn with_member(name: Name, member: impl Into<String>) -> Self {
+        let member = member.into();
+        Self {
+            heartbeat_interval: Self::DEFAULT_HEARTBEAT_INTERVAL,
+            metrics: Arc::new(ConsumerGroupMetrics::new(&name, &member)),
+            member,
+            name,
+            partitions: Self::DEFAULT_PARTITIONS,
+            state: Arc::new(watch::channel(ConsumerGroupAssignment::default()).0),
+        }
+    }
+
+    pub fn with_heartbeat_interval(mut self, heartbeat_interval: Duration) -> Self {
+        self.heartbeat_interval = heartbeat_interval;
+        self
+    }
+
+    /// Set the number of the partitions shared by the members without the messenger's help.
+    ///
+    /// NOTE: all members should have the same number of partitions.
+    pub fn with_partitions(mut self, partitions: u32) -> Self {
+        self.partitions = partitions.max(1);
+        self
+    }
+
+    pub fn member(&self) -> &str {
+        &self.member
+    }
+
+    pub fn name(&self) -> &Name {
+        &self.name
+    }
+
+    /// Watch the assignments of this member, which are changed on every rebalance.
+    pub fn subscribe_rebalances(&self) -> watch::Receiver<ConsumerGroupAssignment> {
+        self.state.subscribe()
+    }
+
+    /// Return the topic which the members exchange their heartbeats through.
+    fn control_topic(&self, topic: &Name) -> Result<Name> {
+        let name = &self.name;
+        format!("{topic}.group-{name}")
+            .parse()
+            .map_err(|error| anyhow!("failed to parse consumer group topic of {topic}: {error}"))
+    }
+
+    /// Replace the assignment of this member, notifying the watchers if changed.
+    pub(super) fn rebalance(&self, members: BTreeSet<String>, partitions: BTreeSet<u32>) {
+        let Self { member, name, .. } = self;
+        let is_changed = self.state.send_if_modified(|state| {
+            if state.members == members && state.partitions == partitions {
+                return false;
+            }
+
+            state.generation += 1;
+            state.members = members;
+            state.partitions = partitions;
+            info!(
+                "Rebalanced consumer group {name}: {member} owns {num_partitions} partitions among {num_members} members (generation {generation})",
+                generation = state.generation,
+                num_members = state.members.len(),
+                num_partitions = state.partitions.len(),
+            );
+            true
+        });
+        if is_changed {
+            self.metrics.rebalances.add(1, &self.metrics.attributes);
+        }
+    }
+
+    fn owns(&self, partition: u32) -> bool {
+        self.state.borrow().partitions.contains(&partition)
+    }
+}
+
+/// The share of the group assigned to this member.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsumerGroupAssignment {
+    /// Increased on every rebalance
+    pub generation: u64,
+    /// The live members of the group, as far as this member knows
+    pub members: BTreeSet<String>,
+    /// The partitions owned by this member; empty if the messenger balances the messages by itself
+    pub partitions: BTreeSet<u32>,
+}
+
+#[derive(Debug)]
+struct ConsumerGroupMetrics {
+    attributes: [KeyValue; 2],
+    lag: Histogram<f64>,
+    messages: Counter<u64>,
+    rebalances: Counter<u64>,
+}
+
+impl ConsumerGroupMetrics {
+    fn new(name: &Name, member: &str) -> Self {
+        let meter = global::meter("dash-pipe");
+        Self {
+            attributes: [
+                KeyValue::new("group", name.to_string()),
+                KeyValue::new("member", member.to_string()),
+            ],
+            lag: meter
+                .f64_histogram("dash_pipe_consumer_group_lag")
+                .with_description("The delay between publishing and consuming the messages")
+                .with_unit("s")
+                .build(),
+            messages: meter
+                .u64_counter("dash_pipe_consumer_group_messages")
+                .with_description("The number of the messages consumed by the member")
+                .build(),
+            rebalances: meter
+                .u64_counter("dash_pipe_consumer_group_rebalances")
+                .with_description("The number of the rebalances observed by the member")
+                .build(),
+        }
+    }
+
+    fn record(&self, topic: &Name, header: Option<PipeMessageHeader>) {
+        let [group, member] = self.attributes.clone();
+        let attributes = [group, member, KeyValue::new("topic", topic.to_string())];
+
+        self.messages.add(1, &attributes);
+        if let Some(PipeMessageHeader { timestamp, .. }) = header {
+            let lag = (Utc::now() - timestamp).num_milliseconds().max(0) as f64 / 1_000.0;
+            self.lag.record(lag, &attributes);
+        }
+    }
+}
+
+/// Join the group with the internal coordinator, for the messengers without consumer groups.
+///
+/// NOTE: the messages may be dropped or duplicated while the members disagree on the membership,
+/// i.e. for a few heartbeats after a member joins or leaves.
+#[instrument(level = Level::INFO, skip(messenger, group), fields(group = %group.name), err(Display))]
+pub(super) async fn subscribe<M, Value>(
+    messenger: &M,
+    topic: Name,
+    group: ConsumerGroup,
+) -> Result<Box<dyn super::Subscriber<Value>>>
+where
+    M: ?Sized + super::Messenger<Value>,
+    Value: 'static + Send + DeserializeOwned,
+{
+    let control_topic = group.control_topic(&topic)?;
+    let publisher = messenger.publish(control_topic.clone()).await?;
+    let heartbeats = messenger.subscribe(control_topic).await?;
+    let inner = messenger.subscribe(topic).await?;
+
+    // NOTE: take all partitions until the other members are found
+    let members = BTreeSet::from([group.member.clone()]);
+    let partitions = assign(&members, &group.member, group.partitions);
+    group.rebalance(members, partitions);
+
+    Ok(Box::new(Subscriber {
+        coordinator: Some(::tokio::spawn(coordinate(
+            group.clone(),
+            publisher,
+            heartbeats,
+        ))),
+        group,
+        inner,
+    }))
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Heartbeat {
+    member: String,
+}
+
+async fn coordinate<Value>(
+    group: ConsumerGroup,
+    publisher: Arc<dyn super::Publisher>,
+    mut heartbeats: Box<dyn super::Subscriber<Value>>,
+) where
+    Value: Send + DeserializeOwned,
+{
+    let name = &group.name;
+    let heartbeat = Heartbeat {
+        member: group.member.clone(),
+    };
+    let data = match PipeMessage::<Heartbeat>::new(heartbeat).to_bytes(Codec::default()) {
+        Ok(data) => data,
+        Err(error) => {
+            warn!("failed to encode consumer group heartbeat ({name}): {error}");
+            return;
+        }
+    };
+
+    let session_timeout = group.heartbeat_interval * 3;
+    let mut membership = Membership::default();
+    let mut ticker = interval(group.heartbeat_interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        select! {
+            _ = ticker.tick() => {
+                if let Err(error) = publisher.send_one(data.clone()).await {
+                    warn!("failed to send consumer group heartbeat ({name}): {error}");
+                }
+
+                // NOTE: this member is always alive for itself
+                let now = Instant::now();
+                membership.observe(group.member.clone(), now);
+                membership.expire(now, session_timeout);
+            }
+            message = heartbeats.read_one_raw() => match message {
+                Ok(Some(message)) => match message.decode::<Heartbeat>() {
+                    Ok(message) => membership.observe(message.value.member, Instant::now()),
+                    Err(error) => warn!("failed to decode consumer group heartbeat ({name}): {error}"),
+                },
+                Ok(None) => {
+                    warn!("consumer group heartbeats are closed: {name}");
+                    break;
+                }
+                Err(error) => warn!("failed to receive consumer group heartbeat ({name}): {error}"),
+            },
+        }
+
+        let members = membership.members();
+        let partitions = assign(&members, &group.member, group.partitions);
+        group.rebalance(members, partitions);
+    }
+}
+
+#[derive(Default)]
+struct Membership {
+    last_seen: BTreeMap<String, Instant>,
+}
+
+impl Membership {
+    fn observe(&mut self, member: String, now: Instant) {
+        self.last_seen.insert(member, now);
+    }
+
+    /// Drop the members which have not sent any heartbeats within the timeout.
+    fn expire(&mut self, now: Instant, timeout: Duration) {
+        self.last_seen
+            .retain(|_, last_seen| now.duration_since(*last_seen) <= timeout);
+    }
+
+    fn members(&self) -> BTreeSet<String> {
+        self.last_seen.keys().cloned().collect()
+    }
+}
+
+/// Assign the partitions to the members by rendezvous hashing,
+/// so that only the partitions of the joined or left members are moved on rebalances.
+fn assign(members: &BTreeSet<String>, member: &str, partitions: u32) -> BTreeSet<u32> {
+    (0..partitions)
+        .filter(|&partition| {
+            members
+                .iter()
+                .max_by_key(|candidate| {
+                    let seed = fnv1a(FNV_OFFSET, candidate.as_bytes());
+                    fnv1a(seed, &partition.to_le_bytes())
+                })
+                .is_some_and(|owner| owner == member)
+        })
+        .collect()
+}
+
+/// Return the partition of the message, which is derived from its id if readable.
+fn partition_of(data: &[u8], header: Option<&PipeMessageHeader>, partitions: u32) -> u32 {
+    let hash = match header {
+        Some(header) => fnv1a(FNV_OFFSET, header.id.as_bytes()),
+        None => fnv1a(FNV_OFFSET, data),
+    };
+    (hash % partitions.max(1) as u64) as u32
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// NOTE: the hashes should be stable across the members, unlike the `std` hashers.
+fn fnv1a(seed: u64, data: &[u8]) -> u64 {
+    data.iter().fold(seed, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Consumes the messages of the group, recording the lag of this member.
+pub(super) struct Subscriber<Value> {
+    /// The internal coordinator, if the messenger does not balance the messages
+    coordinator: Option<JoinHandle<()>>,
+    group: ConsumerGroup,
+    inner: Box<dyn super::Subscriber<Value>>,
+}
+
+impl<Value> Subscriber<Value> {
+    /// Wrap the subscriber whose messages are balanced by the messenger.
+    pub(super) fn native(inner: Box<dyn super::Subscriber<Value>>, group: ConsumerGroup) -> Self {
+        Self {
+            coordinator: None,
+            group,
+            inner,
+        }
+    }
+}
+
+impl<Value> Drop for Subscriber<Value> {
+    fn drop(&mut self) {
+        if let Some(coordinator) = self.coordinator.take() {
+            coordinator.abort();
+        }
+    }
+}
+
+#[async_trait]
+impl<Value> super::Subscriber<Value> for Subscriber<Value>
+where
+    Value: Send + DeserializeOwned,
+{
+    fn topic(&self) -> &Name {
+        self.inner.topic()
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn read_one(&mut self) -> Result<Option<PipeMessage<Value>>> {
+        self.read_one_raw()
+            .await?
+            .map(|message| message.decode())
+            .transpose()
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn read_one_raw(&mut self) -> Result<Option<RawMessage>> {
+        loop {
+            let message = match self.inner.read_one_raw().await? {
+                Some(message) => message,
+                None => return Ok(None),
+            };
+
+            // Skip the messages owned by the other members
+            let header = PipeMessageHeader::peek(&message.data);
+            if self.coordinator.is_some() {
+                let partition = partition_of(&message.data, header.as_ref(), self.group.partitions);
+                if !self.group.owns(partition) {
+                    continue;
+                }
+            }
+
+            self.group.metrics.record(self.inner.topic(), header);
+            break Ok(Some(message));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn members(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn assign_partitions_exclusively() {
+        let members = members(&["a", "b", "c"]);
+        let assignments: Vec<_> = members
+            .iter()
+            .map(|member| assign(&members, member, 64))
+            .collect();
+
+        let total: usize = assignments.iter().map(BTreeSet::len).sum();
+        assert_eq!(total, 64);
+
+        let merged: BTreeSet<_> = assignments.into_iter().flatten().collect();
+        assert_eq!(merged, (0..64).collect());
+    }
+
+    #[test]
+    fn rebalance_only_partitions_of_left_member() {
+        let before = members(&["a", "b", "c"]);
+        let after = members(&["a", "b"]);
+
+        for member in ["a", "b"] {
+            let kept = assign(&before, member, 64);
+            let now = assign(&after, member, 64);
+            assert!(kept.is_subset(&now), "{member} has lost its partitions");
+        }
+    }
+
+    #[test]
+    fn expire_silent_members() {
+        let now = Instant::now();
+        let timeout = Duration::from_secs(15);
+
+        let mut membership = Membership::default();
+        membership.observe("a".into(), now);
+        membership.observe("b".into(), now + Duration::from_secs(10));
+        membership.expire(now + Duration::from_secs(20), timeout);
+        assert_eq!(membership.members(), members(&["b"]));
+    }
+}
@@ -1,18 +1,21 @@
-use std::sync::Arc;
+use std::{collections::BTreeSet, sync::Arc};
 
 use anyhow::{anyhow, bail, Result};
 use ark_core_k8s::data::Name;
 use async_trait::async_trait;
 use bytes::Bytes;
 use clap::Parser;
+use futures::future::try_join_all;
 use rdkafka::{
-    consumer::{Consumer, StreamConsumer},
+    consumer::{
+        BaseConsumer, Consumer, ConsumerContext, DefaultConsumerContext, Rebalance, StreamConsumer,
+    },
     producer::{FutureProducer, FutureRecord, Producer},
     util::Timeout,
-    ClientConfig, Message,
+    ClientConfig, ClientContext, Message,
 };
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use tracing::{debug, instrument, Level};
+use tracing::{debug, instrument, warn, Level};
 
 use crate::message::PipeMessage;
 
@@ -57,6 +60,62 @@ impl<Value> super::Messenger<Value> for Messenger {
             .map_err(|error| anyhow!("failed to subscribe Kafka topic: {error}"))?;
         Ok(Box::new(Subscriber { consumer, topic }))
     }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn subscribe_group(
+        &self,
+        topic: Name,
+        group: super::ConsumerGroup,
+    ) -> Result<Box<dyn super::Subscriber<Value>>>
+    where
+        Value: 'static + Send + DeserializeOwned,
+    {
+        let mut config = self.config.clone();
+        config
+            .set("group.id", group.name().as_str())
+            .set("client.id", group.member());
+
+        let context = GroupContext {
+            group: group.clone(),
+        };
+        let consumer: StreamConsumer<GroupContext> = config.create_with_context(context)?;
+        consumer
+            .subscribe(&[&topic])
+            .map_err(|error| anyhow!("failed to subscribe Kafka topic: {error}"))?;
+        Ok(Box::new(super::group::Subscriber::native(
+            Box::new(Subscriber { consumer, topic }),
+            group,
+        )))
+    }
+}
+
+/// Notifies the rebalances of the Kafka consumer group.
+struct GroupContext {
+    group: super::ConsumerGroup,
+}
+
+impl ClientContext for GroupContext {}
+
+impl ConsumerContext for GroupContext {
+    fn post_rebalance(&self, _: &BaseConsumer<Self>, rebalance: &Rebalance<'_>) {
+        // NOTE: the other members are hidden by the Kafka brokers
+        let members = BTreeSet::from([self.group.member().to_string()]);
+        match rebalance {
+            Rebalance::Assign(partitions) => {
+                let partitions = partitions
+                    .elements()
+                    .iter()
+                    .filter_map(|element| element.partition().try_into().ok())
+                    .collect();
+                self.group.rebalance(members, partitions)
+            }
+            Rebalance::Revoke(_) => self.group.rebalance(members, BTreeSet::default()),
+            Rebalance::Error(error) => {
+                let name = self.group.name();
+                warn!("failed to rebalance Kafka consumer group ({name}): {error}")
+            }
+        }
+    }
 }
 
 pub struct Publisher {
@@ -116,6 +175,28 @@ impl super::Publisher for Publisher {
             .map_err(|(error, _)| anyhow!("failed to publish data to Kafka: {error}"))
     }
 
+    #[instrument(
+        level = Level::INFO,
+        skip_all,
+        fields(
+            data.len = %data.len(),
+            data.model = %self.topic.as_str(),
+        ),
+        err(Display),
+    )]
+    async fn send_batch(&self, data: Vec<Bytes>) -> Result<()> {
+        // enqueue all records first, and then wait for the deliveries
+        try_join_all(data.iter().map(|data| {
+            self.client.send(
+                FutureRecord::<[u8], [u8]>::to(&self.topic).payload(&**data),
+                Timeout::Never,
+            )
+        }))
+        .await
+        .map(|_| ())
+        .map_err(|(error, _)| anyhow!("failed to publish data to Kafka: {error}"))
+    }
+
     #[instrument(
         level = Level::INFO,
         skip_all,
@@ -132,15 +213,19 @@ impl super::Publisher for Publisher {
     }
 }
 
-pub struct Subscriber {
-    consumer: StreamConsumer,
+pub struct Subscriber<C = DefaultConsumerContext>
+where
+    C: 'static + ConsumerContext,
+{
+    consumer: StreamConsumer<C>,
     topic: Name,
 }
 
 #[async_trait]
-impl<Value> super::Subscriber<Value> for Subscriber
+impl<C, Value> super::Subscriber<Value> for Subscriber<C>
 where
     Self: Send + Sync,
+    C: 'static + ConsumerContext,
     Value: Send + DeserializeOwned,
 {
     fn topic(&self) -> &Name {
@@ -169,6 +254,28 @@ where
                     .map(|input: PipeMessage<Value>| Some(input.drop_reply()))
             })
     }
+
+    #[instrument(
+        level = Level::INFO,
+        skip_all,
+        fields(
+            data.len = %1usize,
+            data.model = %self.topic.as_str(),
+        ),
+        err(Display),
+    )]
+    async fn read_one_raw(&mut self) -> Result<Option<super::RawMessage>> {
+        self.consumer
+            .recv()
+            .await
+            .map(|message| {
+                Some(super::RawMessage {
+                    data: Bytes::copy_from_slice(message.payload().unwrap_or_default()),
+                    inbox: None,
+                })
+            })
+            .map_err(|error| anyhow!("failed to subscribe Kafka input: {error}"))
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Parser)]
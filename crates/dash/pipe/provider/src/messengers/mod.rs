@@ -1,35 +1,70 @@
+mod group;
 #[cfg(feature = "kafka")]
 mod kafka;
+#[cfg(feature = "mqtt")]
+mod mqtt;
 #[cfg(feature = "nats")]
 mod nats;
+mod priority;
 #[cfg(feature = "ros2")]
 mod ros2;
 
+pub use self::group::{ConsumerGroup, ConsumerGroupAssignment};
+#[cfg(feature = "mqtt")]
+pub use self::mqtt::sanitize_topic_name;
+
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
 use ark_core_k8s::data::Name;
 use async_trait::async_trait;
 use bytes::Bytes;
-use clap::Parser;
+use clap::{ArgAction, Parser};
 use schemars::JsonSchema;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use strum::{Display, EnumString};
 use tracing::{debug, instrument, Level};
 
-use crate::message::{PipeMessage, PipeReply};
+use crate::{
+    message::{PipeMessage, PipeReply},
+    schema::evolution::SchemaEvolution,
+};
 
 #[instrument(level = Level::INFO, skip_all, err(Display))]
 pub async fn init_messenger<Value>(args: &MessengerArgs) -> Result<Box<dyn Messenger<Value>>> {
     debug!("Initializing Messenger IO");
 
+    fn with_priority_lanes<M, Value>(
+        args: &MessengerArgs,
+        messenger: M,
+    ) -> Box<dyn Messenger<Value>>
+    where
+        M: 'static + Messenger<Value> + Messenger<()>,
+    {
+        if args.priority_lanes {
+            Box::new(self::priority::Messenger::new(messenger))
+        } else {
+            Box::new(messenger)
+        }
+    }
+
     Ok(match args.default_messenger {
         #[cfg(feature = "kafka")]
-        MessengerType::Kafka => Box::new(self::kafka::Messenger::try_new(&args.kafka)?),
+        MessengerType::Kafka => {
+            with_priority_lanes(args, self::kafka::Messenger::try_new(&args.kafka)?)
+        }
+        #[cfg(feature = "mqtt")]
+        MessengerType::Mqtt => {
+            with_priority_lanes(args, self::mqtt::Messenger::try_new(&args.mqtt).await?)
+        }
         #[cfg(feature = "nats")]
-        MessengerType::Nats => Box::new(self::nats::Messenger::try_new(&args.nats).await?),
+        MessengerType::Nats => {
+            with_priority_lanes(args, self::nats::Messenger::try_new(&args.nats).await?)
+        }
         #[cfg(feature = "ros2")]
-        MessengerType::Ros2 => Box::new(self::ros2::Messenger::try_new(&args.ros2)?),
+        MessengerType::Ros2 => {
+            with_priority_lanes(args, self::ros2::Messenger::try_new(&args.ros2)?)
+        }
     })
 }
 
@@ -57,6 +92,21 @@ where
     {
         self.subscribe(topic).await
     }
+
+    /// Join the consumer group, sharing the messages of the topic among its members.
+    ///
+    /// NOTE: the messengers without consumer groups are coordinated by the members themselves.
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn subscribe_group(
+        &self,
+        topic: Name,
+        group: ConsumerGroup,
+    ) -> Result<Box<dyn Subscriber<Value>>>
+    where
+        Value: 'static + Send + DeserializeOwned,
+    {
+        self::group::subscribe(self, topic, group).await
+    }
 }
 
 #[async_trait]
@@ -92,6 +142,18 @@ where
     {
         <T as Messenger<Value>>::subscribe_queued(*self, topic, queue_group).await
     }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn subscribe_group(
+        &self,
+        topic: Name,
+        group: ConsumerGroup,
+    ) -> Result<Box<dyn Subscriber<Value>>>
+    where
+        Value: 'static + Send + DeserializeOwned,
+    {
+        <T as Messenger<Value>>::subscribe_group(*self, topic, group).await
+    }
 }
 
 #[async_trait]
@@ -124,6 +186,18 @@ impl<Value> Messenger<Value> for Box<dyn Messenger<Value>> {
     {
         self.as_ref().subscribe_queued(topic, queue_group).await
     }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn subscribe_group(
+        &self,
+        topic: Name,
+        group: ConsumerGroup,
+    ) -> Result<Box<dyn Subscriber<Value>>>
+    where
+        Value: 'static + Send + DeserializeOwned,
+    {
+        self.as_ref().subscribe_group(topic, group).await
+    }
 }
 
 #[async_trait]
@@ -146,6 +220,16 @@ where
     where
         Value: 'async_trait;
 
+    async fn send_batch(&self, data: Vec<Value>) -> Result<()>
+    where
+        Value: 'async_trait + Send,
+    {
+        for data in data {
+            self.send_one(data).await?;
+        }
+        Ok(())
+    }
+
     async fn flush(&self) -> Result<()>;
 }
 
@@ -186,6 +270,45 @@ where
     fn topic(&self) -> &Name;
 
     async fn read_one(&mut self) -> Result<Option<PipeMessage<Value>>>;
+
+    /// Read a message without decoding, so that it can be forwarded as-is.
+    async fn read_one_raw(&mut self) -> Result<Option<RawMessage>>;
+}
+
+/// A received message before decoding.
+#[derive(Clone, Debug)]
+pub struct RawMessage {
+    pub data: Bytes,
+    /// The request-reply inbox, if any
+    pub inbox: Option<String>,
+}
+
+impl RawMessage {
+    pub(crate) fn decode<Value>(&self) -> Result<PipeMessage<Value>>
+    where
+        Value: DeserializeOwned,
+    {
+        self.decode_with(None)
+    }
+
+    /// Decode the message, fitting it into the reader schema if given.
+    pub(crate) fn decode_with<Value>(
+        &self,
+        evolution: Option<&SchemaEvolution>,
+    ) -> Result<PipeMessage<Value>>
+    where
+        Value: DeserializeOwned,
+    {
+        let input: PipeMessage<Value> = match evolution {
+            Some(evolution) => evolution.decode(&self.data),
+            None => self.data.as_ref().try_into(),
+        }
+        .map_err(|error| anyhow!("failed to decode input: {error}"))?;
+        Ok(match &self.inbox {
+            Some(inbox) => input.with_reply_inbox(inbox.clone()),
+            None => input.drop_reply(),
+        })
+    }
 }
 
 #[derive(
@@ -212,6 +335,18 @@ pub enum MessengerType {
     )]
     Kafka,
 
+    #[cfg(feature = "mqtt")]
+    #[cfg_attr(
+        all(
+            not(feature = "nats"),
+            not(feature = "ros2"),
+            not(feature = "kafka"),
+            feature = "mqtt",
+        ),
+        default
+    )]
+    Mqtt,
+
     #[cfg(feature = "nats")]
     #[cfg_attr(feature = "nats", default)]
     Nats,
@@ -229,6 +364,8 @@ impl MessengerType {
         match self {
             #[cfg(feature = "kafka")]
             Self::Kafka => true,
+            #[cfg(feature = "mqtt")]
+            Self::Mqtt => false,
             #[cfg(feature = "nats")]
             Self::Nats => true,
             #[cfg(feature = "ros2")]
@@ -241,6 +378,8 @@ impl MessengerType {
         match self {
             #[cfg(feature = "kafka")]
             Self::Kafka => false,
+            #[cfg(feature = "mqtt")]
+            Self::Mqtt => false,
             #[cfg(feature = "nats")]
             Self::Nats => false,
             #[cfg(feature = "ros2")]
@@ -254,10 +393,19 @@ pub struct MessengerArgs {
     #[arg(long, env = "PIPE_DEFAULT_MESSENGER", value_name = "TYPE", default_value_t = Default::default())]
     default_messenger: MessengerType,
 
+    /// Shard the topics into the priority lanes, draining them by the weights of priorities.
+    #[arg(long, env = "PIPE_PRIORITY_LANES", action = ArgAction::SetTrue)]
+    #[serde(default)]
+    priority_lanes: bool,
+
     #[cfg(feature = "kafka")]
     #[command(flatten)]
     kafka: self::kafka::MessengerKafkaArgs,
 
+    #[cfg(feature = "mqtt")]
+    #[command(flatten)]
+    mqtt: self::mqtt::MessengerMqttArgs,
+
     #[cfg(feature = "nats")]
     #[command(flatten)]
     nats: self::nats::MessengerNatsArgs,
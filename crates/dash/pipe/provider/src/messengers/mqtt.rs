@@ -0,0 +1,469 @@
+use std::{collections::HashMap, path::PathBuf, sync::Arc, time::Duration};
+
+use anyhow::{anyhow, bail, Result};
+use ark_core_k8s::data::Name;
+use async_trait::async_trait;
+use bytes::Bytes;
+use clap::Parser;
+use rumqttc::v5::{
+    mqttbytes::{
+        v5::{LastWill, Packet},
+        QoS,
+    },
+    AsyncClient, Event, EventLoop, MqttOptions,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tokio::{
+    sync::{mpsc, Mutex},
+    task::JoinHandle,
+};
+use tracing::{debug, instrument, warn, Level};
+
+use crate::message::PipeMessage;
+
+type Routes = Arc<Mutex<HashMap<String, Vec<mpsc::Sender<Bytes>>>>>;
+
+pub struct Messenger {
+    _handle: Arc<MessengerHandle>,
+    client: AsyncClient,
+    qos: QoS,
+    routes: Routes,
+}
+
+impl Messenger {
+    const PAYLOAD_OFFLINE: &'static str = "offline";
+    const PAYLOAD_ONLINE: &'static str = "online";
+
+    #[instrument(level = Level::INFO, err(Display))]
+    pub async fn try_new(args: &MessengerMqttArgs) -> Result<Self> {
+        debug!("Initializing Messenger IO - MQTT");
+
+        #[instrument(level = Level::INFO, skip_all, err(Display))]
+        async fn parse_password(args: &MessengerMqttArgs) -> Result<Option<String>> {
+            fn parse_plain(password: impl AsRef<str>) -> String {
+                password
+                    .as_ref()
+                    .split('\n')
+                    .next()
+                    .unwrap()
+                    .trim()
+                    .to_string()
+            }
+
+            match args.mqtt_password_path.as_ref() {
+                Some(path) => ::tokio::fs::read_to_string(path)
+                    .await
+                    .map(parse_plain)
+                    .map(Some)
+                    .map_err(|error| anyhow!("failed to get MQTT password: {error}")),
+                None => match args.mqtt_password.as_ref() {
+                    Some(plain) => Ok(Some(parse_plain(plain))),
+                    None => Ok(None),
+                },
+            }
+        }
+
+        // parse data
+        let client_id = match args.mqtt_client_id.clone() {
+            Some(client_id) => client_id,
+            None => ::gethostname::gethostname()
+                .into_string()
+                .map_err(|_| anyhow!("failed to get hostname as MQTT client ID"))?,
+        };
+        let qos = parse_qos(args.mqtt_qos)?;
+        let status_topic = format!(
+            "{prefix}/{client_id}",
+            prefix = args.mqtt_status_topic.trim_end_matches('/'),
+        );
+
+        let mut options = MqttOptions::new(&client_id, &args.mqtt_host, args.mqtt_port);
+        options
+            .set_keep_alive(Duration::from_secs(args.mqtt_keep_alive))
+            .set_last_will(LastWill::new(
+                &status_topic,
+                Self::PAYLOAD_OFFLINE,
+                QoS::AtLeastOnce,
+                true,
+                None,
+            ));
+        if let Some(user) = args.mqtt_account.as_ref() {
+            if let Some(pass) = parse_password(args).await? {
+                options.set_credentials(user.clone(), pass);
+            }
+        }
+
+        // spin event loop
+        let (client, event_loop) = AsyncClient::new(options, args.mqtt_capacity);
+        let routes = Routes::default();
+        let handle = ::tokio::spawn(loop_forever(
+            client.clone(),
+            event_loop,
+            qos,
+            routes.clone(),
+            status_topic,
+        ));
+
+        Ok(Self {
+            _handle: Arc::new(MessengerHandle {
+                inner: Some(handle),
+            }),
+            client,
+            qos,
+            routes,
+        })
+    }
+}
+
+#[async_trait]
+impl<Value> super::Messenger<Value> for Messenger {
+    fn messenger_type(&self) -> super::MessengerType {
+        super::MessengerType::Mqtt
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn publish(&self, topic: Name) -> Result<Arc<dyn super::Publisher>> {
+        Ok(Arc::new(Publisher {
+            _handle: self._handle.clone(),
+            client: self.client.clone(),
+            qos: self.qos,
+            topic_name: parse_topic_name(&topic),
+            topic,
+        }))
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn subscribe(&self, topic: Name) -> Result<Box<dyn super::Subscriber<Value>>>
+    where
+        Value: Send + DeserializeOwned,
+    {
+        let topic_name = parse_topic_name(&topic);
+        let (tx, rx) = mpsc::channel(Subscriber::CAPACITY);
+
+        // register the route before subscribing, not to miss any messages
+        self.routes
+            .lock()
+            .await
+            .entry(topic_name.clone())
+            .or_default()
+            .push(tx);
+
+        self.client
+            .subscribe(&topic_name, self.qos)
+            .await
+            .map_err(|error| anyhow!("failed to subscribe MQTT topic: {error}"))?;
+
+        Ok(Box::new(Subscriber {
+            _handle: self._handle.clone(),
+            inner: rx,
+            topic,
+        }))
+    }
+}
+
+struct MessengerHandle {
+    inner: Option<JoinHandle<()>>,
+}
+
+impl Drop for MessengerHandle {
+    fn drop(&mut self) {
+        if let Some(handle) = self.inner.take() {
+            handle.abort()
+        }
+    }
+}
+
+async fn loop_forever(
+    client: AsyncClient,
+    mut event_loop: EventLoop,
+    qos: QoS,
+    routes: Routes,
+    status_topic: String,
+) {
+    loop {
+        match event_loop.poll().await {
+            // (re)connected: advertise liveness and restore the subscriptions
+            Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                if let Err(error) = client
+                    .publish(
+                        &status_topic,
+                        QoS::AtLeastOnce,
+                        true,
+                        Messenger::PAYLOAD_ONLINE,
+                    )
+                    .await
+                {
+                    warn!("failed to publish MQTT liveness: {error}");
+                }
+
+                let topics: Vec<_> = routes.lock().await.keys().cloned().collect();
+                for topic in topics {
+                    if let Err(error) = client.subscribe(&topic, qos).await {
+                        warn!("failed to resubscribe MQTT topic: {error}");
+                    }
+                }
+            }
+            Ok(Event::Incoming(Packet::Publish(publish))) => {
+                let topic = String::from_utf8_lossy(&publish.topic);
+                let mut routes = routes.lock().await;
+                if let Some(senders) = routes.get_mut(topic.as_ref()) {
+                    // drop the closed subscribers
+                    senders.retain(|sender| !sender.is_closed());
+                    for sender in senders.iter() {
+                        if sender.send(publish.payload.clone()).await.is_err() {
+                            debug!("MQTT subscriber has been closed: {topic}");
+                        }
+                    }
+                }
+            }
+            Ok(_) => continue,
+            Err(error) => {
+                warn!("failed to poll MQTT events: {error}");
+
+                // the event loop reconnects on the next poll
+                ::tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        }
+    }
+}
+
+pub struct Publisher {
+    _handle: Arc<MessengerHandle>,
+    client: AsyncClient,
+    qos: QoS,
+    topic: Name,
+    topic_name: String,
+}
+
+#[async_trait]
+impl super::Publisher for Publisher {
+    fn topic(&self) -> &Name {
+        &self.topic
+    }
+
+    #[instrument(
+        level = Level::INFO,
+        skip_all,
+        fields(
+            data.len = %_data.len(),
+            data.model = %self.topic.as_str(),
+        ),
+        err(Display),
+    )]
+    async fn reply_one(&self, _data: Bytes, _inbox: String) -> Result<()> {
+        bail!("cannot reply with MQTT")
+    }
+
+    #[instrument(
+        level = Level::INFO,
+        skip_all,
+        fields(
+            data.len = %_data.len(),
+            data.model = %self.topic.as_str(),
+        ),
+        err(Display),
+    )]
+    async fn request_one(&self, _data: Bytes) -> Result<Bytes> {
+        bail!("cannot request with MQTT")
+    }
+
+    #[instrument(
+        level = Level::INFO,
+        skip_all,
+        fields(
+            data.len = %data.len(),
+            data.model = %self.topic.as_str(),
+        ),
+        err(Display),
+    )]
+    async fn send_one(&self, data: Bytes) -> Result<()> {
+        self.client
+            .publish(&self.topic_name, self.qos, false, data)
+            .await
+            .map_err(|error| anyhow!("failed to publish data to MQTT: {error}"))
+    }
+
+    #[instrument(
+        level = Level::INFO,
+        skip_all,
+        fields(
+            data.len = %1usize,
+            data.model = %self.topic.as_str(),
+        ),
+        err(Display),
+    )]
+    async fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+pub struct Subscriber {
+    _handle: Arc<MessengerHandle>,
+    inner: mpsc::Receiver<Bytes>,
+    topic: Name,
+}
+
+impl Subscriber {
+    const CAPACITY: usize = 64;
+}
+
+#[async_trait]
+impl<Value> super::Subscriber<Value> for Subscriber
+where
+    Self: Send + Sync,
+    Value: Send + DeserializeOwned,
+{
+    fn topic(&self) -> &Name {
+        &self.topic
+    }
+
+    #[instrument(
+        level = Level::INFO,
+        skip_all,
+        fields(
+            data.len = %1usize,
+            data.model = %self.topic.as_str(),
+        ),
+        err(Display),
+    )]
+    async fn read_one(&mut self) -> Result<Option<PipeMessage<Value>>> {
+        self.inner
+            .recv()
+            .await
+            .map(|payload| {
+                match PipeMessage::<Value>::try_from(payload.clone()) {
+                    Ok(input) => Ok(input.drop_reply()),
+                    // accept the raw payloads from the edge devices
+                    Err(_) => ::serde_json::from_slice(&payload).map(PipeMessage::new),
+                }
+                .map_err(|error| anyhow!("failed to subscribe MQTT input: {error}"))
+            })
+            .transpose()
+    }
+
+    #[instrument(
+        level = Level::INFO,
+        skip_all,
+        fields(
+            data.len = %1usize,
+            data.model = %self.topic.as_str(),
+        ),
+        err(Display),
+    )]
+    async fn read_one_raw(&mut self) -> Result<Option<super::RawMessage>> {
+        Ok(self
+            .inner
+            .recv()
+            .await
+            .map(|data| super::RawMessage { data, inbox: None }))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Parser)]
+pub struct MessengerMqttArgs {
+    #[arg(long, env = "MQTT_ACCOUNT", value_name = "NAME")]
+    mqtt_account: Option<String>,
+
+    /// The maximum number of pending requests
+    #[arg(long, env = "MQTT_CAPACITY", value_name = "COUNT", default_value_t = MessengerMqttArgs::default_capacity())]
+    #[serde(default = "MessengerMqttArgs::default_capacity")]
+    mqtt_capacity: usize,
+
+    /// The client ID; the hostname is used if not given
+    #[arg(long, env = "MQTT_CLIENT_ID", value_name = "ID")]
+    mqtt_client_id: Option<String>,
+
+    #[arg(long, env = "MQTT_HOST", value_name = "ADDR", default_value_t = MessengerMqttArgs::default_host())]
+    #[serde(default = "MessengerMqttArgs::default_host")]
+    mqtt_host: String,
+
+    #[arg(long, env = "MQTT_KEEP_ALIVE", value_name = "SECONDS", default_value_t = MessengerMqttArgs::default_keep_alive())]
+    #[serde(default = "MessengerMqttArgs::default_keep_alive")]
+    mqtt_keep_alive: u64,
+
+    #[arg(long, env = "MQTT_PASSWORD", value_name = "PLAIN")]
+    mqtt_password: Option<String>,
+
+    #[arg(long, env = "MQTT_PASSWORD_PATH", value_name = "PATH")]
+    mqtt_password_path: Option<PathBuf>,
+
+    #[arg(long, env = "MQTT_PORT", value_name = "PORT", default_value_t = MessengerMqttArgs::default_port())]
+    #[serde(default = "MessengerMqttArgs::default_port")]
+    mqtt_port: u16,
+
+    /// The QoS level: 0 (at most once), 1 (at least once) or 2 (exactly once)
+    #[arg(long, env = "MQTT_QOS", value_name = "LEVEL", default_value_t = MessengerMqttArgs::default_qos())]
+    #[serde(default = "MessengerMqttArgs::default_qos")]
+    mqtt_qos: u8,
+
+    /// The topic prefix to publish the liveness of this client, retained
+    #[arg(long, env = "MQTT_STATUS_TOPIC", value_name = "TOPIC", default_value_t = MessengerMqttArgs::default_status_topic())]
+    #[serde(default = "MessengerMqttArgs::default_status_topic")]
+    mqtt_status_topic: String,
+}
+
+impl MessengerMqttArgs {
+    const fn default_capacity() -> usize {
+        64
+    }
+
+    fn default_host() -> String {
+        "localhost".into()
+    }
+
+    const fn default_keep_alive() -> u64 {
+        30 // in seconds
+    }
+
+    const fn default_port() -> u16 {
+        1883
+    }
+
+    const fn default_qos() -> u8 {
+        1
+    }
+
+    fn default_status_topic() -> String {
+        "dash/pipe/status".into()
+    }
+}
+
+fn parse_qos(level: u8) -> Result<QoS> {
+    match level {
+        0 => Ok(QoS::AtMostOnce),
+        1 => Ok(QoS::AtLeastOnce),
+        2 => Ok(QoS::ExactlyOnce),
+        level => bail!("invalid MQTT QoS level: {level}"),
+    }
+}
+
+/// Convert the pipe name into a MQTT topic name (e.g. `sensor.room-1` => `sensor/room-1`).
+fn parse_topic_name(topic: &Name) -> String {
+    topic.replace('.', "/")
+}
+
+/// Sanitize the MQTT topic name to fit the pipe name (e.g. `Sensor/Room 1/` => `sensor.room_1`).
+pub fn sanitize_topic_name(topic: &str) -> Result<Name> {
+    let name = topic
+        .split('/')
+        .map(|field| {
+            field
+                .chars()
+                .map(|c| match c.to_ascii_lowercase() {
+                    c @ ('a'..='z' | '0'..='9' | '-' | '_') => c,
+                    _ => '_',
+                })
+                .collect::<String>()
+        })
+        .map(|field| {
+            field
+                .trim_start_matches(|c: char| !c.is_ascii_lowercase())
+                .trim_end_matches(|c: char| !c.is_ascii_alphanumeric())
+                .to_string()
+        })
+        .filter(|field| !field.is_empty())
+        .collect::<Vec<_>>()
+        .join(".");
+
+    name.parse()
+        .map_err(|error| anyhow!("failed to sanitize MQTT topic name {topic:?}: {error}"))
+}
@@ -1,4 +1,4 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{collections::BTreeSet, path::PathBuf, sync::Arc};
 
 use anyhow::{anyhow, bail, Result};
 use ark_core_k8s::data::Name;
@@ -122,6 +122,30 @@ impl<Value> super::Messenger<Value> for Messenger {
                 .map(|inner| Subscriber { inner, topic })?,
         ))
     }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn subscribe_group(
+        &self,
+        topic: Name,
+        group: super::ConsumerGroup,
+    ) -> Result<Box<dyn super::Subscriber<Value>>>
+    where
+        Value: 'static + Send + DeserializeOwned,
+    {
+        let inner = self
+            .client
+            .queue_subscribe(topic.clone(), group.name().to_string())
+            .await
+            .map(|inner| Subscriber { inner, topic })?;
+
+        // NOTE: the queue groups are balanced by the NATS servers, hiding the other members
+        let members = BTreeSet::from([group.member().to_string()]);
+        group.rebalance(members, BTreeSet::default());
+        Ok(Box::new(super::group::Subscriber::native(
+            Box::new(inner),
+            group,
+        )))
+    }
 }
 
 pub struct Publisher {
@@ -241,6 +265,22 @@ where
             .transpose()
             .map_err(|error| anyhow!("failed to subscribe NATS input: {error}"))
     }
+
+    #[instrument(
+        level = Level::INFO,
+        skip_all,
+        fields(
+            data.len = %1usize,
+            data.model = %self.topic.as_str(),
+        ),
+        err(Display),
+    )]
+    async fn read_one_raw(&mut self) -> Result<Option<super::RawMessage>> {
+        Ok(self.inner.next().await.map(|message| super::RawMessage {
+            data: message.payload,
+            inbox: message.reply.map(|inbox| inbox.to_string()),
+        }))
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Parser)]
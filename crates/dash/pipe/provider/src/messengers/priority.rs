@@ -0,0 +1,379 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use ark_core_k8s::data::Name;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::{future::BoxFuture, stream::FuturesUnordered, FutureExt, StreamExt};
+use serde::de::DeserializeOwned;
+use tracing::{instrument, Level};
+
+use crate::message::{PipeMessage, PipePriority};
+
+use super::{ConsumerGroup, RawMessage};
+
+/// Shard the topics into the priority lanes,
+/// as none of the messengers support the message priorities natively.
+pub struct Messenger<M> {
+    inner: M,
+}
+
+impl<M> Messenger<M> {
+    pub const fn new(inner: M) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl<M, Value> super::Messenger<Value> for Messenger<M>
+where
+    M: super::Messenger<Value> + super::Messenger<()>,
+{
+    fn messenger_type(&self) -> super::MessengerType {
+        <M as super::Messenger<Value>>::messenger_type(&self.inner)
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn publish(&self, topic: Name) -> Result<Arc<dyn super::Publisher>> {
+        let publish = |priority: PipePriority| {
+            let topic = priority.topic(&topic);
+            async move { <M as super::Messenger<Value>>::publish(&self.inner, topic?).await }
+        };
+
+        Ok(Arc::new(Publisher {
+            high: publish(PipePriority::High).await?,
+            normal: publish(PipePriority::Normal).await?,
+            low: publish(PipePriority::Low).await?,
+            topic,
+        }))
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn subscribe(&self, topic: Name) -> Result<Box<dyn super::Subscriber<Value>>>
+    where
+        Value: Send + DeserializeOwned,
+    {
+        let mut lanes = Vec::with_capacity(PipePriority::ALL.len());
+        for priority in PipePriority::ALL {
+            let stream =
+                <M as super::Messenger<()>>::subscribe(&self.inner, priority.topic(&topic)?)
+                    .await?;
+            lanes.push((priority, stream));
+        }
+        Ok(Box::new(Subscriber::new(topic, lanes)))
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn subscribe_queued(
+        &self,
+        topic: Name,
+        queue_group: Name,
+    ) -> Result<Box<dyn super::Subscriber<Value>>>
+    where
+        Value: Send + DeserializeOwned,
+    {
+        let mut lanes = Vec::with_capacity(PipePriority::ALL.len());
+        for priority in PipePriority::ALL {
+            let stream = <M as super::Messenger<()>>::subscribe_queued(
+                &self.inner,
+                priority.topic(&topic)?,
+                priority.topic(&queue_group)?,
+            )
+            .await?;
+            lanes.push((priority, stream));
+        }
+        Ok(Box::new(Subscriber::new(topic, lanes)))
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn subscribe_group(
+        &self,
+        topic: Name,
+        group: ConsumerGroup,
+    ) -> Result<Box<dyn super::Subscriber<Value>>>
+    where
+        Value: 'static + Send + DeserializeOwned,
+    {
+        // NOTE: the lanes are shared by the same members
+        let mut lanes = Vec::with_capacity(PipePriority::ALL.len());
+        for priority in PipePriority::ALL {
+            let stream = <M as super::Messenger<()>>::subscribe_group(
+                &self.inner,
+                priority.topic(&topic)?,
+                group.clone(),
+            )
+            .await?;
+            lanes.push((priority, stream));
+        }
+        Ok(Box::new(Subscriber::new(topic, lanes)))
+    }
+}
+
+pub struct Publisher {
+    high: Arc<dyn super::Publisher>,
+    normal: Arc<dyn super::Publisher>,
+    low: Arc<dyn super::Publisher>,
+    topic: Name,
+}
+
+impl Publisher {
+    fn lane(&self, data: &[u8]) -> &Arc<dyn super::Publisher> {
+        match PipePriority::peek(data) {
+            PipePriority::High => &self.high,
+            PipePriority::Normal => &self.normal,
+            PipePriority::Low => &self.low,
+        }
+    }
+}
+
+#[async_trait]
+impl super::Publisher for Publisher {
+    fn topic(&self) -> &Name {
+        &self.topic
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn reply_one(&self, data: Bytes, inbox: String) -> Result<()> {
+        // NOTE: the replies are sent to the inbox directly, regardless of the lanes
+        self.normal.reply_one(data, inbox).await
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn request_one(&self, data: Bytes) -> Result<Bytes> {
+        self.lane(&data).request_one(data).await
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn send_one(&self, data: Bytes) -> Result<()> {
+        self.lane(&data).send_one(data).await
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn flush(&self) -> Result<()> {
+        self.high.flush().await?;
+        self.normal.flush().await?;
+        self.low.flush().await
+    }
+}
+
+type LaneStream = Box<dyn super::Subscriber<()>>;
+
+type LaneRead = (usize, LaneStream, Result<Option<RawMessage>>);
+
+/// Drain the priority lanes by their weights,
+/// so that the lower lanes are not starved while the higher lanes are busy.
+pub struct Subscriber {
+    lanes: Vec<Lane>,
+    reads: FuturesUnordered<BoxFuture<'static, LaneRead>>,
+    topic: Name,
+}
+
+impl Subscriber {
+    fn new(topic: Name, streams: Vec<(PipePriority, LaneStream)>) -> Self {
+        Self {
+            lanes: streams
+                .into_iter()
+                .map(|(priority, stream)| Lane {
+                    buffered: None,
+                    credits: priority.weight(),
+                    priority,
+                    stream: Some(stream),
+                })
+                .collect(),
+            reads: FuturesUnordered::default(),
+            topic,
+        }
+    }
+
+    async fn next(&mut self) -> Result<Option<RawMessage>> {
+        // Resume reading the lanes which have no buffered messages
+        for (index, lane) in self.lanes.iter_mut().enumerate() {
+            if lane.buffered.is_none() {
+                if let Some(stream) = lane.stream.take() {
+                    self.reads.push(read(index, stream));
+                }
+            }
+        }
+
+        // Collect the messages which have already arrived
+        while let Some(Some((index, stream, result))) = self.reads.next().now_or_never() {
+            if !self.complete(index, stream, result)? {
+                return Ok(None);
+            }
+        }
+        if let Some(message) = self.drain() {
+            return Ok(Some(message));
+        }
+
+        // Wait for any message
+        match self.reads.next().await {
+            Some((index, stream, result)) => {
+                if self.complete(index, stream, result)? {
+                    Ok(self.drain())
+                } else {
+                    Ok(None)
+                }
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Park the stream of the lane, buffering the arrived message.
+    ///
+    /// Returns `false` if the lane is closed.
+    fn complete(
+        &mut self,
+        index: usize,
+        stream: LaneStream,
+        result: Result<Option<RawMessage>>,
+    ) -> Result<bool> {
+        let lane = &mut self.lanes[index];
+        lane.stream = Some(stream);
+        lane.buffered = result?;
+        Ok(lane.buffered.is_some())
+    }
+
+    fn drain(&mut self) -> Option<RawMessage> {
+        // Take a message from the lanes with the remaining credits, in the order of priorities
+        if let Some(lane) = self
+            .lanes
+            .iter_mut()
+            .find(|lane| lane.credits > 0 && lane.buffered.is_some())
+        {
+            lane.credits -= 1;
+            return lane.buffered.take();
+        }
+
+        // Start a new round if only the exhausted lanes have the messages
+        if self.lanes.iter().any(|lane| lane.buffered.is_some()) {
+            for lane in &mut self.lanes {
+                lane.credits = lane.priority.weight();
+            }
+            self.drain()
+        } else {
+            None
+        }
+    }
+}
+
+#[async_trait]
+impl<Value> super::Subscriber<Value> for Subscriber
+where
+    Value: Send + DeserializeOwned,
+{
+    fn topic(&self) -> &Name {
+        &self.topic
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn read_one(&mut self) -> Result<Option<PipeMessage<Value>>> {
+        self.next()
+            .await?
+            .map(|message| message.decode())
+            .transpose()
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn read_one_raw(&mut self) -> Result<Option<RawMessage>> {
+        self.next().await
+    }
+}
+
+struct Lane {
+    buffered: Option<RawMessage>,
+    credits: u32,
+    priority: PipePriority,
+    stream: Option<LaneStream>,
+}
+
+fn read(index: usize, mut stream: LaneStream) -> BoxFuture<'static, LaneRead> {
+    async move {
+        let result = stream.read_one_raw().await;
+        (index, stream, result)
+    }
+    .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use futures::future::pending;
+
+    use super::*;
+
+    struct FakeStream {
+        messages: VecDeque<RawMessage>,
+        topic: Name,
+    }
+
+    impl FakeStream {
+        fn new(priority: PipePriority, len: usize) -> (PipePriority, LaneStream) {
+            let messages = (0..len)
+                .map(|_| RawMessage {
+                    data: priority.to_string().into(),
+                    inbox: None,
+                })
+                .collect();
+            let topic = "test".parse().unwrap();
+            (priority, Box::new(Self { messages, topic }))
+        }
+    }
+
+    #[async_trait]
+    impl super::super::Subscriber<()> for FakeStream {
+        fn topic(&self) -> &Name {
+            &self.topic
+        }
+
+        async fn read_one(&mut self) -> Result<Option<PipeMessage<()>>> {
+            unimplemented!()
+        }
+
+        async fn read_one_raw(&mut self) -> Result<Option<RawMessage>> {
+            match self.messages.pop_front() {
+                Some(message) => Ok(Some(message)),
+                None => pending().await,
+            }
+        }
+    }
+
+    async fn drain(subscriber: &mut Subscriber, len: usize) -> Vec<String> {
+        let mut priorities = Vec::with_capacity(len);
+        for _ in 0..len {
+            let message = subscriber.next().await.unwrap().unwrap();
+            priorities.push(String::from_utf8(message.data.to_vec()).unwrap());
+        }
+        priorities
+    }
+
+    #[::tokio::test]
+    async fn drain_lanes_by_weights() {
+        let mut subscriber = Subscriber::new(
+            "test".parse().unwrap(),
+            vec![
+                FakeStream::new(PipePriority::High, 8),
+                FakeStream::new(PipePriority::Normal, 8),
+                FakeStream::new(PipePriority::Low, 8),
+            ],
+        );
+
+        let round = ["High", "High", "High", "High", "Normal", "Normal", "Low"];
+        let expected: Vec<_> = round.iter().chain(&round).map(|p| p.to_string()).collect();
+        assert_eq!(drain(&mut subscriber, expected.len()).await, expected);
+    }
+
+    #[::tokio::test]
+    async fn drain_idle_lanes_immediately() {
+        let mut subscriber = Subscriber::new(
+            "test".parse().unwrap(),
+            vec![
+                FakeStream::new(PipePriority::High, 0),
+                FakeStream::new(PipePriority::Normal, 0),
+                FakeStream::new(PipePriority::Low, 3),
+            ],
+        );
+
+        assert_eq!(drain(&mut subscriber, 3).await, ["Low", "Low", "Low"]);
+    }
+}
@@ -270,6 +270,22 @@ where
             })
             .transpose()
     }
+
+    #[instrument(
+        level = Level::INFO,
+        skip_all,
+        fields(
+            data.len = %1usize,
+            data.model = %self.topic.as_str(),
+        ),
+        err(Display),
+    )]
+    async fn read_one_raw(&mut self) -> Result<Option<super::RawMessage>> {
+        Ok(self.inner.next().await.map(|message| super::RawMessage {
+            data: message.data.into(),
+            inbox: None,
+        }))
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Parser)]
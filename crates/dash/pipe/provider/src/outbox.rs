@@ -0,0 +1,367 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use ark_core_k8s::data::Name;
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+use tokio::{spawn, time::sleep};
+use tracing::{info, instrument, warn, Level};
+use uuid::Uuid;
+
+use crate::{
+    message::PipeMessage,
+    messengers::Publisher,
+    storage::{MetadataStorage, StorageSet, StorageType},
+};
+
+/// The state of a published message, which is appended on every transition.
+#[derive(
+    Copy, Clone, Debug, Display, EnumString, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema,
+)]
+pub enum OutboxState {
+    /// The payloads are stored, but the message may not be published yet.
+    Staged,
+    /// The message is published.
+    Committed,
+    /// The message is given up, and its payloads are garbage-collected.
+    Reaped,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxRecord {
+    /// The id of the published message
+    pub id: Uuid,
+    pub state: OutboxState,
+    /// The encoded message, exactly as published; only kept while staged
+    #[serde(default)]
+    pub data: Option<Bytes>,
+    /// The stored payloads of the message; only kept while staged
+    #[serde(default)]
+    pub payloads: Vec<OutboxPayload>,
+}
+
+impl OutboxRecord {
+    const TOPIC_SUFFIX: &'static str = ".outbox";
+
+    /// Return the outbox model of the given topic.
+    pub fn topic(model: &Name) -> Result<Name> {
+        format!("{model}{suffix}", suffix = Self::TOPIC_SUFFIX)
+            .parse()
+            .map_err(|error| anyhow!("failed to parse outbox topic of {model}: {error}"))
+    }
+
+    /// Stage the message, tracking the payloads which are newly stored for the topic.
+    ///
+    /// NOTE: the payloads inherited from the inputs are owned by the upstream
+    pub(crate) fn staged<Value>(message: &PipeMessage<Value>, topic: &Name, data: Bytes) -> Self {
+        Self {
+            id: message.id(),
+            state: OutboxState::Staged,
+            data: Some(data),
+            payloads: message
+                .payloads()
+                .iter()
+                .filter_map(|payload| payload.location())
+                .filter(|(_, model, _)| *model == topic)
+                .map(|(storage, model, path)| OutboxPayload {
+                    storage,
+                    model: model.clone(),
+                    path: path.into(),
+                })
+                .collect(),
+        }
+    }
+
+    const fn finished(id: Uuid, state: OutboxState) -> Self {
+        Self {
+            id,
+            state,
+            data: None,
+            payloads: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxPayload {
+    pub storage: StorageType,
+    pub model: Name,
+    pub path: String,
+}
+
+/// A journal of the staged messages, which survives the crashes of the publishers.
+#[async_trait]
+pub trait OutboxStore
+where
+    Self: Send + Sync,
+{
+    /// Stage the message durably, before being published.
+    async fn stage(&self, record: &OutboxRecord) -> Result<()>;
+
+    /// Mark the staged message as committed or reaped.
+    async fn finish(&self, id: Uuid, state: OutboxState) -> Result<()>;
+
+    /// List the staged messages which are not finished yet, with their staged time.
+    async fn list_pending(&self) -> Result<Vec<(DateTime<Utc>, OutboxRecord)>>;
+}
+
+/// An outbox store, which appends the records into the metadata storage of `<model>.outbox`.
+pub struct MetadataOutboxStore {
+    inner: Arc<dyn Send + Sync + MetadataStorage<OutboxRecord>>,
+}
+
+impl MetadataOutboxStore {
+    #[instrument(level = Level::INFO, skip(storage), err(Display))]
+    pub async fn try_new(storage: &StorageSet, model: &Name) -> Result<Self> {
+        let model = OutboxRecord::topic(model)?;
+        Ok(Self {
+            inner: storage
+                .create_default_metadata_storage::<OutboxRecord>(&model, None)
+                .await?,
+        })
+    }
+}
+
+#[async_trait]
+impl OutboxStore for MetadataOutboxStore {
+    #[instrument(level = Level::INFO, skip_all, fields(id = %record.id), err(Display))]
+    async fn stage(&self, record: &OutboxRecord) -> Result<()> {
+        let message = PipeMessage::new(record.clone());
+        self.inner.put_metadata(&[&message]).await?;
+
+        // NOTE: the staged record should be persisted before publishing
+        self.inner.flush().await
+    }
+
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    async fn finish(&self, id: Uuid, state: OutboxState) -> Result<()> {
+        // NOTE: a lost record is recovered by the reaper, resulting in at-least-once delivery
+        let message = PipeMessage::new(OutboxRecord::finished(id, state));
+        self.inner.put_metadata(&[&message]).await
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn list_pending(&self) -> Result<Vec<(DateTime<Utc>, OutboxRecord)>> {
+        let records: Vec<_> = self
+            .inner
+            .list_metadata()
+            .await?
+            .map_ok(|message| (message.timestamp(), message.value))
+            .try_collect()
+            .await?;
+        Ok(pending(records))
+    }
+}
+
+/// Return the staged records which are not finished, in the staged order.
+fn pending(
+    records: impl IntoIterator<Item = (DateTime<Utc>, OutboxRecord)>,
+) -> Vec<(DateTime<Utc>, OutboxRecord)> {
+    let mut finished = HashSet::new();
+    let mut staged = HashMap::new();
+    for (timestamp, record) in records {
+        match record.state {
+            OutboxState::Staged => {
+                staged.insert(record.id, (timestamp, record));
+            }
+            OutboxState::Committed | OutboxState::Reaped => {
+                finished.insert(record.id);
+            }
+        }
+    }
+
+    let mut pending: Vec<_> = staged
+        .into_iter()
+        .filter(|(id, _)| !finished.contains(id))
+        .map(|(_, entry)| entry)
+        .collect();
+    pending.sort_by_key(|(timestamp, _)| *timestamp);
+    pending
+}
+
+/// The policy of reaping the incomplete messages.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutboxOptions {
+    /// The staged messages younger than this are regarded as being published.
+    pub grace: Duration,
+    pub interval: Duration,
+}
+
+impl Default for OutboxOptions {
+    fn default() -> Self {
+        Self {
+            grace: Duration::from_secs(5 * 60),
+            interval: Duration::from_secs(60),
+        }
+    }
+}
+
+pub(crate) struct Outbox {
+    inner: Arc<dyn Publisher>,
+    options: OutboxOptions,
+    storage: Arc<StorageSet>,
+    store: Arc<dyn OutboxStore>,
+}
+
+impl Outbox {
+    pub(crate) fn spawn(
+        inner: Arc<dyn Publisher>,
+        storage: Arc<StorageSet>,
+        store: Arc<dyn OutboxStore>,
+        options: OutboxOptions,
+    ) -> Arc<Self> {
+        let outbox = Arc::new(Self {
+            inner,
+            options,
+            storage,
+            store,
+        });
+
+        // reap the incomplete messages until the publishers are dropped
+        let interval = options.interval.max(Duration::from_secs(1));
+        let weak = Arc::downgrade(&outbox);
+        spawn(async move {
+            loop {
+                match weak.upgrade() {
+                    Some(outbox) => {
+                        if let Err(error) = outbox.reap().await {
+                            warn!("failed to reap outbox: {error}");
+                        }
+                    }
+                    None => break,
+                }
+                sleep(interval).await;
+            }
+        });
+        outbox
+    }
+
+    /// Stage the message, publish it and then commit it.
+    #[instrument(
+        level = Level::INFO,
+        skip_all,
+        fields(
+            data.len = %1usize,
+            data.model = %self.inner.topic().as_str(),
+        ),
+        err(Display),
+    )]
+    pub(crate) async fn send_one(&self, record: OutboxRecord) -> Result<()> {
+        let OutboxRecord { id, data, .. } = &record;
+        let data = data.clone().unwrap_or_default();
+        self.store.stage(&record).await?;
+
+        match self.inner.send_one(data).await {
+            Ok(()) => self.store.finish(*id, OutboxState::Committed).await,
+            // NOTE: the caller retries the message with the newly stored payloads
+            Err(error) => {
+                if let Err(error) = self.collect_garbage(&record).await {
+                    warn!("{error}");
+                }
+                Err(error)
+            }
+        }
+    }
+
+    /// Republish the incomplete messages if their payloads are alive, or garbage-collect them.
+    #[instrument(level = Level::INFO, skip_all, fields(data.model = %self.inner.topic().as_str()), err(Display))]
+    async fn reap(&self) -> Result<()> {
+        let deadline = Utc::now()
+            - ::chrono::Duration::from_std(self.options.grace)
+                .map_err(|error| anyhow!("failed to parse outbox grace period: {error}"))?;
+
+        for (timestamp, record) in self.store.list_pending().await? {
+            if timestamp >= deadline {
+                continue;
+            }
+
+            let id = record.id;
+            match (&record.data, self.is_alive(&record).await) {
+                (Some(data), true) => {
+                    info!("Republishing incomplete message: {id}");
+                    self.inner.send_one(data.clone()).await?;
+                    self.store.finish(id, OutboxState::Committed).await?;
+                }
+                _ => {
+                    info!("Reaping incomplete message: {id}");
+                    self.collect_garbage(&record).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn is_alive(&self, record: &OutboxRecord) -> bool {
+        for OutboxPayload {
+            storage,
+            model,
+            path,
+        } in &record.payloads
+        {
+            if self.storage.get(*storage).get(model, path).await.is_err() {
+                return false;
+            }
+        }
+        true
+    }
+
+    async fn collect_garbage(&self, record: &OutboxRecord) -> Result<()> {
+        for OutboxPayload {
+            storage,
+            model,
+            path,
+        } in &record.payloads
+        {
+            if let Err(error) = self
+                .storage
+                .get(*storage)
+                .delete_with_model(model, path)
+                .await
+            {
+                warn!("failed to delete orphaned payload ({model}/{path}): {error}");
+            }
+        }
+        self.store.finish(record.id, OutboxState::Reaped).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pending_skips_finished_records() {
+        let now = Utc::now();
+        let staged = |id| OutboxRecord {
+            id,
+            state: OutboxState::Staged,
+            data: Some(Bytes::from_static(b"{}")),
+            payloads: Vec::new(),
+        };
+        let ids: Vec<_> = (0..3).map(|_| Uuid::new_v4()).collect();
+
+        let records = vec![
+            (now, staged(ids[0])),
+            (now, OutboxRecord::finished(ids[1], OutboxState::Committed)),
+            (now, staged(ids[1])),
+            (now, staged(ids[2])),
+            (now, OutboxRecord::finished(ids[2], OutboxState::Reaped)),
+        ];
+        let pending: Vec<_> = pending(records)
+            .into_iter()
+            .map(|(_, record)| record.id)
+            .collect();
+        assert_eq!(pending, vec![ids[0]]);
+    }
+}
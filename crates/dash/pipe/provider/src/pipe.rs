@@ -1,6 +1,7 @@
 use std::{
     collections::HashMap,
     fmt,
+    path::PathBuf,
     process::exit,
     sync::{
         atomic::{AtomicUsize, Ordering},
@@ -9,12 +10,12 @@ use std::{
     time::{Duration, Instant},
 };
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use ark_core_k8s::data::Name;
 use clap::{ArgAction, Args, Parser};
 use derivative::Derivative;
 use futures::Future;
-use opentelemetry::global;
+use opentelemetry::{global, trace::TraceContextExt};
 use schemars::JsonSchema;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use strum::{Display, EnumString};
@@ -24,15 +25,23 @@ use tokio::{
     task::{yield_now, JoinHandle},
     time::sleep,
 };
-use tracing::{debug, error, info, instrument, warn, Level};
+use tracing::{debug, error, info, instrument, warn, Level, Span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 
 use crate::{
+    dlq::DeadLetterQueue,
     function::{
         Function, FunctionBuilder, FunctionContext, OwnedFunctionBuilder, OwnedFunctionBuilderArgs,
         RemoteFunction,
     },
-    message::{Codec, PipeMessage, PipeMessages, PipePayload},
-    messengers::{init_messenger, MessengerArgs, Publisher, PublisherExt, Subscriber},
+    message::{Codec, PipeLineage, PipeMessage, PipeMessages, PipePayload},
+    messengers::{
+        init_messenger, ConsumerGroup, MessengerArgs, Publisher, PublisherExt, RawMessage,
+        Subscriber,
+    },
+    outbox::{MetadataOutboxStore, Outbox, OutboxOptions, OutboxRecord},
+    schema::evolution::SchemaEvolution,
+    spill::{SpillBuffer, SpillOptions},
     storage::{DummyStorageArgs, MetadataStorageArgs, MetadataStorageType, StorageIO, StorageSet},
 };
 
@@ -69,6 +78,15 @@ where
     #[serde(default)]
     bootstrap: bool,
 
+    /// Share the inputs among the replicas of the consumer group.
+    #[arg(long, env = "PIPE_CONSUMER_GROUP", value_name = "NAME")]
+    #[serde(default)]
+    consumer_group: Option<Name>,
+
+    #[arg(long, env = "PIPE_CONSUMER_GROUP_PARTITIONS", value_name = "NUM")]
+    #[serde(default)]
+    consumer_group_partitions: Option<u32>,
+
     #[arg(long, env = "PIPE_DEFAULT_MODEL_IN", value_name = "POLICY")]
     #[serde(default)]
     default_model_in: Option<DefaultModelIn>,
@@ -83,6 +101,11 @@ where
     #[serde(default)]
     disable_sas: bool,
 
+    /// Move the inputs failed after the given delivery attempts to `<model_in>.dlq`.
+    #[arg(long, env = "PIPE_DLQ_MAX_ATTEMPTS", value_name = "NUM")]
+    #[serde(default)]
+    dlq_max_attempts: Option<u32>,
+
     #[arg(long, env = "PIPE_ENCODER", value_name = "CODEC")]
     #[serde(default)]
     encoder: Option<Codec>,
@@ -109,14 +132,38 @@ where
     #[serde(default)]
     model_in: Option<Name>,
 
+    /// The generation of the input model, which is recorded into the lineage of the outputs.
+    #[arg(long, env = "PIPE_MODEL_IN_VERSION", value_name = "VERSION")]
+    #[serde(default)]
+    model_in_version: Option<i64>,
+
     #[arg(long, env = "PIPE_MODEL_OUT", value_name = "NAME")]
     #[serde(default)]
     model_out: Option<Name>,
 
+    /// Stage the outputs in the metadata storage until published, reaping the incomplete ones.
+    #[arg(long, env = "PIPE_OUTBOX", action = ArgAction::SetTrue)]
+    #[serde(default)]
+    outbox: bool,
+
     #[arg(long, env = "PIPE_QUEUE_GROUP", action = ArgAction::SetTrue)]
     #[serde(default)]
     queue_group: bool,
 
+    /// Fill the missing fields of the inputs with the schema defaults and ignore the unknown ones.
+    #[arg(long, env = "PIPE_SCHEMA_COMPAT", action = ArgAction::SetTrue)]
+    #[serde(default)]
+    schema_compat: bool,
+
+    /// Spill the unsent outputs into the directory while the messenger is unreachable.
+    #[arg(long, env = "PIPE_SPILL_DIR", value_name = "PATH")]
+    #[serde(default)]
+    spill_dir: Option<PathBuf>,
+
+    #[arg(long, env = "PIPE_SPILL_MAX_BYTES", value_name = "BYTES")]
+    #[serde(default)]
+    spill_max_bytes: Option<u64>,
+
     #[command(flatten)]
     storage: S,
 }
@@ -162,11 +209,21 @@ where
         self
     }
 
+    pub fn with_consumer_group(mut self, consumer_group: Option<Name>) -> Self {
+        self.consumer_group = consumer_group;
+        self
+    }
+
     pub fn with_default_model_in(mut self, default_model_in: DefaultModelIn) -> Self {
         self.default_model_in = Some(default_model_in);
         self
     }
 
+    pub fn with_dlq_max_attempts(mut self, dlq_max_attempts: u32) -> Self {
+        self.dlq_max_attempts = Some(dlq_max_attempts);
+        self
+    }
+
     pub fn with_function_args(mut self, function_args: <F as FunctionBuilder>::Args) -> Self {
         self.function_args = function_args;
         self
@@ -182,11 +239,31 @@ where
         self
     }
 
+    pub fn with_model_in_version(mut self, model_in_version: Option<i64>) -> Self {
+        self.model_in_version = model_in_version;
+        self
+    }
+
     pub fn with_model_out(mut self, model_out: Option<Name>) -> Self {
         self.model_out = model_out;
         self
     }
 
+    pub fn with_outbox(mut self, outbox: bool) -> Self {
+        self.outbox = outbox;
+        self
+    }
+
+    pub fn with_schema_compat(mut self, schema_compat: bool) -> Self {
+        self.schema_compat = schema_compat;
+        self
+    }
+
+    pub fn with_spill_dir(mut self, spill_dir: Option<PathBuf>) -> Self {
+        self.spill_dir = spill_dir;
+        self
+    }
+
     pub fn with_storage(mut self, storage: S) -> Self {
         self.storage = storage;
         self
@@ -308,6 +385,26 @@ where
 
         let function = self.init_function(&mut function_context, &storage).await?;
 
+        debug!("Initializing Dead-Letter Queue");
+        let dead_letter = match (self.dlq_max_attempts, self.model_in.as_ref()) {
+            (Some(max_attempts), Some(model)) => Some(Arc::new(
+                DeadLetterQueue::try_new(
+                    &messenger,
+                    self.encoder.unwrap_or_default(),
+                    model,
+                    max_attempts,
+                )
+                .await?,
+            )),
+            _ => None,
+        };
+
+        let evolution = if self.schema_compat {
+            Some(Arc::new(SchemaEvolution::new::<<F as Function>::Input>()?))
+        } else {
+            None
+        };
+
         debug!("Initializing Reader");
         let reader = match self.model_in.as_ref() {
             Some(model) => {
@@ -315,10 +412,18 @@ where
 
                 Some(ReadContext {
                     _job: ReadSession {
+                        dead_letter: dead_letter.clone(),
+                        evolution: evolution.clone(),
                         function_context: function_context.clone(),
                         model_out: self.model_out.clone(),
                         storage: storage.input.clone(),
-                        stream: if self.queue_group {
+                        stream: if let Some(group) = self.consumer_group.clone() {
+                            let group = ConsumerGroup::try_new(group)?.with_partitions(
+                                self.consumer_group_partitions
+                                    .unwrap_or(ConsumerGroup::DEFAULT_PARTITIONS),
+                            );
+                            messenger.subscribe_group(model.clone(), group).await
+                        } else if self.queue_group {
                             messenger
                                 .subscribe_queued(model.clone(), model.clone())
                                 .await
@@ -332,6 +437,7 @@ where
                     .await,
                     function_context: function_context.clone(),
                     model_in: model.clone(),
+                    model_in_version: self.model_in_version,
                     rx,
                 })
             }
@@ -339,22 +445,57 @@ where
         };
 
         debug!("Initializing Writer");
+        let stream = match self.model_out.as_ref() {
+            Some(model) => Some(messenger.publish(model.clone()).await?),
+            None => None,
+        };
+        let stream = match (self.spill_dir.as_ref(), stream) {
+            (Some(dir), Some(stream)) => {
+                debug!("Initializing Spill Buffer");
+                let options = SpillOptions {
+                    max_bytes: self
+                        .spill_max_bytes
+                        .unwrap_or(SpillOptions::DEFAULT_MAX_BYTES),
+                    ..SpillOptions::new(dir)
+                };
+                let stream: Arc<dyn Publisher> = SpillBuffer::try_spawn(stream, options).await?;
+                Some(stream)
+            }
+            (_, stream) => stream,
+        };
+        let outbox = match (self.outbox, self.model_out.as_ref(), stream.as_ref()) {
+            (true, Some(model), Some(stream)) => {
+                if function_context.is_disabled_store_metadata() {
+                    bail!("outbox requires the metadata storage; enable PIPE_PERSISTENCE_METADATA");
+                }
+
+                debug!("Initializing Outbox");
+                let store = MetadataOutboxStore::try_new(&storage.output, model).await?;
+                Some(Outbox::spawn(
+                    stream.clone(),
+                    storage.output.clone(),
+                    Arc::new(store),
+                    OutboxOptions::default(),
+                ))
+            }
+            _ => None,
+        };
         let writer = WriteContext {
             atomic_session: AtomicSession::new(max_tasks),
             encoder: self.encoder.unwrap_or_default(),
             function_context: function_context.clone(),
             model_in: self.model_in.clone(),
             model_out: self.model_out.clone(),
+            outbox,
             storage: storage.output.clone(),
-            stream: match self.model_out.as_ref() {
-                Some(model) => Some(messenger.publish(model.clone()).await?),
-                None => None,
-            },
+            stream,
         };
 
         Ok(Context {
             batch_size: self.batch_size,
             batch_timeout: self.batch_timeout_ms.map(Duration::from_millis),
+            dead_letter,
+            evolution,
             function,
             function_context,
             reader,
@@ -465,9 +606,7 @@ where
         ),
         err(Display),
     )]
-    async fn recv_one<Value>(
-        reader: &mut ReadContext<Value>,
-    ) -> Result<Option<PipeMessage<Value>>> {
+    async fn recv_one<Value>(reader: &mut ReadContext<Value>) -> Result<Option<ReadInput<Value>>> {
         loop {
             select! {
                 input = reader.rx.recv() => break Ok(input),
@@ -492,6 +631,7 @@ where
         writer: &WriteContext,
         stream: &Arc<dyn Publisher>,
         input_payloads: &HashMap<String, PipePayload>,
+        input_lineage: &[PipeLineage],
         messages: PipeMessages<Value>,
     ) -> Result<()>
     where
@@ -506,7 +646,9 @@ where
         }
         .into_vec();
 
-        for message in messages {
+        for mut message in messages {
+            message.set_lineage(input_lineage);
+            message.inject_context();
             if !writer.function_context.is_disabled_store_metadata() {
                 if let Err(error) = writer
                     .storage
@@ -521,17 +663,30 @@ where
             let data = message
                 .to_bytes(writer.encoder)
                 .map_err(|error| anyhow!("failed to parse output message: {error}"))?;
-            stream.reply_or_send_one(data, message.reply).await?
+            match (&writer.outbox, &message.reply) {
+                (Some(outbox), None) => {
+                    outbox
+                        .send_one(OutboxRecord::staged(&message, stream.topic(), data))
+                        .await?
+                }
+                (_, _) => stream.reply_or_send_one(data, message.reply).await?,
+            }
         }
         Ok(())
     }
 
-    let inputs = match &mut ctx.reader {
+    let mut raws = Vec::default();
+    let mut inputs = match &mut ctx.reader {
         Some(reader) => {
-            let input = match recv_one(reader).await? {
+            let ReadInput {
+                message: input,
+                raw,
+            } = match recv_one(reader).await? {
                 Some(input) => input,
                 None => return Ok(()),
             };
+            raws.extend(raw);
+
             match ctx.batch_size {
                 Some(batch_size) => {
                     let timer = ctx.batch_timeout.map(Timer::new);
@@ -545,10 +700,15 @@ where
                         {
                             break;
                         } else {
-                            inputs.push(match recv_one(reader).await? {
+                            let ReadInput {
+                                message: input,
+                                raw,
+                            } = match recv_one(reader).await? {
                                 Some(input) => input,
                                 None => return Ok(()),
-                            })
+                            };
+                            raws.extend(raw);
+                            inputs.push(input)
                         }
                     }
                     PipeMessages::Batch(inputs)
@@ -559,7 +719,27 @@ where
         None => PipeMessages::None,
     };
 
+    // continue the traces of the upstream functions
+    match &inputs {
+        PipeMessages::None => (),
+        PipeMessages::Single(input) => Span::current().set_parent(input.extract_context()),
+        PipeMessages::Batch(inputs) => {
+            for input in inputs {
+                let context = input.extract_context();
+                let span = context.span();
+                let span_context = span.span_context();
+                if span_context.is_valid() {
+                    Span::current().add_link(span_context.clone());
+                }
+            }
+        }
+    }
+
     let input_payloads = inputs.as_payloads_map();
+    let input_lineage = match &ctx.reader {
+        Some(reader) => inputs.as_lineage(&reader.model_in, reader.model_in_version),
+        None => Vec::default(),
+    };
 
     #[instrument(
         level = Level::INFO,
@@ -583,20 +763,87 @@ where
         function.tick(inputs).await
     }
 
-    match call_function(
-        ctx.writer.model_in(),
-        ctx.writer.model_out(),
-        &mut ctx.function,
-        inputs,
-    )
-    .await?
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn redecode_inputs<Value>(
+        evolution: Option<&SchemaEvolution>,
+        function_context: &FunctionContext,
+        model_out: &Option<Name>,
+        storage: &StorageSet,
+        raws: &[RawMessage],
+        is_batch: bool,
+    ) -> Result<PipeMessages<Value>>
+    where
+        Value: DeserializeOwned,
     {
+        let mut inputs = Vec::with_capacity(raws.len());
+        for raw in raws {
+            let input = raw
+                .decode_with::<Value>(evolution)?
+                .with_reply_target(model_out);
+            inputs.push(
+                if function_context.is_disabled_load() || input.payloads.is_empty() {
+                    input.drop_payloads()
+                } else {
+                    input.load_payloads(storage).await?
+                },
+            );
+        }
+
+        Ok(if is_batch {
+            PipeMessages::Batch(inputs)
+        } else {
+            inputs
+                .pop()
+                .map(PipeMessages::Single)
+                .unwrap_or(PipeMessages::None)
+        })
+    }
+
+    let mut attempts = 0;
+    let outputs = loop {
+        attempts += 1;
+        let error = match call_function(
+            ctx.writer.model_in(),
+            ctx.writer.model_out(),
+            &mut ctx.function,
+            inputs,
+        )
+        .await
+        {
+            Ok(outputs) => break outputs,
+            Err(error) => error,
+        };
+
+        // Retry with the raw inputs, as the decoded ones are consumed by the function
+        match ctx.dead_letter.as_ref().filter(|_| !raws.is_empty()) {
+            Some(dead_letter) if attempts < dead_letter.max_attempts() => {
+                warn!("failed to call function (attempt {attempts}): {error}");
+                inputs = redecode_inputs(
+                    ctx.evolution.as_deref(),
+                    &ctx.function_context,
+                    &ctx.writer.model_out,
+                    &ctx.storage.input,
+                    &raws,
+                    ctx.batch_size.is_some(),
+                )
+                .await?;
+            }
+            Some(dead_letter) => {
+                let payloads: Vec<_> = raws.into_iter().map(|raw| raw.data).collect();
+                dead_letter.send(&payloads, attempts, &error).await?;
+                return Err(error);
+            }
+            None => return Err(error),
+        }
+    };
+
+    match outputs {
         PipeMessages::None => Ok(()),
         outputs => match ctx.writer.stream.clone() {
             Some(stream) => {
                 let writer = ctx.writer.clone();
                 ctx.writer.atomic_session.spawn(async move {
-                    send_one(&writer, &stream, &input_payloads, outputs).await
+                    send_one(&writer, &stream, &input_payloads, &input_lineage, outputs).await
                 });
                 ctx.writer.atomic_session.wait().await;
                 Ok(())
@@ -637,6 +884,8 @@ where
 {
     batch_size: Option<usize>,
     batch_timeout: Option<Duration>,
+    dead_letter: Option<Arc<DeadLetterQueue>>,
+    evolution: Option<Arc<SchemaEvolution>>,
     function: F,
     function_context: FunctionContext,
     reader: Option<ReadContext<<F as Function>::Input>>,
@@ -648,7 +897,8 @@ struct ReadContext<Value> {
     _job: JoinHandle<()>,
     function_context: FunctionContext,
     model_in: Name,
-    rx: Receiver<PipeMessage<Value>>,
+    model_in_version: Option<i64>,
+    rx: Receiver<ReadInput<Value>>,
 }
 
 impl<Value> ReadContext<Value> {
@@ -657,12 +907,20 @@ impl<Value> ReadContext<Value> {
     }
 }
 
+/// A decoded input, with its raw message kept for the dead-letter queue.
+struct ReadInput<Value> {
+    message: PipeMessage<Value>,
+    raw: Option<RawMessage>,
+}
+
 struct ReadSession<Value> {
+    dead_letter: Option<Arc<DeadLetterQueue>>,
+    evolution: Option<Arc<SchemaEvolution>>,
     function_context: FunctionContext,
     model_out: Option<Name>,
     storage: Arc<StorageSet>,
     stream: Box<dyn Subscriber<Value>>,
-    tx: Arc<Sender<PipeMessage<Value>>>,
+    tx: Arc<Sender<ReadInput<Value>>>,
 }
 
 impl<Value> ReadSession<Value>
@@ -689,37 +947,48 @@ where
     async fn read_input_one(&mut self) -> Result<()> {
         #[instrument(level = Level::INFO, skip_all, err(Display))]
         async fn send_one<Value>(
-            tx: &Sender<PipeMessage<Value>>,
-            input: PipeMessage<Value>,
+            tx: &Sender<ReadInput<Value>>,
+            message: PipeMessage<Value>,
+            raw: Option<RawMessage>,
         ) -> Result<()> {
-            tx.send(input)
+            tx.send(ReadInput { message, raw })
                 .await
                 .map_err(|error| anyhow!("failed to send input: {error}"))
         }
 
-        match self
-            .stream
-            .read_one()
-            .await?
-            .map(|input| input.with_reply_target(&self.model_out))
-        {
-            Some(input) => {
-                if self.function_context.is_disabled_load() || input.payloads.is_empty() {
-                    send_one(&self.tx, input.drop_payloads()).await
-                } else {
-                    let storage = self.storage.clone();
-                    let tx = self.tx.clone();
-                    spawn(async move {
-                        let input = input
-                            .load_payloads(&storage)
-                            .await
-                            .map_err(|error| anyhow!("failed to read input: {error}"))?;
-                        send_one(&tx, input).await
-                    });
-                    Ok(())
-                }
-            }
-            None => Ok(()),
+        let (input, raw) = match (&self.dead_letter, &self.evolution) {
+            (None, None) => match self.stream.read_one().await? {
+                Some(input) => (input, None),
+                None => return Ok(()),
+            },
+            // keep the raw message to retry or move it to the dead-letter queue
+            (dead_letter, evolution) => match self.stream.read_one_raw().await? {
+                Some(raw) => match (raw.decode_with(evolution.as_deref()), dead_letter) {
+                    (Ok(input), _) => (input, Some(raw)),
+                    // a poison message never succeeds in decoding, so skip retrying
+                    (Err(error), Some(dead_letter)) => {
+                        return dead_letter.send(&[raw.data], 1, &error).await
+                    }
+                    (Err(error), None) => return Err(error),
+                },
+                None => return Ok(()),
+            },
+        };
+        let input = input.with_reply_target(&self.model_out);
+
+        if self.function_context.is_disabled_load() || input.payloads.is_empty() {
+            send_one(&self.tx, input.drop_payloads(), raw).await
+        } else {
+            let storage = self.storage.clone();
+            let tx = self.tx.clone();
+            spawn(async move {
+                let input = input
+                    .load_payloads(&storage)
+                    .await
+                    .map_err(|error| anyhow!("failed to read input: {error}"))?;
+                send_one(&tx, input, raw).await
+            });
+            Ok(())
         }
     }
 }
@@ -731,6 +1000,7 @@ struct WriteContext {
     function_context: FunctionContext,
     model_in: Option<Name>,
     model_out: Option<Name>,
+    outbox: Option<Arc<Outbox>>,
     storage: Arc<StorageSet>,
     stream: Option<Arc<dyn Publisher>>,
 }
@@ -0,0 +1,281 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+use tokio::{
+    select,
+    sync::{Mutex, Notify, OwnedSemaphorePermit, Semaphore},
+    time::sleep,
+};
+
+/// The limits of the messages passing through a publisher or a subscriber.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimitOptions {
+    pub max_bytes_per_sec: Option<u64>,
+    /// The maximum number of the concurrent sends (publishers only)
+    pub max_in_flight: Option<usize>,
+    pub max_messages_per_sec: Option<u64>,
+    /// The maximum number of the waiting sends on [`RateLimitOverflow::DropOldest`]
+    pub max_pending: usize,
+    pub overflow: RateLimitOverflow,
+}
+
+impl Default for RateLimitOptions {
+    fn default() -> Self {
+        Self {
+            max_bytes_per_sec: None,
+            max_in_flight: None,
+            max_messages_per_sec: None,
+            max_pending: 1_024,
+            overflow: RateLimitOverflow::default(),
+        }
+    }
+}
+
+/// The policy on the messages exceeding the limits.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    Display,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum RateLimitOverflow {
+    /// Wait until the message is admitted
+    #[default]
+    Block,
+    /// Drop the oldest waiting messages (publishers),
+    /// or the messages being read (subscribers)
+    DropOldest,
+    /// Fail immediately
+    Error,
+}
+
+/// An async token bucket, refilled continuously up to a second of the rate.
+struct TokenBucket {
+    capacity: f64,
+    last: Instant,
+    rate: f64,
+    tokens: f64,
+}
+
+impl TokenBucket {
+    fn new(rate: u64) -> Self {
+        let rate = rate.max(1) as f64;
+        Self {
+            capacity: rate,
+            last: Instant::now(),
+            rate,
+            tokens: rate,
+        }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last = now;
+    }
+
+    /// Return the time to wait until the given tokens are available.
+    ///
+    /// NOTE: The requests larger than the capacity are admitted once the bucket is full.
+    fn wait_time(&self, n: f64) -> Duration {
+        let required = n.min(self.capacity) - self.tokens;
+        if required > 0.0 {
+            Duration::from_secs_f64(required / self.rate)
+        } else {
+            Duration::ZERO
+        }
+    }
+}
+
+#[derive(Default)]
+struct TokenBuckets {
+    bytes: Option<TokenBucket>,
+    messages: Option<TokenBucket>,
+}
+
+impl TokenBuckets {
+    /// Take the tokens of both buckets atomically, or return the time to wait.
+    fn try_take(&mut self, len: usize) -> Result<(), Duration> {
+        let requests = [(&mut self.messages, 1.0), (&mut self.bytes, len as f64)];
+
+        let mut wait = Duration::ZERO;
+        for (bucket, n) in &requests {
+            if let Some(bucket) = bucket {
+                wait = wait.max(bucket.wait_time(*n));
+            }
+        }
+        if !wait.is_zero() {
+            return Err(wait);
+        }
+
+        for (bucket, n) in requests {
+            if let Some(bucket) = bucket {
+                bucket.tokens -= n;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub(crate) struct RateLimiter {
+    arrivals: Notify,
+    buckets: Mutex<TokenBuckets>,
+    in_flight: Option<Arc<Semaphore>>,
+    next_ticket: AtomicU64,
+    options: RateLimitOptions,
+}
+
+/// An admitted message, holding its in-flight slot until dropped.
+pub(crate) struct RateLimitPermit {
+    _in_flight: Option<OwnedSemaphorePermit>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(options: RateLimitOptions) -> Self {
+        Self {
+            arrivals: Notify::new(),
+            buckets: Mutex::new(TokenBuckets {
+                bytes: options.max_bytes_per_sec.map(TokenBucket::new),
+                messages: options.max_messages_per_sec.map(TokenBucket::new),
+            }),
+            in_flight: options
+                .max_in_flight
+                .map(|max_in_flight| Arc::new(Semaphore::new(max_in_flight.max(1)))),
+            next_ticket: AtomicU64::default(),
+            options,
+        }
+    }
+
+    async fn try_take(&self, len: usize) -> Result<(), Duration> {
+        let mut guard = self.buckets.lock().await;
+        let buckets = &mut *guard;
+        for bucket in [&mut buckets.bytes, &mut buckets.messages]
+            .into_iter()
+            .flatten()
+        {
+            bucket.refill();
+        }
+        buckets.try_take(len)
+    }
+
+    /// Wait for the message to be sent, returning `None` if it has been dropped.
+    pub(crate) async fn acquire(&self, len: usize) -> Result<Option<RateLimitPermit>> {
+        let ticket = self.next_ticket.fetch_add(1, Ordering::SeqCst);
+        self.arrivals.notify_waiters();
+
+        // the waiter is dropped once too many messages are queued after it
+        let is_dropped = || {
+            self.options.overflow == RateLimitOverflow::DropOldest
+                && self.next_ticket.load(Ordering::SeqCst) - ticket
+                    > self.options.max_pending as u64
+        };
+
+        loop {
+            let arrived = self.arrivals.notified();
+            match self.try_take(len).await {
+                Ok(()) => break,
+                Err(wait) => match self.options.overflow {
+                    RateLimitOverflow::Block => sleep(wait).await,
+                    RateLimitOverflow::DropOldest => select! {
+                        () = sleep(wait) => (),
+                        () = arrived => if is_dropped() {
+                            return Ok(None);
+                        },
+                    },
+                    RateLimitOverflow::Error => bail!("rate limit exceeded"),
+                },
+            }
+        }
+
+        let in_flight = match &self.in_flight {
+            Some(semaphore) => Some(match self.options.overflow {
+                RateLimitOverflow::Block => semaphore.clone().acquire_owned().await?,
+                RateLimitOverflow::DropOldest => loop {
+                    let arrived = self.arrivals.notified();
+                    select! {
+                        permit = semaphore.clone().acquire_owned() => break permit?,
+                        () = arrived => if is_dropped() {
+                            return Ok(None);
+                        },
+                    }
+                },
+                RateLimitOverflow::Error => match semaphore.clone().try_acquire_owned() {
+                    Ok(permit) => permit,
+                    Err(_) => bail!("too many in-flight messages"),
+                },
+            }),
+            None => None,
+        };
+        Ok(Some(RateLimitPermit {
+            _in_flight: in_flight,
+        }))
+    }
+
+    /// Admit the received message, returning `false` if it should be dropped.
+    pub(crate) async fn admit(&self, len: usize) -> Result<bool> {
+        loop {
+            match self.try_take(len).await {
+                Ok(()) => break Ok(true),
+                Err(wait) => match self.options.overflow {
+                    RateLimitOverflow::Block => sleep(wait).await,
+                    RateLimitOverflow::DropOldest => break Ok(false),
+                    RateLimitOverflow::Error => bail!("rate limit exceeded"),
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[::tokio::test]
+    async fn drop_messages_over_rate() {
+        let limiter = RateLimiter::new(RateLimitOptions {
+            max_messages_per_sec: Some(2),
+            overflow: RateLimitOverflow::DropOldest,
+            ..Default::default()
+        });
+
+        assert!(limiter.admit(1).await.unwrap());
+        assert!(limiter.admit(1).await.unwrap());
+        assert!(!limiter.admit(1).await.unwrap());
+    }
+
+    #[::tokio::test]
+    async fn fail_messages_over_in_flight() {
+        let limiter = RateLimiter::new(RateLimitOptions {
+            max_in_flight: Some(1),
+            overflow: RateLimitOverflow::Error,
+            ..Default::default()
+        });
+
+        let permit = limiter.acquire(1).await.unwrap();
+        assert!(permit.is_some());
+        assert!(limiter.acquire(1).await.is_err());
+
+        drop(permit);
+        assert!(limiter.acquire(1).await.unwrap().is_some());
+    }
+}
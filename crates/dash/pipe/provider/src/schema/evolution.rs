@@ -0,0 +1,176 @@
+use anyhow::{anyhow, Result};
+use schemars::{schema_for, JsonSchema};
+use serde::de::DeserializeOwned;
+
+use crate::message::{DynValue, PipeMessage};
+
+/// Reads the messages written with the other versions of the reader schema.
+///
+/// The missing fields are filled with the defaults declared in the schema,
+/// and the unknown fields are ignored, so that the producers and the consumers
+/// can be upgraded independently.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchemaEvolution {
+    schema: DynValue,
+}
+
+impl SchemaEvolution {
+    pub fn new<Value>() -> Result<Self>
+    where
+        Value: JsonSchema,
+    {
+        Ok(Self {
+            schema: ::serde_json::to_value(schema_for!(Value))
+                .map_err(|error| anyhow!("failed to build reader schema: {error}"))?,
+        })
+    }
+
+    pub const fn with_schema(schema: DynValue) -> Self {
+        Self { schema }
+    }
+
+    pub fn decode<Value>(&self, data: &[u8]) -> Result<PipeMessage<Value>>
+    where
+        Value: DeserializeOwned,
+    {
+        let message: PipeMessage<DynValue> = data.try_into()?;
+        message.try_map_value(|value| {
+            ::serde_json::from_value(self.upgrade(value)).map_err(|error| {
+                anyhow!("failed to decode message with the reader schema: {error}")
+            })
+        })
+    }
+
+    /// Fit the value into the reader schema.
+    pub fn upgrade(&self, mut value: DynValue) -> DynValue {
+        upgrade(&self.schema, &self.schema, &mut value);
+        value
+    }
+}
+
+fn upgrade(root: &DynValue, schema: &DynValue, value: &mut DynValue) {
+    let schema = resolve(root, schema);
+    match value {
+        DynValue::Array(items) => {
+            if let Some(schema) = schema.get("items") {
+                for item in items {
+                    upgrade(root, schema, item);
+                }
+            }
+        }
+        DynValue::Object(fields) => {
+            let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) else {
+                return;
+            };
+
+            // NOTE: the maps with the extra fields are kept as-is
+            let is_closed = match schema.get("additionalProperties") {
+                None => true,
+                Some(additional) => additional == &DynValue::Bool(false),
+            };
+            if is_closed {
+                fields.retain(|field, _| properties.contains_key(field));
+            }
+
+            for (field, property) in properties {
+                match fields.get_mut(field) {
+                    Some(value) => upgrade(root, property, value),
+                    None => {
+                        let default = property
+                            .get("default")
+                            .or_else(|| resolve(root, property).get("default"));
+                        if let Some(default) = default {
+                            fields.insert(field.clone(), default.clone());
+                        }
+                    }
+                }
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Follow the references (`$ref`) and the single-element compositions (`allOf`).
+fn resolve<'a>(root: &'a DynValue, schema: &'a DynValue) -> &'a DynValue {
+    if let Some(reference) = schema.get("$ref").and_then(|r| r.as_str()) {
+        let target = reference
+            .strip_prefix("#/definitions/")
+            .and_then(|name| root.get("definitions")?.get(name))
+            .or_else(|| {
+                reference
+                    .strip_prefix("#/$defs/")
+                    .and_then(|name| root.get("$defs")?.get(name))
+            });
+        if let Some(target) = target {
+            return resolve(root, target);
+        }
+    }
+
+    match schema
+        .get("allOf")
+        .and_then(|all_of| all_of.as_array())
+        .map(Vec::as_slice)
+    {
+        Some([schema]) => resolve(root, schema),
+        _ => schema,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+    #[serde(deny_unknown_fields)]
+    struct Reader {
+        id: u64,
+        #[serde(default = "Reader::default_retries")]
+        retries: u32,
+        #[serde(default)]
+        inner: Inner,
+    }
+
+    impl Reader {
+        const fn default_retries() -> u32 {
+            3
+        }
+    }
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+    #[serde(deny_unknown_fields)]
+    struct Inner {
+        #[serde(default)]
+        enabled: bool,
+    }
+
+    #[test]
+    fn upgrade_with_defaults_and_unknown_fields() {
+        let evolution = SchemaEvolution::new::<Reader>().unwrap();
+
+        let value = evolution.upgrade(json!({
+            "id": 1,
+            "added": "by the newer producer",
+            "inner": {"enabled": true, "added": 42},
+        }));
+        let reader: Reader = ::serde_json::from_value(value).unwrap();
+        assert_eq!(
+            reader,
+            Reader {
+                id: 1,
+                retries: 3,
+                inner: Inner { enabled: true },
+            },
+        );
+    }
+
+    #[test]
+    fn upgrade_keeps_required_fields_missing() {
+        let evolution = SchemaEvolution::new::<Reader>().unwrap();
+
+        let value = evolution.upgrade(json!({"retries": 1}));
+        assert!(::serde_json::from_value::<Reader>(value).is_err());
+    }
+}
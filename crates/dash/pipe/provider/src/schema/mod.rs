@@ -2,5 +2,7 @@
 pub mod arrow;
 #[cfg(feature = "deltalake")]
 pub mod deltalake;
+pub mod evolution;
 #[cfg(feature = "lancedb")]
 pub mod lancedb;
+pub mod registry;
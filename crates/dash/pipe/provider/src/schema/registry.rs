@@ -0,0 +1,376 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use ark_core_k8s::data::Name;
+use async_trait::async_trait;
+use clap::Parser;
+use schemars::{schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+use tokio::{fs, sync::RwLock};
+use tracing::{debug, instrument, Level};
+
+use crate::message::DynValue;
+
+/// A registered schema of a topic.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SchemaVersion {
+    pub version: u32,
+    pub schema: DynValue,
+}
+
+impl SchemaVersion {
+    /// The message header recording the version of the writer schema.
+    pub const HEADER: &'static str = "schema-version";
+}
+
+#[async_trait]
+pub trait SchemaRegistryBackend
+where
+    Self: Send + Sync,
+{
+    /// List the registered schemas of the topic, in the ascending order of versions.
+    async fn list(&self, topic: &Name) -> Result<Vec<SchemaVersion>>;
+
+    async fn put(&self, topic: &Name, schema: SchemaVersion) -> Result<()>;
+}
+
+pub struct SchemaRegistry {
+    backend: Box<dyn SchemaRegistryBackend>,
+}
+
+impl SchemaRegistry {
+    #[instrument(level = Level::INFO, err(Display))]
+    pub async fn try_new(args: &SchemaRegistryArgs) -> Result<Option<Self>> {
+        let backend: Box<dyn SchemaRegistryBackend> = match args.schema_registry {
+            Some(SchemaRegistryType::File) => match args.schema_registry_path.clone() {
+                Some(path) => Box::new(FileBackend::try_new(path).await?),
+                None => bail!("schema registry path is required for file backend"),
+            },
+            Some(SchemaRegistryType::Memory) => Box::<MemoryBackend>::default(),
+            None => return Ok(None),
+        };
+
+        debug!("Initializing Schema Registry");
+        Ok(Some(Self { backend }))
+    }
+
+    pub fn with_backend<B>(backend: B) -> Self
+    where
+        B: 'static + SchemaRegistryBackend,
+    {
+        Self {
+            backend: Box::new(backend),
+        }
+    }
+
+    /// Register the writer schema of the topic, reusing the identical version if any.
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn negotiate<Value>(&self, topic: &Name) -> Result<SchemaVersion>
+    where
+        Value: JsonSchema,
+    {
+        let schema = ::serde_json::to_value(schema_for!(Value))?;
+        let versions = self.backend.list(topic).await?;
+
+        if let Some(version) = versions.iter().find(|version| version.schema == schema) {
+            return Ok(version.clone());
+        }
+        if let Some(latest) = versions.last() {
+            check_compatibility(&latest.schema, &schema).map_err(|error| {
+                anyhow!(
+                    "incompatible schema with {topic} (version {version}): {error}",
+                    version = latest.version,
+                )
+            })?;
+        }
+
+        let version = SchemaVersion {
+            version: versions
+                .last()
+                .map(|latest| latest.version + 1)
+                .unwrap_or(1),
+            schema,
+        };
+        self.backend.put(topic, version.clone()).await?;
+        Ok(version)
+    }
+
+    /// Check whether the reader schema can read the latest version of the topic.
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn check<Value>(&self, topic: &Name) -> Result<Option<SchemaVersion>>
+    where
+        Value: JsonSchema,
+    {
+        let schema = ::serde_json::to_value(schema_for!(Value))?;
+        match self.backend.list(topic).await?.pop() {
+            Some(latest) => {
+                check_compatibility(&schema, &latest.schema).map_err(|error| {
+                    anyhow!(
+                        "incompatible schema with {topic} (version {version}): {error}",
+                        version = latest.version,
+                    )
+                })?;
+                Ok(Some(latest))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Check whether the messages of the writer schema can be read by the reader schema.
+///
+/// NOTE: The referenced definitions (`$ref`) are regarded as compatible.
+pub fn check_compatibility(reader: &DynValue, writer: &DynValue) -> Result<()> {
+    fn check(path: &str, reader: &DynValue, writer: &DynValue) -> Result<()> {
+        match (get_type(reader), get_type(writer)) {
+            (Some(reader_type), Some(writer_type))
+                if reader_type != writer_type
+                    && !(reader_type == "number" && writer_type == "integer") =>
+            {
+                bail!("type of {path:?} has been changed from {reader_type} to {writer_type}")
+            }
+            _ => (),
+        }
+
+        let writer_required = get_required(writer);
+        if let Some(field) = get_required(reader)
+            .find(|field| !writer_required.clone().any(|required| required == *field))
+        {
+            bail!("required field {path}.{field} is missing")
+        }
+
+        if let (Some(reader), Some(writer)) = (get_properties(reader), get_properties(writer)) {
+            for (field, reader) in reader {
+                if let Some(writer) = writer.get(field) {
+                    check(&format!("{path}.{field}"), reader, writer)?;
+                }
+            }
+        }
+        match (reader.get("items"), writer.get("items")) {
+            (Some(reader), Some(writer)) => check(&format!("{path}[]"), reader, writer),
+            _ => Ok(()),
+        }
+    }
+
+    check("", reader, writer)
+}
+
+/// Check whether the value conforms to the schema.
+///
+/// NOTE: Only the types and the required fields are validated.
+pub fn validate(schema: &DynValue, value: &DynValue) -> Result<()> {
+    fn check(path: &str, schema: &DynValue, value: &DynValue) -> Result<()> {
+        if let Some(expected) = get_type(schema) {
+            let is_matched = match value {
+                DynValue::Null => expected == "null",
+                DynValue::Bool(_) => expected == "boolean",
+                DynValue::Number(number) => {
+                    expected == "number" || (expected == "integer" && !number.is_f64())
+                }
+                DynValue::String(_) => expected == "string",
+                DynValue::Array(_) => expected == "array",
+                DynValue::Object(_) => expected == "object",
+            };
+            if !is_matched {
+                bail!("field {path:?} is not a(n) {expected}")
+            }
+        }
+
+        match value {
+            DynValue::Array(items) => match schema.get("items") {
+                Some(schema) => items
+                    .iter()
+                    .try_for_each(|item| check(&format!("{path}[]"), schema, item)),
+                None => Ok(()),
+            },
+            DynValue::Object(fields) => {
+                if let Some(field) = get_required(schema).find(|field| !fields.contains_key(*field))
+                {
+                    bail!("required field {path}.{field} is missing")
+                }
+                match get_properties(schema) {
+                    Some(properties) => {
+                        fields
+                            .iter()
+                            .try_for_each(|(field, value)| match properties.get(field) {
+                                Some(schema) => check(&format!("{path}.{field}"), schema, value),
+                                None => Ok(()),
+                            })
+                    }
+                    None => Ok(()),
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
+    check("", schema, value)
+}
+
+fn get_type(schema: &DynValue) -> Option<&str> {
+    schema.get("type")?.as_str()
+}
+
+fn get_required(schema: &DynValue) -> impl Clone + Iterator<Item = &str> {
+    schema
+        .get("required")
+        .and_then(|required| required.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|field| field.as_str())
+}
+
+fn get_properties(schema: &DynValue) -> Option<&::serde_json::Map<String, DynValue>> {
+    schema.get("properties")?.as_object()
+}
+
+#[derive(Default)]
+pub struct MemoryBackend {
+    schemas: RwLock<HashMap<Name, Vec<SchemaVersion>>>,
+}
+
+#[async_trait]
+impl SchemaRegistryBackend for MemoryBackend {
+    async fn list(&self, topic: &Name) -> Result<Vec<SchemaVersion>> {
+        Ok(self
+            .schemas
+            .read()
+            .await
+            .get(topic)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn put(&self, topic: &Name, schema: SchemaVersion) -> Result<()> {
+        self.schemas
+            .write()
+            .await
+            .entry(topic.clone())
+            .or_default()
+            .push(schema);
+        Ok(())
+    }
+}
+
+/// A schema registry backend storing the schemas as `<path>/<topic>.json`,
+/// e.g. on a shared volume.
+pub struct FileBackend {
+    path: PathBuf,
+}
+
+impl FileBackend {
+    pub async fn try_new(path: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&path).await.map_err(|error| {
+            anyhow!(
+                "failed to create schema registry directory {path}: {error}",
+                path = path.display(),
+            )
+        })?;
+        Ok(Self { path })
+    }
+
+    fn get_path(&self, topic: &Name) -> PathBuf {
+        self.path.join(format!("{topic}.json"))
+    }
+}
+
+#[async_trait]
+impl SchemaRegistryBackend for FileBackend {
+    async fn list(&self, topic: &Name) -> Result<Vec<SchemaVersion>> {
+        match fs::read(self.get_path(topic)).await {
+            Ok(data) => ::serde_json::from_slice(&data)
+                .map_err(|error| anyhow!("failed to parse schemas of {topic}: {error}")),
+            Err(error) if error.kind() == ::std::io::ErrorKind::NotFound => Ok(Vec::default()),
+            Err(error) => bail!("failed to read schemas of {topic}: {error}"),
+        }
+    }
+
+    async fn put(&self, topic: &Name, schema: SchemaVersion) -> Result<()> {
+        let mut schemas = self.list(topic).await?;
+        schemas.push(schema);
+
+        let data = ::serde_json::to_vec_pretty(&schemas)?;
+        fs::write(self.get_path(topic), data)
+            .await
+            .map_err(|error| anyhow!("failed to write schemas of {topic}: {error}"))
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Parser)]
+pub struct SchemaRegistryArgs {
+    /// Check the message schemas with the registry.
+    #[arg(long, env = "PIPE_SCHEMA_REGISTRY", value_name = "TYPE")]
+    #[serde(default)]
+    pub schema_registry: Option<SchemaRegistryType>,
+
+    #[arg(long, env = "PIPE_SCHEMA_REGISTRY_PATH", value_name = "PATH")]
+    #[serde(default)]
+    pub schema_registry_path: Option<PathBuf>,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum SchemaRegistryType {
+    File,
+    Memory,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn compatibility_of_added_optional_field() {
+        let reader = json!({
+            "type": "object",
+            "required": ["id"],
+            "properties": {"id": {"type": "integer"}},
+        });
+        let writer = json!({
+            "type": "object",
+            "required": ["id"],
+            "properties": {"id": {"type": "integer"}, "name": {"type": "string"}},
+        });
+        assert!(check_compatibility(&reader, &writer).is_ok());
+        assert!(check_compatibility(&writer, &reader).is_ok());
+    }
+
+    #[test]
+    fn compatibility_of_removed_required_field() {
+        let reader = json!({
+            "type": "object",
+            "required": ["id"],
+            "properties": {"id": {"type": "integer"}},
+        });
+        let writer = json!({"type": "object", "properties": {}});
+        assert!(check_compatibility(&reader, &writer).is_err());
+    }
+
+    #[test]
+    fn validate_nested_types() {
+        let schema = json!({
+            "type": "object",
+            "required": ["items"],
+            "properties": {"items": {"type": "array", "items": {"type": "number"}}},
+        });
+        assert!(validate(&schema, &json!({"items": [1, 2.5]})).is_ok());
+        assert!(validate(&schema, &json!({"items": ["1"]})).is_err());
+        assert!(validate(&schema, &json!({})).is_err());
+    }
+}
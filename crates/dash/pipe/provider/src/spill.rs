@@ -0,0 +1,415 @@
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail, Result};
+use ark_core_k8s::data::Name;
+use async_trait::async_trait;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use opentelemetry::{
+    global,
+    metrics::{Counter, Gauge},
+    KeyValue,
+};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    fs::{self, File, OpenOptions},
+    io::AsyncWriteExt,
+    spawn,
+    sync::Mutex,
+    time::sleep,
+};
+use tracing::{info, instrument, warn, Level};
+
+use crate::messengers::Publisher;
+
+/// The local disk buffer of the outgoing messages while the messenger is unreachable.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpillOptions {
+    /// The directory of the spill files, one per topic.
+    pub dir: PathBuf,
+    /// The messages beyond this size are rejected while spilling.
+    pub max_bytes: u64,
+    /// The interval of draining the spilled messages.
+    pub interval: Duration,
+}
+
+impl SpillOptions {
+    pub const DEFAULT_MAX_BYTES: u64 = 256 << 20;
+
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_bytes: Self::DEFAULT_MAX_BYTES,
+            interval: Duration::from_secs(1),
+        }
+    }
+}
+
+struct SpillMetrics {
+    attributes: [KeyValue; 1],
+    bytes: Gauge<u64>,
+    messages: Gauge<u64>,
+    rejected: Counter<u64>,
+}
+
+impl SpillMetrics {
+    fn new(topic: &Name) -> Self {
+        let meter = global::meter("dash-pipe");
+        Self {
+            attributes: [KeyValue::new("topic", topic.to_string())],
+            bytes: meter
+                .u64_gauge("dash_pipe_spill_bytes")
+                .with_description("The size of the spilled messages waiting for the messenger")
+                .with_unit("By")
+                .build(),
+            messages: meter
+                .u64_gauge("dash_pipe_spill_messages")
+                .with_description("The number of the spilled messages waiting for the messenger")
+                .build(),
+            rejected: meter
+                .u64_counter("dash_pipe_spill_rejected")
+                .with_description("The number of the messages rejected by the full spill buffer")
+                .build(),
+        }
+    }
+}
+
+/// A publisher which appends the unsent messages into a write-ahead log on the local disk,
+/// and sends them in order once the messenger is reachable again.
+///
+/// NOTE: the messages may be sent again if the publisher crashes while draining
+pub(crate) struct SpillBuffer {
+    inner: Arc<dyn Publisher>,
+    metrics: SpillMetrics,
+    options: SpillOptions,
+    path: PathBuf,
+    state: Mutex<SpillState>,
+}
+
+struct SpillState {
+    file: File,
+    len_bytes: u64,
+    queue: VecDeque<Bytes>,
+}
+
+impl SpillBuffer {
+    #[instrument(level = Level::INFO, skip(inner), fields(topic = %inner.topic()), err(Display))]
+    pub(crate) async fn try_spawn(
+        inner: Arc<dyn Publisher>,
+        options: SpillOptions,
+    ) -> Result<Arc<Self>> {
+        fs::create_dir_all(&options.dir).await.map_err(|error| {
+            anyhow!(
+                "failed to create spill directory {dir}: {error}",
+                dir = options.dir.display(),
+            )
+        })?;
+        let topic = inner.topic().clone();
+        let path = options.dir.join(format!("{topic}.spill"));
+
+        // recover the messages spilled by the previous runs
+        let queue = match fs::read(&path).await {
+            Ok(data) => {
+                let (queue, is_intact) = decode_records(data.into());
+                if !is_intact {
+                    warn!(
+                        "discarding the corrupted tail of spill file {path}",
+                        path = path.display(),
+                    );
+                }
+                if !queue.is_empty() {
+                    info!(
+                        "recovered {len} spilled messages of {topic}",
+                        len = queue.len()
+                    );
+                }
+                queue
+            }
+            Err(error) if error.kind() == ::std::io::ErrorKind::NotFound => VecDeque::default(),
+            Err(error) => {
+                bail!(
+                    "failed to load spill file {path}: {error}",
+                    path = path.display(),
+                )
+            }
+        };
+        let file = write_records(&path, &queue).await?;
+
+        let buffer = Arc::new(Self {
+            inner,
+            metrics: SpillMetrics::new(&topic),
+            options,
+            path,
+            state: Mutex::new(SpillState {
+                file,
+                len_bytes: queue.iter().map(|data| record_len(data)).sum(),
+                queue,
+            }),
+        });
+        buffer.record(&*buffer.state.lock().await);
+
+        // drain the spilled messages until the publishers are dropped
+        let interval = buffer.options.interval.max(Duration::from_millis(1));
+        let weak = Arc::downgrade(&buffer);
+        spawn(async move {
+            loop {
+                sleep(interval).await;
+                match weak.upgrade() {
+                    Some(buffer) => {
+                        if let Err(error) = buffer.drain().await {
+                            warn!("failed to drain spilled messages: {error}");
+                        }
+                    }
+                    None => break,
+                }
+            }
+        });
+        Ok(buffer)
+    }
+
+    async fn is_spilling(&self) -> bool {
+        !self.state.lock().await.queue.is_empty()
+    }
+
+    async fn spill(&self, data: Vec<Bytes>) -> Result<()> {
+        let mut state = self.state.lock().await;
+        let len_bytes: u64 = data.iter().map(|data| record_len(data)).sum();
+        if state.len_bytes + len_bytes > self.options.max_bytes {
+            self.metrics
+                .rejected
+                .add(data.len() as u64, &self.metrics.attributes);
+            bail!(
+                "failed to spill messages of {topic}: spill buffer is full",
+                topic = self.inner.topic(),
+            );
+        }
+
+        let mut buf = BytesMut::with_capacity(len_bytes as usize);
+        for data in &data {
+            encode_record(&mut buf, data);
+        }
+        state
+            .file
+            .write_all(&buf)
+            .await
+            .map_err(|error| anyhow!("failed to write spill file: {error}"))?;
+        state
+            .file
+            .flush()
+            .await
+            .map_err(|error| anyhow!("failed to flush spill file: {error}"))?;
+
+        state.len_bytes += len_bytes;
+        state.queue.extend(data);
+        self.record(&state);
+        Ok(())
+    }
+
+    /// Send the spilled messages in order, stopping at the first failure.
+    async fn drain(&self) -> Result<()> {
+        let mut state = self.state.lock().await;
+        let mut sent = 0usize;
+        while let Some(data) = state.queue.front().cloned() {
+            if let Err(error) = self.inner.send_one(data).await {
+                warn!(
+                    "failed to drain spilled messages of {topic}: {error}",
+                    topic = self.inner.topic(),
+                );
+                break;
+            }
+            if let Some(data) = state.queue.pop_front() {
+                state.len_bytes -= record_len(&data);
+            }
+            sent += 1;
+        }
+        if sent == 0 {
+            return Ok(());
+        }
+
+        // compact the spill file
+        state.file = write_records(&self.path, &state.queue).await?;
+        self.record(&state);
+        info!(
+            "drained {sent} spilled messages of {topic}",
+            topic = self.inner.topic(),
+        );
+        Ok(())
+    }
+
+    fn record(&self, state: &SpillState) {
+        let attributes = &self.metrics.attributes;
+        self.metrics.bytes.record(state.len_bytes, attributes);
+        self.metrics
+            .messages
+            .record(state.queue.len() as u64, attributes);
+    }
+}
+
+#[async_trait]
+impl Publisher for SpillBuffer {
+    fn topic(&self) -> &Name {
+        self.inner.topic()
+    }
+
+    async fn reply_one(&self, data: Bytes, inbox: String) -> Result<()> {
+        self.inner.reply_one(data, inbox).await
+    }
+
+    async fn request_one(&self, data: Bytes) -> Result<Bytes> {
+        self.inner.request_one(data).await
+    }
+
+    async fn send_one(&self, data: Bytes) -> Result<()> {
+        // NOTE: the messages are queued behind the spilled ones to keep the order
+        if !self.is_spilling().await {
+            match self.inner.send_one(data.clone()).await {
+                Ok(()) => return Ok(()),
+                Err(error) => warn!(
+                    "spilling messages of {topic}: {error}",
+                    topic = self.inner.topic(),
+                ),
+            }
+        }
+        self.spill(vec![data]).await
+    }
+
+    async fn send_batch(&self, data: Vec<Bytes>) -> Result<()> {
+        if !self.is_spilling().await {
+            match self.inner.send_batch(data.clone()).await {
+                Ok(()) => return Ok(()),
+                Err(error) => warn!(
+                    "spilling messages of {topic}: {error}",
+                    topic = self.inner.topic(),
+                ),
+            }
+        }
+        self.spill(data).await
+    }
+
+    async fn flush(&self) -> Result<()> {
+        self.drain().await?;
+        self.inner.flush().await
+    }
+}
+
+/// The length and the CRC-32 checksum of each record.
+const HEADER_LEN: usize = 8;
+
+fn record_len(data: &[u8]) -> u64 {
+    (HEADER_LEN + data.len()) as u64
+}
+
+fn encode_record(buf: &mut BytesMut, data: &[u8]) {
+    buf.put_u32_le(data.len() as u32);
+    buf.put_u32_le(crc32(data));
+    buf.put_slice(data);
+}
+
+/// Decode the records, returning `false` if the tail is truncated or corrupted.
+fn decode_records(mut buf: Bytes) -> (VecDeque<Bytes>, bool) {
+    let mut records = VecDeque::default();
+    while buf.has_remaining() {
+        if buf.remaining() < HEADER_LEN {
+            return (records, false);
+        }
+        let len = buf.get_u32_le() as usize;
+        let checksum = buf.get_u32_le();
+        if buf.remaining() < len {
+            return (records, false);
+        }
+
+        let data = buf.split_to(len);
+        if crc32(&data) != checksum {
+            return (records, false);
+        }
+        records.push_back(data);
+    }
+    (records, true)
+}
+
+async fn write_records(path: &Path, records: &VecDeque<Bytes>) -> Result<File> {
+    let mut buf = BytesMut::default();
+    for data in records {
+        encode_record(&mut buf, data);
+    }
+
+    // NOTE: replace the file atomically, so that a crash leaves either version intact
+    let path_tmp = path.with_extension("spill.tmp");
+    fs::write(&path_tmp, &buf)
+        .await
+        .map_err(|error| anyhow!("failed to compact spill file: {error}"))?;
+    fs::rename(&path_tmp, path)
+        .await
+        .map_err(|error| anyhow!("failed to replace spill file: {error}"))?;
+
+    OpenOptions::new()
+        .append(true)
+        .open(path)
+        .await
+        .map_err(|error| anyhow!("failed to open spill file: {error}"))
+}
+
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut crc = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[index] = crc;
+        index += 1;
+    }
+    table
+};
+
+/// The CRC-32 (IEEE 802.3) checksum.
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &byte| {
+        CRC32_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_of_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn decode_records_until_corrupted() {
+        let mut buf = BytesMut::default();
+        encode_record(&mut buf, b"hello");
+        encode_record(&mut buf, b"");
+        encode_record(&mut buf, b"world");
+
+        let (records, is_intact) = decode_records(buf.clone().freeze());
+        assert!(is_intact);
+        assert_eq!(records, [&b"hello"[..], b"", b"world"]);
+
+        // truncated tail
+        let (records, is_intact) = decode_records(buf.clone().freeze().slice(..buf.len() - 1));
+        assert!(!is_intact);
+        assert_eq!(records, [&b"hello"[..], b""]);
+
+        // corrupted payload
+        let len = buf.len();
+        buf[len - 1] ^= 0xFF;
+        let (records, is_intact) = decode_records(buf.freeze());
+        assert!(!is_intact);
+        assert_eq!(records, [&b"hello"[..], b""]);
+    }
+}
@@ -0,0 +1,239 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use anyhow::{anyhow, Result};
+use ark_core_k8s::data::Name;
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{SecondsFormat, Utc};
+#[cfg(feature = "azure")]
+use dash_pipe_api::storage::StorageAzureArgs;
+#[cfg(feature = "gcs")]
+use dash_pipe_api::storage::StorageGcsArgs;
+use object_store::{path::Path, ObjectStore};
+use tracing::{debug, instrument, Level, Span};
+
+type BuildFn = dyn Send + Sync + Fn(&str) -> Result<Arc<dyn ObjectStore>>;
+
+/// A generic object storage, where each model is stored in its own bucket (container).
+#[derive(Clone)]
+pub struct Storage {
+    build: Arc<BuildFn>,
+    buckets: Arc<RwLock<HashMap<String, Arc<dyn ObjectStore>>>>,
+    model: Option<Name>,
+    name: String,
+    pipe_name: Name,
+    pipe_timestamp: String,
+    storage_type: super::StorageType,
+}
+
+impl Storage {
+    #[cfg(feature = "azure")]
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    pub fn try_new_azure(
+        StorageAzureArgs {
+            azure_access_key,
+            azure_account,
+        }: &StorageAzureArgs,
+        name: String,
+        model: Option<&Name>,
+        pipe_name: &Name,
+    ) -> Result<Self> {
+        debug!("Initializing Storage Set ({model:?}) - Azure Blob");
+
+        let azure_access_key = azure_access_key.clone();
+        let azure_account = azure_account.clone();
+        let build = move |container: &str| {
+            let mut builder = ::object_store::azure::MicrosoftAzureBuilder::from_env()
+                .with_container_name(container);
+            if let Some(account) = &azure_account {
+                builder = builder.with_account(account);
+            }
+            if let Some(access_key) = &azure_access_key {
+                builder = builder.with_access_key(access_key);
+            }
+            Ok(Arc::new(builder.build()?) as Arc<dyn ObjectStore>)
+        };
+
+        Ok(Self::new(
+            super::StorageType::Azure,
+            Arc::new(build),
+            name,
+            model,
+            pipe_name,
+        ))
+    }
+
+    #[cfg(feature = "gcs")]
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    pub fn try_new_gcs(
+        StorageGcsArgs {
+            gcs_service_account_path,
+        }: &StorageGcsArgs,
+        name: String,
+        model: Option<&Name>,
+        pipe_name: &Name,
+    ) -> Result<Self> {
+        debug!("Initializing Storage Set ({model:?}) - GCS");
+
+        let gcs_service_account_path = gcs_service_account_path.clone();
+        let build = move |bucket: &str| {
+            let mut builder =
+                ::object_store::gcp::GoogleCloudStorageBuilder::from_env().with_bucket_name(bucket);
+            if let Some(path) = &gcs_service_account_path {
+                builder = builder.with_service_account_path(path);
+            }
+            Ok(Arc::new(builder.build()?) as Arc<dyn ObjectStore>)
+        };
+
+        Ok(Self::new(
+            super::StorageType::Gcs,
+            Arc::new(build),
+            name,
+            model,
+            pipe_name,
+        ))
+    }
+
+    fn new(
+        storage_type: super::StorageType,
+        build: Arc<BuildFn>,
+        name: String,
+        model: Option<&Name>,
+        pipe_name: &Name,
+    ) -> Self {
+        Self {
+            build,
+            buckets: Default::default(),
+            model: model.cloned(),
+            name,
+            pipe_name: pipe_name.clone(),
+            pipe_timestamp: Utc::now()
+                .to_rfc3339_opts(SecondsFormat::Nanos, true)
+                .replace(':', "-"),
+            storage_type,
+        }
+    }
+
+    /// Connect to the bucket of the model lazily, so that the unused backends need no credentials.
+    fn get_bucket(&self, model: &Name) -> Result<Arc<dyn ObjectStore>> {
+        let bucket_name = model.storage();
+        if let Some(bucket) = self
+            .buckets
+            .read()
+            .map_err(|error| anyhow!("failed to read {} buckets: {error}", self.storage_type))?
+            .get(bucket_name)
+        {
+            return Ok(bucket.clone());
+        }
+
+        let bucket = (self.build)(bucket_name).map_err(|error| {
+            anyhow!(
+                "failed to init {} bucket {bucket_name}: {error}",
+                self.storage_type,
+            )
+        })?;
+        self.buckets
+            .write()
+            .map_err(|error| anyhow!("failed to write {} buckets: {error}", self.storage_type))?
+            .insert(bucket_name.into(), bucket.clone());
+        Ok(bucket)
+    }
+}
+
+#[async_trait]
+impl super::Storage for Storage {
+    fn model(&self) -> Option<&Name> {
+        self.model.as_ref()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn storage_type(&self) -> super::StorageType {
+        self.storage_type
+    }
+
+    #[instrument(
+        level = Level::INFO,
+        skip_all,
+        fields(
+            data.len,
+            data.model = %model.as_str(),
+            storage.name = %self.name,
+            storage.r#type = %self.storage_type,
+        ),
+        err(Display),
+    )]
+    async fn get(&self, model: &Name, path: &str) -> Result<Bytes> {
+        let bytes = self
+            .get_bucket(model)?
+            .get(&Path::from(path))
+            .await
+            .map_err(|error| anyhow!("failed to get object from {}: {error}", self.storage_type))?
+            .bytes()
+            .await
+            .map_err(|error| {
+                anyhow!(
+                    "failed to get object data from {}: {error}",
+                    self.storage_type,
+                )
+            })?;
+
+        // Record the result as part of the current span.
+        Span::current().record("data.len", bytes.len());
+        Ok(bytes)
+    }
+
+    #[instrument(
+        level = Level::INFO,
+        skip_all,
+        fields(
+            data.len = %bytes.len(),
+            data.model = %model.as_str(),
+            storage.name = %self.name,
+            storage.r#type = %self.storage_type,
+        ),
+        err(Display),
+    )]
+    async fn put_with_model(&self, model: &Name, path: &str, bytes: Bytes) -> Result<String> {
+        let path = format!(
+            "{kind}/{prefix}/{timestamp}/{path}",
+            kind = super::name::KIND_STORAGE,
+            prefix = &self.pipe_name,
+            timestamp = &self.pipe_timestamp,
+        );
+
+        self.get_bucket(model)?
+            .put(&Path::from(path.as_str()), bytes.into())
+            .await
+            .map(|_| path)
+            .map_err(|error| anyhow!("failed to put object into {}: {error}", self.storage_type))
+    }
+
+    #[instrument(
+        level = Level::INFO,
+        skip_all,
+        fields(
+            data.len = %1usize,
+            data.model = %model.as_str(),
+            storage.name = %self.name,
+            storage.r#type = %self.storage_type,
+        ),
+        err(Display),
+    )]
+    async fn delete_with_model(&self, model: &Name, path: &str) -> Result<()> {
+        self.get_bucket(model)?
+            .delete(&Path::from(path))
+            .await
+            .map_err(|error| {
+                anyhow!(
+                    "failed to delete object from {}: {error}",
+                    self.storage_type,
+                )
+            })
+    }
+}
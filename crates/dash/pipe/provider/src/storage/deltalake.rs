@@ -2,7 +2,7 @@ use std::{
     collections::{BTreeMap, HashMap},
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc,
+        Arc, Weak,
     },
     time::{Duration, Instant},
 };
@@ -10,6 +10,7 @@ use std::{
 use anyhow::{anyhow, bail, Result};
 use ark_core_k8s::data::Name;
 use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
 use dash_pipe_api::storage::StorageS3Args;
 use deltalake::{
     aws,
@@ -18,7 +19,7 @@ use deltalake::{
     operations::create::CreateBuilder,
     protocol::SaveMode,
     writer::{DeltaWriter, JsonWriter},
-    DeltaTable, DeltaTableBuilder, DeltaTableError,
+    DeltaOps, DeltaTable, DeltaTableBuilder, DeltaTableError,
 };
 use inflector::Inflector;
 use schemars::{schema::RootSchema, JsonSchema};
@@ -28,7 +29,7 @@ use tokio::{
     sync::{Mutex, RwLock},
     time::sleep,
 };
-use tracing::{debug, instrument, Level};
+use tracing::{debug, info, instrument, warn, Level};
 
 use crate::{
     message::{DynValue, PipeMessage},
@@ -118,7 +119,16 @@ impl GlobalStorageContext {
 
             // load or create a table
             let name = self.name.clone();
-            match StorageContext::try_new::<DynValue>(&self.args, name, model, self.flush).await {
+            let compaction = Default::default();
+            match StorageContext::try_new::<DynValue>(
+                &self.args,
+                name,
+                model,
+                self.flush,
+                &compaction,
+            )
+            .await
+            {
                 Ok(ctx) => {
                     self.storages
                         .write()
@@ -149,6 +159,7 @@ impl Storage {
         name: String,
         model: Option<&Name>,
         flush: Option<Duration>,
+        compaction: &super::StorageCompactionArgs,
     ) -> Result<Self>
     where
         Value: JsonSchema,
@@ -156,7 +167,14 @@ impl Storage {
         Ok(Self {
             inner: match model {
                 Some(model) => Some(
-                    StorageContext::try_new::<Value>(args, name, model.storage(), flush).await?,
+                    StorageContext::try_new::<Value>(
+                        args,
+                        name,
+                        model.storage(),
+                        flush,
+                        compaction,
+                    )
+                    .await?,
                 ),
                 None => None,
             },
@@ -179,6 +197,39 @@ impl<Value> super::MetadataStorage<Value> for Storage {
         }
     }
 
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    async fn list_metadata_range(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<super::Stream<PipeMessage<Value>>>
+    where
+        Value: 'static + Send + DeserializeOwned,
+    {
+        match self.inner.as_ref() {
+            Some(inner) => {
+                <StorageContext as super::MetadataStorage<Value>>::list_metadata_range(
+                    inner, from, to,
+                )
+                .await
+            }
+            None => bail!("cannot init dataframe from uninited DeltaLake table"),
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    async fn query_metadata(&self, sql: &str) -> Result<super::Stream<PipeMessage<Value>>>
+    where
+        Value: 'static + Send + DeserializeOwned,
+    {
+        match self.inner.as_ref() {
+            Some(inner) => {
+                <StorageContext as super::MetadataStorage<Value>>::query_metadata(inner, sql).await
+            }
+            None => bail!("cannot init dataframe from uninited DeltaLake table"),
+        }
+    }
+
     #[instrument(level = Level::INFO, skip_all, err(Display))]
     async fn put_metadata(&self, values: &[&PipeMessage<Value>]) -> Result<()>
     where
@@ -199,6 +250,14 @@ impl<Value> super::MetadataStorage<Value> for Storage {
             None => Ok(()),
         }
     }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn compact(&self) -> Result<()> {
+        match self.inner.as_ref() {
+            Some(inner) => <StorageContext as super::MetadataStorage<Value>>::compact(inner).await,
+            None => Ok(()),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -206,6 +265,7 @@ pub struct StorageContext {
     model: String,
     model_raw: String,
     name: String,
+    retention: Duration,
     table: Arc<RwLock<DeltaTable>>,
     writer: Arc<Mutex<StorageTableWriter>>,
 }
@@ -219,6 +279,7 @@ impl StorageContext {
         name: String,
         model: &str,
         flush: Option<Duration>,
+        compaction: &super::StorageCompactionArgs,
     ) -> Result<Self>
     where
         Value: JsonSchema,
@@ -243,10 +304,21 @@ impl StorageContext {
         let table = Arc::new(RwLock::new(table));
         let writer = StorageTableWriter::new(table.clone(), writer, flush);
 
+        let retention = compaction.retention();
+        if let Some(interval) = compaction.interval() {
+            spawn_compaction(
+                Arc::downgrade(&table),
+                model_raw.clone(),
+                interval,
+                retention,
+            );
+        }
+
         Ok(Self {
             model,
             model_raw,
             name,
+            retention,
             table,
             writer,
         })
@@ -331,6 +403,64 @@ impl<Value> super::MetadataStorage<Value> for StorageContext {
         })
     }
 
+    #[instrument(
+        level = Level::INFO,
+        skip(self),
+        fields(
+            data.len = %1usize,
+            data.model = %self.model_raw,
+            storage.name = &self.name,
+            storage.r#type = %Self::STORAGE_TYPE,
+        ),
+        err(Display),
+    )]
+    async fn list_metadata_range(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<super::Stream<PipeMessage<Value>>>
+    where
+        Value: 'static + Send + DeserializeOwned,
+    {
+        let sql = format!(
+            r#"SELECT * FROM "{model}"
+WHERE CAST("__timestamp" AS TIMESTAMP) >= CAST('{from}' AS TIMESTAMP)
+  AND CAST("__timestamp" AS TIMESTAMP) < CAST('{to}' AS TIMESTAMP)
+ORDER BY "__timestamp""#,
+            model = &self.model,
+            from = from.to_rfc3339_opts(SecondsFormat::Micros, true),
+            to = to.to_rfc3339_opts(SecondsFormat::Micros, true),
+        );
+        let df = self.sql(&sql).await?;
+
+        df.try_into_decoder().await.map_err(|error| {
+            anyhow!("failed to get object metadata range from DeltaLake object store: {error}")
+        })
+    }
+
+    /// NOTE: The table is registered as the snake-cased model name.
+    #[instrument(
+        level = Level::INFO,
+        skip(self),
+        fields(
+            data.len = %1usize,
+            data.model = %self.model_raw,
+            storage.name = &self.name,
+            storage.r#type = %Self::STORAGE_TYPE,
+        ),
+        err(Display),
+    )]
+    async fn query_metadata(&self, sql: &str) -> Result<super::Stream<PipeMessage<Value>>>
+    where
+        Value: 'static + Send + DeserializeOwned,
+    {
+        let df = self.sql(sql).await?;
+
+        df.try_into_decoder().await.map_err(|error| {
+            anyhow!("failed to get queried object metadata from DeltaLake object store: {error}")
+        })
+    }
+
     #[instrument(
         level = Level::INFO,
         skip_all,
@@ -376,6 +506,75 @@ impl<Value> super::MetadataStorage<Value> for StorageContext {
     async fn flush(&self) -> Result<()> {
         self.writer.lock().await.flush().await
     }
+
+    #[instrument(
+        level = Level::INFO,
+        skip_all,
+        fields(
+            data.len = %1usize,
+            data.model = %self.model_raw,
+            storage.name = &self.name,
+            storage.r#type = %Self::STORAGE_TYPE,
+        ),
+        err(Display),
+    )]
+    async fn compact(&self) -> Result<()> {
+        compact_table(&self.table, self.retention).await
+    }
+}
+
+/// Run the compaction periodically, until the table is dropped.
+fn spawn_compaction(
+    table: Weak<RwLock<DeltaTable>>,
+    model: String,
+    interval: Duration,
+    retention: Duration,
+) {
+    ::tokio::spawn(async move {
+        loop {
+            sleep(interval).await;
+            let Some(table) = table.upgrade() else {
+                break;
+            };
+            if let Err(error) = compact_table(&table, retention).await {
+                warn!("failed to compact DeltaLake table {model}: {error}");
+            }
+        }
+    });
+}
+
+#[instrument(level = Level::INFO, skip(table), err(Display))]
+async fn compact_table(table: &RwLock<DeltaTable>, retention: Duration) -> Result<()> {
+    // assert ACID by acquiring WRITE access for table
+    let mut table = table.write().await;
+    if table.schema().is_none() {
+        return Ok(());
+    }
+
+    // bin-pack the small files
+    let (optimized, metrics) = DeltaOps(table.clone())
+        .optimize()
+        .await
+        .map_err(|error| anyhow!("failed to optimize DeltaLake table: {error}"))?;
+
+    // remove the files no longer referenced by the versions within the retention period
+    let retention = ::chrono::Duration::from_std(retention)
+        .map_err(|error| anyhow!("failed to parse DeltaLake retention period: {error}"))?;
+    let (vacuumed, metrics_vacuum) = DeltaOps(optimized)
+        .vacuum()
+        .with_retention_period(retention)
+        .with_enforce_retention_duration(false)
+        .await
+        .map_err(|error| anyhow!("failed to vacuum DeltaLake table: {error}"))?;
+
+    info!(
+        "compacted DeltaLake table: {added} file(s) added, {removed} file(s) removed, {deleted} file(s) vacuumed",
+        added = metrics.num_files_added,
+        removed = metrics.num_files_removed,
+        deleted = metrics_vacuum.files_deleted.len(),
+    );
+    *table = vacuumed;
+    Ok(())
 }
 
 struct StorageTableWriter {
@@ -473,6 +672,7 @@ async fn load_table(
     StorageS3Args {
         access_key,
         s3_endpoint,
+        s3_replicas: _,
         region,
         secret_key,
     }: &StorageS3Args,
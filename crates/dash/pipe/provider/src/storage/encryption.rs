@@ -0,0 +1,209 @@
+use std::sync::Arc;
+
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Key, Nonce,
+};
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use bytes::Bytes;
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument, Level};
+
+use crate::message::PipePayloadEncryption;
+
+/// A key encryption key (KEK), which wraps the per-payload data keys.
+///
+/// The KMS integrations can be plugged in by implementing this trait.
+#[async_trait]
+pub trait KeyWrapper
+where
+    Self: Send + Sync,
+{
+    fn key_id(&self) -> &str;
+
+    async fn wrap(&self, data_key: &[u8]) -> Result<Bytes>;
+
+    async fn unwrap(&self, key_id: &str, wrapped_key: &[u8]) -> Result<Bytes>;
+}
+
+/// A locally-provided AES-256 master key.
+pub struct LocalMasterKey {
+    cipher: Aes256Gcm,
+    key_id: String,
+}
+
+impl LocalMasterKey {
+    const NONCE_LEN: usize = 12;
+
+    pub fn try_new(key_id: String, master_key: &[u8]) -> Result<Self> {
+        if master_key.len() != 32 {
+            bail!(
+                "master key should be 32 bytes, but given {}",
+                master_key.len()
+            )
+        }
+        Ok(Self {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(master_key)),
+            key_id,
+        })
+    }
+}
+
+#[async_trait]
+impl KeyWrapper for LocalMasterKey {
+    fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    async fn wrap(&self, data_key: &[u8]) -> Result<Bytes> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let wrapped_key = self
+            .cipher
+            .encrypt(&nonce, data_key)
+            .map_err(|error| anyhow!("failed to wrap data key: {error}"))?;
+
+        // nonce || wrapped key
+        Ok([nonce.as_slice(), &wrapped_key].concat().into())
+    }
+
+    async fn unwrap(&self, key_id: &str, wrapped_key: &[u8]) -> Result<Bytes> {
+        if key_id != self.key_id {
+            bail!("unknown master key: {key_id:?}")
+        }
+        if wrapped_key.len() < Self::NONCE_LEN {
+            bail!("malformed wrapped data key")
+        }
+
+        let (nonce, wrapped_key) = wrapped_key.split_at(Self::NONCE_LEN);
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), wrapped_key)
+            .map(Into::into)
+            .map_err(|error| anyhow!("failed to unwrap data key: {error}"))
+    }
+}
+
+/// An envelope encryption layer of the payloads at rest.
+#[derive(Clone)]
+pub struct StorageEncryption {
+    wrapper: Arc<dyn KeyWrapper>,
+}
+
+impl StorageEncryption {
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    pub fn try_new(args: &StorageEncryptionArgs) -> Result<Option<Self>> {
+        match &args.encryption_master_key {
+            Some(master_key) => {
+                debug!("Initializing Storage Encryption");
+
+                let master_key = BASE64
+                    .decode(master_key)
+                    .map_err(|error| anyhow!("failed to decode master key: {error}"))?;
+                let wrapper = LocalMasterKey::try_new(args.encryption_key_id.clone(), &master_key)?;
+                Ok(Some(Self::with_wrapper(wrapper)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn with_wrapper<W>(wrapper: W) -> Self
+    where
+        W: 'static + KeyWrapper,
+    {
+        Self {
+            wrapper: Arc::new(wrapper),
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip_all, fields(data.len = %plaintext.len()), err(Display))]
+    pub(crate) async fn encrypt(&self, plaintext: &[u8]) -> Result<(Bytes, PipePayloadEncryption)> {
+        let data_key = Aes256Gcm::generate_key(OsRng);
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = Aes256Gcm::new(&data_key)
+            .encrypt(&nonce, plaintext)
+            .map_err(|error| anyhow!("failed to encrypt payload: {error}"))?;
+
+        let encryption = PipePayloadEncryption {
+            data_key: self.wrapper.wrap(&data_key).await?,
+            key_id: self.wrapper.key_id().into(),
+            nonce: nonce.to_vec().into(),
+        };
+        Ok((ciphertext.into(), encryption))
+    }
+
+    #[instrument(level = Level::INFO, skip_all, fields(data.len = %ciphertext.len()), err(Display))]
+    pub(crate) async fn decrypt(
+        &self,
+        ciphertext: &[u8],
+        encryption: &PipePayloadEncryption,
+    ) -> Result<Bytes> {
+        let PipePayloadEncryption {
+            data_key,
+            key_id,
+            nonce,
+        } = encryption;
+
+        let data_key = self.wrapper.unwrap(key_id, data_key).await?;
+        if data_key.len() != 32 || nonce.len() != LocalMasterKey::NONCE_LEN {
+            bail!("malformed payload encryption metadata")
+        }
+        Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&data_key))
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map(Into::into)
+            .map_err(|error| anyhow!("failed to decrypt payload: {error}"))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Parser)]
+pub struct StorageEncryptionArgs {
+    #[arg(
+        long,
+        env = "PIPE_ENCRYPTION_KEY_ID",
+        value_name = "NAME",
+        default_value_t = StorageEncryptionArgs::default_key_id(),
+    )]
+    #[serde(default = "StorageEncryptionArgs::default_key_id")]
+    pub encryption_key_id: String,
+
+    /// Encrypt the payloads at rest with the base64-encoded AES-256 master key.
+    #[arg(long, env = "PIPE_ENCRYPTION_MASTER_KEY", value_name = "VALUE")]
+    #[serde(default)]
+    pub encryption_master_key: Option<String>,
+}
+
+impl Default for StorageEncryptionArgs {
+    fn default() -> Self {
+        Self {
+            encryption_key_id: Self::default_key_id(),
+            encryption_master_key: None,
+        }
+    }
+}
+
+impl StorageEncryptionArgs {
+    fn default_key_id() -> String {
+        "local".into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[::tokio::test]
+    async fn encrypt_and_decrypt_payload() {
+        let encryption = StorageEncryption::with_wrapper(
+            LocalMasterKey::try_new("test".into(), &[7; 32]).unwrap(),
+        );
+
+        let plaintext = b"hello world";
+        let (ciphertext, metadata) = encryption.encrypt(plaintext).await.unwrap();
+        assert_ne!(&ciphertext[..], plaintext);
+        assert_eq!(metadata.key_id, "test");
+
+        let decrypted = encryption.decrypt(&ciphertext, &metadata).await.unwrap();
+        assert_eq!(&decrypted[..], plaintext);
+    }
+}
@@ -225,6 +225,7 @@ async fn load_table(
     StorageS3Args {
         access_key,
         s3_endpoint,
+        s3_replicas: _,
         region,
         secret_key,
     }: &StorageS3Args,
@@ -1,5 +1,9 @@
+#[cfg(any(feature = "azure", feature = "gcs"))]
+pub mod cloud;
 #[cfg(feature = "deltalake")]
 pub mod deltalake;
+#[cfg(feature = "encryption")]
+pub mod encryption;
 #[cfg(feature = "lancedb")]
 pub mod lancedb;
 pub mod passthrough;
@@ -13,8 +17,9 @@ use ark_core_k8s::data::Name;
 use async_stream::try_stream;
 use async_trait::async_trait;
 use bytes::Bytes;
+use chrono::{DateTime, Utc};
 use clap::{ArgAction, Parser};
-use futures::{StreamExt, TryStreamExt};
+use futures::{future::ready, StreamExt, TryStreamExt};
 use schemars::JsonSchema;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use strum::{Display, EnumString};
@@ -40,10 +45,16 @@ impl StorageIO {
 
 pub struct StorageSet {
     args: StorageArgs,
+    #[cfg(feature = "azure")]
+    azure: self::cloud::Storage,
     default: StorageType,
     default_metadata: MetadataStorageType,
     #[cfg(feature = "deltalake")]
     deltalake: self::deltalake::Storage,
+    #[cfg(feature = "encryption")]
+    encryption: Option<self::encryption::StorageEncryption>,
+    #[cfg(feature = "gcs")]
+    gcs: self::cloud::Storage,
     #[cfg(feature = "lancedb")]
     lancedb: self::lancedb::Storage,
     passthrough: self::passthrough::Storage,
@@ -72,7 +83,7 @@ impl StorageSet {
         }
 
         let default = match args.persistence {
-            Some(true) => StorageType::PERSISTENT,
+            Some(true) => args.persistence_type.unwrap_or(StorageType::PERSISTENT),
             Some(false) | None => StorageType::TEMPORARY,
         };
         let pipe_name = args
@@ -87,6 +98,13 @@ impl StorageSet {
 
         Ok(Self {
             args: args.clone(),
+            #[cfg(feature = "azure")]
+            azure: self::cloud::Storage::try_new_azure(
+                &args.azure,
+                args.storage_name.clone(),
+                model,
+                &pipe_name,
+            )?,
             default,
             default_metadata: default_metadata.default_storage,
             #[cfg(feature = "deltalake")]
@@ -98,11 +116,21 @@ impl StorageSet {
                     args.storage_name.clone(),
                     model,
                     flush,
+                    &args.compaction,
                 )
                 .await?
             } else {
                 self::deltalake::Storage::default()
             },
+            #[cfg(feature = "encryption")]
+            encryption: self::encryption::StorageEncryption::try_new(&args.encryption)?,
+            #[cfg(feature = "gcs")]
+            gcs: self::cloud::Storage::try_new_gcs(
+                &args.gcs,
+                args.storage_name.clone(),
+                model,
+                &pipe_name,
+            )?,
             #[cfg(feature = "lancedb")]
             lancedb: if persistence_metadata {
                 self::lancedb::Storage::try_new::<Value>(&args.s3, args.storage_name.clone(), model)
@@ -118,6 +146,10 @@ impl StorageSet {
 
     pub const fn get(&self, storage_type: StorageType) -> &(dyn Send + Sync + Storage) {
         match storage_type {
+            #[cfg(feature = "azure")]
+            StorageType::Azure => &self.azure,
+            #[cfg(feature = "gcs")]
+            StorageType::Gcs => &self.gcs,
             StorageType::Passthrough => &self.passthrough,
             #[cfg(feature = "s3")]
             StorageType::S3 => &self.s3,
@@ -156,6 +188,11 @@ impl StorageSet {
 }
 
 impl StorageSet {
+    #[cfg(feature = "azure")]
+    pub const fn get_azure(&self) -> &self::cloud::Storage {
+        &self.azure
+    }
+
     #[cfg(feature = "deltalake")]
     pub async fn create_deltalake<Value>(
         &self,
@@ -170,6 +207,7 @@ impl StorageSet {
             self.args.storage_name.clone(),
             model.storage(),
             flush,
+            &self.args.compaction,
         )
         .await
     }
@@ -188,6 +226,16 @@ impl StorageSet {
         &self.deltalake
     }
 
+    #[cfg(feature = "encryption")]
+    pub const fn encryption(&self) -> Option<&self::encryption::StorageEncryption> {
+        self.encryption.as_ref()
+    }
+
+    #[cfg(feature = "gcs")]
+    pub const fn get_gcs(&self) -> &self::cloud::Storage {
+        &self.gcs
+    }
+
     #[cfg(feature = "lancedb")]
     pub const fn get_lancedb(&self) -> &self::lancedb::Storage {
         &self.lancedb
@@ -323,6 +371,14 @@ pub trait MetadataStorageExt<Value> {
     async fn list_as_empty(&self) -> Result<Stream<PipeMessage<Value>>>
     where
         Value: 'static + Send + DeserializeOwned;
+
+    async fn query(
+        &self,
+        storage: &Arc<StorageSet>,
+        sql: &str,
+    ) -> Result<Stream<PipeMessage<Value>>>
+    where
+        Value: 'static + Send + DeserializeOwned;
 }
 
 #[async_trait]
@@ -359,6 +415,26 @@ where
         }
         .boxed())
     }
+
+    #[instrument(level = Level::INFO, skip(self, storage), err(Display))]
+    async fn query(
+        &self,
+        storage: &Arc<StorageSet>,
+        sql: &str,
+    ) -> Result<Stream<PipeMessage<Value>>>
+    where
+        Value: 'static + Send + DeserializeOwned,
+    {
+        let mut list = self.query_metadata(sql).await?;
+
+        let storage = storage.clone();
+        Ok(try_stream! {
+            while let Some(message) = list.try_next().await? {
+                yield message.load_payloads(&storage).await?;
+            }
+        }
+        .boxed())
+    }
 }
 
 #[async_trait]
@@ -367,6 +443,32 @@ pub trait MetadataStorage<Value = ()> {
     where
         Value: 'static + Send + DeserializeOwned;
 
+    /// List the metadata whose timestamps are in the range of `[from, to)`.
+    async fn list_metadata_range(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Stream<PipeMessage<Value>>>
+    where
+        Value: 'static + Send + DeserializeOwned,
+    {
+        let list = self.list_metadata().await?;
+        Ok(list
+            .try_filter(move |message| {
+                let timestamp = message.timestamp();
+                ready(from <= timestamp && timestamp < to)
+            })
+            .boxed())
+    }
+
+    /// Query the metadata with the SQL statement, which should select the whole messages.
+    async fn query_metadata(&self, _sql: &str) -> Result<Stream<PipeMessage<Value>>>
+    where
+        Value: 'static + Send + DeserializeOwned,
+    {
+        bail!("querying metadata is not supported")
+    }
+
     async fn put_metadata(&self, values: &[&PipeMessage<Value>]) -> Result<()>
     where
         Value: 'async_trait + Send + Sync + Clone + Serialize + JsonSchema;
@@ -374,6 +476,11 @@ pub trait MetadataStorage<Value = ()> {
     async fn flush(&self) -> Result<()> {
         Ok(())
     }
+
+    /// Compact the small files and vacuum the old versions of the storage.
+    async fn compact(&self) -> Result<()> {
+        Ok(())
+    }
 }
 
 #[derive(
@@ -392,6 +499,10 @@ pub trait MetadataStorage<Value = ()> {
     JsonSchema,
 )]
 pub enum StorageType {
+    #[cfg(feature = "azure")]
+    Azure,
+    #[cfg(feature = "gcs")]
+    Gcs,
     Passthrough,
     #[cfg(feature = "s3")]
     S3,
@@ -457,13 +568,37 @@ pub trait Storage {
 
 #[derive(Clone, Debug, Serialize, Deserialize, Parser)]
 pub struct StorageArgs {
+    #[cfg(feature = "azure")]
+    #[command(flatten)]
+    #[serde(default)]
+    pub azure: ::dash_pipe_api::storage::StorageAzureArgs,
+
+    #[command(flatten)]
+    #[serde(default)]
+    pub compaction: StorageCompactionArgs,
+
+    #[cfg(feature = "encryption")]
+    #[command(flatten)]
+    #[serde(default)]
+    pub encryption: self::encryption::StorageEncryptionArgs,
+
     #[arg(long, env = "PIPE_FLUSH", value_name = "MS", default_value_t = 10_000)]
     flush_ms: u64,
 
+    #[cfg(feature = "gcs")]
+    #[command(flatten)]
+    #[serde(default)]
+    pub gcs: ::dash_pipe_api::storage::StorageGcsArgs,
+
     #[arg(long, env = "PIPE_PERSISTENCE", action = ArgAction::SetTrue)]
     #[serde(default)]
     persistence: Option<bool>,
 
+    /// The storage of the persistent payloads.
+    #[arg(long, env = "PIPE_PERSISTENCE_TYPE", value_name = "TYPE")]
+    #[serde(default)]
+    persistence_type: Option<StorageType>,
+
     #[arg(long, env = "PIPE_PERSISTENCE_METADATA", action = ArgAction::SetTrue)]
     #[serde(default)]
     persistence_metadata: Option<bool>,
@@ -501,6 +636,51 @@ impl StorageArgs {
     }
 }
 
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, Parser)]
+pub struct StorageCompactionArgs {
+    /// Compact the metadata storage periodically, or never if zero.
+    #[arg(long, env = "PIPE_COMPACTION", value_name = "MS", default_value_t = 0)]
+    #[serde(default)]
+    compaction_ms: u64,
+
+    /// The retention period of the old metadata versions to be vacuumed.
+    #[arg(
+        long,
+        env = "PIPE_COMPACTION_RETENTION",
+        value_name = "HOURS",
+        default_value_t = StorageCompactionArgs::default_retention_hours(),
+    )]
+    #[serde(default = "StorageCompactionArgs::default_retention_hours")]
+    compaction_retention_hours: u64,
+}
+
+impl Default for StorageCompactionArgs {
+    fn default() -> Self {
+        Self {
+            compaction_ms: 0,
+            compaction_retention_hours: Self::default_retention_hours(),
+        }
+    }
+}
+
+impl StorageCompactionArgs {
+    const fn default_retention_hours() -> u64 {
+        7 * 24
+    }
+
+    pub const fn interval(&self) -> Option<Duration> {
+        if self.compaction_ms > 0 {
+            Some(Duration::from_millis(self.compaction_ms))
+        } else {
+            None
+        }
+    }
+
+    pub const fn retention(&self) -> Duration {
+        Duration::from_secs(self.compaction_retention_hours * 60 * 60)
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, Parser)]
 pub struct DummyStorageArgs {}
 
@@ -1,3 +1,5 @@
+use std::time::Instant;
+
 use anyhow::{anyhow, Result};
 use ark_core_k8s::data::Name;
 use async_trait::async_trait;
@@ -8,15 +10,112 @@ use futures::{FutureExt, TryFutureExt, TryStreamExt};
 use minio::s3::{
     args::PutObjectApiArgs, client::Client, creds::StaticProvider, http::BaseUrl, types::S3Api,
 };
-use tracing::{debug, instrument, Level, Span};
+use opentelemetry::{
+    global,
+    metrics::{Counter, Histogram},
+    KeyValue,
+};
+use tracing::{debug, instrument, warn, Level, Span};
+use url::Url;
 
 #[derive(Clone)]
-pub struct Storage {
+struct Endpoint {
     client: Client,
+    url: Url,
+}
+
+impl Endpoint {
+    fn try_new(url: &Url, access_key: &str, secret_key: &str) -> Result<Self> {
+        let base_url: BaseUrl = url
+            .as_str()
+            .parse()
+            .map_err(|error| anyhow!("failed to parse s3 storage endpoint: {error}"))?;
+        let provider = StaticProvider::new(access_key, secret_key, None);
+        let ssl_cert_file = None;
+        let ignore_cert_check = Some(!base_url.https);
+
+        Ok(Self {
+            client: Client::new(
+                base_url,
+                Some(Box::new(provider)),
+                ssl_cert_file,
+                ignore_cert_check,
+            )?,
+            url: url.clone(),
+        })
+    }
+
+    async fn get(&self, bucket_name: &str, path: &str) -> Result<Bytes> {
+        self.client
+            .get_object(bucket_name, path)
+            .send()
+            .map_err(|error| anyhow!("failed to get object from S3 object store: {error}"))
+            .and_then(|response| {
+                response
+                    .content
+                    .to_stream()
+                    .and_then(|(stream, _size)| stream.try_collect().map_err(Into::into))
+                    .map(|result| result.map(|bytes: BytesMut| bytes.into()))
+                    .map_err(|error| {
+                        anyhow!("failed to get object data from S3 object store: {error}")
+                    })
+            })
+            .await
+    }
+
+    async fn put(&self, bucket_name: &str, path: &str, bytes: &Bytes) -> Result<()> {
+        let args = PutObjectApiArgs::new(bucket_name, path, bytes)?;
+
+        self.client
+            .put_object_api(&args)
+            .await
+            .map(|_| ())
+            .map_err(|error| anyhow!("failed to put object into S3 object store: {error}"))
+    }
+
+    async fn delete(&self, bucket_name: &str, path: &str) -> Result<()> {
+        self.client
+            .remove_object(bucket_name, path)
+            .send()
+            .map_ok(|_| ())
+            .map_err(|error| anyhow!("failed to delete object from S3 object store: {error}"))
+            .await
+    }
+}
+
+#[derive(Clone)]
+struct ReplicationMetrics {
+    failures: Counter<u64>,
+    lag: Histogram<f64>,
+}
+
+impl ReplicationMetrics {
+    fn new() -> Self {
+        let meter = global::meter("dash-pipe");
+        Self {
+            failures: meter
+                .u64_counter("dash_pipe_storage_s3_replication_failures")
+                .with_description("The number of the failed replications to the S3 replicas")
+                .build(),
+            lag: meter
+                .f64_histogram("dash_pipe_storage_s3_replication_lag")
+                .with_description("The delay between the primary and the replica writes")
+                .with_unit("s")
+                .build(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Storage {
+    metrics: ReplicationMetrics,
     model: Option<Name>,
     name: String,
     pipe_name: Name,
     pipe_timestamp: String,
+    primary: Endpoint,
+    /// The secondary endpoints, in the order of failover
+    replicas: Vec<Endpoint>,
 }
 
 impl Storage {
@@ -28,6 +127,7 @@ impl Storage {
             access_key,
             region: _,
             s3_endpoint,
+            s3_replicas,
             secret_key,
         }: &StorageS3Args,
         name: String,
@@ -36,29 +136,49 @@ impl Storage {
     ) -> Result<Self> {
         debug!("Initializing Storage Set ({model:?}) - S3");
 
-        let base_url: BaseUrl = s3_endpoint
-            .as_str()
-            .parse()
-            .map_err(|error| anyhow!("failed to parse s3 storage endpoint: {error}"))?;
-        let provider = StaticProvider::new(access_key, secret_key, None);
-        let ssl_cert_file = None;
-        let ignore_cert_check = Some(!base_url.https);
-
         Ok(Self {
-            client: Client::new(
-                base_url,
-                Some(Box::new(provider)),
-                ssl_cert_file,
-                ignore_cert_check,
-            )?,
+            metrics: ReplicationMetrics::new(),
             model: model.cloned(),
             name,
             pipe_name: pipe_name.clone(),
             pipe_timestamp: Utc::now()
                 .to_rfc3339_opts(SecondsFormat::Nanos, true)
                 .replace(':', "-"),
+            primary: Endpoint::try_new(s3_endpoint, access_key, secret_key)?,
+            replicas: s3_replicas
+                .iter()
+                .map(|url| Endpoint::try_new(url, access_key, secret_key))
+                .collect::<Result<_>>()?,
         })
     }
+
+    /// Replicate the object to the secondaries in background.
+    fn replicate(&self, bucket_name: &str, path: &str, bytes: &Bytes) {
+        let timestamp = Instant::now();
+        for replica in &self.replicas {
+            let bucket_name = bucket_name.to_string();
+            let bytes = bytes.clone();
+            let metrics = self.metrics.clone();
+            let path = path.to_string();
+            let replica = replica.clone();
+
+            ::tokio::spawn(async move {
+                let attributes = [KeyValue::new("endpoint", replica.url.to_string())];
+                match replica.put(&bucket_name, &path, &bytes).await {
+                    Ok(()) => metrics
+                        .lag
+                        .record(timestamp.elapsed().as_secs_f64(), &attributes),
+                    Err(error) => {
+                        metrics.failures.add(1, &attributes);
+                        warn!(
+                            "failed to replicate {path} to {url}: {error}",
+                            url = replica.url,
+                        );
+                    }
+                }
+            });
+        }
+    }
 }
 
 #[async_trait]
@@ -89,36 +209,27 @@ impl super::Storage for Storage {
     async fn get(&self, model: &Name, path: &str) -> Result<Bytes> {
         let bucket_name = model.storage();
 
-        // Record the result as part of the current span.
-        let span = Span::current();
-        let record_data_len = |bytes: Option<&BytesMut>| {
-            span.record(
-                "data.len",
-                bytes.map(|bytes| bytes.len()).unwrap_or_default(),
-            );
-        };
+        // Fall back to the replicas on read failures
+        let mut result = self.primary.get(bucket_name, path).await;
+        for replica in &self.replicas {
+            match result {
+                Ok(_) => break,
+                Err(error) => {
+                    warn!(
+                        "failed to get {path}; falling back to {url}: {error}",
+                        url = replica.url,
+                    );
+                    result = replica.get(bucket_name, path).await;
+                }
+            }
+        }
 
-        self.client
-            .get_object(bucket_name, path)
-            .send()
-            .map_err(|error| anyhow!("failed to get object from S3 object store: {error}"))
-            .and_then(|response| {
-                response
-                    .content
-                    .to_stream()
-                    .and_then(|(stream, _size)| stream.try_collect().map_err(Into::into))
-                    .map(|result| {
-                        result.map(|bytes: BytesMut| {
-                            record_data_len(Some(&bytes));
-                            bytes.into()
-                        })
-                    })
-                    .map_err(|error| {
-                        record_data_len(None);
-                        anyhow!("failed to get object data from S3 object store: {error}")
-                    })
-            })
-            .await
+        // Record the result as part of the current span.
+        Span::current().record(
+            "data.len",
+            result.as_ref().map(|bytes| bytes.len()).unwrap_or_default(),
+        );
+        result
     }
 
     #[instrument(
@@ -140,13 +251,10 @@ impl super::Storage for Storage {
             prefix = &self.pipe_name,
             timestamp = &self.pipe_timestamp,
         );
-        let args = PutObjectApiArgs::new(bucket_name, &path, &bytes)?;
 
-        self.client
-            .put_object_api(&args)
-            .await
-            .map(|_| path)
-            .map_err(|error| anyhow!("failed to put object into S3 object store: {error}"))
+        self.primary.put(bucket_name, &path, &bytes).await?;
+        self.replicate(bucket_name, &path, &bytes);
+        Ok(path)
     }
 
     #[instrument(
@@ -163,11 +271,15 @@ impl super::Storage for Storage {
     async fn delete_with_model(&self, model: &Name, path: &str) -> Result<()> {
         let bucket_name = model.storage();
 
-        self.client
-            .remove_object(bucket_name, path)
-            .send()
-            .map_ok(|_| ())
-            .map_err(|error| anyhow!("failed to delete object from S3 object store: {error}"))
-            .await
+        self.primary.delete(bucket_name, path).await?;
+        for replica in &self.replicas {
+            if let Err(error) = replica.delete(bucket_name, path).await {
+                warn!(
+                    "failed to delete {path} from {url}: {error}",
+                    url = replica.url,
+                );
+            }
+        }
+        Ok(())
     }
 }
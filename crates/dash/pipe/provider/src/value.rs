@@ -0,0 +1,103 @@
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use schemars::JsonSchema;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::message::{Codec, PipeMessage, PipePayload};
+
+/// A typed pipe value, whose large binary fields are carried as the message payloads.
+///
+/// It is usually derived with `#[derive(PipePayload)]`.
+pub trait PipeValue
+where
+    Self: Sized,
+{
+    /// The message body, which holds the other fields and the small binary fields inline.
+    type Value: Serialize + DeserializeOwned + JsonSchema;
+
+    /// The preferred codec of the messages.
+    const CODEC: Codec = Codec::Json;
+
+    /// Split the value into the message body and the payloads.
+    fn into_message(self) -> PipeMessage<Self::Value>;
+
+    /// Restore the value from the message, whose payloads should be loaded.
+    fn try_from_message(message: PipeMessage<Self::Value>) -> Result<Self>;
+
+    fn to_bytes(self) -> Result<Bytes> {
+        self.into_message().to_bytes(Self::CODEC)
+    }
+}
+
+fn payload_key<Value>(message: &PipeMessage<Value>, field: &str) -> String {
+    format!("{field}/{id}", id = message.id())
+}
+
+#[doc(hidden)]
+pub fn split_payload<Value>(
+    message: &mut PipeMessage<Value>,
+    field: &str,
+    value: Bytes,
+    inline_max: usize,
+) -> Option<Bytes> {
+    if value.len() <= inline_max {
+        Some(value)
+    } else {
+        let key = payload_key(message, field);
+        message.payloads.push(PipePayload::new(key, Some(value)));
+        None
+    }
+}
+
+#[doc(hidden)]
+pub fn join_payload<Value>(
+    message: &PipeMessage<Value>,
+    field: &str,
+    value: Option<Bytes>,
+) -> Result<Bytes> {
+    if let Some(value) = value {
+        return Ok(value);
+    }
+
+    let key = payload_key(message, field);
+    message
+        .payloads
+        .iter()
+        .find(|payload| payload.key == key)
+        .and_then(|payload| payload.value.clone())
+        .ok_or_else(|| anyhow!("missing payload: {key}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::PipePayload;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, PipePayload)]
+    #[pipe(inline_max = 4)]
+    struct Frame {
+        index: usize,
+        thumbnail: Vec<u8>,
+        image: Bytes,
+    }
+
+    fn frame() -> Frame {
+        Frame {
+            index: 1,
+            thumbnail: vec![0; 4],
+            image: Bytes::from_static(&[0; 8]),
+        }
+    }
+
+    #[test]
+    fn split_large_binary_fields() {
+        let message = frame().into_message();
+        assert!(message.value.thumbnail.is_some());
+        assert!(message.value.image.is_none());
+        assert_eq!(message.payloads.len(), 1);
+
+        let restored = Frame::try_from_message(message).expect("failed to restore the value");
+        assert_eq!(restored, frame());
+    }
+}
@@ -0,0 +1,21 @@
+import asyncio
+
+from dash_pipe import PipeClient, PyPipeMessage
+
+
+async def main() -> None:
+    client = await PipeClient.connect()
+
+    subscriber = await client.subscribe('my-topic')
+    publisher = await client.publish('my-topic')
+
+    await publisher.send(PyPipeMessage([], {'msg': 'hello world'}))
+    await publisher.flush()
+
+    async for message in subscriber:
+        print(message.value)
+        break
+
+
+if __name__ == '__main__':
+    asyncio.run(main())
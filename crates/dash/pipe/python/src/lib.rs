@@ -0,0 +1,222 @@
+use std::sync::Arc;
+
+use bytes::Bytes;
+use clap::Parser;
+use dash_pipe_provider::{
+    messengers::{Publisher, Subscriber},
+    storage::Storage,
+    DynValue, Name, PipeClient, PipeClientArgs, PipeMessage, PipePublisher, PipeSubscriber,
+    PyPipeMessage,
+};
+use pyo3::{
+    exceptions::{PyException, PyStopAsyncIteration},
+    prelude::*,
+    types::PyBytes,
+};
+use tokio::sync::Mutex;
+
+/// A pipe client, which produces and consumes the pipe messages.
+///
+/// All the I/O methods are awaitable, and run on the shared tokio runtime.
+#[pyclass(name = "PipeClient")]
+struct PyPipeClient {
+    inner: Arc<PipeClient>,
+}
+
+#[pymethods]
+impl PyPipeClient {
+    /// Connect to the messenger and the storage, configured by the environment variables.
+    #[staticmethod]
+    fn connect(py: Python<'_>) -> PyResult<Bound<'_, PyAny>> {
+        ::pyo3_asyncio::tokio::future_into_py(py, async move {
+            // NOTE: the python arguments should not be parsed as the client's
+            let args = PipeClientArgs::try_parse_from(["dash-pipe"]).map_err(error_to_py)?;
+            PipeClient::try_new_dynamic(&args)
+                .await
+                .map(|inner| Self {
+                    inner: Arc::new(inner),
+                })
+                .map_err(error_to_py)
+        })
+    }
+
+    fn publish<'py>(&self, py: Python<'py>, topic: &str) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.inner.clone();
+        let topic = parse_topic(topic)?;
+        ::pyo3_asyncio::tokio::future_into_py(py, async move {
+            client
+                .publish(topic)
+                .await
+                .map(|inner| PyPipePublisher { inner })
+                .map_err(error_to_py)
+        })
+    }
+
+    fn subscribe<'py>(&self, py: Python<'py>, topic: &str) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.inner.clone();
+        let topic = parse_topic(topic)?;
+        ::pyo3_asyncio::tokio::future_into_py(py, async move {
+            client
+                .subscribe(topic)
+                .await
+                .map(|inner| PyPipeSubscriber {
+                    inner: Arc::new(Mutex::new(inner)),
+                })
+                .map_err(error_to_py)
+        })
+    }
+
+    /// Send a request message to the topic, and wait for the reply.
+    fn call<'py>(
+        &self,
+        py: Python<'py>,
+        topic: &str,
+        message: PyPipeMessage,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.inner.clone();
+        let topic = parse_topic(topic)?;
+        ::pyo3_asyncio::tokio::future_into_py(py, async move {
+            client
+                .call::<DynValue>(topic, message.into())
+                .await
+                .map(PyPipeMessage::from)
+                .map_err(error_to_py)
+        })
+    }
+
+    /// Load the data from the default storage.
+    fn get<'py>(&self, py: Python<'py>, model: &str, path: String) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.inner.clone();
+        let model = parse_topic(model)?;
+        ::pyo3_asyncio::tokio::future_into_py(py, async move {
+            let data = client
+                .storage()
+                .get_default()
+                .get(&model, &path)
+                .await
+                .map_err(error_to_py)?;
+            Ok(Python::with_gil(|py| {
+                PyBytes::new_bound(py, &data).unbind()
+            }))
+        })
+    }
+
+    /// Store the data into the default storage, and return its path.
+    fn put<'py>(
+        &self,
+        py: Python<'py>,
+        model: &str,
+        path: String,
+        data: &[u8],
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.inner.clone();
+        let model = parse_topic(model)?;
+        let data = Bytes::copy_from_slice(data);
+        ::pyo3_asyncio::tokio::future_into_py(py, async move {
+            client
+                .storage()
+                .get_default()
+                .put(Some(&model), &path, data)
+                .await
+                .map_err(error_to_py)
+        })
+    }
+}
+
+#[pyclass(name = "PipePublisher")]
+struct PyPipePublisher {
+    inner: PipePublisher,
+}
+
+#[pymethods]
+impl PyPipePublisher {
+    fn send<'py>(&self, py: Python<'py>, message: PyPipeMessage) -> PyResult<Bound<'py, PyAny>> {
+        let publisher = self.inner.clone();
+        ::pyo3_asyncio::tokio::future_into_py(py, async move {
+            <PipePublisher as Publisher<PipeMessage, PipeMessage>>::send_one(
+                &publisher,
+                message.into(),
+            )
+            .await
+            .map_err(error_to_py)
+        })
+    }
+
+    /// Send a request message, and wait for the reply.
+    fn request<'py>(&self, py: Python<'py>, message: PyPipeMessage) -> PyResult<Bound<'py, PyAny>> {
+        let publisher = self.inner.clone();
+        ::pyo3_asyncio::tokio::future_into_py(py, async move {
+            <PipePublisher as Publisher<PipeMessage, PipeMessage>>::request_one(
+                &publisher,
+                message.into(),
+            )
+            .await
+            .map(PyPipeMessage::from)
+            .map_err(error_to_py)
+        })
+    }
+
+    fn flush<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let publisher = self.inner.clone();
+        ::pyo3_asyncio::tokio::future_into_py(py, async move {
+            <PipePublisher as Publisher<PipeMessage, PipeMessage>>::flush(&publisher)
+                .await
+                .map_err(error_to_py)
+        })
+    }
+}
+
+/// A pipe subscriber, which can be also consumed with `async for`.
+#[pyclass(name = "PipeSubscriber")]
+struct PyPipeSubscriber {
+    inner: Arc<Mutex<PipeSubscriber<DynValue>>>,
+}
+
+#[pymethods]
+impl PyPipeSubscriber {
+    /// Read a message, or `None` if the subscription is closed.
+    fn read<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let subscriber = self.inner.clone();
+        ::pyo3_asyncio::tokio::future_into_py(py, async move {
+            subscriber
+                .lock()
+                .await
+                .read_one()
+                .await
+                .map(|message| message.map(PyPipeMessage::from))
+                .map_err(error_to_py)
+        })
+    }
+
+    fn __aiter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __anext__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let subscriber = self.inner.clone();
+        ::pyo3_asyncio::tokio::future_into_py(py, async move {
+            match subscriber.lock().await.read_one().await {
+                Ok(Some(message)) => Ok(PyPipeMessage::from(message)),
+                Ok(None) => Err(PyStopAsyncIteration::new_err(())),
+                Err(error) => Err(error_to_py(error)),
+            }
+        })
+    }
+}
+
+fn parse_topic(topic: &str) -> PyResult<Name> {
+    topic.parse().map_err(error_to_py)
+}
+
+fn error_to_py(error: impl ToString) -> PyErr {
+    PyException::new_err(error.to_string())
+}
+
+#[pymodule]
+fn dash_pipe(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyPipeClient>()?;
+    m.add_class::<PyPipeMessage>()?;
+    m.add_class::<PyPipePublisher>()?;
+    m.add_class::<PyPipeSubscriber>()?;
+    Ok(())
+}
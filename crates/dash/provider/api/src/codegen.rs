@@ -0,0 +1,66 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The options to generate a typed client of a function.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CodegenOptions {
+    #[serde(default)]
+    pub language: CodegenLanguage,
+    #[serde(default)]
+    pub transport: CodegenTransport,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum CodegenLanguage {
+    #[default]
+    Rust,
+    TypeScript,
+}
+
+impl CodegenLanguage {
+    /// Return the file extension of the generated module.
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            Self::Rust => "rs",
+            Self::TypeScript => "ts",
+        }
+    }
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum CodegenTransport {
+    /// Invoke the function through the dash gateway.
+    #[default]
+    Http,
+    /// Request the function over dash-pipe, whose topic is named after the function.
+    Pipe,
+}
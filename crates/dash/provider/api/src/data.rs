@@ -2,6 +2,7 @@ use std::iter::Sum;
 
 use byte_unit::Byte;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 #[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub struct Capacity {
@@ -53,3 +54,151 @@ impl Sum for Capacity {
         })
     }
 }
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Usage {
+    pub bytes: Byte,
+    pub objects: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListOptions {
+    #[serde(default)]
+    pub offset: usize,
+    #[serde(default = "ListOptions::default_limit")]
+    pub limit: usize,
+    /// The comma-separated field paths to be returned, e.g. `name,spec.size`.
+    /// If it is not set, all fields are returned.
+    #[serde(default)]
+    pub fields: Option<String>,
+}
+
+impl Default for ListOptions {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            limit: Self::default_limit(),
+            fields: None,
+        }
+    }
+}
+
+impl ListOptions {
+    pub const MAX_LIMIT: usize = 1_000;
+
+    const fn default_limit() -> usize {
+        30
+    }
+
+    /// Return the number of the leading items to be fetched from each storage.
+    pub fn fetch_limit(&self) -> usize {
+        self.offset.saturating_add(self.limit.min(Self::MAX_LIMIT))
+    }
+
+    pub fn paginate(&self, items: Vec<Value>) -> Vec<Value> {
+        items
+            .into_iter()
+            .skip(self.offset)
+            .take(self.limit.min(Self::MAX_LIMIT))
+            .map(|item| self.project(item))
+            .collect()
+    }
+
+    /// Keep only the requested fields of the given item.
+    pub fn project(&self, item: Value) -> Value {
+        match self.fields.as_deref() {
+            Some(fields) => project(fields, item),
+            None => item,
+        }
+    }
+}
+
+/// The options to list the resources page by page.
+///
+/// The pages are fetched from the Kubernetes API server with the `limit` and `continue` tokens,
+/// so that the whole resources are not loaded at once.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceListOptions {
+    /// The maximum number of the resources in a page.
+    #[serde(default)]
+    pub limit: Option<u32>,
+    /// The token of the next page, which is returned with the previous page.
+    #[serde(default, rename = "continue")]
+    pub continue_token: Option<String>,
+    /// The Kubernetes label selector, e.g. `app=foo,tier!=bar`.
+    #[serde(default)]
+    pub label_selector: Option<String>,
+    /// The state of the resources, e.g. `Ready`.
+    ///
+    /// NOTE: the state is filtered per page, so a page may have fewer resources than the limit.
+    #[serde(default)]
+    pub state: Option<String>,
+    /// The comma-separated field paths to be returned, e.g. `metadata.name,status.state`.
+    /// If it is not set, all fields are returned.
+    #[serde(default)]
+    pub fields: Option<String>,
+}
+
+impl ResourceListOptions {
+    /// Return the page size, which is bounded by [`ListOptions::MAX_LIMIT`].
+    pub fn page_limit(&self) -> u32 {
+        self.limit
+            .unwrap_or(ListOptions::default_limit() as u32)
+            .clamp(1, ListOptions::MAX_LIMIT as u32)
+    }
+
+    /// Return `true` if the state of the resource matches the filter.
+    pub fn is_state_matched(&self, state: Option<impl ToString>) -> bool {
+        match self.state.as_deref() {
+            Some(filter) => state.is_some_and(|state| state.to_string() == filter),
+            None => true,
+        }
+    }
+
+    /// Keep only the requested fields of the given item.
+    pub fn project(&self, item: Value) -> Value {
+        match self.fields.as_deref() {
+            Some(fields) => project(fields, item),
+            None => item,
+        }
+    }
+}
+
+/// A page of the resources.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceList<T> {
+    pub items: Vec<T>,
+    /// The token of the next page; it is missing on the last page.
+    #[serde(default, rename = "continue", skip_serializing_if = "Option::is_none")]
+    pub continue_token: Option<String>,
+}
+
+/// Keep only the given comma-separated field paths of the item.
+fn project(fields: &str, item: Value) -> Value {
+    let mut projected = Value::Object(Map::default());
+    for path in fields
+        .split(',')
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+    {
+        let keys: Vec<_> = path.split('.').collect();
+        let value = keys.iter().try_fold(&item, |value, key| value.get(key));
+        if let Some(value) = value {
+            let (last, parents) = keys.split_last().unwrap();
+            let target = parents.iter().try_fold(&mut projected, |target, key| {
+                target.as_object_mut().map(|target| {
+                    target
+                        .entry(key.to_string())
+                        .or_insert_with(|| Value::Object(Map::default()))
+                })
+            });
+            if let Some(target) = target.and_then(Value::as_object_mut) {
+                target.insert(last.to_string(), value.clone());
+            }
+        }
+    }
+    projected
+}
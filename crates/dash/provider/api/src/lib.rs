@@ -1,5 +1,7 @@
+pub mod codegen;
 pub mod data;
 pub mod job;
+pub mod wasm;
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -15,6 +17,7 @@ pub struct TaskChannel {
 #[serde(tag = "kind", content = "spec")]
 pub enum TaskChannelKind {
     Job(self::job::TaskChannelKindJob),
+    Wasm(self::wasm::TaskChannelKindWasm),
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -222,7 +222,7 @@ impl<'a> DashProviderClient<'a> {
                                 )
                             })
                     }
-                    None => {
+                    Some(TaskChannelKind::Wasm(_)) | None => {
                         bail!("only the K8S job can be watched: {task_name:?} => {job_name:?}")
                     }
                 }
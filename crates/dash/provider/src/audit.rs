@@ -0,0 +1,184 @@
+use anyhow::{anyhow, Result};
+use dash_api::audit::{AuditQuery, AuditRecord};
+use sea_orm::{
+    prelude::StringLen, ActiveModelBehavior, ActiveModelTrait, ActiveValue, ColumnTrait,
+    ConnectionTrait, Database, DatabaseConnection, DbErr, DeriveEntityModel, DerivePrimaryKey,
+    DeriveRelation, EntityTrait, EnumIter, PrimaryKeyTrait, QueryFilter, QueryOrder, QuerySelect,
+    Schema,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tracing::{instrument, Level};
+
+/// An append-only store of the controller mutations, which is kept in the metadata storage.
+#[derive(Clone)]
+pub struct AuditClient {
+    db: DatabaseConnection,
+}
+
+impl AuditClient {
+    const ENV_DATABASE_URL: &'static str = "DASH_AUDIT_DATABASE_URL";
+    const NATIVE_URL: &'static str = "postgres://dash-postgres/dash";
+
+    #[instrument(level = Level::INFO, err(Display))]
+    pub async fn try_default() -> Result<Self> {
+        let url =
+            ::std::env::var(Self::ENV_DATABASE_URL).unwrap_or_else(|_| Self::NATIVE_URL.into());
+        let db = Database::connect(url).await?;
+
+        Entity::init(&db)
+            .await
+            .map(|()| Self { db })
+            .map_err(Into::into)
+    }
+
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn record(&self, record: AuditRecord) -> Result<()> {
+        let AuditRecord {
+            timestamp,
+            actor,
+            operation,
+            kind,
+            namespace,
+            name,
+            old_hash,
+            new_hash,
+        } = record;
+
+        ActiveModel {
+            id: ActiveValue::NotSet,
+            timestamp: ActiveValue::Set(timestamp.naive_utc()),
+            actor: ActiveValue::Set(actor),
+            operation: ActiveValue::Set(operation.to_string()),
+            kind: ActiveValue::Set(kind),
+            namespace: ActiveValue::Set(namespace),
+            name: ActiveValue::Set(name),
+            old_hash: ActiveValue::Set(old_hash),
+            new_hash: ActiveValue::Set(new_hash),
+        }
+        .insert(&self.db)
+        .await
+        .map(|_| ())
+        .map_err(|error| anyhow!("failed to record the audit log: {error}"))
+    }
+
+    /// List the latest records of the namespace, in the reverse order of time.
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn list(&self, namespace: &str, query: &AuditQuery) -> Result<Vec<AuditRecord>> {
+        let mut select = Entity::find().filter(Column::Namespace.eq(namespace));
+        if let Some(kind) = &query.kind {
+            select = select.filter(Column::Kind.eq(kind));
+        }
+        if let Some(name) = &query.name {
+            select = select.filter(Column::Name.eq(name));
+        }
+        if let Some(since) = query.since {
+            select = select.filter(Column::Timestamp.gte(since.naive_utc()));
+        }
+        if let Some(until) = query.until {
+            select = select.filter(Column::Timestamp.lt(until.naive_utc()));
+        }
+
+        select
+            .order_by_desc(Column::Id)
+            .limit(query.limit())
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect()
+    }
+}
+
+/// Hash the state of the resource, except its metadata which changes on every write.
+pub fn hash_state<K>(data: &K) -> Result<String>
+where
+    K: Serialize,
+{
+    let mut value = ::serde_json::to_value(data)?;
+    if let Value::Object(map) = &mut value {
+        map.remove("metadata");
+    }
+
+    let bytes = ::serde_json::to_vec(&value)?;
+    Ok(Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+#[derive(Clone, Debug, Default, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "__dash_audit_logs")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    #[sea_orm(column_type = "Timestamp", indexed)]
+    pub timestamp: ::chrono::NaiveDateTime,
+    #[sea_orm(column_type = "String(StringLen::N(64))")]
+    pub actor: String,
+    #[sea_orm(column_type = "String(StringLen::N(16))")]
+    pub operation: String,
+    #[sea_orm(column_type = "String(StringLen::N(64))")]
+    pub kind: String,
+    #[sea_orm(column_type = "String(StringLen::N(64))", nullable, indexed)]
+    pub namespace: Option<String>,
+    #[sea_orm(column_type = "String(StringLen::N(253))")]
+    pub name: String,
+    #[sea_orm(column_type = "String(StringLen::N(64))", nullable)]
+    pub old_hash: Option<String>,
+    #[sea_orm(column_type = "String(StringLen::N(64))", nullable)]
+    pub new_hash: Option<String>,
+}
+
+impl TryFrom<Model> for AuditRecord {
+    type Error = ::anyhow::Error;
+
+    fn try_from(value: Model) -> Result<Self, Self::Error> {
+        let Model {
+            id: _,
+            timestamp,
+            actor,
+            operation,
+            kind,
+            namespace,
+            name,
+            old_hash,
+            new_hash,
+        } = value;
+
+        Ok(Self {
+            timestamp: timestamp.and_utc(),
+            actor,
+            operation: operation
+                .parse()
+                .map_err(|error| anyhow!("invalid audit operation {operation:?}: {error}"))?,
+            kind,
+            namespace,
+            name,
+            old_hash,
+            new_hash,
+        })
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Entity {
+    #[instrument(level = Level::INFO, skip(db), err(Display))]
+    async fn init(db: &DatabaseConnection) -> Result<(), DbErr> {
+        // Check whether the audit table is already exist
+        if Self::find_by_id(0).one(db).await.is_ok() {
+            return Ok(());
+        }
+
+        // Create an audit table
+        let builder = db.get_database_backend();
+        let statement =
+            builder.build(&Schema::new(db.get_database_backend()).create_table_from_entity(Self));
+        db.execute(statement).await.map(|_| ())
+    }
+}
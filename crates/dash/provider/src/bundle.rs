@@ -0,0 +1,315 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use anyhow::{anyhow, bail, Result};
+use chrono::Utc;
+use dash_api::{
+    bundle::{
+        DashBundle, DashBundleConflictStrategy, DashBundleImportItem, DashBundleImportReport,
+        DashBundleImportResult, DashBundleItem,
+    },
+    function::FunctionCrd,
+    model::{ModelCrd, ModelFieldKindExtendedSpec, ModelFieldKindSpec, ModelFieldsSpec, ModelSpec},
+    model_storage_binding::{ModelStorageBindingCrd, ModelStorageBindingStorageKind},
+    storage::ModelStorageCrd,
+    task::TaskCrd,
+};
+use k8s_openapi::NamespaceResourceScope;
+use kube::{
+    api::{ListParams, Patch, PatchParams, PostParams},
+    core::ObjectMeta,
+    Api, Client, Resource, ResourceExt,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::json;
+use tracing::{info, instrument, Level};
+
+pub struct DashBundleClient<'namespace, 'kube> {
+    pub namespace: &'namespace str,
+    pub kube: &'kube Client,
+}
+
+impl<'namespace, 'kube> DashBundleClient<'namespace, 'kube> {
+    /// Export all the dash resources of the namespace.
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn export(&self) -> Result<DashBundle> {
+        Ok(DashBundle {
+            version: DashBundle::VERSION.into(),
+            namespace: self.namespace.into(),
+            exported_at: Utc::now(),
+            bindings: self
+                .list::<ModelStorageBindingCrd, _>(|item| item.spec)
+                .await?,
+            functions: self.list::<FunctionCrd, _>(|item| item.spec).await?,
+            models: self.list::<ModelCrd, _>(|item| item.spec).await?,
+            storages: self.list::<ModelStorageCrd, _>(|item| item.spec).await?,
+            tasks: self.list::<TaskCrd, _>(|item| item.spec).await?,
+        })
+    }
+
+    /// Import the bundle into the namespace.
+    ///
+    /// The resources are applied in the order of dependencies, and the references to
+    /// the renamed resources are rewritten to their new names.
+    #[instrument(level = Level::INFO, skip(self, bundle), fields(bundle.namespace = %bundle.namespace), err(Display))]
+    pub async fn import(
+        &self,
+        mut bundle: DashBundle,
+        strategy: DashBundleConflictStrategy,
+    ) -> Result<DashBundleImportReport> {
+        if bundle.version != DashBundle::VERSION {
+            bail!(
+                "unsupported bundle version: expected {expected}, but given {given}",
+                expected = DashBundle::VERSION,
+                given = &bundle.version,
+            )
+        }
+
+        // Plan the names of the resources before rewriting the references
+        let mut report = DashBundleImportReport::default();
+        let storages = self
+            .plan::<ModelStorageCrd, _>(&mut report, &bundle.storages, strategy)
+            .await?;
+        let models = self
+            .plan::<ModelCrd, _>(&mut report, &bundle.models, strategy)
+            .await?;
+        let bindings = self
+            .plan::<ModelStorageBindingCrd, _>(&mut report, &bundle.bindings, strategy)
+            .await?;
+        let functions = self
+            .plan::<FunctionCrd, _>(&mut report, &bundle.functions, strategy)
+            .await?;
+        let tasks = self
+            .plan::<TaskCrd, _>(&mut report, &bundle.tasks, strategy)
+            .await?;
+
+        // Rewrite the references
+        for item in &mut bundle.models {
+            if let ModelSpec::Fields(fields) = &mut item.spec {
+                rewrite_fields(fields, &models.renamed);
+            }
+        }
+        for item in &mut bundle.bindings {
+            let spec = &mut item.spec;
+            rewrite(&mut spec.model, &models.renamed);
+            match &mut spec.storage {
+                ModelStorageBindingStorageKind::Cloned(storage) => {
+                    rewrite(&mut storage.source, &storages.renamed);
+                    rewrite(&mut storage.target, &storages.renamed);
+                    if let Some(name) = &mut storage.source_binding_name {
+                        rewrite(name, &bindings.renamed);
+                    }
+                }
+                ModelStorageBindingStorageKind::Owned(storage) => {
+                    rewrite(&mut storage.target, &storages.renamed);
+                }
+            }
+        }
+        for item in &mut bundle.functions {
+            for name in [&mut item.spec.input, &mut item.spec.output] {
+                if let Some(renamed) = models.renamed.get(name.as_str()) {
+                    *name = renamed
+                        .parse()
+                        .map_err(|error| anyhow!("invalid model name ({renamed}): {error}"))?;
+                }
+            }
+        }
+        for item in &mut bundle.tasks {
+            rewrite_fields(&mut item.spec.input, &models.renamed);
+            for dependency in &mut item.spec.dependencies {
+                rewrite(&mut dependency.name, &tasks.renamed);
+            }
+        }
+
+        // Apply the resources
+        self.apply::<ModelStorageCrd, _>(bundle.storages, &storages)
+            .await?;
+        self.apply::<ModelCrd, _>(bundle.models, &models).await?;
+        self.apply::<ModelStorageBindingCrd, _>(bundle.bindings, &bindings)
+            .await?;
+        self.apply::<FunctionCrd, _>(bundle.functions, &functions)
+            .await?;
+        self.apply::<TaskCrd, _>(bundle.tasks, &tasks).await?;
+        Ok(report)
+    }
+
+    async fn list<K, Spec>(&self, f: impl Fn(K) -> Spec) -> Result<Vec<DashBundleItem<Spec>>>
+    where
+        K: Clone
+            + fmt::Debug
+            + DeserializeOwned
+            + Resource<DynamicType = (), Scope = NamespaceResourceScope>,
+    {
+        let api = Api::<K>::namespaced(self.kube.clone(), self.namespace);
+        let lp = ListParams::default();
+        let mut items: Vec<_> = api
+            .list(&lp)
+            .await
+            .map_err(|error| anyhow!("failed to list {}: {error}", K::kind(&())))?
+            .items
+            .into_iter()
+            .map(|item| DashBundleItem {
+                name: item.name_any(),
+                labels: item.labels().clone(),
+                spec: f(item),
+            })
+            .collect();
+        items.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(items)
+    }
+
+    async fn plan<K, Spec>(
+        &self,
+        report: &mut DashBundleImportReport,
+        items: &[DashBundleItem<Spec>],
+        strategy: DashBundleConflictStrategy,
+    ) -> Result<DashBundleImportPlan>
+    where
+        K: Clone
+            + fmt::Debug
+            + DeserializeOwned
+            + Resource<DynamicType = (), Scope = NamespaceResourceScope>,
+    {
+        let kind = K::kind(&()).to_string();
+        let api = Api::<K>::namespaced(self.kube.clone(), self.namespace);
+        let lp = ListParams::default();
+        let existing: BTreeSet<_> = api
+            .list(&lp)
+            .await
+            .map_err(|error| anyhow!("failed to list {kind}: {error}"))?
+            .items
+            .into_iter()
+            .map(|item| item.name_any())
+            .collect();
+        let bundled: BTreeSet<_> = items.iter().map(|item| item.name.as_str()).collect();
+
+        let mut plan = DashBundleImportPlan::default();
+        for item in items {
+            let source_name = item.name.clone();
+            let (name, result) = if !existing.contains(&source_name) {
+                (source_name.clone(), DashBundleImportResult::Created)
+            } else {
+                match strategy {
+                    DashBundleConflictStrategy::Skip => {
+                        (source_name.clone(), DashBundleImportResult::Skipped)
+                    }
+                    DashBundleConflictStrategy::Overwrite => {
+                        (source_name.clone(), DashBundleImportResult::Overwritten)
+                    }
+                    DashBundleConflictStrategy::Rename => {
+                        let name = (1..)
+                            .map(|index| format!("{source_name}-{index}"))
+                            .find(|name| {
+                                !existing.contains(name)
+                                    && !bundled.contains(name.as_str())
+                                    && !plan.renamed.values().any(|renamed| renamed == name)
+                            })
+                            .unwrap();
+                        plan.renamed.insert(source_name.clone(), name.clone());
+                        (name, DashBundleImportResult::Renamed)
+                    }
+                }
+            };
+
+            plan.results.insert(source_name.clone(), result);
+            report.items.push(DashBundleImportItem {
+                kind: kind.clone(),
+                source_name,
+                name,
+                result,
+            });
+        }
+        Ok(plan)
+    }
+
+    async fn apply<K, Spec>(
+        &self,
+        items: Vec<DashBundleItem<Spec>>,
+        plan: &DashBundleImportPlan,
+    ) -> Result<()>
+    where
+        K: Clone
+            + fmt::Debug
+            + DeserializeOwned
+            + Serialize
+            + Resource<DynamicType = (), Scope = NamespaceResourceScope>,
+        Spec: Serialize,
+    {
+        let api = Api::<K>::namespaced(self.kube.clone(), self.namespace);
+        for DashBundleItem { name, labels, spec } in items {
+            let result = plan
+                .results
+                .get(&name)
+                .copied()
+                .unwrap_or(DashBundleImportResult::Skipped);
+            let name = plan.renamed.get(&name).cloned().unwrap_or(name);
+
+            let object = json!({
+                "apiVersion": K::api_version(&()),
+                "kind": K::kind(&()),
+                "metadata": ObjectMeta {
+                    name: Some(name.clone()),
+                    namespace: Some(self.namespace.into()),
+                    labels: Some(labels),
+                    ..Default::default()
+                },
+                "spec": spec,
+            });
+
+            match result {
+                DashBundleImportResult::Created | DashBundleImportResult::Renamed => {
+                    let pp = PostParams {
+                        field_manager: Some(crate::NAME.into()),
+                        ..Default::default()
+                    };
+                    let object: K = ::serde_json::from_value(object)?;
+                    api.create(&pp, &object).await.map(|_| ())
+                }
+                DashBundleImportResult::Overwritten => {
+                    let pp = PatchParams {
+                        field_manager: Some(crate::NAME.into()),
+                        force: true,
+                        ..Default::default()
+                    };
+                    api.patch(&name, &pp, &Patch::Apply(object))
+                        .await
+                        .map(|_| ())
+                }
+                DashBundleImportResult::Skipped => continue,
+            }
+            .map_err(|error| {
+                anyhow!(
+                    "failed to import {kind} ({name}): {error}",
+                    kind = K::kind(&()),
+                )
+            })?;
+            info!("imported {kind}: {name}", kind = K::kind(&()));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct DashBundleImportPlan {
+    /// The new names of the renamed resources, indexed by the names in the bundle
+    renamed: BTreeMap<String, String>,
+    results: BTreeMap<String, DashBundleImportResult>,
+}
+
+fn rewrite(name: &mut String, renamed: &BTreeMap<String, String>) {
+    if let Some(renamed) = renamed.get(name) {
+        *name = renamed.clone();
+    }
+}
+
+fn rewrite_fields(fields: &mut ModelFieldsSpec, renamed: &BTreeMap<String, String>) {
+    for field in fields {
+        if let ModelFieldKindSpec::Extended(ModelFieldKindExtendedSpec::Model { name }) =
+            &mut field.kind
+        {
+            rewrite(name, renamed);
+        }
+    }
+}
@@ -1,4 +1,6 @@
 pub mod job;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
@@ -206,6 +208,8 @@ impl<'a> TaskSession<'a> {
 
 pub enum TaskActorClient {
     Job(Box<self::job::TaskActorJobClient>),
+    #[cfg(feature = "wasm")]
+    Wasm(Box<self::wasm::TaskActorWasmClient>),
 }
 
 impl TaskActorClient {
@@ -219,12 +223,23 @@ impl TaskActorClient {
                     .map(Box::new)
                     .map(Self::Job)
             }
+            #[cfg(feature = "wasm")]
+            TaskActorSpec::Wasm(spec) => {
+                self::wasm::TaskActorWasmClient::try_new(namespace.into(), kube, spec)
+                    .await
+                    .map(Box::new)
+                    .map(Self::Wasm)
+            }
+            #[cfg(not(feature = "wasm"))]
+            TaskActorSpec::Wasm(_) => Err(anyhow!("wasm actor is not enabled on this provider")),
         }
     }
 
     pub const fn kube(&self) -> &Client {
         match self {
             Self::Job(client) => client.kube(),
+            #[cfg(feature = "wasm")]
+            Self::Wasm(client) => client.kube(),
         }
     }
 
@@ -235,6 +250,8 @@ impl TaskActorClient {
     {
         match self {
             Self::Job(client) => client.exists(input).await,
+            #[cfg(feature = "wasm")]
+            Self::Wasm(client) => client.exists(input).await,
         }
     }
 
@@ -247,6 +264,8 @@ impl TaskActorClient {
             metadata: input.metadata.clone(),
             actor: match self {
                 Self::Job(client) => client.create(input).await.map(TaskChannelKind::Job)?,
+                #[cfg(feature = "wasm")]
+                Self::Wasm(client) => client.create(input).await.map(TaskChannelKind::Wasm)?,
             },
         })
     }
@@ -260,6 +279,8 @@ impl TaskActorClient {
             metadata: input.metadata.clone(),
             actor: match self {
                 Self::Job(client) => client.delete(input).await.map(TaskChannelKind::Job)?,
+                #[cfg(feature = "wasm")]
+                Self::Wasm(client) => client.delete(input).await.map(TaskChannelKind::Wasm)?,
             },
         })
     }
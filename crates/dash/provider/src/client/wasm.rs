@@ -0,0 +1,304 @@
+use std::{
+    collections::BTreeMap,
+    sync::{LazyLock, Mutex},
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail, Result};
+use byte_unit::Byte;
+use chrono::Utc;
+use dash_api::task::{TaskActorSourceSpec, TaskActorWasmLimitsSpec, TaskActorWasmSpec};
+use dash_provider_api::wasm::TaskChannelKindWasm;
+use kube::Client;
+use serde::Serialize;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tokio::{task::JoinHandle, time::timeout};
+use tracing::{info, instrument, Level};
+use uuid::Uuid;
+use wasmtime::{Caller, Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+use crate::storage::KubernetesStorageClient;
+
+use super::SessionContext;
+
+/// The name of the host module, which provides the message IO bindings.
+const HOST_MODULE: &str = "dash";
+
+/// The name of the exported entrypoint, which returns `0` on success.
+const ENTRYPOINT: &str = "run";
+
+const DEFAULT_FUEL: u64 = 1_000_000_000;
+const DEFAULT_MEMORY: usize = 64 * 1024 * 1024; // 64 MiB
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30); // 30 seconds
+
+static ENGINE: LazyLock<Engine> = LazyLock::new(|| {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    Engine::new(&config).expect("failed to init wasm engine")
+});
+
+/// The compiled modules, by the digests of their binaries.
+static MODULES: LazyLock<Mutex<BTreeMap<String, Module>>> = LazyLock::new(Default::default);
+
+/// The running (or finished, but not collected yet) sessions, by `{namespace}/{name}`.
+static SESSIONS: LazyLock<Mutex<BTreeMap<String, JoinHandle<Result<Value>>>>> =
+    LazyLock::new(Default::default);
+
+pub struct TaskActorWasmClient {
+    pub kube: Client,
+    limits: Limits,
+    module: Module,
+}
+
+impl TaskActorWasmClient {
+    #[instrument(level = Level::INFO, skip(kube, spec), err(Display))]
+    pub async fn try_new(
+        namespace: String,
+        kube: &Client,
+        spec: &TaskActorWasmSpec,
+    ) -> Result<Self> {
+        let client = KubernetesStorageClient {
+            namespace: &namespace,
+            kube,
+        };
+        let (name, content) = match &spec.source {
+            TaskActorSourceSpec::ConfigMapRef(spec) => client.load_config_map_binary(spec).await?,
+        };
+
+        Ok(Self {
+            kube: kube.clone(),
+            limits: Limits::try_from(&spec.limits)?,
+            module: load_module(name, &content)?,
+        })
+    }
+}
+
+impl TaskActorWasmClient {
+    pub const fn kube(&self) -> &Client {
+        &self.kube
+    }
+
+    #[instrument(level = Level::INFO, skip(self, input), fields(metadata.name = %input.metadata.name, metadata.namespace = %input.metadata.namespace), err(Display))]
+    pub async fn exists<Spec>(&self, input: &SessionContext<Spec>) -> Result<bool>
+    where
+        Spec: Serialize,
+    {
+        let key = session_key(input);
+        let sessions = SESSIONS.lock().map_err(|error| anyhow!("{error}"))?;
+        Ok(sessions
+            .get(&key)
+            .map(|session| !session.is_finished())
+            .unwrap_or_default())
+    }
+
+    #[instrument(level = Level::INFO, skip(self, input), fields(metadata.name = %input.metadata.name, metadata.namespace = %input.metadata.namespace), err(Display))]
+    pub async fn create<Spec>(&self, input: &SessionContext<Spec>) -> Result<TaskChannelKindWasm>
+    where
+        Spec: Serialize,
+    {
+        let key = session_key(input);
+        let message = to_message(&input.spec)?;
+
+        let mut sessions = SESSIONS.lock().map_err(|error| anyhow!("{error}"))?;
+        if sessions.contains_key(&key) {
+            return Ok(TaskChannelKindWasm::default());
+        }
+
+        let limits = self.limits;
+        let module = self.module.clone();
+        let session = ::tokio::spawn(async move {
+            let task = ::tokio::task::spawn_blocking(move || execute(&module, limits, message));
+            match timeout(limits.timeout, task).await {
+                Ok(Ok(result)) => result,
+                Ok(Err(error)) => bail!("failed to join wasm function: {error}"),
+                Err(_) => bail!("wasm function timed out: {:?}", limits.timeout),
+            }
+        });
+        sessions.insert(key, session);
+        Ok(TaskChannelKindWasm::default())
+    }
+
+    #[instrument(level = Level::INFO, skip(self, input), fields(metadata.name = %input.metadata.name, metadata.namespace = %input.metadata.namespace), err(Display))]
+    pub async fn delete<Spec>(&self, input: &SessionContext<Spec>) -> Result<TaskChannelKindWasm>
+    where
+        Spec: Serialize,
+    {
+        let key = session_key(input);
+        let session = SESSIONS
+            .lock()
+            .map_err(|error| anyhow!("{error}"))?
+            .remove(&key);
+
+        let result = match session {
+            Some(session) if session.is_finished() => session
+                .await
+                .map_err(|error| anyhow!("failed to join wasm function: {error}"))
+                .and_then(|result| result),
+            Some(session) => {
+                session.abort();
+                Err(anyhow!("wasm function is cancelled"))
+            }
+            // NOTE: the result is lost if the provider has been restarted
+            None => return Ok(TaskChannelKindWasm::default()),
+        };
+
+        Ok(match result {
+            Ok(output) => TaskChannelKindWasm {
+                error: None,
+                output: Some(output),
+            },
+            Err(error) => TaskChannelKindWasm {
+                error: Some(error.to_string()),
+                output: None,
+            },
+        })
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Limits {
+    fuel: u64,
+    memory: usize,
+    timeout: Duration,
+}
+
+impl TryFrom<&TaskActorWasmLimitsSpec> for Limits {
+    type Error = ::anyhow::Error;
+
+    fn try_from(spec: &TaskActorWasmLimitsSpec) -> Result<Self, Self::Error> {
+        let TaskActorWasmLimitsSpec {
+            fuel,
+            memory,
+            timeout_seconds,
+        } = spec;
+
+        Ok(Self {
+            fuel: fuel.unwrap_or(DEFAULT_FUEL),
+            memory: match memory {
+                Some(memory) => match memory.0.parse::<Byte>() {
+                    Ok(memory) => memory.as_u64().try_into()?,
+                    Err(error) => bail!("failed to parse wasm memory limit: {error}"),
+                },
+                None => DEFAULT_MEMORY,
+            },
+            timeout: timeout_seconds
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_TIMEOUT),
+        })
+    }
+}
+
+struct State {
+    input: Vec<u8>,
+    limits: StoreLimits,
+    output: Option<Vec<u8>>,
+}
+
+#[instrument(level = Level::INFO, skip(content), err(Display))]
+fn load_module(name: &str, content: &[u8]) -> Result<Module> {
+    let digest = format!("{:x}", Sha256::digest(content));
+
+    let mut modules = MODULES.lock().map_err(|error| anyhow!("{error}"))?;
+    if let Some(module) = modules.get(&digest) {
+        return Ok(module.clone());
+    }
+
+    let module = Module::new(&ENGINE, content)
+        .map_err(|error| anyhow!("failed to compile wasm module {name:?}: {error}"))?;
+    info!("compiled wasm module: {name} ({digest})");
+    modules.insert(digest, module.clone());
+    Ok(module)
+}
+
+fn session_key<Spec>(input: &SessionContext<Spec>) -> String {
+    format!(
+        "{namespace}/{name}",
+        namespace = &input.metadata.namespace,
+        name = &input.metadata.name,
+    )
+}
+
+/// Wrap the input value in the layout of `PipeMessage`.
+fn to_message<Spec>(spec: &Spec) -> Result<Vec<u8>>
+where
+    Spec: Serialize,
+{
+    let mut message = json!({
+        "__id": Uuid::new_v4(),
+        "__payloads": [],
+        "__timestamp": Utc::now(),
+    });
+    match ::serde_json::to_value(spec)? {
+        Value::Object(value) => message.as_object_mut().unwrap().extend(value),
+        Value::Null => (),
+        _ => bail!("the input of wasm function should be an object"),
+    }
+    ::serde_json::to_vec(&message).map_err(Into::into)
+}
+
+fn execute(module: &Module, limits: Limits, input: Vec<u8>) -> Result<Value> {
+    let state = State {
+        input,
+        limits: StoreLimitsBuilder::new().memory_size(limits.memory).build(),
+        output: None,
+    };
+    let mut store = Store::new(&ENGINE, state);
+    store.limiter(|state| &mut state.limits);
+    store.set_fuel(limits.fuel)?;
+
+    let mut linker = Linker::new(&ENGINE);
+    linker.func_wrap(HOST_MODULE, "input_len", |caller: Caller<'_, State>| {
+        caller.data().input.len() as u32
+    })?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "input_read",
+        |mut caller: Caller<'_, State>, ptr: u32| -> Result<()> {
+            let memory = load_memory(&mut caller)?;
+            let (data, state) = memory.data_and_store_mut(&mut caller);
+            let ptr = ptr as usize;
+            match data.get_mut(ptr..ptr + state.input.len()) {
+                Some(buf) => {
+                    buf.copy_from_slice(&state.input);
+                    Ok(())
+                }
+                None => bail!("input buffer is out of bounds"),
+            }
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "output_write",
+        |mut caller: Caller<'_, State>, ptr: u32, len: u32| -> Result<()> {
+            let memory = load_memory(&mut caller)?;
+            let (data, state) = memory.data_and_store_mut(&mut caller);
+            let (ptr, len) = (ptr as usize, len as usize);
+            match data.get(ptr..ptr + len) {
+                Some(buf) => {
+                    state.output = Some(buf.to_vec());
+                    Ok(())
+                }
+                None => bail!("output buffer is out of bounds"),
+            }
+        },
+    )?;
+
+    let instance = linker.instantiate(&mut store, module)?;
+    let entrypoint = instance.get_typed_func::<(), i32>(&mut store, ENTRYPOINT)?;
+    match entrypoint.call(&mut store, ())? {
+        0 => match store.into_data().output {
+            Some(output) => ::serde_json::from_slice(&output)
+                .map_err(|error| anyhow!("failed to parse the output of wasm function: {error}")),
+            None => Ok(Value::Null),
+        },
+        code => bail!("wasm function exited with code {code}"),
+    }
+}
+
+fn load_memory(caller: &mut Caller<'_, State>) -> Result<::wasmtime::Memory> {
+    caller
+        .get_export("memory")
+        .and_then(|export| export.into_memory())
+        .ok_or_else(|| anyhow!("wasm module should export its memory"))
+}
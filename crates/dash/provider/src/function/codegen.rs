@@ -0,0 +1,431 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+};
+
+use anyhow::{anyhow, bail, Result};
+use dash_api::{
+    function::FunctionCrd,
+    model::{ModelFieldKindNativeSpec, ModelFieldKindObjectSpec, ModelFieldNativeSpec},
+};
+use dash_provider_api::codegen::{CodegenLanguage, CodegenOptions, CodegenTransport};
+use inflector::Inflector;
+use kube::ResourceExt;
+
+/// Generates the typed client of a function from its input and output model fields,
+/// so that the consumers do not need to hand-write the request and response types.
+pub struct FunctionCodegen<'a> {
+    function: &'a FunctionCrd,
+    options: CodegenOptions,
+}
+
+impl<'a> FunctionCodegen<'a> {
+    pub const fn new(function: &'a FunctionCrd, options: CodegenOptions) -> Self {
+        Self { function, options }
+    }
+
+    pub fn generate(&self) -> Result<String> {
+        let name = self.function.name_any();
+        let spec = self
+            .function
+            .status
+            .as_ref()
+            .and_then(|status| status.spec.as_ref())
+            .ok_or_else(|| anyhow!("function is not ready: {name:?}"))?;
+
+        let mut types = Vec::default();
+        TypeBuilder::new(&spec.input).build(&mut types, "Input")?;
+        TypeBuilder::new(&spec.output).build(&mut types, "Output")?;
+
+        let module = Module {
+            namespace: self.function.namespace().unwrap_or_default(),
+            name,
+            generation: self.function.metadata.generation.unwrap_or_default(),
+            types,
+        };
+        let code = match (self.options.language, self.options.transport) {
+            (CodegenLanguage::Rust, transport) => module.to_rust(transport),
+            (CodegenLanguage::TypeScript, CodegenTransport::Http) => module.to_typescript(),
+            (CodegenLanguage::TypeScript, CodegenTransport::Pipe) => {
+                bail!("dash-pipe transport is not supported on TypeScript")
+            }
+        };
+        code.map_err(|error| anyhow!("failed to generate the client: {error}"))
+    }
+}
+
+struct Module {
+    namespace: String,
+    name: String,
+    generation: i64,
+    types: Vec<TypeDef>,
+}
+
+impl Module {
+    fn to_rust(&self, transport: CodegenTransport) -> Result<String, ::std::fmt::Error> {
+        let Self {
+            namespace,
+            name,
+            generation,
+            types,
+        } = self;
+
+        let mut s = String::default();
+        writeln!(
+            s,
+            "//! A typed client of the dash function `{namespace}/{name}`."
+        )?;
+        writeln!(s, "//!")?;
+        writeln!(
+            s,
+            "//! NOTE: generated from the generation {generation} of the function; do not edit by hand."
+        )?;
+        writeln!(s)?;
+        writeln!(s, "use serde::{{Deserialize, Serialize}};")?;
+        writeln!(s)?;
+        writeln!(s, "pub const NAME: &str = {name:?};")?;
+        writeln!(s, "pub const NAMESPACE: &str = {namespace:?};")?;
+
+        for type_ in types {
+            writeln!(s)?;
+            match &type_.kind {
+                TypeDefKind::Enum(choices) => {
+                    writeln!(
+                        s,
+                        "#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]"
+                    )?;
+                    writeln!(s, "pub enum {} {{", type_.name)?;
+                    for choice in choices {
+                        writeln!(s, "    #[serde(rename = {choice:?})]")?;
+                        writeln!(s, "    {},", to_rust_variant(choice))?;
+                    }
+                    writeln!(s, "}}")?;
+                }
+                TypeDefKind::Struct(fields) => {
+                    writeln!(
+                        s,
+                        "#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]"
+                    )?;
+                    writeln!(s, "pub struct {} {{", type_.name)?;
+                    for field in fields {
+                        let ident = to_rust_ident(&field.name);
+                        let mut field_type = field.type_.to_rust();
+                        if field.optional {
+                            field_type = format!("Option<{field_type}>");
+                            writeln!(
+                                s,
+                                "    #[serde(default, skip_serializing_if = \"Option::is_none\")]"
+                            )?;
+                        }
+                        if ident != field.name {
+                            writeln!(s, "    #[serde(rename = {:?})]", &field.name)?;
+                        }
+                        writeln!(s, "    pub {ident}: {field_type},")?;
+                    }
+                    writeln!(s, "}}")?;
+                }
+            }
+        }
+
+        writeln!(s)?;
+        match transport {
+            CodegenTransport::Http => {
+                writeln!(s, "/// Invoke the function through the dash gateway.")?;
+                writeln!(s, "pub async fn call(")?;
+                writeln!(s, "    client: &::dash_client::DashClient,")?;
+                writeln!(s, "    input: &Input,")?;
+                writeln!(s, ") -> ::anyhow::Result<Output> {{")?;
+                writeln!(s, "    client.invoke_function(NAME, input).await")?;
+                writeln!(s, "}}")?;
+            }
+            CodegenTransport::Pipe => {
+                writeln!(s, "/// Request the function over dash-pipe.")?;
+                writeln!(s, "pub async fn call(")?;
+                writeln!(s, "    client: &::dash_pipe_provider::PipeClient,")?;
+                writeln!(s, "    input: &Input,")?;
+                writeln!(s, ") -> ::anyhow::Result<Output> {{")?;
+                writeln!(s, "    let topic = NAME.parse()?;")?;
+                writeln!(s, "    let data = ::serde_json::to_value(input)?;")?;
+                writeln!(
+                    s,
+                    "    let data = ::dash_pipe_provider::PipeMessage::new(data);"
+                )?;
+                writeln!(
+                    s,
+                    "    let output = client.call::<Output>(topic, data).await?;"
+                )?;
+                writeln!(s, "    Ok(output.value)")?;
+                writeln!(s, "}}")?;
+            }
+        }
+        Ok(s)
+    }
+
+    fn to_typescript(&self) -> Result<String, ::std::fmt::Error> {
+        let Self {
+            namespace,
+            name,
+            generation,
+            types,
+        } = self;
+
+        let mut s = String::default();
+        writeln!(
+            s,
+            "// A typed client of the dash function `{namespace}/{name}`."
+        )?;
+        writeln!(s, "//")?;
+        writeln!(
+            s,
+            "// NOTE: generated from the generation {generation} of the function; do not edit by hand."
+        )?;
+        writeln!(s)?;
+        writeln!(s, "export const NAME = {name:?};")?;
+        writeln!(s, "export const NAMESPACE = {namespace:?};")?;
+
+        for type_ in types {
+            writeln!(s)?;
+            match &type_.kind {
+                TypeDefKind::Enum(choices) => {
+                    let choices = choices
+                        .iter()
+                        .map(|choice| format!("{choice:?}"))
+                        .collect::<Vec<_>>()
+                        .join(" | ");
+                    writeln!(s, "export type {} = {choices};", type_.name)?;
+                }
+                TypeDefKind::Struct(fields) => {
+                    writeln!(s, "export interface {} {{", type_.name)?;
+                    for field in fields {
+                        let key = if field.name.contains('-') {
+                            format!("{:?}", &field.name)
+                        } else {
+                            field.name.clone()
+                        };
+                        let optional = if field.optional { "?" } else { "" };
+                        writeln!(s, "  {key}{optional}: {};", field.type_.to_typescript())?;
+                    }
+                    writeln!(s, "}}")?;
+                }
+            }
+        }
+
+        writeln!(s)?;
+        writeln!(s, "/** Invoke the function through the dash gateway. */")?;
+        writeln!(
+            s,
+            "export async function call(host: string, input: Input): Promise<Output> {{"
+        )?;
+        writeln!(
+            s,
+            "  const response = await fetch(`${{host}}/function/${{NAME}}/invoke`, {{"
+        )?;
+        writeln!(s, "    method: \"POST\",")?;
+        writeln!(s, "    headers: {{")?;
+        writeln!(s, "      \"Content-Type\": \"application/json\",")?;
+        writeln!(s, "      \"X-ARK-NAMESPACE\": NAMESPACE,")?;
+        writeln!(s, "    }},")?;
+        writeln!(s, "    body: JSON.stringify(input),")?;
+        writeln!(s, "  }});")?;
+        writeln!(s, "  const result = await response.json();")?;
+        writeln!(s, "  if (result.result === \"ok\") {{")?;
+        writeln!(s, "    return result.spec as Output;")?;
+        writeln!(s, "  }}")?;
+        writeln!(s, "  throw new Error(result.spec);")?;
+        writeln!(s, "}}")?;
+        Ok(s)
+    }
+}
+
+struct TypeBuilder<'a> {
+    fields: BTreeMap<&'a str, &'a ModelFieldNativeSpec>,
+}
+
+impl<'a> TypeBuilder<'a> {
+    fn new(fields: &'a [ModelFieldNativeSpec]) -> Self {
+        Self {
+            fields: fields
+                .iter()
+                .map(|field| (field.name.as_str(), field))
+                .collect(),
+        }
+    }
+
+    fn build(&self, types: &mut Vec<TypeDef>, name: &str) -> Result<()> {
+        let children: Vec<_> = self
+            .fields
+            .keys()
+            .copied()
+            .filter(|child| get_parent_name(child) == Some("/"))
+            .collect();
+        self.build_struct(types, name, children)
+    }
+
+    fn build_struct<I>(&self, types: &mut Vec<TypeDef>, name: &str, children: I) -> Result<()>
+    where
+        I: IntoIterator,
+        <I as IntoIterator>::Item: AsRef<str>,
+    {
+        let mut fields = Vec::default();
+        for child in children {
+            let child = child.as_ref();
+            let field = self
+                .fields
+                .get(child)
+                .ok_or_else(|| anyhow!("no such field: {child:?}"))?;
+            let field_name =
+                get_field_name(child).ok_or_else(|| anyhow!("invalid field name: {child:?}"))?;
+            let type_name = format!("{name}{}", field_name.to_pascal_case());
+
+            let type_ = match &field.kind {
+                // BEGIN primitive types
+                ModelFieldKindNativeSpec::None {} => TypeRef::Any,
+                ModelFieldKindNativeSpec::Boolean { .. } => TypeRef::Boolean,
+                ModelFieldKindNativeSpec::Integer { .. } => TypeRef::Integer,
+                ModelFieldKindNativeSpec::Number { .. } => TypeRef::Number,
+                ModelFieldKindNativeSpec::String { .. } => TypeRef::String,
+                ModelFieldKindNativeSpec::OneOfStrings { choices, .. } => {
+                    let variants: BTreeSet<_> = choices.iter().map(to_rust_variant).collect();
+                    if variants.len() != choices.len() {
+                        bail!("ambiguous choices of the field: {child:?}");
+                    }
+                    types.push(TypeDef {
+                        name: type_name.clone(),
+                        kind: TypeDefKind::Enum(choices.clone()),
+                    });
+                    TypeRef::Named(type_name)
+                }
+                // BEGIN string formats
+                ModelFieldKindNativeSpec::DateTime { .. } => TypeRef::DateTime,
+                ModelFieldKindNativeSpec::Ip {} => TypeRef::String,
+                ModelFieldKindNativeSpec::Uuid {} => TypeRef::String,
+                // BEGIN aggregation types
+                ModelFieldKindNativeSpec::StringArray {} => TypeRef::StringArray,
+                ModelFieldKindNativeSpec::Object {
+                    kind: ModelFieldKindObjectSpec::Dynamic {},
+                    ..
+                } => TypeRef::Map,
+                ModelFieldKindNativeSpec::Object { children, .. } => {
+                    self.build_struct(types, &type_name, children)?;
+                    TypeRef::Named(type_name)
+                }
+                ModelFieldKindNativeSpec::ObjectArray { children } => {
+                    self.build_struct(types, &type_name, children)?;
+                    TypeRef::Array(type_name)
+                }
+            };
+
+            fields.push(FieldDef {
+                name: field_name.into(),
+                type_,
+                optional: field.attribute.optional,
+            });
+        }
+
+        types.push(TypeDef {
+            name: name.into(),
+            kind: TypeDefKind::Struct(fields),
+        });
+        Ok(())
+    }
+}
+
+struct TypeDef {
+    name: String,
+    kind: TypeDefKind,
+}
+
+enum TypeDefKind {
+    Enum(Vec<String>),
+    Struct(Vec<FieldDef>),
+}
+
+struct FieldDef {
+    name: String,
+    type_: TypeRef,
+    optional: bool,
+}
+
+enum TypeRef {
+    Any,
+    Boolean,
+    Integer,
+    Number,
+    String,
+    DateTime,
+    StringArray,
+    Map,
+    Named(String),
+    Array(String),
+}
+
+impl TypeRef {
+    fn to_rust(&self) -> String {
+        match self {
+            Self::Any => "::serde_json::Value".into(),
+            Self::Boolean => "bool".into(),
+            Self::Integer => "i64".into(),
+            Self::Number => "f64".into(),
+            Self::String => "String".into(),
+            Self::DateTime => "::chrono::DateTime<::chrono::Utc>".into(),
+            Self::StringArray => "Vec<String>".into(),
+            Self::Map => "::serde_json::Map<String, ::serde_json::Value>".into(),
+            Self::Named(name) => name.clone(),
+            Self::Array(name) => format!("Vec<{name}>"),
+        }
+    }
+
+    fn to_typescript(&self) -> String {
+        match self {
+            Self::Any => "unknown".into(),
+            Self::Boolean => "boolean".into(),
+            Self::Integer | Self::Number => "number".into(),
+            // NOTE: the timestamps are formatted in RFC 3339
+            Self::String | Self::DateTime => "string".into(),
+            Self::StringArray => "string[]".into(),
+            Self::Map => "Record<string, unknown>".into(),
+            Self::Named(name) => name.clone(),
+            Self::Array(name) => format!("{name}[]"),
+        }
+    }
+}
+
+/// Return the last segment of the field name, e.g. `bar` of `/foo/bar/`.
+fn get_field_name(name: &str) -> Option<&str> {
+    name.strip_suffix('/')?
+        .rsplit_once('/')
+        .map(|(_, name)| name)
+        .filter(|name| !name.is_empty())
+}
+
+/// Return the parent of the field name, e.g. `/foo/` of `/foo/bar/`.
+fn get_parent_name(name: &str) -> Option<&str> {
+    let (parent, _) = name.strip_suffix('/')?.rsplit_once('/')?;
+    Some(&name[..parent.len() + 1])
+}
+
+fn to_rust_ident(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "box", "break", "const", "continue", "crate", "do", "dyn", "else",
+        "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let", "loop",
+        "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "self",
+        "static", "struct", "super", "trait", "true", "try", "type", "typeof", "unsafe", "unsized",
+        "use", "virtual", "where", "while", "yield",
+    ];
+
+    let ident = name.replace('-', "_");
+    if KEYWORDS.contains(&ident.as_str()) {
+        format!("{ident}_")
+    } else {
+        ident
+    }
+}
+
+fn to_rust_variant(choice: impl AsRef<str>) -> String {
+    let variant = choice.as_ref().to_pascal_case();
+    if variant.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        variant
+    } else {
+        format!("V{variant}")
+    }
+}
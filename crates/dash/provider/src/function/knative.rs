@@ -0,0 +1,158 @@
+use anyhow::{anyhow, Result};
+use dash_api::function::{FunctionAutoscalingSpec, FunctionEndpointStatus, FunctionKnativeSpec};
+use kube::{
+    api::{DeleteParams, Patch, PatchParams},
+    core::{ApiResource, DynamicObject, GroupVersionKind},
+    Api, Client,
+};
+use serde_json::{json, Value};
+use tracing::{info, instrument, Level};
+
+pub struct KnativeServiceClient<'namespace, 'kube> {
+    pub namespace: &'namespace str,
+    pub kube: &'kube Client,
+}
+
+impl<'namespace, 'kube> KnativeServiceClient<'namespace, 'kube> {
+    pub const LABEL_FUNCTION: &'static str = "dash.ulagbulag.io/function";
+
+    fn api(&self) -> Api<DynamicObject> {
+        let gvk = GroupVersionKind::gvk("serving.knative.dev", "v1", "Service");
+        let ar = ApiResource::from_gvk_with_plural(&gvk, "services");
+        Api::namespaced_with(self.kube.clone(), self.namespace, &ar)
+    }
+
+    #[instrument(level = Level::INFO, skip(self, field_manager, spec), err(Display))]
+    pub async fn apply(
+        &self,
+        field_manager: &str,
+        name: &str,
+        spec: &FunctionKnativeSpec,
+    ) -> Result<()> {
+        let FunctionKnativeSpec {
+            image,
+            args,
+            env,
+            port,
+            autoscaling:
+                FunctionAutoscalingSpec {
+                    min_scale,
+                    max_scale,
+                    target_concurrency,
+                    scale_to_zero_retention_period,
+                },
+        } = spec;
+        let namespace = self.namespace;
+
+        let annotations: serde_json::Map<_, _> = [
+            ("min-scale", Some(min_scale.to_string())),
+            ("max-scale", max_scale.map(|value| value.to_string())),
+            ("target", target_concurrency.map(|value| value.to_string())),
+            (
+                "scale-to-zero-pod-retention-period",
+                scale_to_zero_retention_period.clone(),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some((format!("autoscaling.knative.dev/{key}"), value?.into())))
+        .collect();
+
+        let container = json!({
+            "name": "function",
+            "image": image,
+            "args": args,
+            "env": env
+                .iter()
+                .map(|(name, value)| json!({ "name": name, "value": value }))
+                .collect::<Vec<_>>(),
+            "ports": port
+                .map(|port| vec![json!({ "containerPort": port })])
+                .unwrap_or_default(),
+        });
+
+        let api = self.api();
+        let patch = Patch::Apply(json!({
+            "apiVersion": "serving.knative.dev/v1",
+            "kind": "Service",
+            "metadata": {
+                "name": name,
+                "namespace": namespace,
+                "labels": {
+                    Self::LABEL_FUNCTION: name,
+                },
+            },
+            "spec": {
+                "template": {
+                    "metadata": {
+                        "annotations": annotations,
+                        "labels": {
+                            Self::LABEL_FUNCTION: name,
+                        },
+                    },
+                    "spec": {
+                        "containers": [container],
+                    },
+                },
+            },
+        }));
+        let pp = PatchParams::apply(field_manager).force();
+        api.patch(name, &pp, &patch)
+            .await
+            .map(|_| info!("applied knative service: {namespace}/{name}"))
+            .map_err(|error| {
+                anyhow!("failed to apply knative service ({namespace}/{name}): {error}")
+            })
+    }
+
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn delete(&self, name: &str) -> Result<()> {
+        let namespace = self.namespace;
+        let api = self.api();
+        if api.get_opt(name).await?.is_none() {
+            return Ok(());
+        }
+
+        let dp = DeleteParams::background();
+        api.delete(name, &dp)
+            .await
+            .map(|_| info!("deleted knative service: {namespace}/{name}"))
+            .map_err(|error| {
+                anyhow!("failed to delete knative service ({namespace}/{name}): {error}")
+            })
+    }
+
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn load_endpoint(&self, name: &str) -> Result<FunctionEndpointStatus> {
+        let status = match self.api().get_opt(name).await? {
+            Some(service) => service.data.get("status").cloned().unwrap_or_default(),
+            None => {
+                return Ok(FunctionEndpointStatus {
+                    message: Some("knative service is not found".into()),
+                    ..Default::default()
+                })
+            }
+        };
+
+        let condition = status
+            .get("conditions")
+            .and_then(Value::as_array)
+            .and_then(|conditions| {
+                conditions.iter().find(|condition| {
+                    condition.get("type").and_then(Value::as_str) == Some("Ready")
+                })
+            });
+        let get_string = |value: Option<&Value>, key: &str| {
+            value
+                .and_then(|value| value.get(key))
+                .and_then(Value::as_str)
+                .map(ToString::to_string)
+        };
+
+        Ok(FunctionEndpointStatus {
+            ready: get_string(condition, "status").as_deref() == Some("True"),
+            message: get_string(condition, "message"),
+            revision: get_string(Some(&status), "latestReadyRevisionName"),
+            url: get_string(status.get("address"), "url"),
+        })
+    }
+}
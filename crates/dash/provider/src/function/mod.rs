@@ -0,0 +1,149 @@
+pub mod codegen;
+pub mod knative;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::Utc;
+use dash_api::function::{
+    FunctionCrd, FunctionExec, FunctionState, FunctionTestExampleSpec, FunctionTestFailure,
+    FunctionTestStatus,
+};
+use dash_provider_api::codegen::CodegenOptions;
+use kube::{Api, Client, ResourceExt};
+use serde_json::Value;
+use tracing::{instrument, warn, Level};
+use uuid::Uuid;
+
+use crate::{
+    lineage::{load_function_edge, LineageClient},
+    storage::{Storage, StorageClient},
+};
+
+use self::codegen::FunctionCodegen;
+
+pub struct FunctionSession<'namespace, 'kube> {
+    pub namespace: &'namespace str,
+    pub kube: &'kube Client,
+}
+
+impl<'namespace, 'kube> FunctionSession<'namespace, 'kube> {
+    /// Invoke the deployed function with the given input value, and return its output.
+    ///
+    /// The run is recorded into the lineage of its models.
+    #[instrument(level = Level::INFO, skip(self, value), err(Display))]
+    pub async fn invoke(&self, name: &str, value: &Value) -> Result<Value> {
+        let api = Api::<FunctionCrd>::namespaced(self.kube.clone(), self.namespace);
+        let function = api.get(name).await?;
+
+        let output = self.call(&function, name, value).await?;
+        if let Some(lineage) = LineageClient::shared().await {
+            let run = Some(Uuid::new_v4().to_string());
+            let edge = load_function_edge(self.kube, self.namespace, &function, run).await;
+            let result = match edge {
+                Ok(edge) => lineage.record(edge).await,
+                Err(error) => Err(error),
+            };
+            if let Err(error) = result {
+                warn!("failed to record the function run ({name}): {error}");
+            }
+        }
+        Ok(output)
+    }
+
+    async fn call(&self, function: &FunctionCrd, name: &str, value: &Value) -> Result<Value> {
+        let status = match function.status.clone() {
+            Some(status) if status.state == FunctionState::Ready => status,
+            Some(_) | None => bail!("function is not ready: {name:?}"),
+        };
+        match &function.spec.exec {
+            FunctionExec::Knative(_) => (),
+            FunctionExec::Placeholder {} | FunctionExec::Straw(_) => {
+                bail!("only the knative function can be invoked: {name:?}")
+            }
+        }
+
+        let url = match status.endpoint.and_then(|endpoint| endpoint.url) {
+            Some(url) => url,
+            None => bail!("function has no endpoint yet: {name:?}"),
+        };
+
+        // NOTE: the knative activator holds the request while scaling from zero
+        let response = ::reqwest::Client::new()
+            .post(&url)
+            .json(value)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|error| anyhow!("failed to invoke function ({name} => {url}): {error}"))?;
+        response
+            .json()
+            .await
+            .map_err(|error| anyhow!("failed to parse function output ({name}): {error}"))
+    }
+
+    /// Generate the typed client of the function from its input and output models.
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn generate_client(&self, name: &str, options: CodegenOptions) -> Result<String> {
+        let api = Api::<FunctionCrd>::namespaced(self.kube.clone(), self.namespace);
+        let function = api.get(name).await?;
+        FunctionCodegen::new(&function, options).generate()
+    }
+
+    /// Run the function against its golden examples, and collect the mismatched ones.
+    ///
+    /// Returns `None` if the function has no examples to test.
+    #[instrument(level = Level::INFO, skip_all, fields(name = %function.name_any()), err(Display))]
+    pub async fn test(&self, function: &FunctionCrd) -> Result<Option<FunctionTestStatus>> {
+        let examples = match &function.spec.test {
+            Some(test) if !test.examples.is_empty() => &test.examples,
+            Some(_) | None => return Ok(None),
+        };
+        let name = function.name_any();
+
+        let mut failures = vec![];
+        for example in examples {
+            if let Err(error) = self.test_example(function, &name, example).await {
+                failures.push(FunctionTestFailure {
+                    example: example.clone(),
+                    message: error.to_string(),
+                });
+            }
+        }
+
+        let failed = failures.len() as u32;
+        Ok(Some(FunctionTestStatus {
+            generation: function.metadata.generation.unwrap_or_default(),
+            passed: examples.len() as u32 - failed,
+            failed,
+            failures,
+            last_tested: Utc::now(),
+        }))
+    }
+
+    async fn test_example(
+        &self,
+        function: &FunctionCrd,
+        name: &str,
+        FunctionTestExampleSpec { input, output }: &FunctionTestExampleSpec,
+    ) -> Result<()> {
+        let storage = StorageClient {
+            namespace: self.namespace,
+            kube: self.kube,
+        };
+        let input = storage
+            .get(&function.spec.input, input)
+            .await
+            .map_err(|error| anyhow!("failed to load input example ({input}): {error}"))?;
+        let expected = storage
+            .get(&function.spec.output, output)
+            .await
+            .map_err(|error| anyhow!("failed to load output example ({output}): {error}"))?;
+
+        // NOTE: the golden examples are not recorded into the lineage
+        let output = self.call(function, name, &input).await?;
+        if output == expected {
+            Ok(())
+        } else {
+            bail!("unexpected output: {output}")
+        }
+    }
+}
@@ -1,7 +1,11 @@
 #![recursion_limit = "256"]
 
+pub mod audit;
+pub mod bundle;
 pub mod client;
+pub mod function;
 pub mod input;
+pub mod lineage;
 pub mod storage;
 
 pub mod imp {
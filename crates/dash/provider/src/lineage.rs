@@ -0,0 +1,245 @@
+use std::collections::{BTreeSet, VecDeque};
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use dash_api::{
+    function::FunctionCrd,
+    lineage::{LineageEdge, LineageGraph, LineageModel, LineageQuery},
+    model::ModelCrd,
+};
+use kube::{Api, Client, ResourceExt};
+use sea_orm::{
+    prelude::StringLen, ActiveModelBehavior, ActiveModelTrait, ActiveValue, ColumnTrait,
+    ConnectionTrait, Database, DatabaseConnection, DbErr, DeriveEntityModel, DerivePrimaryKey,
+    DeriveRelation, EntityTrait, EnumIter, PrimaryKeyTrait, QueryFilter, QueryOrder, QuerySelect,
+    Schema,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::OnceCell;
+use tracing::{instrument, warn, Level};
+
+/// An append-only store of the model derivations, which is kept in the metadata storage.
+#[derive(Clone)]
+pub struct LineageClient {
+    db: DatabaseConnection,
+}
+
+impl LineageClient {
+    const ENV_DATABASE_URL: &'static str = "DASH_LINEAGE_DATABASE_URL";
+    const NATIVE_URL: &'static str = "postgres://dash-postgres/dash";
+
+    /// The maximum number of the derivations to load per model.
+    const MAX_EDGES: u64 = 1000;
+
+    #[instrument(level = Level::INFO, err(Display))]
+    pub async fn try_default() -> Result<Self> {
+        let url =
+            ::std::env::var(Self::ENV_DATABASE_URL).unwrap_or_else(|_| Self::NATIVE_URL.into());
+        let db = Database::connect(url).await?;
+
+        Entity::init(&db)
+            .await
+            .map(|()| Self { db })
+            .map_err(Into::into)
+    }
+
+    /// Load the store shared by the process.
+    ///
+    /// If the store is not available, the derivations are not recorded.
+    pub async fn shared() -> Option<&'static Self> {
+        static CLIENT: OnceCell<Option<LineageClient>> = OnceCell::const_new();
+
+        CLIENT
+            .get_or_init(|| async {
+                match Self::try_default().await {
+                    Ok(client) => Some(client),
+                    Err(error) => {
+                        warn!("failed to init lineage store; skipping lineage: {error}");
+                        None
+                    }
+                }
+            })
+            .await
+            .as_ref()
+    }
+
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn record(&self, edge: LineageEdge) -> Result<()> {
+        let LineageEdge {
+            timestamp,
+            namespace,
+            function,
+            function_version,
+            run,
+            upstream,
+            downstream,
+        } = edge;
+
+        ActiveModel {
+            id: ActiveValue::NotSet,
+            timestamp: ActiveValue::Set(timestamp.naive_utc()),
+            namespace: ActiveValue::Set(namespace),
+            function: ActiveValue::Set(function),
+            function_version: ActiveValue::Set(function_version),
+            run: ActiveValue::Set(run),
+            upstream_model: ActiveValue::Set(upstream.name),
+            upstream_version: ActiveValue::Set(upstream.version),
+            downstream_model: ActiveValue::Set(downstream.name),
+            downstream_version: ActiveValue::Set(downstream.version),
+        }
+        .insert(&self.db)
+        .await
+        .map(|_| ())
+        .map_err(|error| anyhow!("failed to record the lineage: {error}"))
+    }
+
+    /// Follow the derivations from the model, e.g. to find the models to be invalidated.
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn downstream(
+        &self,
+        namespace: &str,
+        name: &str,
+        query: &LineageQuery,
+    ) -> Result<LineageGraph> {
+        let root = LineageModel {
+            name: name.into(),
+            version: query.version,
+        };
+
+        let mut edges = vec![];
+        let mut visited = BTreeSet::default();
+        let mut queue = VecDeque::from([(root.clone(), 0)]);
+        while let Some((model, depth)) = queue.pop_front() {
+            if depth >= query.depth() || !visited.insert(model.clone()) {
+                continue;
+            }
+
+            let mut select = Entity::find()
+                .filter(Column::Namespace.eq(namespace))
+                .filter(Column::UpstreamModel.eq(&model.name));
+            if let Some(version) = model.version {
+                select = select.filter(Column::UpstreamVersion.eq(version));
+            }
+
+            let children = select
+                .order_by_asc(Column::Id)
+                .limit(Self::MAX_EDGES)
+                .all(&self.db)
+                .await?;
+            for child in children {
+                let edge = LineageEdge::from(child);
+                queue.push_back((edge.downstream.clone(), depth + 1));
+                edges.push(edge);
+            }
+        }
+        Ok(LineageGraph { root, edges })
+    }
+}
+
+/// Build the derivation performed by the function, with the current versions of its models.
+#[instrument(level = Level::INFO, skip(kube, function), fields(name = %function.name_any()), err(Display))]
+pub async fn load_function_edge(
+    kube: &Client,
+    namespace: &str,
+    function: &FunctionCrd,
+    run: Option<String>,
+) -> Result<LineageEdge> {
+    async fn load_model(api: &Api<ModelCrd>, name: String) -> Result<LineageModel> {
+        let version = api
+            .get_opt(&name)
+            .await
+            .map_err(|error| anyhow!("failed to load the model ({name}): {error}"))?
+            .and_then(|model| model.metadata.generation);
+        Ok(LineageModel { name, version })
+    }
+
+    let api = Api::<ModelCrd>::namespaced(kube.clone(), namespace);
+    Ok(LineageEdge {
+        timestamp: Utc::now(),
+        namespace: namespace.into(),
+        function: function.name_any(),
+        function_version: function.metadata.generation,
+        run,
+        upstream: load_model(&api, function.spec.input.to_string()).await?,
+        downstream: load_model(&api, function.spec.output.to_string()).await?,
+    })
+}
+
+#[derive(Clone, Debug, Default, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "__dash_lineage_edges")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    #[sea_orm(column_type = "Timestamp")]
+    pub timestamp: ::chrono::NaiveDateTime,
+    #[sea_orm(column_type = "String(StringLen::N(64))", indexed)]
+    pub namespace: String,
+    #[sea_orm(column_type = "String(StringLen::N(253))")]
+    pub function: String,
+    #[sea_orm(nullable)]
+    pub function_version: Option<i64>,
+    #[sea_orm(column_type = "String(StringLen::N(64))", nullable)]
+    pub run: Option<String>,
+    #[sea_orm(column_type = "String(StringLen::N(253))", indexed)]
+    pub upstream_model: String,
+    #[sea_orm(nullable)]
+    pub upstream_version: Option<i64>,
+    #[sea_orm(column_type = "String(StringLen::N(253))")]
+    pub downstream_model: String,
+    #[sea_orm(nullable)]
+    pub downstream_version: Option<i64>,
+}
+
+impl From<Model> for LineageEdge {
+    fn from(value: Model) -> Self {
+        let Model {
+            id: _,
+            timestamp,
+            namespace,
+            function,
+            function_version,
+            run,
+            upstream_model,
+            upstream_version,
+            downstream_model,
+            downstream_version,
+        } = value;
+
+        Self {
+            timestamp: timestamp.and_utc(),
+            namespace,
+            function,
+            function_version,
+            run,
+            upstream: LineageModel {
+                name: upstream_model,
+                version: upstream_version,
+            },
+            downstream: LineageModel {
+                name: downstream_model,
+                version: downstream_version,
+            },
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Entity {
+    #[instrument(level = Level::INFO, skip(db), err(Display))]
+    async fn init(db: &DatabaseConnection) -> Result<(), DbErr> {
+        // Check whether the lineage table is already exist
+        if Self::find_by_id(0).one(db).await.is_ok() {
+            return Ok(());
+        }
+
+        // Create a lineage table
+        let builder = db.get_database_backend();
+        let statement =
+            builder.build(&Schema::new(db.get_database_backend()).create_table_from_entity(Self));
+        db.execute(statement).await.map(|_| ())
+    }
+}
@@ -0,0 +1,247 @@
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Result};
+use bytes::BytesMut;
+use futures::{TryFutureExt, TryStreamExt};
+use minio::s3::{
+    args::{PutObjectApiArgs, StatObjectArgs},
+    types::S3Api,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::fs;
+use tracing::{info, instrument, Level};
+
+use super::{
+    transfer::{hash_file, CHECKSUM_PREFIX},
+    ObjectStorageSession, ObjectTransferOptions, ObjectTransferReport,
+};
+
+/// The blobs, addressed by their SHA-256 digests.
+const BLOB_PREFIX: &str = ".dash/blobs/sha256/";
+
+/// The digests of the artifacts, addressed by their model paths.
+const PATH_PREFIX: &str = ".dash/paths/";
+
+/// The referrers of the blobs, one empty marker object per model path.
+const REFERRER_PREFIX: &str = ".dash/referrers/sha256/";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactRef {
+    /// The hex-encoded SHA-256 digest of the artifact
+    pub digest: String,
+    /// Whether the identical blob has already been stored
+    pub deduplicated: bool,
+    pub size: u64,
+}
+
+impl ObjectStorageSession {
+    /// Store the artifact by its digest, sharing the blob with the identical artifacts.
+    #[instrument(level = Level::INFO, skip(self, data), err(Display))]
+    pub async fn put_artifact(
+        &self,
+        bucket: &str,
+        model: &str,
+        path: &str,
+        data: &[u8],
+    ) -> Result<ArtifactRef> {
+        let digest = format!("{:x}", Sha256::digest(data));
+        let deduplicated = self.is_blob_exists(bucket, &digest).await;
+        if !deduplicated {
+            let args = PutObjectApiArgs::new(bucket, &blob_key(&digest), data)?;
+            if let Err(error) = self.client.put_object_api(&args).await {
+                bail!("failed to put artifact blob ({bucket}/{digest}): {error}")
+            }
+        }
+
+        self.link_artifact(bucket, model, path, &digest).await?;
+        Ok(ArtifactRef {
+            digest,
+            deduplicated,
+            size: data.len() as u64,
+        })
+    }
+
+    /// Store the large artifact file by its digest, streaming the blob only if it is new.
+    #[instrument(level = Level::INFO, skip(self, options), err(Display))]
+    pub async fn put_artifact_file(
+        &self,
+        bucket: &str,
+        model: &str,
+        path: &str,
+        file: &Path,
+        options: &ObjectTransferOptions,
+    ) -> Result<ArtifactRef> {
+        let mut hasher = Sha256::new();
+        let size = fs::File::open(file)
+            .map_err(|error| anyhow!("failed to open file ({}): {error}", file.display()))
+            .and_then(|file| hash_file(file, &mut hasher))
+            .await?;
+        let digest = format!("{:x}", hasher.finalize());
+
+        let deduplicated = self.is_blob_exists(bucket, &digest).await;
+        if !deduplicated {
+            let ObjectTransferReport { sha256, .. } = self
+                .upload_file(bucket, &blob_key(&digest), file, options)
+                .await?;
+            if sha256 != digest {
+                self.remove_blob(bucket, &digest).await?;
+                bail!(
+                    "the artifact file ({}) has been changed during upload",
+                    file.display(),
+                )
+            }
+        }
+
+        self.link_artifact(bucket, model, path, &digest).await?;
+        Ok(ArtifactRef {
+            digest,
+            deduplicated,
+            size,
+        })
+    }
+
+    /// Download the artifact of the model path into the file.
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn get_artifact_file(
+        &self,
+        bucket: &str,
+        model: &str,
+        path: &str,
+        file: &Path,
+    ) -> Result<Option<ObjectTransferReport>> {
+        match self.resolve_artifact(bucket, model, path).await? {
+            Some(digest) => self
+                .download_file(bucket, &blob_key(&digest), file)
+                .await
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Resolve the model path into the digest of the artifact.
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn resolve_artifact(
+        &self,
+        bucket: &str,
+        model: &str,
+        path: &str,
+    ) -> Result<Option<String>> {
+        let key = path_key(model, path);
+        match self.client.get_object(bucket, &key).send().await {
+            Ok(response) => response
+                .content
+                .to_stream()
+                .and_then(|(stream, _size)| stream.try_collect().map_err(Into::into))
+                .await
+                .map(|bytes: BytesMut| Some(String::from_utf8_lossy(&bytes).trim().to_string()))
+                .map_err(|error| anyhow!("failed to resolve artifact ({bucket}/{key}): {error}")),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Count the model paths referring to the blob.
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn get_artifact_ref_count(&self, bucket: &str, digest: &str) -> Result<usize> {
+        self.get_referrers(bucket, digest)
+            .await
+            .map(|keys| keys.len())
+    }
+
+    /// Unlink the model path, deleting the blob when no longer referred.
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn delete_artifact(&self, bucket: &str, model: &str, path: &str) -> Result<bool> {
+        let digest = match self.resolve_artifact(bucket, model, path).await? {
+            Some(digest) => digest,
+            None => return Ok(false),
+        };
+
+        self.remove_object(bucket, &referrer_key(&digest, model, path))
+            .await?;
+        self.remove_object(bucket, &path_key(model, path)).await?;
+
+        if self.get_referrers(bucket, &digest).await?.is_empty() {
+            info!("deleting unreferred artifact blob: {digest}");
+            self.remove_blob(bucket, &digest).await?;
+        }
+        Ok(true)
+    }
+
+    async fn link_artifact(
+        &self,
+        bucket: &str,
+        model: &str,
+        path: &str,
+        digest: &str,
+    ) -> Result<()> {
+        // Unlink the previous artifact of the path, if changed
+        if let Some(previous) = self.resolve_artifact(bucket, model, path).await? {
+            if previous != digest {
+                self.delete_artifact(bucket, model, path).await?;
+            }
+        }
+
+        let args = PutObjectApiArgs::new(bucket, &referrer_key(digest, model, path), &[])?;
+        if let Err(error) = self.client.put_object_api(&args).await {
+            bail!("failed to refer artifact blob ({bucket}/{digest}): {error}")
+        }
+
+        let key = path_key(model, path);
+        let args = PutObjectApiArgs::new(bucket, &key, digest.as_bytes())?;
+        match self.client.put_object_api(&args).await {
+            Ok(_) => Ok(()),
+            Err(error) => bail!("failed to link artifact ({bucket}/{key}): {error}"),
+        }
+    }
+
+    async fn get_referrers(&self, bucket: &str, digest: &str) -> Result<Vec<String>> {
+        match self
+            .client
+            .list_objects_v2(bucket)
+            .prefix(Some(format!("{REFERRER_PREFIX}{digest}/")))
+            .send()
+            .await
+        {
+            Ok(response) => Ok(response
+                .contents
+                .into_iter()
+                .map(|item| item.name)
+                .collect()),
+            Err(error) => bail!("failed to list artifact referrers ({bucket}/{digest}): {error}"),
+        }
+    }
+
+    async fn is_blob_exists(&self, bucket: &str, digest: &str) -> bool {
+        match StatObjectArgs::new(bucket, &blob_key(digest)) {
+            Ok(args) => self.client.stat_object(&args).await.is_ok(),
+            Err(_) => false,
+        }
+    }
+
+    async fn remove_blob(&self, bucket: &str, digest: &str) -> Result<()> {
+        let key = blob_key(digest);
+        self.remove_object(bucket, &key).await?;
+        self.remove_object(bucket, &format!("{CHECKSUM_PREFIX}{key}"))
+            .await
+    }
+
+    async fn remove_object(&self, bucket: &str, key: &str) -> Result<()> {
+        match self.client.remove_object(bucket, key).send().await {
+            Ok(_) => Ok(()),
+            Err(error) => bail!("failed to delete object ({bucket}/{key}): {error}"),
+        }
+    }
+}
+
+fn blob_key(digest: &str) -> String {
+    format!("{BLOB_PREFIX}{digest}")
+}
+
+fn path_key(model: &str, path: &str) -> String {
+    format!("{PATH_PREFIX}{model}/{path}")
+}
+
+fn referrer_key(digest: &str, model: &str, path: &str) -> String {
+    format!("{REFERRER_PREFIX}{digest}/{model}/{path}")
+}
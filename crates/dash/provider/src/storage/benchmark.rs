@@ -0,0 +1,414 @@
+use anyhow::{anyhow, bail, Result};
+use dash_api::{
+    model_storage_binding::ModelStorageBindingStorageSpec,
+    storage::{
+        ModelStorageBenchmarkOperation, ModelStorageBenchmarkResult, ModelStorageCrd,
+        ModelStorageKindSpec,
+    },
+};
+use k8s_openapi::api::{
+    batch::v1::{Job, JobSpec},
+    core::v1::{Container, EnvVar, Pod, PodSpec, PodTemplateSpec, Volume, VolumeMount},
+};
+use kube::{
+    api::{DeleteParams, ListParams, ObjectMeta, PostParams},
+    Api, Client, ResourceExt,
+};
+use maplit::btreemap;
+use serde::Deserialize;
+use tracing::{instrument, Level};
+
+use super::{DatabaseStorageClient, FileSystemStorageClient, ObjectStorageSession};
+
+/// Runs the IO benchmarks of the model storages as jobs.
+///
+/// The benchmark containers report their results as the termination messages.
+pub struct StorageBenchmarkClient<'namespace, 'kube> {
+    pub namespace: &'namespace str,
+    pub kube: &'kube Client,
+}
+
+impl<'namespace, 'kube> StorageBenchmarkClient<'namespace, 'kube> {
+    const MOUNT_PATH: &'static str = "/mnt/dash";
+
+    /// Create a benchmark job of the model storage.
+    ///
+    /// Return `false` if the kind of the model storage cannot be benchmarked.
+    #[instrument(level = Level::INFO, skip(self, crd), err(Display))]
+    pub async fn create_job(&self, job_name: &str, crd: &ModelStorageCrd) -> Result<bool> {
+        let Self { namespace, kube } = *self;
+        let storage_name = crd.name_any();
+
+        let (image, script, extra_env, volumes) = match &crd.spec.kind {
+            ModelStorageKindSpec::Database(storage) => (
+                "docker.io/library/postgres:latest",
+                SCRIPT_DATABASE,
+                vec![env(
+                    "DATABASE_URL",
+                    DatabaseStorageClient::infer_url(storage),
+                )],
+                Vec::default(),
+            ),
+            ModelStorageKindSpec::FileSystem(storage) => {
+                if storage.read_only {
+                    return Ok(false);
+                }
+
+                let storage = ModelStorageBindingStorageSpec {
+                    source: None,
+                    source_binding_name: None,
+                    target: storage,
+                    target_name: &storage_name,
+                };
+                let client = FileSystemStorageClient::new(kube, namespace, storage);
+                (
+                    "docker.io/library/debian:stable-slim",
+                    SCRIPT_FILESYSTEM,
+                    vec![env("MOUNT_PATH", Self::MOUNT_PATH.into())],
+                    vec![client.get_root_volume()],
+                )
+            }
+            ModelStorageKindSpec::Kubernetes(_) => return Ok(false),
+            ModelStorageKindSpec::ObjectStorage(storage) => {
+                let session = ObjectStorageSession::load_storage_provider(
+                    kube,
+                    namespace,
+                    &storage_name,
+                    Some(&crd.metadata),
+                    storage,
+                    None,
+                )
+                .await?;
+                let creds = session.fetch_provider();
+                (
+                    "docker.io/minio/mc:latest",
+                    SCRIPT_OBJECT_STORAGE,
+                    vec![
+                        env("TARGET_ACCESS_KEY", creds.access_key),
+                        env("TARGET_ENDPOINT", session.endpoint.to_string()),
+                        env("TARGET_SECRET_KEY", creds.secret_key),
+                    ],
+                    Vec::default(),
+                )
+            }
+        };
+
+        let api = Api::<Job>::namespaced(kube.clone(), namespace);
+        if api.get_opt(job_name).await?.is_some() {
+            return Ok(true);
+        }
+
+        let labels = btreemap! {
+            "dash.ulagbulag.io/modelstorage-benchmark".into() => "true".into(),
+            "dash.ulagbulag.io/modelstorage-name".into() => storage_name,
+        };
+        let job = Job {
+            metadata: ObjectMeta {
+                labels: Some(labels.clone()),
+                name: Some(job_name.into()),
+                namespace: Some(namespace.into()),
+                ..Default::default()
+            },
+            spec: Some(JobSpec {
+                backoff_limit: Some(0),
+                template: PodTemplateSpec {
+                    metadata: Some(ObjectMeta {
+                        labels: Some(labels),
+                        ..Default::default()
+                    }),
+                    spec: Some(PodSpec {
+                        containers: vec![Container {
+                            name: "benchmark".into(),
+                            image: Some(image.into()),
+                            image_pull_policy: Some("Always".into()),
+                            command: Some(vec![
+                                "/usr/bin/env".into(),
+                                "/bin/bash".into(),
+                                "-c".into(),
+                            ]),
+                            args: Some(vec![format!("{SCRIPT_COMMON}\n{script}")]),
+                            env: Some(
+                                [
+                                    env("BENCHMARK_NAME", job_name.into()),
+                                    env("LARGE_COUNT", "8".into()),
+                                    env("LARGE_SIZE", (1u64 << 20).to_string()),
+                                    env("SMALL_COUNT", "64".into()),
+                                    env("SMALL_SIZE", (4u64 << 10).to_string()),
+                                ]
+                                .into_iter()
+                                .chain(extra_env)
+                                .collect(),
+                            ),
+                            volume_mounts: Some(
+                                volumes
+                                    .iter()
+                                    .map(|Volume { name, .. }| VolumeMount {
+                                        name: name.clone(),
+                                        mount_path: Self::MOUNT_PATH.into(),
+                                        ..Default::default()
+                                    })
+                                    .collect(),
+                            ),
+                            ..Default::default()
+                        }],
+                        restart_policy: Some("Never".into()),
+                        volumes: Some(volumes),
+                        ..Default::default()
+                    }),
+                },
+                ..Default::default()
+            }),
+            status: None,
+        };
+
+        let pp = PostParams::default();
+        api.create(&pp, &job)
+            .await
+            .map(|_| true)
+            .map_err(|error| anyhow!("failed to create a benchmark job ({job_name}): {error}"))
+    }
+
+    /// Load the results of the finished benchmark job.
+    ///
+    /// Return `None` if the job is still running.
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn load_results(
+        &self,
+        job_name: &str,
+    ) -> Result<Option<Result<Vec<ModelStorageBenchmarkResult>>>> {
+        let Self { namespace, kube } = *self;
+
+        let api = Api::<Job>::namespaced(kube.clone(), namespace);
+        let status = match api.get_opt(job_name).await? {
+            Some(job) => job.status.unwrap_or_default(),
+            None => return Ok(Some(Err(anyhow!("benchmark job is not found")))),
+        };
+        if status.failed.unwrap_or_default() > 0 {
+            return Ok(Some(Err(anyhow!("benchmark job is failed"))));
+        }
+        if status.succeeded.unwrap_or_default() == 0 {
+            return Ok(None);
+        }
+
+        let api = Api::<Pod>::namespaced(kube.clone(), namespace);
+        let lp = ListParams::default().labels(&format!("job-name={job_name}"));
+        let message = api
+            .list(&lp)
+            .await?
+            .items
+            .into_iter()
+            .filter_map(|pod| pod.status?.container_statuses)
+            .flatten()
+            .find_map(|status| status.state?.terminated?.message);
+
+        match message {
+            Some(message) => Ok(Some(parse_results(&message))),
+            None => Ok(Some(Err(anyhow!("benchmark results are not reported")))),
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn delete_job(&self, job_name: &str) -> Result<()> {
+        let Self { namespace, kube } = *self;
+
+        let api = Api::<Job>::namespaced(kube.clone(), namespace);
+        let dp = DeleteParams::background();
+        match api.delete(job_name, &dp).await {
+            Ok(_) => Ok(()),
+            Err(::kube::Error::Api(error)) if error.code == 404 => Ok(()),
+            Err(error) => bail!("failed to delete benchmark job ({job_name}): {error}"),
+        }
+    }
+}
+
+/// A raw measurement, as reported by the benchmark script.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BenchmarkReport {
+    operation: ModelStorageBenchmarkOperation,
+    object_size: u64,
+    count: u64,
+    total_ns: u64,
+    p50_ns: u64,
+    p90_ns: u64,
+    p99_ns: u64,
+}
+
+impl From<BenchmarkReport> for ModelStorageBenchmarkResult {
+    fn from(value: BenchmarkReport) -> Self {
+        let BenchmarkReport {
+            operation,
+            object_size,
+            count,
+            total_ns,
+            p50_ns,
+            p90_ns,
+            p99_ns,
+        } = value;
+
+        let to_ms = |ns: u64| ns as f64 / 1e6;
+        Self {
+            operation,
+            object_size,
+            count,
+            throughput_bytes_per_second: if total_ns > 0 {
+                (object_size * count) as f64 * 1e9 / total_ns as f64
+            } else {
+                0.0
+            },
+            latency_p50_ms: to_ms(p50_ns),
+            latency_p90_ms: to_ms(p90_ns),
+            latency_p99_ms: to_ms(p99_ns),
+        }
+    }
+}
+
+fn parse_results(message: &str) -> Result<Vec<ModelStorageBenchmarkResult>> {
+    ::serde_json::from_str::<Vec<BenchmarkReport>>(message.trim())
+        .map(|reports| reports.into_iter().map(Into::into).collect())
+        .map_err(|error| anyhow!("failed to parse benchmark results: {error}"))
+}
+
+fn env(name: &str, value: String) -> EnvVar {
+    EnvVar {
+        name: name.into(),
+        value: Some(value),
+        value_from: None,
+    }
+}
+
+/// Measure the operations defined by the storage-specific scripts:
+/// `__setup`, `__write SIZE INDEX`, `__read SIZE INDEX` and `__teardown`.
+const SCRIPT_COMMON: &str = r#"
+#!/bin/bash
+
+# Prehibit errors
+set -e -o pipefail
+
+__RESULTS='/tmp/results'
+
+function __now() {
+    date '+%s%N'
+}
+
+# Usage: __measure OPERATION SIZE COUNT FUNCTION
+function __measure() {
+    local operation="$1"
+    local size="$2"
+    local count="$3"
+    local fn="$4"
+    local latencies="/tmp/latencies-${operation}-${size}"
+
+    : >"${latencies}"
+    local begin="$(__now)"
+    for i in $(seq 1 "${count}"); do
+        local index="${i}"
+        if [ "x${operation#Random}" != "x${operation}" ]; then
+            index="$((RANDOM % count + 1))"
+        fi
+
+        local started="$(__now)"
+        "${fn}" "${size}" "${index}"
+        echo "$(($(__now) - started))" >>"${latencies}"
+    done
+    local total="$(($(__now) - begin))"
+
+    sort -n "${latencies}" -o "${latencies}"
+    function __percentile() {
+        sed -n "$((($1 * count + 99) / 100))p" "${latencies}"
+    }
+    echo "{\"operation\":\"${operation}\",\"objectSize\":${size},\"count\":${count},\"totalNs\":${total},\"p50Ns\":$(__percentile 50),\"p90Ns\":$(__percentile 90),\"p99Ns\":$(__percentile 99)}" >>"${__RESULTS}"
+}
+
+function __run() {
+    echo '* Setting up benchmark...'
+    __setup
+    trap __teardown EXIT
+
+    : >"${__RESULTS}"
+    for size_count in "${SMALL_SIZE}:${SMALL_COUNT}" "${LARGE_SIZE}:${LARGE_COUNT}"; do
+        local size="${size_count%%:*}"
+        local count="${size_count##*:}"
+        head -c "${size}" /dev/urandom >"/tmp/object-${size}"
+
+        echo "* Benchmarking ${count} objects of ${size} bytes..."
+        __measure 'SequentialWrite' "${size}" "${count}" __write
+        __measure 'RandomWrite' "${size}" "${count}" __write
+        __measure 'SequentialRead' "${size}" "${count}" __read
+        __measure 'RandomRead' "${size}" "${count}" __read
+    done
+
+    # Report
+    echo "[$(paste -sd, "${__RESULTS}")]" >/dev/termination-log
+    cat /dev/termination-log
+}
+"#;
+
+const SCRIPT_DATABASE: &str = r#"
+__TABLE="\"${BENCHMARK_NAME}\""
+
+function __psql() {
+    psql "${DATABASE_URL}" --set ON_ERROR_STOP=1 --quiet -At -c "$1" >/dev/null
+}
+
+function __setup() {
+    __psql "CREATE TABLE IF NOT EXISTS ${__TABLE} (id BIGINT NOT NULL, size BIGINT NOT NULL, data BYTEA NOT NULL, PRIMARY KEY (id, size))"
+}
+
+function __teardown() {
+    __psql "DROP TABLE IF EXISTS ${__TABLE}"
+}
+
+function __write() {
+    __psql "INSERT INTO ${__TABLE} VALUES ($2, $1, decode(repeat('ab', $1), 'hex')) ON CONFLICT (id, size) DO UPDATE SET data = EXCLUDED.data"
+}
+
+function __read() {
+    __psql "SELECT length(data) FROM ${__TABLE} WHERE id = $2 AND size = $1"
+}
+
+__run
+"#;
+
+const SCRIPT_FILESYSTEM: &str = r#"
+__DIR="${MOUNT_PATH}/.${BENCHMARK_NAME}"
+
+function __setup() {
+    mkdir -p "${__DIR}"
+}
+
+function __teardown() {
+    rm -rf "${__DIR}"
+}
+
+function __write() {
+    dd if="/tmp/object-$1" of="${__DIR}/object-$1-$2" bs="$1" count=1 conv=fsync status=none
+}
+
+function __read() {
+    cat "${__DIR}/object-$1-$2" >/dev/null
+}
+
+__run
+"#;
+
+const SCRIPT_OBJECT_STORAGE: &str = r#"
+function __setup() {
+    mc alias set 'target' "${TARGET_ENDPOINT}" "${TARGET_ACCESS_KEY}" "${TARGET_SECRET_KEY}" >/dev/null
+    mc mb "target/${BENCHMARK_NAME}" --ignore-existing >/dev/null
+}
+
+function __teardown() {
+    mc rb "target/${BENCHMARK_NAME}" --force >/dev/null
+}
+
+function __write() {
+    mc cp --quiet "/tmp/object-$1" "target/${BENCHMARK_NAME}/object-$1-$2" >/dev/null
+}
+
+function __read() {
+    mc cat "target/${BENCHMARK_NAME}/object-$1-$2" >/dev/null
+}
+
+__run
+"#;
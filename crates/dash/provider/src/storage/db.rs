@@ -1,6 +1,7 @@
 use std::{borrow::Borrow, collections::BTreeMap, fmt};
 
 use anyhow::{anyhow, bail, Result};
+use byte_unit::Byte;
 use chrono::{NaiveDateTime, Utc};
 use dash_api::{
     model::{
@@ -8,17 +9,28 @@ use dash_api::{
         ModelFieldKindObjectSpec, ModelFieldKindStringSpec, ModelFieldNativeSpec,
         ModelFieldsNativeSpec, ModelState,
     },
+    model_storage_binding::ModelStorageBindingReplicationStatus,
     storage::db::{
         ModelStorageDatabaseBorrowedSpec, ModelStorageDatabaseOwnedSpec, ModelStorageDatabaseSpec,
     },
 };
-use kube::ResourceExt;
+use dash_provider_api::data::Usage;
+use k8s_openapi::api::{
+    batch::v1::{Job, JobSpec},
+    core::v1::{Container, EnvVar, PodSpec, PodTemplateSpec},
+};
+use kube::{
+    api::{ObjectMeta, PostParams},
+    Api, Client, ResourceExt,
+};
+use maplit::btreemap;
 use sea_orm::{
     prelude::StringLen,
     sea_query::{ColumnDef, Expr, IntoIden, Table, TableRef},
     ActiveModelBehavior, ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait, Database,
-    DatabaseConnection, DbErr, DeriveEntityModel, DerivePrimaryKey, DeriveRelation, EntityTrait,
-    EnumIter, Iden, PrimaryKeyTrait, QueryFilter, QueryOrder, QueryResult, Schema, Statement,
+    DatabaseBackend, DatabaseConnection, DbErr, DeriveEntityModel, DerivePrimaryKey,
+    DeriveRelation, EntityTrait, EnumIter, Iden, PrimaryKeyTrait, QueryFilter, QueryOrder,
+    QueryResult, Schema, Statement,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
@@ -82,6 +94,138 @@ impl DatabaseStorageClient {
             model,
         }
     }
+
+    /// List the tables of the models, which have been created by this client.
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn list_tables(&self) -> Result<Vec<String>> {
+        let backend = self.db.get_database_backend();
+        let statement = Statement::from_string(
+            backend,
+            r#"SELECT DISTINCT "model_name" FROM "__dash_model_migrations""#,
+        );
+
+        let mut tables = Vec::default();
+        for row in self.db.query_all(statement).await? {
+            let name: String = row.try_get("", "model_name")?;
+
+            let table_name = RuntimeIden::from_str(&name);
+            let statement =
+                Statement::from_string(backend, format!(r#"SELECT * FROM "{table_name}" LIMIT 0"#));
+            if self.db.execute(statement).await.is_ok() {
+                tables.push(name);
+            }
+        }
+        Ok(tables)
+    }
+
+    pub fn infer_url(storage: &ModelStorageDatabaseSpec) -> String {
+        match storage {
+            ModelStorageDatabaseSpec::Borrowed(storage) => storage.url.as_str().into(),
+            ModelStorageDatabaseSpec::Owned(ModelStorageDatabaseOwnedSpec {}) => {
+                Self::NATIVE_URL.into()
+            }
+        }
+    }
+
+    /// Copy the rows of the model table from the source database into the target database,
+    /// and verify that both tables have the same number of rows.
+    ///
+    /// The target table should be created before, and its rows are replaced.
+    #[instrument(level = Level::INFO, skip(kube, source, target, model), err(Display))]
+    pub async fn migrate_table(
+        kube: &Client,
+        namespace: &str,
+        job_name: &str,
+        source: &ModelStorageDatabaseSpec,
+        target: &ModelStorageDatabaseSpec,
+        model: &ModelCrd,
+    ) -> Result<()> {
+        let api = Api::<Job>::namespaced(kube.clone(), namespace);
+        if api.get_opt(job_name).await?.is_some() {
+            return Ok(());
+        }
+
+        let table_name = model.name_any();
+        let labels = btreemap! {
+            "dash.ulagbulag.io/modelstorage-database-command".into() => "migrate".into(),
+            "dash.ulagbulag.io/modelstorage-name".into() => table_name.clone(),
+        };
+        let env = |name: &str, value: String| EnvVar {
+            name: name.into(),
+            value: Some(value),
+            value_from: None,
+        };
+
+        let job = Job {
+            metadata: ObjectMeta {
+                labels: Some(labels.clone()),
+                name: Some(job_name.into()),
+                namespace: Some(namespace.into()),
+                ..Default::default()
+            },
+            spec: Some(JobSpec {
+                template: PodTemplateSpec {
+                    metadata: Some(ObjectMeta {
+                        labels: Some(labels),
+                        ..Default::default()
+                    }),
+                    spec: Some(PodSpec {
+                        containers: vec![Container {
+                            name: "postgres-client".into(),
+                            image: Some("docker.io/library/postgres:latest".into()),
+                            image_pull_policy: Some("Always".into()),
+                            command: Some(vec![
+                                "/usr/bin/env".into(),
+                                "/bin/bash".into(),
+                                "-c".into(),
+                            ]),
+                            args: Some(vec![r#"
+#!/bin/bash
+
+# Prehibit errors
+set -e -o pipefail
+
+# Copy
+echo '* Copying table...'
+psql "${TARGET_URL}" --set ON_ERROR_STOP=1 -c "TRUNCATE TABLE \"${TABLE_NAME}\""
+pg_dump "${SOURCE_URL}" --data-only --no-owner --no-privileges --table="\"${TABLE_NAME}\"" |
+    psql "${TARGET_URL}" --set ON_ERROR_STOP=1 --quiet
+
+# Verify
+echo '* Verifying table...'
+__SOURCE_ROWS="$(psql "${SOURCE_URL}" -At -c "SELECT COUNT(*) FROM \"${TABLE_NAME}\"")"
+__TARGET_ROWS="$(psql "${TARGET_URL}" -At -c "SELECT COUNT(*) FROM \"${TABLE_NAME}\"")"
+if [ "x${__SOURCE_ROWS}" != "x${__TARGET_ROWS}" ]; then
+    echo "* Verification failed: ${__SOURCE_ROWS} => ${__TARGET_ROWS} rows" >&2
+    exit 1
+fi
+
+# Finished!
+exec true
+"#
+                            .into()]),
+                            env: Some(vec![
+                                env("SOURCE_URL", Self::infer_url(source)),
+                                env("TABLE_NAME", table_name),
+                                env("TARGET_URL", Self::infer_url(target)),
+                            ]),
+                            ..Default::default()
+                        }],
+                        restart_policy: Some("OnFailure".into()),
+                        ..Default::default()
+                    }),
+                },
+                ..Default::default()
+            }),
+            status: None,
+        };
+
+        let pp = PostParams::default();
+        api.create(&pp, &job)
+            .await
+            .map(|_| ())
+            .map_err(|error| anyhow!("failed to create a migration job ({job_name}): {error}"))
+    }
 }
 
 pub struct DatabaseStorageSession<'model> {
@@ -173,13 +317,11 @@ impl<'model> DatabaseStorageSession<'model> {
     }
 
     #[instrument(level = Level::INFO, skip(self), err(Display))]
-    pub async fn get_list(&self) -> Result<Vec<Value>> {
-        const LIMIT: usize = 30;
-
+    pub async fn get_list(&self, limit: usize) -> Result<Vec<Value>> {
         let (_, table_name) = self.get_table_name();
         let statement = Statement::from_string(
             self.db.get_database_backend(),
-            format!(r#"SELECT * FROM "{table_name}" LIMIT {LIMIT}"#),
+            format!(r#"SELECT * FROM "{table_name}" LIMIT {limit}"#),
         );
 
         let rows = self.db.query_all(statement).await?;
@@ -190,6 +332,40 @@ impl<'model> DatabaseStorageSession<'model> {
             .collect()
     }
 
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn get_usage(&self) -> Result<Option<Usage>> {
+        let (name, _) = self.get_table_name();
+        let backend = self.db.get_database_backend();
+        if backend != DatabaseBackend::Postgres {
+            return Ok(None);
+        }
+
+        // NOTE: the number of rows is estimated by the planner statistics,
+        // so that the tables are not scanned on every reconciliation
+        let statement = Statement::from_sql_and_values(
+            backend,
+            r#"SELECT pg_total_relation_size("c"."oid") AS "bytes", GREATEST("c"."reltuples", 0)::BIGINT AS "objects" FROM "pg_class" AS "c" WHERE "c"."relname" = $1 AND "c"."relkind" = 'r' LIMIT 1"#,
+            [name.clone().into()],
+        );
+
+        match self
+            .db
+            .query_one(statement)
+            .await
+            .map_err(|error| anyhow!("failed to get table usage ({name}): {error}"))?
+        {
+            Some(row) => {
+                let bytes: i64 = row.try_get("", "bytes")?;
+                let objects: i64 = row.try_get("", "objects")?;
+                Ok(Some(Usage {
+                    bytes: Byte::from_u64(bytes.max(0) as u64),
+                    objects: Some(objects.max(0) as u64),
+                }))
+            }
+            None => Ok(None),
+        }
+    }
+
     #[instrument(level = Level::INFO, skip(self), err(Display))]
     async fn get_current_table_fields(&self) -> Result<Option<ModelFieldsNativeSpec>> {
         let (name, table_name) = self.get_table_name();
@@ -360,6 +536,129 @@ impl<'model> DatabaseStorageSession<'model> {
         self.db.execute(statement).await?;
         Ok(())
     }
+
+    /// Return the name of the logical replication of the model table into the given model storage.
+    ///
+    /// NOTE: the publication, the subscription and the replication slot share the name.
+    pub fn get_replication_name(&self, target_name: &str) -> String {
+        let (name, _) = self.get_table_name();
+        let hash = RuntimeIden::from_str(format!("{target_name}/{name}"));
+
+        // NOTE: PostgreSQL truncates the identifiers longer than 63 bytes
+        format!("dash_{hash:.48}")
+    }
+
+    /// Publish the model table of the source database for the logical replication.
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn create_publication(&self, name: &str) -> Result<()> {
+        let (model_name, table_name) = self.get_table_name();
+        if !self.is_table_exists().await? {
+            bail!("no such table in the source database: {model_name}")
+        }
+
+        let backend = self.db.get_database_backend();
+        let statement = Statement::from_string(
+            backend,
+            format!(r#"SELECT 1 FROM "pg_publication" WHERE "pubname" = '{name}'"#),
+        );
+        if self.db.query_one(statement).await?.is_some() {
+            return Ok(());
+        }
+
+        let statement = Statement::from_string(
+            backend,
+            format!(r#"CREATE PUBLICATION "{name}" FOR TABLE "{table_name}""#),
+        );
+        self.db
+            .execute(statement)
+            .await
+            .map(|_| ())
+            .map_err(|error| anyhow!("failed to create a publication ({name}): {error}"))
+    }
+
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn delete_publication(&self, name: &str) -> Result<()> {
+        let statement = Statement::from_string(
+            self.db.get_database_backend(),
+            format!(r#"DROP PUBLICATION IF EXISTS "{name}""#),
+        );
+
+        self.db.execute(statement).await?;
+        Ok(())
+    }
+
+    /// Subscribe the publication of the source database into the model table.
+    ///
+    /// The existing rows of the source are copied first, and then the changes are streamed.
+    /// The replication slot is created in the source database with the same name.
+    #[instrument(level = Level::INFO, skip(self, source), err(Display))]
+    pub async fn create_subscription(
+        &self,
+        name: &str,
+        source: &ModelStorageDatabaseSpec,
+    ) -> Result<()> {
+        let backend = self.db.get_database_backend();
+        let statement = Statement::from_string(
+            backend,
+            format!(r#"SELECT 1 FROM "pg_subscription" WHERE "subname" = '{name}'"#),
+        );
+        if self.db.query_one(statement).await?.is_some() {
+            return Ok(());
+        }
+
+        let source_url = DatabaseStorageClient::infer_url(source).replace('\'', "''");
+        let statement = Statement::from_string(
+            backend,
+            format!(
+                r#"CREATE SUBSCRIPTION "{name}" CONNECTION '{source_url}' PUBLICATION "{name}" WITH (copy_data = true)"#
+            ),
+        );
+        self.db
+            .execute(statement)
+            .await
+            .map(|_| ())
+            .map_err(|error| anyhow!("failed to create a subscription ({name}): {error}"))
+    }
+
+    /// Stop the subscription, dropping its replication slot in the source database.
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn delete_subscription(&self, name: &str) -> Result<()> {
+        let statement = Statement::from_string(
+            self.db.get_database_backend(),
+            format!(r#"DROP SUBSCRIPTION IF EXISTS "{name}""#),
+        );
+
+        self.db.execute(statement).await?;
+        Ok(())
+    }
+
+    /// Inspect the replication slot of the source database.
+    ///
+    /// The lag is the size of the WAL which has not been confirmed by the subscriber yet.
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn get_replication_status(
+        &self,
+        name: &str,
+    ) -> Result<ModelStorageBindingReplicationStatus> {
+        let statement = Statement::from_string(
+            self.db.get_database_backend(),
+            format!(
+                r#"SELECT "active", pg_wal_lsn_diff(pg_current_wal_lsn(), "confirmed_flush_lsn")::bigint AS "lag_bytes" FROM "pg_replication_slots" WHERE "slot_name" = '{name}'"#
+            ),
+        );
+
+        // NOTE: the slot is missing if the subscription has not been created yet
+        let (active, lag_bytes) = match self.db.query_one(statement).await? {
+            Some(row) => (row.try_get("", "active")?, row.try_get("", "lag_bytes")?),
+            None => (false, None),
+        };
+        Ok(ModelStorageBindingReplicationStatus {
+            name: name.into(),
+            active,
+            lag_bytes,
+            last_checked: Utc::now(),
+        })
+    }
 }
 
 #[derive(Clone, Debug, Default, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
@@ -0,0 +1,350 @@
+use anyhow::{anyhow, bail, Result};
+use byte_unit::Byte;
+use dash_api::{
+    model::ModelCrd,
+    model_storage_binding::ModelStorageBindingStorageSpec,
+    storage::fs::{ModelStorageFileSystemSourceSpec, ModelStorageFileSystemSpec},
+};
+use k8s_openapi::{
+    api::{
+        batch::v1::{Job, JobSpec},
+        core::v1::{
+            CephFSPersistentVolumeSource, CephFSVolumeSource, Container, EnvVar,
+            HostPathVolumeSource, LocalObjectReference, NFSVolumeSource, ObjectReference,
+            PersistentVolume, PersistentVolumeClaim, PersistentVolumeClaimSpec,
+            PersistentVolumeSpec, PodSpec, PodTemplateSpec, SecretReference, Volume, VolumeMount,
+            VolumeResourceRequirements,
+        },
+    },
+    apimachinery::pkg::{api::resource::Quantity, apis::meta::v1::OwnerReference},
+};
+use kube::{
+    api::{DeleteParams, ObjectMeta},
+    Api, Client, ResourceExt,
+};
+use maplit::btreemap;
+use tracing::{instrument, Level};
+
+pub struct FileSystemStorageClient<'storage> {
+    kube: Client,
+    namespace: String,
+    storage: &'storage ModelStorageFileSystemSpec,
+    storage_name: String,
+}
+
+impl<'storage> FileSystemStorageClient<'storage> {
+    pub fn new(
+        kube: &Client,
+        namespace: &str,
+        storage: ModelStorageBindingStorageSpec<'_, &'storage ModelStorageFileSystemSpec>,
+    ) -> Self {
+        Self {
+            kube: kube.clone(),
+            namespace: namespace.into(),
+            storage: storage.target,
+            storage_name: storage.target_name.into(),
+        }
+    }
+
+    /// Return the root directory of the filesystem as a writable volume.
+    pub(super) fn get_root_volume(&self) -> Volume {
+        let name = "root".to_string();
+        let path = self.storage.source.path().to_string();
+
+        match &self.storage.source {
+            ModelStorageFileSystemSourceSpec::CephFs(spec) => Volume {
+                name,
+                cephfs: Some(CephFSVolumeSource {
+                    monitors: spec.monitors.clone(),
+                    path: Some(path),
+                    secret_ref: spec
+                        .secret_name
+                        .clone()
+                        .map(|name| LocalObjectReference { name }),
+                    user: spec.user.clone(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ModelStorageFileSystemSourceSpec::HostPath(_) => Volume {
+                name,
+                host_path: Some(HostPathVolumeSource {
+                    path,
+                    type_: Some("DirectoryOrCreate".into()),
+                }),
+                ..Default::default()
+            },
+            ModelStorageFileSystemSourceSpec::Nfs(spec) => Volume {
+                name,
+                nfs: Some(NFSVolumeSource {
+                    path,
+                    read_only: None,
+                    server: spec.server.clone(),
+                }),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn get_session<'model>(
+        &self,
+        model: &'model ModelCrd,
+    ) -> FileSystemStorageSession<'_, 'model> {
+        FileSystemStorageSession {
+            client: self,
+            model,
+        }
+    }
+}
+
+pub struct FileSystemStorageSession<'client, 'model> {
+    client: &'client FileSystemStorageClient<'client>,
+    model: &'model ModelCrd,
+}
+
+impl<'client, 'model> FileSystemStorageSession<'client, 'model> {
+    const DEFAULT_CAPACITY: &'static str = "1Gi";
+    const MOUNT_PATH: &'static str = "/mnt/dash";
+
+    /// Return the name of the persistent volume claim, which is mounted by the model users.
+    pub fn get_volume_claim_name(&self) -> String {
+        format!("dash-model-{}", self.model.name_any())
+    }
+
+    fn get_volume_name(&self) -> String {
+        format!(
+            "dash-{namespace}-{storage}-{model}",
+            namespace = &self.client.namespace,
+            storage = &self.client.storage_name,
+            model = self.model.name_any(),
+        )
+    }
+
+    fn get_directory(&self) -> String {
+        format!(
+            "{root}/{model}",
+            root = self.client.storage.source.path().trim_end_matches('/'),
+            model = self.model.name_any(),
+        )
+    }
+
+    #[instrument(level = Level::INFO, skip(self, owner_references), err(Display))]
+    pub async fn create_volume(
+        &self,
+        owner_references: Vec<OwnerReference>,
+        quota: Option<Byte>,
+    ) -> Result<()> {
+        let capacity = quota
+            .map(|quota| Quantity(quota.as_u128().to_string()))
+            .or_else(|| self.client.storage.capacity.clone())
+            .unwrap_or_else(|| Quantity(Self::DEFAULT_CAPACITY.into()));
+        let access_modes = if self.client.storage.read_only {
+            vec!["ReadOnlyMany".into()]
+        } else {
+            vec!["ReadWriteMany".into()]
+        };
+
+        // the model directory should exist before being mounted
+        self.get_or_create_directory_job("create", "mkdir -p")
+            .await?;
+
+        let claim_name = self.get_volume_claim_name();
+        let volume_name = self.get_volume_name();
+        let labels = btreemap! {
+            "dash.ulagbulag.io/modelstorage-name".into() => self.client.storage_name.clone(),
+            "dash.ulagbulag.io/model-name".into() => self.model.name_any(),
+        };
+
+        let api = Api::<PersistentVolume>::all(self.client.kube.clone());
+        super::object::get_or_create(&api, "persistent volume", &volume_name, || {
+            PersistentVolume {
+                metadata: ObjectMeta {
+                    labels: Some(labels.clone()),
+                    name: Some(volume_name.clone()),
+                    ..Default::default()
+                },
+                spec: Some(PersistentVolumeSpec {
+                    access_modes: Some(access_modes.clone()),
+                    capacity: Some(btreemap! {
+                        "storage".into() => capacity.clone(),
+                    }),
+                    claim_ref: Some(ObjectReference {
+                        name: Some(claim_name.clone()),
+                        namespace: Some(self.client.namespace.clone()),
+                        ..Default::default()
+                    }),
+                    mount_options: Some(self.client.storage.mount_options.clone())
+                        .filter(|options| !options.is_empty()),
+                    // NOTE: the data is deleted by the operator with the deletion policy
+                    persistent_volume_reclaim_policy: Some("Retain".into()),
+                    storage_class_name: Some(String::default()),
+                    ..self.get_persistent_volume_source()
+                }),
+                status: None,
+            }
+        })
+        .await?;
+
+        let api = Api::<PersistentVolumeClaim>::namespaced(
+            self.client.kube.clone(),
+            &self.client.namespace,
+        );
+        super::object::get_or_create(&api, "persistent volume claim", &claim_name, || {
+            PersistentVolumeClaim {
+                metadata: ObjectMeta {
+                    labels: Some(labels.clone()),
+                    name: Some(claim_name.clone()),
+                    namespace: Some(self.client.namespace.clone()),
+                    owner_references: Some(owner_references),
+                    ..Default::default()
+                },
+                spec: Some(PersistentVolumeClaimSpec {
+                    access_modes: Some(access_modes),
+                    resources: Some(VolumeResourceRequirements {
+                        requests: Some(btreemap! {
+                            "storage".into() => capacity,
+                        }),
+                        ..Default::default()
+                    }),
+                    storage_class_name: Some(String::default()),
+                    volume_name: Some(volume_name.clone()),
+                    ..Default::default()
+                }),
+                status: None,
+            }
+        })
+        .await
+        .map(|_| ())
+    }
+
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn delete_volume(&self, delete_data: bool) -> Result<()> {
+        let claim_name = self.get_volume_claim_name();
+        let volume_name = self.get_volume_name();
+        let dp = DeleteParams::background();
+
+        let api = Api::<PersistentVolumeClaim>::namespaced(
+            self.client.kube.clone(),
+            &self.client.namespace,
+        );
+        match api.delete(&claim_name, &dp).await {
+            Ok(_) => (),
+            Err(::kube::Error::Api(error)) if error.code == 404 => (),
+            Err(error) => bail!("failed to delete persistent volume claim ({claim_name}): {error}"),
+        }
+
+        let api = Api::<PersistentVolume>::all(self.client.kube.clone());
+        match api.delete(&volume_name, &dp).await {
+            Ok(_) => (),
+            Err(::kube::Error::Api(error)) if error.code == 404 => (),
+            Err(error) => bail!("failed to delete persistent volume ({volume_name}): {error}"),
+        }
+
+        if delete_data {
+            self.get_or_create_directory_job("delete", "rm -rf")
+                .await
+                .map_err(|error| anyhow!("failed to delete a directory ({volume_name}): {error}"))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn get_persistent_volume_source(&self) -> PersistentVolumeSpec {
+        let path = self.get_directory();
+        let read_only = Some(self.client.storage.read_only);
+
+        match &self.client.storage.source {
+            ModelStorageFileSystemSourceSpec::CephFs(spec) => PersistentVolumeSpec {
+                cephfs: Some(CephFSPersistentVolumeSource {
+                    monitors: spec.monitors.clone(),
+                    path: Some(path),
+                    read_only,
+                    secret_ref: spec.secret_name.clone().map(|name| SecretReference {
+                        name: Some(name),
+                        namespace: Some(self.client.namespace.clone()),
+                    }),
+                    user: spec.user.clone(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ModelStorageFileSystemSourceSpec::HostPath(_) => PersistentVolumeSpec {
+                host_path: Some(HostPathVolumeSource {
+                    path,
+                    type_: Some("Directory".into()),
+                }),
+                ..Default::default()
+            },
+            ModelStorageFileSystemSourceSpec::Nfs(spec) => PersistentVolumeSpec {
+                nfs: Some(NFSVolumeSource {
+                    path,
+                    read_only,
+                    server: spec.server.clone(),
+                }),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    async fn get_or_create_directory_job(&self, command: &str, program: &str) -> Result<()> {
+        if self.client.storage.read_only {
+            return Ok(());
+        }
+
+        let model_name = self.model.name_any();
+        let name = format!("{volume}-{command}", volume = self.get_volume_name(),);
+        let labels = btreemap! {
+            "dash.ulagbulag.io/modelstorage-name".into() => self.client.storage_name.clone(),
+            "dash.ulagbulag.io/modelstorage-filesystem-command".into() => command.into(),
+        };
+
+        let api = Api::<Job>::namespaced(self.client.kube.clone(), &self.client.namespace);
+        super::object::get_or_create(&api, "job", &name, || Job {
+            metadata: ObjectMeta {
+                labels: Some(labels.clone()),
+                name: Some(name.clone()),
+                namespace: Some(self.client.namespace.clone()),
+                ..Default::default()
+            },
+            spec: Some(JobSpec {
+                ttl_seconds_after_finished: Some(30),
+                template: PodTemplateSpec {
+                    metadata: Some(ObjectMeta {
+                        labels: Some(labels),
+                        ..Default::default()
+                    }),
+                    spec: Some(PodSpec {
+                        containers: vec![Container {
+                            name: "filesystem".into(),
+                            image: Some("docker.io/library/busybox:latest".into()),
+                            command: Some(vec!["/bin/sh".into(), "-c".into()]),
+                            args: Some(vec![format!(
+                                r#"exec {program} "{mount_path}/${{MODEL_NAME}}""#,
+                                mount_path = Self::MOUNT_PATH,
+                            )]),
+                            env: Some(vec![EnvVar {
+                                name: "MODEL_NAME".into(),
+                                value: Some(model_name),
+                                value_from: None,
+                            }]),
+                            volume_mounts: Some(vec![VolumeMount {
+                                name: "root".into(),
+                                mount_path: Self::MOUNT_PATH.into(),
+                                ..Default::default()
+                            }]),
+                            ..Default::default()
+                        }],
+                        restart_policy: Some("OnFailure".into()),
+                        volumes: Some(vec![self.client.get_root_volume()]),
+                        ..Default::default()
+                    }),
+                },
+                ..Default::default()
+            }),
+            status: None,
+        })
+        .await
+        .map(|_| ())
+    }
+}
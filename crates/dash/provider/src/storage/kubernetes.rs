@@ -1,10 +1,14 @@
-use std::time::Duration;
+use std::{fmt, time::Duration};
 
 use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Utc};
 use dash_api::{
+    approval::{ApprovalCrd, ApprovalDecisionSpec, ApprovalPolicySpec},
+    function::FunctionCrd,
+    maintenance::MaintenanceWindowCrd,
     model::{
         ModelCrd, ModelCustomResourceDefinitionRefSpec, ModelFieldsNativeSpec, ModelSpec,
-        ModelState,
+        ModelState, ModelVersion,
     },
     model_claim::{ModelClaimCrd, ModelClaimState},
     model_storage_binding::{
@@ -13,7 +17,9 @@ use dash_api::{
     },
     storage::{ModelStorageCrd, ModelStorageKindSpec, ModelStorageState},
     task::{TaskActorSourceConfigMapRefSpec, TaskCrd, TaskState},
+    tenant::TenantConfigCrd,
 };
+use dash_provider_api::data::{ResourceList, ResourceListOptions};
 use futures::{stream::FuturesUnordered, TryStreamExt};
 use itertools::Itertools;
 use k8s_openapi::{
@@ -24,13 +30,13 @@ use k8s_openapi::{
     ClusterResourceScope, NamespaceResourceScope,
 };
 use kube::{
-    api::{DeleteParams, ListParams, PostParams},
+    api::{DeleteParams, ListParams, Patch, PatchParams, PostParams},
     core::{object::HasStatus, DynamicObject, ObjectMeta},
     discovery, Api, Client, Resource, ResourceExt,
 };
 use maplit::btreemap;
-use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
 use tokio::time::sleep;
 use tracing::{instrument, Level};
 
@@ -100,6 +106,75 @@ impl<'namespace, 'kube> KubernetesStorageClient<'namespace, 'kube> {
         }
     }
 
+    /// Load a page of the resources, which are filtered by the labels and the state.
+    ///
+    /// If only the metadata fields are requested, the resources are listed as metadata only.
+    async fn load_page<K, State>(
+        &self,
+        options: &ResourceListOptions,
+        state: impl Fn(&K) -> Option<State>,
+    ) -> Result<ResourceList<Value>>
+    where
+        K: Clone
+            + fmt::Debug
+            + DeserializeOwned
+            + Serialize
+            + Resource<Scope = NamespaceResourceScope>,
+        <K as Resource>::DynamicType: Default,
+        State: ToString,
+    {
+        let api = self.api_namespaced::<K>();
+        let mut lp = ListParams::default().limit(options.page_limit());
+        if let Some(token) = options.continue_token.as_deref() {
+            lp = lp.continue_token(token);
+        }
+        if let Some(selector) = options.label_selector.as_deref() {
+            lp = lp.labels(selector);
+        }
+
+        let is_metadata_only = options.state.is_none()
+            && options.fields.as_deref().is_some_and(|fields| {
+                fields
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|path| !path.is_empty())
+                    .all(|path| path == "metadata" || path.starts_with("metadata."))
+            });
+
+        let (items, continue_token) = if is_metadata_only {
+            let list = api.list_metadata(&lp).await?;
+            let items = list
+                .items
+                .into_iter()
+                .map(|mut item| {
+                    item.metadata.managed_fields = None;
+                    ::serde_json::to_value(item)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            (items, list.metadata.continue_)
+        } else {
+            let list = api.list(&lp).await?;
+            let items = list
+                .items
+                .into_iter()
+                .filter(|item| options.is_state_matched(state(item)))
+                .map(|mut item| {
+                    item.meta_mut().managed_fields = None;
+                    ::serde_json::to_value(item)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            (items, list.metadata.continue_)
+        };
+
+        Ok(ResourceList {
+            items: items
+                .into_iter()
+                .map(|item| options.project(item))
+                .collect(),
+            continue_token: continue_token.filter(|token| !token.is_empty()),
+        })
+    }
+
     #[instrument(level = Level::INFO, skip(self), err(Display))]
     pub async fn load_config_map<'f>(
         &self,
@@ -119,6 +194,28 @@ impl<'namespace, 'kube> KubernetesStorageClient<'namespace, 'kube> {
         }
     }
 
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn load_config_map_binary<'f>(
+        &self,
+        spec: &'f TaskActorSourceConfigMapRefSpec,
+    ) -> Result<(&'f str, Vec<u8>)> {
+        let TaskActorSourceConfigMapRefSpec { name, path } = spec;
+
+        let api = self.api_namespaced::<ConfigMap>();
+        let config_map = api.get(name).await?;
+
+        match config_map
+            .binary_data
+            .and_then(|mut binary_data| binary_data.remove(path))
+        {
+            Some(content) => Ok((path, content.0)),
+            None => bail!(
+                "no such binary file in ConfigMap: {path:?} in {namespace}::{name}",
+                namespace = self.namespace,
+            ),
+        }
+    }
+
     #[instrument(level = Level::INFO, skip(self, parsed), err(Display))]
     pub async fn load_custom_resource(
         &self,
@@ -138,9 +235,10 @@ impl<'namespace, 'kube> KubernetesStorageClient<'namespace, 'kube> {
         &self,
         spec: &ModelCustomResourceDefinitionRefSpec,
         parsed: &ModelFieldsNativeSpec,
+        limit: usize,
     ) -> Result<Vec<Value>> {
         let api = self.api_custom_resource(spec, None).await?;
-        let lp = ListParams::default();
+        let lp = ListParams::default().limit(limit.try_into().unwrap_or(u32::MAX));
         api.list(&lp).await.map_err(Into::into).and_then(|list| {
             list.items
                 .into_iter()
@@ -209,6 +307,17 @@ impl<'namespace, 'kube> KubernetesStorageClient<'namespace, 'kube> {
             .ok_or_else(|| anyhow!("no such model: {name:?}"))
     }
 
+    /// Return the schema versions of the model, from the oldest one.
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn load_model_history(&self, name: &str) -> Result<Vec<ModelVersion>> {
+        let api = self.api_namespaced::<ModelCrd>();
+        let model = api.get(name).await?;
+        Ok(model
+            .status
+            .map(|status| status.history)
+            .unwrap_or_default())
+    }
+
     #[instrument(level = Level::INFO, skip(self), err(Display))]
     pub async fn load_model_all(&self) -> Result<Vec<ResourceRef>> {
         let api = self.api_namespaced::<ModelCrd>();
@@ -232,6 +341,17 @@ impl<'namespace, 'kube> KubernetesStorageClient<'namespace, 'kube> {
             .collect())
     }
 
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn load_model_page(
+        &self,
+        options: &ResourceListOptions,
+    ) -> Result<ResourceList<Value>> {
+        self.load_page(options, |model: &ModelCrd| {
+            model.status().map(|status| status.state)
+        })
+        .await
+    }
+
     #[instrument(level = Level::INFO, skip(self), err(Display))]
     pub async fn load_model_or_create_as_dynamic(
         &self,
@@ -288,6 +408,108 @@ impl<'namespace, 'kube> KubernetesStorageClient<'namespace, 'kube> {
             Some(_) | None => bail!("model claim is not ready: {name:?}"),
         }
     }
+
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn load_model_claims_all(&self) -> Result<Vec<ModelClaimCrd>> {
+        let api = self.api_namespaced::<ModelClaimCrd>();
+        let lp = ListParams::default();
+
+        api.list(&lp)
+            .await
+            .map(|list| list.items)
+            .map_err(Into::into)
+    }
+}
+
+impl<'namespace, 'kube> KubernetesStorageClient<'namespace, 'kube> {
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn load_approval(&self, name: &str) -> Result<Option<ApprovalCrd>> {
+        let api = self.api_namespaced::<ApprovalCrd>();
+        api.get_opt(name).await.map_err(Into::into)
+    }
+
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn load_approvals_all(&self) -> Result<Vec<ApprovalCrd>> {
+        let api = self.api_namespaced::<ApprovalCrd>();
+        let lp = ListParams::default();
+
+        api.list(&lp)
+            .await
+            .map(|list| list.items)
+            .map_err(Into::into)
+    }
+
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn load_approval_policy(&self) -> Result<ApprovalPolicySpec> {
+        self.load_tenant_config().await.map(|config| {
+            config
+                .map(|config| config.spec.approval)
+                .unwrap_or_default()
+        })
+    }
+
+    #[instrument(level = Level::INFO, skip(self, approval), err(Display))]
+    pub async fn create_approval(&self, approval: &ApprovalCrd) -> Result<ApprovalCrd> {
+        let api = self.api_namespaced::<ApprovalCrd>();
+        let pp = PostParams::default();
+        api.create(&pp, approval).await.map_err(Into::into)
+    }
+
+    #[instrument(level = Level::INFO, skip(self, decision), err(Display))]
+    pub async fn decide_approval(
+        &self,
+        name: &str,
+        decision: ApprovalDecisionSpec,
+    ) -> Result<ApprovalCrd> {
+        let api = self.api_namespaced::<ApprovalCrd>();
+        let patch = Patch::Merge(json!({
+            "spec": {
+                "decision": decision,
+            },
+        }));
+        let pp = PatchParams::default();
+        api.patch(name, &pp, &patch).await.map_err(Into::into)
+    }
+
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn delete_approval(&self, name: &str) -> Result<()> {
+        let api = self.api_namespaced::<ApprovalCrd>();
+        let dp = DeleteParams::default();
+        api.delete(name, &dp).await.map(|_| ()).map_err(Into::into)
+    }
+}
+
+impl<'namespace, 'kube> KubernetesStorageClient<'namespace, 'kube> {
+    /// Load the maintenance window which freezes the namespace, closing the last if many.
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn load_maintenance_window(&self) -> Result<Option<MaintenanceWindowCrd>> {
+        let api = self.api_all::<MaintenanceWindowCrd>();
+        let lp = ListParams::default();
+
+        let windows = match api.list(&lp).await {
+            Ok(list) => list.items,
+            // NOTE: the maintenance windows are not installed yet
+            Err(::kube::Error::Api(error)) if error.code == 404 => return Ok(None),
+            Err(error) => bail!("failed to load maintenance windows: {error}"),
+        };
+
+        let now = Utc::now();
+        Ok(windows
+            .into_iter()
+            .filter(|window| window.spec.is_freezing(self.namespace, now))
+            .max_by_key(|window| window.spec.ends_at.unwrap_or(DateTime::<Utc>::MAX_UTC)))
+    }
+}
+
+impl<'namespace, 'kube> KubernetesStorageClient<'namespace, 'kube> {
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn load_tenant_config(&self) -> Result<Option<TenantConfigCrd>> {
+        let api = self.api_namespaced::<TenantConfigCrd>();
+        api.get_opt(TenantConfigCrd::NAME).await.map_err(|error| {
+            let namespace = self.namespace;
+            anyhow!("failed to load tenant config ({namespace}): {error}")
+        })
+    }
 }
 
 impl<'namespace, 'kube> KubernetesStorageClient<'namespace, 'kube> {
@@ -329,12 +551,37 @@ impl<'namespace, 'kube> KubernetesStorageClient<'namespace, 'kube> {
             })
             .map_err(Into::into)
     }
+
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn load_model_storage_page(
+        &self,
+        options: &ResourceListOptions,
+    ) -> Result<ResourceList<Value>> {
+        self.load_page(options, |storage: &ModelStorageCrd| {
+            storage.status().map(|status| status.state)
+        })
+        .await
+    }
+}
+
+impl<'namespace, 'kube> KubernetesStorageClient<'namespace, 'kube> {
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn load_function_page(
+        &self,
+        options: &ResourceListOptions,
+    ) -> Result<ResourceList<Value>> {
+        self.load_page(options, |function: &FunctionCrd| {
+            function.status().map(|status| status.state)
+        })
+        .await
+    }
 }
 
 impl<'namespace, 'kube> KubernetesStorageClient<'namespace, 'kube> {
     pub async fn create_model_storage_binding(
         &self,
         field_manager: &str,
+        name: String,
         model_name: String,
         storage: ModelStorageBindingStorageKind<String>,
         resources: Option<ResourceRequirements>,
@@ -347,7 +594,7 @@ impl<'namespace, 'kube> KubernetesStorageClient<'namespace, 'kube> {
         };
         let data = ModelStorageBindingCrd {
             metadata: ObjectMeta {
-                name: Some(model_name.clone()),
+                name: Some(name),
                 namespace: Some(self.namespace.into()),
                 labels: Some(btreemap! {
                     "dash.ulagbulag.io/managed-by".into() => field_manager.into(),
@@ -366,6 +613,29 @@ impl<'namespace, 'kube> KubernetesStorageClient<'namespace, 'kube> {
         api.create(&pp, &data).await.map_err(Into::into)
     }
 
+    #[instrument(level = Level::INFO, skip(self, resources), err(Display))]
+    pub async fn update_model_storage_binding_resources(
+        &self,
+        field_manager: &str,
+        name: &str,
+        resources: &ResourceRequirements,
+    ) -> Result<()> {
+        let api = self.api_namespaced::<ModelStorageBindingCrd>();
+        let patch = Patch::Merge(json!({
+            "spec": {
+                "resources": resources,
+            },
+        }));
+        let pp = PatchParams::apply(field_manager);
+
+        api.patch(name, &pp, &patch)
+            .await
+            .map(|_| ())
+            .map_err(|error| {
+                anyhow!("failed to update model storage binding resources ({name}): {error}")
+            })
+    }
+
     #[instrument(level = Level::INFO, skip(self), err(Display))]
     pub async fn delete_model_storage_binding_by_model(&self, model_name: &str) -> Result<()> {
         let api = self.api_namespaced::<ModelStorageBindingCrd>();
@@ -417,7 +687,13 @@ impl<'namespace, 'kube> KubernetesStorageClient<'namespace, 'kube> {
             .filter(|binding| {
                 binding
                     .status()
-                    .map(|status| matches!(status.state, ModelStorageBindingState::Ready))
+                    // NOTE: the migrating bindings are still bound to the last model storages
+                    .map(|status| {
+                        matches!(
+                            status.state,
+                            ModelStorageBindingState::Ready | ModelStorageBindingState::Migrating,
+                        )
+                    })
                     .unwrap_or_default()
             })
             .collect())
@@ -498,6 +774,17 @@ impl<'namespace, 'kube> KubernetesStorageClient<'namespace, 'kube> {
             .collect())
     }
 
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn load_task_page(
+        &self,
+        options: &ResourceListOptions,
+    ) -> Result<ResourceList<Value>> {
+        self.load_page(options, |task: &TaskCrd| {
+            task.status().map(|status| status.state)
+        })
+        .await
+    }
+
     #[instrument(level = Level::INFO, skip(self), err(Display))]
     pub async fn load_task_all_by_model(&self, model_name: &str) -> Result<Vec<TaskCrd>> {
         let api = self.api_namespaced::<TaskCrd>();
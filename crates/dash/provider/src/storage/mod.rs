@@ -1,6 +1,10 @@
+mod artifact;
+mod benchmark;
 mod db;
+mod fs;
 mod kubernetes;
 mod object;
+mod transfer;
 
 use anyhow::{bail, Result};
 use async_trait::async_trait;
@@ -13,9 +17,11 @@ use dash_api::model_storage_binding::{
     ModelStorageBindingStatus, ModelStorageBindingStorageSourceSpec, ModelStorageBindingStorageSpec,
 };
 use dash_api::storage::db::ModelStorageDatabaseSpec;
+use dash_api::storage::fs::ModelStorageFileSystemSpec;
 use dash_api::storage::kubernetes::ModelStorageKubernetesSpec;
 use dash_api::storage::object::ModelStorageObjectSpec;
 use dash_api::storage::{ModelStorageKindSpec, ModelStorageSpec};
+use dash_provider_api::data::ListOptions;
 use kube::api::ObjectMeta;
 use kube::ResourceExt;
 use kube::{core::object::HasStatus, Client};
@@ -23,16 +29,20 @@ use serde_json::Value;
 use tracing::{instrument, Level};
 
 pub use self::{
+    artifact::ArtifactRef,
+    benchmark::StorageBenchmarkClient,
     db::DatabaseStorageClient,
+    fs::{FileSystemStorageClient, FileSystemStorageSession},
     kubernetes::KubernetesStorageClient,
-    object::{ObjectStorageClient, ObjectStorageSession},
+    object::{ObjectStorageClient, ObjectStorageSession, ObjectStorageSyncMetrics},
+    transfer::{ObjectTransferOptions, ObjectTransferReport},
 };
 
 #[async_trait]
 pub trait Storage {
     async fn get(&self, model_name: &str, ref_name: &str) -> Result<Value>;
 
-    async fn list(&self, model_name: &str) -> Result<Vec<Value>>;
+    async fn list(&self, model_name: &str, options: &ListOptions) -> Result<Vec<Value>>;
 }
 
 pub struct StorageClient<'namespace, 'kube> {
@@ -77,7 +87,8 @@ impl<'namespace, 'kube> Storage for StorageClient<'namespace, 'kube> {
     }
 
     #[instrument(level = Level::INFO, skip(self), err(Display))]
-    async fn list(&self, model_name: &str) -> Result<Vec<Value>> {
+    async fn list(&self, model_name: &str, options: &ListOptions) -> Result<Vec<Value>> {
+        let limit = options.fetch_limit();
         let model = self.get_model(model_name).await?;
         let mut items = vec![];
         for (_, storage) in self.get_model_storage_bindings(model_name).await? {
@@ -103,10 +114,10 @@ impl<'namespace, 'kube> Storage for StorageClient<'namespace, 'kube> {
                     target,
                     target_name,
                 };
-                items.append(&mut self.list_by_storage(storage, &model).await?);
+                items.append(&mut self.list_by_storage(storage, &model, limit).await?);
             }
         }
-        Ok(items)
+        Ok(options.paginate(items))
     }
 }
 
@@ -148,6 +159,15 @@ impl<'namespace, 'kube> StorageClient<'namespace, 'kube> {
                 self.get_by_storage_with_database(storage, model, ref_name)
                     .await
             }
+            ModelStorageKindSpec::FileSystem(target) => {
+                let storage = ModelStorageBindingStorageSpec {
+                    source: assert_source_is_none(storage.source, "FileSystem")?,
+                    source_binding_name: storage.source_binding_name,
+                    target,
+                    target_name: storage.target_name,
+                };
+                self.get_by_storage_with_file_system(storage, model, ref_name)
+            }
             ModelStorageKindSpec::Kubernetes(target) => {
                 let storage = ModelStorageBindingStorageSpec {
                     source: assert_source_is_none(storage.source, "Kubernetes")?,
@@ -163,6 +183,7 @@ impl<'namespace, 'kube> StorageClient<'namespace, 'kube> {
                     source: assert_source_is_same(storage.source, "ObjectStorage", |source| {
                         match &source.kind {
                             ModelStorageKindSpec::Database(_) => Err("Database"),
+                            ModelStorageKindSpec::FileSystem(_) => Err("FileSystem"),
                             ModelStorageKindSpec::Kubernetes(_) => Err("Kubernetes"),
                             ModelStorageKindSpec::ObjectStorage(source) => Ok(source),
                         }
@@ -191,6 +212,16 @@ impl<'namespace, 'kube> StorageClient<'namespace, 'kube> {
             .await
     }
 
+    fn get_by_storage_with_file_system(
+        &self,
+        _storage: ModelStorageBindingStorageSpec<'_, &ModelStorageFileSystemSpec>,
+        _model: &ModelCrd,
+        _ref_name: &str,
+    ) -> Result<Option<Value>> {
+        // NOTE: the files are accessed by mounting the volume, not by the values
+        Ok(None)
+    }
+
     #[instrument(level = Level::INFO, skip(self, storage, model), fields(model.name = %model.name_any(), model.namespace = model.namespace()), err(Display))]
     async fn get_by_storage_with_kubernetes(
         &self,
@@ -271,6 +302,7 @@ impl<'namespace, 'kube> StorageClient<'namespace, 'kube> {
         &self,
         storage: ModelStorageBindingStorageSpec<'_, &ModelStorageSpec>,
         model: &ModelCrd,
+        limit: usize,
     ) -> Result<Vec<Value>> {
         match &storage.target.kind {
             ModelStorageKindSpec::Database(target) => {
@@ -280,7 +312,17 @@ impl<'namespace, 'kube> StorageClient<'namespace, 'kube> {
                     target,
                     target_name: storage.target_name,
                 };
-                self.list_by_storage_with_database(storage, model).await
+                self.list_by_storage_with_database(storage, model, limit)
+                    .await
+            }
+            ModelStorageKindSpec::FileSystem(target) => {
+                let storage = ModelStorageBindingStorageSpec {
+                    source: assert_source_is_none(storage.source, "FileSystem")?,
+                    source_binding_name: storage.source_binding_name,
+                    target,
+                    target_name: storage.target_name,
+                };
+                self.list_by_storage_with_file_system(storage, model, limit)
             }
             ModelStorageKindSpec::Kubernetes(target) => {
                 let storage = ModelStorageBindingStorageSpec {
@@ -289,13 +331,15 @@ impl<'namespace, 'kube> StorageClient<'namespace, 'kube> {
                     target,
                     target_name: storage.target_name,
                 };
-                self.list_by_storage_with_kubernetes(storage, model).await
+                self.list_by_storage_with_kubernetes(storage, model, limit)
+                    .await
             }
             ModelStorageKindSpec::ObjectStorage(target) => {
                 let storage = ModelStorageBindingStorageSpec {
                     source: assert_source_is_same(storage.source, "ObjectStorage", |source| {
                         match &source.kind {
                             ModelStorageKindSpec::Database(_) => Err("Database"),
+                            ModelStorageKindSpec::FileSystem(_) => Err("FileSystem"),
                             ModelStorageKindSpec::Kubernetes(_) => Err("Kubernetes"),
                             ModelStorageKindSpec::ObjectStorage(source) => Ok(source),
                         }
@@ -304,7 +348,8 @@ impl<'namespace, 'kube> StorageClient<'namespace, 'kube> {
                     target,
                     target_name: storage.target_name,
                 };
-                self.list_by_storage_with_object(storage, model).await
+                self.list_by_storage_with_object(storage, model, limit)
+                    .await
             }
         }
     }
@@ -314,26 +359,38 @@ impl<'namespace, 'kube> StorageClient<'namespace, 'kube> {
         &self,
         storage: ModelStorageBindingStorageSpec<'_, &ModelStorageDatabaseSpec>,
         model: &ModelCrd,
+        limit: usize,
     ) -> Result<Vec<Value>> {
         DatabaseStorageClient::try_new(storage.target)
             .await?
             .get_session(model)
-            .get_list()
+            .get_list(limit)
             .await
     }
 
+    fn list_by_storage_with_file_system(
+        &self,
+        _storage: ModelStorageBindingStorageSpec<'_, &ModelStorageFileSystemSpec>,
+        _model: &ModelCrd,
+        _limit: usize,
+    ) -> Result<Vec<Value>> {
+        // NOTE: the files are accessed by mounting the volume, not by the values
+        Ok(Default::default())
+    }
+
     #[instrument(level = Level::INFO, skip(self, storage), fields(model.name = %model.name_any(), model.namespace = model.namespace()), err(Display))]
     async fn list_by_storage_with_kubernetes(
         &self,
         storage: ModelStorageBindingStorageSpec<'_, &ModelStorageKubernetesSpec>,
         model: &ModelCrd,
+        limit: usize,
     ) -> Result<Vec<Value>> {
         let ModelStorageKubernetesSpec {} = storage.target;
         match &model.spec {
             ModelSpec::Dynamic {} => Ok(Default::default()),
             ModelSpec::Fields(_) => Ok(Default::default()),
             ModelSpec::CustomResourceDefinitionRef(spec) => {
-                self.list_custom_resource(model, spec).await
+                self.list_custom_resource(model, spec, limit).await
             }
         }
     }
@@ -343,11 +400,12 @@ impl<'namespace, 'kube> StorageClient<'namespace, 'kube> {
         &self,
         storage: ModelStorageBindingStorageSpec<'_, &ModelStorageObjectSpec>,
         model: &ModelCrd,
+        limit: usize,
     ) -> Result<Vec<Value>> {
         ObjectStorageClient::try_new(self.kube, self.namespace, None, storage, None)
             .await?
             .get_session(self.kube, self.namespace, model)
-            .get_list()
+            .get_list(limit)
             .await
     }
 
@@ -356,6 +414,7 @@ impl<'namespace, 'kube> StorageClient<'namespace, 'kube> {
         &self,
         model: &ModelCrd,
         spec: &ModelCustomResourceDefinitionRefSpec,
+        limit: usize,
     ) -> Result<Vec<Value>> {
         let parsed = get_model_fields_parsed(model);
 
@@ -363,7 +422,7 @@ impl<'namespace, 'kube> StorageClient<'namespace, 'kube> {
             namespace: self.namespace,
             kube: self.kube,
         };
-        storage.load_custom_resource_all(spec, parsed).await
+        storage.load_custom_resource_all(spec, parsed, limit).await
     }
 }
 
@@ -9,20 +9,21 @@ use dash_api::{
     model::{ModelCrd, ModelCustomResourceDefinitionRefSpec},
     model_storage_binding::{
         ModelStorageBindingStorageSourceSpec, ModelStorageBindingStorageSpec,
-        ModelStorageBindingSyncPolicy, ModelStorageBindingSyncPolicyPull,
-        ModelStorageBindingSyncPolicyPush,
+        ModelStorageBindingSyncPolicy, ModelStorageBindingSyncPolicyConflict,
+        ModelStorageBindingSyncPolicyPull, ModelStorageBindingSyncPolicyPush,
     },
     model_user::ModelUserAccessTokenSecretRefSpec,
     storage::{
         object::{
             get_object_storage_owned_endpoint, ModelStorageObjectBorrowedSpec,
-            ModelStorageObjectClonedSpec, ModelStorageObjectOwnedReplicationSpec,
-            ModelStorageObjectOwnedSpec, ModelStorageObjectRefSpec, ModelStorageObjectSpec,
+            ModelStorageObjectClonedSpec, ModelStorageObjectLifecycleRuleSpec,
+            ModelStorageObjectOwnedReplicationSpec, ModelStorageObjectOwnedSpec,
+            ModelStorageObjectRefSpec, ModelStorageObjectSpec,
         },
         ModelStorageCrd,
     },
 };
-use dash_provider_api::data::Capacity;
+use dash_provider_api::data::{Capacity, Usage};
 use futures::{stream::FuturesUnordered, FutureExt, TryFutureExt, TryStreamExt};
 use k8s_openapi::{
     api::{
@@ -59,12 +60,16 @@ use kube::{
 use maplit::btreemap;
 use minio::s3::{
     args::{
-        BucketExistsArgs, DeleteBucketReplicationArgs, GetBucketReplicationArgs, MakeBucketArgs,
+        BucketExistsArgs, DeleteBucketLifecycleArgs, DeleteBucketReplicationArgs,
+        GetBucketReplicationArgs, ListBucketsArgs, MakeBucketArgs, SetBucketLifecycleArgs,
         SetBucketReplicationArgs, SetBucketVersioningArgs,
     },
     creds::{Credentials, Provider, StaticProvider},
     http::BaseUrl,
-    types::{Destination, ReplicationConfig, ReplicationRule, S3Api},
+    types::{
+        Destination, Filter, LifecycleConfig, LifecycleRule, ReplicationConfig, ReplicationRule,
+        S3Api,
+    },
     utils::Multimap,
 };
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
@@ -220,7 +225,10 @@ impl<'model> ObjectStorageSession {
         storage: &ModelStorageObjectBorrowedSpec,
         prometheus_url: Option<&str>,
     ) -> Result<Self> {
-        let ModelStorageObjectBorrowedSpec { reference } = storage;
+        let ModelStorageObjectBorrowedSpec {
+            reference,
+            lifecycle: _,
+        } = storage;
         Self::load_storage_provider_by_reference(kube, namespace, name, reference, prometheus_url)
             .await
     }
@@ -968,6 +976,33 @@ impl<'model> ObjectStorageSession {
         let admin = MinioAdminClient { storage: self };
         admin.get_capacity_global().await
     }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    pub async fn list_buckets(&self) -> Result<Vec<String>> {
+        self.client
+            .list_buckets(&ListBucketsArgs::new())
+            .await
+            .map(|response| {
+                response
+                    .buckets
+                    .into_iter()
+                    .map(|bucket| bucket.name)
+                    .collect()
+            })
+            .map_err(|error| {
+                anyhow!(
+                    "failed to list buckets ({name}: {origin}): {error}",
+                    name = &self.name,
+                    origin = &self.endpoint,
+                )
+            })
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    pub async fn get_usage_buckets(&self) -> Result<BTreeMap<String, Usage>> {
+        let admin = MinioAdminClient { storage: self };
+        admin.get_usage_buckets().await
+    }
 }
 
 pub struct ObjectStorageRef<'client, 'model, 'source> {
@@ -1073,7 +1108,7 @@ impl<'client, 'model, 'source> ObjectStorageRef<'client, 'model, 'source> {
     }
 
     #[instrument(level = Level::INFO, skip(self), err(Display))]
-    pub async fn get_list(&self) -> Result<Vec<Value>> {
+    pub async fn get_list(&self, limit: usize) -> Result<Vec<Value>> {
         let bucket_name = self.get_bucket_name();
 
         match self
@@ -1086,6 +1121,9 @@ impl<'client, 'model, 'source> ObjectStorageRef<'client, 'model, 'source> {
             Ok(response) => response
                 .contents
                 .into_iter()
+                // skip the objects managed by dash itself
+                .filter(|item| !item.name.starts_with(super::transfer::RESERVED_PREFIX))
+                .take(limit)
                 .map(|item| async move { self.get(&item.name).await })
                 .collect::<FuturesUnordered<_>>()
                 .try_collect()
@@ -1096,11 +1134,12 @@ impl<'client, 'model, 'source> ObjectStorageRef<'client, 'model, 'source> {
         }
     }
 
-    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    #[instrument(level = Level::INFO, skip(self, lifecycle), err(Display))]
     pub async fn create_bucket(
         &self,
         owner_references: Vec<OwnerReference>,
         quota: Option<Byte>,
+        lifecycle: &[ModelStorageObjectLifecycleRuleSpec],
     ) -> Result<()> {
         let mut bucket_name = self.get_bucket_name();
         if !self.is_bucket_exists().await? {
@@ -1114,6 +1153,7 @@ impl<'client, 'model, 'source> ObjectStorageRef<'client, 'model, 'source> {
         if let Some(quota) = quota {
             self.set_bucket_quota(&bucket_name, quota).await?;
         }
+        self.set_bucket_lifecycle(&bucket_name, lifecycle).await?;
         self.sync_bucket(bucket_name).await?;
         self.create_bucket_service(owner_references).await
     }
@@ -1259,10 +1299,119 @@ impl<'client, 'model, 'source> ObjectStorageRef<'client, 'model, 'source> {
         self.admin().set_capacity_bucket(bucket_name, quota).await
     }
 
+    #[instrument(level = Level::INFO, skip(self, lifecycle), err(Display))]
+    async fn set_bucket_lifecycle(
+        &self,
+        bucket_name: &str,
+        lifecycle: &[ModelStorageObjectLifecycleRuleSpec],
+    ) -> Result<()> {
+        // NOTE: the lifecycle of the bucket is fully managed by the model storage
+        if lifecycle.is_empty() {
+            return self
+                .target
+                .client
+                .delete_bucket_lifecycle(&DeleteBucketLifecycleArgs::new(bucket_name)?)
+                .await
+                .map(|_| ())
+                .map_err(|error| {
+                    anyhow!("failed to delete bucket lifecycle ({bucket_name}): {error}")
+                });
+        }
+
+        let rules = lifecycle
+            .iter()
+            .map(|rule| LifecycleRule {
+                abort_incomplete_multipart_upload_days_after_initiation: None,
+                expiration_date: None,
+                expiration_days: rule.expiration_days.map(|days| days as usize),
+                expiration_expired_object_delete_marker: None,
+                filter: Filter {
+                    and_operator: None,
+                    prefix: Some(rule.prefix.clone().unwrap_or_default()),
+                    tag: None,
+                },
+                id: rule.name.clone(),
+                noncurrent_version_expiration_noncurrent_days: rule
+                    .noncurrent_version_expiration_days
+                    .map(|days| days as usize),
+                noncurrent_version_transition_noncurrent_days: None,
+                noncurrent_version_transition_storage_class: None,
+                status: true,
+                transition_date: None,
+                transition_days: rule
+                    .transition
+                    .as_ref()
+                    .map(|transition| transition.days as usize),
+                transition_storage_class: rule
+                    .transition
+                    .as_ref()
+                    .map(|transition| transition.storage_class.clone()),
+            })
+            .collect();
+
+        self.target
+            .client
+            .set_bucket_lifecycle(&SetBucketLifecycleArgs {
+                extra_headers: None,
+                extra_query_params: None,
+                region: None,
+                bucket: bucket_name,
+                config: &LifecycleConfig { rules },
+            })
+            .await
+            .map(|_| ())
+            .map_err(|error| anyhow!("failed to set bucket lifecycle ({bucket_name}): {error}"))
+    }
+
+    /// Re-apply the replication rules of the bucket, e.g. after the sync has been failed.
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn resync_bucket(&self) -> Result<()> {
+        self.sync_bucket(self.get_bucket_name()).await
+    }
+
+    /// Collect the metrics of the continuous sync between the source and the target, if any.
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn get_sync_metrics(&self) -> Result<Option<ObjectStorageSyncMetrics>> {
+        let sync_policy = match &self.source {
+            Some((_, sync_policy)) => *sync_policy,
+            None => return Ok(None),
+        };
+
+        let bucket_name = self.get_bucket_name();
+        let mut metrics = ObjectStorageSyncMetrics::default();
+        let mut is_synced = false;
+        if sync_policy.pull == ModelStorageBindingSyncPolicyPull::Always {
+            let (source_session, bucket) = self.inverted(&bucket_name)?;
+            metrics += source_session
+                .admin()
+                .get_replication_metrics(bucket)
+                .await?;
+            is_synced = true;
+        }
+        if sync_policy.push == ModelStorageBindingSyncPolicyPush::Always {
+            metrics += self.admin().get_replication_metrics(&bucket_name).await?;
+            is_synced = true;
+        }
+
+        if is_synced {
+            Ok(Some(metrics))
+        } else {
+            Ok(None)
+        }
+    }
+
     #[instrument(level = Level::INFO, skip(self), err(Display))]
     async fn sync_bucket(&self, bucket_name: String) -> Result<()> {
         match &self.source {
-            Some((source, ModelStorageBindingSyncPolicy { pull, push })) => {
+            Some((
+                source,
+                ModelStorageBindingSyncPolicy {
+                    pull,
+                    push,
+                    replication: _,
+                    conflict,
+                },
+            )) => {
                 match pull {
                     ModelStorageBindingSyncPolicyPull::Always => {
                         self.sync_bucket_pull_always(&bucket_name).await?
@@ -1275,7 +1424,11 @@ impl<'client, 'model, 'source> ObjectStorageRef<'client, 'model, 'source> {
                 }
                 match push {
                     ModelStorageBindingSyncPolicyPush::Always => {
-                        self.sync_bucket_push_always(source, &bucket_name).await?
+                        // NOTE: the source wins the conflicts unless the target is the latest
+                        let overwrite =
+                            *conflict == ModelStorageBindingSyncPolicyConflict::LastWriterWins;
+                        self.sync_bucket_push_always(source, &bucket_name, overwrite)
+                            .await?
                     }
                     ModelStorageBindingSyncPolicyPush::OnDelete => (),
                     ModelStorageBindingSyncPolicyPush::Never => (),
@@ -1299,7 +1452,9 @@ impl<'client, 'model, 'source> ObjectStorageRef<'client, 'model, 'source> {
 
         let (source_session, bucket) = self.inverted(bucket)?;
         let source = self.target;
-        source_session.sync_bucket_push_always(source, bucket).await
+        source_session
+            .sync_bucket_push_always(source, bucket, true)
+            .await
     }
 
     #[instrument(level = Level::INFO, skip(self, source), err(Display))]
@@ -1316,11 +1471,15 @@ impl<'client, 'model, 'source> ObjectStorageRef<'client, 'model, 'source> {
         self.get_or_create_bucket_job(bucket, "pull", spec).await
     }
 
+    /// Replicate the objects of the target into the source.
+    ///
+    /// Unless `overwrite` is set, neither the existing objects nor the deletions are replicated.
     #[instrument(level = Level::INFO, skip(self, source), err(Display))]
     async fn sync_bucket_push_always(
         &self,
         source: &ObjectStorageSession,
         bucket: &str,
+        overwrite: bool,
     ) -> Result<()> {
         self.target
             .client
@@ -1346,38 +1505,44 @@ impl<'client, 'model, 'source> ObjectStorageRef<'client, 'model, 'source> {
             .map(|response| response.config.rules)
             .unwrap_or_default();
 
-        if rules
+        match rules
             .iter()
-            .any(|rule| rule.destination.bucket_arn == bucket_arn)
+            .position(|rule| rule.destination.bucket_arn == bucket_arn)
         {
-            return Ok(());
-        } else {
-            rules.push(ReplicationRule {
-                destination: Destination {
-                    bucket_arn: bucket_arn.clone(),
-                    access_control_translation: None,
-                    account: None,
-                    encryption_config: None,
-                    metrics: None,
-                    replication_time: None,
-                    storage_class: None,
-                },
-                delete_marker_replication_status: Some(true),
-                existing_object_replication_status: Some(true),
-                filter: None,
-                id: Some(bucket_arn.clone()),
-                prefix: None,
-                priority: rules
-                    .iter()
-                    .map(|rule| rule.priority.unwrap_or(1))
-                    .max()
-                    .unwrap_or_default()
-                    .checked_add(1),
-                source_selection_criteria: None,
-                delete_replication_status: Some(true),
-                status: true,
-            });
+            Some(index) if rules[index].existing_object_replication_status == Some(overwrite) => {
+                return Ok(())
+            }
+            // the conflict policy has been changed
+            Some(index) => {
+                rules.remove(index);
+            }
+            None => (),
         }
+        rules.push(ReplicationRule {
+            destination: Destination {
+                bucket_arn: bucket_arn.clone(),
+                access_control_translation: None,
+                account: None,
+                encryption_config: None,
+                metrics: None,
+                replication_time: None,
+                storage_class: None,
+            },
+            delete_marker_replication_status: Some(overwrite),
+            existing_object_replication_status: Some(overwrite),
+            filter: None,
+            id: Some(bucket_arn.clone()),
+            prefix: None,
+            priority: rules
+                .iter()
+                .map(|rule| rule.priority.unwrap_or(1))
+                .max()
+                .unwrap_or_default()
+                .checked_add(1),
+            source_selection_criteria: None,
+            delete_replication_status: Some(overwrite),
+            status: true,
+        });
 
         self.target
             .client
@@ -1427,7 +1592,15 @@ impl<'client, 'model, 'source> ObjectStorageRef<'client, 'model, 'source> {
     ) -> Result<bool> {
         let bucket_name = bucket_name.unwrap_or_else(|| self.get_bucket_name());
         match &self.source {
-            Some((source, ModelStorageBindingSyncPolicy { pull, push })) => {
+            Some((
+                source,
+                ModelStorageBindingSyncPolicy {
+                    pull,
+                    push,
+                    replication: _,
+                    conflict: _,
+                },
+            )) => {
                 let delete = match pull {
                     ModelStorageBindingSyncPolicyPull::Always => {
                         self.unsync_bucket_pull_always(&bucket_name).await?
@@ -1510,26 +1683,63 @@ impl<'client, 'model, 'source> ObjectStorageRef<'client, 'model, 'source> {
         Ok(false)
     }
 
+    /// Copy the bucket from the source storage into the target storage,
+    /// and verify that the buckets have the same objects.
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn migrate_bucket(&self, job_name: &str) -> Result<()> {
+        let bucket_name = self.get_bucket_name();
+        let source = match &self.source {
+            Some((source, _)) => *source,
+            None => bail!("cannot migrate a bucket without source storage ({bucket_name})"),
+        };
+
+        let spec = BucketJobSpec {
+            keep_finished: true,
+            source: Some(source),
+            sync_source: true,
+            sync_source_overwrite: true,
+            verify_source: true,
+            ..Default::default()
+        };
+        self.get_or_create_bucket_job_with_name(job_name, &bucket_name, "migrate", spec)
+            .await
+            .map_err(|error| anyhow!("failed to migrate a bucket ({bucket_name}): {error}"))
+    }
+
     #[instrument(level = Level::INFO, skip_all, fields(bucket = %bucket, command = %command), err(Display))]
     async fn get_or_create_bucket_job(
         &self,
         bucket: &str,
         command: &str,
+        spec: BucketJobSpec<'_>,
+    ) -> Result<()> {
+        let name = format!(
+            "{bucket}-{command}-{timestamp}",
+            timestamp = Utc::now().timestamp(),
+        );
+        self.get_or_create_bucket_job_with_name(&name, bucket, command, spec)
+            .await
+    }
+
+    #[instrument(level = Level::INFO, skip_all, fields(name = %name, bucket = %bucket, command = %command), err(Display))]
+    async fn get_or_create_bucket_job_with_name(
+        &self,
+        name: &str,
+        bucket: &str,
+        command: &str,
         BucketJobSpec {
             delete_source,
             delete_target,
+            keep_finished,
             source,
             sync_source,
             sync_source_overwrite,
+            verify_source,
         }: BucketJobSpec<'_>,
     ) -> Result<()> {
         let api = Api::<Job>::namespaced(self.kube.clone(), self.namespace);
-        let name = format!(
-            "{bucket}-{command}-{timestamp}",
-            timestamp = Utc::now().timestamp(),
-        );
 
-        get_or_create(&api, "job", &name, || {
+        get_or_create(&api, "job", name, || {
             let source_bucket = self.source_binding_name.unwrap_or(bucket).to_string();
             let target_bucket = bucket.to_string();
 
@@ -1549,12 +1759,13 @@ impl<'client, 'model, 'source> ObjectStorageRef<'client, 'model, 'source> {
             Job {
                 metadata: ObjectMeta {
                     labels: Some(labels.clone()),
-                    name: Some(name.clone()),
+                    name: Some(name.into()),
                     namespace: Some(self.namespace.to_string()),
                     ..Default::default()
                 },
                 spec: Some(JobSpec {
-                    ttl_seconds_after_finished: Some(30),
+                    // NOTE: the kept jobs should be deleted by the caller
+                    ttl_seconds_after_finished: if keep_finished { None } else { Some(30) },
                     template: PodTemplateSpec {
                         metadata: Some(ObjectMeta {
                             labels: Some(labels),
@@ -1603,6 +1814,19 @@ if [ "x${SOURCE_ENDPOINT}" != 'x' ]; then
     fi
 fi
 
+# Verify
+if [ "x${SOURCE_ENDPOINT}" != 'x' ]; then
+    if [ "x${SOURCE_VERIFY}" = 'xtrue' ]; then
+        echo '* Verifying buckets...'
+        __DIFF="$(mc diff "source/${SOURCE_BUCKET}" "target/${TARGET_BUCKET}")"
+        if [ "x${__DIFF}" != 'x' ]; then
+            echo "${__DIFF}" >&2
+            echo '* Verification failed: the buckets are different' >&2
+            exit 1
+        fi
+    fi
+fi
+
 # Delete
 echo '* Deleting buckets...'
 if [ "x${SOURCE_ENDPOINT}" != 'x' ]; then
@@ -1658,6 +1882,11 @@ exec true
                                         value: Some(sync_source_overwrite.to_string()),
                                         value_from: None,
                                     },
+                                    EnvVar {
+                                        name: "SOURCE_VERIFY".into(),
+                                        value: Some(verify_source.to_string()),
+                                        value_from: None,
+                                    },
                                     EnvVar {
                                         name: "TARGET_ACCESS_KEY".into(),
                                         value: Some(target_creds.access_key),
@@ -1700,6 +1929,22 @@ exec true
     }
 }
 
+/// The metrics of the bucket replication, summed up over all the directions.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ObjectStorageSyncMetrics {
+    pub pending_objects: u64,
+    pub pending_bytes: u64,
+    pub failed_objects: u64,
+}
+
+impl ::core::ops::AddAssign for ObjectStorageSyncMetrics {
+    fn add_assign(&mut self, rhs: Self) {
+        self.pending_objects = self.pending_objects.saturating_add(rhs.pending_objects);
+        self.pending_bytes = self.pending_bytes.saturating_add(rhs.pending_bytes);
+        self.failed_objects = self.failed_objects.saturating_add(rhs.failed_objects);
+    }
+}
+
 struct MinioAdminClient<'storage> {
     storage: &'storage ObjectStorageSession,
 }
@@ -1835,6 +2080,105 @@ impl<'storage> MinioAdminClient<'storage> {
             .await
     }
 
+    async fn get_usage_buckets(&self) -> Result<BTreeMap<String, Usage>> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Data {
+            #[serde(default)]
+            buckets_usage_info: BTreeMap<String, DataBucket>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct DataBucket {
+            size: u64,
+            objects_count: u64,
+        }
+
+        // NOTE: the data usage is collected by the minio scanner in background,
+        // so the result may be a few minutes late
+        self.execute::<&str>(Method::GET, "/admin/v3/datausageinfo", &[], None)
+            .map_err(Error::from)
+            .map(|result| {
+                result.and_then(|bytes| ::serde_json::from_slice(&bytes).map_err(Into::into))
+            })
+            .map_ok(|data: Data| {
+                data.buckets_usage_info
+                    .into_iter()
+                    .map(|(bucket_name, bucket)| {
+                        let usage = Usage {
+                            bytes: Byte::from_u64(bucket.size),
+                            objects: Some(bucket.objects_count),
+                        };
+                        (bucket_name, usage)
+                    })
+                    .collect()
+            })
+            .map_err(|error| {
+                anyhow!(
+                    "failed to get bucket usage ({name}: {origin}): {error}",
+                    name = &self.storage.name,
+                    origin = &self.storage.endpoint,
+                )
+            })
+            .await
+    }
+
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    async fn get_replication_metrics(&self, bucket_name: &str) -> Result<ObjectStorageSyncMetrics> {
+        #[derive(Default, Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Data {
+            #[serde(default)]
+            pending_replication_count: u64,
+            #[serde(default)]
+            pending_replication_size: u64,
+            #[serde(default)]
+            failed_replication_count: u64,
+        }
+
+        let method = Method::GET.as_str().try_into().unwrap();
+        let mut query_params = Multimap::default();
+        query_params.insert("replication-metrics".into(), String::default());
+
+        // NOTE: the bucket replication metrics are served by the S3 API, not the admin API
+        self.storage
+            .client
+            .execute(
+                method,
+                &Default::default(),
+                &mut Default::default(),
+                &query_params,
+                Some(bucket_name),
+                None,
+                None,
+            )
+            .map_err(Error::from)
+            .and_then(|response| response.bytes().map_err(Into::into))
+            .map(|result| {
+                result.and_then(|bytes| {
+                    if bytes.is_empty() {
+                        Ok(Data::default())
+                    } else {
+                        ::serde_json::from_slice(&bytes).map_err(Into::into)
+                    }
+                })
+            })
+            .map_ok(|data: Data| ObjectStorageSyncMetrics {
+                pending_objects: data.pending_replication_count,
+                pending_bytes: data.pending_replication_size,
+                failed_objects: data.failed_replication_count,
+            })
+            .map_err(|error| {
+                anyhow!(
+                    "failed to get replication metrics ({name}: {origin}/{bucket_name}): {error}",
+                    name = &self.storage.name,
+                    origin = &self.storage.endpoint,
+                )
+            })
+            .await
+    }
+
     #[instrument(level = Level::INFO, skip(self), err(Display))]
     async fn is_site_replication_enabled(&self) -> Result<bool> {
         self.execute::<&str>(Method::GET, "/admin/v3/site-replication/info", &[], None)
@@ -2145,6 +2489,7 @@ async fn get_or_create_minio_tenant(
         prometheus_url,
         storage:
             ModelStorageObjectOwnedSpec {
+                lifecycle: _,
                 minio_console_external_service,
                 minio_external_service,
                 replication:
@@ -2446,12 +2791,19 @@ export MINIO_ROOT_PASSWORD="{password}"
 struct BucketJobSpec<'a> {
     delete_source: bool,
     delete_target: bool,
+    keep_finished: bool,
     source: Option<&'a ObjectStorageSession>,
     sync_source: bool,
     sync_source_overwrite: bool,
+    verify_source: bool,
 }
 
-async fn get_or_create<K, Data>(api: &Api<K>, kind: &str, name: &str, data: Data) -> Result<K>
+pub(super) async fn get_or_create<K, Data>(
+    api: &Api<K>,
+    kind: &str,
+    name: &str,
+    data: Data,
+) -> Result<K>
 where
     Data: FnOnce() -> K,
     K: Clone + fmt::Debug + Serialize + DeserializeOwned,
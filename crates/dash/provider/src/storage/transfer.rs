@@ -0,0 +1,529 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use bytes::BytesMut;
+use futures::{TryFutureExt, TryStreamExt};
+use minio::s3::{
+    args::{
+        AbortMultipartUploadArgs, CompleteMultipartUploadArgs, CreateMultipartUploadArgs,
+        PutObjectApiArgs, StatObjectArgs, UploadPartArgs,
+    },
+    types::{Part, S3Api},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::{
+    fs,
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+};
+use tracing::{info, instrument, warn, Level};
+
+use super::ObjectStorageSession;
+
+/// The reserved prefix of the objects managed by dash itself, rather than the models.
+pub const RESERVED_PREFIX: &str = ".dash/";
+
+/// The reserved prefix of the objects storing the checksums of the transferred objects.
+pub const CHECKSUM_PREFIX: &str = ".dash/checksum/";
+
+/// S3 requires every part except the last one to be at least 5 MiB.
+const MIN_PART_SIZE: usize = 5 * 1024 * 1024;
+
+/// S3 allows at most 10,000 parts per multipart upload.
+const MAX_PARTS: u16 = 10_000;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ObjectTransferOptions {
+    /// The size of each uploaded part
+    pub part_size: usize,
+    /// The file recording the progress, so that an interrupted transfer can be resumed
+    pub checkpoint: Option<PathBuf>,
+}
+
+impl Default for ObjectTransferOptions {
+    fn default() -> Self {
+        Self {
+            part_size: 64 * 1024 * 1024,
+            checkpoint: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectTransferReport {
+    /// The total size of the object in bytes
+    pub size: u64,
+    /// The bytes skipped as they had been transferred before the resume
+    pub resumed: u64,
+    /// The hex-encoded SHA-256 checksum of the object
+    pub sha256: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UploadCheckpoint {
+    bucket: String,
+    key: String,
+    upload_id: String,
+    part_size: usize,
+    parts: Vec<UploadedPart>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UploadedPart {
+    number: u16,
+    etag: String,
+    size: usize,
+    sha256: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DownloadCheckpoint {
+    bucket: String,
+    key: String,
+    etag: String,
+}
+
+impl ObjectStorageSession {
+    /// Upload a large object part by part, without buffering it entirely in memory.
+    ///
+    /// When a checkpoint is given, the completed parts are recorded into it
+    /// and skipped on the next call with the same source.
+    #[instrument(level = Level::INFO, skip(self, reader, options), err(Display))]
+    pub async fn upload_stream<R>(
+        &self,
+        bucket: &str,
+        key: &str,
+        mut reader: R,
+        options: &ObjectTransferOptions,
+    ) -> Result<ObjectTransferReport>
+    where
+        R: AsyncRead + Send + Unpin,
+    {
+        let part_size = options.part_size.max(MIN_PART_SIZE);
+        let checkpoint_path = options.checkpoint.as_deref();
+
+        // Step 1. Resume or begin the multipart upload
+        let mut checkpoint = match load_checkpoint::<UploadCheckpoint>(checkpoint_path).await? {
+            Some(checkpoint)
+                if checkpoint.bucket == bucket
+                    && checkpoint.key == key
+                    && checkpoint.part_size == part_size =>
+            {
+                info!(
+                    "resuming the upload of {bucket}/{key} from part {}",
+                    checkpoint.parts.len() + 1,
+                );
+                checkpoint
+            }
+            Some(_) | None => {
+                let args = CreateMultipartUploadArgs::new(bucket, key)?;
+                let upload_id = match self.client.create_multipart_upload(&args).await {
+                    Ok(response) => response.upload_id,
+                    Err(error) => {
+                        bail!("failed to begin multipart upload ({bucket}/{key}): {error}")
+                    }
+                };
+                let checkpoint = UploadCheckpoint {
+                    bucket: bucket.into(),
+                    key: key.into(),
+                    upload_id,
+                    part_size,
+                    parts: Vec::default(),
+                };
+                save_checkpoint(checkpoint_path, &checkpoint).await?;
+                checkpoint
+            }
+        };
+
+        // Step 2. Upload the parts which are not uploaded yet
+        let mut buf = vec![0; part_size];
+        let mut hasher = Sha256::new();
+        let mut number = 0u16;
+        let mut resumed = 0u64;
+        let mut size = 0u64;
+        loop {
+            let len = read_part(&mut reader, &mut buf).await?;
+            if len == 0 && number > 0 {
+                break;
+            }
+            if number == MAX_PARTS {
+                bail!("too large object to upload ({bucket}/{key}); try a larger part size");
+            }
+            number += 1;
+
+            let data = &buf[..len];
+            let sha256 = format!("{:x}", Sha256::digest(data));
+            hasher.update(data);
+            size += len as u64;
+
+            match checkpoint.parts.get(number as usize - 1) {
+                Some(part) if part.size == len && part.sha256 == sha256 => resumed += len as u64,
+                Some(_) => {
+                    self.abort_upload(&checkpoint, checkpoint_path).await;
+                    bail!("the source of {bucket}/{key} has been changed since the checkpoint")
+                }
+                None => {
+                    let args =
+                        UploadPartArgs::new(bucket, key, &checkpoint.upload_id, number, data)?;
+                    let etag = match self.client.upload_part(&args).await {
+                        Ok(response) => response.etag,
+                        Err(error) => {
+                            bail!("failed to upload part {number} ({bucket}/{key}): {error}")
+                        }
+                    };
+                    checkpoint.parts.push(UploadedPart {
+                        number,
+                        etag,
+                        size: len,
+                        sha256,
+                    });
+                    save_checkpoint(checkpoint_path, &checkpoint).await?;
+                }
+            }
+
+            if len < part_size {
+                break;
+            }
+        }
+        if checkpoint.parts.len() > number as usize {
+            self.abort_upload(&checkpoint, checkpoint_path).await;
+            bail!("the source of {bucket}/{key} has been truncated since the checkpoint")
+        }
+
+        // Step 3. Complete the upload
+        let parts: Vec<_> = checkpoint
+            .parts
+            .iter()
+            .map(|part| Part {
+                number: part.number,
+                etag: part.etag.clone(),
+            })
+            .collect();
+        let args = CompleteMultipartUploadArgs::new(bucket, key, &checkpoint.upload_id, &parts)?;
+        if let Err(error) = self.client.complete_multipart_upload(&args).await {
+            bail!("failed to complete multipart upload ({bucket}/{key}): {error}")
+        }
+
+        // Step 4. Store the checksum to verify the downloads
+        let sha256 = format!("{:x}", hasher.finalize());
+        let checksum_key = format!("{CHECKSUM_PREFIX}{key}");
+        let args = PutObjectApiArgs::new(bucket, &checksum_key, sha256.as_bytes())?;
+        if let Err(error) = self.client.put_object_api(&args).await {
+            bail!("failed to store the checksum ({bucket}/{key}): {error}")
+        }
+
+        remove_checkpoint(checkpoint_path).await?;
+        Ok(ObjectTransferReport {
+            size,
+            resumed,
+            sha256,
+        })
+    }
+
+    /// Upload a large file, resuming from `<path>.upload` when interrupted.
+    #[instrument(level = Level::INFO, skip(self, options), err(Display))]
+    pub async fn upload_file(
+        &self,
+        bucket: &str,
+        key: &str,
+        path: &Path,
+        options: &ObjectTransferOptions,
+    ) -> Result<ObjectTransferReport> {
+        let file = fs::File::open(path)
+            .await
+            .map_err(|error| anyhow!("failed to open file ({}): {error}", path.display()))?;
+
+        let options = ObjectTransferOptions {
+            checkpoint: Some(
+                options
+                    .checkpoint
+                    .clone()
+                    .unwrap_or_else(|| with_extension(path, "upload")),
+            ),
+            ..options.clone()
+        };
+        self.upload_stream(bucket, key, file, &options).await
+    }
+
+    /// Download a large object into the writer, verifying its checksum if stored.
+    #[instrument(level = Level::INFO, skip(self, writer), err(Display))]
+    pub async fn download_stream<W>(
+        &self,
+        bucket: &str,
+        key: &str,
+        mut writer: W,
+    ) -> Result<ObjectTransferReport>
+    where
+        W: AsyncWrite + Send + Unpin,
+    {
+        let mut hasher = Sha256::new();
+        let size = self
+            .download_range(bucket, key, 0, &mut writer, &mut hasher)
+            .await?;
+
+        let sha256 = format!("{:x}", hasher.finalize());
+        self.verify_checksum(bucket, key, &sha256).await?;
+        Ok(ObjectTransferReport {
+            size,
+            resumed: 0,
+            sha256,
+        })
+    }
+
+    /// Download a large object into the file, resuming from the partially
+    /// downloaded file if the object has not been changed since then.
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn download_file(
+        &self,
+        bucket: &str,
+        key: &str,
+        path: &Path,
+    ) -> Result<ObjectTransferReport> {
+        let checkpoint_path = with_extension(path, "download");
+        let etag = match self
+            .client
+            .stat_object(&StatObjectArgs::new(bucket, key)?)
+            .await
+        {
+            Ok(response) => response.etag,
+            Err(error) => bail!("failed to stat object ({bucket}/{key}): {error}"),
+        };
+
+        // Step 1. Validate the partially downloaded file
+        let mut hasher = Sha256::new();
+        let resumed = match load_checkpoint::<DownloadCheckpoint>(Some(&checkpoint_path)).await? {
+            Some(checkpoint)
+                if checkpoint.bucket == bucket
+                    && checkpoint.key == key
+                    && checkpoint.etag == etag =>
+            {
+                match fs::File::open(path).await {
+                    Ok(file) => hash_file(file, &mut hasher).await?,
+                    Err(_) => 0,
+                }
+            }
+            Some(_) | None => {
+                let checkpoint = DownloadCheckpoint {
+                    bucket: bucket.into(),
+                    key: key.into(),
+                    etag,
+                };
+                save_checkpoint(Some(&checkpoint_path), &checkpoint).await?;
+                0
+            }
+        };
+        if resumed > 0 {
+            info!("resuming the download of {bucket}/{key} from {resumed} bytes");
+        }
+
+        // Step 2. Download the remaining bytes
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(resumed > 0)
+            .truncate(resumed == 0)
+            .open(path)
+            .await
+            .map_err(|error| anyhow!("failed to open file ({}): {error}", path.display()))?;
+        let size = resumed
+            + self
+                .download_range(bucket, key, resumed, &mut file, &mut hasher)
+                .await?;
+
+        // Step 3. Verify the checksum
+        let sha256 = format!("{:x}", hasher.finalize());
+        if let Err(error) = self.verify_checksum(bucket, key, &sha256).await {
+            // the partial file is corrupted; restart from scratch on the next call
+            fs::remove_file(path).await.ok();
+            remove_checkpoint(Some(&checkpoint_path)).await?;
+            return Err(error);
+        }
+
+        remove_checkpoint(Some(&checkpoint_path)).await?;
+        Ok(ObjectTransferReport {
+            size,
+            resumed,
+            sha256,
+        })
+    }
+
+    async fn download_range<W>(
+        &self,
+        bucket: &str,
+        key: &str,
+        offset: u64,
+        writer: &mut W,
+        hasher: &mut Sha256,
+    ) -> Result<u64>
+    where
+        W: AsyncWrite + Send + Unpin,
+    {
+        let response = self
+            .client
+            .get_object(bucket, key)
+            .offset(Some(offset))
+            .send()
+            .await
+            .map_err(|error| anyhow!("failed to get object ({bucket}/{key}): {error}"))?;
+        let (mut stream, _size) = response
+            .content
+            .to_stream()
+            .await
+            .map_err(|error| anyhow!("failed to get object data ({bucket}/{key}): {error}"))?;
+
+        let mut size = 0u64;
+        while let Some(chunk) = stream
+            .try_next()
+            .await
+            .map_err(|error| anyhow!("failed to get object data ({bucket}/{key}): {error}"))?
+        {
+            hasher.update(&chunk);
+            writer.write_all(&chunk).await.map_err(|error| {
+                anyhow!("failed to write object data ({bucket}/{key}): {error}")
+            })?;
+            size += chunk.len() as u64;
+        }
+        writer
+            .flush()
+            .await
+            .map_err(|error| anyhow!("failed to write object data ({bucket}/{key}): {error}"))?;
+        Ok(size)
+    }
+
+    async fn verify_checksum(&self, bucket: &str, key: &str, sha256: &str) -> Result<()> {
+        let checksum_key = format!("{CHECKSUM_PREFIX}{key}");
+        let expected = match self.client.get_object(bucket, &checksum_key).send().await {
+            Ok(response) => response
+                .content
+                .to_stream()
+                .and_then(|(stream, _size)| stream.try_collect().map_err(Into::into))
+                .await
+                .map(|bytes: BytesMut| String::from_utf8_lossy(&bytes).trim().to_string())
+                .map_err(|error| anyhow!("failed to get checksum ({bucket}/{key}): {error}"))?,
+            // the object has not been uploaded as a stream
+            Err(_) => return Ok(()),
+        };
+
+        if expected == sha256 {
+            Ok(())
+        } else {
+            bail!("checksum mismatch ({bucket}/{key}): expected {expected}, but given {sha256}")
+        }
+    }
+
+    async fn abort_upload(&self, checkpoint: &UploadCheckpoint, checkpoint_path: Option<&Path>) {
+        let UploadCheckpoint {
+            bucket,
+            key,
+            upload_id,
+            ..
+        } = checkpoint;
+
+        match AbortMultipartUploadArgs::new(bucket, key, upload_id) {
+            Ok(args) => {
+                if let Err(error) = self.client.abort_multipart_upload(&args).await {
+                    warn!("failed to abort multipart upload ({bucket}/{key}): {error}");
+                }
+            }
+            Err(error) => warn!("failed to abort multipart upload ({bucket}/{key}): {error}"),
+        }
+        if let Err(error) = remove_checkpoint(checkpoint_path).await {
+            warn!("{error}");
+        }
+    }
+}
+
+/// Fill the buffer as much as possible, returning the number of the read bytes.
+async fn read_part<R>(reader: &mut R, buf: &mut [u8]) -> Result<usize>
+where
+    R: AsyncRead + Send + Unpin,
+{
+    let mut len = 0;
+    while len < buf.len() {
+        match reader.read(&mut buf[len..]).await {
+            Ok(0) => break,
+            Ok(read) => len += read,
+            Err(error) => bail!("failed to read the source: {error}"),
+        }
+    }
+    Ok(len)
+}
+
+pub(super) async fn hash_file(mut file: fs::File, hasher: &mut Sha256) -> Result<u64> {
+    let mut buf = vec![0; MIN_PART_SIZE];
+    let mut size = 0u64;
+    loop {
+        let len = read_part(&mut file, &mut buf).await?;
+        hasher.update(&buf[..len]);
+        size += len as u64;
+        if len < buf.len() {
+            break Ok(size);
+        }
+    }
+}
+
+fn with_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension);
+    path.into()
+}
+
+async fn load_checkpoint<T>(path: Option<&Path>) -> Result<Option<T>>
+where
+    T: DeserializeOwned,
+{
+    let path = match path {
+        Some(path) => path,
+        None => return Ok(None),
+    };
+    match fs::read(path).await {
+        Ok(data) => match ::serde_json::from_slice(&data) {
+            Ok(checkpoint) => Ok(Some(checkpoint)),
+            Err(error) => {
+                warn!(
+                    "ignoring corrupted checkpoint ({}): {error}",
+                    path.display()
+                );
+                Ok(None)
+            }
+        },
+        Err(error) if error.kind() == ::std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => bail!("failed to load checkpoint ({}): {error}", path.display()),
+    }
+}
+
+async fn save_checkpoint<T>(path: Option<&Path>, checkpoint: &T) -> Result<()>
+where
+    T: Serialize,
+{
+    let path = match path {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+    let data = ::serde_json::to_vec(checkpoint)?;
+
+    // write atomically so that an interruption never leaves a broken checkpoint
+    let tmp = with_extension(path, "tmp");
+    fs::write(&tmp, data)
+        .await
+        .map_err(|error| anyhow!("failed to save checkpoint ({}): {error}", path.display()))?;
+    fs::rename(&tmp, path)
+        .await
+        .map_err(|error| anyhow!("failed to save checkpoint ({}): {error}", path.display()))
+}
+
+async fn remove_checkpoint(path: Option<&Path>) -> Result<()> {
+    match path {
+        Some(path) => match fs::remove_file(path).await {
+            Ok(()) => Ok(()),
+            Err(error) if error.kind() == ::std::io::ErrorKind::NotFound => Ok(()),
+            Err(error) => bail!("failed to remove checkpoint ({}): {error}", path.display()),
+        },
+        None => Ok(()),
+    }
+}
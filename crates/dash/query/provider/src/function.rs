@@ -153,6 +153,7 @@ impl fmt::Display for DashFunction {
                     exec: _,
                     type_: _,
                     volatility: _,
+                    test: _,
                 },
             ..
         } = self;
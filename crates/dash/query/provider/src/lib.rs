@@ -220,6 +220,7 @@ async fn load_models<'a>(
                             access_key: credentials.access_key,
                             region: StorageS3Args::default_region().into(),
                             s3_endpoint: object_storage.endpoint,
+                            s3_replicas: Vec::default(),
                             secret_key: credentials.secret_key,
                         }
                     })
@@ -275,6 +276,7 @@ async fn load_functions(
                 exec: _,
                 type_,
                 volatility,
+                test,
             } = function.spec;
 
             let spec = FunctionSpec {
@@ -283,6 +285,7 @@ async fn load_functions(
                 exec: (),
                 type_,
                 volatility,
+                test,
             };
             match self::function::DashFunction::try_new(messenger, name, model_in, spec).await {
                 Ok(function) => Some(function),
@@ -0,0 +1,81 @@
+mod validate;
+
+use std::{fs::File, io::BufReader, net::SocketAddr, path::PathBuf};
+
+use actix_web::{get, web::Data, App, HttpResponse, HttpServer, Responder};
+use actix_web_opentelemetry::{RequestMetrics, RequestTracing};
+use anyhow::{anyhow, Result};
+use ark_core::{env::infer, tracer};
+use kube::Client;
+use opentelemetry::global;
+use rustls::ServerConfig;
+use tracing::{instrument, Level};
+
+#[instrument(level = Level::INFO)]
+#[get("/")]
+async fn index() -> impl Responder {
+    HttpResponse::Ok().json("dash-webhook")
+}
+
+#[instrument(level = Level::INFO)]
+#[get("/health")]
+async fn health() -> impl Responder {
+    HttpResponse::Ok().json("healthy")
+}
+
+#[instrument(level = Level::INFO, err(Display))]
+fn load_tls_config() -> Result<ServerConfig> {
+    let cert_path = infer::<_, PathBuf>("TLS_CERT_PATH")
+        .unwrap_or_else(|_| "/var/run/secrets/dash.ulagbulag.io/webhook/tls.crt".into());
+    let key_path = infer::<_, PathBuf>("TLS_KEY_PATH")
+        .unwrap_or_else(|_| "/var/run/secrets/dash.ulagbulag.io/webhook/tls.key".into());
+
+    let open = |path: &PathBuf| {
+        File::open(path)
+            .map(BufReader::new)
+            .map_err(|error| anyhow!("failed to open {path:?}: {error}"))
+    };
+
+    let certs = ::rustls_pemfile::certs(&mut open(&cert_path)?)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| anyhow!("failed to parse TLS certificates: {error}"))?;
+    let key = ::rustls_pemfile::private_key(&mut open(&key_path)?)
+        .map_err(|error| anyhow!("failed to parse TLS private key: {error}"))?
+        .ok_or_else(|| anyhow!("no TLS private key: {key_path:?}"))?;
+
+    ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|error| anyhow!("failed to init TLS config: {error}"))
+}
+
+#[actix_web::main]
+async fn main() {
+    async fn try_main() -> Result<()> {
+        // Initialize kubernetes client
+        let addr =
+            infer::<_, SocketAddr>("BIND_ADDR").unwrap_or_else(|_| "0.0.0.0:443".parse().unwrap());
+        let client = Data::new(Client::try_default().await?);
+        let tls = load_tls_config()?;
+
+        // Start web server
+        HttpServer::new(move || {
+            let app = App::new().app_data(Data::clone(&client));
+            let app = app
+                .service(index)
+                .service(health)
+                .service(crate::validate::post);
+            app.wrap(RequestTracing::default())
+                .wrap(RequestMetrics::default())
+        })
+        .bind_rustls_0_23(addr, tls)
+        .unwrap_or_else(|e| panic!("failed to bind to {addr}: {e}"))
+        .run()
+        .await
+        .map_err(Into::into)
+    }
+
+    tracer::init_once();
+    try_main().await.expect("running a server");
+    global::shutdown_tracer_provider()
+}
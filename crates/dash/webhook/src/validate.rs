@@ -0,0 +1,171 @@
+use actix_web::{
+    post,
+    web::{Data, Json},
+    HttpResponse, Responder,
+};
+use anyhow::{anyhow, bail, Result};
+use dash_api::{
+    model::ModelCrd,
+    model_storage_binding::ModelStorageBindingCrd,
+    storage::{ModelStorageCrd, StorageResourceRequirements},
+};
+use dash_operator::{
+    consts::infer_prometheus_url,
+    validator::{
+        model::ModelValidator, model_storage_binding::ModelStorageBindingValidator,
+        storage::ModelStorageValidator,
+    },
+};
+use dash_provider::storage::KubernetesStorageClient;
+use kube::{
+    core::{
+        admission::{AdmissionRequest, AdmissionResponse, AdmissionReview, Operation},
+        DynamicObject,
+    },
+    Client,
+};
+use serde::de::DeserializeOwned;
+use tracing::{instrument, warn, Level};
+
+#[instrument(level = Level::INFO, skip_all)]
+#[post("/validate")]
+pub async fn post(
+    kube: Data<Client>,
+    review: Json<AdmissionReview<DynamicObject>>,
+) -> impl Responder {
+    let request: AdmissionRequest<DynamicObject> = match review.into_inner().try_into() {
+        Ok(request) => request,
+        Err(error) => {
+            warn!("failed to parse admission review: {error}");
+            return HttpResponse::BadRequest()
+                .json(AdmissionResponse::invalid(error.to_string()).into_review());
+        }
+    };
+
+    let response = AdmissionResponse::from(&request);
+    let response = match validate(&kube, &request).await {
+        Ok(()) => response,
+        Err(error) => {
+            let AdmissionRequest {
+                kind,
+                name,
+                namespace,
+                ..
+            } = &request;
+            let namespace = namespace.as_deref().unwrap_or_default();
+            let kind = &kind.kind;
+            warn!("denied {kind} ({namespace}/{name}): {error}");
+            response.deny(error.to_string())
+        }
+    };
+    HttpResponse::Ok().json(response.into_review())
+}
+
+#[instrument(level = Level::INFO, skip_all, err(Display))]
+async fn validate(kube: &Client, request: &AdmissionRequest<DynamicObject>) -> Result<()> {
+    // the deletions are handled by the finalizers
+    let object = match (request.operation, request.object.as_ref()) {
+        (Operation::Create | Operation::Update, Some(object)) => object,
+        _ => return Ok(()),
+    };
+    // the status updates are made by the operator itself
+    if request.sub_resource.is_some() {
+        return Ok(());
+    }
+
+    let namespace = request
+        .namespace
+        .as_deref()
+        .ok_or_else(|| anyhow!("metadata.namespace: namespace is required"))?;
+    let kubernetes_storage = KubernetesStorageClient { namespace, kube };
+
+    match request.kind.kind.as_str() {
+        "Model" => {
+            let crd: ModelCrd = parse(object)?;
+            let validator = ModelValidator { kubernetes_storage };
+            let fields = validator
+                .validate_model(crd.spec.clone())
+                .await
+                .map_err(|error| anyhow!("spec: {error}"))?;
+
+            match (request.operation, request.old_object.as_ref()) {
+                (Operation::Update, Some(old_object)) => {
+                    let old_crd: ModelCrd = parse(old_object)?;
+                    let last = old_crd
+                        .status
+                        .as_ref()
+                        .and_then(|status| status.fields.as_ref());
+                    validator
+                        .validate_model_update(&crd, last, &fields)
+                        .await
+                        .map(|_| ())
+                        .map_err(|error| anyhow!("spec: {error}"))
+                }
+                _ => Ok(()),
+            }
+        }
+        "ModelStorage" => {
+            let crd: ModelStorageCrd = parse(object)?;
+            match (request.operation, request.old_object.as_ref()) {
+                (Operation::Update, Some(old_object)) => {
+                    let old_crd: ModelStorageCrd = parse(old_object)?;
+                    if old_crd.spec.kind.to_kind() != crd.spec.kind.to_kind() {
+                        bail!(
+                            "spec.kind: model storage kind is immutable: {old} => {new}",
+                            old = old_crd.spec.kind.to_kind(),
+                            new = crd.spec.kind.to_kind(),
+                        );
+                    }
+                    Ok(())
+                }
+                _ if crd.spec.kind.is_unique() => {
+                    let prometheus_url = infer_prometheus_url();
+                    let validator = ModelStorageValidator {
+                        kubernetes_storage,
+                        prometheus_url: &prometheus_url,
+                    };
+                    validator
+                        .validate_model_storage_conflict(&request.name, crd.spec.kind.to_kind())
+                        .await
+                        .map_err(|error| anyhow!("spec.kind: {error}"))
+                }
+                _ => Ok(()),
+            }
+        }
+        "ModelStorageBinding" => {
+            let crd: ModelStorageBindingCrd = parse(object)?;
+            ModelStorageBindingValidator::validate_spec(&crd.spec)?;
+            if !matches!(request.operation, Operation::Create) {
+                return Ok(());
+            }
+
+            // the missing model storages are reported by the operator
+            let storage = match kubernetes_storage
+                .load_model_storage(crd.spec.storage.target())
+                .await
+            {
+                Ok(storage) => storage,
+                Err(_) => return Ok(()),
+            };
+            let prometheus_url = infer_prometheus_url();
+            let validator = ModelStorageValidator {
+                kubernetes_storage,
+                prometheus_url: &prometheus_url,
+            };
+            validator
+                .validate_model_storage_quota(&storage, crd.spec.resources.quota())
+                .await
+                .map_err(|error| anyhow!("spec.resources: {error}"))
+        }
+        kind => bail!("unsupported kind: {kind}"),
+    }
+}
+
+fn parse<K>(object: &DynamicObject) -> Result<K>
+where
+    K: DeserializeOwned,
+{
+    ::serde_json::to_value(object)
+        .and_then(::serde_json::from_value)
+        .map_err(|error| anyhow!("failed to parse object: {error}"))
+}
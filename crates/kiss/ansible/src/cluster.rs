@@ -3,7 +3,10 @@ use std::{borrow::Cow, collections::BTreeMap, net::IpAddr};
 use anyhow::{anyhow, bail, Result};
 use itertools::Itertools;
 use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
-use kiss_api::r#box::{BoxCrd, BoxGroupRole, BoxGroupSpec, BoxSpec, BoxState};
+use kiss_api::{
+    condition::{REASON_ETCD_MEMBER_VOTER, TYPE_ETCD_MEMBER},
+    r#box::{BoxCrd, BoxGroupRole, BoxGroupSpec, BoxSpec, BoxState},
+};
 use kube::{api::ListParams, Api, Client, Error};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -117,7 +120,29 @@ impl<'a> ClusterState<'a> {
         let filter = ClusterBoxFilter::RunningWith {
             uuid: self.owner_uuid,
         };
-        let mut nodes = self.control_planes.to_vec(filter);
+        let nodes = self
+            .get_etcd_nodes(filter)
+            .into_iter()
+            .map(|(node, _)| node);
+
+        let fn_sort = sort_nodes_by_date;
+
+        const NODE_ROLE: &str = "etcd";
+        get_nodes_as_string(nodes, NODE_ROLE, fn_sort)
+    }
+
+    /// Return the control-plane boxes which should be the etcd members,
+    /// including the ones which are joining now.
+    pub fn get_etcd_boxes(&self) -> Vec<&BoxCrd> {
+        let filter = ClusterBoxFilter::RunningOrJoining;
+        self.get_etcd_nodes(filter)
+            .into_iter()
+            .map(|(_, r#box)| r#box)
+            .collect()
+    }
+
+    fn get_etcd_nodes(&self, filter: ClusterBoxFilter) -> Vec<(&ClusterBoxState, &BoxCrd)> {
+        let mut nodes: Vec<_> = self.control_planes.iter(filter).collect();
 
         // truncate the number of nodes to `etcd_nodes_max`
         if self.config.etcd_nodes_max > 0 {
@@ -128,11 +153,12 @@ impl<'a> ClusterState<'a> {
         if nodes.len() % 2 == 0 {
             nodes.pop();
         }
+        nodes
+    }
 
-        let fn_sort = sort_nodes_by_date;
-
-        const NODE_ROLE: &str = "etcd";
-        get_nodes_as_string(nodes, NODE_ROLE, fn_sort)
+    /// Return all the control-plane boxes, including the ones which are not running.
+    pub fn get_control_planes(&self) -> impl Iterator<Item = &BoxCrd> {
+        self.control_planes.nodes.values()
     }
 
     pub fn get_worker_nodes(&self) -> Result<impl Iterator<Item = &BoxCrd>> {
@@ -195,13 +221,52 @@ impl<'a> ClusterState<'a> {
         control_planes_running == control_planes_total
     }
 
+    /// Return `true` if the etcd cluster preserves its quorum.
+    ///
+    /// The health is reported by the `kiss-manager` as the box conditions,
+    /// so the cluster is regarded as healthy if nothing is reported yet.
+    pub fn is_etcd_healthy(&self) -> bool {
+        let voters: Vec<_> = self
+            .control_planes
+            .nodes
+            .values()
+            .filter_map(|r#box| r#box.status.as_ref())
+            .filter_map(|status| {
+                status
+                    .conditions
+                    .iter()
+                    .find(|condition| condition.type_ == TYPE_ETCD_MEMBER)
+            })
+            .filter(|condition| condition.reason == REASON_ETCD_MEMBER_VOTER)
+            .collect();
+        if voters.is_empty() {
+            return true;
+        }
+
+        let voters_total = voters.len();
+        let voters_healthy = voters
+            .iter()
+            .filter(|condition| condition.status == "True")
+            .count();
+
+        info!(
+            "Cluster \"{}\" status: {}/{} etcd members are healthy",
+            &self.owner_group.cluster_name, voters_healthy, voters_total,
+        );
+
+        // assert the quorum is preserved
+        voters_healthy >= voters_total / 2 + 1
+    }
+
     fn is_node_control_plane(&self) -> bool {
         self.control_planes.contains(self.owner_uuid)
     }
 
     pub fn is_joinable(&self) -> bool {
         if self.is_node_control_plane() {
-            self.is_control_plane_ready() && self.control_planes.is_next(self.owner_uuid)
+            self.is_control_plane_ready()
+                && self.is_etcd_healthy()
+                && self.control_planes.is_next(self.owner_uuid)
         } else {
             self.is_control_plane_running()
         }
@@ -289,7 +354,7 @@ impl ClusterBoxGroup {
     fn iter(&self, filter: ClusterBoxFilter) -> impl Iterator<Item = (&ClusterBoxState, &BoxCrd)> {
         self.nodes
             .iter()
-            .filter(|(node, _)| node.is_running || filter.contains(node.uuid))
+            .filter(|(node, _)| node.is_running || filter.contains(node))
             .sorted_by_key(|&(node, _)| (&node.created_at, node))
     }
 
@@ -315,14 +380,16 @@ impl ClusterBoxGroup {
 
 enum ClusterBoxFilter {
     Running,
+    RunningOrJoining,
     RunningWith { uuid: Uuid },
 }
 
 impl ClusterBoxFilter {
-    fn contains(&self, target: Uuid) -> bool {
+    fn contains(&self, node: &ClusterBoxState) -> bool {
         match self {
             Self::Running => false,
-            Self::RunningWith { uuid } => *uuid == target,
+            Self::RunningOrJoining => node.is_joining,
+            Self::RunningWith { uuid } => *uuid == node.uuid,
         }
     }
 }
@@ -368,6 +435,7 @@ struct ClusterBoxState {
     created_at: Option<Time>,
     hostname: String,
     ip: Option<IpAddr>,
+    is_joining: bool,
     is_ready: bool,
     is_running: bool,
     uuid: Uuid,
@@ -389,15 +457,20 @@ impl ClusterBoxState {
                 .as_ref()
                 .and_then(|status| status.access.primary.as_ref())
                 .map(|interface| interface.address),
+            is_joining: object
+                .status
+                .as_ref()
+                .map(|status| matches!(status.state, BoxState::Joining))
+                .unwrap_or_default(),
             is_ready: object
                 .status
                 .as_ref()
-                .map(|status| matches!(status.state, BoxState::Ready | BoxState::Running))
+                .map(|status| matches!(status.state, BoxState::Ready) || status.state.is_joined())
                 .unwrap_or_default(),
             is_running: object
                 .status
                 .as_ref()
-                .map(|status| matches!(status.state, BoxState::Running))
+                .map(|status| status.state.is_joined())
                 .unwrap_or_default(),
             uuid: object.spec.machine.uuid,
         }
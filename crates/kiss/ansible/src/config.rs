@@ -1,17 +1,25 @@
-use std::net::Ipv4Addr;
+use std::{collections::BTreeSet, iter, net::Ipv4Addr};
 
-use anyhow::{anyhow, Error, Result};
+use anyhow::{anyhow, bail, Error, Result};
 use ipnet::Ipv4Net;
 use k8s_openapi::api::core::v1::ConfigMap;
+use kiss_api::{
+    enrollment::EnrollmentPolicy, r#box::BoxRetryPolicySpec, sensor::BoxSensorThresholds,
+};
 use kube::{Api, Client};
+use serde::{Deserialize, Serialize};
 use tracing::{instrument, Level};
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+use crate::console::KissConsoleProtocol;
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct KissConfig {
     pub allow_critical_commands: bool,
     pub allow_pruning_network_interfaces: bool,
     pub bootstrapper_network_dns_server_ns1: Ipv4Addr,
     pub bootstrapper_network_dns_server_ns2: Ipv4Addr,
+    pub console: KissConsoleConfig,
+    pub enrollment_policy: EnrollmentPolicy,
     pub etcd_nodes_max: usize,
     pub group_enable_default_cluster: bool,
     pub group_enforce_ansible_control_planes: bool,
@@ -27,8 +35,12 @@ pub struct KissConfig {
     pub network_ipv4_gateway: Ipv4Addr,
     pub network_ipv4_subnet: Ipv4Net,
     pub network_nameserver_incluster_ipv4: Ipv4Addr,
+    pub networks: Vec<KissNetworkConfig>,
     pub os_default: String,
     pub os_kernel: String,
+    pub remediation: KissRemediationConfig,
+    pub retry: BoxRetryPolicySpec,
+    pub sensor: KissSensorConfig,
 }
 
 impl KissConfig {
@@ -49,6 +61,21 @@ impl KissConfig {
                 &config,
                 "bootstrapper_network_dns_server_ns2",
             )?,
+            console: KissConsoleConfig {
+                enabled: infer_optional(&config, "console_enabled")?.unwrap_or_default(),
+                image: infer_optional(&config, "console_image")?
+                    .unwrap_or_else(|| KissConsoleConfig::DEFAULT_IMAGE.into()),
+                protocol: infer_optional(&config, "console_protocol")?.unwrap_or_default(),
+                bucket: match infer_optional::<_, String>(&config, "console_bucket_name")? {
+                    Some(name) if !name.is_empty() => Some(KissConsoleBucketConfig {
+                        endpoint: infer(&config, "console_bucket_endpoint")?,
+                        name,
+                        secret_name: infer(&config, "console_bucket_secret_name")?,
+                    }),
+                    _ => None,
+                },
+            },
+            enrollment_policy: infer_optional(&config, "enrollment_policy")?.unwrap_or_default(),
             etcd_nodes_max: infer(&config, "etcd_nodes_max")?,
             group_enable_default_cluster: infer(&config, "group_enable_default_cluster")?,
             group_enforce_ansible_control_planes: infer(
@@ -67,12 +94,165 @@ impl KissConfig {
             network_ipv4_gateway: infer(&config, "network_ipv4_gateway")?,
             network_ipv4_subnet: infer(&config, "network_ipv4_subnet")?,
             network_nameserver_incluster_ipv4: infer(&config, "network_nameserver_incluster_ipv4")?,
+            networks: infer_networks(&config)?,
             os_default: infer(&config, "os_default")?,
             os_kernel: infer(&config, "os_kernel")?,
+            remediation: KissRemediationConfig {
+                enabled: infer_optional(&config, "remediation_enabled")?.unwrap_or_default(),
+                interval_seconds: infer_optional(&config, "remediation_interval_seconds")?
+                    .unwrap_or(KissRemediationConfig::DEFAULT_INTERVAL_SECONDS),
+                max_reboots_per_day: infer_optional(&config, "remediation_max_reboots_per_day")?
+                    .unwrap_or(KissRemediationConfig::DEFAULT_MAX_REBOOTS_PER_DAY),
+                not_ready_seconds: infer_optional(&config, "remediation_not_ready_seconds")?
+                    .unwrap_or(KissRemediationConfig::DEFAULT_NOT_READY_SECONDS),
+            },
+            retry: BoxRetryPolicySpec {
+                max_attempts: infer_optional(&config, "retry_max_attempts")?,
+                backoff_seconds: infer_optional(&config, "retry_backoff_seconds")?,
+                max_backoff_seconds: infer_optional(&config, "retry_max_backoff_seconds")?,
+                jitter_percent: infer_optional(&config, "retry_jitter_percent")?,
+            },
+            sensor: KissSensorConfig {
+                enabled: infer_optional(&config, "sensor_enabled")?.unwrap_or_default(),
+                interval_seconds: infer_optional(&config, "sensor_interval_seconds")?
+                    .unwrap_or(KissSensorConfig::DEFAULT_INTERVAL_SECONDS),
+                thresholds: BoxSensorThresholds {
+                    max_power_watts: infer_optional(&config, "sensor_max_power_watts")?,
+                    max_temperature_celsius: infer_optional(
+                        &config,
+                        "sensor_max_temperature_celsius",
+                    )?,
+                },
+                topic: infer_optional(&config, "sensor_topic")?
+                    .unwrap_or_else(|| KissSensorConfig::DEFAULT_TOPIC.into()),
+            },
         })
     }
 }
 
+/// The serial-over-LAN console capture of the boxes during provisioning.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KissConsoleConfig {
+    pub enabled: bool,
+    pub image: String,
+    pub protocol: KissConsoleProtocol,
+    /// The object storage bucket to upload the console logs, or only the pod logs if missing.
+    pub bucket: Option<KissConsoleBucketConfig>,
+}
+
+impl KissConsoleConfig {
+    const DEFAULT_IMAGE: &'static str = "quay.io/ulagbulag/openark-console-capture:latest";
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct KissConsoleBucketConfig {
+    pub endpoint: String,
+    pub name: String,
+    /// The secret providing `AWS_ACCESS_KEY_ID` and `AWS_SECRET_ACCESS_KEY`.
+    pub secret_name: String,
+}
+
+/// The remediation of the boxes whose nodes have been not ready for a long time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KissRemediationConfig {
+    pub enabled: bool,
+    pub interval_seconds: u64,
+    /// The box is quarantined if the power-cycles exceed this within a day.
+    pub max_reboots_per_day: usize,
+    pub not_ready_seconds: u64,
+}
+
+impl KissRemediationConfig {
+    const DEFAULT_INTERVAL_SECONDS: u64 = 60; // 1 minute
+    const DEFAULT_MAX_REBOOTS_PER_DAY: usize = 3;
+    const DEFAULT_NOT_READY_SECONDS: u64 = 10 * 60; // 10 minutes
+}
+
+/// The BMC sensor monitoring, which publishes the readings into the dash pipe.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KissSensorConfig {
+    pub enabled: bool,
+    pub interval_seconds: u64,
+    pub thresholds: BoxSensorThresholds,
+    pub topic: String,
+}
+
+impl KissSensorConfig {
+    const DEFAULT_INTERVAL_SECONDS: u64 = 60; // 1 minute
+    const DEFAULT_TOPIC: &'static str = "kiss.sensor";
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KissNetworkConfig {
+    pub name: String,
+    #[serde(default)]
+    pub vlan: Option<u16>,
+    pub subnet: Ipv4Net,
+    #[serde(default)]
+    pub gateway: Option<Ipv4Addr>,
+    #[serde(default)]
+    pub dhcp_range_begin: Option<Ipv4Addr>,
+    #[serde(default)]
+    pub dhcp_range_end: Option<Ipv4Addr>,
+    #[serde(default)]
+    pub mtu_size: Option<u16>,
+}
+
+impl KissNetworkConfig {
+    /// The name of the network where the boxes are provisioned.
+    pub const NAME_PROVISION: &'static str = "provision";
+}
+
+/// Load the provision network and the additional networks.
+fn infer_networks(config: &ConfigMap) -> Result<Vec<KissNetworkConfig>> {
+    let provision = KissNetworkConfig {
+        name: KissNetworkConfig::NAME_PROVISION.into(),
+        vlan: None,
+        subnet: infer(config, "network_ipv4_subnet")?,
+        gateway: Some(infer(config, "network_ipv4_gateway")?),
+        dhcp_range_begin: Some(infer(config, "network_ipv4_dhcp_range_begin")?),
+        dhcp_range_end: Some(infer(config, "network_ipv4_dhcp_range_end")?),
+        mtu_size: Some(infer(config, "network_interface_mtu_size")?),
+    };
+
+    let key = "networks";
+    let networks: Vec<KissNetworkConfig> = match config
+        .data
+        .as_ref()
+        .and_then(|data| data.get(key))
+        .filter(|value| !value.trim().is_empty())
+    {
+        Some(value) => ::serde_json::from_str(value).map_err(|error| {
+            anyhow!("failed to parse the configuration variable {key}: {error}")
+        })?,
+        None => Default::default(),
+    };
+
+    let networks: Vec<_> = iter::once(provision).chain(networks).collect();
+    let mut names = BTreeSet::default();
+    for network in &networks {
+        let name = &network.name;
+        if !names.insert(name) {
+            bail!("duplicated network: {name}");
+        }
+        if let Some(vlan) = network.vlan {
+            if !(1..=4094).contains(&vlan) {
+                bail!("invalid VLAN tag of the network {name}: {vlan}");
+            }
+        }
+        for address in [network.dhcp_range_begin, network.dhcp_range_end]
+            .into_iter()
+            .flatten()
+        {
+            if !network.subnet.contains(&address) {
+                bail!("DHCP range of the network {name} is out of the subnet: {address}");
+            }
+        }
+    }
+    Ok(networks)
+}
+
 pub fn infer<K: AsRef<str>, R>(config: &ConfigMap, key: K) -> Result<R>
 where
     R: ::core::str::FromStr,
@@ -87,3 +267,19 @@ where
         .ok_or_else(|| anyhow!("failed to find the configuration variable: {key}"))
         .and_then(|e| e.parse().map_err(Into::into))
 }
+
+/// Same as [`infer`], but the missing variable is regarded as `None`.
+pub fn infer_optional<K: AsRef<str>, R>(config: &ConfigMap, key: K) -> Result<Option<R>>
+where
+    R: ::core::str::FromStr,
+    <R as ::core::str::FromStr>::Err: Into<Error> + Send + Sync + 'static,
+{
+    let key = key.as_ref();
+
+    config
+        .data
+        .as_ref()
+        .and_then(|data| data.get(key))
+        .map(|e| e.parse().map_err(Into::into))
+        .transpose()
+}
@@ -0,0 +1,190 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{bail, Error, Result};
+use k8s_openapi::api::core::v1::{
+    Container, EnvFromSource, EnvVar, EnvVarSource, ObjectFieldSelector, SecretEnvSource,
+    SecretKeySelector,
+};
+use kiss_api::r#box::{BoxPowerType, BoxState};
+
+use crate::{config::KissConsoleConfig, AnsibleJob};
+
+/// How to open the serial console of the box.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum KissConsoleProtocol {
+    /// IPMI serial-over-LAN (`ipmitool sol activate`)
+    #[default]
+    Ipmi,
+    /// The serial console of the Redfish BMC, which is served over SSH
+    Redfish,
+}
+
+impl FromStr for KissConsoleProtocol {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ipmi" => Ok(Self::Ipmi),
+            "redfish" => Ok(Self::Redfish),
+            _ => bail!("unknown console protocol: {s}"),
+        }
+    }
+}
+
+impl fmt::Display for KissConsoleProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ipmi => f.write_str("ipmi"),
+            Self::Redfish => f.write_str("redfish"),
+        }
+    }
+}
+
+/// Build a sidecar capturing the serial console of the box while the job is running.
+///
+/// The console output is streamed into the pod logs, and uploaded into the bucket
+/// per box and attempt when the pod is terminated.
+pub(crate) fn sidecar(config: &KissConsoleConfig, job: &AnsibleJob<'_>) -> Option<Container> {
+    if !config.enabled
+        || !matches!(
+            job.new_state,
+            Some(BoxState::Commissioning | BoxState::Joining | BoxState::Upgrading),
+        )
+    {
+        return None;
+    }
+
+    let host = job
+        .r#box
+        .spec
+        .power
+        .as_ref()
+        .filter(|power| matches!(power.r#type, BoxPowerType::Ipmi))
+        .and_then(|power| power.address.as_ref())?
+        .to_string();
+
+    let attempt = job
+        .r#box
+        .status
+        .as_ref()
+        .and_then(|status| status.retry.as_ref())
+        .filter(|retry| retry.task == job.task)
+        .map(|retry| retry.attempts)
+        .unwrap_or_default()
+        + 1;
+
+    let secret_env = |name: &str, key: &str| EnvVar {
+        name: name.into(),
+        value_from: Some(EnvVarSource {
+            secret_key_ref: Some(SecretKeySelector {
+                name: "kiss-config".into(),
+                key: key.into(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let value_env = |name: &str, value: String| EnvVar {
+        name: name.into(),
+        value: Some(value),
+        ..Default::default()
+    };
+
+    let mut env = vec![
+        value_env("KISS_CONSOLE_HOST", host),
+        value_env("KISS_CONSOLE_PROTOCOL", config.protocol.to_string()),
+        value_env(
+            "KISS_CONSOLE_KEY",
+            format!(
+                "{box_name}/{task}/attempt-{attempt}",
+                box_name = job.r#box.spec.machine.uuid,
+                task = job.task,
+            ),
+        ),
+        secret_env("KISS_CONSOLE_USERNAME", "power_ipmi_username"),
+        // NOTE: read by `ipmitool -E` and `sshpass -e`
+        secret_env("IPMI_PASSWORD", "power_ipmi_password"),
+        secret_env("SSHPASS", "power_ipmi_password"),
+        EnvVar {
+            name: "POD_NAME".into(),
+            value_from: Some(EnvVarSource {
+                field_ref: Some(ObjectFieldSelector {
+                    field_path: "metadata.name".into(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+    ];
+    if let Some(bucket) = &config.bucket {
+        env.push(value_env("KISS_CONSOLE_BUCKET", bucket.name.clone()));
+        env.push(value_env(
+            "KISS_CONSOLE_BUCKET_ENDPOINT",
+            bucket.endpoint.clone(),
+        ));
+    }
+
+    Some(Container {
+        name: "console".into(),
+        image: Some(config.image.clone()),
+        image_pull_policy: Some("Always".into()),
+        command: Some(vec!["bash".into(), "-c".into()]),
+        args: Some(vec![SCRIPT.into()]),
+        env: Some(env),
+        env_from: config.bucket.as_ref().map(|bucket| {
+            vec![EnvFromSource {
+                secret_ref: Some(SecretEnvSource {
+                    name: bucket.secret_name.clone(),
+                    optional: Some(false),
+                }),
+                ..Default::default()
+            }]
+        }),
+        // NOTE: run as a native sidecar, which is terminated after the ansible container
+        restart_policy: Some("Always".into()),
+        ..Default::default()
+    })
+}
+
+const SCRIPT: &str = r#"
+set -u
+
+LOG_FILE="/tmp/console.log"
+
+function upload() {
+    if [ -n "${KISS_CONSOLE_BUCKET:-}" ]; then
+        aws s3 cp --endpoint-url "${KISS_CONSOLE_BUCKET_ENDPOINT}" "${LOG_FILE}" \
+            "s3://${KISS_CONSOLE_BUCKET}/${KISS_CONSOLE_KEY}-${POD_NAME}.log" ||
+            echo "[console] failed to upload the console log" >&2
+    fi
+}
+trap upload EXIT
+trap 'exit 0' INT TERM
+
+function capture() {
+    while true; do
+        echo "[console] connecting to ${KISS_CONSOLE_HOST} (${KISS_CONSOLE_PROTOCOL})"
+        case "${KISS_CONSOLE_PROTOCOL}" in
+        ipmi)
+            # NOTE: release the session left by the previous attempts
+            ipmitool -I lanplus -H "${KISS_CONSOLE_HOST}" -U "${KISS_CONSOLE_USERNAME}" -E \
+                sol deactivate >/dev/null 2>&1 || true
+            sleep infinity | ipmitool -I lanplus -H "${KISS_CONSOLE_HOST}" \
+                -U "${KISS_CONSOLE_USERNAME}" -E sol activate
+            ;;
+        redfish)
+            sleep infinity | sshpass -e ssh -tt \
+                -o StrictHostKeyChecking=no -o UserKnownHostsFile=/dev/null \
+                "${KISS_CONSOLE_USERNAME}@${KISS_CONSOLE_HOST}"
+            ;;
+        esac
+        echo "[console] disconnected; reconnecting in 5 seconds"
+        sleep 5
+    done
+}
+
+capture 2>&1 | tee -a "${LOG_FILE}" &
+wait "$!"
+"#;
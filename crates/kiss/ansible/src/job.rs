@@ -1,6 +1,9 @@
-use k8s_openapi::api::core::v1::{
-    Affinity, NodeAffinity, NodeSelector, NodeSelectorRequirement, NodeSelectorTerm,
-    PreferredSchedulingTerm,
+use k8s_openapi::api::{
+    batch::v1::Job,
+    core::v1::{
+        Affinity, NodeAffinity, NodeSelector, NodeSelectorRequirement, NodeSelectorTerm,
+        PreferredSchedulingTerm,
+    },
 };
 
 pub fn affinity() -> Affinity {
@@ -34,3 +37,18 @@ pub fn affinity() -> Affinity {
         ..Default::default()
     }
 }
+
+/// Return `true` if the job is completed or failed.
+pub fn is_finished(job: &Job) -> bool {
+    job.metadata.deletion_timestamp.is_none()
+        && job
+            .status
+            .as_ref()
+            .and_then(|status| status.conditions.as_ref())
+            .is_some_and(|conditions| {
+                conditions.iter().any(|condition| {
+                    matches!(condition.type_.as_str(), "Complete" | "Failed")
+                        && condition.status == "True"
+                })
+            })
+}
@@ -1,6 +1,11 @@
 pub mod cluster;
-mod config;
+pub mod config;
+pub mod console;
 pub mod job;
+mod lock;
+mod maintenance;
+
+use std::collections::BTreeMap;
 
 use anyhow::Result;
 use inflector::Inflector;
@@ -15,13 +20,18 @@ use k8s_openapi::{
     },
     apimachinery::pkg::api::resource::Quantity,
 };
-use kiss_api::r#box::{BoxCrd, BoxGroupRole, BoxGroupSpec, BoxPowerType, BoxState};
+use kiss_api::{
+    group::BoxGroupCrd,
+    ipam::IpAddressCrd,
+    network_policy::NetworkPolicyProfileCrd,
+    r#box::{BoxCrd, BoxGroupRole, BoxGroupSpec, BoxPowerType, BoxState, BoxVersionSpec},
+};
 use kube::{
     api::{DeleteParams, ListParams, PostParams},
     core::ObjectMeta,
     Api, Client, Error,
 };
-use tracing::{info, instrument, Level};
+use tracing::{info, instrument, warn, Level};
 
 pub struct AnsibleClient {
     pub kiss: self::config::KissConfig,
@@ -31,10 +41,16 @@ impl AnsibleClient {
     pub const LABEL_BOX_NAME: &'static str = "kiss.ulagbulag.io/box_name";
     pub const LABEL_BOX_MACHINE_UUID: &'static str = "kiss.ulagbulag.io/box_machine_uuid";
     pub const LABEL_COMPLETED_STATE: &'static str = "kiss.ulagbulag.io/completed_state";
+    pub const LABEL_FAILED_STATE: &'static str = "kiss.ulagbulag.io/failed_state";
     pub const LABEL_JOB_NAME: &'static str = "kiss.ulagbulag.io/job_name";
     pub const LABEL_JOB_IS_CRITICAL: &'static str = "kiss.ulagbulag.io/is_critical";
     pub const LABEL_VERIFY_BIND_GROUP: &'static str = "kiss.ulagbulag.io/verify-bind-group";
 
+    pub const ANNOTATION_COMPLETED_VERSION_KERNEL: &'static str =
+        "kiss.ulagbulag.io/completed_version_kernel";
+    pub const ANNOTATION_COMPLETED_VERSION_OS: &'static str =
+        "kiss.ulagbulag.io/completed_version_os";
+
     #[instrument(level = Level::INFO, skip_all, err(Display))]
     pub async fn try_default(kube: &Client) -> Result<Self> {
         Ok(Self {
@@ -43,12 +59,44 @@ impl AnsibleClient {
     }
 
     #[instrument(level = Level::INFO, skip(self, kube, job), err(Display))]
-    pub async fn spawn(&self, kube: &Client, job: AnsibleJob<'_>) -> Result<bool, Error> {
-        let ns = ::kiss_api::consts::NAMESPACE;
+    pub async fn spawn(
+        &self,
+        kube: &Client,
+        job: AnsibleJob<'_>,
+    ) -> Result<AnsibleJobStatus, Error> {
         let box_name = job.r#box.spec.machine.uuid.to_string();
-        let box_status = job.r#box.status.as_ref();
         let name = format!("box-{}-{}", &job.task, &box_name);
 
+        // pause spawning the jobs during the maintenance windows
+        if let Some(window) = self::maintenance::load_freezing_window(kube).await? {
+            info!("Frozen by the maintenance window ({window}): {name}");
+            return Ok(AnsibleJobStatus::Frozen);
+        }
+
+        // realize mutual exclusivity (SPAWNER)
+        let lock = match self::lock::BoxLock::try_acquire(kube, &box_name, &name).await? {
+            Some(lock) => lock,
+            None => {
+                info!("Another job is being spawned: {name}");
+                return Ok(AnsibleJobStatus::Busy);
+            }
+        };
+
+        let result = self.try_spawn(kube, job, &box_name, name).await;
+        lock.release().await?;
+        result
+    }
+
+    async fn try_spawn(
+        &self,
+        kube: &Client,
+        job: AnsibleJob<'_>,
+        box_name: &str,
+        name: String,
+    ) -> Result<AnsibleJobStatus, Error> {
+        let ns = ::kiss_api::consts::NAMESPACE;
+        let box_status = job.r#box.status.as_ref();
+
         let verify_bind_group = job
             .r#box
             .metadata
@@ -66,13 +114,72 @@ impl AnsibleClient {
         let group = &job.r#box.spec.group;
         let reset = self.kiss.group_force_reset || verify_bind_group && bind_group != Some(group);
 
+        // collect the static addresses reserved for the box
+        let reservations: BTreeMap<_, _> = {
+            let api = Api::<IpAddressCrd>::all(kube.clone());
+            let lp = ListParams::default();
+            let uuid = job.r#box.spec.machine.uuid;
+            api.list(&lp)
+                .await?
+                .items
+                .into_iter()
+                .filter(|address| address.spec.reservation == Some(uuid))
+                .map(|address| (address.spec.network, address.spec.address))
+                .collect()
+        };
+
+        // render the network policy profile of the box group
+        let network_policy = {
+            let api = Api::<BoxGroupCrd>::all(kube.clone());
+            let lp = ListParams::default();
+            let profile = api
+                .list(&lp)
+                .await?
+                .items
+                .into_iter()
+                .filter(|group| group.contains(&job.r#box))
+                .find_map(|group| group.spec.network_policy);
+
+            match profile {
+                Some(name) => {
+                    let api = Api::<NetworkPolicyProfileCrd>::all(kube.clone());
+                    let profile = match api.get_opt(&name).await? {
+                        Some(profile) => profile,
+                        None => {
+                            warn!("failed to find the network policy profile: {name}");
+                            return Ok(AnsibleJobStatus::NetworkPolicyNotReady);
+                        }
+                    };
+                    let render = || {
+                        Ok::<_, String>((
+                            profile.spec.digest()?,
+                            profile.spec.to_nftables()?,
+                            ::serde_json::to_string(&profile.spec.sysctl)
+                                .map_err(|error| error.to_string())?,
+                        ))
+                    };
+                    match render() {
+                        Ok((digest, nftables, sysctl)) => {
+                            Some((name, profile.spec.enforce, digest, nftables, sysctl))
+                        }
+                        Err(error) => {
+                            warn!("failed to render the network policy profile ({name}): {error}");
+                            return Ok(AnsibleJobStatus::NetworkPolicyNotReady);
+                        }
+                    }
+                }
+                None => None,
+            }
+        };
+
         let priority_class_name = match group.role {
             BoxGroupRole::ControlPlane => "system-cluster-critical",
             _ => "k8s-cluster-critical",
         };
 
         {
-            let dp = DeleteParams::background();
+            // NOTE: the jobs are left until their pods are terminated
+            let dp = DeleteParams::foreground();
             let lp = ListParams {
                 label_selector: Some(format!(
                     "{}={box_name},{}!=true",
@@ -94,6 +201,25 @@ impl AnsibleClient {
             }
         }
 
+        // realize mutual exclusivity (BOX)
+        {
+            let api = Api::<Job>::namespaced(kube.clone(), ns);
+            let lp = ListParams {
+                label_selector: Some(format!("{}={box_name}", AnsibleClient::LABEL_BOX_NAME)),
+                ..Default::default()
+            };
+            if api
+                .list(&lp)
+                .await?
+                .items
+                .iter()
+                .any(|job| !crate::job::is_finished(job))
+            {
+                info!("Waiting for the other jobs to be terminated: {name}");
+                return Ok(AnsibleJobStatus::Busy);
+            }
+        }
+
         // realize mutual exclusivity (QUEUE)
         let cluster_state =
             self::cluster::ClusterState::load(kube, &self.kiss, &job.r#box.spec, job.use_workers)
@@ -107,7 +233,7 @@ impl AnsibleClient {
                     &box_name,
                     &job.r#box.spec.group.cluster_name,
                 );
-                return Ok(false);
+                return Ok(AnsibleJobStatus::ClusterNotReady);
             }
         }
 
@@ -117,7 +243,7 @@ impl AnsibleClient {
             namespace: Some(ns.into()),
             labels: Some(
                 vec![
-                    Some((Self::LABEL_BOX_NAME.into(), box_name.clone())),
+                    Some((Self::LABEL_BOX_NAME.into(), box_name.to_string())),
                     Some((
                         Self::LABEL_BOX_MACHINE_UUID.into(),
                         job.r#box.spec.machine.uuid.to_string(),
@@ -133,11 +259,31 @@ impl AnsibleClient {
                         .as_ref()
                         .map(ToString::to_string)
                         .map(|state| (Self::LABEL_COMPLETED_STATE.into(), state)),
+                    job.new_state
+                        .map(|state| state.fail())
+                        .as_ref()
+                        .map(ToString::to_string)
+                        .map(|state| (Self::LABEL_FAILED_STATE.into(), state)),
                 ]
                 .into_iter()
                 .flatten()
                 .collect(),
             ),
+            annotations: job.new_version.map(|version| {
+                vec![
+                    version
+                        .kernel
+                        .clone()
+                        .map(|kernel| (Self::ANNOTATION_COMPLETED_VERSION_KERNEL.into(), kernel)),
+                    version
+                        .os
+                        .clone()
+                        .map(|os| (Self::ANNOTATION_COMPLETED_VERSION_OS.into(), os)),
+                ]
+                .into_iter()
+                .flatten()
+                .collect()
+            }),
             ..Default::default()
         };
         let spec = JobSpec {
@@ -157,6 +303,8 @@ impl AnsibleClient {
                         ..Default::default()
                     }),
                     host_network: Some(true),
+                    init_containers: self::console::sidecar(&self.kiss.console, &job)
+                        .map(|container| vec![container]),
                     priority_class_name: Some(priority_class_name.into()),
                     restart_policy: Some("OnFailure".into()),
                     service_account: Some("ansible-playbook".into()),
@@ -210,6 +358,11 @@ impl AnsibleClient {
                                 value: Some(job.r#box.spec.machine.uuid.to_string()),
                                 ..Default::default()
                             },
+                            EnvVar {
+                                name: "kiss_box_arch".into(),
+                                value: Some(job.r#box.spec.machine.arch().to_string()),
+                                ..Default::default()
+                            },
                             EnvVar {
                                 name: "ansible_ssh_host".into(),
                                 value: box_status
@@ -252,7 +405,14 @@ impl AnsibleClient {
                             EnvVar {
                                 name: "kiss_cluster_control_planes".into(),
                                 value: Some(
-                                    if matches!(job.new_state, None | Some(BoxState::Joining)) {
+                                    if matches!(
+                                        job.new_state,
+                                        None | Some(
+                                            BoxState::Joining
+                                                | BoxState::Upgrading
+                                                | BoxState::Decommissioning
+                                        )
+                                    ) {
                                         cluster_state.get_control_planes_as_string()
                                     } else {
                                         Default::default()
@@ -263,7 +423,14 @@ impl AnsibleClient {
                             EnvVar {
                                 name: "kiss_cluster_etcd_nodes".into(),
                                 value: Some(
-                                    if matches!(job.new_state, None | Some(BoxState::Joining)) {
+                                    if matches!(
+                                        job.new_state,
+                                        None | Some(
+                                            BoxState::Joining
+                                                | BoxState::Upgrading
+                                                | BoxState::Decommissioning
+                                        )
+                                    ) {
                                         cluster_state.get_etcd_nodes_as_string()
                                     } else {
                                         Default::default()
@@ -305,6 +472,26 @@ impl AnsibleClient {
                                 }),
                                 ..Default::default()
                             },
+                            EnvVar {
+                                name: "kiss_decommission_disks".into(),
+                                value: job
+                                    .r#box
+                                    .spec
+                                    .decommission
+                                    .as_ref()
+                                    .and_then(|spec| ::serde_json::to_string(&spec.disks).ok()),
+                                ..Default::default()
+                            },
+                            EnvVar {
+                                name: "kiss_decommission_wipe_method".into(),
+                                value: job
+                                    .r#box
+                                    .spec
+                                    .decommission
+                                    .as_ref()
+                                    .map(|spec| spec.wipe_method.to_string()),
+                                ..Default::default()
+                            },
                             EnvVar {
                                 name: "kiss_group_enable_default_cluster".into(),
                                 value: Some(self.kiss.group_enable_default_cluster.to_string()),
@@ -428,6 +615,51 @@ impl AnsibleClient {
                                 }),
                                 ..Default::default()
                             },
+                            EnvVar {
+                                name: "kiss_networks".into(),
+                                value: ::serde_json::to_string(&self.kiss.networks).ok(),
+                                ..Default::default()
+                            },
+                            EnvVar {
+                                name: "kiss_networks_reserved".into(),
+                                value: ::serde_json::to_string(&reservations).ok(),
+                                ..Default::default()
+                            },
+                            EnvVar {
+                                name: "kiss_network_policy_digest".into(),
+                                value: network_policy
+                                    .as_ref()
+                                    .map(|(_, _, digest, _, _)| digest.clone()),
+                                ..Default::default()
+                            },
+                            EnvVar {
+                                name: "kiss_network_policy_enforce".into(),
+                                value: network_policy
+                                    .as_ref()
+                                    .map(|(_, enforce, _, _, _)| enforce.to_string()),
+                                ..Default::default()
+                            },
+                            EnvVar {
+                                name: "kiss_network_policy_nftables".into(),
+                                value: network_policy
+                                    .as_ref()
+                                    .map(|(_, _, _, nftables, _)| nftables.clone()),
+                                ..Default::default()
+                            },
+                            EnvVar {
+                                name: "kiss_network_policy_profile".into(),
+                                value: network_policy
+                                    .as_ref()
+                                    .map(|(name, _, _, _, _)| name.clone()),
+                                ..Default::default()
+                            },
+                            EnvVar {
+                                name: "kiss_network_policy_sysctl".into(),
+                                value: network_policy
+                                    .as_ref()
+                                    .map(|(_, _, _, _, sysctl)| sysctl.clone()),
+                                ..Default::default()
+                            },
                             EnvVar {
                                 name: "kiss_os_default".into(),
                                 value: Some(self.kiss.os_default.to_string()),
@@ -510,6 +742,16 @@ impl AnsibleClient {
                                 }),
                                 ..Default::default()
                             },
+                            EnvVar {
+                                name: "kiss_upgrade_kernel_version".into(),
+                                value: job.new_version.and_then(|version| version.kernel.clone()),
+                                ..Default::default()
+                            },
+                            EnvVar {
+                                name: "kiss_upgrade_os_version".into(),
+                                value: job.new_version.and_then(|version| version.os.clone()),
+                                ..Default::default()
+                            },
                         ]),
                         resources: Some(job.resource_type.into()),
                         volume_mounts: Some(vec![
@@ -635,7 +877,46 @@ impl AnsibleClient {
         }
 
         info!("spawned a job: {name}");
-        Ok(true)
+        Ok(AnsibleJobStatus::Spawned)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AnsibleJobStatus {
+    Spawned,
+    /// The other jobs of the box are not terminated yet.
+    Busy,
+    /// The cluster is not ready to accept the box.
+    ClusterNotReady,
+    /// The network policy profile of the box group is missing or invalid.
+    NetworkPolicyNotReady,
+    /// The jobs are paused by a maintenance window.
+    Frozen,
+}
+
+impl AnsibleJobStatus {
+    pub const fn is_spawned(&self) -> bool {
+        matches!(self, Self::Spawned)
+    }
+
+    pub const fn reason(&self) -> &'static str {
+        match self {
+            Self::Spawned => "Spawned",
+            Self::Busy => "JobRunning",
+            Self::ClusterNotReady => "ClusterNotReady",
+            Self::NetworkPolicyNotReady => "NetworkPolicyNotReady",
+            Self::Frozen => "MaintenanceWindow",
+        }
+    }
+
+    pub const fn message(&self) -> &'static str {
+        match self {
+            Self::Spawned => "the job is spawned",
+            Self::Busy => "waiting for the other jobs of the box to be terminated",
+            Self::ClusterNotReady => "waiting for the cluster to be ready",
+            Self::NetworkPolicyNotReady => "waiting for the network policy profile to be valid",
+            Self::Frozen => "waiting for the maintenance window to be closed",
+        }
     }
 }
 
@@ -645,6 +926,7 @@ pub struct AnsibleJob<'a> {
     pub r#box: &'a BoxCrd,
     pub new_group: Option<&'a BoxGroupSpec>,
     pub new_state: Option<BoxState>,
+    pub new_version: Option<&'a BoxVersionSpec>,
     pub is_critical: bool,
     pub resource_type: AnsibleResourceType,
     pub use_workers: bool,
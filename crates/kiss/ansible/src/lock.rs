@@ -0,0 +1,106 @@
+use k8s_openapi::{
+    api::coordination::v1::{Lease, LeaseSpec},
+    apimachinery::pkg::apis::meta::v1::{MicroTime, Preconditions},
+    chrono::{Duration, Utc},
+};
+use kube::{
+    api::{DeleteParams, PostParams},
+    core::ObjectMeta,
+    Api, Client, Error, ResourceExt,
+};
+use tracing::{instrument, Level};
+
+/// A lease which guarantees that at most one spawner handles the jobs of the box at once.
+pub(crate) struct BoxLock {
+    api: Api<Lease>,
+    name: String,
+}
+
+impl BoxLock {
+    // NOTE: the lock is held only while spawning a job, so it expires shortly
+    const DURATION_SECONDS: i32 = 60;
+
+    /// Try to acquire the lock of the box, returning `None` if it is held by another spawner.
+    #[instrument(level = Level::INFO, skip(kube), err(Display))]
+    pub(crate) async fn try_acquire(
+        kube: &Client,
+        box_name: &str,
+        holder: &str,
+    ) -> Result<Option<Self>, Error> {
+        let ns = ::kiss_api::consts::NAMESPACE;
+        let api = Api::<Lease>::namespaced(kube.clone(), ns);
+        let name = format!("box-{box_name}");
+
+        let now = Utc::now();
+        let lease = Lease {
+            metadata: ObjectMeta {
+                name: Some(name.clone()),
+                namespace: Some(ns.into()),
+                ..Default::default()
+            },
+            spec: Some(LeaseSpec {
+                acquire_time: Some(MicroTime(now)),
+                holder_identity: Some(holder.into()),
+                lease_duration_seconds: Some(Self::DURATION_SECONDS),
+                renew_time: Some(MicroTime(now)),
+                ..Default::default()
+            }),
+        };
+        let pp = PostParams {
+            dry_run: false,
+            field_manager: Some("kiss-ansible".into()),
+        };
+
+        match api.create(&pp, &lease).await {
+            Ok(_) => return Ok(Some(Self { api, name })),
+            Err(Error::Api(error)) if error.code == 409 => (),
+            Err(error) => return Err(error),
+        }
+
+        // take over the expired lock, which has been left by a crashed spawner
+        let last = match api.get_opt(&name).await? {
+            Some(last) => last,
+            None => return Ok(None),
+        };
+        let is_expired = last
+            .spec
+            .as_ref()
+            .and_then(|spec| {
+                let renew_time = spec.renew_time.as_ref()?.0;
+                let duration = Duration::seconds(spec.lease_duration_seconds?.into());
+                Some(renew_time + duration < now)
+            })
+            .unwrap_or(true);
+        if !is_expired {
+            return Ok(None);
+        }
+
+        let dp = DeleteParams {
+            preconditions: Some(Preconditions {
+                resource_version: last.resource_version(),
+                uid: None,
+            }),
+            ..Default::default()
+        };
+        match api.delete(&name, &dp).await {
+            Ok(_) => (),
+            Err(Error::Api(error)) if error.code == 404 || error.code == 409 => return Ok(None),
+            Err(error) => return Err(error),
+        }
+        match api.create(&pp, &lease).await {
+            Ok(_) => Ok(Some(Self { api, name })),
+            Err(Error::Api(error)) if error.code == 409 => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip_all, fields(name = %self.name), err(Display))]
+    pub(crate) async fn release(self) -> Result<(), Error> {
+        let dp = DeleteParams::default();
+        match self.api.delete(&self.name, &dp).await {
+            Ok(_) => Ok(()),
+            Err(Error::Api(error)) if error.code == 404 => Ok(()),
+            Err(error) => Err(error),
+        }
+    }
+}
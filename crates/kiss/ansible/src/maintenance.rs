@@ -0,0 +1,44 @@
+use k8s_openapi::chrono::{DateTime, Utc};
+use kube::{
+    api::{ApiResource, DynamicObject, GroupVersionKind, ListParams},
+    Api, Client, Error, ResourceExt,
+};
+use serde_json::Value;
+use tracing::{instrument, Level};
+
+/// Find the maintenance window of the dash which freezes the KISS jobs.
+///
+/// NOTE: the window is loaded dynamically, so that KISS does not depend on the dash.
+#[instrument(level = Level::INFO, skip(kube), err(Display))]
+pub(crate) async fn load_freezing_window(kube: &Client) -> Result<Option<String>, Error> {
+    let gvk = GroupVersionKind::gvk("dash.ulagbulag.io", "v1alpha1", "MaintenanceWindow");
+    let ar = ApiResource::from_gvk_with_plural(&gvk, "maintenancewindows");
+    let api = Api::<DynamicObject>::all_with(kube.clone(), &ar);
+    let lp = ListParams::default();
+
+    let windows = match api.list(&lp).await {
+        Ok(list) => list.items,
+        // NOTE: the dash is not installed
+        Err(Error::Api(error)) if error.code == 404 => return Ok(None),
+        Err(error) => return Err(error),
+    };
+
+    let now = Utc::now();
+    Ok(windows
+        .into_iter()
+        .find(|window| is_freezing(&window.data["spec"], now))
+        .map(|window| window.name_any()))
+}
+
+fn is_freezing(spec: &Value, now: DateTime<Utc>) -> bool {
+    let parse = |key: &str| {
+        spec[key]
+            .as_str()
+            .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+            .map(|value| value.with_timezone(&Utc))
+    };
+
+    spec["kiss"].as_bool().unwrap_or_default()
+        && !parse("startsAt").is_some_and(|starts_at| now < starts_at)
+        && !parse("endsAt").is_some_and(|ends_at| now >= ends_at)
+}
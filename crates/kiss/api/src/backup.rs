@@ -0,0 +1,128 @@
+use chrono::{DateTime, Utc};
+use kube::{CustomResource, ResourceExt};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// A scheduled backup of a cluster, which stores the etcd snapshots
+/// and the manifests of the custom resources into an object storage bucket.
+///
+/// The backups are taken by a `CronJob` which is managed by the operator.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema, CustomResource)]
+#[kube(
+    category = "kiss",
+    group = "kiss.ulagbulag.io",
+    version = "v1alpha1",
+    kind = "ClusterBackup",
+    root = "ClusterBackupCrd",
+    status = "ClusterBackupStatus",
+    shortname = "clusterbackup",
+    printcolumn = r#"{
+        "name": "cluster",
+        "type": "string",
+        "description": "cluster name",
+        "jsonPath": ".spec.clusterName"
+    }"#,
+    printcolumn = r#"{
+        "name": "schedule",
+        "type": "string",
+        "description": "backup schedule",
+        "jsonPath": ".spec.schedule"
+    }"#,
+    printcolumn = r#"{
+        "name": "last-successful",
+        "type": "date",
+        "description": "last successful backup time",
+        "jsonPath": ".status.lastSuccessfulBackup"
+    }"#,
+    printcolumn = r#"{
+        "name": "created-at",
+        "type": "date",
+        "description": "created time",
+        "jsonPath": ".metadata.creationTimestamp"
+    }"#,
+    printcolumn = r#"{
+        "name": "version",
+        "type": "integer",
+        "description": "backup version",
+        "jsonPath": ".metadata.generation"
+    }"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusterBackupSpec {
+    #[serde(default = "ClusterBackupSpec::default_cluster_name")]
+    pub cluster_name: String,
+    /// The cron schedule of the backups.
+    #[serde(default = "ClusterBackupSpec::default_schedule")]
+    pub schedule: String,
+    pub bucket: ClusterBackupBucketSpec,
+    /// Whether to take the etcd snapshots.
+    #[serde(default = "ClusterBackupSpec::default_etcd")]
+    pub etcd: bool,
+    /// The API groups of the custom resources to be collected.
+    #[serde(default = "ClusterBackupSpec::default_groups")]
+    pub groups: Vec<String>,
+    /// The number of the latest backups to be kept.
+    #[serde(default = "ClusterBackupSpec::default_retention")]
+    pub retention: u32,
+}
+
+impl ClusterBackupSpec {
+    fn default_cluster_name() -> String {
+        "default".into()
+    }
+
+    fn default_schedule() -> String {
+        "0 3 * * *".into() // daily
+    }
+
+    const fn default_etcd() -> bool {
+        true
+    }
+
+    fn default_groups() -> Vec<String> {
+        vec!["kiss.ulagbulag.io".into(), "dash.ulagbulag.io".into()]
+    }
+
+    const fn default_retention() -> u32 {
+        7
+    }
+}
+
+impl ClusterBackupCrd {
+    pub const LABEL_BACKUP_NAME: &'static str = "kiss.ulagbulag.io/backup-name";
+
+    pub fn cron_job_name(&self) -> String {
+        format!("cluster-backup-{}", self.name_any())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusterBackupBucketSpec {
+    /// The S3-compatible endpoint, e.g. `http://minio.dash.svc`.
+    pub endpoint: String,
+    pub name: String,
+    /// The prefix of the backup objects; defaults to the cluster name.
+    #[serde(default)]
+    pub prefix: Option<String>,
+    /// The secret which stores `AWS_ACCESS_KEY_ID` and `AWS_SECRET_ACCESS_KEY`.
+    pub secret_name: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusterBackupStatus {
+    /// The cron job which takes the backups.
+    #[serde(default)]
+    pub cron_job_name: Option<String>,
+    /// The completion time of the last verified backup.
+    #[serde(default)]
+    pub last_successful_backup: Option<DateTime<Utc>>,
+    /// The job of the last verified backup.
+    #[serde(default)]
+    pub last_successful_job_name: Option<String>,
+    /// The start time of the last failed backup.
+    #[serde(default)]
+    pub last_failed_backup: Option<DateTime<Utc>>,
+    pub last_updated: DateTime<Utc>,
+}
@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use kube::{CustomResource, ResourceExt};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+use uuid::Uuid;
+
+use crate::r#box::{BoxCrd, BoxGroupSpec};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema, CustomResource)]
+#[kube(
+    category = "kiss",
+    group = "kiss.ulagbulag.io",
+    version = "v1alpha1",
+    kind = "BootProfile",
+    root = "BootProfileCrd",
+    status = "BootProfileStatus",
+    shortname = "bootprofile",
+    printcolumn = r#"{
+        "name": "priority",
+        "type": "integer",
+        "description": "priority of the boot profile",
+        "jsonPath": ".spec.priority"
+    }"#,
+    printcolumn = r#"{
+        "name": "kernel",
+        "type": "string",
+        "description": "kernel image of the boot profile",
+        "jsonPath": ".spec.boot.kernel"
+    }"#,
+    printcolumn = r#"{
+        "name": "created-at",
+        "type": "date",
+        "description": "created time of the boot profile",
+        "jsonPath": ".metadata.creationTimestamp"
+    }"#,
+    printcolumn = r#"{
+        "name": "updated-at",
+        "type": "date",
+        "description": "updated time of the boot profile",
+        "jsonPath": ".status.lastUpdated"
+    }"#,
+    printcolumn = r#"{
+        "name": "version",
+        "type": "integer",
+        "description": "boot profile version",
+        "jsonPath": ".metadata.generation"
+    }"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct BootProfileSpec {
+    pub boot: BootProfileBootSpec,
+    /// The profile with the highest priority is applied if a box is matched by multiple profiles.
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(default)]
+    pub selector: BootProfileSelectorSpec,
+    #[serde(default)]
+    pub template: Option<BootProfileTemplateSpec>,
+}
+
+impl BootProfileCrd {
+    pub const FINALIZER_NAME: &'static str = "kiss.ulagbulag.io/finalizer-boot-profiles";
+
+    /// The config map which serves the iPXE scripts and the provisioning templates.
+    pub const CONFIG_MAP_NAME: &'static str = "boot-profiles";
+    /// The config map which serves the matchbox groups.
+    pub const CONFIG_MAP_NAME_GROUPS: &'static str = "boot-profiles-groups";
+    /// The config map which serves the matchbox profiles.
+    pub const CONFIG_MAP_NAME_PROFILES: &'static str = "boot-profiles-profiles";
+
+    /// Return the most preferred profile for the given machine.
+    pub fn find<'a, I>(profiles: I, uuid: Uuid, r#box: Option<&BoxCrd>) -> Option<&'a Self>
+    where
+        I: IntoIterator<Item = &'a Self>,
+    {
+        profiles
+            .into_iter()
+            .filter(|profile| profile.spec.selector.matches(uuid, r#box))
+            // NOTE: the smallest name is preferred among the profiles with the same priority
+            .max_by(|a, b| {
+                a.spec
+                    .priority
+                    .cmp(&b.spec.priority)
+                    .then_with(|| b.name_any().cmp(&a.name_any()))
+            })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BootProfileBootSpec {
+    /// The URL of the kernel image.
+    pub kernel: String,
+    /// The URLs of the initial ramdisks.
+    #[serde(default)]
+    pub initrd: Vec<String>,
+    /// The kernel arguments.
+    ///
+    /// The URL of the template is given as `${template_url}`.
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BootProfileSelectorSpec {
+    /// The group of the boxes.
+    #[serde(default)]
+    pub group: Option<BoxGroupSpec>,
+    /// The labels of the boxes.
+    #[serde(default)]
+    pub match_labels: BTreeMap<String, String>,
+    /// The machine UUIDs, which can be given even before the boxes are registered.
+    #[serde(default)]
+    pub machines: Vec<Uuid>,
+}
+
+impl BootProfileSelectorSpec {
+    pub fn matches(&self, uuid: Uuid, r#box: Option<&BoxCrd>) -> bool {
+        if !self.machines.is_empty() && !self.machines.contains(&uuid) {
+            return false;
+        }
+
+        match r#box {
+            Some(r#box) => {
+                let labels = r#box.labels();
+                self.group
+                    .as_ref()
+                    .map(|group| group == &r#box.spec.group)
+                    .unwrap_or(true)
+                    && self
+                        .match_labels
+                        .iter()
+                        .all(|(key, value)| labels.get(key) == Some(value))
+            }
+            // unregistered machines can be selected only by their UUIDs
+            None => {
+                !self.machines.is_empty() && self.group.is_none() && self.match_labels.is_empty()
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BootProfileTemplateSpec {
+    pub kind: BootProfileTemplateKind,
+    /// The template, which is served as-is.
+    pub content: String,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum BootProfileTemplateKind {
+    CloudInit,
+    Ignition,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BootProfileStatus {
+    /// The machines which are booted with this profile.
+    #[serde(default)]
+    pub machines: Vec<Uuid>,
+    pub last_updated: DateTime<Utc>,
+}
@@ -7,9 +7,15 @@ use serde::{Deserialize, Serialize};
 use strum::{Display, EnumString};
 use uuid::Uuid;
 
-use crate::rack::RackRef;
+use crate::{condition::Condition, network_policy::BoxNetworkPolicyStatus, rack::RackRef};
 
 impl BoxCrd {
+    /// The label which releases the quarantined box to be provisioned.
+    pub const LABEL_ENROLLMENT_APPROVED: &'static str = "kiss.ulagbulag.io/enrollment-approved";
+
+    /// The label which releases the box quarantined by the remediation.
+    pub const LABEL_REMEDIATION_RELEASED: &'static str = "kiss.ulagbulag.io/remediation-released";
+
     pub fn last_updated(&self) -> Option<&DateTime<Utc>> {
         self.status
             .as_ref()
@@ -96,6 +102,9 @@ impl BoxCrd {
 )]
 #[serde(rename_all = "camelCase")]
 pub struct BoxSpec {
+    /// Decommission the box: drain, wipe the disks, remove from the cluster and power off.
+    #[serde(default)]
+    pub decommission: Option<BoxDecommissionSpec>,
     #[serde(default)]
     pub group: BoxGroupSpec,
     pub machine: BoxMachineSpec,
@@ -114,7 +123,30 @@ pub struct BoxStatus {
     pub access: BoxAccessSpec,
     #[serde(default)]
     pub bind_group: Option<BoxGroupSpec>,
+    #[serde(default)]
+    pub conditions: Vec<Condition>,
+    /// The progress of the decommission, which is reported by the playbook.
+    #[serde(default)]
+    pub decommission: Option<BoxDecommissionStatus>,
+    /// The hardware inventory, which is collected on commissioning.
+    #[serde(default)]
+    pub inventory: Option<BoxInventorySpec>,
     pub last_updated: DateTime<Utc>,
+    /// The network interfaces assigned to each network, which are configured on commissioning.
+    #[serde(default)]
+    pub networks: Vec<BoxNetworkSpec>,
+    /// The compliance to the network policy profile, which is reported by the maintenance task.
+    #[serde(default)]
+    pub network_policy: Option<BoxNetworkPolicyStatus>,
+    /// The health of the bound node, which is tracked by the remediation.
+    #[serde(default)]
+    pub remediation: Option<BoxRemediationStatus>,
+    /// The failed attempts of the current task.
+    #[serde(default)]
+    pub retry: Option<BoxRetryStatus>,
+    /// The OS and kernel version, which is applied by the last upgrade.
+    #[serde(default)]
+    pub version: Option<BoxVersionSpec>,
 }
 
 #[derive(
@@ -136,24 +168,41 @@ pub struct BoxStatus {
 pub enum BoxState {
     #[default]
     New,
+    /// The box has no valid enrollment token, so it should be approved manually.
+    NewQuarantined,
     Commissioning,
     Ready,
     Joining,
     Running,
+    Upgrading,
+    UpgradeFailed,
+    /// The node has been not ready for a long time, so the box is being power-cycled.
+    Remediating,
+    /// The remediation keeps failing, so the box should be released manually.
+    Quarantined,
     GroupChanged,
     Failed,
     Disconnected,
+    Decommissioning,
+    DecommissionFailed,
+    Decommissioned,
 }
 
 impl BoxState {
     pub const fn as_task(&self) -> Option<&'static str> {
         match self {
-            Self::New => None,
+            Self::New | Self::NewQuarantined => None,
             Self::Commissioning => Some("commission"),
             Self::Ready => None,
             Self::Joining => Some("join"),
             Self::Running => Some("ping"),
+            Self::Upgrading => Some("upgrade-box"),
+            Self::UpgradeFailed => None,
+            Self::Remediating => Some("remediate"),
+            Self::Quarantined => None,
             Self::GroupChanged | Self::Failed | Self::Disconnected => Some("reset"),
+            Self::Decommissioning => Some("decommission"),
+            Self::DecommissionFailed | Self::Decommissioned => None,
         }
     }
 
@@ -168,13 +217,21 @@ impl BoxState {
     pub const fn next(&self) -> Self {
         match self {
             Self::New => Self::Commissioning,
+            Self::NewQuarantined => Self::NewQuarantined,
             Self::Commissioning => Self::Commissioning,
             Self::Ready => Self::Joining,
             Self::Joining => Self::Joining,
             Self::Running => Self::Running,
+            Self::Upgrading => Self::Upgrading,
+            Self::UpgradeFailed => Self::UpgradeFailed,
+            Self::Remediating => Self::Remediating,
+            Self::Quarantined => Self::Quarantined,
             Self::GroupChanged => Self::GroupChanged,
             Self::Failed => Self::Failed,
             Self::Disconnected => Self::Disconnected,
+            Self::Decommissioning => Self::Decommissioning,
+            Self::DecommissionFailed => Self::DecommissionFailed,
+            Self::Decommissioned => Self::Decommissioned,
         }
     }
 
@@ -182,12 +239,19 @@ impl BoxState {
         let fallback_update = Duration::try_hours(2).unwrap();
 
         match self {
-            Self::New => None,
+            Self::New | Self::NewQuarantined => None,
             Self::Commissioning => Some(fallback_update),
             Self::Ready => None,
             Self::Joining => Some(fallback_update),
             Self::Running => None,
+            Self::Upgrading => Some(fallback_update),
+            Self::UpgradeFailed => None,
+            Self::Remediating => Duration::try_minutes(30),
+            Self::Quarantined => None,
             Self::GroupChanged | Self::Failed | Self::Disconnected => None,
+            // NOTE: wiping the large disks may take a long time
+            Self::Decommissioning => Duration::try_hours(24),
+            Self::DecommissionFailed | Self::Decommissioned => None,
         }
     }
 
@@ -197,14 +261,49 @@ impl BoxState {
 
     pub const fn complete(&self) -> Option<Self> {
         match self {
-            Self::New => None,
+            Self::New | Self::NewQuarantined => None,
             Self::Commissioning => None,
             Self::Ready => None,
             Self::Joining => Some(Self::Running),
             Self::Running => None,
+            Self::Upgrading => Some(Self::Running),
+            Self::UpgradeFailed => None,
+            Self::Remediating => Some(Self::Running),
+            Self::Quarantined => None,
             Self::GroupChanged | Self::Failed | Self::Disconnected => None,
+            Self::Decommissioning => Some(Self::Decommissioned),
+            Self::DecommissionFailed | Self::Decommissioned => None,
+        }
+    }
+
+    pub const fn fail(&self) -> Self {
+        match self {
+            Self::Upgrading | Self::UpgradeFailed => Self::UpgradeFailed,
+            Self::Remediating | Self::Quarantined => Self::Quarantined,
+            Self::Decommissioning | Self::DecommissionFailed => Self::DecommissionFailed,
+            _ => Self::Failed,
         }
     }
+
+    /// Return `true` if the box is still a member of the bound cluster.
+    pub const fn is_joined(&self) -> bool {
+        matches!(
+            self,
+            Self::Running
+                | Self::Upgrading
+                | Self::UpgradeFailed
+                | Self::Remediating
+                | Self::Quarantined
+        )
+    }
+
+    /// Return `true` if the box is being (or has been) decommissioned.
+    pub const fn is_decommissioned(&self) -> bool {
+        matches!(
+            self,
+            Self::Decommissioning | Self::DecommissionFailed | Self::Decommissioned
+        )
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
@@ -236,6 +335,275 @@ pub struct BoxAccessInterfaceSpec {
     pub speed_mbps: Option<u64>,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxNetworkSpec {
+    /// The name of the network, which is defined in the KISS configuration.
+    pub name: String,
+    pub interface: String,
+    #[serde(default)]
+    pub vlan: Option<u16>,
+    #[serde(default)]
+    pub address: Option<IpAddr>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxVersionSpec {
+    /// The OS image version, e.g. `3975.2.1` (flatcar) or `9.4` (rocky9).
+    #[serde(default)]
+    pub os: Option<String>,
+    /// The kernel release, e.g. `6.8.0-45-generic`.
+    #[serde(default)]
+    pub kernel: Option<String>,
+}
+
+impl BoxVersionSpec {
+    /// Overlay the desired version onto the current one.
+    pub fn merge(&self, current: Option<&Self>) -> Self {
+        Self {
+            os: self
+                .os
+                .clone()
+                .or_else(|| current.and_then(|current| current.os.clone())),
+            kernel: self
+                .kernel
+                .clone()
+                .or_else(|| current.and_then(|current| current.kernel.clone())),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxDecommissionSpec {
+    /// The disks to be wiped, e.g. `nvme0n1`; all the disks but the boot disk are wiped if empty.
+    #[serde(default)]
+    pub disks: Vec<String>,
+    #[serde(default)]
+    pub wipe_method: BoxWipeMethod,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    Default,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum BoxWipeMethod {
+    /// Keep the data.
+    None,
+    /// Erase the partition tables and the filesystem signatures only.
+    Quick,
+    /// Overwrite the whole disks with zeros.
+    #[default]
+    Zero,
+    /// Overwrite the whole disks with random data, and then with zeros.
+    Random,
+    /// Use the firmware's secure erase, e.g. NVMe format with user data erase.
+    Secure,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxDecommissionStatus {
+    pub step: BoxDecommissionStep,
+    pub last_updated: DateTime<Utc>,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum BoxDecommissionStep {
+    Draining,
+    Wiping,
+    Removing,
+    PoweringOff,
+}
+
+/// The retry policy of the failed tasks.
+///
+/// The missing fields are inherited from the KISS configuration.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxRetryPolicySpec {
+    /// The maximum number of the attempts, including the first one.
+    #[serde(default)]
+    pub max_attempts: Option<u32>,
+    /// The delay before the first retry, which is doubled on every failure.
+    #[serde(default)]
+    pub backoff_seconds: Option<u64>,
+    #[serde(default)]
+    pub max_backoff_seconds: Option<u64>,
+    /// The maximum additional delay, relative to the backoff.
+    #[serde(default)]
+    pub jitter_percent: Option<u32>,
+}
+
+impl BoxRetryPolicySpec {
+    const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+    const DEFAULT_BACKOFF_SECONDS: u64 = 60; // 1 minute
+    const DEFAULT_MAX_BACKOFF_SECONDS: u64 = 60 * 60; // 1 hour
+    const DEFAULT_JITTER_PERCENT: u32 = 10;
+
+    /// Fill the missing fields with the fallback policy.
+    pub fn or(&self, fallback: &Self) -> Self {
+        Self {
+            max_attempts: self.max_attempts.or(fallback.max_attempts),
+            backoff_seconds: self.backoff_seconds.or(fallback.backoff_seconds),
+            max_backoff_seconds: self.max_backoff_seconds.or(fallback.max_backoff_seconds),
+            jitter_percent: self.jitter_percent.or(fallback.jitter_percent),
+        }
+    }
+
+    /// Return `true` if no more attempts are allowed after the given failed attempts.
+    pub fn is_exhausted(&self, attempts: u32) -> bool {
+        attempts >= self.max_attempts.unwrap_or(Self::DEFAULT_MAX_ATTEMPTS)
+    }
+
+    /// Return the delay before retrying the task after the given failed attempts.
+    pub fn backoff(&self, machine: &BoxMachineSpec, attempts: u32) -> Duration {
+        let backoff = self
+            .backoff_seconds
+            .unwrap_or(Self::DEFAULT_BACKOFF_SECONDS)
+            .saturating_mul(1 << attempts.saturating_sub(1).min(32))
+            .min(
+                self.max_backoff_seconds
+                    .unwrap_or(Self::DEFAULT_MAX_BACKOFF_SECONDS),
+            );
+
+        // NOTE: the jitter is derived from the machine so that the reconcilers agree on it
+        let jitter_percent = self
+            .jitter_percent
+            .unwrap_or(Self::DEFAULT_JITTER_PERCENT)
+            .min(100) as u64;
+        let seed = (machine.uuid.as_u128() as u64).wrapping_add(attempts as u64) % 1000;
+        let jitter = backoff.saturating_mul(jitter_percent) / 100 * seed / 1000;
+
+        let seconds = backoff.saturating_add(jitter);
+        Duration::try_seconds(seconds.try_into().unwrap_or(i64::MAX)).unwrap_or(Duration::MAX)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxRetryStatus {
+    /// The number of the failed attempts.
+    pub attempts: u32,
+    pub task: String,
+    /// The failure reason, which is extracted from the job logs.
+    #[serde(default)]
+    pub reason: Option<String>,
+    pub last_failed: DateTime<Utc>,
+    /// The time to retry the task, which is cleared when retried or exhausted.
+    #[serde(default)]
+    pub retry_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxRemediationStatus {
+    /// The time when the node is first observed as not ready.
+    #[serde(default)]
+    pub not_ready_since: Option<DateTime<Utc>>,
+    /// Whether the node has been not ready longer than the threshold.
+    #[serde(default)]
+    pub requested: bool,
+    /// The recent power-cycles, which are limited per day.
+    #[serde(default)]
+    pub reboots: Vec<DateTime<Utc>>,
+}
+
+impl BoxRemediationStatus {
+    /// Forget the power-cycles older than a day.
+    pub fn prune_reboots(&mut self, now: DateTime<Utc>) {
+        let since = now - Duration::try_days(1).unwrap();
+        self.reboots.retain(|&rebooted| rebooted > since);
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxInventorySpec {
+    #[serde(default)]
+    pub cpu: BoxInventoryCpuSpec,
+    // Total memory (MiB)
+    #[serde(default)]
+    pub memory_mb: Option<u64>,
+    #[serde(default)]
+    pub disks: Vec<BoxInventoryDiskSpec>,
+    #[serde(default)]
+    pub nics: Vec<BoxInventoryNicSpec>,
+    #[serde(default)]
+    pub gpus: Vec<BoxInventoryGpuSpec>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxInventoryCpuSpec {
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub sockets: Option<u32>,
+    #[serde(default)]
+    pub cores: Option<u32>,
+    #[serde(default)]
+    pub threads: Option<u32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxInventoryDiskSpec {
+    pub name: String,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub serial: Option<String>,
+    #[serde(default)]
+    pub size_bytes: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxInventoryNicSpec {
+    pub name: String,
+    #[serde(default)]
+    pub mac_address: Option<String>,
+    // Speed (Mb/s)
+    #[serde(default)]
+    pub speed_mbps: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxInventoryGpuSpec {
+    #[serde(default)]
+    pub vendor: Option<String>,
+    pub model: String,
+}
+
 #[derive(
     Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
 )]
@@ -334,15 +702,50 @@ impl BoxGroupRole {
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct BoxMachineSpec {
+    /// The CPU architecture, which is reported on discovery.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arch: Option<BoxArch>,
     pub uuid: Uuid,
 }
 
 impl BoxMachineSpec {
+    pub fn arch(&self) -> BoxArch {
+        self.arch.unwrap_or_default()
+    }
+
     pub fn hostname(&self) -> String {
         self.uuid.to_string()
     }
 }
 
+/// The CPU architecture of the box, named after the container image platforms.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    Default,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum BoxArch {
+    #[default]
+    #[serde(alias = "x86_64")]
+    #[strum(serialize = "x86_64", to_string = "amd64")]
+    Amd64,
+    #[serde(alias = "aarch64")]
+    #[strum(serialize = "aarch64", to_string = "arm64")]
+    Arm64,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct BoxPowerSpec {
@@ -391,19 +794,154 @@ pub mod request {
         }
     }
 
+    /// The hardware inventory, which is reported as strings by the commissioning playbook.
+    #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct BoxInventoryQuery {
+        #[serde(default)]
+        pub cpu: BoxInventoryCpuQuery,
+        #[serde(default)]
+        pub memory_mb: Option<String>,
+        #[serde(default)]
+        pub disks: Vec<BoxInventoryDiskQuery>,
+        #[serde(default)]
+        pub nics: Vec<BoxInventoryNicQuery>,
+        #[serde(default)]
+        pub gpus: Vec<BoxInventoryGpuQuery>,
+    }
+
+    impl TryFrom<BoxInventoryQuery> for BoxInventorySpec {
+        type Error = <u64 as ::core::str::FromStr>::Err;
+
+        fn try_from(value: BoxInventoryQuery) -> Result<Self, Self::Error> {
+            Ok(Self {
+                cpu: BoxInventoryCpuSpec {
+                    model: non_empty(value.cpu.model),
+                    sockets: parse(value.cpu.sockets)?,
+                    cores: parse(value.cpu.cores)?,
+                    threads: parse(value.cpu.threads)?,
+                },
+                memory_mb: parse(value.memory_mb)?,
+                disks: value
+                    .disks
+                    .into_iter()
+                    .map(|disk| {
+                        Ok(BoxInventoryDiskSpec {
+                            name: disk.name,
+                            model: non_empty(disk.model),
+                            serial: non_empty(disk.serial),
+                            size_bytes: parse(disk.size_bytes)?,
+                        })
+                    })
+                    .collect::<Result<_, Self::Error>>()?,
+                nics: value
+                    .nics
+                    .into_iter()
+                    .map(|nic| {
+                        Ok(BoxInventoryNicSpec {
+                            name: nic.name,
+                            mac_address: non_empty(nic.mac_address),
+                            speed_mbps: parse(nic.speed_mbps)?,
+                        })
+                    })
+                    .collect::<Result<_, Self::Error>>()?,
+                gpus: value
+                    .gpus
+                    .into_iter()
+                    .map(|gpu| BoxInventoryGpuSpec {
+                        vendor: non_empty(gpu.vendor),
+                        model: gpu.model,
+                    })
+                    .collect(),
+            })
+        }
+    }
+
+    #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct BoxInventoryCpuQuery {
+        #[serde(default)]
+        pub model: Option<String>,
+        #[serde(default)]
+        pub sockets: Option<String>,
+        #[serde(default)]
+        pub cores: Option<String>,
+        #[serde(default)]
+        pub threads: Option<String>,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct BoxInventoryDiskQuery {
+        pub name: String,
+        #[serde(default)]
+        pub model: Option<String>,
+        #[serde(default)]
+        pub serial: Option<String>,
+        #[serde(default)]
+        pub size_bytes: Option<String>,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct BoxInventoryNicQuery {
+        pub name: String,
+        #[serde(default)]
+        pub mac_address: Option<String>,
+        // Speed (Mb/s)
+        #[serde(default)]
+        pub speed_mbps: Option<String>,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct BoxInventoryGpuQuery {
+        #[serde(default)]
+        pub vendor: Option<String>,
+        pub model: String,
+    }
+
+    // NOTE: the playbook renders the missing facts as empty strings
+    fn non_empty(value: Option<String>) -> Option<String> {
+        value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    }
+
+    fn parse<T>(value: Option<String>) -> Result<Option<T>, <T as ::core::str::FromStr>::Err>
+    where
+        T: ::core::str::FromStr,
+    {
+        non_empty(value).map(|value| value.parse()).transpose()
+    }
+
     #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
     pub struct BoxNewQuery {
         #[serde(flatten)]
         pub access_primary: BoxAccessInterfaceQuery,
         #[serde(flatten)]
         pub machine: BoxMachineSpec,
+        /// The enrollment token, which is required on the first contact.
+        #[serde(default)]
+        pub token: Option<String>,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct BoxDecommissionQuery {
+        pub machine: BoxMachineSpec,
+        pub step: BoxDecommissionStep,
     }
 
     #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
     #[serde(rename_all = "camelCase")]
     pub struct BoxCommissionQuery {
         pub access: BoxAccessSpec<BoxAccessInterfaceQuery>,
+        #[serde(default)]
+        pub inventory: Option<BoxInventoryQuery>,
         pub machine: BoxMachineSpec,
+        #[serde(default)]
+        pub networks: Vec<BoxNetworkSpec>,
         pub power: Option<BoxPowerSpec>,
         pub reset: bool,
     }
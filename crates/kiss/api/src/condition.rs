@@ -0,0 +1,82 @@
+use chrono::Utc;
+pub use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
+
+/// The condition whether the sensor readings of the box are beyond the thresholds.
+pub const TYPE_DEGRADED: &str = "Degraded";
+/// The condition whether the box is a healthy voting member of the etcd cluster.
+pub const TYPE_ETCD_MEMBER: &str = "EtcdMember";
+/// The condition whether the task of the box is waiting for the other jobs.
+pub const TYPE_JOB_QUEUED: &str = "JobQueued";
+
+/// A fan of the box is not healthy.
+pub const REASON_DEGRADED_FAN: &str = "FanFailure";
+/// All the sensor readings of the box are within the thresholds.
+pub const REASON_DEGRADED_NONE: &str = "Healthy";
+/// The power draw of the box is beyond the threshold.
+pub const REASON_DEGRADED_POWER: &str = "PowerExceeded";
+/// A temperature of the box is beyond the threshold.
+pub const REASON_DEGRADED_TEMPERATURE: &str = "TemperatureExceeded";
+/// The box is a learner (non-voting) member of the etcd cluster.
+pub const REASON_ETCD_MEMBER_LEARNER: &str = "Learner";
+/// The box is not a member of the etcd cluster.
+pub const REASON_ETCD_MEMBER_NONE: &str = "NotMember";
+/// The box is a voting member of the etcd cluster.
+pub const REASON_ETCD_MEMBER_VOTER: &str = "Voter";
+
+/// Insert or update the condition of the given type.
+///
+/// The transition time is kept if the status is not changed.
+pub fn set_condition(
+    conditions: &mut Vec<Condition>,
+    type_: &str,
+    status: bool,
+    reason: &str,
+    message: impl ToString,
+    observed_generation: Option<i64>,
+) {
+    let condition = Condition {
+        last_transition_time: Time(Utc::now()),
+        message: message.to_string(),
+        observed_generation,
+        reason: reason.into(),
+        status: if status { "True" } else { "False" }.into(),
+        type_: type_.into(),
+    };
+
+    match conditions.iter_mut().find(|last| last.type_ == type_) {
+        Some(last) => {
+            let last_transition_time = if last.status == condition.status {
+                last.last_transition_time.clone()
+            } else {
+                condition.last_transition_time.clone()
+            };
+            *last = Condition {
+                last_transition_time,
+                ..condition
+            };
+        }
+        None => conditions.push(condition),
+    }
+}
+
+/// Return the updated conditions, starting from the last ones if any.
+pub fn with_condition(
+    last: Option<&[Condition]>,
+    type_: &str,
+    status: bool,
+    reason: &str,
+    message: impl ToString,
+    observed_generation: Option<i64>,
+) -> Vec<Condition> {
+    let mut conditions = last.map(|last| last.to_vec()).unwrap_or_default();
+    set_condition(
+        &mut conditions,
+        type_,
+        status,
+        reason,
+        message,
+        observed_generation,
+    );
+    conditions
+}
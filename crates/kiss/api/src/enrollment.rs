@@ -0,0 +1,164 @@
+use chrono::{DateTime, Utc};
+use kube::{CustomResource, ResourceExt};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use strum::{Display, EnumString};
+use uuid::Uuid;
+
+use crate::r#box::BoxGroupSpec;
+
+/// A token which allows the new boxes to be enrolled.
+///
+/// The token is minted by the operator into the secret `status.secretName`,
+/// and given to the boxes as a kernel argument: `kiss.enrollment_token=<token>`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema, CustomResource)]
+#[kube(
+    category = "kiss",
+    group = "kiss.ulagbulag.io",
+    version = "v1alpha1",
+    kind = "EnrollmentToken",
+    root = "EnrollmentTokenCrd",
+    status = "EnrollmentTokenStatus",
+    shortname = "enrollmenttoken",
+    printcolumn = r#"{
+        "name": "cluster",
+        "type": "string",
+        "description": "cluster name of the enrolled boxes",
+        "jsonPath": ".spec.group.clusterName"
+    }"#,
+    printcolumn = r#"{
+        "name": "role",
+        "type": "string",
+        "description": "role of the enrolled boxes",
+        "jsonPath": ".spec.group.role"
+    }"#,
+    printcolumn = r#"{
+        "name": "max-uses",
+        "type": "integer",
+        "description": "maximum number of the enrolled boxes",
+        "jsonPath": ".spec.maxUses"
+    }"#,
+    printcolumn = r#"{
+        "name": "expires-at",
+        "type": "date",
+        "description": "expiration time of the token",
+        "jsonPath": ".spec.expiresAt"
+    }"#,
+    printcolumn = r#"{
+        "name": "created-at",
+        "type": "date",
+        "description": "created time of the token",
+        "jsonPath": ".metadata.creationTimestamp"
+    }"#,
+    printcolumn = r#"{
+        "name": "version",
+        "type": "integer",
+        "description": "token version",
+        "jsonPath": ".metadata.generation"
+    }"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct EnrollmentTokenSpec {
+    /// The group which the enrolled boxes are bound to.
+    #[serde(default)]
+    pub group: BoxGroupSpec,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// The maximum number of the boxes which can be enrolled with this token.
+    #[serde(default)]
+    pub max_uses: Option<u32>,
+}
+
+impl EnrollmentTokenCrd {
+    /// The key of the secret which stores the token.
+    pub const SECRET_KEY: &'static str = "token";
+
+    pub fn secret_name(&self) -> String {
+        format!("enrollment-token-{}", self.name_any())
+    }
+
+    /// Split the token into the name of the resource and the secret.
+    pub fn parse_token(token: &str) -> Option<(&str, &str)> {
+        token
+            .split_once('.')
+            .filter(|(name, secret)| !name.is_empty() && !secret.is_empty())
+    }
+
+    pub fn hash_secret(secret: &str) -> String {
+        Sha256::digest(secret.as_bytes())
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    /// Verify the secret, returning the reason if the box cannot be enrolled.
+    pub fn verify(&self, secret: &str, uuid: Uuid, now: DateTime<Utc>) -> Result<(), String> {
+        let status = self
+            .status
+            .as_ref()
+            .ok_or_else(|| "token is not minted yet".to_string())?;
+
+        if self.metadata.deletion_timestamp.is_some() {
+            return Err("token is being deleted".into());
+        }
+        if status.secret_hash.as_deref() != Some(Self::hash_secret(secret).as_str()) {
+            return Err("token mismatch".into());
+        }
+        if let Some(expires_at) = self.spec.expires_at {
+            if now >= expires_at {
+                return Err(format!("token has been expired at {expires_at}"));
+            }
+        }
+        if let Some(max_uses) = self.spec.max_uses {
+            if !status.boxes.contains(&uuid) && status.boxes.len() >= max_uses as usize {
+                return Err(format!("token has been used up: {max_uses}"));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EnrollmentTokenStatus {
+    /// The secret which stores the token.
+    #[serde(default)]
+    pub secret_name: Option<String>,
+    /// The SHA-256 hash of the secret part of the token.
+    #[serde(default)]
+    pub secret_hash: Option<String>,
+    /// The boxes which are enrolled with this token.
+    #[serde(default)]
+    pub boxes: Vec<Uuid>,
+    pub last_updated: DateTime<Utc>,
+}
+
+/// How to handle the new boxes which have no valid enrollment tokens.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    Display,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[strum(serialize_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub enum EnrollmentPolicy {
+    /// Enroll all the boxes, even without tokens.
+    #[default]
+    Disabled,
+    /// Register the boxes as quarantined, which should be approved manually.
+    Quarantine,
+    /// Refuse to register the boxes.
+    Reject,
+}
@@ -0,0 +1,117 @@
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::r#box::{BoxArch, BoxCrd, BoxGroupSpec, BoxRetryPolicySpec, BoxVersionSpec};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema, CustomResource)]
+#[kube(
+    category = "kiss",
+    group = "kiss.ulagbulag.io",
+    version = "v1alpha1",
+    kind = "BoxGroup",
+    root = "BoxGroupCrd",
+    shortname = "boxgroup",
+    printcolumn = r#"{
+        "name": "cluster",
+        "type": "string",
+        "description": "cluster name of the box group",
+        "jsonPath": ".spec.group.clusterName"
+    }"#,
+    printcolumn = r#"{
+        "name": "role",
+        "type": "string",
+        "description": "role of the box group",
+        "jsonPath": ".spec.group.role"
+    }"#,
+    printcolumn = r#"{
+        "name": "os",
+        "type": "string",
+        "description": "desired OS image version of the box group",
+        "jsonPath": ".spec.upgrade.version.os"
+    }"#,
+    printcolumn = r#"{
+        "name": "kernel",
+        "type": "string",
+        "description": "desired kernel version of the box group",
+        "jsonPath": ".spec.upgrade.version.kernel"
+    }"#,
+    printcolumn = r#"{
+        "name": "max-unavailable",
+        "type": "integer",
+        "description": "maximum number of the boxes being upgraded at once",
+        "jsonPath": ".spec.upgrade.maxUnavailable"
+    }"#,
+    printcolumn = r#"{
+        "name": "created-at",
+        "type": "date",
+        "description": "created time of the box group",
+        "jsonPath": ".metadata.creationTimestamp"
+    }"#,
+    printcolumn = r#"{
+        "name": "version",
+        "type": "integer",
+        "description": "box group version",
+        "jsonPath": ".metadata.generation"
+    }"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxGroupConfigSpec {
+    /// The CPU architectures of the boxes which are allowed to join; all if empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub architectures: Vec<BoxArch>,
+    /// The boxes which are bound to the cluster with the role.
+    pub group: BoxGroupSpec,
+    /// The name of the network policy profile, which is applied to the boxes.
+    #[serde(default)]
+    pub network_policy: Option<String>,
+    /// The retry policy of the failed tasks, overriding the KISS configuration.
+    #[serde(default)]
+    pub retry: Option<BoxRetryPolicySpec>,
+    #[serde(default)]
+    pub upgrade: Option<BoxGroupUpgradeSpec>,
+}
+
+impl BoxGroupCrd {
+    pub fn contains(&self, r#box: &BoxCrd) -> bool {
+        self.spec.group == r#box.spec.group
+    }
+
+    /// Return `true` if the box can join the group with its CPU architecture.
+    pub fn is_arch_allowed(&self, r#box: &BoxCrd) -> bool {
+        self.spec.architectures.is_empty()
+            || self.spec.architectures.contains(&r#box.spec.machine.arch())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxGroupUpgradeSpec {
+    pub version: BoxVersionSpec,
+    /// The maximum number of the boxes which are being upgraded (or failed to) at once.
+    #[serde(default = "BoxGroupUpgradeSpec::default_max_unavailable")]
+    pub max_unavailable: u32,
+    #[serde(default)]
+    pub paused: bool,
+}
+
+impl BoxGroupUpgradeSpec {
+    const fn default_max_unavailable() -> u32 {
+        1
+    }
+
+    /// Return `true` if the box is not running on the desired version.
+    pub fn is_outdated(&self, current: Option<&BoxVersionSpec>) -> bool {
+        fn is_outdated(desired: Option<&String>, current: Option<&String>) -> bool {
+            desired.is_some() && desired != current
+        }
+
+        is_outdated(
+            self.version.os.as_ref(),
+            current.and_then(|current| current.os.as_ref()),
+        ) || is_outdated(
+            self.version.kernel.as_ref(),
+            current.and_then(|current| current.kernel.as_ref()),
+        )
+    }
+}
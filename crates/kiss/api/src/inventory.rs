@@ -0,0 +1,185 @@
+use std::{collections::BTreeMap, net::IpAddr};
+
+use kube::ResourceExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+use crate::{
+    group::{BoxGroupConfigSpec, BoxGroupCrd},
+    r#box::{
+        BoxArch, BoxCrd, BoxGroupRole, BoxInventorySpec, BoxNetworkSpec, BoxPowerType, BoxState,
+        BoxVersionSpec,
+    },
+    rack::RackRef,
+};
+
+/// The inventory of all the clusters, which is exported to the Git repository.
+///
+/// NOTE: the volatile fields (e.g. the timestamps and the conditions) are excluded,
+///       so that the inventory is changed only if the boxes are changed meaningfully.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Inventory {
+    pub clusters: BTreeMap<String, InventoryCluster>,
+}
+
+impl Inventory {
+    /// The config map which serves the rendered inventory.
+    pub const CONFIG_MAP_NAME: &'static str = "kiss-inventory";
+    /// The file name of the inventory.
+    pub const FILE_NAME_INVENTORY: &'static str = "inventory.yaml";
+    /// The file name of the Ansible hosts.
+    pub const FILE_NAME_HOSTS: &'static str = "hosts.yaml";
+
+    pub fn new(boxes: &[BoxCrd], groups: &[BoxGroupCrd]) -> Self {
+        let mut clusters: BTreeMap<_, InventoryCluster> = BTreeMap::default();
+        for group in groups {
+            let cluster = clusters
+                .entry(group.spec.group.cluster_name.clone())
+                .or_default();
+            cluster.domain = group.spec.group.cluster_domain();
+            cluster.groups.insert(group.name_any(), group.spec.clone());
+        }
+        for r#box in boxes {
+            let cluster = clusters
+                .entry(r#box.spec.group.cluster_name.clone())
+                .or_default();
+            cluster.domain = r#box.spec.group.cluster_domain();
+            cluster
+                .boxes
+                .insert(r#box.name_any(), InventoryBox::new(r#box));
+        }
+        Self { clusters }
+    }
+
+    /// Render the inventory as an Ansible hosts file.
+    ///
+    /// Each cluster is rendered as a group, and each role of the cluster as its child group,
+    /// e.g. `default` and `default_control_plane`.
+    pub fn to_hosts(&self) -> Value {
+        let mut clusters = Map::default();
+        for (cluster_name, cluster) in &self.clusters {
+            let cluster_group = to_ansible_group_name(cluster_name);
+
+            let mut roles: BTreeMap<_, Map<_, _>> = BTreeMap::default();
+            for (name, r#box) in &cluster.boxes {
+                let mut vars = Map::default();
+                vars.insert("ansible_host".into(), r#box.uuid.to_string().into());
+                vars.insert("ansible_host_id".into(), name.clone().into());
+                vars.insert("ansible_host_uuid".into(), r#box.uuid.to_string().into());
+                if let Some(address) = r#box.address {
+                    vars.insert("ansible_ssh_host".into(), address.to_string().into());
+                }
+                vars.insert("kiss_box_arch".into(), r#box.arch.to_string().into());
+                vars.insert("kiss_box_state".into(), r#box.state.to_string().into());
+
+                let role = to_ansible_group_name(&r#box.role.to_string());
+                roles
+                    .entry(format!("{cluster_group}_{role}"))
+                    .or_default()
+                    .insert(r#box.uuid.to_string(), Value::Object(vars));
+            }
+
+            let children: Map<_, _> = roles
+                .into_iter()
+                .map(|(role, hosts)| (role, json!({ "hosts": hosts })))
+                .collect();
+            clusters.insert(
+                cluster_group,
+                json!({
+                    "children": children,
+                    "vars": {
+                        "kiss_cluster_domain": &cluster.domain,
+                        "kiss_cluster_name": cluster_name,
+                    },
+                }),
+            );
+        }
+
+        json!({
+            "all": {
+                "children": clusters,
+            },
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryCluster {
+    pub domain: String,
+    /// The box groups of the cluster.
+    #[serde(default)]
+    pub groups: BTreeMap<String, BoxGroupConfigSpec>,
+    /// The boxes of the cluster, including the ones which are not joined yet.
+    #[serde(default)]
+    pub boxes: BTreeMap<String, InventoryBox>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryBox {
+    pub uuid: Uuid,
+    #[serde(default)]
+    pub arch: BoxArch,
+    pub role: BoxGroupRole,
+    pub state: BoxState,
+    /// Whether the box is joined to the cluster.
+    pub joined: bool,
+    /// The management address of the box.
+    #[serde(default)]
+    pub address: Option<IpAddr>,
+    #[serde(default)]
+    pub inventory: Option<BoxInventorySpec>,
+    #[serde(default)]
+    pub networks: Vec<BoxNetworkSpec>,
+    #[serde(default)]
+    pub power: Option<BoxPowerType>,
+    #[serde(default)]
+    pub rack: Option<RackRef>,
+    #[serde(default)]
+    pub version: Option<BoxVersionSpec>,
+}
+
+impl InventoryBox {
+    fn new(r#box: &BoxCrd) -> Self {
+        let status = r#box.status.as_ref();
+        Self {
+            uuid: r#box.spec.machine.uuid,
+            arch: r#box.spec.machine.arch(),
+            role: r#box.spec.group.role,
+            state: status.map(|status| status.state).unwrap_or_default(),
+            joined: status.and_then(|status| status.bind_group.as_ref()) == Some(&r#box.spec.group),
+            address: status
+                .and_then(|status| status.access.management())
+                .map(|interface| interface.address),
+            inventory: status.and_then(|status| status.inventory.clone()),
+            networks: status
+                .map(|status| status.networks.clone())
+                .unwrap_or_default(),
+            power: r#box.spec.power.as_ref().map(|power| power.r#type),
+            rack: r#box.spec.rack.clone(),
+            version: status.and_then(|status| status.version.clone()),
+        }
+    }
+}
+
+/// Convert the name into a valid Ansible group name, e.g. `ControlPlane` into `control_plane`.
+fn to_ansible_group_name(name: &str) -> String {
+    let mut group = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            if !group.is_empty() {
+                group.push('_');
+            }
+            group.push(c.to_ascii_lowercase());
+        } else if c.is_ascii_alphanumeric() {
+            group.push(c);
+        } else {
+            group.push('_');
+        }
+    }
+    group
+}
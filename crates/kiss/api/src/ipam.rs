@@ -0,0 +1,181 @@
+use std::{collections::BTreeSet, net::Ipv4Addr};
+
+use chrono::{DateTime, Utc};
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+use uuid::Uuid;
+
+/// An IPv4 address of a KISS network, which is leased by a box or reserved statically.
+///
+/// The name should be `<network>-<a>-<b>-<c>-<d>`, so that each address is tracked only once.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema, CustomResource)]
+#[kube(
+    category = "kiss",
+    group = "kiss.ulagbulag.io",
+    version = "v1alpha1",
+    kind = "IpAddress",
+    root = "IpAddressCrd",
+    status = "IpAddressStatus",
+    shortname = "ipaddr",
+    printcolumn = r#"{
+        "name": "network",
+        "type": "string",
+        "description": "network name of the address",
+        "jsonPath": ".spec.network"
+    }"#,
+    printcolumn = r#"{
+        "name": "address",
+        "type": "string",
+        "description": "IPv4 address",
+        "jsonPath": ".spec.address"
+    }"#,
+    printcolumn = r#"{
+        "name": "reserved",
+        "type": "string",
+        "description": "box which the address is reserved for",
+        "jsonPath": ".spec.reservation"
+    }"#,
+    printcolumn = r#"{
+        "name": "leased",
+        "type": "string",
+        "description": "box which holds the address",
+        "jsonPath": ".status.lease"
+    }"#,
+    printcolumn = r#"{
+        "name": "state",
+        "type": "string",
+        "description": "state of the address",
+        "jsonPath": ".status.state"
+    }"#,
+    printcolumn = r#"{
+        "name": "updated-at",
+        "type": "date",
+        "description": "updated time of the address",
+        "jsonPath": ".status.lastUpdated"
+    }"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct IpAddressSpec {
+    /// The name of the network, which is defined in the KISS configuration.
+    pub network: String,
+    pub address: Ipv4Addr,
+    /// The box which the address is reserved for statically.
+    #[serde(default)]
+    pub reservation: Option<Uuid>,
+}
+
+impl IpAddressCrd {
+    pub fn name_of(network: &str, address: Ipv4Addr) -> String {
+        let [a, b, c, d] = address.octets();
+        format!("{network}-{a}-{b}-{c}-{d}")
+    }
+
+    pub fn lease(&self) -> Option<Uuid> {
+        self.status.as_ref().and_then(|status| status.lease)
+    }
+
+    /// Infer the state from the reservation and the lease.
+    pub fn state(&self, lease: Option<Uuid>) -> IpAddressState {
+        match (self.spec.reservation, lease) {
+            (None, None) => IpAddressState::Released,
+            (None, Some(_)) => IpAddressState::Leased,
+            (Some(_), None) => IpAddressState::Reserved,
+            (Some(reservation), Some(lease)) if reservation == lease => IpAddressState::Bound,
+            (Some(_), Some(_)) => IpAddressState::Conflicted,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IpAddressStatus {
+    #[serde(default)]
+    pub state: IpAddressState,
+    /// The box which holds the address, as reported on commissioning.
+    #[serde(default)]
+    pub lease: Option<Uuid>,
+    pub last_updated: DateTime<Utc>,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    Display,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum IpAddressState {
+    /// Neither leased nor reserved.
+    #[default]
+    Released,
+    /// Leased dynamically by a box.
+    Leased,
+    /// Reserved for a box, which does not hold the address yet.
+    Reserved,
+    /// Reserved for a box, which holds the address.
+    Bound,
+    /// Reserved for a box, but leased by another box.
+    Conflicted,
+}
+
+/// Allocate an address of the network to the box.
+///
+/// The reservation is preferred, then the current lease,
+/// and then the first address in the range which is neither leased nor reserved.
+pub fn allocate(
+    addresses: &[IpAddressCrd],
+    network: &str,
+    range_begin: Ipv4Addr,
+    range_end: Ipv4Addr,
+    r#box: Uuid,
+) -> Option<Ipv4Addr> {
+    let addresses: Vec<_> = addresses
+        .iter()
+        .filter(|address| address.spec.network == network)
+        .collect();
+
+    addresses
+        .iter()
+        .find(|address| address.spec.reservation == Some(r#box))
+        .or_else(|| {
+            addresses.iter().find(|address| {
+                address.spec.reservation.is_none() && address.lease() == Some(r#box)
+            })
+        })
+        .map(|address| address.spec.address)
+        .or_else(|| {
+            let used: BTreeSet<_> = addresses
+                .iter()
+                .filter(|address| address.spec.reservation.is_some() || address.lease().is_some())
+                .map(|address| u32::from(address.spec.address))
+                .collect();
+
+            (u32::from(range_begin)..=u32::from(range_end))
+                .find(|address| !used.contains(address))
+                .map(Into::into)
+        })
+}
+
+pub mod request {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct IpAddressAllocateQuery {
+        /// The name of the network, which defaults to the provision network.
+        #[serde(default)]
+        pub network: Option<String>,
+        pub uuid: Uuid,
+    }
+}
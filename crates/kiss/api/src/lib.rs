@@ -1,6 +1,16 @@
+pub mod backup;
+pub mod boot;
 pub mod r#box;
+pub mod condition;
+pub mod enrollment;
+pub mod group;
+pub mod inventory;
+pub mod ipam;
 pub mod netbox;
+pub mod network_policy;
+pub mod progress;
 pub mod rack;
+pub mod sensor;
 
 pub mod consts {
     pub const NAMESPACE: &str = "kiss";
@@ -0,0 +1,363 @@
+use std::{collections::BTreeMap, fmt::Write, net::IpAddr};
+
+use chrono::{DateTime, Utc};
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use strum::{Display, EnumString};
+
+/// The host firewall rules and kernel parameters, which are applied to the boxes of the box groups.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema, CustomResource)]
+#[kube(
+    category = "kiss",
+    group = "kiss.ulagbulag.io",
+    version = "v1alpha1",
+    kind = "NetworkPolicyProfile",
+    root = "NetworkPolicyProfileCrd",
+    status = "NetworkPolicyProfileStatus",
+    shortname = "netpolicy",
+    printcolumn = r#"{
+        "name": "default-policy",
+        "type": "string",
+        "description": "default policy of the incoming packets",
+        "jsonPath": ".spec.firewall.defaultPolicy"
+    }"#,
+    printcolumn = r#"{
+        "name": "compliant",
+        "type": "integer",
+        "description": "number of the compliant boxes",
+        "jsonPath": ".status.compliant"
+    }"#,
+    printcolumn = r#"{
+        "name": "non-compliant",
+        "type": "integer",
+        "description": "number of the non-compliant boxes",
+        "jsonPath": ".status.nonCompliant"
+    }"#,
+    printcolumn = r#"{
+        "name": "created-at",
+        "type": "date",
+        "description": "created time of the network policy profile",
+        "jsonPath": ".metadata.creationTimestamp"
+    }"#,
+    printcolumn = r#"{
+        "name": "version",
+        "type": "integer",
+        "description": "network policy profile version",
+        "jsonPath": ".metadata.generation"
+    }"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkPolicyProfileSpec {
+    #[serde(default)]
+    pub firewall: NetworkPolicyFirewallSpec,
+    /// The kernel parameters, e.g. `net.ipv4.ip_forward: "0"`.
+    #[serde(default)]
+    pub sysctl: BTreeMap<String, String>,
+    /// Re-apply the profile when the maintenance task detects the drifts.
+    #[serde(default)]
+    pub enforce: bool,
+}
+
+impl NetworkPolicyProfileSpec {
+    /// The nftables table, which is owned by the profile.
+    pub const NFTABLES_TABLE: &'static str = "kiss_network_policy";
+
+    /// Render the firewall rules as an nftables script, which replaces the table atomically.
+    pub fn to_nftables(&self) -> Result<String, String> {
+        let table = Self::NFTABLES_TABLE;
+        let mut script = String::new();
+
+        // NOTE: declaring the table first makes the deletion idempotent
+        writeln!(script, "table inet {table}").unwrap();
+        writeln!(script, "delete table inet {table}").unwrap();
+        writeln!(script, "table inet {table} {{").unwrap();
+        writeln!(script, "  chain input {{").unwrap();
+        writeln!(
+            script,
+            "    type filter hook input priority filter; policy {};",
+            match self.firewall.default_policy {
+                NetworkPolicyAction::Accept => "accept",
+                NetworkPolicyAction::Drop | NetworkPolicyAction::Reject => "drop",
+            },
+        )
+        .unwrap();
+        // NOTE: the established connections and the loopback are never blocked
+        writeln!(script, "    ct state established,related accept").unwrap();
+        writeln!(script, "    iif lo accept").unwrap();
+        for rule in &self.firewall.rules {
+            for line in rule.to_nftables()? {
+                writeln!(script, "    {line}").unwrap();
+            }
+        }
+        if matches!(self.firewall.default_policy, NetworkPolicyAction::Reject) {
+            writeln!(script, "    reject").unwrap();
+        }
+        writeln!(script, "  }}").unwrap();
+        writeln!(script, "}}").unwrap();
+        Ok(script)
+    }
+
+    /// Render the kernel parameters as a sysctl configuration file.
+    pub fn to_sysctl(&self) -> Result<String, String> {
+        let mut conf = String::new();
+        for (key, value) in &self.sysctl {
+            if key.is_empty()
+                || !key
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '/'))
+            {
+                return Err(format!("invalid sysctl key: {key:?}"));
+            }
+            if value.contains(['\n', '\r']) {
+                return Err(format!("invalid sysctl value: {key}={value:?}"));
+            }
+            writeln!(conf, "{key} = {value}").unwrap();
+        }
+        Ok(conf)
+    }
+
+    /// Return the digest of the rendered profile, which is compared to detect the outdated boxes.
+    pub fn digest(&self) -> Result<String, String> {
+        let mut hasher = Sha256::new();
+        hasher.update(self.to_nftables()?.as_bytes());
+        hasher.update(self.to_sysctl()?.as_bytes());
+        Ok(hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect())
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkPolicyFirewallSpec {
+    /// The policy of the incoming packets which are not matched by any rules.
+    #[serde(default)]
+    pub default_policy: NetworkPolicyAction,
+    /// The rules of the incoming packets, which are matched in order.
+    #[serde(default)]
+    pub rules: Vec<NetworkPolicyFirewallRule>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkPolicyFirewallRule {
+    pub action: NetworkPolicyAction,
+    /// The name of the incoming interface; all if missing.
+    #[serde(default)]
+    pub interface: Option<String>,
+    #[serde(default)]
+    pub protocol: NetworkPolicyProtocol,
+    /// The destination ports, which are ignored on ICMP; all if empty.
+    #[serde(default)]
+    pub ports: Vec<u16>,
+    /// The source addresses or CIDRs, e.g. `10.0.0.0/8`; all if empty.
+    #[serde(default)]
+    pub sources: Vec<String>,
+}
+
+impl NetworkPolicyFirewallRule {
+    fn to_nftables(&self) -> Result<Vec<String>, String> {
+        let mut matches = vec![];
+
+        if let Some(interface) = &self.interface {
+            if interface.is_empty()
+                || !interface
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+            {
+                return Err(format!("invalid interface name: {interface:?}"));
+            }
+            matches.push(format!("iifname \"{interface}\""));
+        }
+
+        let ports = self
+            .ports
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        match (self.protocol, self.ports.is_empty()) {
+            (NetworkPolicyProtocol::Any, true) => (),
+            (NetworkPolicyProtocol::Any, false) => matches.push(format!(
+                "meta l4proto {{ tcp, udp }} th dport {{ {ports} }}"
+            )),
+            (NetworkPolicyProtocol::Tcp, true) => matches.push("meta l4proto tcp".into()),
+            (NetworkPolicyProtocol::Tcp, false) => matches.push(format!("tcp dport {{ {ports} }}")),
+            (NetworkPolicyProtocol::Udp, true) => matches.push("meta l4proto udp".into()),
+            (NetworkPolicyProtocol::Udp, false) => matches.push(format!("udp dport {{ {ports} }}")),
+            (NetworkPolicyProtocol::Icmp, _) => {
+                matches.push("meta l4proto { icmp, ipv6-icmp }".into())
+            }
+        }
+
+        let action = match self.action {
+            NetworkPolicyAction::Accept => "accept",
+            NetworkPolicyAction::Drop => "drop",
+            NetworkPolicyAction::Reject => "reject",
+        };
+
+        // NOTE: the IPv4 and IPv6 sources cannot be matched by a single rule
+        let mut sources_ipv4 = vec![];
+        let mut sources_ipv6 = vec![];
+        for source in &self.sources {
+            match parse_cidr(source)? {
+                IpAddr::V4(_) => sources_ipv4.push(source.as_str()),
+                IpAddr::V6(_) => sources_ipv6.push(source.as_str()),
+            }
+        }
+        let rule = |source: Option<String>| {
+            matches
+                .iter()
+                .cloned()
+                .chain(source)
+                .chain(Some(action.into()))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        if self.sources.is_empty() {
+            return Ok(vec![rule(None)]);
+        }
+        Ok([("ip", sources_ipv4), ("ip6", sources_ipv6)]
+            .into_iter()
+            .filter(|(_, sources)| !sources.is_empty())
+            .map(|(family, sources)| {
+                rule(Some(
+                    format!("{family} saddr {{ {} }}", sources.join(", "),),
+                ))
+            })
+            .collect())
+    }
+}
+
+fn parse_cidr(source: &str) -> Result<IpAddr, String> {
+    let (address, prefix) = match source.split_once('/') {
+        Some((address, prefix)) => (address, Some(prefix)),
+        None => (source, None),
+    };
+
+    let address: IpAddr = address
+        .parse()
+        .map_err(|_| format!("invalid source address: {source:?}"))?;
+    let max_prefix = if address.is_ipv4() { 32 } else { 128 };
+    match prefix.map(|prefix| prefix.parse::<u8>()) {
+        None => Ok(address),
+        Some(Ok(prefix)) if prefix <= max_prefix => Ok(address),
+        Some(_) => Err(format!("invalid source prefix: {source:?}")),
+    }
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    Default,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum NetworkPolicyAction {
+    #[default]
+    Accept,
+    Drop,
+    Reject,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    Default,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum NetworkPolicyProtocol {
+    #[default]
+    Any,
+    Tcp,
+    Udp,
+    Icmp,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkPolicyProfileStatus {
+    #[serde(default)]
+    pub compliant: u32,
+    #[serde(default)]
+    pub non_compliant: u32,
+    /// The boxes which are drifted from the profile or not checked yet, by the reasons.
+    #[serde(default)]
+    pub non_compliant_boxes: BTreeMap<String, String>,
+    /// The error message, if the profile cannot be rendered.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub last_updated: DateTime<Utc>,
+}
+
+/// The compliance of the box to the network policy profile, which is reported by the playbook.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxNetworkPolicyStatus {
+    pub profile: String,
+    /// The digest of the profile which has been checked.
+    pub digest: String,
+    /// The settings which are not matched with the profile.
+    #[serde(default)]
+    pub drifts: Vec<String>,
+    /// Whether the drifts are corrected by re-applying the profile.
+    #[serde(default)]
+    pub enforced: bool,
+    pub last_checked: DateTime<Utc>,
+}
+
+impl BoxNetworkPolicyStatus {
+    /// Return the reason if the box is not compliant to the given profile.
+    pub fn verify(&self, profile: &str, digest: &str) -> Result<(), String> {
+        if self.profile != profile || self.digest != digest {
+            Err("outdated profile".into())
+        } else if !self.drifts.is_empty() && !self.enforced {
+            Err(format!("drifted: {}", self.drifts.join(", ")))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+pub mod request {
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+
+    use crate::r#box::BoxMachineSpec;
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct BoxNetworkPolicyQuery {
+        pub machine: BoxMachineSpec,
+        pub profile: String,
+        pub digest: String,
+        #[serde(default)]
+        pub drifts: Vec<String>,
+        #[serde(default)]
+        pub enforced: bool,
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+use kube::ResourceExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::r#box::{BoxCrd, BoxGroupSpec, BoxState};
+
+/// The provisioning progress of all the boxes, which is served as a dashboard payload.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvisioningProgress {
+    pub total: usize,
+    /// The number of the boxes in each state.
+    pub states: BTreeMap<BoxState, usize>,
+    /// The boxes which are not running and have not been changed beyond the threshold.
+    pub stuck: Vec<BoxProgress>,
+    /// The boxes which are changed lately, in descending order.
+    pub last_transitions: Vec<BoxProgress>,
+    /// The Ansible jobs which are not finished yet.
+    pub jobs: Vec<JobProgress>,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl ProvisioningProgress {
+    pub fn new(
+        boxes: &[BoxCrd],
+        jobs: Vec<JobProgress>,
+        query: &request::ProvisioningProgressQuery,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let stuck_threshold = query.stuck_threshold();
+        let limit = query.limit();
+
+        let boxes: Vec<_> = boxes.iter().map(BoxProgress::new).collect();
+
+        let mut states = BTreeMap::default();
+        for r#box in &boxes {
+            *states.entry(r#box.state).or_default() += 1;
+        }
+
+        let mut stuck: Vec<_> = boxes
+            .iter()
+            .filter(|r#box| !matches!(r#box.state, BoxState::Running))
+            .filter(|r#box| {
+                r#box
+                    .last_updated
+                    .is_some_and(|last_updated| now > last_updated + stuck_threshold)
+            })
+            .cloned()
+            .collect();
+        stuck.sort_by_key(|r#box| r#box.last_updated);
+
+        let mut last_transitions = boxes;
+        last_transitions.sort_by(|a, b| b.last_updated.cmp(&a.last_updated));
+        last_transitions.truncate(limit);
+
+        Self {
+            total: states.values().sum(),
+            states,
+            stuck,
+            last_transitions,
+            jobs,
+            generated_at: now,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxProgress {
+    pub name: String,
+    pub group: BoxGroupSpec,
+    pub state: BoxState,
+    #[serde(default)]
+    pub last_updated: Option<DateTime<Utc>>,
+    /// The number of the failed attempts of the current task.
+    #[serde(default)]
+    pub attempts: u32,
+}
+
+impl BoxProgress {
+    fn new(r#box: &BoxCrd) -> Self {
+        let status = r#box.status.as_ref();
+        Self {
+            name: r#box.name_any(),
+            group: r#box.spec.group.clone(),
+            state: status.map(|status| status.state).unwrap_or_default(),
+            last_updated: r#box.last_updated().copied(),
+            attempts: status
+                .and_then(|status| status.retry.as_ref())
+                .map(|retry| retry.attempts)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct JobProgress {
+    pub name: String,
+    #[serde(default)]
+    pub box_name: Option<String>,
+    #[serde(default)]
+    pub task: Option<String>,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    /// The number of the running pods.
+    #[serde(default)]
+    pub active: i32,
+    /// The number of the failed pods.
+    #[serde(default)]
+    pub failed: i32,
+}
+
+pub mod request {
+    use super::*;
+
+    #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ProvisioningProgressQuery {
+        /// The boxes which have not been changed for this duration are regarded as stuck.
+        #[serde(default)]
+        pub stuck_seconds: Option<u32>,
+        /// The maximum number of the last transitions.
+        #[serde(default)]
+        pub limit: Option<usize>,
+    }
+
+    impl ProvisioningProgressQuery {
+        const DEFAULT_STUCK_SECONDS: u32 = 30 * 60; // 30 minutes
+        const DEFAULT_LIMIT: usize = 20;
+
+        pub fn stuck_threshold(&self) -> Duration {
+            Duration::seconds(
+                self.stuck_seconds
+                    .unwrap_or(Self::DEFAULT_STUCK_SECONDS)
+                    .into(),
+            )
+        }
+
+        pub fn limit(&self) -> usize {
+            self.limit.unwrap_or(Self::DEFAULT_LIMIT)
+        }
+    }
+}
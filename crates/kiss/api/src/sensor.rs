@@ -0,0 +1,90 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::condition::{REASON_DEGRADED_FAN, REASON_DEGRADED_POWER, REASON_DEGRADED_TEMPERATURE};
+
+/// The sensor readings of the box, which are polled from its BMC.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxSensorReading {
+    /// The name of the box.
+    pub r#box: String,
+    #[serde(default)]
+    pub fans: Vec<BoxFanReading>,
+    /// The total power draw (W).
+    #[serde(default)]
+    pub power_watts: Option<f64>,
+    #[serde(default)]
+    pub temperatures: Vec<BoxTemperatureReading>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl BoxSensorReading {
+    /// Return the reason and the message of the first reading beyond the thresholds, if any.
+    pub fn check(&self, thresholds: &BoxSensorThresholds) -> Option<(&'static str, String)> {
+        for sensor in &self.temperatures {
+            // NOTE: the BMC's own threshold is used when not configured
+            let limit = thresholds
+                .max_temperature_celsius
+                .or(sensor.critical_celsius);
+            if let Some(limit) = limit.filter(|&limit| sensor.celsius >= limit) {
+                return Some((
+                    REASON_DEGRADED_TEMPERATURE,
+                    format!(
+                        "{name} is {value:.1}°C (limit: {limit:.1}°C)",
+                        name = &sensor.name,
+                        value = sensor.celsius,
+                    ),
+                ));
+            }
+        }
+
+        if let Some((value, limit)) = self
+            .power_watts
+            .zip(thresholds.max_power_watts)
+            .filter(|(value, limit)| value >= limit)
+        {
+            return Some((
+                REASON_DEGRADED_POWER,
+                format!("power draw is {value:.1}W (limit: {limit:.1}W)"),
+            ));
+        }
+
+        self.fans.iter().find(|fan| !fan.healthy).map(|fan| {
+            (
+                REASON_DEGRADED_FAN,
+                format!("{name} is not healthy", name = &fan.name),
+            )
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxFanReading {
+    pub name: String,
+    #[serde(default)]
+    pub rpm: Option<f64>,
+    pub healthy: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxTemperatureReading {
+    pub name: String,
+    pub celsius: f64,
+    /// The critical threshold, which is reported by the BMC.
+    #[serde(default)]
+    pub critical_celsius: Option<f64>,
+}
+
+/// The thresholds of the sensor readings, beyond which the box is marked as degraded.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxSensorThresholds {
+    #[serde(default)]
+    pub max_power_watts: Option<f64>,
+    #[serde(default)]
+    pub max_temperature_celsius: Option<f64>,
+}
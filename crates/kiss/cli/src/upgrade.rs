@@ -1,7 +1,9 @@
 use anyhow::{bail, Result};
 use clap::{Parser, ValueEnum};
 use futures::{stream::FuturesUnordered, TryStreamExt};
-use kiss_ansible::{cluster::ClusterState, AnsibleClient, AnsibleJob, AnsibleResourceType};
+use kiss_ansible::{
+    cluster::ClusterState, AnsibleClient, AnsibleJob, AnsibleJobStatus, AnsibleResourceType,
+};
 use kiss_api::r#box::BoxCrd;
 use serde::{Deserialize, Serialize};
 use strum::{Display, EnumString};
@@ -58,7 +60,7 @@ impl ClusterUpgradeArgs {
         let first_node = cluster.get_first_control_plane()?;
         let job = create_job(first_node);
 
-        if client.spawn(kube, job).await? {
+        if client.spawn(kube, job).await?.is_spawned() {
             Ok(ClusterUpgradeStatus::Completed)
         } else {
             Ok(ClusterUpgradeStatus::Failed)
@@ -80,9 +82,9 @@ impl ClusterUpgradeArgs {
             .try_collect()
             .await?;
 
-        if status.iter().all(|&e| e) {
+        if status.iter().all(AnsibleJobStatus::is_spawned) {
             Ok(ClusterUpgradeStatus::Completed)
-        } else if status.iter().any(|&e| e) {
+        } else if status.iter().any(AnsibleJobStatus::is_spawned) {
             Ok(ClusterUpgradeStatus::PartiallyCompleted)
         } else {
             Ok(ClusterUpgradeStatus::Failed)
@@ -116,6 +118,7 @@ fn create_job(target_box: &BoxCrd) -> AnsibleJob {
         r#box: target_box,
         new_group: None,
         new_state: None,
+        new_version: None,
         is_critical: true,
         resource_type: AnsibleResourceType::Normal,
         use_workers: false,
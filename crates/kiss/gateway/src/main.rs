@@ -1,4 +1,7 @@
-use std::net::SocketAddr;
+use std::{
+    collections::BTreeSet,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+};
 
 use actix_web::{
     get, middleware, post,
@@ -6,21 +9,30 @@ use actix_web::{
     App, HttpResponse, HttpServer, Responder,
 };
 use actix_web_opentelemetry::{RequestMetrics, RequestTracing};
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 use ark_core::{env::infer, tracer};
 use chrono::Utc;
-use kiss_api::r#box::{
-    request::{BoxCommissionQuery, BoxNewQuery},
-    BoxAccessSpec, BoxCrd, BoxSpec, BoxState, BoxStatus,
+use k8s_openapi::api::batch::v1::Job;
+use kiss_ansible::{config::KissNetworkConfig, AnsibleClient};
+use kiss_api::{
+    enrollment::{EnrollmentPolicy, EnrollmentTokenCrd},
+    ipam::{request::IpAddressAllocateQuery, IpAddressCrd, IpAddressSpec, IpAddressStatus},
+    network_policy::{request::BoxNetworkPolicyQuery, BoxNetworkPolicyStatus},
+    progress::{request::ProvisioningProgressQuery, JobProgress, ProvisioningProgress},
+    r#box::{
+        request::{BoxCommissionQuery, BoxDecommissionQuery, BoxNewQuery},
+        BoxAccessSpec, BoxCrd, BoxDecommissionStatus, BoxNetworkSpec, BoxSpec, BoxState, BoxStatus,
+    },
 };
 use kube::{
-    api::{Patch, PatchParams, PostParams},
+    api::{DeleteParams, ListParams, Patch, PatchParams, PostParams},
     core::ObjectMeta,
-    Api, Client, CustomResourceExt,
+    Api, Client, CustomResourceExt, ResourceExt,
 };
 use opentelemetry::global;
 use serde_json::json;
 use tracing::{instrument, warn, Level};
+use uuid::Uuid;
 
 #[instrument(level = Level::INFO)]
 #[get("/")]
@@ -34,16 +46,46 @@ async fn health() -> impl Responder {
     HttpResponse::Ok().json("healthy")
 }
 
-#[instrument(level = Level::INFO, skip(client))]
+// NOTE: the query is not traced as it contains the enrollment token
+#[instrument(level = Level::INFO, skip_all)]
 #[get("/new")]
 async fn get_new(client: Data<Client>, Query(query): Query<BoxNewQuery>) -> impl Responder {
     async fn try_handle(client: Data<Client>, query: BoxNewQuery) -> Result<()> {
         let api = Api::<BoxCrd>::all((**client).clone());
 
-        let name = query.machine.uuid.to_string();
+        let uuid = query.machine.uuid;
+        let name = uuid.to_string();
+        let token = enroll(&client, query.token.as_deref(), uuid).await?;
 
         match api.get_opt(&name).await? {
             Some(r#box) => {
+                // record the CPU architecture if reported
+                if let Some(arch) = query
+                    .machine
+                    .arch
+                    .filter(|&arch| r#box.spec.machine.arch != Some(arch))
+                {
+                    let crd = BoxCrd::api_resource();
+                    let patch = Patch::Merge(json!({
+                        "apiVersion": crd.api_version,
+                        "kind": crd.kind,
+                        "spec": {
+                            "machine": {
+                                "arch": arch,
+                            },
+                        },
+                    }));
+                    let pp = PatchParams::apply("kiss-gateway");
+                    api.patch(&name, &pp, &patch).await?;
+                }
+
+                // keep quarantining the box unless a valid token is given
+                let is_quarantined = token.is_none()
+                    && r#box
+                        .status
+                        .as_ref()
+                        .is_some_and(|status| matches!(status.state, BoxState::NewQuarantined));
+
                 let crd = BoxCrd::api_resource();
                 let patch = Patch::Merge(json!({
                     "apiVersion": crd.api_version,
@@ -52,22 +94,49 @@ async fn get_new(client: Data<Client>, Query(query): Query<BoxNewQuery>) -> impl
                         access: BoxAccessSpec {
                             primary: Some(query.access_primary.try_into()?),
                         },
-                        state: BoxState::New,
+                        state: if is_quarantined { BoxState::NewQuarantined } else { BoxState::New },
                         bind_group: r#box.status.as_ref().and_then(|status| status.bind_group.as_ref()).cloned(),
+                        conditions: r#box.status.as_ref().map(|status| status.conditions.clone()).unwrap_or_default(),
+                        decommission: r#box.status.as_ref().and_then(|status| status.decommission.as_ref()).cloned(),
+                        inventory: r#box.status.as_ref().and_then(|status| status.inventory.as_ref()).cloned(),
                         last_updated: Utc::now(),
+                        networks: r#box.status.as_ref().map(|status| status.networks.clone()).unwrap_or_default(),
+                        network_policy: r#box.status.as_ref().and_then(|status| status.network_policy.as_ref()).cloned(),
+                        remediation: r#box.status.as_ref().and_then(|status| status.remediation.as_ref()).cloned(),
+                        retry: r#box.status.as_ref().and_then(|status| status.retry.as_ref()).cloned(),
+                        version: r#box.status.as_ref().and_then(|status| status.version.as_ref()).cloned(),
                     },
                 }));
                 let pp = PatchParams::apply("kiss-gateway");
                 api.patch_status(&name, &pp, &patch).await?;
             }
             None => {
+                let policy = AnsibleClient::try_default(&client)
+                    .await?
+                    .kiss
+                    .enrollment_policy;
+                let state = match (&token, policy) {
+                    (Some(_), _) | (None, EnrollmentPolicy::Disabled) => BoxState::New,
+                    (None, EnrollmentPolicy::Quarantine) => {
+                        warn!("quarantined a client without valid enrollment tokens: {name}");
+                        BoxState::NewQuarantined
+                    }
+                    (None, EnrollmentPolicy::Reject) => {
+                        bail!("rejected a client without valid enrollment tokens: {name}")
+                    }
+                };
+
                 let data = BoxCrd {
                     metadata: ObjectMeta {
                         name: Some(name.clone()),
                         ..Default::default()
                     },
                     spec: BoxSpec {
-                        group: Default::default(),
+                        decommission: None,
+                        group: token
+                            .as_ref()
+                            .map(|token| token.spec.group.clone())
+                            .unwrap_or_default(),
                         machine: query.machine,
                         power: None,
                         rack: None,
@@ -88,15 +157,92 @@ async fn get_new(client: Data<Client>, Query(query): Query<BoxNewQuery>) -> impl
                         access: BoxAccessSpec {
                             primary: Some(query.access_primary.try_into()?),
                         },
-                        state: BoxState::New,
+                        state,
                         bind_group: None,
+                        conditions: Default::default(),
+                        decommission: None,
+                        inventory: None,
                         last_updated: Utc::now(),
+                        networks: Default::default(),
+                        network_policy: None,
+                        remediation: None,
+                        retry: None,
+                        version: None,
                     },
                 }));
                 let pp = PatchParams::apply("kiss-gateway");
                 api.patch_status(&name, &pp, &patch).await?;
             }
         }
+
+        if let Some(token) = token {
+            record_enrollment(&client, &token, uuid).await?;
+        }
+        Ok(())
+    }
+
+    /// Return the enrollment token if it is valid.
+    async fn enroll(
+        client: &Client,
+        token: Option<&str>,
+        uuid: Uuid,
+    ) -> Result<Option<EnrollmentTokenCrd>> {
+        let (name, secret) = match token
+            .filter(|token| !token.is_empty())
+            .map(EnrollmentTokenCrd::parse_token)
+        {
+            Some(Some(token)) => token,
+            Some(None) => {
+                warn!("malformed enrollment token: {uuid}");
+                return Ok(None);
+            }
+            None => return Ok(None),
+        };
+
+        let api = Api::<EnrollmentTokenCrd>::all(client.clone());
+        let token = match api.get_opt(name).await? {
+            Some(token) => token,
+            None => {
+                warn!("no such enrollment token: {name} ({uuid})");
+                return Ok(None);
+            }
+        };
+        match token.verify(secret, uuid, Utc::now()) {
+            Ok(()) => Ok(Some(token)),
+            Err(reason) => {
+                warn!("invalid enrollment token: {name} ({uuid}): {reason}");
+                Ok(None)
+            }
+        }
+    }
+
+    async fn record_enrollment(
+        client: &Client,
+        token: &EnrollmentTokenCrd,
+        uuid: Uuid,
+    ) -> Result<()> {
+        let mut boxes = token
+            .status
+            .as_ref()
+            .map(|status| status.boxes.clone())
+            .unwrap_or_default();
+        if boxes.contains(&uuid) {
+            return Ok(());
+        }
+        boxes.push(uuid);
+
+        let api = Api::<EnrollmentTokenCrd>::all(client.clone());
+        let crd = EnrollmentTokenCrd::api_resource();
+        let patch = Patch::Merge(json!({
+            "apiVersion": crd.api_version,
+            "kind": crd.kind,
+            "status": {
+                "boxes": boxes,
+                "lastUpdated": Utc::now(),
+            },
+        }));
+        let pp = PatchParams::apply("kiss-gateway");
+        api.patch_status(&token.name_any(), &pp, &patch).await?;
         Ok(())
     }
 
@@ -127,6 +273,7 @@ async fn post_commission(
                     "apiVersion": crd.api_version,
                     "kind": crd.kind,
                     "spec": BoxSpec {
+                        decommission: r#box.spec.decommission,
                         group: r#box.spec.group,
                         machine: query.machine,
                         power: query.power,
@@ -144,18 +291,127 @@ async fn post_commission(
                                 .and_then(|status| status.bind_group.as_ref())
                                 .cloned()
                         },
+                        conditions: r#box
+                            .status
+                            .as_ref()
+                            .map(|status| status.conditions.clone())
+                            .unwrap_or_default(),
+                        decommission: None,
+                        inventory: query.inventory.map(TryInto::try_into).transpose()?,
                         last_updated: Utc::now(),
+                        networks: query.networks.clone(),
+                        network_policy: None,
+                        remediation: r#box
+                            .status
+                            .as_ref()
+                            .and_then(|status| status.remediation.as_ref())
+                            .cloned(),
+                        retry: r#box
+                            .status
+                            .as_ref()
+                            .and_then(|status| status.retry.as_ref())
+                            .cloned(),
+                        version: if query.reset {
+                            None
+                        } else {
+                            r#box
+                                .status
+                                .as_ref()
+                                .and_then(|status| status.version.as_ref())
+                                .cloned()
+                        },
                     },
                 }));
                 let pp = PatchParams::apply("kiss-gateway");
                 api.patch(&name, &pp, &patch).await?;
                 api.patch_status(&name, &pp, &patch).await?;
+
+                record_leases(&client, query.machine.uuid, &query.networks).await?;
             }
             None => bail!("no such box: {name}"),
         }
         Ok(())
     }
 
+    /// Track the addresses which are held by the box, releasing the others.
+    async fn record_leases(client: &Client, uuid: Uuid, networks: &[BoxNetworkSpec]) -> Result<()> {
+        let api = Api::<IpAddressCrd>::all(client.clone());
+        let crd = IpAddressCrd::api_resource();
+        let pp = PatchParams::apply("kiss-gateway");
+
+        let mut names = BTreeSet::default();
+        for network in networks {
+            let address = match network.address {
+                Some(IpAddr::V4(address)) => address,
+                Some(IpAddr::V6(_)) | None => continue,
+            };
+            let name = IpAddressCrd::name_of(&network.name, address);
+
+            let ip_address = match api.get_opt(&name).await? {
+                Some(ip_address) => ip_address,
+                None => {
+                    let data = IpAddressCrd {
+                        metadata: ObjectMeta {
+                            name: Some(name.clone()),
+                            ..Default::default()
+                        },
+                        spec: IpAddressSpec {
+                            network: network.name.clone(),
+                            address,
+                            reservation: None,
+                        },
+                        status: None,
+                    };
+                    let pp = PostParams {
+                        dry_run: false,
+                        field_manager: Some("kiss-gateway".into()),
+                    };
+                    api.create(&pp, &data).await?
+                }
+            };
+
+            if let Some(reservation) = ip_address.spec.reservation.filter(|&box_| box_ != uuid) {
+                warn!("address {address} is reserved for {reservation}, but leased by {uuid}");
+            }
+            let patch = Patch::Merge(json!({
+                "apiVersion": crd.api_version,
+                "kind": crd.kind,
+                "status": IpAddressStatus {
+                    state: ip_address.state(Some(uuid)),
+                    lease: Some(uuid),
+                    last_updated: Utc::now(),
+                },
+            }));
+            api.patch_status(&name, &pp, &patch).await?;
+            names.insert(name);
+        }
+
+        let lp = ListParams::default();
+        for ip_address in api.list(&lp).await? {
+            let name = ip_address.name_any();
+            if ip_address.lease() != Some(uuid) || names.contains(&name) {
+                continue;
+            }
+
+            if ip_address.spec.reservation.is_none() {
+                let dp = DeleteParams::default();
+                api.delete(&name, &dp).await?;
+            } else {
+                let patch = Patch::Merge(json!({
+                    "apiVersion": crd.api_version,
+                    "kind": crd.kind,
+                    "status": IpAddressStatus {
+                        state: ip_address.state(None),
+                        lease: None,
+                        last_updated: Utc::now(),
+                    },
+                }));
+                api.patch_status(&name, &pp, &patch).await?;
+            }
+        }
+        Ok(())
+    }
+
     match try_handle(client, query).await {
         Ok(()) => HttpResponse::Ok().json("Ok"),
         Err(e) => {
@@ -165,6 +421,224 @@ async fn post_commission(
     }
 }
 
+#[instrument(level = Level::INFO, skip(client))]
+#[post("/decommission")]
+async fn post_decommission(
+    client: Data<Client>,
+    Json(query): Json<BoxDecommissionQuery>,
+) -> impl Responder {
+    async fn try_handle(client: Data<Client>, query: BoxDecommissionQuery) -> Result<()> {
+        let api = Api::<BoxCrd>::all((**client).clone());
+
+        let name = query.machine.uuid.to_string();
+
+        match api.get_opt(&name).await? {
+            Some(r#box) => {
+                // NOTE: the late reports of the withdrawn decommission are ignored
+                if !r#box
+                    .status
+                    .as_ref()
+                    .is_some_and(|status| matches!(status.state, BoxState::Decommissioning))
+                {
+                    bail!("box is not being decommissioned: {name}");
+                }
+
+                let crd = BoxCrd::api_resource();
+                let patch = Patch::Merge(json!({
+                    "apiVersion": crd.api_version,
+                    "kind": crd.kind,
+                    "status": {
+                        "decommission": BoxDecommissionStatus {
+                            step: query.step,
+                            last_updated: Utc::now(),
+                        },
+                    },
+                }));
+                let pp = PatchParams::apply("kiss-gateway");
+                api.patch_status(&name, &pp, &patch).await?;
+            }
+            None => bail!("no such box: {name}"),
+        }
+        Ok(())
+    }
+
+    match try_handle(client, query).await {
+        Ok(()) => HttpResponse::Ok().json("Ok"),
+        Err(e) => {
+            warn!("failed to report the decommission: {e}");
+            HttpResponse::Forbidden().json("Err")
+        }
+    }
+}
+
+#[instrument(level = Level::INFO, skip(client))]
+#[post("/network-policy")]
+async fn post_network_policy(
+    client: Data<Client>,
+    Json(query): Json<BoxNetworkPolicyQuery>,
+) -> impl Responder {
+    async fn try_handle(client: Data<Client>, query: BoxNetworkPolicyQuery) -> Result<()> {
+        let api = Api::<BoxCrd>::all((**client).clone());
+
+        let name = query.machine.uuid.to_string();
+        if api.get_opt(&name).await?.is_none() {
+            bail!("no such box: {name}");
+        }
+
+        let crd = BoxCrd::api_resource();
+        let patch = Patch::Merge(json!({
+            "apiVersion": crd.api_version,
+            "kind": crd.kind,
+            "status": {
+                "networkPolicy": BoxNetworkPolicyStatus {
+                    profile: query.profile,
+                    digest: query.digest,
+                    drifts: query.drifts,
+                    enforced: query.enforced,
+                    last_checked: Utc::now(),
+                },
+            },
+        }));
+        let pp = PatchParams::apply("kiss-gateway");
+        api.patch_status(&name, &pp, &patch).await?;
+        Ok(())
+    }
+
+    match try_handle(client, query).await {
+        Ok(()) => HttpResponse::Ok().json("Ok"),
+        Err(e) => {
+            warn!("failed to report the network policy: {e}");
+            HttpResponse::Forbidden().json("Err")
+        }
+    }
+}
+
+#[instrument(level = Level::INFO, skip(client))]
+#[get("/progress")]
+async fn get_progress(
+    client: Data<Client>,
+    Query(query): Query<ProvisioningProgressQuery>,
+) -> impl Responder {
+    async fn try_handle(
+        client: Data<Client>,
+        query: ProvisioningProgressQuery,
+    ) -> Result<ProvisioningProgress> {
+        let lp = ListParams::default();
+        let boxes = Api::<BoxCrd>::all((**client).clone())
+            .list(&lp)
+            .await?
+            .items;
+
+        let lp = ListParams {
+            label_selector: Some(AnsibleClient::LABEL_BOX_NAME.into()),
+            ..Default::default()
+        };
+        let jobs = Api::<Job>::namespaced((**client).clone(), ::kiss_api::consts::NAMESPACE)
+            .list(&lp)
+            .await?
+            .items
+            .into_iter()
+            .filter(|job| !::kiss_ansible::job::is_finished(job))
+            .map(|job| {
+                let labels = job.labels();
+                JobProgress {
+                    name: job.name_any(),
+                    box_name: labels.get(AnsibleClient::LABEL_BOX_NAME).cloned(),
+                    task: labels.get(AnsibleClient::LABEL_JOB_NAME).cloned(),
+                    created_at: job.creation_timestamp().map(|time| time.0),
+                    active: job
+                        .status
+                        .as_ref()
+                        .and_then(|status| status.active)
+                        .unwrap_or_default(),
+                    failed: job
+                        .status
+                        .as_ref()
+                        .and_then(|status| status.failed)
+                        .unwrap_or_default(),
+                }
+            })
+            .collect();
+
+        Ok(ProvisioningProgress::new(&boxes, jobs, &query, Utc::now()))
+    }
+
+    match try_handle(client, query).await {
+        Ok(progress) => HttpResponse::Ok().json(progress),
+        Err(e) => {
+            warn!("failed to get the provisioning progress: {e}");
+            HttpResponse::InternalServerError().json("Err")
+        }
+    }
+}
+
+#[instrument(level = Level::INFO, skip(client))]
+#[post("/ipam/allocate")]
+async fn post_ipam_allocate(
+    client: Data<Client>,
+    Json(query): Json<IpAddressAllocateQuery>,
+) -> impl Responder {
+    async fn try_handle(client: Data<Client>, query: IpAddressAllocateQuery) -> Result<Ipv4Addr> {
+        let kiss = AnsibleClient::try_default(&client).await?.kiss;
+        let network_name = query
+            .network
+            .as_deref()
+            .unwrap_or(KissNetworkConfig::NAME_PROVISION);
+        let network = kiss
+            .networks
+            .iter()
+            .find(|network| network.name == network_name)
+            .ok_or_else(|| anyhow!("no such network: {network_name}"))?;
+        let (range_begin, range_end) = match (network.dhcp_range_begin, network.dhcp_range_end) {
+            (Some(begin), Some(end)) => (begin, end),
+            _ => bail!("network has no DHCP range: {network_name}"),
+        };
+
+        let api = Api::<IpAddressCrd>::all((**client).clone());
+        let lp = ListParams::default();
+        let addresses = api.list(&lp).await?.items;
+        let address = ::kiss_api::ipam::allocate(
+            &addresses,
+            network_name,
+            range_begin,
+            range_end,
+            query.uuid,
+        )
+        .ok_or_else(|| anyhow!("no available addresses in the network: {network_name}"))?;
+
+        // reserve the new address, so that it is not allocated twice
+        let name = IpAddressCrd::name_of(network_name, address);
+        if !addresses.iter().any(|address| address.name_any() == name) {
+            let data = IpAddressCrd {
+                metadata: ObjectMeta {
+                    name: Some(name),
+                    ..Default::default()
+                },
+                spec: IpAddressSpec {
+                    network: network_name.into(),
+                    address,
+                    reservation: Some(query.uuid),
+                },
+                status: None,
+            };
+            let pp = PostParams {
+                dry_run: false,
+                field_manager: Some("kiss-gateway".into()),
+            };
+            api.create(&pp, &data).await?;
+        }
+        Ok(address)
+    }
+
+    match try_handle(client, query).await {
+        Ok(address) => HttpResponse::Ok().json(address),
+        Err(e) => {
+            warn!("failed to allocate an address: {e}");
+            HttpResponse::Forbidden().json("Err")
+        }
+    }
+}
+
 #[actix_web::main]
 async fn main() {
     async fn try_main() -> Result<()> {
@@ -180,7 +654,11 @@ async fn main() {
                 .service(index)
                 .service(health)
                 .service(get_new)
-                .service(post_commission);
+                .service(get_progress)
+                .service(post_commission)
+                .service(post_ipam_allocate)
+                .service(post_decommission)
+                .service(post_network_policy);
             app.wrap(middleware::NormalizePath::new(
                 middleware::TrailingSlash::Trim,
             ))
@@ -0,0 +1,455 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env, fs,
+    net::IpAddr,
+    path::PathBuf,
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail, Result};
+use kiss_ansible::cluster::ClusterState;
+use kiss_api::{
+    condition::{
+        with_condition, REASON_ETCD_MEMBER_LEARNER, REASON_ETCD_MEMBER_NONE,
+        REASON_ETCD_MEMBER_VOTER, TYPE_ETCD_MEMBER,
+    },
+    r#box::{BoxCrd, BoxState},
+};
+use kube::{
+    api::{ListParams, Patch, PatchParams},
+    Api, Client, CustomResourceExt, ResourceExt,
+};
+use reqwest::{
+    tls::{Certificate, Identity},
+    Url,
+};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{info, instrument, warn, Level};
+
+pub struct Handler {
+    client: ::reqwest::Client,
+    kube: Client,
+}
+
+impl Handler {
+    const CLIENT_PORT: u16 = 2379;
+    const PEER_PORT: u16 = 2380;
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    pub async fn try_default() -> Result<Self> {
+        // NOTE: the certificates are issued by `kubespray` on each control-plane node
+        let certs_dir: PathBuf = env::var("ETCD_CERTS_DIR")
+            .unwrap_or_else(|_| "/etc/ssl/etcd/ssl".into())
+            .into();
+        let node_name = env::var("NODE_NAME")
+            .map_err(|_| anyhow!("failed to get the node name: NODE_NAME is not set"))?;
+
+        let read = |name: String| {
+            let path = certs_dir.join(&name);
+            fs::read(&path)
+                .map_err(|error| anyhow!("failed to read etcd certificate {path:?}: {error}"))
+        };
+        let ca = Certificate::from_pem(&read("ca.pem".into())?)?;
+        let cert = read(format!("node-{node_name}.pem"))?;
+        let key = read(format!("node-{node_name}-key.pem"))?;
+
+        #[cfg(feature = "rustls-tls")]
+        let identity = Identity::from_pem(&[cert, key].concat())?;
+        #[cfg(not(feature = "rustls-tls"))]
+        let identity = Identity::from_pkcs8_pem(&cert, &key)?;
+
+        Ok(Self {
+            client: ::reqwest::Client::builder()
+                .add_root_certificate(ca)
+                .identity(identity)
+                .timeout(Duration::from_secs(10))
+                .build()?,
+            kube: Client::try_default().await?,
+        })
+    }
+}
+
+impl Handler {
+    /// Reconcile the etcd members with the control-plane boxes, one by one.
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn sync(&self) -> Result<()> {
+        let cluster = ClusterState::load_current_cluster(&self.kube, false).await?;
+
+        // collect the boxes which should be the members
+        let desired: BTreeMap<_, _> = cluster
+            .get_etcd_boxes()
+            .into_iter()
+            .filter_map(|r#box| Some((get_address(r#box)?, r#box)))
+            .collect();
+
+        // load the current members
+        let endpoints: Vec<_> = desired
+            .iter()
+            .filter(|(_, r#box)| is_joined(r#box))
+            .map(|(&address, _)| address)
+            .collect();
+        let members = self.load_members(&endpoints).await?;
+
+        // reflect the members into the boxes
+        for r#box in cluster.get_control_planes() {
+            let member = get_address(r#box)
+                .and_then(|address| members.iter().find(|member| member.address == address));
+            self.update_condition(r#box, member).await?;
+        }
+
+        // NOTE: the disconnected boxes are retained, as they may come back
+        let retained: BTreeSet<_> = cluster
+            .get_control_planes()
+            .filter(|r#box| {
+                r#box
+                    .status
+                    .as_ref()
+                    .is_some_and(|status| matches!(status.state, BoxState::Disconnected))
+            })
+            .filter_map(get_address)
+            .chain(desired.keys().copied())
+            .collect();
+        // NOTE: the learners are added only for the joining boxes,
+        //       as their playbooks start the etcd services
+        let joining: BTreeSet<_> = desired
+            .iter()
+            .filter(|(_, r#box)| !is_joined(r#box))
+            .map(|(&address, _)| address)
+            .collect();
+
+        // NOTE: the members which are not managed by KISS (e.g. the bootstrapper) are never removed
+        let known: BTreeSet<_> = Api::<BoxCrd>::all(self.kube.clone())
+            .list(&ListParams::default())
+            .await?
+            .items
+            .iter()
+            .filter_map(get_address)
+            .collect();
+
+        match EtcdOperation::plan(&members, &known, &retained, &joining)? {
+            Some(operation) => self.execute(&endpoints, operation).await,
+            None => Ok(()),
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    async fn load_members(&self, endpoints: &[IpAddr]) -> Result<Vec<EtcdMemberState>> {
+        let list: EtcdMemberList = self
+            .request(endpoints, "/v3/cluster/member/list", json!({}))
+            .await?;
+
+        let mut members = Vec::with_capacity(list.members.len());
+        for member in list.members {
+            let address = match member.address() {
+                Some(address) => address,
+                None => {
+                    warn!(
+                        "Skipping the etcd member without peer address: {}",
+                        &member.id
+                    );
+                    continue;
+                }
+            };
+            // NOTE: the learners do not serve the client requests
+            let is_healthy = !member.is_learner && self.is_healthy(address).await;
+            members.push(EtcdMemberState {
+                address,
+                is_healthy,
+                member,
+            });
+        }
+        Ok(members)
+    }
+
+    async fn is_healthy(&self, address: IpAddr) -> bool {
+        #[derive(Deserialize)]
+        struct Health {
+            health: String,
+        }
+
+        let url = format!(
+            "https://{}/health",
+            socket_address(address, Self::CLIENT_PORT)
+        );
+        match self.client.get(url).send().await {
+            Ok(response) => response
+                .json::<Health>()
+                .await
+                .is_ok_and(|health| health.health == "true"),
+            Err(e) => {
+                warn!("failed to check the health of the etcd member {address}: {e}");
+                false
+            }
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip(self, endpoints), err(Display))]
+    async fn execute(&self, endpoints: &[IpAddr], operation: EtcdOperation) -> Result<()> {
+        let (path, body) = match &operation {
+            EtcdOperation::AddLearner { address } => (
+                "/v3/cluster/member/add",
+                json!({
+                    "peerURLs": [
+                        format!("https://{}", socket_address(*address, Self::PEER_PORT)),
+                    ],
+                    "isLearner": true,
+                }),
+            ),
+            EtcdOperation::Promote { id, .. } => {
+                ("/v3/cluster/member/promote", json!({ "ID": id }))
+            }
+            EtcdOperation::Remove { id, .. } => ("/v3/cluster/member/remove", json!({ "ID": id })),
+        };
+
+        let _: ::serde_json::Value = self.request(endpoints, path, body).await?;
+        info!(
+            "Applied etcd membership change ({}): {operation:?}",
+            operation.address(),
+        );
+        Ok(())
+    }
+
+    /// Send the request to the first available endpoint.
+    async fn request<T>(
+        &self,
+        endpoints: &[IpAddr],
+        path: &str,
+        body: ::serde_json::Value,
+    ) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let mut last_error = None;
+        for &address in endpoints {
+            let url: Url = format!(
+                "https://{}{path}",
+                socket_address(address, Self::CLIENT_PORT),
+            )
+            .parse()?;
+            let response = self
+                .client
+                .post(url)
+                .json(&body)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            match response {
+                Ok(response) => return response.json().await.map_err(Into::into),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        match last_error {
+            Some(error) => bail!("failed to request etcd {path:?}: {error}"),
+            None => bail!("failed to request etcd {path:?}: no running members"),
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip_all, fields(name = %r#box.name_any()), err(Display))]
+    async fn update_condition(
+        &self,
+        r#box: &BoxCrd,
+        member: Option<&EtcdMemberState>,
+    ) -> Result<()> {
+        let status = match r#box.status.as_ref() {
+            Some(status) => status,
+            None => return Ok(()),
+        };
+
+        let (healthy, reason, message) = match member {
+            Some(member) if member.member.is_learner => (
+                false,
+                REASON_ETCD_MEMBER_LEARNER,
+                "the etcd member is not promoted yet",
+            ),
+            Some(member) if member.is_healthy => {
+                (true, REASON_ETCD_MEMBER_VOTER, "the etcd member is healthy")
+            }
+            Some(_) => (
+                false,
+                REASON_ETCD_MEMBER_VOTER,
+                "the etcd member is unhealthy",
+            ),
+            None => (
+                false,
+                REASON_ETCD_MEMBER_NONE,
+                "the box is not a member of the etcd cluster",
+            ),
+        };
+        let conditions = with_condition(
+            Some(&status.conditions),
+            TYPE_ETCD_MEMBER,
+            healthy,
+            reason,
+            message,
+            r#box.metadata.generation,
+        );
+        if status.conditions == conditions {
+            return Ok(());
+        }
+
+        let api = Api::<BoxCrd>::all(self.kube.clone());
+        let crd = BoxCrd::api_resource();
+        let patch = Patch::Merge(json!({
+            "apiVersion": crd.api_version,
+            "kind": crd.kind,
+            "status": {
+                "conditions": conditions,
+                "lastUpdated": status.last_updated,
+            },
+        }));
+        let pp = PatchParams::apply("kiss-manager");
+        api.patch_status(&r#box.name_any(), &pp, &patch).await?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum EtcdOperation {
+    AddLearner { address: IpAddr },
+    Promote { id: String, address: IpAddr },
+    Remove { id: String, address: IpAddr },
+}
+
+impl EtcdOperation {
+    const fn address(&self) -> IpAddr {
+        match self {
+            Self::AddLearner { address }
+            | Self::Promote { address, .. }
+            | Self::Remove { address, .. } => *address,
+        }
+    }
+
+    /// Plan the next membership change, preserving the quorum.
+    ///
+    /// Only one change is planned at once, so that the quorum is re-evaluated after each change.
+    fn plan(
+        members: &[EtcdMemberState],
+        known: &BTreeSet<IpAddr>,
+        retained: &BTreeSet<IpAddr>,
+        joining: &BTreeSet<IpAddr>,
+    ) -> Result<Option<Self>> {
+        let voters = members
+            .iter()
+            .filter(|member| !member.member.is_learner)
+            .count();
+        let healthy = members.iter().filter(|member| member.is_healthy).count();
+        if voters == 0 {
+            bail!("no etcd voters are found");
+        }
+        if healthy < quorum(voters) {
+            warn!("etcd quorum is lost ({healthy}/{voters}); skipping membership changes");
+            return Ok(None);
+        }
+
+        // promote or remove the learners first, which do not affect the quorum
+        if let Some(learner) = members.iter().find(|member| member.member.is_learner) {
+            let id = learner.member.id.clone();
+            let address = learner.address;
+            return Ok(Some(if retained.contains(&address) {
+                // NOTE: etcd rejects the promotion if the learner is not in sync with the leader
+                Self::Promote { id, address }
+            } else {
+                Self::Remove { id, address }
+            }));
+        }
+
+        // remove the unwanted voters, preferring the unhealthy ones
+        if let Some(voter) = members
+            .iter()
+            .filter(|member| known.contains(&member.address) && !retained.contains(&member.address))
+            .min_by_key(|member| member.is_healthy)
+        {
+            let healthy_remaining = healthy - usize::from(voter.is_healthy);
+            if voters > 1 && healthy_remaining >= quorum(voters - 1) {
+                return Ok(Some(Self::Remove {
+                    id: voter.member.id.clone(),
+                    address: voter.address,
+                }));
+            }
+            warn!(
+                "removing the etcd member {} breaks the quorum; skipping",
+                voter.address,
+            );
+            return Ok(None);
+        }
+
+        // add a joining box as a learner if all the voters are healthy
+        if let Some(&address) = joining
+            .iter()
+            .find(|&address| !members.iter().any(|member| &member.address == address))
+        {
+            if healthy == voters {
+                return Ok(Some(Self::AddLearner { address }));
+            }
+            warn!("some etcd members are unhealthy; pending adding a learner: {address}");
+        }
+        Ok(None)
+    }
+}
+
+const fn quorum(voters: usize) -> usize {
+    voters / 2 + 1
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+struct EtcdMemberList {
+    #[serde(default)]
+    members: Vec<EtcdMember>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EtcdMember {
+    /// The member ID, which is encoded as a string since it is an unsigned 64-bit integer.
+    #[serde(rename = "ID")]
+    id: String,
+    #[serde(default, rename = "peerURLs")]
+    peer_urls: Vec<String>,
+    #[serde(default)]
+    is_learner: bool,
+}
+
+impl EtcdMember {
+    fn address(&self) -> Option<IpAddr> {
+        self.peer_urls
+            .iter()
+            .filter_map(|url| url.parse::<Url>().ok())
+            .find_map(|url| {
+                url.host_str()?
+                    .trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .parse()
+                    .ok()
+            })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct EtcdMemberState {
+    address: IpAddr,
+    is_healthy: bool,
+    member: EtcdMember,
+}
+
+fn get_address(r#box: &BoxCrd) -> Option<IpAddr> {
+    r#box
+        .status
+        .as_ref()
+        .and_then(|status| status.access.primary.as_ref())
+        .map(|interface| interface.address)
+}
+
+fn is_joined(r#box: &BoxCrd) -> bool {
+    r#box
+        .status
+        .as_ref()
+        .is_some_and(|status| status.state.is_joined())
+}
+
+fn socket_address(address: IpAddr, port: u16) -> String {
+    match address {
+        IpAddr::V4(address) => format!("{address}:{port}"),
+        IpAddr::V6(address) => format!("[{address}]:{port}"),
+    }
+}
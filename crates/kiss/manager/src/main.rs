@@ -1,9 +1,11 @@
 mod current;
+mod etcd;
 mod latest;
 
 use std::{cmp::Ordering, time::Duration};
 
 use anyhow::Result;
+use tokio::try_join;
 use tracing::{info, instrument, warn, Level};
 
 #[instrument(level = Level::INFO, skip_all)]
@@ -35,6 +37,32 @@ async fn sync_cluster(
     }
 }
 
+async fn loop_upgrade(
+    current_handler: self::current::Handler,
+    latest_handler: self::latest::Handler,
+) -> Result<()> {
+    // sync the cluster periodically
+    loop {
+        sync_cluster(&current_handler, &latest_handler).await?;
+        ::tokio::time::sleep(Duration::from_secs(5 * 60)).await;
+    }
+}
+
+async fn loop_etcd(etcd_handler: Option<self::etcd::Handler>) -> Result<()> {
+    let etcd_handler = match etcd_handler {
+        Some(handler) => handler,
+        None => return Ok(()),
+    };
+
+    // sync the etcd members periodically
+    loop {
+        if let Err(error) = etcd_handler.sync().await {
+            warn!("failed to sync etcd members: {error}");
+        }
+        ::tokio::time::sleep(Duration::from_secs(30)).await;
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // initialize tracer
@@ -43,10 +71,13 @@ async fn main() -> Result<()> {
     // create the handlers
     let current = self::current::Handler::try_default().await?;
     let latest = self::latest::Handler::default();
+    let etcd = match self::etcd::Handler::try_default().await {
+        Ok(handler) => Some(handler),
+        Err(error) => {
+            warn!("etcd membership management is disabled: {error}");
+            None
+        }
+    };
 
-    // sync the cluster periodically
-    loop {
-        sync_cluster(&current, &latest).await?;
-        ::tokio::time::sleep(Duration::from_secs(5 * 60)).await;
-    }
+    try_join!(loop_upgrade(current, latest), loop_etcd(etcd)).map(|_| ())
 }
@@ -1,16 +1,22 @@
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use ark_core_k8s::manager::Manager;
 use async_trait::async_trait;
 use chrono::Utc;
-use k8s_openapi::api::batch::v1::Job;
+use k8s_openapi::api::{batch::v1::Job, core::v1::Pod};
 use kiss_ansible::AnsibleClient;
-use kiss_api::r#box::{BoxCrd, BoxState};
+use kiss_api::{
+    group::BoxGroupCrd,
+    r#box::{BoxCrd, BoxRetryPolicySpec, BoxRetryStatus, BoxState, BoxVersionSpec},
+};
 use kube::{
-    api::{Patch, PatchParams},
-    runtime::controller::Action,
-    Api, CustomResourceExt, Error, ResourceExt,
+    api::{ListParams, LogParams, Patch, PatchParams},
+    runtime::{
+        controller::Action,
+        events::{Event, EventType, Recorder, Reporter},
+    },
+    Api, Client, CustomResourceExt, Error, Resource, ResourceExt,
 };
 use serde_json::json;
 use tracing::{info, instrument, warn, Level};
@@ -62,6 +68,7 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
         // when the ansible job is succeeded
         if has_completed {
             info!("Job has completed: {name} ({box_name})");
+            Self::clear_box_retry(&manager.kube, &box_name).await?;
 
             // update the state
             if let Some(completed_state) = completed_state {
@@ -78,11 +85,14 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
         }
         // when the ansible job is failed
         else if has_failed {
-            let failed_state = BoxState::Failed;
+            let failed_state = data
+                .labels()
+                .get(AnsibleClient::LABEL_FAILED_STATE)
+                .and_then(|state| state.parse().ok())
+                .unwrap_or(BoxState::Failed);
             warn!("Job has failed: {name} ({box_name})");
-            warn!("Updating box state: {name} ({box_name} => {failed_state})");
 
-            Self::update_box_state(manager, data, failed_state).await
+            Self::handle_failure(manager, data, failed_state).await
         }
         // when the ansible job is not finished yet
         else {
@@ -94,6 +104,89 @@ impl ::ark_core_k8s::manager::Ctx for Ctx {
 }
 
 impl Ctx {
+    /// Schedule a retry of the failed task, or fail the box if the attempts are exhausted.
+    #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any(), namespace = data.namespace(), state = %failed_state), err(Display))]
+    async fn handle_failure(
+        manager: Arc<Manager<Self>>,
+        data: Arc<<Self as ::ark_core_k8s::manager::Ctx>::Data>,
+        failed_state: BoxState,
+    ) -> Result<Action, Error>
+    where
+        Self: Sized,
+    {
+        let name = data.name_any();
+        // box name is already tested by reconciling
+        let box_name = Self::get_box_name(&data).unwrap();
+
+        let api = Api::<BoxCrd>::all(manager.kube.clone());
+        let r#box = match api.get_opt(&box_name).await? {
+            Some(r#box) => r#box,
+            None => {
+                warn!("No such box: {name} ({box_name})");
+                return Ok(Action::await_change());
+            }
+        };
+
+        // NOTE: the cron jobs are rerun by their schedules
+        let is_retryable = !data
+            .owner_references()
+            .iter()
+            .any(|owner| owner.kind == "CronJob");
+
+        let task: String =
+            Self::get_label(&data, AnsibleClient::LABEL_JOB_NAME).unwrap_or_default();
+        let attempts = r#box
+            .status
+            .as_ref()
+            .and_then(|status| status.retry.as_ref())
+            .filter(|retry| retry.task == task)
+            .map(|retry| retry.attempts)
+            .unwrap_or_default()
+            + 1;
+
+        let now = Utc::now();
+        let policy = Self::load_retry_policy(&manager.kube, &r#box).await;
+        let retry = BoxRetryStatus {
+            attempts,
+            task,
+            reason: Self::get_failure_reason(&manager.kube, &data).await,
+            last_failed: now,
+            retry_at: if is_retryable && !policy.is_exhausted(attempts) {
+                Some(now + policy.backoff(&r#box.spec.machine, attempts))
+            } else {
+                None
+            },
+        };
+
+        match retry.retry_at {
+            Some(retry_at) => {
+                info!("Retrying the task at {retry_at}: {name} ({box_name}, attempts: {attempts})");
+                Self::update_box_retry(&api, &box_name, Some(&retry)).await?;
+                Ok(Action::requeue(
+                    <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+                ))
+            }
+            None => {
+                if is_retryable {
+                    let note = format!(
+                        "{task} has failed {attempts} times: {reason}",
+                        task = &retry.task,
+                        reason = retry.reason.as_deref().unwrap_or("unknown"),
+                    );
+                    if let Err(error) =
+                        Self::publish_event(&manager.kube, &r#box, "RetryExhausted", note).await
+                    {
+                        warn!("{error}");
+                    }
+                }
+                Self::update_box_retry(&api, &box_name, Some(&retry)).await?;
+
+                warn!("Updating box state: {name} ({box_name} => {failed_state})");
+                Self::update_box_state(manager, data, failed_state).await
+            }
+        }
+    }
+
     #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any(), namespace = data.namespace(), state = %state), err(Display))]
     async fn update_box_state(
         manager: Arc<Manager<Self>>,
@@ -110,13 +203,20 @@ impl Ctx {
         {
             let api = Api::<BoxCrd>::all(manager.kube.clone());
             let crd = BoxCrd::api_resource();
+            let mut status = json!({
+                "state": state,
+                "lastUpdated": Utc::now(),
+            });
+            // record the upgraded version
+            if state == BoxState::Running {
+                if let Some(version) = Self::get_completed_version(&data) {
+                    status["version"] = json!(version);
+                }
+            }
             let patch = Patch::Apply(json!({
                 "apiVersion": crd.api_version,
                 "kind": crd.kind,
-                "status": {
-                    "state": state,
-                    "lastUpdated": Utc::now(),
-                },
+                "status": status,
             }));
             let pp = PatchParams::apply("kiss-monitor").force();
             api.patch_status(&box_name, &pp, &patch).await?;
@@ -128,6 +228,181 @@ impl Ctx {
         ))
     }
 
+    /// Load the retry policy of the box group, falling back to the KISS configuration.
+    async fn load_retry_policy(kube: &Client, r#box: &BoxCrd) -> BoxRetryPolicySpec {
+        let fallback = match AnsibleClient::try_default(kube).await {
+            Ok(ansible) => ansible.kiss.retry,
+            Err(error) => {
+                warn!("failed to create AnsibleClient: {error}");
+                BoxRetryPolicySpec::default()
+            }
+        };
+
+        let api = Api::<BoxGroupCrd>::all(kube.clone());
+        let lp = ListParams::default();
+        match api.list(&lp).await {
+            Ok(groups) => groups
+                .items
+                .into_iter()
+                .filter(|group| group.contains(r#box))
+                .find_map(|group| group.spec.retry)
+                .map(|policy| policy.or(&fallback))
+                .unwrap_or(fallback),
+            Err(error) => {
+                warn!("failed to list box groups: {error}");
+                fallback
+            }
+        }
+    }
+
+    /// Extract the failure reason from the logs of the job, or from the job status.
+    async fn get_failure_reason(
+        kube: &Client,
+        data: &<Self as ::ark_core_k8s::manager::Ctx>::Data,
+    ) -> Option<String> {
+        const MAX_LEN: usize = 1024;
+
+        let api = Api::<Pod>::namespaced(
+            kube.clone(),
+            <Self as ::ark_core_k8s::manager::Ctx>::NAMESPACE,
+        );
+        let lp = ListParams {
+            label_selector: Some(format!("job-name={}", data.name_any())),
+            ..Default::default()
+        };
+        let lgp = LogParams {
+            tail_lines: Some(200),
+            ..Default::default()
+        };
+
+        let mut pods = api
+            .list(&lp)
+            .await
+            .map(|pods| pods.items)
+            .unwrap_or_default();
+        pods.sort_by_key(|pod| ::core::cmp::Reverse(pod.creation_timestamp()));
+
+        let mut reason = None;
+        for pod in pods {
+            // NOTE: the pods may be already terminated
+            let logs = match api.logs(&pod.name_any(), &lgp).await {
+                Ok(logs) => logs,
+                Err(_) => continue,
+            };
+            // Ansible reports the failed task as: "fatal: [host]: FAILED! => {...}"
+            reason = logs
+                .lines()
+                .rev()
+                .find(|line| line.contains("fatal:") || line.contains("FAILED!"))
+                .map(|line| line.trim().to_string());
+            if reason.is_some() {
+                break;
+            }
+        }
+
+        reason
+            .or_else(|| {
+                data.status
+                    .as_ref()
+                    .and_then(|status| status.conditions.as_ref())
+                    .and_then(|conditions| {
+                        conditions
+                            .iter()
+                            .find(|condition| condition.type_ == "Failed")
+                    })
+                    .and_then(|condition| {
+                        condition
+                            .message
+                            .clone()
+                            .or_else(|| condition.reason.clone())
+                    })
+            })
+            .map(|reason| reason.chars().take(MAX_LEN).collect())
+    }
+
+    #[instrument(level = Level::INFO, skip(api, retry), err(Display))]
+    async fn update_box_retry(
+        api: &Api<BoxCrd>,
+        box_name: &str,
+        retry: Option<&BoxRetryStatus>,
+    ) -> Result<(), Error> {
+        let crd = BoxCrd::api_resource();
+        let mut status = json!({
+            "retry": retry,
+        });
+        // restart the timeout of the task being retried
+        if retry.is_some_and(|retry| retry.retry_at.is_some()) {
+            status["lastUpdated"] = json!(Utc::now());
+        }
+        let patch = Patch::Merge(json!({
+            "apiVersion": crd.api_version,
+            "kind": crd.kind,
+            "status": status,
+        }));
+        let pp = PatchParams::apply("kiss-monitor");
+        api.patch_status(box_name, &pp, &patch).await?;
+        Ok(())
+    }
+
+    /// Clear the failed attempts of the box, as the task is succeeded.
+    async fn clear_box_retry(kube: &Client, box_name: &str) -> Result<(), Error> {
+        let api = Api::<BoxCrd>::all(kube.clone());
+        let has_retry = api
+            .get_opt(box_name)
+            .await?
+            .and_then(|r#box| r#box.status)
+            .is_some_and(|status| status.retry.is_some());
+        if has_retry {
+            Self::update_box_retry(&api, box_name, None).await?;
+        }
+        Ok(())
+    }
+
+    #[instrument(level = Level::INFO, skip(kube, r#box), err(Display))]
+    async fn publish_event(
+        kube: &Client,
+        r#box: &BoxCrd,
+        reason: &str,
+        note: String,
+    ) -> Result<()> {
+        let reporter = Reporter {
+            controller: <Self as ::ark_core_k8s::manager::Ctx>::NAME.into(),
+            instance: None,
+        };
+        let recorder = Recorder::new(kube.clone(), reporter);
+
+        let event = Event {
+            type_: EventType::Warning,
+            reason: reason.into(),
+            note: Some(note),
+            action: "Retry".into(),
+            secondary: None,
+        };
+        recorder
+            .publish(&event, &r#box.object_ref(&()))
+            .await
+            .map_err(|error| anyhow!("failed to publish box event: {error}"))
+    }
+
+    fn get_completed_version(
+        data: &<Self as ::ark_core_k8s::manager::Ctx>::Data,
+    ) -> Option<BoxVersionSpec> {
+        let annotations = data.annotations();
+        let version = BoxVersionSpec {
+            kernel: annotations
+                .get(AnsibleClient::ANNOTATION_COMPLETED_VERSION_KERNEL)
+                .cloned(),
+            os: annotations
+                .get(AnsibleClient::ANNOTATION_COMPLETED_VERSION_OS)
+                .cloned(),
+        };
+        if version.kernel.is_some() || version.os.is_some() {
+            Some(version)
+        } else {
+            None
+        }
+    }
+
     fn get_box_name(data: &<Self as ::ark_core_k8s::manager::Ctx>::Data) -> Option<String> {
         Self::get_label(data, AnsibleClient::LABEL_BOX_NAME)
     }
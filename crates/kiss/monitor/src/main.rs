@@ -1,4 +1,6 @@
 mod ctx;
+mod remediation;
+mod sensor;
 
 use ark_core_k8s::manager::Ctx;
 
@@ -8,5 +10,9 @@ pub(crate) mod consts {
 
 #[tokio::main]
 async fn main() {
-    self::ctx::Ctx::spawn_namespaced().await
+    ::tokio::join!(
+        self::ctx::Ctx::spawn_namespaced(),
+        self::remediation::loop_remediation(),
+        self::sensor::loop_sensors(),
+    );
 }
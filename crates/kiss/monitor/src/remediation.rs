@@ -0,0 +1,160 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use k8s_openapi::api::core::v1::Node;
+use kiss_ansible::config::{KissConfig, KissRemediationConfig};
+use kiss_api::r#box::{BoxCrd, BoxRemediationStatus, BoxState};
+use kube::{
+    api::{ListParams, Patch, PatchParams},
+    Api, Client, CustomResourceExt, ResourceExt,
+};
+use serde_json::json;
+use tokio::time::sleep;
+use tracing::{info, instrument, warn, Level};
+
+/// Watch the nodes of the running boxes periodically, if enabled by the KISS configuration.
+pub async fn loop_remediation() {
+    const FALLBACK: Duration = Duration::from_secs(60); // 1 minute
+
+    let handler = match Handler::try_default().await {
+        Ok(handler) => handler,
+        Err(error) => {
+            warn!("remediation is disabled: {error}");
+            return;
+        }
+    };
+
+    loop {
+        let interval = match handler.sync().await {
+            Ok(interval) => interval,
+            Err(error) => {
+                warn!("failed to sync box remediation: {error}");
+                FALLBACK
+            }
+        };
+        sleep(interval).await;
+    }
+}
+
+struct Handler {
+    kube: Client,
+}
+
+impl Handler {
+    async fn try_default() -> Result<Self> {
+        Ok(Self {
+            kube: Client::try_default().await?,
+        })
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn sync(&self) -> Result<Duration> {
+        let KissConfig {
+            remediation: config,
+            ..
+        } = KissConfig::try_default(&self.kube).await?;
+        let interval = Duration::from_secs(config.interval_seconds.max(1));
+        if !config.enabled {
+            return Ok(interval);
+        }
+
+        let now = Utc::now();
+        let api = Api::<BoxCrd>::all(self.kube.clone());
+        let nodes = Api::<Node>::all(self.kube.clone());
+        let lp = ListParams::default();
+        for r#box in api.list(&lp).await? {
+            let name = r#box.name_any();
+            let status = match r#box.status.as_ref() {
+                // NOTE: the nodes of the other clusters are not visible here
+                Some(status)
+                    if matches!(status.state, BoxState::Running)
+                        && status
+                            .bind_group
+                            .as_ref()
+                            .is_some_and(|group| group.is_default()) =>
+                {
+                    status
+                }
+                _ => continue,
+            };
+
+            let is_ready = match nodes.get_opt(&r#box.spec.machine.hostname()).await {
+                Ok(Some(node)) => is_node_ready(&node),
+                // the node is not registered yet
+                Ok(None) => continue,
+                Err(error) => {
+                    warn!("failed to get node ({name}): {error}");
+                    continue;
+                }
+            };
+
+            // NOTE: the not-ready period is counted from the first observation
+            let remediation = status.remediation.clone().unwrap_or_default();
+            let not_ready_since = if is_ready {
+                None
+            } else {
+                Some(remediation.not_ready_since.unwrap_or(now))
+            };
+            let requested = is_expired(&config, not_ready_since, now);
+            if remediation.not_ready_since == not_ready_since && remediation.requested == requested
+            {
+                continue;
+            }
+
+            if requested && !remediation.requested {
+                info!("Requesting remediation: {name}");
+            }
+            let new_remediation = BoxRemediationStatus {
+                not_ready_since,
+                requested,
+                ..remediation
+            };
+
+            if let Err(error) = update_remediation(&api, &name, &new_remediation).await {
+                warn!("failed to update box remediation ({name}): {error}");
+            }
+        }
+        Ok(interval)
+    }
+}
+
+fn is_expired(
+    config: &KissRemediationConfig,
+    not_ready_since: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> bool {
+    not_ready_since.is_some_and(|since| {
+        (now - since).num_seconds() >= config.not_ready_seconds.try_into().unwrap_or(i64::MAX)
+    })
+}
+
+fn is_node_ready(node: &Node) -> bool {
+    node.status
+        .as_ref()
+        .and_then(|status| status.conditions.as_ref())
+        .and_then(|conditions| {
+            conditions
+                .iter()
+                .find(|condition| condition.type_ == "Ready")
+        })
+        .is_some_and(|condition| condition.status == "True")
+}
+
+async fn update_remediation(
+    api: &Api<BoxCrd>,
+    name: &str,
+    remediation: &BoxRemediationStatus,
+) -> Result<()> {
+    let crd = BoxCrd::api_resource();
+    let patch = Patch::Merge(json!({
+        "apiVersion": crd.api_version,
+        "kind": crd.kind,
+        "status": {
+            "remediation": remediation,
+        },
+    }));
+    let pp = PatchParams::apply(crate::consts::NAME);
+    api.patch_status(name, &pp, &patch).await?;
+    Ok(())
+}
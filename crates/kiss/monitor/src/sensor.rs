@@ -0,0 +1,409 @@
+use std::{net::IpAddr, time::Duration};
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use dash_pipe_provider::{messengers::Publisher, PipeClient, PipeMessage, PipePublisher};
+use k8s_openapi::api::core::v1::Secret;
+use kiss_ansible::config::{KissConfig, KissSensorConfig};
+use kiss_api::{
+    condition::{with_condition, REASON_DEGRADED_NONE, TYPE_DEGRADED},
+    r#box::{BoxCrd, BoxPowerType},
+    sensor::{BoxFanReading, BoxSensorReading, BoxTemperatureReading},
+};
+use kube::{
+    api::{ListParams, Patch, PatchParams},
+    Api, Client, CustomResourceExt, ResourceExt,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::json;
+use tokio::{sync::OnceCell, time::sleep};
+use tracing::{info, instrument, warn, Level};
+
+/// Poll the BMCs of the boxes periodically, if enabled by the KISS configuration.
+pub async fn loop_sensors() {
+    const FALLBACK: Duration = Duration::from_secs(60); // 1 minute
+
+    let handler = match Handler::try_default().await {
+        Ok(handler) => handler,
+        Err(error) => {
+            warn!("sensor monitoring is disabled: {error}");
+            return;
+        }
+    };
+
+    loop {
+        let interval = match handler.sync().await {
+            Ok(interval) => interval,
+            Err(error) => {
+                warn!("failed to sync box sensors: {error}");
+                FALLBACK
+            }
+        };
+        sleep(interval).await;
+    }
+}
+
+struct Handler {
+    client: ::reqwest::Client,
+    kube: Client,
+    pipe: OnceCell<Option<PipeClient<BoxSensorReading>>>,
+}
+
+impl Handler {
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    async fn try_default() -> Result<Self> {
+        Ok(Self {
+            // NOTE: the BMCs are usually serving the self-signed certificates
+            client: ::reqwest::Client::builder()
+                .danger_accept_invalid_certs(true)
+                .timeout(Self::TIMEOUT)
+                .build()?,
+            kube: Client::try_default().await?,
+            pipe: OnceCell::new(),
+        })
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    async fn sync(&self) -> Result<Duration> {
+        let KissConfig { sensor: config, .. } = KissConfig::try_default(&self.kube).await?;
+        let interval = Duration::from_secs(config.interval_seconds.max(1));
+        if !config.enabled {
+            return Ok(interval);
+        }
+
+        let credentials = self.load_credentials().await?;
+        let publisher = self.load_publisher(&config).await;
+
+        let api = Api::<BoxCrd>::all(self.kube.clone());
+        let lp = ListParams::default();
+        for r#box in api.list(&lp).await? {
+            let name = r#box.name_any();
+            let address = match r#box
+                .spec
+                .power
+                .as_ref()
+                .filter(|power| matches!(power.r#type, BoxPowerType::Ipmi))
+                .and_then(|power| power.address)
+            {
+                Some(address) => address,
+                None => continue,
+            };
+
+            let reading = match self.poll(&name, address, &credentials).await {
+                Ok(reading) => reading,
+                Err(error) => {
+                    warn!("failed to poll box sensors ({name}): {error}");
+                    continue;
+                }
+            };
+
+            if let Some(publisher) = &publisher {
+                let message = PipeMessage::new(reading.clone());
+                if let Err(error) = Publisher::<_, PipeMessage>::send_one(publisher, message).await
+                {
+                    warn!("failed to publish box sensors ({name}): {error}");
+                }
+            }
+
+            if let Err(error) = self.update_condition(&api, &r#box, &config, &reading).await {
+                warn!("failed to update box condition ({name}): {error}");
+            }
+        }
+        Ok(interval)
+    }
+
+    async fn load_credentials(&self) -> Result<Credentials> {
+        let api = Api::<Secret>::namespaced(self.kube.clone(), ::kiss_api::consts::NAMESPACE);
+        let secret = api.get("kiss-config").await?;
+
+        let get = |key: &str| {
+            secret
+                .data
+                .as_ref()
+                .and_then(|data| data.get(key))
+                .and_then(|value| String::from_utf8(value.0.clone()).ok())
+                .ok_or_else(|| anyhow!("failed to find the secret variable: {key}"))
+        };
+        Ok(Credentials {
+            username: get("power_ipmi_username")?,
+            password: get("power_ipmi_password")?,
+        })
+    }
+
+    /// Load the publisher of the readings.
+    ///
+    /// If the messenger is not available, the readings are only used to update the boxes.
+    async fn load_publisher(&self, config: &KissSensorConfig) -> Option<PipePublisher> {
+        let pipe = self
+            .pipe
+            .get_or_init(|| async {
+                match PipeClient::try_default().await {
+                    Ok(client) => Some(client),
+                    Err(error) => {
+                        warn!("failed to init pipe client; skipping publishing: {error}");
+                        None
+                    }
+                }
+            })
+            .await
+            .as_ref()?;
+
+        let topic = match config.topic.parse() {
+            Ok(topic) => topic,
+            Err(error) => {
+                warn!(
+                    "invalid sensor topic {topic:?}: {error}",
+                    topic = &config.topic
+                );
+                return None;
+            }
+        };
+        match pipe.publish(topic).await {
+            Ok(publisher) => Some(publisher),
+            Err(error) => {
+                warn!("failed to init sensor publisher: {error}");
+                None
+            }
+        }
+    }
+
+    /// Collect the readings of all chassis over the Redfish API.
+    #[instrument(level = Level::INFO, skip(self, credentials), err(Display))]
+    async fn poll(
+        &self,
+        name: &str,
+        address: IpAddr,
+        credentials: &Credentials,
+    ) -> Result<BoxSensorReading> {
+        let mut reading = BoxSensorReading {
+            r#box: name.into(),
+            fans: Default::default(),
+            power_watts: None,
+            temperatures: Default::default(),
+            timestamp: Utc::now(),
+        };
+
+        let chassis: RedfishCollection = self
+            .get(address, "/redfish/v1/Chassis", credentials)
+            .await?;
+        for RedfishLink { id } in chassis.members {
+            let chassis: RedfishChassis = self.get(address, &id, credentials).await?;
+
+            if let Some(RedfishLink { id }) = chassis.thermal {
+                let thermal: RedfishThermal = self.get(address, &id, credentials).await?;
+                reading.temperatures.extend(
+                    thermal
+                        .temperatures
+                        .into_iter()
+                        .filter(|sensor| sensor.status.is_present())
+                        .filter_map(|sensor| {
+                            Some(BoxTemperatureReading {
+                                name: sensor.name,
+                                celsius: sensor.reading_celsius?,
+                                critical_celsius: sensor.upper_threshold_critical,
+                            })
+                        }),
+                );
+                reading.fans.extend(
+                    thermal
+                        .fans
+                        .into_iter()
+                        .filter(|fan| fan.status.is_present())
+                        .map(|fan| BoxFanReading {
+                            healthy: fan.status.is_healthy(),
+                            name: fan.name,
+                            rpm: fan
+                                .reading
+                                .filter(|_| fan.reading_units.as_deref() != Some("Percent")),
+                        }),
+                );
+            }
+
+            // NOTE: the power draw is reported by the first chassis, not to count it twice
+            if reading.power_watts.is_none() {
+                if let Some(RedfishLink { id }) = chassis.power {
+                    let power: RedfishPower = self.get(address, &id, credentials).await?;
+                    reading.power_watts = power
+                        .power_control
+                        .iter()
+                        .filter_map(|control| control.power_consumed_watts)
+                        .reduce(|a, b| a + b);
+                }
+            }
+        }
+        Ok(reading)
+    }
+
+    async fn get<T>(&self, address: IpAddr, path: &str, credentials: &Credentials) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let host = match address {
+            IpAddr::V4(address) => address.to_string(),
+            IpAddr::V6(address) => format!("[{address}]"),
+        };
+        let url = format!("https://{host}{path}");
+
+        self.client
+            .get(&url)
+            .basic_auth(&credentials.username, Some(&credentials.password))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|error| anyhow!("failed to request redfish ({url}): {error}"))?
+            .json()
+            .await
+            .map_err(|error| anyhow!("failed to parse redfish response ({url}): {error}"))
+    }
+
+    #[instrument(level = Level::INFO, skip_all, fields(name = %r#box.name_any()), err(Display))]
+    async fn update_condition(
+        &self,
+        api: &Api<BoxCrd>,
+        r#box: &BoxCrd,
+        config: &KissSensorConfig,
+        reading: &BoxSensorReading,
+    ) -> Result<()> {
+        let status = match r#box.status.as_ref() {
+            Some(status) => status,
+            None => return Ok(()),
+        };
+
+        let (degraded, reason, message) = match reading.check(&config.thresholds) {
+            Some((reason, message)) => (true, reason, message),
+            None => (
+                false,
+                REASON_DEGRADED_NONE,
+                "all the sensor readings are within the thresholds".into(),
+            ),
+        };
+        let conditions = with_condition(
+            Some(&status.conditions),
+            TYPE_DEGRADED,
+            degraded,
+            reason,
+            &message,
+            r#box.metadata.generation,
+        );
+        if status.conditions == conditions {
+            return Ok(());
+        }
+
+        let name = r#box.name_any();
+        if degraded {
+            warn!("Box is degraded: {name}: {message}");
+        } else {
+            info!("Box is recovered: {name}");
+        }
+
+        let crd = BoxCrd::api_resource();
+        let patch = Patch::Merge(json!({
+            "apiVersion": crd.api_version,
+            "kind": crd.kind,
+            "status": {
+                "conditions": conditions,
+                "lastUpdated": status.last_updated,
+            },
+        }));
+        let pp = PatchParams::apply(crate::consts::NAME);
+        api.patch_status(&name, &pp, &patch).await?;
+        Ok(())
+    }
+}
+
+struct Credentials {
+    username: String,
+    password: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RedfishCollection {
+    #[serde(default)]
+    members: Vec<RedfishLink>,
+}
+
+#[derive(Deserialize)]
+struct RedfishLink {
+    #[serde(rename = "@odata.id")]
+    id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RedfishChassis {
+    #[serde(default)]
+    power: Option<RedfishLink>,
+    #[serde(default)]
+    thermal: Option<RedfishLink>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RedfishThermal {
+    #[serde(default)]
+    fans: Vec<RedfishFan>,
+    #[serde(default)]
+    temperatures: Vec<RedfishTemperature>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RedfishFan {
+    // NOTE: the legacy BMCs name it as `FanName`
+    #[serde(default, alias = "FanName")]
+    name: String,
+    #[serde(default)]
+    reading: Option<f64>,
+    #[serde(default)]
+    reading_units: Option<String>,
+    #[serde(default)]
+    status: RedfishStatus,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RedfishTemperature {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    reading_celsius: Option<f64>,
+    #[serde(default)]
+    status: RedfishStatus,
+    #[serde(default)]
+    upper_threshold_critical: Option<f64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RedfishPower {
+    #[serde(default)]
+    power_control: Vec<RedfishPowerControl>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RedfishPowerControl {
+    #[serde(default)]
+    power_consumed_watts: Option<f64>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RedfishStatus {
+    #[serde(default)]
+    health: Option<String>,
+    #[serde(default)]
+    state: Option<String>,
+}
+
+impl RedfishStatus {
+    fn is_healthy(&self) -> bool {
+        matches!(self.health.as_deref(), None | Some("OK"))
+    }
+
+    fn is_present(&self) -> bool {
+        !matches!(self.state.as_deref(), Some("Absent"))
+    }
+}
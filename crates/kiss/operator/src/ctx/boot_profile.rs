@@ -0,0 +1,253 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Result;
+use ark_core_k8s::manager::Manager;
+use async_trait::async_trait;
+use chrono::Utc;
+use k8s_openapi::api::core::v1::ConfigMap;
+use kiss_api::{
+    boot::{BootProfileBootSpec, BootProfileCrd, BootProfileStatus},
+    r#box::BoxCrd,
+};
+use kube::{
+    api::{ListParams, Patch, PatchParams},
+    core::ObjectMeta,
+    runtime::controller::Action,
+    Api, Client, CustomResourceExt, Error, ResourceExt,
+};
+use serde_json::json;
+use tracing::{info, instrument, Level};
+use uuid::Uuid;
+
+/// The base URL of the boot profiles, which are served by the assets server.
+const BASE_URL: &str = "http://assets.kiss.svc.ops.openark/boot-profiles";
+
+#[derive(Default)]
+pub struct Ctx {}
+
+#[async_trait]
+impl ::ark_core_k8s::manager::Ctx for Ctx {
+    type Data = BootProfileCrd;
+
+    const NAME: &'static str = crate::consts::NAME;
+    const NAMESPACE: &'static str = ::kiss_api::consts::NAMESPACE;
+    // NOTE: the boxes are not watched, so the profiles are re-rendered periodically
+    const FALLBACK: Duration = Duration::from_secs(5 * 60); // 5 minutes
+    const FINALIZER_NAME: &'static str =
+        <Self as ::ark_core_k8s::manager::Ctx>::Data::FINALIZER_NAME;
+
+    #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any(), namespace = data.namespace()), err(Display))]
+    async fn reconcile(
+        manager: Arc<Manager<Self>>,
+        data: Arc<<Self as ::ark_core_k8s::manager::Ctx>::Data>,
+    ) -> Result<Action, Error>
+    where
+        Self: Sized,
+    {
+        let name = data.name_any();
+        let api = Api::<<Self as ::ark_core_k8s::manager::Ctx>::Data>::all(manager.kube.clone());
+
+        if data.metadata.deletion_timestamp.is_some() {
+            Self::render(&manager.kube, Some(&name)).await?;
+            return <Self as ::ark_core_k8s::manager::Ctx>::remove_finalizer_or_requeue(
+                &api, None, &name,
+            )
+            .await;
+        }
+        if !data
+            .finalizers()
+            .iter()
+            .any(|finalizer| finalizer == <Self as ::ark_core_k8s::manager::Ctx>::FINALIZER_NAME)
+        {
+            return <Self as ::ark_core_k8s::manager::Ctx>::add_finalizer_or_requeue(
+                &api, None, &name,
+            )
+            .await;
+        }
+
+        let machines = Self::render(&manager.kube, None)
+            .await?
+            .remove(&name)
+            .unwrap_or_default();
+
+        // update the status
+        if data.status.as_ref().map(|status| &status.machines) != Some(&machines) {
+            let crd = BootProfileCrd::api_resource();
+            let patch = Patch::Merge(json!({
+                "apiVersion": crd.api_version,
+                "kind": crd.kind,
+                "status": BootProfileStatus {
+                    machines,
+                    last_updated: Utc::now(),
+                },
+            }));
+            let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME);
+            api.patch_status(&name, &pp, &patch).await?;
+
+            info!("Reconciled Document {name:?}");
+        }
+
+        // If no events were received, check back after a few minutes
+        Ok(Action::requeue(
+            <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+        ))
+    }
+}
+
+impl Ctx {
+    /// Render all the boot profiles into the config maps, and return the machines of each profile.
+    #[instrument(level = Level::INFO, skip(kube), err(Display))]
+    async fn render(
+        kube: &Client,
+        excluded: Option<&str>,
+    ) -> Result<BTreeMap<String, Vec<Uuid>>, Error> {
+        let lp = ListParams::default();
+        let profiles: Vec<_> = Api::<BootProfileCrd>::all(kube.clone())
+            .list(&lp)
+            .await?
+            .items
+            .into_iter()
+            .filter(|profile| {
+                profile.metadata.deletion_timestamp.is_none()
+                    && Some(profile.name_any().as_str()) != excluded
+            })
+            .collect();
+        let boxes: BTreeMap<_, _> = Api::<BoxCrd>::all(kube.clone())
+            .list(&lp)
+            .await?
+            .items
+            .into_iter()
+            .map(|r#box| (r#box.spec.machine.uuid, r#box))
+            .collect();
+
+        // bind each machine to the most preferred profile
+        let machines: BTreeSet<_> = boxes
+            .keys()
+            .copied()
+            .chain(
+                profiles
+                    .iter()
+                    .flat_map(|profile| profile.spec.selector.machines.iter().copied()),
+            )
+            .collect();
+        let mut bindings: BTreeMap<_, Vec<_>> = BTreeMap::default();
+        for uuid in machines {
+            if let Some(profile) = BootProfileCrd::find(&profiles, uuid, boxes.get(&uuid)) {
+                bindings.entry(profile.name_any()).or_default().push(uuid);
+            }
+        }
+
+        let mut data = BTreeMap::default();
+        let mut groups = BTreeMap::default();
+        let mut matchbox_profiles = BTreeMap::default();
+        for profile in &profiles {
+            let name = profile.name_any();
+            let boot = render_boot(profile);
+
+            if let Some(template) = profile.spec.template.as_ref() {
+                data.insert(format!("{name}.yaml"), template.content.clone());
+            }
+            matchbox_profiles.insert(
+                format!("{name}.json"),
+                format!(
+                    "{:#}",
+                    json!({
+                        "id": &name,
+                        "name": &name,
+                        "boot": &boot,
+                    }),
+                ),
+            );
+
+            for uuid in bindings.get(&name).into_iter().flatten() {
+                data.insert(format!("{uuid}.ipxe"), render_ipxe(&name, &boot));
+                groups.insert(
+                    format!("{name}-{uuid}.json"),
+                    format!(
+                        "{:#}",
+                        json!({
+                            "id": format!("{name}-{uuid}"),
+                            "name": format!("{name}-{uuid}"),
+                            "profile": &name,
+                            "selector": {
+                                "uuid": uuid,
+                            },
+                            "metadata": {},
+                        }),
+                    ),
+                );
+            }
+        }
+
+        apply_config_map(kube, BootProfileCrd::CONFIG_MAP_NAME, data).await?;
+        apply_config_map(kube, BootProfileCrd::CONFIG_MAP_NAME_GROUPS, groups).await?;
+        apply_config_map(
+            kube,
+            BootProfileCrd::CONFIG_MAP_NAME_PROFILES,
+            matchbox_profiles,
+        )
+        .await?;
+        Ok(bindings)
+    }
+}
+
+#[instrument(level = Level::INFO, skip(kube, data), err(Display))]
+async fn apply_config_map(
+    kube: &Client,
+    name: &str,
+    data: BTreeMap<String, String>,
+) -> Result<(), Error> {
+    let namespace = <Ctx as ::ark_core_k8s::manager::Ctx>::NAMESPACE;
+    let api = Api::<ConfigMap>::namespaced(kube.clone(), namespace);
+    let config_map = ConfigMap {
+        metadata: ObjectMeta {
+            name: Some(name.into()),
+            namespace: Some(namespace.into()),
+            ..Default::default()
+        },
+        data: Some(data),
+        ..Default::default()
+    };
+    let pp = PatchParams::apply(<Ctx as ::ark_core_k8s::manager::Ctx>::NAME).force();
+    api.patch(name, &pp, &Patch::Apply(&config_map)).await?;
+    Ok(())
+}
+
+/// Render the boot parameters with the template URL.
+fn render_boot(profile: &BootProfileCrd) -> BootProfileBootSpec {
+    let template_url = format!("{BASE_URL}/{name}.yaml", name = profile.name_any());
+    let boot = &profile.spec.boot;
+    BootProfileBootSpec {
+        kernel: boot.kernel.clone(),
+        initrd: boot.initrd.clone(),
+        args: boot
+            .args
+            .iter()
+            .map(|arg| arg.replace("${template_url}", &template_url))
+            .collect(),
+    }
+}
+
+fn render_ipxe(name: &str, boot: &BootProfileBootSpec) -> String {
+    let BootProfileBootSpec {
+        kernel,
+        initrd,
+        args,
+    } = boot;
+
+    let mut script = format!("#!ipxe\n# Rendered from the boot profile: {name}\n\n");
+    script.push_str(&format!("kernel {kernel}"));
+    for arg in args {
+        script.push_str(&format!(" {arg}"));
+    }
+    script.push('\n');
+    for initrd in initrd {
+        script.push_str(&format!("initrd {initrd}\n"));
+    }
+    script.push_str("boot\n");
+    script
+}
@@ -0,0 +1,564 @@
+use std::{collections::BTreeSet, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use ark_core_k8s::manager::Manager;
+use async_trait::async_trait;
+use chrono::Utc;
+use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
+use kiss_ansible::{AnsibleClient, AnsibleJob, AnsibleResourceType};
+use kiss_api::{
+    condition::{set_condition, Condition, TYPE_JOB_QUEUED},
+    group::{BoxGroupCrd, BoxGroupUpgradeSpec},
+    r#box::{BoxCrd, BoxGroupRole, BoxRetryStatus, BoxState, BoxStatus},
+};
+use kube::{
+    api::{ListParams, Patch, PatchParams},
+    runtime::controller::Action,
+    Api, Client, CustomResourceExt, Error, ResourceExt,
+};
+use serde_json::json;
+use tracing::{info, instrument, warn, Level};
+
+#[derive(Default)]
+pub struct Ctx {}
+
+#[async_trait]
+impl ::ark_core_k8s::manager::Ctx for Ctx {
+    type Data = BoxCrd;
+
+    const NAME: &'static str = crate::consts::NAME;
+    const NAMESPACE: &'static str = ::kiss_api::consts::NAMESPACE;
+
+    fn get_subcrds() -> Vec<CustomResourceDefinition> {
+        vec![
+            ::kiss_api::group::BoxGroupCrd::crd(),
+            ::kiss_api::netbox::NetBoxCrd::crd(),
+            ::kiss_api::rack::RackCrd::crd(),
+        ]
+    }
+
+    #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any(), namespace = data.namespace()), err(Display))]
+    async fn reconcile(
+        manager: Arc<Manager<Self>>,
+        data: Arc<<Self as ::ark_core_k8s::manager::Ctx>::Data>,
+    ) -> Result<Action, Error>
+    where
+        Self: Sized,
+    {
+        let crd = BoxCrd::api_resource();
+        let name = data.name_any();
+        let status = data.status.as_ref();
+        let api = Api::<<Self as ::ark_core_k8s::manager::Ctx>::Data>::all(manager.kube.clone());
+
+        // get the current time
+        let now = Utc::now();
+
+        // load the box's state
+        let old_state = status
+            .as_ref()
+            .map(|status| status.state)
+            .unwrap_or(BoxState::New);
+        let mut new_state = old_state.next();
+        let mut new_group = None;
+        let mut conditions = status
+            .map(|status| status.conditions.clone())
+            .unwrap_or_default();
+
+        // wait for the quarantined boxes being approved
+        if matches!(old_state, BoxState::NewQuarantined) {
+            let is_approved = data
+                .labels()
+                .get(BoxCrd::LABEL_ENROLLMENT_APPROVED)
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(false);
+            if !is_approved {
+                info!("Waiting for the enrollment being approved: {name:?}");
+                return Ok(Action::await_change());
+            }
+
+            info!("Approved the enrollment: {name:?}");
+            new_state = BoxState::New;
+        }
+
+        // wait for the boxes quarantined by the remediation being released
+        let mut new_remediation = status.and_then(|status| status.remediation.clone());
+        let is_remediation_released = matches!(old_state, BoxState::Quarantined)
+            && data
+                .labels()
+                .get(BoxCrd::LABEL_REMEDIATION_RELEASED)
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(false);
+        if is_remediation_released {
+            info!("Released the remediation: {name:?}");
+            new_state = BoxState::Running;
+            new_remediation = None;
+        } else if matches!(old_state, BoxState::Quarantined) {
+            info!("Waiting for the remediation being released: {name:?}");
+        }
+
+        // detect the box's group is changed
+        let is_bind_group_updated = status
+            .as_ref()
+            .and_then(|status| status.bind_group.as_ref())
+            .map(|bind_group| bind_group != &data.spec.group)
+            .unwrap_or(true);
+
+        // wait new boxes with no access methods for begin provisioned
+        if matches!(old_state, BoxState::New)
+            && !status
+                .as_ref()
+                .map(|status| status.access.primary.is_some())
+                .unwrap_or_default()
+        {
+            let timeout = BoxState::timeout_new();
+
+            if let Some(last_updated) = data.last_updated() {
+                if now > *last_updated + timeout {
+                    // update the status
+                    new_state = BoxState::Disconnected;
+                } else {
+                    return Ok(Action::requeue(timeout.to_std().unwrap()));
+                }
+            } else {
+                return Ok(Action::requeue(timeout.to_std().unwrap()));
+            }
+        }
+
+        // capture the timeout
+        if let Some(time_threshold) = old_state.timeout() {
+            if let Some(last_updated) = data.last_updated() {
+                if now > *last_updated + time_threshold {
+                    // update the status
+                    new_state = old_state.fail();
+                }
+            }
+        }
+
+        // capture the group info is changed
+        if matches!(old_state, BoxState::Running) && is_bind_group_updated {
+            new_state = BoxState::Disconnected;
+        }
+
+        // roll out the upgrade of the box group
+        let mut new_version = None;
+        if matches!(old_state, BoxState::Running | BoxState::UpgradeFailed)
+            && !is_bind_group_updated
+        {
+            match Self::load_upgrade(&manager.kube, &data).await? {
+                Some(upgrade) => {
+                    if matches!(old_state, BoxState::Running)
+                        && !upgrade.paused
+                        && Self::is_upgrade_next(&manager.kube, &data, &upgrade).await?
+                    {
+                        info!("Upgrading box: {name:?}");
+                        new_state = BoxState::Upgrading;
+                        new_version = Some(
+                            upgrade
+                                .version
+                                .merge(status.and_then(|status| status.version.as_ref())),
+                        );
+                    }
+                }
+                // recover the box if the failed upgrade is withdrawn
+                None => {
+                    if matches!(old_state, BoxState::UpgradeFailed) {
+                        new_state = BoxState::Running;
+                    }
+                }
+            }
+        }
+
+        // load kiss config
+        let ansible = match AnsibleClient::try_default(&manager.kube).await {
+            Ok(ansible) => ansible,
+            Err(e) => {
+                warn!("failed to create AnsibleClient: {e}");
+                return Ok(Action::requeue(
+                    <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+                ));
+            }
+        };
+
+        // power-cycle the box if its node has been not ready for a long time
+        if matches!(old_state, BoxState::Running) && matches!(new_state, BoxState::Running) {
+            if let Some(remediation) = new_remediation
+                .as_mut()
+                .filter(|remediation| remediation.requested)
+            {
+                remediation.not_ready_since = None;
+                remediation.requested = false;
+                remediation.prune_reboots(now);
+
+                if remediation.reboots.len() >= ansible.kiss.remediation.max_reboots_per_day {
+                    warn!("Quarantining box (too many power-cycles): {name:?}");
+                    new_state = BoxState::Quarantined;
+                } else {
+                    info!("Remediating box: {name:?}");
+                    remediation.reboots.push(now);
+                    new_state = BoxState::Remediating;
+                }
+            }
+        }
+
+        // decommission the box if requested
+        if data.spec.decommission.is_some()
+            && !old_state.is_decommissioned()
+            && status.is_some_and(|status| status.access.primary.is_some())
+        {
+            // NOTE: wiping the disks cannot be undone
+            if !ansible.kiss.allow_critical_commands {
+                warn!("Skipped decommissioning (critical commands are not allowed) {name:?}");
+                return Ok(Action::requeue(
+                    <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+                ));
+            }
+
+            info!("Decommissioning box: {name:?}");
+            new_state = BoxState::Decommissioning;
+        }
+        // recover the box if the failed decommission is withdrawn
+        if matches!(old_state, BoxState::DecommissionFailed) && data.spec.decommission.is_none() {
+            new_state = BoxState::Failed;
+        }
+
+        if !matches!(old_state, BoxState::Joining) && matches!(new_state, BoxState::Joining) {
+            // skip joining to default cluster as worker nodes when external
+            if matches!(data.spec.group.role, BoxGroupRole::ExternalWorker) {
+                info!("Skipped joining (box is external) {name:?}");
+                return Ok(Action::requeue(
+                    <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+                ));
+            }
+
+            // skip joining to default cluster as worker nodes when disabled
+            if !ansible.kiss.group_enable_default_cluster
+                && data.spec.group.is_default()
+                && matches!(data.spec.group.role, BoxGroupRole::GenericWorker)
+            {
+                info!("Skipped joining (default cluster is disabled) {name:?}");
+                return Ok(Action::requeue(
+                    <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+                ));
+            }
+
+            // skip joining to the groups pinned to the other CPU architectures
+            if !Self::is_arch_allowed(&manager.kube, &data).await? {
+                warn!(
+                    "Skipped joining (unsupported CPU architecture: {arch}) {name:?}",
+                    arch = data.spec.machine.arch(),
+                );
+                return Ok(Action::requeue(
+                    <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+                ));
+            }
+
+            // skip joining if already joined
+            if !is_bind_group_updated {
+                let patch = Patch::Merge(json!({
+                    "apiVersion": crd.api_version,
+                    "kind": crd.kind,
+                    "status": BoxStatus {
+                        access: status.map(|status| status.access.clone()).unwrap_or_default(),
+                        state: BoxState::Running,
+                        bind_group: status.and_then(|status| status.bind_group.clone()),
+                        conditions,
+                        decommission: status.and_then(|status| status.decommission.clone()),
+                        inventory: status.and_then(|status| status.inventory.clone()),
+                        last_updated: Utc::now(),
+                        networks: status.map(|status| status.networks.clone()).unwrap_or_default(),
+                        network_policy: status.and_then(|status| status.network_policy.clone()),
+                        remediation: status.and_then(|status| status.remediation.clone()),
+                        retry: None,
+                        version: status.and_then(|status| status.version.clone()),
+                    },
+                }));
+                let pp = PatchParams::apply(Self::NAME);
+                api.patch_status(&name, &pp, &patch).await?;
+
+                info!("Skipped joining (already joined) {name:?}");
+                return Ok(Action::requeue(
+                    <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+                ));
+            }
+
+            // bind to new group
+            new_group = Some(&data.spec.group);
+        }
+
+        // retry the failed task after the backoff
+        let retry_at = status
+            .and_then(|status| status.retry.as_ref())
+            .and_then(|retry| retry.retry_at);
+        let is_retrying = match retry_at {
+            Some(retry_at) if old_state == new_state => {
+                if now < retry_at {
+                    info!("Waiting for retrying the task: {name:?}");
+                    return Ok(Action::requeue(
+                        (retry_at - now)
+                            .to_std()
+                            .unwrap_or(<Self as ::ark_core_k8s::manager::Ctx>::FALLBACK),
+                    ));
+                }
+                info!("Retrying the task: {name:?}");
+                true
+            }
+            _ => false,
+        };
+        if is_retrying && matches!(new_state, BoxState::Upgrading) {
+            new_version = Self::load_upgrade(&manager.kube, &data)
+                .await?
+                .map(|upgrade| {
+                    upgrade
+                        .version
+                        .merge(status.and_then(|status| status.version.as_ref()))
+                });
+        }
+
+        // spawn an Ansible job
+        if old_state != new_state || new_state.cron().is_some() || is_retrying {
+            if let Some(task) = new_state.as_task() {
+                let job_status = ansible
+                    .spawn(
+                        &manager.kube,
+                        AnsibleJob {
+                            cron: new_state.cron(),
+                            task,
+                            r#box: &data,
+                            new_group,
+                            new_state: Some(new_state),
+                            new_version: new_version.as_ref(),
+                            is_critical: false,
+                            resource_type: match (old_state, new_state) {
+                                (_, BoxState::Upgrading | BoxState::Decommissioning)
+                                | (
+                                    BoxState::New
+                                    | BoxState::NewQuarantined
+                                    | BoxState::Commissioning
+                                    | BoxState::Ready
+                                    | BoxState::Joining
+                                    | BoxState::Upgrading
+                                    | BoxState::Decommissioning,
+                                    _,
+                                ) => AnsibleResourceType::Normal,
+                                (
+                                    BoxState::Running
+                                    | BoxState::UpgradeFailed
+                                    | BoxState::Remediating
+                                    | BoxState::Quarantined
+                                    | BoxState::GroupChanged
+                                    | BoxState::Failed
+                                    | BoxState::Disconnected
+                                    | BoxState::DecommissionFailed
+                                    | BoxState::Decommissioned,
+                                    _,
+                                ) => AnsibleResourceType::Minimal,
+                            },
+                            use_workers: false,
+                        },
+                    )
+                    .await?;
+
+                set_condition(
+                    &mut conditions,
+                    TYPE_JOB_QUEUED,
+                    !job_status.is_spawned(),
+                    job_status.reason(),
+                    format!("{task}: {}", job_status.message()),
+                    data.metadata.generation,
+                );
+
+                // If there is a problem spawning a job, check back after a few minutes
+                if !job_status.is_spawned() {
+                    Self::update_conditions(&api, &data, conditions).await?;
+                    info!("Cannot spawn an Ansible job; waiting: {}", &name);
+                    return Ok(Action::requeue(
+                        #[allow(clippy::identity_op)]
+                        Duration::from_secs(1 * 60),
+                    ));
+                }
+            }
+
+            // wait for being changed
+            if old_state == new_state && !is_retrying {
+                Self::update_conditions(&api, &data, conditions).await?;
+                info!("Waiting for being changed: {name:?}");
+                return Ok(Action::await_change());
+            }
+
+            // bind group before joining to a cluster
+            let bind_group = if matches!(new_state, BoxState::Joining) {
+                Some(&data.spec.group)
+            } else {
+                status
+                    .as_ref()
+                    .and_then(|status| status.bind_group.as_ref())
+            };
+
+            let patch = Patch::Merge(json!({
+                "apiVersion": crd.api_version,
+                "kind": crd.kind,
+                "status": BoxStatus {
+                    access: status.map(|status| status.access.clone()).unwrap_or_default(),
+                    state: new_state,
+                    bind_group: bind_group.cloned(),
+                    conditions,
+                    // NOTE: the progress is reported by the playbook from the scratch
+                    decommission: if matches!(new_state, BoxState::Decommissioning)
+                        && old_state != new_state
+                    {
+                        None
+                    } else {
+                        status.and_then(|status| status.decommission.clone())
+                    },
+                    inventory: status.and_then(|status| status.inventory.clone()),
+                    last_updated: Utc::now(),
+                    networks: status.map(|status| status.networks.clone()).unwrap_or_default(),
+                    network_policy: status.and_then(|status| status.network_policy.clone()),
+                    remediation: new_remediation,
+                    // NOTE: the failed attempts are kept until retried or the state is changed
+                    retry: if old_state == new_state {
+                        status
+                            .and_then(|status| status.retry.clone())
+                            .map(|retry| BoxRetryStatus {
+                                retry_at: None,
+                                ..retry
+                            })
+                    } else {
+                        None
+                    },
+                    version: status.and_then(|status| status.version.clone()),
+                },
+            }));
+            let pp = PatchParams::apply(Self::NAME);
+            api.patch_status(&name, &pp, &patch).await?;
+
+            // NOTE: the label is consumed so that it does not release the next quarantine
+            if is_remediation_released {
+                let patch = Patch::Merge(json!({
+                    "metadata": {
+                        "labels": {
+                            BoxCrd::LABEL_REMEDIATION_RELEASED: null,
+                        },
+                    },
+                }));
+                api.patch(&name, &pp, &patch).await?;
+            }
+
+            info!("Reconciled Document {name:?}");
+        }
+
+        // If no events were received, check back after a few minutes
+        Ok(Action::requeue(
+            <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+        ))
+    }
+}
+
+impl Ctx {
+    /// Update the conditions of the box if changed.
+    #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any()), err(Display))]
+    async fn update_conditions(
+        api: &Api<BoxCrd>,
+        data: &BoxCrd,
+        conditions: Vec<Condition>,
+    ) -> Result<(), Error> {
+        let status = match data.status.as_ref() {
+            Some(status) if status.conditions != conditions => status,
+            _ => return Ok(()),
+        };
+
+        let crd = BoxCrd::api_resource();
+        let patch = Patch::Merge(json!({
+            "apiVersion": crd.api_version,
+            "kind": crd.kind,
+            "status": {
+                "conditions": conditions,
+                "lastUpdated": status.last_updated,
+            },
+        }));
+        let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME);
+        api.patch_status(&data.name_any(), &pp, &patch).await?;
+        Ok(())
+    }
+
+    /// Load the upgrade policy of the box group if the box is outdated.
+    #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any()), err(Display))]
+    async fn load_upgrade(
+        kube: &Client,
+        data: &BoxCrd,
+    ) -> Result<Option<BoxGroupUpgradeSpec>, Error> {
+        let api = Api::<BoxGroupCrd>::all(kube.clone());
+        let lp = ListParams::default();
+        let version = data
+            .status
+            .as_ref()
+            .and_then(|status| status.version.as_ref());
+
+        Ok(api
+            .list(&lp)
+            .await?
+            .items
+            .into_iter()
+            .filter(|group| group.contains(data))
+            .filter_map(|group| group.spec.upgrade)
+            .find(|upgrade| upgrade.is_outdated(version)))
+    }
+
+    /// Return `true` if the CPU architecture of the box is allowed by its box groups.
+    #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any()), err(Display))]
+    async fn is_arch_allowed(kube: &Client, data: &BoxCrd) -> Result<bool, Error> {
+        let api = Api::<BoxGroupCrd>::all(kube.clone());
+        let lp = ListParams::default();
+
+        Ok(api
+            .list(&lp)
+            .await?
+            .items
+            .iter()
+            .filter(|group| group.contains(data))
+            .all(|group| group.is_arch_allowed(data)))
+    }
+
+    /// Return `true` if the box is the next one to be upgraded within the budget of the box group.
+    #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any()), err(Display))]
+    async fn is_upgrade_next(
+        kube: &Client,
+        data: &BoxCrd,
+        upgrade: &BoxGroupUpgradeSpec,
+    ) -> Result<bool, Error> {
+        let api = Api::<BoxCrd>::all(kube.clone());
+        let lp = ListParams::default();
+        let boxes: Vec<_> = api
+            .list(&lp)
+            .await?
+            .items
+            .into_iter()
+            .filter(|r#box| r#box.spec.group == data.spec.group)
+            .collect();
+
+        // the failed boxes hold the budget so that the rollout is halted
+        let num_unavailable = boxes
+            .iter()
+            .filter_map(|r#box| r#box.status.as_ref())
+            .filter(|status| matches!(status.state, BoxState::Upgrading | BoxState::UpgradeFailed))
+            .count();
+        let num_available = (upgrade.max_unavailable as usize).saturating_sub(num_unavailable);
+
+        // NOTE: the candidates are sorted by name so that the concurrent reconcilers agree on them
+        let name = data.name_any();
+        let candidates: BTreeSet<_> = boxes
+            .iter()
+            .filter(|r#box| {
+                r#box.status.as_ref().is_some_and(|status| {
+                    matches!(status.state, BoxState::Running)
+                        && upgrade.is_outdated(status.version.as_ref())
+                })
+            })
+            .map(|r#box| r#box.name_any())
+            .collect();
+        Ok(candidates
+            .into_iter()
+            .take(num_available)
+            .any(|candidate| candidate == name))
+    }
+}
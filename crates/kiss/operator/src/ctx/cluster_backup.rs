@@ -0,0 +1,282 @@
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use ark_core_k8s::manager::Manager;
+use async_trait::async_trait;
+use chrono::Utc;
+use k8s_openapi::api::{
+    batch::v1::{CronJob, CronJobSpec, Job, JobSpec, JobTemplateSpec},
+    core::v1::{
+        Affinity, Container, EnvFromSource, EnvVar, EnvVarSource, HostPathVolumeSource,
+        NodeAffinity, NodeSelector, NodeSelectorRequirement, NodeSelectorTerm, ObjectFieldSelector,
+        PodSpec, PodTemplateSpec, SecretEnvSource, Volume, VolumeMount,
+    },
+};
+use kiss_api::backup::{ClusterBackupCrd, ClusterBackupStatus};
+use kube::{
+    api::{ListParams, Patch, PatchParams},
+    core::ObjectMeta,
+    runtime::controller::Action,
+    Api, CustomResourceExt, Error, Resource, ResourceExt,
+};
+use serde_json::json;
+use tracing::{info, instrument, Level};
+
+#[derive(Default)]
+pub struct Ctx {}
+
+#[async_trait]
+impl ::ark_core_k8s::manager::Ctx for Ctx {
+    type Data = ClusterBackupCrd;
+
+    const NAME: &'static str = crate::consts::NAME;
+    const NAMESPACE: &'static str = ::kiss_api::consts::NAMESPACE;
+    const FALLBACK: Duration = Duration::from_secs(5 * 60); // 5 minutes
+
+    #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any(), namespace = data.namespace()), err(Display))]
+    async fn reconcile(
+        manager: Arc<Manager<Self>>,
+        data: Arc<<Self as ::ark_core_k8s::manager::Ctx>::Data>,
+    ) -> Result<Action, Error>
+    where
+        Self: Sized,
+    {
+        let name = data.name_any();
+        let namespace = <Self as ::ark_core_k8s::manager::Ctx>::NAMESPACE;
+        let cron_job_name = data.cron_job_name();
+
+        // apply the cron job
+        let api_cron_job = Api::<CronJob>::namespaced(manager.kube.clone(), namespace);
+        let cron_job = build_cron_job(&data, namespace, &cron_job_name);
+        let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME).force();
+        api_cron_job
+            .patch(&cron_job_name, &pp, &Patch::Apply(&cron_job))
+            .await?;
+
+        // collect the results of the backup jobs
+        // NOTE: the jobs succeed only if the snapshots are verified and uploaded
+        let api_job = Api::<Job>::namespaced(manager.kube.clone(), namespace);
+        let lp = ListParams::default().labels(&format!(
+            "{key}={name}",
+            key = ClusterBackupCrd::LABEL_BACKUP_NAME,
+        ));
+        let jobs = api_job.list(&lp).await?.items;
+
+        let last_status = data.status.as_ref();
+        let last_successful = jobs
+            .iter()
+            .filter_map(|job| {
+                let status = job.status.as_ref()?;
+                if status.succeeded.unwrap_or_default() > 0 {
+                    Some((status.completion_time.as_ref()?.0, job.name_any()))
+                } else {
+                    None
+                }
+            })
+            .max()
+            .map(|(time, name)| (Some(time), Some(name)))
+            .unwrap_or_else(|| {
+                (
+                    last_status.and_then(|status| status.last_successful_backup),
+                    last_status.and_then(|status| status.last_successful_job_name.clone()),
+                )
+            });
+        let last_failed =
+            jobs.iter()
+                .filter_map(|job| {
+                    let status = job.status.as_ref()?;
+                    let is_failed =
+                        status.conditions.as_ref()?.iter().any(|condition| {
+                            condition.type_ == "Failed" && condition.status == "True"
+                        });
+                    if is_failed {
+                        status.start_time.as_ref().map(|time| time.0)
+                    } else {
+                        None
+                    }
+                })
+                .max()
+                .or_else(|| last_status.and_then(|status| status.last_failed_backup));
+
+        // skip updating the status if nothing has been changed
+        let is_changed = last_status
+            .map(|status| {
+                status.cron_job_name.as_ref() != Some(&cron_job_name)
+                    || status.last_successful_backup != last_successful.0
+                    || status.last_failed_backup != last_failed
+            })
+            .unwrap_or(true);
+        if is_changed {
+            if let Some(time) = last_successful.0 {
+                info!("Found the last successful backup of {name:?}: {time}");
+            }
+            update_status(
+                &manager,
+                &name,
+                ClusterBackupStatus {
+                    cron_job_name: Some(cron_job_name),
+                    last_successful_backup: last_successful.0,
+                    last_successful_job_name: last_successful.1,
+                    last_failed_backup: last_failed,
+                    last_updated: Utc::now(),
+                },
+            )
+            .await?;
+        }
+
+        // If no events were received, check back after a few minutes
+        Ok(Action::requeue(
+            <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+        ))
+    }
+}
+
+async fn update_status(
+    manager: &Manager<Ctx>,
+    name: &str,
+    status: ClusterBackupStatus,
+) -> Result<(), Error> {
+    let api = Api::<ClusterBackupCrd>::all(manager.kube.clone());
+    let crd = ClusterBackupCrd::api_resource();
+    let patch = Patch::Merge(json!({
+        "apiVersion": crd.api_version,
+        "kind": crd.kind,
+        "status": status,
+    }));
+    let pp = PatchParams::apply(<Ctx as ::ark_core_k8s::manager::Ctx>::NAME);
+    api.patch_status(name, &pp, &patch).await.map(|_| ())
+}
+
+fn build_cron_job(data: &ClusterBackupCrd, namespace: &str, name: &str) -> CronJob {
+    let spec = &data.spec;
+    let labels: BTreeMap<_, _> = [
+        (
+            ClusterBackupCrd::LABEL_BACKUP_NAME.to_string(),
+            data.name_any(),
+        ),
+        ("kissService".into(), "true".into()),
+        ("serviceType".into(), "proxy".into()),
+    ]
+    .into();
+
+    let env = |name: &str, value: String| EnvVar {
+        name: name.into(),
+        value: Some(value),
+        ..Default::default()
+    };
+    let env_field = |name: &str, field_path: &str| EnvVar {
+        name: name.into(),
+        value_from: Some(EnvVarSource {
+            field_ref: Some(ObjectFieldSelector {
+                field_path: field_path.into(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let pod = PodSpec {
+        affinity: Some(Affinity {
+            // NOTE: the etcd certificates are placed on the control-plane nodes
+            node_affinity: Some(NodeAffinity {
+                required_during_scheduling_ignored_during_execution: Some(NodeSelector {
+                    node_selector_terms: vec![NodeSelectorTerm {
+                        match_expressions: Some(vec![NodeSelectorRequirement {
+                            key: "node-role.kubernetes.io/kiss".into(),
+                            operator: "In".into(),
+                            values: Some(vec!["ControlPlane".into()]),
+                        }]),
+                        ..Default::default()
+                    }],
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }),
+        containers: vec![Container {
+            name: "backup".into(),
+            image: Some("quay.io/ulagbulag/openark-snapshot-backup:latest".into()),
+            image_pull_policy: Some("Always".into()),
+            env: Some(vec![
+                env("BACKUP_BUCKET", spec.bucket.name.clone()),
+                env("BACKUP_ENDPOINT", spec.bucket.endpoint.clone()),
+                env("BACKUP_ETCD", spec.etcd.to_string()),
+                env("BACKUP_GROUPS", spec.groups.join(",")),
+                env(
+                    "BACKUP_PREFIX",
+                    spec.bucket
+                        .prefix
+                        .clone()
+                        .unwrap_or_else(|| spec.cluster_name.clone()),
+                ),
+                env("BACKUP_RETENTION", spec.retention.to_string()),
+                env("ETCD_CERTS_DIR", "/etc/ssl/etcd/ssl".into()),
+                env_field("NODE_IP", "status.hostIP"),
+                env_field("NODE_NAME", "spec.nodeName"),
+            ]),
+            env_from: Some(vec![EnvFromSource {
+                secret_ref: Some(SecretEnvSource {
+                    name: spec.bucket.secret_name.clone(),
+                    optional: Some(false),
+                }),
+                ..Default::default()
+            }]),
+            volume_mounts: Some(vec![VolumeMount {
+                name: "etcd-certs".into(),
+                mount_path: "/etc/ssl/etcd/ssl".into(),
+                read_only: Some(true),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        }],
+        restart_policy: Some("Never".into()),
+        service_account: Some("kiss-system".into()),
+        volumes: Some(vec![Volume {
+            name: "etcd-certs".into(),
+            host_path: Some(HostPathVolumeSource {
+                path: "/etc/ssl/etcd/ssl".into(),
+                type_: Some("Directory".into()),
+            }),
+            ..Default::default()
+        }]),
+        ..Default::default()
+    };
+
+    CronJob {
+        metadata: ObjectMeta {
+            name: Some(name.into()),
+            namespace: Some(namespace.into()),
+            labels: Some(labels.clone()),
+            // NOTE: the cron job is deleted along with the backup
+            owner_references: data.controller_owner_ref(&()).map(|owner| vec![owner]),
+            ..Default::default()
+        },
+        spec: Some(CronJobSpec {
+            concurrency_policy: Some("Forbid".into()),
+            failed_jobs_history_limit: Some(3),
+            schedule: spec.schedule.clone(),
+            starting_deadline_seconds: Some(180 /* 3m */),
+            successful_jobs_history_limit: Some(3),
+            job_template: JobTemplateSpec {
+                metadata: Some(ObjectMeta {
+                    labels: Some(labels.clone()),
+                    ..Default::default()
+                }),
+                spec: Some(JobSpec {
+                    backoff_limit: Some(2),
+                    template: PodTemplateSpec {
+                        metadata: Some(ObjectMeta {
+                            labels: Some(labels),
+                            ..Default::default()
+                        }),
+                        spec: Some(pod),
+                    },
+                    ..Default::default()
+                }),
+            },
+            ..Default::default()
+        }),
+        status: None,
+    }
+}
@@ -0,0 +1,111 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use ark_core_k8s::manager::Manager;
+use async_trait::async_trait;
+use chrono::Utc;
+use k8s_openapi::api::core::v1::Secret;
+use kiss_api::enrollment::{EnrollmentTokenCrd, EnrollmentTokenStatus};
+use kube::{
+    api::{Patch, PatchParams},
+    core::ObjectMeta,
+    runtime::controller::Action,
+    Api, CustomResourceExt, Error, Resource, ResourceExt,
+};
+use rand::{distributions::Alphanumeric, Rng};
+use serde_json::json;
+use tracing::{info, instrument, Level};
+
+#[derive(Default)]
+pub struct Ctx {}
+
+#[async_trait]
+impl ::ark_core_k8s::manager::Ctx for Ctx {
+    type Data = EnrollmentTokenCrd;
+
+    const NAME: &'static str = crate::consts::NAME;
+    const NAMESPACE: &'static str = ::kiss_api::consts::NAMESPACE;
+    const FALLBACK: Duration = Duration::from_secs(5 * 60); // 5 minutes
+
+    #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any(), namespace = data.namespace()), err(Display))]
+    async fn reconcile(
+        manager: Arc<Manager<Self>>,
+        data: Arc<<Self as ::ark_core_k8s::manager::Ctx>::Data>,
+    ) -> Result<Action, Error>
+    where
+        Self: Sized,
+    {
+        let name = data.name_any();
+        let namespace = <Self as ::ark_core_k8s::manager::Ctx>::NAMESPACE;
+        let secret_name = data.secret_name();
+
+        // skip minting if the token is already minted
+        let api_secret = Api::<Secret>::namespaced(manager.kube.clone(), namespace);
+        let is_minted = data
+            .status
+            .as_ref()
+            .is_some_and(|status| status.secret_hash.is_some())
+            && api_secret.get_opt(&secret_name).await?.is_some();
+        if is_minted {
+            return Ok(Action::requeue(
+                <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+            ));
+        }
+
+        // mint a new token
+        let secret: String = ::rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(32)
+            .map(char::from)
+            .collect();
+        let token = Secret {
+            metadata: ObjectMeta {
+                name: Some(secret_name.clone()),
+                namespace: Some(namespace.into()),
+                // NOTE: the secret is deleted along with the token
+                owner_references: data.controller_owner_ref(&()).map(|owner| vec![owner]),
+                ..Default::default()
+            },
+            string_data: Some(
+                [(
+                    EnrollmentTokenCrd::SECRET_KEY.into(),
+                    format!("{name}.{secret}"),
+                )]
+                .into(),
+            ),
+            type_: Some("Opaque".into()),
+            ..Default::default()
+        };
+        let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME).force();
+        api_secret
+            .patch(&secret_name, &pp, &Patch::Apply(&token))
+            .await?;
+
+        // update the status
+        let api = Api::<<Self as ::ark_core_k8s::manager::Ctx>::Data>::all(manager.kube.clone());
+        let crd = EnrollmentTokenCrd::api_resource();
+        let patch = Patch::Merge(json!({
+            "apiVersion": crd.api_version,
+            "kind": crd.kind,
+            "status": EnrollmentTokenStatus {
+                secret_name: Some(secret_name),
+                secret_hash: Some(EnrollmentTokenCrd::hash_secret(&secret)),
+                boxes: data
+                    .status
+                    .as_ref()
+                    .map(|status| status.boxes.clone())
+                    .unwrap_or_default(),
+                last_updated: Utc::now(),
+            },
+        }));
+        let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME);
+        api.patch_status(&name, &pp, &patch).await?;
+
+        info!("Minted enrollment token {name:?}");
+
+        // If no events were received, check back after a few minutes
+        Ok(Action::requeue(
+            <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+        ))
+    }
+}
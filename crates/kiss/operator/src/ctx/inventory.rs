@@ -0,0 +1,105 @@
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+use anyhow::{anyhow, Result};
+use ark_core_k8s::manager::Manager;
+use async_trait::async_trait;
+use k8s_openapi::api::core::v1::ConfigMap;
+use kiss_api::{group::BoxGroupCrd, inventory::Inventory, r#box::BoxCrd};
+use kube::{
+    api::{ListParams, Patch, PatchParams},
+    core::ObjectMeta,
+    runtime::controller::Action,
+    Api, Client, Error, ResourceExt,
+};
+use tracing::{info, instrument, warn, Level};
+
+#[derive(Default)]
+pub struct Ctx {}
+
+#[async_trait]
+impl ::ark_core_k8s::manager::Ctx for Ctx {
+    type Data = BoxCrd;
+
+    const NAME: &'static str = crate::consts::NAME;
+    const NAMESPACE: &'static str = ::kiss_api::consts::NAMESPACE;
+    // NOTE: the deleted boxes and the box groups are not watched, so the inventory is re-rendered periodically
+    const FALLBACK: Duration = Duration::from_secs(5 * 60); // 5 minutes
+
+    #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any()), err(Display))]
+    async fn reconcile(
+        manager: Arc<Manager<Self>>,
+        data: Arc<<Self as ::ark_core_k8s::manager::Ctx>::Data>,
+    ) -> Result<Action, Error>
+    where
+        Self: Sized,
+    {
+        match Self::render(&manager.kube).await {
+            Ok(true) => info!("Rendered inventory from the box {:?}", data.name_any()),
+            Ok(false) => (),
+            Err(e) => warn!("failed to render inventory: {e}"),
+        }
+
+        // If no events were received, check back after a few minutes
+        Ok(Action::requeue(
+            <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+        ))
+    }
+}
+
+impl Ctx {
+    /// Render the inventory into the config map, and return `true` if it is changed.
+    ///
+    /// The config map is exported to the Git repository by the `snapshot-git-inventory` service.
+    #[instrument(level = Level::INFO, skip(kube), err(Display))]
+    async fn render(kube: &Client) -> Result<bool> {
+        let lp = ListParams::default();
+        let boxes: Vec<_> = Api::<BoxCrd>::all(kube.clone())
+            .list(&lp)
+            .await?
+            .items
+            .into_iter()
+            .filter(|r#box| r#box.metadata.deletion_timestamp.is_none())
+            .collect();
+        let groups = Api::<BoxGroupCrd>::all(kube.clone()).list(&lp).await?.items;
+
+        let inventory = Inventory::new(&boxes, &groups);
+        let data = BTreeMap::from([
+            (
+                Inventory::FILE_NAME_INVENTORY.into(),
+                ::serde_yaml::to_string(&inventory)
+                    .map_err(|error| anyhow!("failed to render inventory: {error}"))?,
+            ),
+            (
+                Inventory::FILE_NAME_HOSTS.into(),
+                ::serde_yaml::to_string(&inventory.to_hosts())
+                    .map_err(|error| anyhow!("failed to render Ansible hosts: {error}"))?,
+            ),
+        ]);
+
+        let namespace = <Self as ::ark_core_k8s::manager::Ctx>::NAMESPACE;
+        let api = Api::<ConfigMap>::namespaced(kube.clone(), namespace);
+        let name = Inventory::CONFIG_MAP_NAME;
+        if api
+            .get_opt(name)
+            .await?
+            .and_then(|config_map| config_map.data)
+            .as_ref()
+            == Some(&data)
+        {
+            return Ok(false);
+        }
+
+        let config_map = ConfigMap {
+            metadata: ObjectMeta {
+                name: Some(name.into()),
+                namespace: Some(namespace.into()),
+                ..Default::default()
+            },
+            data: Some(data),
+            ..Default::default()
+        };
+        let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME).force();
+        api.patch(name, &pp, &Patch::Apply(&config_map)).await?;
+        Ok(true)
+    }
+}
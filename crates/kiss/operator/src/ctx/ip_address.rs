@@ -0,0 +1,131 @@
+use std::{net::IpAddr, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use ark_core_k8s::manager::Manager;
+use async_trait::async_trait;
+use chrono::Utc;
+use kiss_ansible::AnsibleClient;
+use kiss_api::{
+    ipam::{IpAddressCrd, IpAddressState, IpAddressStatus},
+    r#box::BoxCrd,
+};
+use kube::{
+    api::{ListParams, Patch, PatchParams},
+    runtime::controller::Action,
+    Api, CustomResourceExt, Error, ResourceExt,
+};
+use serde_json::json;
+use tracing::{info, instrument, warn, Level};
+
+#[derive(Default)]
+pub struct Ctx {}
+
+#[async_trait]
+impl ::ark_core_k8s::manager::Ctx for Ctx {
+    type Data = IpAddressCrd;
+
+    const NAME: &'static str = crate::consts::NAME;
+    const NAMESPACE: &'static str = ::kiss_api::consts::NAMESPACE;
+    const FALLBACK: Duration = Duration::from_secs(5 * 60); // 5 minutes
+
+    #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any(), namespace = data.namespace()), err(Display))]
+    async fn reconcile(
+        manager: Arc<Manager<Self>>,
+        data: Arc<<Self as ::ark_core_k8s::manager::Ctx>::Data>,
+    ) -> Result<Action, Error>
+    where
+        Self: Sized,
+    {
+        let name = data.name_any();
+        let network = &data.spec.network;
+        let address = data.spec.address;
+
+        // load kiss config
+        let ansible = match AnsibleClient::try_default(&manager.kube).await {
+            Ok(ansible) => ansible,
+            Err(e) => {
+                warn!("failed to create AnsibleClient: {e}");
+                return Ok(Action::requeue(
+                    <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+                ));
+            }
+        };
+
+        // validate the address
+        match ansible
+            .kiss
+            .networks
+            .iter()
+            .find(|config| &config.name == network)
+        {
+            Some(config) if config.subnet.contains(&address) => {}
+            Some(config) => {
+                warn!(
+                    "address {address} is out of the subnet of the network {network} ({subnet}): {name:?}",
+                    subnet = config.subnet,
+                );
+                return Ok(Action::await_change());
+            }
+            None => {
+                warn!("no such network {network}: {name:?}");
+                return Ok(Action::await_change());
+            }
+        }
+
+        // find the box which holds the address, if not recorded yet
+        let lease = match data.lease() {
+            Some(lease) => Some(lease),
+            None => {
+                let api = Api::<BoxCrd>::all(manager.kube.clone());
+                let lp = ListParams::default();
+                api.list(&lp)
+                    .await?
+                    .items
+                    .into_iter()
+                    .find(|r#box| {
+                        r#box.status.as_ref().is_some_and(|status| {
+                            status.networks.iter().any(|assigned| {
+                                &assigned.name == network
+                                    && assigned.address == Some(IpAddr::V4(address))
+                            })
+                        })
+                    })
+                    .map(|r#box| r#box.spec.machine.uuid)
+            }
+        };
+
+        // detect the conflicts
+        let state = data.state(lease);
+        if let (IpAddressState::Conflicted, Some(reservation), Some(lease)) =
+            (state, data.spec.reservation, lease)
+        {
+            warn!("address {address} is reserved for {reservation}, but already leased by {lease}: {name:?}");
+        }
+
+        // update the status
+        let status = data.status.as_ref();
+        if !status.is_some_and(|status| status.state == state && status.lease == lease) {
+            let api =
+                Api::<<Self as ::ark_core_k8s::manager::Ctx>::Data>::all(manager.kube.clone());
+            let crd = IpAddressCrd::api_resource();
+            let patch = Patch::Merge(json!({
+                "apiVersion": crd.api_version,
+                "kind": crd.kind,
+                "status": IpAddressStatus {
+                    state,
+                    lease,
+                    last_updated: Utc::now(),
+                },
+            }));
+            let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME);
+            api.patch_status(&name, &pp, &patch).await?;
+
+            info!("Updated address {address} ({network}): {state}");
+        }
+
+        // If no events were received, check back after a few minutes
+        Ok(Action::requeue(
+            <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+        ))
+    }
+}
@@ -0,0 +1,7 @@
+pub(crate) mod boot_profile;
+pub(crate) mod r#box;
+pub(crate) mod cluster_backup;
+pub(crate) mod enrollment_token;
+pub(crate) mod inventory;
+pub(crate) mod ip_address;
+pub(crate) mod network_policy_profile;
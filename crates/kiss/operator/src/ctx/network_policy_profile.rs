@@ -0,0 +1,136 @@
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use ark_core_k8s::manager::Manager;
+use async_trait::async_trait;
+use chrono::Utc;
+use kiss_api::{
+    group::BoxGroupCrd,
+    network_policy::{NetworkPolicyProfileCrd, NetworkPolicyProfileStatus},
+    r#box::{BoxCrd, BoxState},
+};
+use kube::{
+    api::{ListParams, Patch, PatchParams},
+    runtime::controller::Action,
+    Api, CustomResourceExt, Error, ResourceExt,
+};
+use serde_json::json;
+use tracing::{info, instrument, warn, Level};
+
+#[derive(Default)]
+pub struct Ctx {}
+
+#[async_trait]
+impl ::ark_core_k8s::manager::Ctx for Ctx {
+    type Data = NetworkPolicyProfileCrd;
+
+    const NAME: &'static str = crate::consts::NAME;
+    const NAMESPACE: &'static str = ::kiss_api::consts::NAMESPACE;
+    // NOTE: the boxes are checked by the hourly maintenance task
+    const FALLBACK: Duration = Duration::from_secs(5 * 60); // 5 minutes
+
+    #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any(), namespace = data.namespace()), err(Display))]
+    async fn reconcile(
+        manager: Arc<Manager<Self>>,
+        data: Arc<<Self as ::ark_core_k8s::manager::Ctx>::Data>,
+    ) -> Result<Action, Error>
+    where
+        Self: Sized,
+    {
+        let name = data.name_any();
+
+        let (digest, message) = match data.spec.digest() {
+            Ok(digest) => (Some(digest), None),
+            Err(error) => {
+                warn!("failed to render the network policy profile ({name}): {error}");
+                (None, Some(error))
+            }
+        };
+
+        // collect the boxes of the groups which refer to the profile
+        let groups: Vec<_> = {
+            let api = Api::<BoxGroupCrd>::all(manager.kube.clone());
+            let lp = ListParams::default();
+            api.list(&lp)
+                .await?
+                .items
+                .into_iter()
+                .filter(|group| group.spec.network_policy.as_ref() == Some(&name))
+                .collect()
+        };
+        let boxes: Vec<_> = {
+            let api = Api::<BoxCrd>::all(manager.kube.clone());
+            let lp = ListParams::default();
+            api.list(&lp)
+                .await?
+                .items
+                .into_iter()
+                .filter(|r#box| groups.iter().any(|group| group.contains(r#box)))
+                // NOTE: only the running boxes are checked by the maintenance task
+                .filter(|r#box| {
+                    r#box
+                        .status
+                        .as_ref()
+                        .is_some_and(|status| matches!(status.state, BoxState::Running))
+                })
+                .collect()
+        };
+
+        let mut compliant = 0;
+        let mut non_compliant_boxes = BTreeMap::default();
+        for r#box in &boxes {
+            let status = r#box
+                .status
+                .as_ref()
+                .and_then(|status| status.network_policy.as_ref());
+            let result = match (status, digest.as_deref()) {
+                (Some(status), Some(digest)) => status.verify(&name, digest),
+                (Some(_), None) => Err("invalid profile".into()),
+                (None, _) => Err("not checked yet".into()),
+            };
+            match result {
+                Ok(()) => compliant += 1,
+                Err(reason) => {
+                    non_compliant_boxes.insert(r#box.name_any(), reason);
+                }
+            }
+        }
+
+        let status = NetworkPolicyProfileStatus {
+            compliant,
+            non_compliant: non_compliant_boxes.len() as u32,
+            non_compliant_boxes,
+            message,
+            last_updated: Utc::now(),
+        };
+
+        // update the status only if changed
+        let is_changed = data.status.as_ref().map_or(true, |last| {
+            last.compliant != status.compliant
+                || last.non_compliant_boxes != status.non_compliant_boxes
+                || last.message != status.message
+        });
+        if is_changed {
+            let api =
+                Api::<<Self as ::ark_core_k8s::manager::Ctx>::Data>::all(manager.kube.clone());
+            let crd = NetworkPolicyProfileCrd::api_resource();
+            let patch = Patch::Merge(json!({
+                "apiVersion": crd.api_version,
+                "kind": crd.kind,
+                "status": status,
+            }));
+            let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME);
+            api.patch_status(&name, &pp, &patch).await?;
+
+            info!(
+                "Updated network policy profile {name:?}: {} compliant, {} non-compliant",
+                status.compliant, status.non_compliant,
+            );
+        }
+
+        // If no events were received, check back after a few minutes
+        Ok(Action::requeue(
+            <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+        ))
+    }
+}
@@ -1,6 +1,7 @@
 mod ctx;
 
 use ark_core_k8s::manager::Ctx;
+use tokio::join;
 
 pub(crate) mod consts {
     pub const NAME: &str = "kiss-operator";
@@ -8,5 +9,13 @@ pub(crate) mod consts {
 
 #[tokio::main]
 async fn main() {
-    self::ctx::Ctx::spawn_crd().await
+    join!(
+        self::ctx::boot_profile::Ctx::spawn_crd(),
+        self::ctx::r#box::Ctx::spawn_crd(),
+        self::ctx::cluster_backup::Ctx::spawn_crd(),
+        self::ctx::enrollment_token::Ctx::spawn_crd(),
+        self::ctx::inventory::Ctx::spawn(),
+        self::ctx::ip_address::Ctx::spawn_crd(),
+        self::ctx::network_policy_profile::Ctx::spawn_crd(),
+    );
 }
@@ -0,0 +1,499 @@
+#[cfg(feature = "df-polars")]
+mod polars;
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    sync::{LazyLock, Mutex},
+};
+
+use anyhow::{anyhow, Result};
+use ark_core_k8s::data::Url;
+use chrono::{DateTime, TimeDelta, Utc};
+use k8s_openapi::api::core::v1::ObjectReference;
+use kube::{
+    runtime::events::{Event, EventType, Recorder, Reporter},
+    Client, CustomResource, CustomResourceExt,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::{
+    frame::LazyFrame,
+    graph::{GraphData, GraphMetadataPinnedExt, GraphScope},
+    resource::NetworkResource,
+};
+
+/// The states of the alert rules, which are shared by the optimization loop.
+pub static ALERT_MANAGER: LazyLock<NetworkAlertManager> =
+    LazyLock::new(NetworkAlertManager::default);
+
+/// Watches a column of the graphs, and fires when the condition holds for a while.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, CustomResource)]
+#[kube(
+    group = "kubegraph.ulagbulag.io",
+    version = "v1alpha1",
+    kind = "NetworkAlertRule",
+    root = "NetworkAlertRuleCrd",
+    shortname = "nar",
+    namespaced,
+    printcolumn = r#"{
+        "name": "target",
+        "type": "string",
+        "description": "watched entities",
+        "jsonPath": ".spec.target"
+    }"#,
+    printcolumn = r#"{
+        "name": "column",
+        "type": "string",
+        "description": "watched column",
+        "jsonPath": ".spec.condition.column"
+    }"#,
+    printcolumn = r#"{
+        "name": "created-at",
+        "type": "date",
+        "description": "created time",
+        "jsonPath": ".metadata.creationTimestamp"
+    }"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkAlertRuleSpec {
+    /// The problems in the same namespace whose graphs are watched; all problems if empty
+    #[serde(default)]
+    pub problems: BTreeSet<String>,
+
+    #[serde(default)]
+    pub target: NetworkAlertTarget,
+
+    pub condition: NetworkAlertCondition,
+
+    /// How long the condition should hold before firing, in seconds
+    #[serde(default)]
+    pub for_seconds: u64,
+
+    /// The endpoint to be notified of the alerts, in addition to the kubernetes events
+    #[serde(default)]
+    pub webhook: Option<NetworkAlertWebhookSpec>,
+}
+
+impl NetworkResource for NetworkAlertRuleCrd {
+    type Filter = ();
+
+    fn description(&self) -> String {
+        <Self as NetworkResource>::type_name().into()
+    }
+
+    fn type_name() -> &'static str
+    where
+        Self: Sized,
+    {
+        <Self as CustomResourceExt>::crd_name()
+    }
+}
+
+impl NetworkAlertRuleSpec {
+    /// Return whether the rule watches the problem in the same namespace.
+    pub fn matches(&self, problem: &GraphScope) -> bool {
+        self.problems.is_empty() || self.problems.contains(&problem.name)
+    }
+
+    /// Return the watched entities meeting the condition, with their values.
+    pub fn evaluate<M>(
+        &self,
+        graph: &GraphData<LazyFrame>,
+        metadata: &M,
+    ) -> Result<BTreeMap<String, f64>>
+    where
+        M: GraphMetadataPinnedExt,
+    {
+        let frame = match self.target {
+            NetworkAlertTarget::Edges => graph.edges.clone(),
+            NetworkAlertTarget::Nodes => graph.nodes.clone(),
+        };
+
+        match frame {
+            LazyFrame::Empty => Ok(BTreeMap::default()),
+            #[cfg(not(feature = "df-polars"))]
+            LazyFrame::Native(_) => {
+                ::anyhow::bail!("alert rules are not supported by the native dataframe backend")
+            }
+            #[cfg(feature = "df-polars")]
+            LazyFrame::Polars(frame) => self::polars::evaluate(self, frame, metadata),
+        }
+    }
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum NetworkAlertTarget {
+    /// The edges are named after their endpoints, i.e. `<src> -> <sink>`
+    #[default]
+    Edges,
+    Nodes,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkAlertCondition {
+    /// The column to be watched, e.g. `capacity`
+    pub column: String,
+
+    /// The column subtracted from the watched one, e.g. `flow` to watch the available capacity
+    #[serde(default)]
+    pub minus: Option<String>,
+
+    pub operator: NetworkAlertOperator,
+
+    pub threshold: f64,
+}
+
+impl fmt::Display for NetworkAlertCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            column,
+            minus,
+            operator,
+            threshold,
+        } = self;
+
+        match minus {
+            Some(minus) => write!(f, "{column} - {minus} {operator} {threshold}"),
+            None => write!(f, "{column} {operator} {threshold}"),
+        }
+    }
+}
+
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+pub enum NetworkAlertOperator {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl fmt::Display for NetworkAlertOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lt => "<".fmt(f),
+            Self::Le => "<=".fmt(f),
+            Self::Gt => ">".fmt(f),
+            Self::Ge => ">=".fmt(f),
+        }
+    }
+}
+
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkAlertWebhookSpec {
+    pub endpoint: Url,
+}
+
+/// Tracks how long the conditions of the rules hold per problem and entity,
+/// and fires or resolves the alerts on their transitions.
+#[derive(Default)]
+pub struct NetworkAlertManager {
+    states: Mutex<BTreeMap<(GraphScope, GraphScope), AlertState>>,
+}
+
+impl NetworkAlertManager {
+    /// Drop the states of the rules or problems which are removed.
+    pub fn retain(
+        &self,
+        rules: &BTreeSet<GraphScope>,
+        problems: &BTreeSet<&GraphScope>,
+    ) -> Result<()> {
+        self.lock()?
+            .retain(|(rule, problem), _| rules.contains(rule) && problems.contains(problem));
+        Ok(())
+    }
+
+    pub fn observe(
+        &self,
+        rule: &GraphScope,
+        problem: &GraphScope,
+        spec: &NetworkAlertRuleSpec,
+        breaches: BTreeMap<String, f64>,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<NetworkAlert>> {
+        let mut states = self.lock()?;
+        let state = states.entry((rule.clone(), problem.clone())).or_default();
+        Ok(state.transit(spec.for_seconds, breaches, now))
+    }
+
+    fn lock(
+        &self,
+    ) -> Result<::std::sync::MutexGuard<'_, BTreeMap<(GraphScope, GraphScope), AlertState>>> {
+        self.states
+            .lock()
+            .map_err(|error| anyhow!("failed to lock alert states: {error}"))
+    }
+}
+
+#[derive(Default)]
+struct AlertState {
+    targets: BTreeMap<String, AlertTargetState>,
+}
+
+impl AlertState {
+    fn transit(
+        &mut self,
+        for_seconds: u64,
+        breaches: BTreeMap<String, f64>,
+        now: DateTime<Utc>,
+    ) -> Vec<NetworkAlert> {
+        let duration = TimeDelta::seconds(for_seconds.try_into().unwrap_or(i64::MAX));
+        let mut alerts = Vec::default();
+
+        // NOTE: the alerts are resolved as soon as the condition does not hold
+        self.targets.retain(|target, state| {
+            let is_breached = breaches.contains_key(target);
+            if !is_breached && state.is_firing {
+                alerts.push(NetworkAlert {
+                    target: target.clone(),
+                    state: NetworkAlertState::Resolved,
+                    value: None,
+                    since: state.since,
+                });
+            }
+            is_breached
+        });
+
+        for (target, value) in breaches {
+            let state = self
+                .targets
+                .entry(target.clone())
+                .or_insert(AlertTargetState {
+                    is_firing: false,
+                    since: now,
+                });
+            if !state.is_firing && now - state.since >= duration {
+                state.is_firing = true;
+                alerts.push(NetworkAlert {
+                    target: target.clone(),
+                    state: NetworkAlertState::Firing,
+                    value: Some(value),
+                    since: state.since,
+                });
+            }
+        }
+        alerts
+    }
+}
+
+struct AlertTargetState {
+    is_firing: bool,
+    since: DateTime<Utc>,
+}
+
+/// A transition of the alert of an entity.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkAlert {
+    pub target: String,
+    pub state: NetworkAlertState,
+    /// The value of the watched column, if firing
+    #[serde(default)]
+    pub value: Option<f64>,
+    /// When the condition has started to hold
+    pub since: DateTime<Utc>,
+}
+
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+pub enum NetworkAlertState {
+    Firing,
+    Resolved,
+}
+
+/// The payload of the webhook notifications.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkAlertNotification {
+    pub rule: GraphScope,
+    pub problem: String,
+    pub condition: NetworkAlertCondition,
+    #[serde(flatten)]
+    pub alert: NetworkAlert,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl NetworkAlert {
+    /// Report the alert to the rule as a kubernetes event, and notify the webhook if given.
+    pub async fn report(
+        self,
+        kube: &Client,
+        rule: &GraphScope,
+        spec: &NetworkAlertRuleSpec,
+        problem: &GraphScope,
+    ) -> Result<()> {
+        let condition = &spec.condition;
+        let target = &self.target;
+        let event = match self.state {
+            NetworkAlertState::Firing => {
+                let value = self.value.unwrap_or_default();
+                warn!("The alert is firing: {rule}: {problem}: {target} ({condition}: {value})");
+                Event {
+                    type_: EventType::Warning,
+                    reason: "AlertFiring".into(),
+                    note: Some(format!(
+                        "{name}: {target}: {condition} for {for_seconds}s (value: {value})",
+                        name = &problem.name,
+                        for_seconds = spec.for_seconds,
+                    )),
+                    action: "FireAlert".into(),
+                    secondary: None,
+                }
+            }
+            NetworkAlertState::Resolved => {
+                info!("The alert is resolved: {rule}: {problem}: {target}");
+                Event {
+                    type_: EventType::Normal,
+                    reason: "AlertResolved".into(),
+                    note: Some(format!(
+                        "{name}: {target}: {condition} does not hold anymore",
+                        name = &problem.name,
+                    )),
+                    action: "ResolveAlert".into(),
+                    secondary: None,
+                }
+            }
+        };
+        publish_rule_event(kube, rule, &event)
+            .await
+            .map_err(|error| anyhow!("failed to report alert: {error}"))?;
+
+        match &spec.webhook {
+            Some(webhook) => {
+                let notification = NetworkAlertNotification {
+                    rule: rule.clone(),
+                    problem: problem.name.clone(),
+                    condition: condition.clone(),
+                    alert: self,
+                    timestamp: Utc::now(),
+                };
+                notify_webhook(webhook, &notification).await
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+async fn publish_rule_event(
+    kube: &Client,
+    rule: &GraphScope,
+    event: &Event,
+) -> Result<(), ::kube::Error> {
+    let reporter = Reporter {
+        controller: "kubegraph-alert".into(),
+        instance: None,
+    };
+    let recorder = Recorder::new(kube.clone(), reporter);
+
+    let api_resource = NetworkAlertRuleCrd::api_resource();
+    let reference = ObjectReference {
+        api_version: Some(api_resource.api_version),
+        kind: Some(api_resource.kind),
+        name: Some(rule.name.clone()),
+        namespace: Some(rule.namespace.clone()),
+        ..Default::default()
+    };
+    recorder.publish(event, &reference).await
+}
+
+#[cfg(feature = "alert-webhook")]
+async fn notify_webhook(
+    webhook: &NetworkAlertWebhookSpec,
+    notification: &NetworkAlertNotification,
+) -> Result<()> {
+    ::reqwest::Client::new()
+        .post(webhook.endpoint.0.clone())
+        .json(notification)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map(|_| ())
+        .map_err(|error| anyhow!("failed to notify alert webhook: {error}"))
+}
+
+#[cfg(not(feature = "alert-webhook"))]
+async fn notify_webhook(
+    webhook: &NetworkAlertWebhookSpec,
+    _: &NetworkAlertNotification,
+) -> Result<()> {
+    let endpoint = &webhook.endpoint;
+    ::anyhow::bail!("alert webhooks are not enabled; skipping notification: {endpoint}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alert_fire_after_duration_and_resolve() {
+        let mut state = AlertState::default();
+        let start = Utc::now();
+        let after = |seconds| start + TimeDelta::seconds(seconds);
+        let breaches = |targets: &[&str]| {
+            targets
+                .iter()
+                .map(|target| ((*target).into(), 1.0))
+                .collect::<BTreeMap<String, _>>()
+        };
+
+        // pending
+        assert_eq!(state.transit(300, breaches(&["a -> b"]), start), vec![]);
+        assert_eq!(
+            state.transit(300, breaches(&["a -> b"]), after(200)),
+            vec![]
+        );
+
+        // firing only once
+        let alerts = state.transit(300, breaches(&["a -> b"]), after(300));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].state, NetworkAlertState::Firing);
+        assert_eq!(alerts[0].since, start);
+        assert_eq!(
+            state.transit(300, breaches(&["a -> b"]), after(400)),
+            vec![]
+        );
+
+        // resolved
+        let alerts = state.transit(300, breaches(&[]), after(500));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].state, NetworkAlertState::Resolved);
+
+        // the pending alerts are reset silently
+        assert_eq!(
+            state.transit(300, breaches(&["a -> b"]), after(600)),
+            vec![]
+        );
+        assert_eq!(state.transit(300, breaches(&[]), after(700)), vec![]);
+        assert_eq!(
+            state.transit(300, breaches(&["a -> b"]), after(800)),
+            vec![]
+        );
+        assert_eq!(
+            state.transit(300, breaches(&["a -> b"]), after(1000)),
+            vec![]
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use pl::{datatypes::DataType, lazy::dsl, lazy::frame::LazyFrame};
+
+use crate::{frame::polars::get_column, graph::GraphMetadataPinnedExt};
+
+use super::{
+    NetworkAlertCondition, NetworkAlertOperator, NetworkAlertRuleSpec, NetworkAlertTarget,
+};
+
+const KEY_VALUE: &str = "alert.value";
+
+pub(super) fn evaluate<M>(
+    spec: &NetworkAlertRuleSpec,
+    frame: LazyFrame,
+    metadata: &M,
+) -> Result<BTreeMap<String, f64>>
+where
+    M: GraphMetadataPinnedExt,
+{
+    let NetworkAlertCondition {
+        column,
+        minus,
+        operator,
+        threshold,
+    } = &spec.condition;
+
+    let value = dsl::col(column.as_str()).cast(DataType::Float64);
+    let value = match minus {
+        Some(minus) => value - dsl::col(minus.as_str()).cast(DataType::Float64),
+        None => value,
+    };
+    let threshold = dsl::lit(*threshold);
+    let filter = match operator {
+        NetworkAlertOperator::Lt => dsl::col(KEY_VALUE).lt(threshold),
+        NetworkAlertOperator::Le => dsl::col(KEY_VALUE).lt_eq(threshold),
+        NetworkAlertOperator::Gt => dsl::col(KEY_VALUE).gt(threshold),
+        NetworkAlertOperator::Ge => dsl::col(KEY_VALUE).gt_eq(threshold),
+    };
+
+    match spec.target {
+        NetworkAlertTarget::Edges => {
+            let key_sink = metadata.sink();
+            let key_src = metadata.src();
+
+            let edges = frame
+                .select([
+                    dsl::col(key_src),
+                    dsl::col(key_sink),
+                    value.alias(KEY_VALUE),
+                ])
+                .filter(filter)
+                .collect()
+                .map_err(|error| anyhow!("failed to evaluate edge alerts: {error}"))?;
+
+            let src = get_column(&edges, "edge", "src", key_src, Some(&DataType::String))?;
+            let sink = get_column(&edges, "edge", "sink", key_sink, Some(&DataType::String))?;
+            let value = get_column(&edges, "edge", "alert", KEY_VALUE, None)?;
+
+            Ok(src
+                .str()?
+                .into_iter()
+                .zip(sink.str()?)
+                .zip(value.f64()?)
+                .filter_map(|((src, sink), value)| Some((format!("{} -> {}", src?, sink?), value?)))
+                .collect())
+        }
+        NetworkAlertTarget::Nodes => {
+            let key_name = metadata.name();
+
+            let nodes = frame
+                .select([dsl::col(key_name), value.alias(KEY_VALUE)])
+                .filter(filter)
+                .collect()
+                .map_err(|error| anyhow!("failed to evaluate node alerts: {error}"))?;
+
+            let name = get_column(&nodes, "node", "name", key_name, Some(&DataType::String))?;
+            let value = get_column(&nodes, "node", "alert", KEY_VALUE, None)?;
+
+            Ok(name
+                .str()?
+                .into_iter()
+                .zip(value.f64()?)
+                .filter_map(|(name, value)| Some((name?.into(), value?)))
+                .collect())
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::VecDeque;
+
+/// Count the incident edges of each node, normalized by the maximum possible degree.
+pub(super) fn degree_centrality(len: usize, edges: &[(usize, usize)]) -> Vec<f64> {
+    let mut degrees = vec![0usize; len];
+    for &(src, sink) in edges {
+        degrees[src] += 1;
+        degrees[sink] += 1;
+    }
+
+    let scale = if len > 1 { 1.0 / (len - 1) as f64 } else { 0.0 };
+    degrees
+        .into_iter()
+        .map(|degree| degree as f64 * scale)
+        .collect()
+}
+
+/// Compute the directed betweenness centrality of each node with Brandes' algorithm.
+pub(super) fn betweenness_centrality(len: usize, edges: &[(usize, usize)]) -> Vec<f64> {
+    let mut adjacency = vec![Vec::default(); len];
+    for &(src, sink) in edges {
+        if src != sink {
+            adjacency[src].push(sink);
+        }
+    }
+
+    let mut centrality = vec![0.0; len];
+    for source in 0..len {
+        let mut stack = Vec::with_capacity(len);
+        let mut predecessors = vec![Vec::default(); len];
+        let mut paths = vec![0.0f64; len];
+        let mut distances = vec![None; len];
+        paths[source] = 1.0;
+        distances[source] = Some(0usize);
+
+        // Step 1. Count the shortest paths from the source
+        let mut queue = VecDeque::from([source]);
+        while let Some(node) = queue.pop_front() {
+            stack.push(node);
+            let distance = distances[node].unwrap_or_default() + 1;
+            for &next in &adjacency[node] {
+                if distances[next].is_none() {
+                    distances[next] = Some(distance);
+                    queue.push_back(next);
+                }
+                if distances[next] == Some(distance) {
+                    paths[next] += paths[node];
+                    predecessors[next].push(node);
+                }
+            }
+        }
+
+        // Step 2. Accumulate the dependencies in the reverse order
+        let mut dependencies = vec![0.0; len];
+        while let Some(node) = stack.pop() {
+            for &prev in &predecessors[node] {
+                dependencies[prev] += paths[prev] / paths[node] * (1.0 + dependencies[node]);
+            }
+            if node != source {
+                centrality[node] += dependencies[node];
+            }
+        }
+    }
+
+    let scale = if len > 2 {
+        1.0 / ((len - 1) * (len - 2)) as f64
+    } else {
+        0.0
+    };
+    centrality.into_iter().map(|value| value * scale).collect()
+}
+
+/// Label the weakly connected components, in the order of their first nodes.
+pub(super) fn connected_components(len: usize, edges: &[(usize, usize)]) -> Vec<u32> {
+    fn find(parents: &mut [usize], mut node: usize) -> usize {
+        while parents[node] != node {
+            parents[node] = parents[parents[node]];
+            node = parents[node];
+        }
+        node
+    }
+
+    let mut parents: Vec<_> = (0..len).collect();
+    for &(src, sink) in edges {
+        let src = find(&mut parents, src);
+        let sink = find(&mut parents, sink);
+        if src != sink {
+            parents[src.max(sink)] = src.min(sink);
+        }
+    }
+
+    let mut labels = vec![None; len];
+    let mut num_components = 0;
+    (0..len)
+        .map(|node| {
+            let root = find(&mut parents, node);
+            *labels[root].get_or_insert_with(|| {
+                num_components += 1;
+                num_components - 1
+            })
+        })
+        .collect()
+}
+
+/// Mark the bridges, whose removal increases the number of connected components.
+pub(super) fn bridges(len: usize, edges: &[(usize, usize)]) -> Vec<bool> {
+    let mut adjacency = vec![Vec::default(); len];
+    for (id, &(src, sink)) in edges.iter().enumerate() {
+        if src != sink {
+            adjacency[src].push((sink, id));
+            adjacency[sink].push((src, id));
+        }
+    }
+
+    let mut discovered = vec![None; len];
+    let mut lows = vec![0usize; len];
+    let mut timer = 0;
+    let mut is_bridge = vec![false; edges.len()];
+
+    for root in 0..len {
+        if discovered[root].is_some() {
+            continue;
+        }
+        discovered[root] = Some(timer);
+        lows[root] = timer;
+        timer += 1;
+
+        // (node, parent edge, next adjacency index)
+        let mut stack = vec![(root, None, 0)];
+        while let Some(top) = stack.last_mut() {
+            let (node, parent_edge) = (top.0, top.1);
+            match adjacency[node].get(top.2).copied() {
+                Some((next, id)) => {
+                    top.2 += 1;
+                    if Some(id) == parent_edge {
+                        continue;
+                    }
+                    match discovered[next] {
+                        Some(time) => lows[node] = lows[node].min(time),
+                        None => {
+                            discovered[next] = Some(timer);
+                            lows[next] = timer;
+                            timer += 1;
+                            stack.push((next, Some(id), 0));
+                        }
+                    }
+                }
+                None => {
+                    stack.pop();
+                    if let (Some(&(parent, _, _)), Some(id)) = (stack.last(), parent_edge) {
+                        lows[parent] = lows[parent].min(lows[node]);
+                        if discovered[parent].is_some_and(|time| lows[node] > time) {
+                            is_bridge[id] = true;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    is_bridge
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn degree_centrality_of_star() {
+        let edges = [(0, 1), (0, 2), (0, 3)];
+        let centrality = degree_centrality(4, &edges);
+        assert_eq!(centrality, [1.0, 1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0]);
+    }
+
+    #[test]
+    fn betweenness_centrality_of_path() {
+        let edges = [(0, 1), (1, 2)];
+        let centrality = betweenness_centrality(3, &edges);
+        assert_eq!(centrality, [0.0, 0.5, 0.0]);
+    }
+
+    #[test]
+    fn connected_components_of_forest() {
+        let edges = [(3, 2), (0, 4)];
+        let components = connected_components(5, &edges);
+        assert_eq!(components, [0, 1, 2, 2, 0]);
+    }
+
+    #[test]
+    fn bridges_of_cycle_with_tail() {
+        let edges = [(0, 1), (1, 2), (2, 0), (2, 3)];
+        let is_bridge = bridges(4, &edges);
+        assert_eq!(is_bridge, [false, false, false, true]);
+    }
+}
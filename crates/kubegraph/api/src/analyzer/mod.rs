@@ -0,0 +1,180 @@
+#[cfg(feature = "df-polars")]
+mod algorithm;
+#[cfg(feature = "df-polars")]
+mod polars;
+
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    frame::LazyFrame,
+    graph::{GraphData, GraphMetadataPinned},
+};
+
+/// A graph analyzer appending the derived metrics as extra node/edge columns,
+/// so that the problems can reference them in their cost functions.
+pub trait NetworkAnalyzer {
+    fn analyze(
+        &self,
+        graph: GraphData<LazyFrame>,
+        metadata: &GraphMetadataPinned,
+    ) -> Result<GraphData<LazyFrame>>;
+}
+
+/// An ordered chain of analyzers, where each analyzer can reference the
+/// columns derived by the previous ones.
+#[derive(Default)]
+pub struct NetworkAnalyzerPipeline {
+    analyzers: Vec<Box<dyn Send + Sync + NetworkAnalyzer>>,
+}
+
+impl NetworkAnalyzerPipeline {
+    pub fn push<A>(&mut self, analyzer: A)
+    where
+        A: 'static + Send + Sync + NetworkAnalyzer,
+    {
+        self.analyzers.push(Box::new(analyzer))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.analyzers.is_empty()
+    }
+}
+
+impl FromIterator<NetworkAnalyzerSpec> for NetworkAnalyzerPipeline {
+    fn from_iter<T: IntoIterator<Item = NetworkAnalyzerSpec>>(iter: T) -> Self {
+        let mut pipeline = Self::default();
+        for spec in iter {
+            pipeline.push(spec);
+        }
+        pipeline
+    }
+}
+
+impl NetworkAnalyzer for NetworkAnalyzerPipeline {
+    fn analyze(
+        &self,
+        graph: GraphData<LazyFrame>,
+        metadata: &GraphMetadataPinned,
+    ) -> Result<GraphData<LazyFrame>> {
+        self.analyzers
+            .iter()
+            .try_fold(graph, |graph, analyzer| analyzer.analyze(graph, metadata))
+    }
+}
+
+/// A built-in analyzer.
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum NetworkAnalyzerSpec {
+    /// Append the normalized degree centrality of each node
+    #[serde(rename_all = "camelCase")]
+    DegreeCentrality {
+        #[serde(default = "NetworkAnalyzerSpec::default_key_degree_centrality")]
+        key: String,
+    },
+    /// Append the normalized betweenness centrality of each node
+    #[serde(rename_all = "camelCase")]
+    BetweennessCentrality {
+        #[serde(default = "NetworkAnalyzerSpec::default_key_betweenness_centrality")]
+        key: String,
+    },
+    /// Append the weakly connected component index of each node
+    #[serde(rename_all = "camelCase")]
+    ConnectedComponents {
+        #[serde(default = "NetworkAnalyzerSpec::default_key_connected_components")]
+        key: String,
+    },
+    /// Mark the edges whose removal disconnects the graph
+    #[serde(rename_all = "camelCase")]
+    Bottleneck {
+        #[serde(default = "NetworkAnalyzerSpec::default_key_bottleneck")]
+        key: String,
+    },
+    /// Derive the edge unit costs from the zones and regions of their nodes,
+    /// so that the intra-zone flows are preferred to the inter-zone and inter-region ones.
+    ///
+    /// The edges whose topology is unknown are regarded as inter-region.
+    #[serde(rename_all = "camelCase")]
+    TopologyCost {
+        #[serde(default = "NetworkAnalyzerSpec::default_key_region")]
+        key_region: String,
+        #[serde(default = "NetworkAnalyzerSpec::default_key_zone")]
+        key_zone: String,
+        #[serde(default = "NetworkAnalyzerSpec::default_intra_zone_cost")]
+        intra_zone_cost: u64,
+        #[serde(default = "NetworkAnalyzerSpec::default_inter_zone_cost")]
+        inter_zone_cost: u64,
+        #[serde(default = "NetworkAnalyzerSpec::default_inter_region_cost")]
+        inter_region_cost: u64,
+        /// Whether the given unit costs are overwritten; otherwise only the missing ones are filled
+        #[serde(default)]
+        overwrite: bool,
+    },
+}
+
+impl NetworkAnalyzerSpec {
+    fn default_key_degree_centrality() -> String {
+        "degree_centrality".into()
+    }
+
+    fn default_key_betweenness_centrality() -> String {
+        "betweenness_centrality".into()
+    }
+
+    fn default_key_connected_components() -> String {
+        "component".into()
+    }
+
+    fn default_key_bottleneck() -> String {
+        "bottleneck".into()
+    }
+
+    fn default_key_region() -> String {
+        "topology.kubernetes.io/region".into()
+    }
+
+    fn default_key_zone() -> String {
+        "topology.kubernetes.io/zone".into()
+    }
+
+    const fn default_intra_zone_cost() -> u64 {
+        1
+    }
+
+    const fn default_inter_zone_cost() -> u64 {
+        10
+    }
+
+    const fn default_inter_region_cost() -> u64 {
+        100
+    }
+}
+
+impl NetworkAnalyzer for NetworkAnalyzerSpec {
+    fn analyze(
+        &self,
+        graph: GraphData<LazyFrame>,
+        metadata: &GraphMetadataPinned,
+    ) -> Result<GraphData<LazyFrame>> {
+        match graph {
+            GraphData {
+                edges: _,
+                nodes: LazyFrame::Empty,
+            } => Ok(graph),
+            #[cfg(not(feature = "df-polars"))]
+            GraphData {
+                edges: _,
+                nodes: LazyFrame::Native(_),
+            } => ::anyhow::bail!("analyzers are not supported by the native dataframe backend"),
+            #[cfg(feature = "df-polars")]
+            GraphData {
+                edges,
+                nodes: LazyFrame::Polars(nodes),
+            } => self::polars::analyze(self, edges, nodes, metadata),
+        }
+    }
+}
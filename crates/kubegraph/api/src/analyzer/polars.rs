@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use pl::{
+    datatypes::DataType,
+    frame::DataFrame,
+    lazy::frame::{IntoLazy, LazyFrame},
+    prelude::NamedFrom,
+    series::Series,
+};
+
+use crate::{
+    frame::polars::get_column,
+    graph::{GraphData, GraphMetadataPinned, GraphMetadataPinnedExt},
+};
+
+use super::{algorithm, NetworkAnalyzerSpec};
+
+pub(super) fn analyze(
+    spec: &NetworkAnalyzerSpec,
+    edges: crate::frame::LazyFrame,
+    nodes: LazyFrame,
+    metadata: &GraphMetadataPinned,
+) -> Result<GraphData<crate::frame::LazyFrame>> {
+    let key_name = metadata.name();
+    let key_sink = metadata.sink();
+    let key_src = metadata.src();
+
+    // Step 1. Collect the topology
+    let mut nodes = nodes
+        .collect()
+        .map_err(|error| anyhow!("failed to collect nodes: {error}"))?;
+    let names = get_column(&nodes, "node", "name", key_name, Some(&DataType::String))?;
+    let indices: BTreeMap<_, _> = names
+        .str()?
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, name)| Some((name?.to_string(), index)))
+        .collect();
+
+    let mut edges = match edges {
+        crate::frame::LazyFrame::Empty => None,
+        crate::frame::LazyFrame::Polars(edges) => Some(
+            edges
+                .collect()
+                .map_err(|error| anyhow!("failed to collect edges: {error}"))?,
+        ),
+    };
+    let pairs: Vec<Option<(usize, usize)>> = match &edges {
+        Some(edges) => {
+            let src = get_column(edges, "edge", "src", key_src, Some(&DataType::String))?;
+            let sink = get_column(edges, "edge", "sink", key_sink, Some(&DataType::String))?;
+            src.str()?
+                .into_iter()
+                .zip(sink.str()?)
+                .map(|(src, sink)| Some((*indices.get(src?)?, *indices.get(sink?)?)))
+                .collect()
+        }
+        None => Vec::default(),
+    };
+
+    // Skip the dangling edges, which refer to the unknown nodes
+    let topology: Vec<_> = pairs.iter().flatten().copied().collect();
+    let len = names.len();
+
+    // Step 2. Append the analyzed columns
+    match spec {
+        NetworkAnalyzerSpec::DegreeCentrality { key } => {
+            let values = algorithm::degree_centrality(len, &topology);
+            append_column(&mut nodes, "node", Series::new(key.as_str().into(), values))?;
+        }
+        NetworkAnalyzerSpec::BetweennessCentrality { key } => {
+            let values = algorithm::betweenness_centrality(len, &topology);
+            append_column(&mut nodes, "node", Series::new(key.as_str().into(), values))?;
+        }
+        NetworkAnalyzerSpec::ConnectedComponents { key } => {
+            let values = algorithm::connected_components(len, &topology);
+            append_column(&mut nodes, "node", Series::new(key.as_str().into(), values))?;
+        }
+        NetworkAnalyzerSpec::Bottleneck { key } => {
+            if let Some(edges) = &mut edges {
+                let mut values = algorithm::bridges(len, &topology).into_iter();
+                let values: Vec<_> = pairs
+                    .iter()
+                    .map(|pair| pair.is_some() && values.next().unwrap_or_default())
+                    .collect();
+                append_column(edges, "edge", Series::new(key.as_str().into(), values))?;
+            }
+        }
+        NetworkAnalyzerSpec::TopologyCost {
+            key_region,
+            key_zone,
+            intra_zone_cost,
+            inter_zone_cost,
+            inter_region_cost,
+            overwrite,
+        } => {
+            if let Some(edges) = &mut edges {
+                let key_unit_cost = metadata.unit_cost();
+                let regions = get_labels(&nodes, "region", key_region)?;
+                let zones = get_labels(&nodes, "zone", key_zone)?;
+
+                let is_same = |labels: &[Option<String>], (src, sink): (usize, usize)| {
+                    labels[src].is_some() && labels[src] == labels[sink]
+                };
+                let derived = pairs.iter().map(|pair| match *pair {
+                    Some(pair) if is_same(&zones, pair) => *intra_zone_cost,
+                    Some(pair) if is_same(&regions, pair) => *inter_zone_cost,
+                    _ => *inter_region_cost,
+                } as i64);
+
+                let values: Vec<_> = if !*overwrite && edges.column(key_unit_cost).is_ok() {
+                    let given = get_column(
+                        edges,
+                        "edge",
+                        "unit_cost",
+                        key_unit_cost,
+                        Some(&DataType::Int64),
+                    )?;
+                    given
+                        .i64()?
+                        .into_iter()
+                        .zip(derived)
+                        .map(|(given, derived)| given.unwrap_or(derived))
+                        .collect()
+                } else {
+                    derived.collect()
+                };
+                append_column(edges, "edge", Series::new(key_unit_cost.into(), values))?;
+            }
+        }
+    }
+
+    Ok(GraphData {
+        edges: edges.map(|edges| edges.lazy().into()).unwrap_or_default(),
+        nodes: nodes.lazy().into(),
+    })
+}
+
+/// Load the string labels of the nodes; the missing column is regarded as unknown labels.
+fn get_labels(df: &DataFrame, key: &str, name: &str) -> Result<Vec<Option<String>>> {
+    if df.column(name).is_err() {
+        return Ok(vec![None; df.height()]);
+    }
+    let column = get_column(df, "node", key, name, Some(&DataType::String))?;
+    Ok(column
+        .str()?
+        .into_iter()
+        .map(|label| label.map(Into::into))
+        .collect())
+}
+
+fn append_column(df: &mut DataFrame, kind: &str, column: Series) -> Result<()> {
+    let name = column.name().clone();
+    df.with_column(column)
+        .map(|_| ())
+        .map_err(|error| anyhow!("failed to append {kind} column {name}: {error}"))
+}
@@ -0,0 +1,279 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{LazyLock, Mutex},
+};
+
+use anyhow::{anyhow, Result};
+use kube::{
+    runtime::events::{Event, EventType},
+    Client,
+};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::{
+    frame::LazyFrame,
+    graph::{GraphMetadataPinnedExt, GraphScope},
+    runner::{publish_problem_event, RunnerCircuitBreakerPolicy},
+    simulation::collect_flows,
+};
+
+/// The circuit breakers of the problems, which are shared by the optimization loop.
+pub static CIRCUIT_BREAKER: LazyLock<NetworkCircuitBreaker> =
+    LazyLock::new(NetworkCircuitBreaker::default);
+
+/// Compares the consecutive solutions of each problem, and opens the circuit
+/// to pause the runners when the solutions are anomalous.
+///
+/// The circuit is closed when the solutions are stable again,
+/// or a human resets it with the [`NetworkCircuitBreaker::ANNOTATION_RESET`] annotation.
+#[derive(Default)]
+pub struct NetworkCircuitBreaker {
+    states: Mutex<BTreeMap<GraphScope, CircuitState>>,
+}
+
+impl NetworkCircuitBreaker {
+    /// Change the value of the annotation to reset the circuit of the problem.
+    pub const ANNOTATION_RESET: &'static str = "kubegraph.ulagbulag.io/reset-circuit";
+
+    /// Close the circuit if the reset annotation of the problem has been changed.
+    pub fn reset(&self, scope: &GraphScope, token: Option<&str>) -> Result<()> {
+        let mut states = self.lock()?;
+        let state = states.entry(scope.clone()).or_default();
+        if token.is_some() && state.reset.as_deref() != token {
+            if state.is_open {
+                info!("The circuit is reset: {scope}");
+            }
+            *state = CircuitState::default();
+        }
+        state.reset = token.map(Into::into);
+        Ok(())
+    }
+
+    /// Drop the states of the problems which are removed or do not use the circuit breaker.
+    pub fn retain(&self, scopes: &BTreeSet<&GraphScope>) -> Result<()> {
+        self.lock()?.retain(|scope, _| scopes.contains(scope));
+        Ok(())
+    }
+
+    pub fn observe<M>(
+        &self,
+        scope: &GraphScope,
+        policy: &RunnerCircuitBreakerPolicy,
+        edges: &LazyFrame,
+        metadata: &M,
+    ) -> Result<CircuitDecision>
+    where
+        M: GraphMetadataPinnedExt,
+    {
+        let solution = CircuitSolution {
+            flows: collect_flows(edges.clone(), metadata)?,
+            objective: edges.flow_cost(metadata)?,
+        };
+
+        let mut states = self.lock()?;
+        let state = states.entry(scope.clone()).or_default();
+        let anomaly = state
+            .last
+            .as_ref()
+            .and_then(|last| last.detect(policy, &solution));
+        state.last = Some(solution);
+        Ok(state.transit(policy, anomaly))
+    }
+
+    fn lock(&self) -> Result<::std::sync::MutexGuard<'_, BTreeMap<GraphScope, CircuitState>>> {
+        self.states
+            .lock()
+            .map_err(|error| anyhow!("failed to lock circuit states: {error}"))
+    }
+}
+
+#[derive(Default)]
+struct CircuitState {
+    is_open: bool,
+    last: Option<CircuitSolution>,
+    reset: Option<String>,
+    stable_cycles: u32,
+}
+
+impl CircuitState {
+    fn transit(
+        &mut self,
+        policy: &RunnerCircuitBreakerPolicy,
+        anomaly: Option<CircuitAnomaly>,
+    ) -> CircuitDecision {
+        match (self.is_open, anomaly) {
+            (false, None) => CircuitDecision::Closed,
+            (false, Some(anomaly)) => {
+                self.is_open = true;
+                self.stable_cycles = 0;
+                CircuitDecision::Opened(anomaly)
+            }
+            (true, Some(_)) => {
+                self.stable_cycles = 0;
+                CircuitDecision::Open
+            }
+            (true, None) => {
+                self.stable_cycles += 1;
+                if policy.stable_cycles_to_close > 0
+                    && self.stable_cycles >= policy.stable_cycles_to_close
+                {
+                    self.is_open = false;
+                    self.stable_cycles = 0;
+                    CircuitDecision::Reclosed
+                } else {
+                    CircuitDecision::Open
+                }
+            }
+        }
+    }
+}
+
+struct CircuitSolution {
+    flows: BTreeMap<(String, String), f64>,
+    objective: f64,
+}
+
+impl CircuitSolution {
+    fn detect(&self, policy: &RunnerCircuitBreakerPolicy, next: &Self) -> Option<CircuitAnomaly> {
+        let total = |flows: &BTreeMap<_, f64>| flows.values().map(|flow| flow.abs()).sum::<f64>();
+        let total = total(&self.flows).max(total(&next.flows));
+        if total > 0.0 {
+            let changed: f64 = self
+                .flows
+                .iter()
+                .map(|(key, flow)| (flow - next.flows.get(key).unwrap_or(&0.0)).abs())
+                .chain(
+                    next.flows
+                        .iter()
+                        .filter(|(key, _)| !self.flows.contains_key(key))
+                        .map(|(_, flow)| flow.abs()),
+                )
+                .sum();
+            let ratio = 100.0 * changed / total;
+            if ratio > policy.max_flow_change_percent as f64 {
+                return Some(CircuitAnomaly::FlowChange(ratio));
+            }
+        }
+
+        if self.objective > 0.0 {
+            let ratio = 100.0 * (next.objective - self.objective) / self.objective;
+            if ratio > policy.max_objective_regression_percent as f64 {
+                return Some(CircuitAnomaly::ObjectiveRegression(ratio));
+            }
+        }
+        None
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CircuitDecision {
+    /// The solution is stable
+    Closed,
+    /// The solution is anomalous, or not stable enough yet
+    Open,
+    /// The solution has just become anomalous
+    Opened(CircuitAnomaly),
+    /// The solutions have become stable again
+    Reclosed,
+}
+
+impl CircuitDecision {
+    pub const fn is_open(&self) -> bool {
+        matches!(self, Self::Open | Self::Opened(_))
+    }
+
+    /// Report the state changes of the circuit to the problem as kubernetes events.
+    pub async fn report(&self, kube: &Client, scope: &GraphScope) -> Result<()> {
+        let event = match self {
+            Self::Closed | Self::Open => return Ok(()),
+            Self::Opened(anomaly) => {
+                warn!("The circuit is opened: {scope}: {anomaly}");
+                Event {
+                    type_: EventType::Warning,
+                    reason: anomaly.reason().into(),
+                    note: Some(format!("Paused the runners: {anomaly}")),
+                    action: "OpenCircuit".into(),
+                    secondary: None,
+                }
+            }
+            Self::Reclosed => {
+                info!("The circuit is closed: {scope}");
+                Event {
+                    type_: EventType::Normal,
+                    reason: "Stabilized".into(),
+                    note: Some("Resumed the runners: the solutions are stable".into()),
+                    action: "CloseCircuit".into(),
+                    secondary: None,
+                }
+            }
+        };
+        publish_problem_event(kube, scope, &event)
+            .await
+            .map_err(|error| anyhow!("failed to report circuit: {error}"))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Error)]
+pub enum CircuitAnomaly {
+    #[error("the edge flows are changed by {0:.1}%")]
+    FlowChange(f64),
+    #[error("the objective value is regressed by {0:.1}%")]
+    ObjectiveRegression(f64),
+}
+
+impl CircuitAnomaly {
+    pub const fn reason(&self) -> &'static str {
+        match self {
+            Self::FlowChange(_) => "FlowChange",
+            Self::ObjectiveRegression(_) => "ObjectiveRegression",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solution(flows: &[(&str, &str, f64)], objective: f64) -> CircuitSolution {
+        CircuitSolution {
+            flows: flows
+                .iter()
+                .map(|(src, sink, flow)| (((*src).into(), (*sink).into()), *flow))
+                .collect(),
+            objective,
+        }
+    }
+
+    #[test]
+    fn circuit_open_and_reclose() {
+        let policy = RunnerCircuitBreakerPolicy {
+            stable_cycles_to_close: 2,
+            ..Default::default()
+        };
+        let mut state = CircuitState::default();
+
+        let stable = solution(&[("a", "b", 10.0)], 10.0);
+        let oscillated = solution(&[("b", "a", 10.0)], 10.0);
+        let regressed = solution(&[("a", "b", 10.0)], 20.0);
+
+        assert_eq!(stable.detect(&policy, &stable), None);
+        assert_eq!(
+            stable.detect(&policy, &oscillated),
+            Some(CircuitAnomaly::FlowChange(200.0)),
+        );
+        assert_eq!(
+            stable.detect(&policy, &regressed),
+            Some(CircuitAnomaly::ObjectiveRegression(100.0)),
+        );
+
+        let anomaly = stable.detect(&policy, &oscillated);
+        assert!(matches!(
+            state.transit(&policy, anomaly),
+            CircuitDecision::Opened(_),
+        ));
+        assert_eq!(state.transit(&policy, None), CircuitDecision::Open);
+        assert_eq!(state.transit(&policy, None), CircuitDecision::Reclosed);
+        assert_eq!(state.transit(&policy, None), CircuitDecision::Closed);
+    }
+}
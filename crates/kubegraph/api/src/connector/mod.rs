@@ -7,21 +7,27 @@ pub mod local;
 #[cfg(feature = "connector-prometheus")]
 pub mod prometheus;
 
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, time::Duration};
 
 use anyhow::Result;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use futures::{stream::FuturesUnordered, TryStreamExt};
-use kube::{CustomResource, CustomResourceExt};
+use kube::{
+    api::{Patch, PatchParams},
+    Api, Client, CustomResource, CustomResourceExt, Resource,
+};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use tokio::time::{sleep, Instant};
-use tracing::{error, info, instrument, Level};
+use tracing::{error, info, instrument, warn, Level};
 
 use crate::{
     frame::LazyFrame,
     graph::{Graph, GraphData, GraphScope, NetworkGraphDB},
-    resource::{NetworkResource, NetworkResourceDB},
+    metrics::METRICS,
+    resource::{NetworkResource, NetworkResourceClient, NetworkResourceDB},
     visualizer::NetworkVisualizerExt,
     vm::{NetworkVirtualMachine, NetworkVirtualMachineRestartPolicy},
 };
@@ -40,7 +46,7 @@ where
         info!("Starting {name} connector...");
 
         let mut inited = false;
-        let mut scopes = BTreeMap::default();
+        let mut scopes: BTreeMap<_, (Option<i64>, Instant)> = BTreeMap::default();
         loop {
             let instant = Instant::now();
 
@@ -50,22 +56,36 @@ where
                 let name = self.name();
                 info!("Reloading {name} connector...");
 
-                // Collect all new/updated resource scopes
+                // Collect all new/updated/expired resource scopes
                 let mut new_connectors = Vec::default();
                 let mut new_scopes = BTreeMap::default();
                 for cr in connectors {
                     let scope = GraphScope::from_resource::<NetworkConnectorCrd>(&cr);
-                    let version = cr.metadata.resource_version.clone();
+                    // NOTE: the generation is not bumped by the status updates
+                    let version = cr.metadata.generation;
 
-                    if scopes
-                        .get(&scope) // updated
-                        .map(|last_version| last_version != &version)
+                    let last_pulled = match scopes.get(&scope) {
+                        // updated
+                        Some((last_version, _)) if last_version != &version => None,
+                        // expired
+                        Some((_, last_pulled)) => match cr.spec.poll_interval() {
+                            Some(interval) if instant.duration_since(*last_pulled) >= interval => {
+                                None
+                            }
+                            Some(_) | None => Some(*last_pulled),
+                        },
                         // new
-                        .unwrap_or(true)
-                    {
-                        new_connectors.push(cr);
+                        None => None,
+                    };
+                    match last_pulled {
+                        Some(last_pulled) => {
+                            new_scopes.insert(scope, (version, last_pulled));
+                        }
+                        None => {
+                            new_scopes.insert(scope, (version, instant));
+                            new_connectors.push(cr);
+                        }
                     }
-                    new_scopes.insert(scope, version);
                 }
 
                 // Collect all removed scopes
@@ -76,8 +96,22 @@ where
                     .map(NetworkConnectorEvent::Deleted)
                     .collect();
 
-                match self.pull(new_connectors).await {
+                let instant_pull = Instant::now();
+                let result = self.pull(new_connectors.clone()).await;
+                METRICS.record_connector_poll(self.name(), instant_pull.elapsed(), result.is_ok());
+
+                let statuses = match result {
                     Ok(data) => {
+                        let mut statuses = BTreeMap::default();
+                        for graph in &data {
+                            METRICS.record_graph(&graph.scope, &graph.data);
+
+                            if let Some(cr) = graph.connector.as_deref() {
+                                let scope = GraphScope::from_resource(cr);
+                                statuses.insert(scope, NetworkConnectorStatus::ready(&graph.data));
+                            }
+                        }
+
                         // Collect all new/updated resources
                         events.extend(data.into_iter().map(NetworkConnectorEvent::Applied));
 
@@ -97,16 +131,40 @@ where
                             Ok(()) => {
                                 // Update the scopes database
                                 scopes = new_scopes;
+                                statuses
                             }
                             Err(error) => {
                                 let name = self.name();
                                 error!("failed to store graphs from {name:?}: {error}");
+                                let message = format!("failed to store graphs: {error}");
+                                BTreeMap::from_iter(new_connectors.iter().map(|cr| {
+                                    let scope = GraphScope::from_resource(cr);
+                                    (scope, NetworkConnectorStatus::failed(message.clone()))
+                                }))
                             }
                         }
                     }
                     Err(error) => {
                         let name = self.name();
                         error!("failed to pull graphs from {name:?}: {error}");
+                        let message = format!("failed to pull graphs: {error}");
+                        BTreeMap::from_iter(new_connectors.iter().map(|cr| {
+                            let scope = GraphScope::from_resource(cr);
+                            (scope, NetworkConnectorStatus::failed(message.clone()))
+                        }))
+                    }
+                };
+
+                // Report the per-connector status
+                let kube = vm.resource_db().kube();
+                for cr in &new_connectors {
+                    let scope = GraphScope::from_resource(cr);
+                    let status = statuses.get(&scope).cloned().unwrap_or_else(|| {
+                        NetworkConnectorStatus::failed("no graph has been pulled".into())
+                    });
+                    if let Err(error) = update_status(kube, &scope, status).await {
+                        let GraphScope { namespace, name } = scope;
+                        warn!("failed to update connector status ({namespace}/{name}): {error}");
                     }
                 }
             }
@@ -177,8 +235,21 @@ enum NetworkConnectorEvent {
     version = "v1alpha1",
     kind = "NetworkConnector",
     root = "NetworkConnectorCrd",
+    status = "NetworkConnectorStatus",
     shortname = "nc",
     namespaced,
+    printcolumn = r#"{
+        "name": "state",
+        "type": "string",
+        "description": "state of the connector",
+        "jsonPath": ".status.state"
+    }"#,
+    printcolumn = r#"{
+        "name": "rows",
+        "type": "integer",
+        "description": "the number of the pulled edges",
+        "jsonPath": ".status.edges"
+    }"#,
     printcolumn = r#"{
         "name": "created-at",
         "type": "date",
@@ -190,12 +261,22 @@ enum NetworkConnectorEvent {
         "type": "integer",
         "description": "connector version",
         "jsonPath": ".metadata.generation"
+    }"#,
+    printcolumn = r#"{
+        "name": "last-sync",
+        "type": "date",
+        "description": "last synchronized time",
+        "jsonPath": ".status.lastSyncTime"
     }"#
 )]
 #[serde(rename_all = "camelCase")]
 pub struct NetworkConnectorSpec {
     #[serde(flatten)]
     pub kind: NetworkConnectorKind,
+
+    /// The interval to re-pull the graphs; pulled only on changes if not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub poll_interval_secs: Option<u64>,
 }
 
 impl NetworkResource for NetworkConnectorCrd {
@@ -218,6 +299,12 @@ impl NetworkConnectorSpec {
         self.kind.name()
     }
 
+    pub fn poll_interval(&self) -> Option<Duration> {
+        self.poll_interval_secs
+            .filter(|&secs| secs > 0)
+            .map(Duration::from_secs)
+    }
+
     pub const fn to_ref(&self) -> NetworkConnectorType {
         self.kind.to_ref()
     }
@@ -229,6 +316,114 @@ impl PartialEq<NetworkConnectorType> for NetworkConnectorSpec {
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkConnectorStatus {
+    #[serde(default)]
+    pub state: NetworkConnectorState,
+    /// The number of the pulled edges
+    #[serde(default)]
+    pub edges: Option<usize>,
+    /// The number of the pulled nodes
+    #[serde(default)]
+    pub nodes: Option<usize>,
+    #[serde(default)]
+    pub last_sync_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub message: Option<String>,
+    pub last_updated: DateTime<Utc>,
+}
+
+impl NetworkConnectorStatus {
+    fn ready(data: &GraphData<LazyFrame>) -> Self {
+        let now = Utc::now();
+        let count_rows = |frame: &LazyFrame| match frame.count_rows() {
+            Ok(len) => Some(len),
+            Err(error) => {
+                warn!("failed to count connector rows: {error}");
+                None
+            }
+        };
+
+        Self {
+            state: NetworkConnectorState::Ready,
+            edges: count_rows(&data.edges),
+            nodes: count_rows(&data.nodes),
+            last_sync_time: Some(now),
+            message: None,
+            last_updated: now,
+        }
+    }
+
+    fn failed(message: String) -> Self {
+        Self {
+            state: NetworkConnectorState::Failed,
+            edges: None,
+            nodes: None,
+            last_sync_time: None,
+            message: Some(message),
+            last_updated: Utc::now(),
+        }
+    }
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum NetworkConnectorState {
+    #[default]
+    Pending,
+    Ready,
+    Failed,
+}
+
+#[instrument(level = Level::INFO, skip(kube, status), err(Display))]
+async fn update_status(
+    kube: &Client,
+    scope: &GraphScope,
+    status: NetworkConnectorStatus,
+) -> Result<()> {
+    let GraphScope { namespace, name } = scope;
+    let api = Api::<NetworkConnectorCrd>::namespaced(kube.clone(), namespace);
+    let crd = NetworkConnectorCrd::api_resource();
+
+    // NOTE: keep the last synchronized time and row counts on failures
+    let status = match status.state {
+        NetworkConnectorState::Failed => match api.get_opt(name).await? {
+            Some(NetworkConnectorCrd {
+                status: Some(last), ..
+            }) => NetworkConnectorStatus {
+                edges: last.edges,
+                nodes: last.nodes,
+                last_sync_time: last.last_sync_time,
+                ..status
+            },
+            _ => status,
+        },
+        _ => status,
+    };
+
+    let patch = Patch::Merge(json!({
+        "apiVersion": crd.api_version,
+        "kind": crd.kind,
+        "status": status,
+    }));
+    let pp = PatchParams::apply(crate::consts::NAMESPACE);
+    api.patch_status(name, &pp, &patch).await?;
+    Ok(())
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[non_exhaustive]
 #[serde(rename_all = "camelCase")]
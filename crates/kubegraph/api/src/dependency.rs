@@ -6,7 +6,7 @@ use async_trait::async_trait;
 use crate::{
     connector::NetworkConnectorCrd,
     frame::LazyFrame,
-    function::NetworkFunctionCrd,
+    function::{dry_run::NetworkFunctionDryRunOutput, NetworkFunctionCrd},
     graph::{Graph, GraphData, GraphEdges, GraphScope},
     problem::VirtualProblem,
 };
@@ -18,6 +18,14 @@ pub trait NetworkDependencySolver {
         problem: &VirtualProblem,
         spec: NetworkDependencySolverSpec,
     ) -> Result<NetworkDependencyPipelineTemplate<GraphData<LazyFrame>>>;
+
+    /// Evaluate the function against the graphs, without building the pipeline.
+    async fn dry_run(
+        &self,
+        problem: &VirtualProblem,
+        function: NetworkFunctionCrd,
+        graphs: Vec<Graph<GraphData<LazyFrame>>>,
+    ) -> Result<NetworkFunctionDryRunOutput>;
 }
 
 pub struct NetworkDependencySolverSpec {
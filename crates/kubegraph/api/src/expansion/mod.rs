@@ -0,0 +1,136 @@
+#[cfg(feature = "df-polars")]
+mod polars;
+
+use anyhow::{bail, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    frame::LazyFrame,
+    graph::{GraphData, GraphMetadataPinnedExt},
+};
+
+/// Expand the graph over the discrete time steps, so that the solver can plan ahead
+/// for the known future demands (e.g. batch job queues, business-hours traffic).
+///
+/// Each node is replicated per time step as `{name}@{offset}`, and the holdover edges
+/// carry the remaining supply of a node into its next time step.
+/// The nodes which have the time column are regarded as the scheduled demands (or supplies)
+/// at the given time offset; the others are placed at the current time step.
+///
+/// After solving, only the current time step is applied.
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeExpansionSpec {
+    /// The time to plan ahead, in seconds
+    pub horizon_seconds: u64,
+
+    /// The duration of each time step, in seconds
+    pub step_seconds: u64,
+
+    /// The node column of the holdover capacity; unlimited if missing
+    #[serde(default = "TimeExpansionSpec::default_key_holdover_capacity")]
+    pub key_holdover_capacity: String,
+
+    /// The edge column marking the holdover edges
+    #[serde(default = "TimeExpansionSpec::default_key_holdover")]
+    pub key_holdover: String,
+
+    /// The node and edge column of the time offset, in seconds
+    #[serde(default = "TimeExpansionSpec::default_key_time")]
+    pub key_time: String,
+
+    /// The unit cost of holding the supply over a time step
+    #[serde(default)]
+    pub holdover_unit_cost: u64,
+}
+
+impl TimeExpansionSpec {
+    fn default_key_holdover_capacity() -> String {
+        "holdover_capacity".into()
+    }
+
+    fn default_key_holdover() -> String {
+        "holdover".into()
+    }
+
+    fn default_key_time() -> String {
+        "t".into()
+    }
+
+    /// Return the number of the time steps, including the current one.
+    pub fn steps(&self) -> Result<u64> {
+        if self.step_seconds == 0 {
+            bail!("the time step should be positive")
+        }
+        Ok(self.horizon_seconds.div_ceil(self.step_seconds).max(1))
+    }
+
+    /// Replicate the nodes and edges per time step, linking them with the holdover edges.
+    pub fn expand<M>(
+        &self,
+        graph: GraphData<LazyFrame>,
+        metadata: &M,
+    ) -> Result<GraphData<LazyFrame>>
+    where
+        M: GraphMetadataPinnedExt,
+    {
+        match graph {
+            GraphData {
+                edges: _,
+                nodes: LazyFrame::Empty,
+            } => Ok(graph),
+            #[cfg(not(feature = "df-polars"))]
+            GraphData { .. } => {
+                bail!("time expansion is not supported by the native dataframe backend")
+            }
+            #[cfg(feature = "df-polars")]
+            GraphData { edges, nodes } => self::polars::expand(
+                self,
+                match edges {
+                    LazyFrame::Empty => None,
+                    edges => Some(edges.try_into_polars()?),
+                },
+                nodes.try_into_polars()?,
+                metadata,
+            )
+            .map(Into::into),
+        }
+    }
+
+    /// Restore the current time step of the expanded graph, dropping the planned ones.
+    pub fn collapse<M>(
+        &self,
+        graph: GraphData<LazyFrame>,
+        metadata: &M,
+    ) -> Result<GraphData<LazyFrame>>
+    where
+        M: GraphMetadataPinnedExt,
+    {
+        match graph {
+            GraphData {
+                edges: LazyFrame::Empty,
+                nodes: LazyFrame::Empty,
+            } => Ok(graph),
+            #[cfg(not(feature = "df-polars"))]
+            GraphData { .. } => {
+                bail!("time expansion is not supported by the native dataframe backend")
+            }
+            #[cfg(feature = "df-polars")]
+            GraphData { edges, nodes } => Ok(GraphData {
+                edges: match edges {
+                    LazyFrame::Empty => LazyFrame::Empty,
+                    edges => self::polars::collapse_edges(self, edges.try_into_polars()?, metadata)
+                        .into(),
+                },
+                nodes: match nodes {
+                    LazyFrame::Empty => LazyFrame::Empty,
+                    nodes => self::polars::collapse_nodes(self, nodes.try_into_polars()?, metadata)
+                        .into(),
+                },
+            }),
+        }
+    }
+}
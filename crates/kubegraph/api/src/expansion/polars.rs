@@ -0,0 +1,196 @@
+use anyhow::{anyhow, Result};
+use pl::{
+    datatypes::DataType,
+    lazy::{dsl, frame::LazyFrame},
+    prelude::{JoinArgs, JoinType, UnionArgs, UniqueKeepStrategy},
+};
+
+use crate::{
+    graph::{GraphData, GraphMetadataPinned, GraphMetadataPinnedExt},
+    problem::ProblemSpec,
+};
+
+use super::TimeExpansionSpec;
+
+/// The pattern of the time offset suffix of the expanded names.
+const PATTERN_TIME_SUFFIX: &str = r"@[0-9]+$";
+
+pub(super) fn expand<M>(
+    spec: &TimeExpansionSpec,
+    edges: Option<LazyFrame>,
+    mut nodes: LazyFrame,
+    metadata: &M,
+) -> Result<GraphData<LazyFrame>>
+where
+    M: GraphMetadataPinnedExt,
+{
+    let key_capacity = metadata.capacity();
+    let key_name = metadata.name();
+    let key_sink = metadata.sink();
+    let key_src = metadata.src();
+    let key_supply = metadata.supply();
+    let key_unit_cost = metadata.unit_cost();
+    let key_holdover = spec.key_holdover.as_str();
+    let key_holdover_capacity = spec.key_holdover_capacity.as_str();
+    let key_time = spec.key_time.as_str();
+
+    let step = i64::try_from(spec.step_seconds)
+        .map_err(|_| anyhow!("too large time step: {}", spec.step_seconds))?;
+    let offsets: Vec<_> = (0..spec.steps()?)
+        .map(|index| (index as i64).saturating_mul(step))
+        .collect();
+    let horizon = offsets
+        .last()
+        .copied()
+        .unwrap_or_default()
+        .saturating_add(step);
+
+    let max_capacity = ProblemSpec::<GraphMetadataPinned>::MAX_CAPACITY as i64;
+    let holdover_unit_cost = i64::try_from(spec.holdover_unit_cost).unwrap_or(i64::MAX);
+
+    let expand_name = |name: &str, offset: dsl::Expr| {
+        dsl::col(name) + dsl::lit("@") + offset.cast(DataType::String)
+    };
+
+    // Step 1. Align the scheduled nodes to the time steps
+    let schema = nodes
+        .collect_schema()
+        .map_err(|error| anyhow!("failed to get nodes schema: {error}"))?;
+    let time = if schema.contains(key_time) {
+        let time = dsl::col(key_time).cast(DataType::Int64).fill_null(0i64);
+        time.clone() - time % dsl::lit(step)
+    } else {
+        dsl::lit(0i64)
+    };
+    let nodes = nodes.with_column(time.alias(key_time));
+
+    // Step 2. Collect the entities and their scheduled supplies
+    let entities = nodes
+        .clone()
+        .select([dsl::all().exclude([key_supply, key_time])])
+        .unique_stable(Some(vec![key_name.into()]), UniqueKeepStrategy::First);
+    let schedule = nodes
+        .filter(
+            dsl::col(key_time)
+                .gt_eq(dsl::lit(0i64))
+                .and(dsl::col(key_time).lt(dsl::lit(horizon))),
+        )
+        .group_by([dsl::col(key_name), dsl::col(key_time)])
+        .agg([dsl::col(key_supply).sum()]);
+
+    // Step 3. Replicate the nodes per time step
+    let args = UnionArgs {
+        rechunk: true,
+        to_supertypes: true,
+        ..Default::default()
+    };
+    let expanded_nodes = dsl::concat(
+        offsets
+            .iter()
+            .map(|&offset| {
+                entities
+                    .clone()
+                    .with_column(dsl::lit(offset).alias(key_time))
+            })
+            .collect::<Vec<_>>(),
+        args.clone(),
+    )
+    .map_err(|error| anyhow!("failed to expand nodes: {error}"))?
+    .join(
+        schedule,
+        [dsl::col(key_name), dsl::col(key_time)],
+        [dsl::col(key_name), dsl::col(key_time)],
+        JoinArgs::new(JoinType::Left),
+    )
+    .with_column(dsl::col(key_supply).fill_null(0i64))
+    .with_column(expand_name(key_name, dsl::col(key_time)).alias(key_name));
+
+    // Step 4. Replicate the edges per time step
+    let mut expanded_edges: Vec<_> = match edges {
+        Some(edges) => offsets
+            .iter()
+            .map(|&offset| {
+                edges.clone().with_columns([
+                    expand_name(key_src, dsl::lit(offset)).alias(key_src),
+                    expand_name(key_sink, dsl::lit(offset)).alias(key_sink),
+                    dsl::lit(offset).alias(key_time),
+                    dsl::lit(false).alias(key_holdover),
+                ])
+            })
+            .collect(),
+        None => Vec::default(),
+    };
+
+    // Step 5. Link the time steps with the holdover edges
+    let holdover_capacity = if schema.contains(key_holdover_capacity) {
+        dsl::col(key_holdover_capacity)
+            .cast(DataType::Int64)
+            .fill_null(max_capacity)
+    } else {
+        dsl::lit(max_capacity)
+    };
+    expanded_edges.extend(offsets.windows(2).map(|window| {
+        let (offset, next) = (window[0], window[1]);
+        entities.clone().select([
+            expand_name(key_name, dsl::lit(offset)).alias(key_src),
+            expand_name(key_name, dsl::lit(next)).alias(key_sink),
+            holdover_capacity.clone().alias(key_capacity),
+            dsl::lit(holdover_unit_cost).alias(key_unit_cost),
+            dsl::lit(offset).alias(key_time),
+            dsl::lit(true).alias(key_holdover),
+        ])
+    }));
+
+    Ok(GraphData {
+        edges: dsl::concat_lf_diagonal(expanded_edges, args)
+            .map_err(|error| anyhow!("failed to expand edges: {error}"))?,
+        nodes: expanded_nodes,
+    })
+}
+
+pub(super) fn collapse_edges<M>(
+    spec: &TimeExpansionSpec,
+    edges: LazyFrame,
+    metadata: &M,
+) -> LazyFrame
+where
+    M: GraphMetadataPinnedExt,
+{
+    let key_sink = metadata.sink();
+    let key_src = metadata.src();
+    let key_holdover = spec.key_holdover.as_str();
+    let key_time = spec.key_time.as_str();
+
+    edges
+        .filter(
+            dsl::col(key_time)
+                .eq(dsl::lit(0i64))
+                .and(dsl::col(key_holdover).not()),
+        )
+        .with_columns([strip_time_suffix(key_src), strip_time_suffix(key_sink)])
+        .select([dsl::all().exclude([key_holdover, key_time])])
+}
+
+pub(super) fn collapse_nodes<M>(
+    spec: &TimeExpansionSpec,
+    nodes: LazyFrame,
+    metadata: &M,
+) -> LazyFrame
+where
+    M: GraphMetadataPinnedExt,
+{
+    let key_name = metadata.name();
+    let key_time = spec.key_time.as_str();
+
+    nodes
+        .filter(dsl::col(key_time).eq(dsl::lit(0i64)))
+        .with_column(strip_time_suffix(key_name))
+        .select([dsl::all().exclude([key_time])])
+}
+
+fn strip_time_suffix(name: &str) -> dsl::Expr {
+    dsl::col(name)
+        .str()
+        .replace(dsl::lit(PATTERN_TIME_SUFFIX), dsl::lit(""), false)
+        .alias(name)
+}
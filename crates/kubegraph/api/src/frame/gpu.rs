@@ -0,0 +1,139 @@
+use std::{
+    env,
+    io::Cursor,
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail, Result};
+use duration_string::DurationString;
+use pl::{
+    frame::DataFrame,
+    lazy::frame::LazyFrame,
+    prelude::{IpcReader, SerReader},
+};
+use reqwest::{header, Client, Url};
+use tracing::{debug, warn};
+
+/// The GPU dataframe executor, which is enabled if the endpoint is given.
+static GPU: LazyLock<Option<GpuExecutor>> = LazyLock::new(|| match GpuExecutor::try_default() {
+    Ok(executor) => executor,
+    Err(error) => {
+        warn!("failed to init GPU dataframe executor: {error}");
+        None
+    }
+});
+
+/// Offloads the lazy queries into a GPU dataframe executor (e.g. `cudf-polars`),
+/// which runs the serialized logical plans and returns the results as Arrow IPC.
+///
+/// NOTE: EXPERIMENTAL! The executor should speak the same version of the polars plans.
+struct GpuExecutor {
+    backoff: Duration,
+    client: Client,
+    endpoint: Url,
+    unavailable_until: Mutex<Option<Instant>>,
+}
+
+impl GpuExecutor {
+    const ENV_BACKOFF: &'static str = "KUBEGRAPH_DF_GPU_BACKOFF";
+    const ENV_ENDPOINT: &'static str = "KUBEGRAPH_DF_GPU_ENDPOINT";
+    const ENV_TIMEOUT: &'static str = "KUBEGRAPH_DF_GPU_TIMEOUT";
+
+    fn try_default() -> Result<Option<Self>> {
+        let endpoint = match env::var(Self::ENV_ENDPOINT) {
+            Ok(endpoint) if !endpoint.is_empty() => endpoint,
+            Ok(_) | Err(_) => return Ok(None),
+        };
+        let endpoint: Url = endpoint
+            .parse()
+            .map_err(|error| anyhow!("failed to parse {}: {error}", Self::ENV_ENDPOINT))?;
+        let endpoint = endpoint
+            .join("collect")
+            .map_err(|error| anyhow!("failed to build GPU executor endpoint: {error}"))?;
+
+        let backoff = parse_duration(Self::ENV_BACKOFF, Duration::from_secs(60))?;
+        let timeout = parse_duration(Self::ENV_TIMEOUT, Duration::from_secs(30))?;
+        let client = Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|error| anyhow!("failed to build GPU executor client: {error}"))?;
+
+        debug!("Using GPU dataframe executor: {endpoint}");
+        Ok(Some(Self {
+            backoff,
+            client,
+            endpoint,
+            unavailable_until: Mutex::default(),
+        }))
+    }
+
+    fn is_available(&self) -> bool {
+        self.unavailable_until
+            .lock()
+            .map(|until| !until.is_some_and(|until| Instant::now() < until))
+            .unwrap_or_default()
+    }
+
+    fn mark_unavailable(&self) {
+        if let Ok(mut until) = self.unavailable_until.lock() {
+            *until = Some(Instant::now() + self.backoff);
+        }
+    }
+
+    async fn collect(&self, df: &LazyFrame) -> Result<DataFrame> {
+        let plan = ::serde_json::to_vec(&df.logical_plan)
+            .map_err(|error| anyhow!("failed to serialize the query plan: {error}"))?;
+
+        let response = self
+            .client
+            .post(self.endpoint.clone())
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::ACCEPT, "application/vnd.apache.arrow.file")
+            .body(plan)
+            .send()
+            .await
+            .map_err(|error| anyhow!("failed to send the query plan: {error}"))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_default();
+            bail!("failed to execute the query plan: [{status}] {message}");
+        }
+
+        let data = response
+            .bytes()
+            .await
+            .map_err(|error| anyhow!("failed to receive the query result: {error}"))?;
+        IpcReader::new(Cursor::new(data))
+            .finish()
+            .map_err(|error| anyhow!("failed to decode the query result: {error}"))
+    }
+}
+
+/// Collect the lazy query on the GPU executor, returning `None` if it is not available
+/// so that the caller can fall back to the CPU.
+pub(super) async fn try_collect(df: &LazyFrame) -> Option<DataFrame> {
+    let executor = GPU.as_ref().filter(|executor| executor.is_available())?;
+    match executor.collect(df).await {
+        Ok(df) => Some(df),
+        Err(error) => {
+            warn!(
+                "failed to collect on GPU; falling back to CPU for {backoff:?}: {error}",
+                backoff = executor.backoff,
+            );
+            executor.mark_unavailable();
+            None
+        }
+    }
+}
+
+fn parse_duration(key: &str, default: Duration) -> Result<Duration> {
+    match env::var(key) {
+        Ok(value) if !value.is_empty() => value
+            .parse::<DurationString>()
+            .map(Into::into)
+            .map_err(|error| anyhow!("failed to parse {key}: {error}")),
+        Ok(_) | Err(_) => Ok(default),
+    }
+}
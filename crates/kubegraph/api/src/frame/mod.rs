@@ -1,3 +1,7 @@
+#[cfg(feature = "df-polars-gpu")]
+mod gpu;
+#[cfg(not(feature = "df-polars"))]
+pub mod native;
 #[cfg(feature = "df-polars")]
 pub mod polars;
 
@@ -6,7 +10,6 @@ use std::{
     ops::{Add, Div, Mul, Neg, Not, Sub},
 };
 
-use ::polars::datatypes::DataType;
 use anyhow::{anyhow, bail, Result};
 #[cfg(feature = "df-polars")]
 use pl::lazy::dsl;
@@ -14,7 +17,9 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     function::FunctionMetadata,
-    graph::{GraphDataType, GraphMetadataExt, GraphMetadataPinnedExt, GraphScope},
+    graph::{
+        GraphDataType, GraphEdgeDirection, GraphMetadataExt, GraphMetadataPinnedExt, GraphScope,
+    },
     ops::{And, Eq, Ge, Gt, Le, Lt, Max, Min, Ne, Or},
     problem::ProblemSpec,
     vm::{Feature, Number},
@@ -24,6 +29,8 @@ use crate::{
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum DataFrame {
     Empty,
+    #[cfg(not(feature = "df-polars"))]
+    Native(self::native::DataFrame),
     #[cfg(feature = "df-polars")]
     Polars(::pl::frame::DataFrame),
 }
@@ -32,6 +39,9 @@ impl fmt::Display for DataFrame {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Empty => "Empty".fmt(f),
+            #[cfg(not(feature = "df-polars"))]
+            Self::Native(df) => df.fmt(f),
+            #[cfg(feature = "df-polars")]
             Self::Polars(df) => df.fmt(f),
         }
     }
@@ -41,8 +51,12 @@ impl DataFrame {
     pub fn drop_null_columns(self) -> Self {
         match self {
             Self::Empty => Self::Empty,
+            #[cfg(not(feature = "df-polars"))]
+            Self::Native(df) => Self::Native(df.drop_null_columns()),
             #[cfg(feature = "df-polars")]
             Self::Polars(df) => {
+                use pl::datatypes::DataType;
+
                 let null_columns: Vec<_> = df
                     .get_columns()
                     .iter()
@@ -63,6 +77,8 @@ impl DataFrame {
     pub fn lazy(self) -> LazyFrame {
         match self {
             Self::Empty => LazyFrame::Empty,
+            #[cfg(not(feature = "df-polars"))]
+            Self::Native(df) => LazyFrame::Native(df.into()),
             #[cfg(feature = "df-polars")]
             Self::Polars(df) => LazyFrame::Polars(::pl::lazy::frame::IntoLazy::lazy(df)),
         }
@@ -73,6 +89,8 @@ impl DataFrame {
 pub enum LazyFrame {
     #[default]
     Empty,
+    #[cfg(not(feature = "df-polars"))]
+    Native(self::native::LazyFrame),
     #[cfg(feature = "df-polars")]
     Polars(::pl::lazy::frame::LazyFrame),
 }
@@ -81,6 +99,8 @@ impl From<DataFrame> for LazyFrame {
     fn from(value: DataFrame) -> Self {
         match value {
             DataFrame::Empty => Self::Empty,
+            #[cfg(not(feature = "df-polars"))]
+            DataFrame::Native(df) => LazyFrame::Native(df.into()),
             #[cfg(feature = "df-polars")]
             DataFrame::Polars(df) => LazyFrame::Polars(::pl::lazy::frame::IntoLazy::lazy(df)),
         }
@@ -91,6 +111,9 @@ impl fmt::Debug for LazyFrame {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Empty => write!(f, "Empty"),
+            #[cfg(not(feature = "df-polars"))]
+            Self::Native(df) => f.debug_tuple("Native").field(df).finish(),
+            #[cfg(feature = "df-polars")]
             Self::Polars(_) => f.debug_tuple("Polars").finish(),
         }
     }
@@ -100,6 +123,10 @@ impl LazyFrame {
     pub fn all(&self) -> Result<LazySlice> {
         match self {
             Self::Empty => bail!("cannot get all columns from empty lazyframe"),
+            #[cfg(not(feature = "df-polars"))]
+            Self::Native(_) => Ok(LazySlice::Native(self::native::Expr::Literal(
+                self::native::Value::Bool(true),
+            ))),
             #[cfg(feature = "df-polars")]
             Self::Polars(_) => Ok(LazySlice::Polars(dsl::all())),
         }
@@ -112,6 +139,8 @@ impl LazyFrame {
     {
         match self {
             Self::Empty => Self::Empty,
+            #[cfg(not(feature = "df-polars"))]
+            Self::Native(df) => Self::Native(self::native::cast(df, ty, from, to)),
             #[cfg(feature = "df-polars")]
             Self::Polars(df) => Self::Polars(self::polars::cast(df, ty, from, to)),
         }
@@ -120,11 +149,21 @@ impl LazyFrame {
     pub async fn collect(self) -> Result<DataFrame> {
         match self {
             Self::Empty => Ok(DataFrame::Empty),
-            #[cfg(feature = "df-polars")]
-            Self::Polars(df) => df
+            #[cfg(not(feature = "df-polars"))]
+            Self::Native(df) => df
                 .collect()
-                .map(DataFrame::Polars)
-                .map_err(|error| ::anyhow::anyhow!("failed to collect polars dataframe: {error}")),
+                .map(DataFrame::Native)
+                .map_err(|error| ::anyhow::anyhow!("failed to collect native dataframe: {error}")),
+            #[cfg(feature = "df-polars")]
+            Self::Polars(df) => {
+                #[cfg(feature = "df-polars-gpu")]
+                if let Some(df) = self::gpu::try_collect(&df).await {
+                    return Ok(DataFrame::Polars(df));
+                }
+                df.collect().map(DataFrame::Polars).map_err(|error| {
+                    ::anyhow::anyhow!("failed to collect polars dataframe: {error}")
+                })
+            }
         }
     }
 
@@ -132,18 +171,37 @@ impl LazyFrame {
         match (self, other) {
             (Self::Empty, Self::Empty) => Ok(Self::Empty),
             (Self::Empty, value) | (value, Self::Empty) => Ok(value),
+            #[cfg(not(feature = "df-polars"))]
+            (Self::Native(a), Self::Native(b)) => Ok(Self::Native(self::native::concat(a, b))),
             #[cfg(feature = "df-polars")]
             (Self::Polars(a), Self::Polars(b)) => self::polars::concat(a, b).map(Self::Polars),
         }
     }
 
+    /// Count the number of the rows, which executes the query.
+    pub fn count_rows(&self) -> Result<usize> {
+        match self {
+            Self::Empty => Ok(0),
+            #[cfg(not(feature = "df-polars"))]
+            Self::Native(df) => self::native::count_rows(df.clone()),
+            #[cfg(feature = "df-polars")]
+            Self::Polars(df) => self::polars::count_rows(df.clone()),
+        }
+    }
+
     /// Create a fully-connected edges
     pub fn fabric<M>(&self, problem: &ProblemSpec<M>) -> Result<Self>
     where
         M: GraphMetadataPinnedExt,
     {
         let ProblemSpec {
+            analyzers: _,
+            direction,
             metadata,
+            runner_policy: _,
+            schedule: _,
+            simulation: _,
+            time_expansion: _,
             verbose: _,
         } = problem;
 
@@ -164,23 +222,55 @@ impl LazyFrame {
 
         match self {
             Self::Empty => bail!("cannot get fabric from empty lazyframe"),
+            #[cfg(not(feature = "df-polars"))]
+            Self::Native(nodes) => self::native::fabric(
+                nodes.clone(),
+                metadata,
+                *direction,
+                ProblemSpec::<M>::MAX_CAPACITY,
+            )
+            .map(Self::Native),
             #[cfg(feature = "df-polars")]
-            Self::Polars(nodes) => Ok(Self::Polars(
-                select_polars_edge_side(&nodes, metadata.name(), metadata.src())
+            Self::Polars(nodes) => {
+                let edges = select_polars_edge_side(&nodes, metadata.name(), metadata.src())
                     .cross_join(
                         select_polars_edge_side(&nodes, metadata.name(), metadata.sink()),
                         None,
-                    )
-                    .with_column(
-                        dsl::lit(ProblemSpec::<M>::MAX_CAPACITY).alias(metadata.capacity()),
-                    ),
-            )),
+                    );
+                let edges = match direction {
+                    GraphEdgeDirection::Directed => edges,
+                    // NOTE: the reversed edges are mirrored by the solvers
+                    GraphEdgeDirection::Undirected => {
+                        edges.filter(dsl::col(metadata.src()).lt(dsl::col(metadata.sink())))
+                    }
+                };
+                Ok(Self::Polars(edges.with_columns([
+                    dsl::lit(ProblemSpec::<M>::MAX_CAPACITY).alias(metadata.capacity()),
+                    dsl::lit(direction.as_str()).alias(metadata.direction()),
+                ])))
+            }
+        }
+    }
+
+    /// Sum up the costs of the edge flows, i.e. the objective value of the solved problem.
+    pub fn flow_cost<M>(&self, metadata: &M) -> Result<f64>
+    where
+        M: GraphMetadataPinnedExt,
+    {
+        match self {
+            Self::Empty => Ok(0.0),
+            #[cfg(not(feature = "df-polars"))]
+            Self::Native(edges) => self::native::flow_cost(edges.clone(), metadata),
+            #[cfg(feature = "df-polars")]
+            Self::Polars(edges) => self::polars::flow_cost(edges.clone(), metadata),
         }
     }
 
     pub fn get_column(&self, name: &str) -> Result<LazySlice> {
         match self {
             Self::Empty => bail!("cannot get column from empty lazyframe"),
+            #[cfg(not(feature = "df-polars"))]
+            Self::Native(_) => Ok(LazySlice::Native(self::native::Expr::Column(name.into()))),
             #[cfg(feature = "df-polars")]
             Self::Polars(_) => Ok(LazySlice::Polars(dsl::col(name))),
         }
@@ -189,6 +279,11 @@ impl LazyFrame {
     fn alias(&mut self, key: &str, value: &str) -> Result<()> {
         match self {
             Self::Empty => bail!("cannot make an alias to empty lazyframe: {key:?}"),
+            #[cfg(not(feature = "df-polars"))]
+            Self::Native(df) => {
+                df.with_column(key, value.to_string().into_native());
+                Ok(())
+            }
             #[cfg(feature = "df-polars")]
             Self::Polars(df) => {
                 *df = df.clone().with_column(dsl::lit(value).alias(key));
@@ -220,6 +315,11 @@ impl LazyFrame {
     pub fn apply_filter(&mut self, filter: LazySlice) -> Result<()> {
         match (self, filter) {
             (Self::Empty, _) => bail!("cannot apply filter into empty lazyframe"),
+            #[cfg(not(feature = "df-polars"))]
+            (Self::Native(df), LazySlice::Native(filter)) => {
+                df.filter(filter);
+                Ok(())
+            }
             #[cfg(feature = "df-polars")]
             (Self::Polars(df), LazySlice::Polars(filter)) => {
                 *df = df.clone().filter(filter);
@@ -231,6 +331,11 @@ impl LazyFrame {
     pub fn fill_column_with_feature(&mut self, name: &str, value: Feature) -> Result<()> {
         match self {
             Self::Empty => bail!("cannot fill column with feature into empty lazyframe: {name:?}"),
+            #[cfg(not(feature = "df-polars"))]
+            Self::Native(df) => {
+                df.with_column(name, value.into_native());
+                Ok(())
+            }
             #[cfg(feature = "df-polars")]
             Self::Polars(df) => {
                 *df = df.clone().with_column(value.into_polars().alias(name));
@@ -242,6 +347,27 @@ impl LazyFrame {
     pub fn fill_column_with_value(&mut self, name: &str, value: Number) -> Result<()> {
         match self {
             Self::Empty => bail!("cannot fill column with name into empty lazyframe: {name:?}"),
+            #[cfg(not(feature = "df-polars"))]
+            Self::Native(df) => {
+                df.with_column(name, value.into_native());
+                Ok(())
+            }
+            #[cfg(feature = "df-polars")]
+            Self::Polars(df) => {
+                *df = df.clone().with_column(value.into_polars().alias(name));
+                Ok(())
+            }
+        }
+    }
+
+    pub fn fill_column_with_string(&mut self, name: &str, value: String) -> Result<()> {
+        match self {
+            Self::Empty => bail!("cannot fill column with string into empty lazyframe: {name:?}"),
+            #[cfg(not(feature = "df-polars"))]
+            Self::Native(df) => {
+                df.with_column(name, value.into_native());
+                Ok(())
+            }
             #[cfg(feature = "df-polars")]
             Self::Polars(df) => {
                 *df = df.clone().with_column(value.into_polars().alias(name));
@@ -253,6 +379,11 @@ impl LazyFrame {
     pub fn insert_column(&mut self, name: &str, column: LazySlice) -> Result<()> {
         match (self, column) {
             (Self::Empty, _) => bail!("cannot fill column into empty lazyframe: {name:?}"),
+            #[cfg(not(feature = "df-polars"))]
+            (Self::Native(df), LazySlice::Native(column)) => {
+                df.with_column(name, column);
+                Ok(())
+            }
             #[cfg(feature = "df-polars")]
             (Self::Polars(df), LazySlice::Polars(column)) => {
                 *df = df.clone().with_column(column.alias(name));
@@ -261,6 +392,38 @@ impl LazyFrame {
         }
     }
 
+    /// Keep only the given columns, in order.
+    pub fn select_columns(&mut self, names: &[String]) -> Result<()> {
+        match self {
+            Self::Empty => bail!("cannot select columns from empty lazyframe: {names:?}"),
+            #[cfg(not(feature = "df-polars"))]
+            Self::Native(df) => {
+                df.select(
+                    names
+                        .iter()
+                        .map(|name| (self::native::Expr::Column(name.clone()), name.clone()))
+                        .collect(),
+                );
+                Ok(())
+            }
+            #[cfg(feature = "df-polars")]
+            Self::Polars(df) => {
+                *df = df
+                    .clone()
+                    .select(names.iter().map(|name| dsl::col(name.as_str())));
+                Ok(())
+            }
+        }
+    }
+
+    #[cfg(not(feature = "df-polars"))]
+    pub fn try_into_native(self) -> Result<self::native::LazyFrame> {
+        match self {
+            Self::Empty => Ok(self::native::LazyFrame::default()),
+            Self::Native(df) => Ok(df),
+        }
+    }
+
     #[cfg(feature = "df-polars")]
     pub fn try_into_polars(self) -> Result<::pl::lazy::frame::LazyFrame> {
         match self {
@@ -278,6 +441,10 @@ pub enum LazySliceOrScalar<T> {
 
 macro_rules! impl_expr_function_builtin {
     ( impl $ty:ident ( $fn:ident ) for Vec<LazySliceOrScalar< $scalar:ty >> {
+        native: {
+            op: $op_native:ident,
+            acc: $acc_native:expr,
+        },
         polars: {
             op: $fn_polars:ident,
             acc: $acc_polars:expr,
@@ -304,6 +471,25 @@ macro_rules! impl_expr_function_builtin {
                                 " with multiple slices",
                             ))
                         }
+                        #[cfg(not(feature = "df-polars"))]
+                        (
+                            LazySliceOrScalar::LazySlice(LazySlice::Native(slice)),
+                            LazySliceOrScalar::Scalar(b),
+                        )
+                        | (
+                            LazySliceOrScalar::Scalar(b),
+                            LazySliceOrScalar::LazySlice(LazySlice::Native(slice)),
+                        ) => {
+                            let a = self::native::Expr::aggregate(
+                                self::native::AggregateOp::$op_native,
+                                slice,
+                                None,
+                            );
+                            let b = b.into_native();
+
+                            let acc = $acc_native(a, b);
+                            LazySliceOrScalar::LazySlice(LazySlice::Native(acc))
+                        }
                         #[cfg(feature = "df-polars")]
                         (
                             LazySliceOrScalar::LazySlice(LazySlice::Polars(slice)),
@@ -332,12 +518,28 @@ macro_rules! impl_expr_function_builtin {
 }
 
 impl_expr_function_builtin!(impl Max(max) for Vec<LazySliceOrScalar<Number>> {
+    native: {
+        op: Max,
+        acc: |a: self::native::Expr, b: self::native::Expr| self::native::Expr::select(
+            self::native::Expr::binary(self::native::BinaryOp::Ge, a.clone(), b.clone()),
+            a,
+            b,
+        ),
+    },
     polars: {
         op: max,
         acc: |a: dsl::Expr, b: dsl::Expr| dsl::when(a.clone().gt_eq(b.clone())).then(a).otherwise(b),
     },
 });
 impl_expr_function_builtin!(impl Min(min) for Vec<LazySliceOrScalar<Number>> {
+    native: {
+        op: Min,
+        acc: |a: self::native::Expr, b: self::native::Expr| self::native::Expr::select(
+            self::native::Expr::binary(self::native::BinaryOp::Le, a.clone(), b.clone()),
+            a,
+            b,
+        ),
+    },
     polars: {
         op: min,
         acc: |a: dsl::Expr, b: dsl::Expr| dsl::when(a.clone().lt_eq(b.clone())).then(a).otherwise(b),
@@ -346,12 +548,89 @@ impl_expr_function_builtin!(impl Min(min) for Vec<LazySliceOrScalar<Number>> {
 
 #[derive(Clone)]
 pub enum LazySlice {
+    #[cfg(not(feature = "df-polars"))]
+    Native(self::native::Expr),
     #[cfg(feature = "df-polars")]
     Polars(dsl::Expr),
 }
 
+impl LazySlice {
+    /// Test whether the strings contain the regex pattern.
+    pub fn matches(self, pattern: &str) -> Self {
+        match self {
+            #[cfg(not(feature = "df-polars"))]
+            Self::Native(src) => {
+                Self::Native(self::native::Expr::Matches(Box::new(src), pattern.into()))
+            }
+            #[cfg(feature = "df-polars")]
+            Self::Polars(src) => Self::Polars(src.str().contains(dsl::lit(pattern), true)),
+        }
+    }
+
+    /// Select the values of `then` where the condition holds, or `otherwise`.
+    pub fn select(self, then: Self, otherwise: Self) -> Self {
+        match (self, then, otherwise) {
+            #[cfg(not(feature = "df-polars"))]
+            (Self::Native(cond), Self::Native(then), Self::Native(otherwise)) => {
+                Self::Native(self::native::Expr::select(cond, then, otherwise))
+            }
+            #[cfg(feature = "df-polars")]
+            (Self::Polars(cond), Self::Polars(then), Self::Polars(otherwise)) => {
+                Self::Polars(dsl::when(cond).then(then).otherwise(otherwise))
+            }
+        }
+    }
+
+    pub fn sum(self) -> Self {
+        match self {
+            #[cfg(not(feature = "df-polars"))]
+            Self::Native(src) => Self::Native(self::native::Expr::aggregate(
+                self::native::AggregateOp::Sum,
+                src,
+                None,
+            )),
+            #[cfg(feature = "df-polars")]
+            Self::Polars(src) => Self::Polars(src.sum()),
+        }
+    }
+
+    pub fn sum_over(self, group: Self) -> Self {
+        match (self, group) {
+            #[cfg(not(feature = "df-polars"))]
+            (Self::Native(src), Self::Native(group)) => Self::Native(
+                self::native::Expr::aggregate(self::native::AggregateOp::Sum, src, Some(group)),
+            ),
+            #[cfg(feature = "df-polars")]
+            (Self::Polars(src), Self::Polars(group)) => Self::Polars(src.sum().over([group])),
+        }
+    }
+
+    pub fn max_over(self, group: Self) -> Self {
+        match (self, group) {
+            #[cfg(not(feature = "df-polars"))]
+            (Self::Native(src), Self::Native(group)) => Self::Native(
+                self::native::Expr::aggregate(self::native::AggregateOp::Max, src, Some(group)),
+            ),
+            #[cfg(feature = "df-polars")]
+            (Self::Polars(src), Self::Polars(group)) => Self::Polars(src.max().over([group])),
+        }
+    }
+
+    pub fn min_over(self, group: Self) -> Self {
+        match (self, group) {
+            #[cfg(not(feature = "df-polars"))]
+            (Self::Native(src), Self::Native(group)) => Self::Native(
+                self::native::Expr::aggregate(self::native::AggregateOp::Min, src, Some(group)),
+            ),
+            #[cfg(feature = "df-polars")]
+            (Self::Polars(src), Self::Polars(group)) => Self::Polars(src.min().over([group])),
+        }
+    }
+}
+
 macro_rules! impl_expr_unary {
     ( impl $ty:ident ( $fn:ident ) for LazySlice {
+        native: $op_native:ident,
         polars: $fn_polars:ident,
     } ) => {
         impl $ty for LazySlice {
@@ -359,6 +638,11 @@ macro_rules! impl_expr_unary {
 
             fn $fn(self) -> Self::Output {
                 match self {
+                    #[cfg(not(feature = "df-polars"))]
+                    Self::Native(src) => Self::Native(self::native::Expr::unary(
+                        self::native::UnaryOp::$op_native,
+                        src,
+                    )),
                     #[cfg(feature = "df-polars")]
                     Self::Polars(src) => Self::Polars(src.$fn_polars()),
                 }
@@ -368,14 +652,17 @@ macro_rules! impl_expr_unary {
 }
 
 impl_expr_unary!(impl Neg(neg) for LazySlice {
+    native: Neg,
     polars: neg,
 });
 impl_expr_unary!(impl Not(not) for LazySlice {
+    native: Not,
     polars: not,
 });
 
 macro_rules! impl_expr_binary {
     ( impl $ty:ident ( $fn:ident ) for $target:ident {
+        native: $op_native:ident,
         polars: $fn_polars:ident,
     } ) => {
         impl $ty for LazySlice {
@@ -383,17 +670,39 @@ macro_rules! impl_expr_binary {
 
             fn $fn(self, rhs: Self) -> Self::Output {
                 match (self, rhs) {
+                    #[cfg(not(feature = "df-polars"))]
+                    (Self::Native(lhs), Self::Native(rhs)) => Self::Native(
+                        self::native::Expr::binary(self::native::BinaryOp::$op_native, lhs, rhs),
+                    ),
                     #[cfg(feature = "df-polars")]
                     (Self::Polars(lhs), Self::Polars(rhs)) => Self::Polars(lhs.$fn_polars(rhs)),
                 }
             }
         }
 
+        impl_expr_binary!(impl $ty ( $fn ) for $target as Scalar {
+            native: $op_native,
+            polars: $fn_polars,
+        });
+    };
+    ( impl $ty:ident ( $fn:ident ) for $target:ident as Scalar {
+        native: $op_native:ident,
+        polars: $fn_polars:ident,
+    } ) => {
         impl $ty<$target> for LazySlice {
             type Output = Self;
 
             fn $fn(self, rhs: $target) -> Self::Output {
                 match self {
+                    #[cfg(not(feature = "df-polars"))]
+                    Self::Native(lhs) => {
+                        let rhs = rhs.into_native();
+                        Self::Native(self::native::Expr::binary(
+                            self::native::BinaryOp::$op_native,
+                            lhs,
+                            rhs,
+                        ))
+                    }
                     #[cfg(feature = "df-polars")]
                     Self::Polars(lhs) => {
                         let rhs = rhs.into_polars();
@@ -408,6 +717,15 @@ macro_rules! impl_expr_binary {
 
             fn $fn(self, rhs: LazySlice) -> Self::Output {
                 match rhs {
+                    #[cfg(not(feature = "df-polars"))]
+                    LazySlice::Native(rhs) => {
+                        let lhs = self.into_native();
+                        LazySlice::Native(self::native::Expr::binary(
+                            self::native::BinaryOp::$op_native,
+                            lhs,
+                            rhs,
+                        ))
+                    }
                     #[cfg(feature = "df-polars")]
                     LazySlice::Polars(rhs) => {
                         let lhs = self.into_polars();
@@ -420,41 +738,61 @@ macro_rules! impl_expr_binary {
 }
 
 impl_expr_binary!(impl Add(add) for Number {
+    native: Add,
     polars: add,
 });
 impl_expr_binary!(impl Sub(sub) for Number {
+    native: Sub,
     polars: sub,
 });
 impl_expr_binary!(impl Mul(mul) for Number {
+    native: Mul,
     polars: mul,
 });
 impl_expr_binary!(impl Div(div) for Number {
+    native: Div,
     polars: div,
 });
 impl_expr_binary!(impl Eq(eq) for Number {
+    native: Eq,
     polars: eq,
 });
 impl_expr_binary!(impl Ne(ne) for Number {
+    native: Ne,
     polars: neq,
 });
 impl_expr_binary!(impl Ge(ge) for Number {
+    native: Ge,
     polars: gt_eq,
 });
 impl_expr_binary!(impl Gt(gt) for Number {
+    native: Gt,
     polars: gt,
 });
 impl_expr_binary!(impl Le(le) for Number {
+    native: Le,
     polars: lt_eq,
 });
 impl_expr_binary!(impl Lt(lt) for Number {
+    native: Lt,
     polars: lt,
 });
 impl_expr_binary!(impl And(and) for Feature {
+    native: And,
     polars: and,
 });
 impl_expr_binary!(impl Or(or) for Feature {
+    native: Or,
     polars: or,
 });
+impl_expr_binary!(impl Eq(eq) for String as Scalar {
+    native: Eq,
+    polars: eq,
+});
+impl_expr_binary!(impl Ne(ne) for String as Scalar {
+    native: Ne,
+    polars: neq,
+});
 
 pub trait IntoLazySlice {
     fn try_into_lazy_slice(self, df: &LazyFrame) -> Result<LazySlice>
@@ -463,11 +801,18 @@ pub trait IntoLazySlice {
     {
         match df {
             LazyFrame::Empty => bail!("cannot get slice from empty lazyframe"),
+            #[cfg(not(feature = "df-polars"))]
+            LazyFrame::Native(_) => Ok(LazySlice::Native(self.into_native())),
             #[cfg(feature = "df-polars")]
             LazyFrame::Polars(_) => Ok(LazySlice::Polars(self.into_polars())),
         }
     }
 
+    #[cfg(not(feature = "df-polars"))]
+    fn into_native(self) -> self::native::Expr
+    where
+        Self: Sized;
+
     #[cfg(feature = "df-polars")]
     fn into_polars(self) -> dsl::Expr
     where
@@ -475,13 +820,35 @@ pub trait IntoLazySlice {
 }
 
 impl IntoLazySlice for Feature {
+    #[cfg(not(feature = "df-polars"))]
+    fn into_native(self) -> self::native::Expr {
+        self::native::Expr::Literal(self::native::Value::Bool(self.into_inner()))
+    }
+
     #[cfg(feature = "df-polars")]
     fn into_polars(self) -> dsl::Expr {
         dsl::Expr::Literal(::pl::prelude::LiteralValue::Boolean(self.into_inner()))
     }
 }
 
+impl IntoLazySlice for String {
+    #[cfg(not(feature = "df-polars"))]
+    fn into_native(self) -> self::native::Expr {
+        self::native::Expr::Literal(self::native::Value::String(self))
+    }
+
+    #[cfg(feature = "df-polars")]
+    fn into_polars(self) -> dsl::Expr {
+        dsl::lit(self)
+    }
+}
+
 impl IntoLazySlice for Number {
+    #[cfg(not(feature = "df-polars"))]
+    fn into_native(self) -> self::native::Expr {
+        self::native::Expr::Literal(self::native::Value::Number(self.into_inner()))
+    }
+
     #[cfg(feature = "df-polars")]
     fn into_polars(self) -> dsl::Expr {
         dsl::Expr::Literal(::pl::prelude::LiteralValue::Int64(
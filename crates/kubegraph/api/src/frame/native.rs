@@ -0,0 +1,1013 @@
+use std::{collections::BTreeMap, fmt};
+
+use anyhow::{anyhow, bail, Result};
+use ordered_float::OrderedFloat;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    graph::{
+        GraphDataType, GraphEdgeDirection, GraphEdges, GraphEntry, GraphEntryValue,
+        GraphMetadataExt, GraphMetadataPinnedExt,
+    },
+    vm::{Feature, Number},
+};
+
+/// A minimal columnar dataframe, which is used when no other backend is enabled.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DataFrame {
+    columns: Vec<Column>,
+}
+
+impl fmt::Display for DataFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<_> = self
+            .columns
+            .iter()
+            .map(|column| column.name.as_str())
+            .collect();
+        writeln!(f, "{}", names.join("\t"))?;
+
+        for index in 0..self.height() {
+            let row: Vec<_> = self
+                .columns
+                .iter()
+                .map(|column| column.values[index].to_string())
+                .collect();
+            writeln!(f, "{}", row.join("\t"))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromIterator<GraphEntry> for DataFrame {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = GraphEntry>,
+    {
+        let entries: Vec<_> = iter.into_iter().collect();
+
+        let mut columns: Vec<Column> = Vec::default();
+        for (index, GraphEntry { others }) in entries.into_iter().enumerate() {
+            for (name, value) in others {
+                let position = match columns.iter().position(|column| column.name == name) {
+                    Some(position) => position,
+                    None => {
+                        columns.push(Column {
+                            name,
+                            values: vec![Value::Null; index],
+                        });
+                        columns.len() - 1
+                    }
+                };
+                columns[position].values.push(value.into());
+            }
+
+            // NOTE: fill the missing values of the current row
+            for column in &mut columns {
+                column.values.resize(index + 1, Value::Null);
+            }
+        }
+        Self { columns }
+    }
+}
+
+impl DataFrame {
+    pub fn new(columns: Vec<Column>) -> Result<Self> {
+        if let Some(first) = columns.first() {
+            let height = first.values.len();
+            if let Some(column) = columns.iter().find(|column| column.values.len() != height) {
+                bail!(
+                    "column length mismatch: {name:?} has {len} rows, but expected {height}",
+                    name = column.name,
+                    len = column.values.len(),
+                )
+            }
+        }
+        for (index, column) in columns.iter().enumerate() {
+            if columns[..index]
+                .iter()
+                .any(|other| other.name == column.name)
+            {
+                bail!("duplicated column: {name:?}", name = column.name)
+            }
+        }
+        Ok(Self { columns })
+    }
+
+    pub fn column(&self, name: &str) -> Option<&Column> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    pub fn column_names(&self) -> impl Iterator<Item = &str> {
+        self.columns.iter().map(|column| column.name.as_str())
+    }
+
+    pub fn height(&self) -> usize {
+        self.columns
+            .first()
+            .map(|column| column.values.len())
+            .unwrap_or_default()
+    }
+
+    /// Iterate the rows as graph entries, skipping the null values.
+    pub fn entries(&self) -> impl Iterator<Item = GraphEntry> + '_ {
+        (0..self.height()).map(|index| GraphEntry {
+            others: self
+                .columns
+                .iter()
+                .filter_map(|column| {
+                    let value = column.values[index].to_graph_entry_value()?;
+                    Some((column.name.clone(), value))
+                })
+                .collect(),
+        })
+    }
+
+    pub fn drop_null_columns(mut self) -> Self {
+        self.columns
+            .retain(|column| column.values.iter().any(|value| !value.is_null()));
+        self
+    }
+
+    pub fn head(mut self, limit: usize) -> Self {
+        for column in &mut self.columns {
+            column.values.truncate(limit);
+        }
+        self
+    }
+
+    /// Keep only the given columns, in order.
+    pub fn select<S>(&self, names: &[S]) -> Result<Self>
+    where
+        S: AsRef<str>,
+    {
+        names
+            .iter()
+            .map(|name| {
+                let name = name.as_ref();
+                self.column(name)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("no such column: {name:?}"))
+            })
+            .collect::<Result<_>>()
+            .map(|columns| Self { columns })
+    }
+
+    fn with_column(&mut self, name: String, values: Vec<Value>) {
+        match self.columns.iter_mut().find(|column| column.name == name) {
+            Some(column) => column.values = values,
+            None => self.columns.push(Column { name, values }),
+        }
+    }
+
+    fn filter(self, mask: &[Value]) -> Result<Self> {
+        let mask = mask
+            .iter()
+            .map(|value| match value {
+                Value::Null | Value::Bool(false) => Ok(false),
+                Value::Bool(true) => Ok(true),
+                value => bail!("cannot filter rows with a non-boolean value: {value}"),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            columns: self
+                .columns
+                .into_iter()
+                .map(|Column { name, values }| Column {
+                    name,
+                    values: values
+                        .into_iter()
+                        .zip(&mask)
+                        .filter(|(_, keep)| **keep)
+                        .map(|(value, _)| value)
+                        .collect(),
+                })
+                .collect(),
+        })
+    }
+
+    /// Stack the frames vertically, filling the missing columns with nulls.
+    fn concat_diagonal(frames: Vec<Self>) -> Self {
+        let mut names: Vec<String> = Vec::default();
+        for frame in &frames {
+            for name in frame.column_names() {
+                if !names.iter().any(|known| known == name) {
+                    names.push(name.into());
+                }
+            }
+        }
+
+        let columns = names
+            .into_iter()
+            .map(|name| {
+                let values = frames
+                    .iter()
+                    .flat_map(|frame| match frame.column(&name) {
+                        Some(column) => column.values.clone(),
+                        None => vec![Value::Null; frame.height()],
+                    })
+                    .collect();
+                Column { name, values }
+            })
+            .collect();
+        Self { columns }
+    }
+
+    fn cross_join(&self, other: &Self) -> Self {
+        let height_a = self.height();
+        let height_b = other.height();
+
+        let columns_a = self.columns.iter().map(|Column { name, values }| Column {
+            name: name.clone(),
+            values: values
+                .iter()
+                .flat_map(|value| ::std::iter::repeat(value.clone()).take(height_b))
+                .collect(),
+        });
+        let columns_b = other.columns.iter().map(|Column { name, values }| Column {
+            name: name.clone(),
+            values: (0..height_a).flat_map(|_| values.iter().cloned()).collect(),
+        });
+        Self {
+            columns: columns_a.chain(columns_b).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Column {
+    pub name: String,
+    pub values: Vec<Value>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Value {
+    #[default]
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null => "null".fmt(f),
+            Self::Bool(value) => value.fmt(f),
+            Self::Number(value) => value.fmt(f),
+            Self::String(value) => value.fmt(f),
+        }
+    }
+}
+
+impl From<GraphEntryValue> for Value {
+    fn from(value: GraphEntryValue) -> Self {
+        match value {
+            GraphEntryValue::Feature(value) => Self::Bool(value.into_inner()),
+            GraphEntryValue::Number(value) => Self::Number(value.into_inner()),
+            GraphEntryValue::String(value) => Self::String(value),
+        }
+    }
+}
+
+impl Value {
+    pub const fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            Self::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    fn to_graph_entry_value(&self) -> Option<GraphEntryValue> {
+        match self {
+            Self::Null => None,
+            Self::Bool(value) => Some(GraphEntryValue::Feature(Feature::new(*value))),
+            Self::Number(value) => Some(GraphEntryValue::Number(Number::new(*value))),
+            Self::String(value) => Some(GraphEntryValue::String(value.clone())),
+        }
+    }
+
+    fn to_group_key(&self) -> GroupKey {
+        match self {
+            Self::Null => GroupKey::Null,
+            Self::Bool(value) => GroupKey::Bool(*value),
+            Self::Number(value) => GroupKey::Number(OrderedFloat(*value)),
+            Self::String(value) => GroupKey::String(value.clone()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum GroupKey {
+    Null,
+    Bool(bool),
+    Number(OrderedFloat<f64>),
+    String(String),
+}
+
+/// A query plan over a [`DataFrame`], which is executed on collecting.
+#[derive(Clone, Debug, Default)]
+pub struct LazyFrame {
+    input: Input,
+    ops: Vec<Operation>,
+}
+
+impl From<DataFrame> for LazyFrame {
+    fn from(df: DataFrame) -> Self {
+        Self {
+            input: Input::Frame(df),
+            ops: Vec::default(),
+        }
+    }
+}
+
+impl From<DataFrame> for super::LazyFrame {
+    fn from(df: DataFrame) -> Self {
+        Self::Native(df.into())
+    }
+}
+
+impl From<LazyFrame> for super::LazyFrame {
+    fn from(df: LazyFrame) -> Self {
+        Self::Native(df)
+    }
+}
+
+impl FromIterator<GraphEdges<LazyFrame>> for GraphEdges<super::LazyFrame> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = GraphEdges<LazyFrame>>,
+    {
+        let inputs: Vec<_> = iter.into_iter().map(|GraphEdges(edges)| edges).collect();
+        Self(super::LazyFrame::Native(LazyFrame {
+            input: Input::Concat(inputs),
+            ops: Vec::default(),
+        }))
+    }
+}
+
+impl LazyFrame {
+    pub fn collect(self) -> Result<DataFrame> {
+        let Self { input, ops } = self;
+
+        let df = match input {
+            Input::Frame(df) => df,
+            Input::Concat(inputs) => DataFrame::concat_diagonal(
+                inputs
+                    .into_iter()
+                    .map(Self::collect)
+                    .collect::<Result<_>>()?,
+            ),
+        };
+        ops.into_iter().try_fold(df, Operation::apply)
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.ops.push(Operation::Limit(limit));
+        self
+    }
+
+    pub(super) fn filter(&mut self, filter: Expr) {
+        self.ops.push(Operation::Filter(filter))
+    }
+
+    pub(super) fn with_column(&mut self, name: &str, expr: Expr) {
+        self.ops.push(Operation::WithColumn(name.into(), expr))
+    }
+
+    pub(super) fn select(&mut self, exprs: Vec<(Expr, String)>) {
+        self.ops.push(Operation::Select(exprs))
+    }
+
+    /// Infer the column names without executing the query.
+    fn schema(&self) -> Vec<String> {
+        let mut names = match &self.input {
+            Input::Frame(df) => df.column_names().map(Into::into).collect(),
+            Input::Concat(inputs) => {
+                let mut names: Vec<String> = Vec::default();
+                for name in inputs.iter().flat_map(Self::schema) {
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+                names
+            }
+        };
+
+        for op in &self.ops {
+            match op {
+                Operation::Filter(_) | Operation::Limit(_) => (),
+                Operation::Select(exprs) => {
+                    names = exprs.iter().map(|(_, name)| name.clone()).collect();
+                }
+                Operation::WithColumn(name, _) => {
+                    if !names.contains(name) {
+                        names.push(name.clone());
+                    }
+                }
+            }
+        }
+        names
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Input {
+    Frame(DataFrame),
+    /// Stack the frames diagonally.
+    Concat(Vec<LazyFrame>),
+}
+
+impl Default for Input {
+    fn default() -> Self {
+        Self::Frame(DataFrame::default())
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Operation {
+    Filter(Expr),
+    Limit(usize),
+    Select(Vec<(Expr, String)>),
+    WithColumn(String, Expr),
+}
+
+impl Operation {
+    fn apply(self, df: DataFrame) -> Result<DataFrame> {
+        match self {
+            Self::Filter(filter) => {
+                let mask = filter.evaluate(&df)?.into_values(df.height());
+                df.filter(&mask)
+            }
+            Self::Limit(limit) => Ok(df.head(limit)),
+            Self::Select(exprs) => {
+                let height = df.height();
+                exprs
+                    .into_iter()
+                    .map(|(expr, name)| {
+                        expr.evaluate(&df).map(|series| Column {
+                            name,
+                            values: series.into_values(height),
+                        })
+                    })
+                    .collect::<Result<_>>()
+                    .and_then(DataFrame::new)
+            }
+            Self::WithColumn(name, expr) => {
+                let height = df.height();
+                let values = expr.evaluate(&df)?.into_values(height);
+
+                let mut df = df;
+                df.with_column(name, values);
+                Ok(df)
+            }
+        }
+    }
+}
+
+/// An expression over the columns of a [`DataFrame`].
+#[derive(Clone, Debug)]
+pub enum Expr {
+    Column(String),
+    Literal(Value),
+    Unary(UnaryOp, Box<Self>),
+    Binary(BinaryOp, Box<Self>, Box<Self>),
+    Select {
+        cond: Box<Self>,
+        then: Box<Self>,
+        otherwise: Box<Self>,
+    },
+    /// Test whether the strings contain the regex pattern.
+    Matches(Box<Self>, String),
+    Aggregate {
+        op: AggregateOp,
+        src: Box<Self>,
+        group: Option<Box<Self>>,
+    },
+}
+
+impl Expr {
+    pub(super) fn aggregate(op: AggregateOp, src: Self, group: Option<Self>) -> Self {
+        Self::Aggregate {
+            op,
+            src: Box::new(src),
+            group: group.map(Box::new),
+        }
+    }
+
+    pub(super) fn binary(op: BinaryOp, lhs: Self, rhs: Self) -> Self {
+        Self::Binary(op, Box::new(lhs), Box::new(rhs))
+    }
+
+    pub(super) fn select(cond: Self, then: Self, otherwise: Self) -> Self {
+        Self::Select {
+            cond: Box::new(cond),
+            then: Box::new(then),
+            otherwise: Box::new(otherwise),
+        }
+    }
+
+    pub(super) fn unary(op: UnaryOp, src: Self) -> Self {
+        Self::Unary(op, Box::new(src))
+    }
+
+    fn evaluate(&self, df: &DataFrame) -> Result<Series> {
+        match self {
+            Self::Column(name) => df
+                .column(name)
+                .map(|column| Series::Column(column.values.clone()))
+                .ok_or_else(|| anyhow!("no such column: {name:?}")),
+            Self::Literal(value) => Ok(Series::Scalar(value.clone())),
+            Self::Unary(op, src) => src.evaluate(df)?.map(|value| op.apply(value)),
+            Self::Binary(op, lhs, rhs) => {
+                Series::zip(lhs.evaluate(df)?, rhs.evaluate(df)?, |lhs, rhs| {
+                    op.apply(lhs, rhs)
+                })
+            }
+            Self::Select {
+                cond,
+                then,
+                otherwise,
+            } => {
+                let height = df.height();
+                let cond = cond.evaluate(df)?;
+                let then = then.evaluate(df)?;
+                let otherwise = otherwise.evaluate(df)?;
+
+                match (cond, then, otherwise) {
+                    (Series::Scalar(cond), then, otherwise) => match cond {
+                        Value::Bool(true) => Ok(then),
+                        Value::Null | Value::Bool(false) => Ok(otherwise),
+                        cond => bail!("cannot select values with a non-boolean value: {cond}"),
+                    },
+                    (cond, then, otherwise) => cond
+                        .into_values(height)
+                        .into_iter()
+                        .zip(then.into_values(height))
+                        .zip(otherwise.into_values(height))
+                        .map(|((cond, then), otherwise)| match cond {
+                            Value::Bool(true) => Ok(then),
+                            Value::Null | Value::Bool(false) => Ok(otherwise),
+                            cond => {
+                                bail!("cannot select values with a non-boolean value: {cond}")
+                            }
+                        })
+                        .collect::<Result<_>>()
+                        .map(Series::Column),
+                }
+            }
+            Self::Matches(src, pattern) => {
+                let pattern = Regex::new(pattern)
+                    .map_err(|error| anyhow!("failed to parse regex pattern: {error}"))?;
+                src.evaluate(df)?.map(|value| match value {
+                    Value::Null => Ok(Value::Null),
+                    Value::String(value) => Ok(Value::Bool(pattern.is_match(&value))),
+                    value => bail!("cannot match a non-string value: {value}"),
+                })
+            }
+            Self::Aggregate { op, src, group } => {
+                let height = df.height();
+                let src = src.evaluate(df)?.into_values(height);
+                match group {
+                    None => op.apply(src).map(Series::Scalar),
+                    Some(group) => {
+                        let keys: Vec<_> = group
+                            .evaluate(df)?
+                            .into_values(height)
+                            .iter()
+                            .map(Value::to_group_key)
+                            .collect();
+
+                        let mut groups: BTreeMap<_, Vec<_>> = BTreeMap::default();
+                        for (key, value) in keys.iter().zip(src) {
+                            groups.entry(key.clone()).or_default().push(value);
+                        }
+                        let groups = groups
+                            .into_iter()
+                            .map(|(key, values)| op.apply(values).map(|value| (key, value)))
+                            .collect::<Result<BTreeMap<_, _>>>()?;
+
+                        Ok(Series::Column(
+                            keys.iter()
+                                .map(|key| groups.get(key).cloned().unwrap_or_default())
+                                .collect(),
+                        ))
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum UnaryOp {
+    Neg,
+    Not,
+}
+
+impl UnaryOp {
+    fn apply(self, value: Value) -> Result<Value> {
+        match (self, value) {
+            (_, Value::Null) => Ok(Value::Null),
+            (Self::Neg, Value::Number(value)) => Ok(Value::Number(-value)),
+            (Self::Not, Value::Bool(value)) => Ok(Value::Bool(!value)),
+            (op, value) => bail!("cannot apply {op:?} to {value}"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Eq,
+    Ne,
+    Ge,
+    Gt,
+    Le,
+    Lt,
+    And,
+    Or,
+}
+
+impl BinaryOp {
+    fn apply(self, lhs: Value, rhs: Value) -> Result<Value> {
+        match (self, lhs, rhs) {
+            (_, Value::Null, _) | (_, _, Value::Null) => Ok(Value::Null),
+            (Self::Add, Value::Number(lhs), Value::Number(rhs)) => Ok(Value::Number(lhs + rhs)),
+            (Self::Sub, Value::Number(lhs), Value::Number(rhs)) => Ok(Value::Number(lhs - rhs)),
+            (Self::Mul, Value::Number(lhs), Value::Number(rhs)) => Ok(Value::Number(lhs * rhs)),
+            (Self::Div, Value::Number(lhs), Value::Number(rhs)) => Ok(Value::Number(lhs / rhs)),
+            (Self::And, Value::Bool(lhs), Value::Bool(rhs)) => Ok(Value::Bool(lhs && rhs)),
+            (Self::Or, Value::Bool(lhs), Value::Bool(rhs)) => Ok(Value::Bool(lhs || rhs)),
+            (op @ (Self::Eq | Self::Ne | Self::Ge | Self::Gt | Self::Le | Self::Lt), lhs, rhs) => {
+                let ordering = match (&lhs, &rhs) {
+                    (Value::Bool(lhs), Value::Bool(rhs)) => lhs.partial_cmp(rhs),
+                    (Value::Number(lhs), Value::Number(rhs)) => lhs.partial_cmp(rhs),
+                    (Value::String(lhs), Value::String(rhs)) => lhs.partial_cmp(rhs),
+                    _ => bail!("cannot compare {lhs} and {rhs}"),
+                };
+                Ok(match ordering {
+                    Some(ordering) => Value::Bool(match op {
+                        Self::Eq => ordering.is_eq(),
+                        Self::Ne => ordering.is_ne(),
+                        Self::Ge => ordering.is_ge(),
+                        Self::Gt => ordering.is_gt(),
+                        Self::Le => ordering.is_le(),
+                        _ => ordering.is_lt(),
+                    }),
+                    // NOTE: NaN is not comparable
+                    None => Value::Null,
+                })
+            }
+            (op, lhs, rhs) => bail!("cannot apply {op:?} to {lhs} and {rhs}"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AggregateOp {
+    Max,
+    Min,
+    Sum,
+}
+
+impl AggregateOp {
+    /// Aggregate the values, skipping the nulls.
+    fn apply(self, values: Vec<Value>) -> Result<Value> {
+        let values = values
+            .into_iter()
+            .filter(|value| !value.is_null())
+            .map(|value| {
+                value
+                    .as_number()
+                    .ok_or_else(|| anyhow!("cannot aggregate {self:?} with {value}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(match self {
+            Self::Max => values
+                .into_iter()
+                .reduce(f64::max)
+                .map(Value::Number)
+                .unwrap_or_default(),
+            Self::Min => values
+                .into_iter()
+                .reduce(f64::min)
+                .map(Value::Number)
+                .unwrap_or_default(),
+            Self::Sum => Value::Number(values.into_iter().sum()),
+        })
+    }
+}
+
+enum Series {
+    Scalar(Value),
+    Column(Vec<Value>),
+}
+
+impl Series {
+    fn into_values(self, height: usize) -> Vec<Value> {
+        match self {
+            Self::Scalar(value) => vec![value; height],
+            Self::Column(values) => values,
+        }
+    }
+
+    fn map<F>(self, f: F) -> Result<Self>
+    where
+        F: Fn(Value) -> Result<Value>,
+    {
+        match self {
+            Self::Scalar(value) => f(value).map(Self::Scalar),
+            Self::Column(values) => values
+                .into_iter()
+                .map(f)
+                .collect::<Result<_>>()
+                .map(Self::Column),
+        }
+    }
+
+    fn zip<F>(lhs: Self, rhs: Self, f: F) -> Result<Self>
+    where
+        F: Fn(Value, Value) -> Result<Value>,
+    {
+        match (lhs, rhs) {
+            (Self::Scalar(lhs), Self::Scalar(rhs)) => f(lhs, rhs).map(Self::Scalar),
+            (Self::Scalar(lhs), Self::Column(rhs)) => rhs
+                .into_iter()
+                .map(|rhs| f(lhs.clone(), rhs))
+                .collect::<Result<_>>()
+                .map(Self::Column),
+            (Self::Column(lhs), Self::Scalar(rhs)) => lhs
+                .into_iter()
+                .map(|lhs| f(lhs, rhs.clone()))
+                .collect::<Result<_>>()
+                .map(Self::Column),
+            (Self::Column(lhs), Self::Column(rhs)) => {
+                if lhs.len() != rhs.len() {
+                    bail!(
+                        "column length mismatch: {len_lhs} and {len_rhs}",
+                        len_lhs = lhs.len(),
+                        len_rhs = rhs.len(),
+                    )
+                }
+                lhs.into_iter()
+                    .zip(rhs)
+                    .map(|(lhs, rhs)| f(lhs, rhs))
+                    .collect::<Result<_>>()
+                    .map(Self::Column)
+            }
+        }
+    }
+}
+
+pub(super) fn cast<MF, MT>(mut df: LazyFrame, ty: GraphDataType, from: &MF, to: &MT) -> LazyFrame
+where
+    MF: GraphMetadataExt,
+    MT: GraphMetadataPinnedExt,
+{
+    let col = |from: &str, to: &str| (Expr::Column(from.into()), to.to_string());
+
+    let exprs = match ty {
+        GraphDataType::Edge => {
+            let mut exprs = vec![
+                col(from.src(), to.src()),
+                col(from.sink(), to.sink()),
+                col(from.capacity(), to.capacity()),
+                col(from.unit_cost(), to.unit_cost()),
+            ];
+            // NOTE: the edges are directed unless specified
+            if df.schema().iter().any(|name| name == from.direction()) {
+                exprs.push(col(from.direction(), to.direction()));
+            }
+            exprs
+        }
+        GraphDataType::Node => vec![
+            col(from.name(), to.name()),
+            col(from.capacity(), to.capacity()),
+            col(from.supply(), to.supply()),
+            col(from.unit_cost(), to.unit_cost()),
+        ],
+    };
+
+    df.select(exprs);
+    df
+}
+
+pub(super) fn concat(a: LazyFrame, b: LazyFrame) -> LazyFrame {
+    LazyFrame {
+        input: Input::Concat(vec![a, b]),
+        ops: Vec::default(),
+    }
+}
+
+pub(super) fn count_rows(df: LazyFrame) -> Result<usize> {
+    df.collect().map(|df| df.height())
+}
+
+/// Create a fully-connected edges, which executes the nodes query.
+pub(super) fn fabric<M>(
+    nodes: LazyFrame,
+    metadata: &M,
+    direction: GraphEdgeDirection,
+    capacity: u64,
+) -> Result<LazyFrame>
+where
+    M: GraphMetadataPinnedExt,
+{
+    let nodes = nodes
+        .collect()
+        .map_err(|error| anyhow!("failed to collect nodes: {error}"))?;
+
+    let select_edge_side = |side: &str| DataFrame {
+        columns: nodes
+            .columns
+            .iter()
+            .map(|Column { name, values }| Column {
+                name: if name == metadata.name() {
+                    side.into()
+                } else {
+                    format!("{side}.{name}")
+                },
+                values: values.clone(),
+            })
+            .collect(),
+    };
+
+    let mut edges: LazyFrame = select_edge_side(metadata.src())
+        .cross_join(&select_edge_side(metadata.sink()))
+        .into();
+    match direction {
+        GraphEdgeDirection::Directed => (),
+        // NOTE: the reversed edges are mirrored by the solvers
+        GraphEdgeDirection::Undirected => edges.filter(Expr::binary(
+            BinaryOp::Lt,
+            Expr::Column(metadata.src().into()),
+            Expr::Column(metadata.sink().into()),
+        )),
+    }
+    edges.with_column(
+        metadata.capacity(),
+        Expr::Literal(Value::Number(capacity as f64)),
+    );
+    edges.with_column(
+        metadata.direction(),
+        Expr::Literal(Value::String(direction.as_str().into())),
+    );
+    Ok(edges)
+}
+
+pub(super) fn flow_cost<M>(edges: LazyFrame, metadata: &M) -> Result<f64>
+where
+    M: GraphMetadataPinnedExt,
+{
+    let key = "cost";
+    let mut edges = edges;
+    edges.select(vec![(
+        Expr::aggregate(
+            AggregateOp::Sum,
+            Expr::binary(
+                BinaryOp::Mul,
+                Expr::Column(metadata.flow().into()),
+                Expr::Column(metadata.unit_cost().into()),
+            ),
+            None,
+        ),
+        key.into(),
+    )]);
+
+    let df = edges
+        .collect()
+        .map_err(|error| anyhow!("failed to sum up edge flow costs: {error}"))?;
+    Ok(df
+        .column(key)
+        .and_then(|column| column.values.first())
+        .and_then(Value::as_number)
+        .unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> DataFrame {
+        DataFrame::new(vec![
+            Column {
+                name: "name".into(),
+                values: vec![
+                    Value::String("a".into()),
+                    Value::String("b".into()),
+                    Value::String("c".into()),
+                ],
+            },
+            Column {
+                name: "group".into(),
+                values: vec![
+                    Value::String("x".into()),
+                    Value::String("x".into()),
+                    Value::String("y".into()),
+                ],
+            },
+            Column {
+                name: "capacity".into(),
+                values: vec![Value::Number(1.0), Value::Number(2.0), Value::Null],
+            },
+        ])
+        .expect("failed to create a sample dataframe")
+    }
+
+    #[test]
+    fn filter_and_with_column() {
+        let mut df: LazyFrame = sample().into();
+        df.with_column(
+            "double",
+            Expr::binary(
+                BinaryOp::Mul,
+                Expr::Column("capacity".into()),
+                Expr::Literal(Value::Number(2.0)),
+            ),
+        );
+        df.filter(Expr::binary(
+            BinaryOp::Gt,
+            Expr::Column("capacity".into()),
+            Expr::Literal(Value::Number(1.0)),
+        ));
+
+        let df = df.collect().expect("failed to collect");
+        assert_eq!(df.height(), 1);
+        assert_eq!(
+            df.column("double").map(|column| column.values.clone()),
+            Some(vec![Value::Number(4.0)]),
+        );
+    }
+
+    #[test]
+    fn aggregate_over_groups() {
+        let mut df: LazyFrame = sample().into();
+        df.with_column(
+            "total",
+            Expr::aggregate(
+                AggregateOp::Sum,
+                Expr::Column("capacity".into()),
+                Some(Expr::Column("group".into())),
+            ),
+        );
+
+        let df = df.collect().expect("failed to collect");
+        assert_eq!(
+            df.column("total").map(|column| column.values.clone()),
+            Some(vec![
+                Value::Number(3.0),
+                Value::Number(3.0),
+                Value::Number(0.0),
+            ]),
+        );
+    }
+
+    #[test]
+    fn concat_diagonally() {
+        let a = sample();
+        let b = DataFrame::new(vec![Column {
+            name: "extra".into(),
+            values: vec![Value::Bool(true)],
+        }])
+        .expect("failed to create a dataframe");
+
+        let GraphEdges(df) = [GraphEdges(a.into()), GraphEdges(b.into())]
+            .into_iter()
+            .collect::<GraphEdges<super::super::LazyFrame>>();
+        let df = match df {
+            super::super::LazyFrame::Native(df) => df.collect().expect("failed to collect"),
+            _ => unreachable!(),
+        };
+
+        assert_eq!(df.height(), 4);
+        assert_eq!(
+            df.column_names().collect::<Vec<_>>(),
+            ["name", "group", "capacity", "extra"],
+        );
+        assert_eq!(
+            df.column("extra").map(|column| column.values.clone()),
+            Some(vec![
+                Value::Null,
+                Value::Null,
+                Value::Null,
+                Value::Bool(true)
+            ]),
+        );
+    }
+}
@@ -11,7 +11,10 @@ use pl::{
 };
 
 use crate::{
-    graph::{GraphDataType, GraphEdges, GraphMetadataExt, GraphMetadataPinnedExt},
+    graph::{
+        GraphData, GraphDataType, GraphEdgeDirection, GraphEdges, GraphMetadataExt,
+        GraphMetadataPinnedExt,
+    },
     vm::{Feature, Number},
 };
 
@@ -27,6 +30,16 @@ impl From<LazyFrame> for super::LazyFrame {
     }
 }
 
+impl From<GraphData<LazyFrame>> for GraphData<super::LazyFrame> {
+    fn from(graph: GraphData<LazyFrame>) -> Self {
+        let GraphData { edges, nodes } = graph;
+        Self {
+            edges: edges.into(),
+            nodes: nodes.into(),
+        }
+    }
+}
+
 impl FromIterator<GraphEdges<LazyFrame>> for GraphEdges<super::LazyFrame> {
     fn from_iter<I>(iter: I) -> Self
     where
@@ -45,19 +58,26 @@ impl FromIterator<GraphEdges<LazyFrame>> for GraphEdges<super::LazyFrame> {
     }
 }
 
-pub(super) fn cast<MF, MT>(df: LazyFrame, ty: GraphDataType, from: &MF, to: &MT) -> LazyFrame
+pub(super) fn cast<MF, MT>(mut df: LazyFrame, ty: GraphDataType, from: &MF, to: &MT) -> LazyFrame
 where
     MF: GraphMetadataExt,
     MT: GraphMetadataPinnedExt,
 {
     let exprs = match ty {
-        GraphDataType::Edge => [
-            dsl::col(from.src()).alias(to.src()),
-            dsl::col(from.sink()).alias(to.sink()),
-            dsl::col(from.capacity()).alias(to.capacity()),
-            dsl::col(from.unit_cost()).alias(to.unit_cost()),
-        ],
-        GraphDataType::Node => [
+        GraphDataType::Edge => {
+            let mut exprs = vec![
+                dsl::col(from.src()).alias(to.src()),
+                dsl::col(from.sink()).alias(to.sink()),
+                dsl::col(from.capacity()).alias(to.capacity()),
+                dsl::col(from.unit_cost()).alias(to.unit_cost()),
+            ];
+            // NOTE: the edges are directed unless specified
+            if has_column(&mut df, from.direction()) {
+                exprs.push(dsl::col(from.direction()).alias(to.direction()));
+            }
+            exprs
+        }
+        GraphDataType::Node => vec![
             dsl::col(from.name()).alias(to.name()),
             dsl::col(from.capacity()).alias(to.capacity()),
             dsl::col(from.supply()).alias(to.supply()),
@@ -76,6 +96,101 @@ pub(super) fn concat(a: LazyFrame, b: LazyFrame) -> Result<LazyFrame> {
     dsl::concat([a, b], args).map_err(Into::into)
 }
 
+pub(super) fn count_rows(df: LazyFrame) -> Result<usize> {
+    let key = "len";
+    let df = df
+        .select([dsl::len().cast(DataType::UInt64).alias(key)])
+        .collect()
+        .map_err(|error| anyhow!("failed to count rows: {error}"))?;
+
+    get_column(&df, "frame", key, key, None)?
+        .u64()
+        .map_err(|error| anyhow!("failed to parse the number of rows: {error}"))?
+        .get(0)
+        .map(|len| len as usize)
+        .ok_or_else(|| anyhow!("failed to get the number of rows"))
+}
+
+pub(super) fn flow_cost<M>(edges: LazyFrame, metadata: &M) -> Result<f64>
+where
+    M: GraphMetadataPinnedExt,
+{
+    let key = "cost";
+    let df = edges
+        .select([(dsl::col(metadata.flow()).cast(DataType::Float64)
+            * dsl::col(metadata.unit_cost()).cast(DataType::Float64))
+        .sum()
+        .alias(key)])
+        .collect()
+        .map_err(|error| anyhow!("failed to sum up edge flow costs: {error}"))?;
+
+    Ok(get_column(&df, "edge", key, key, None)?
+        .f64()
+        .map_err(|error| anyhow!("failed to parse edge flow costs: {error}"))?
+        .get(0)
+        .unwrap_or_default())
+}
+
+fn has_column(df: &mut LazyFrame, name: &str) -> bool {
+    df.collect_schema()
+        .map(|schema| schema.contains(name))
+        .unwrap_or_default()
+}
+
+/// Append the reversed edges of the undirected edges, so that the solvers can treat
+/// all the edges as directed ones.
+///
+/// The side columns (e.g. `src.capacity` and `sink.capacity`) are swapped as well.
+pub fn mirror_undirected_edges<M>(mut edges: LazyFrame, metadata: &M) -> Result<LazyFrame>
+where
+    M: GraphMetadataPinnedExt,
+{
+    let key_direction = metadata.direction();
+    let key_sink = metadata.sink();
+    let key_src = metadata.src();
+
+    let schema = edges
+        .collect_schema()
+        .map_err(|error| anyhow!("failed to get edges schema: {error}"))?;
+    if !schema.contains(key_direction) {
+        return Ok(edges);
+    }
+
+    let prefix_sink = format!("{key_sink}.");
+    let prefix_src = format!("{key_src}.");
+    let exprs: Vec<_> = schema
+        .iter_names()
+        .map(|name| {
+            let name = name.as_str();
+            let mirrored = if name == key_src {
+                key_sink.into()
+            } else if name == key_sink {
+                key_src.into()
+            } else if let Some(suffix) = name.strip_prefix(&prefix_src) {
+                format!("{prefix_sink}{suffix}")
+            } else if let Some(suffix) = name.strip_prefix(&prefix_sink) {
+                format!("{prefix_src}{suffix}")
+            } else {
+                name.to_string()
+            };
+            dsl::col(name).alias(mirrored)
+        })
+        .collect();
+
+    let mirrored_edges = edges
+        .clone()
+        .filter(dsl::col(key_direction).eq(dsl::lit(GraphEdgeDirection::Undirected.as_str())))
+        .select(exprs);
+
+    let args = UnionArgs {
+        rechunk: true,
+        to_supertypes: true,
+        ..Default::default()
+    };
+    dsl::concat_lf_diagonal([edges, mirrored_edges], args)
+        .map_err(|error| anyhow!("failed to mirror undirected edges: {error}"))
+}
+
 pub fn get_column(
     df: &DataFrame,
     kind: &str,
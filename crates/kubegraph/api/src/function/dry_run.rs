@@ -0,0 +1,122 @@
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    frame::{DataFrame, LazyFrame},
+    graph::{GraphMetadataPinned, GraphMetadataPinnedExt},
+    problem::VirtualProblem,
+};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkFunctionDryRunRequest {
+    pub problem: VirtualProblem<GraphMetadataPinned>,
+    /// The name of the function in the namespace of the problem
+    pub function: String,
+    /// The maximum number of the sampled rows
+    #[serde(default = "NetworkFunctionDryRunRequest::default_limit")]
+    pub limit: u32,
+}
+
+impl NetworkFunctionDryRunRequest {
+    const fn default_limit() -> u32 {
+        10
+    }
+}
+
+/// The outputs of a function, which are not committed to the pipeline.
+pub struct NetworkFunctionDryRunOutput {
+    /// The columns provided by the function
+    pub columns: Vec<String>,
+    pub data: LazyFrame,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkFunctionDryRunReport {
+    /// The columns provided by the function
+    pub columns: Vec<String>,
+    /// The sampled rows of the provided columns, with the node (or edge) keys
+    pub data: DataFrame,
+}
+
+impl NetworkFunctionDryRunReport {
+    pub fn sample<M>(output: NetworkFunctionDryRunOutput, metadata: &M, limit: u32) -> Result<Self>
+    where
+        M: GraphMetadataPinnedExt,
+    {
+        let NetworkFunctionDryRunOutput { columns, data } = output;
+
+        let keys: Vec<_> = [metadata.name(), metadata.src(), metadata.sink()]
+            .into_iter()
+            .chain(columns.iter().map(String::as_str))
+            .collect();
+
+        let data = match data {
+            LazyFrame::Empty => DataFrame::Empty,
+            #[cfg(not(feature = "df-polars"))]
+            LazyFrame::Native(df) => DataFrame::Native(sample_native(df, &keys, limit)?),
+            #[cfg(feature = "df-polars")]
+            LazyFrame::Polars(df) => DataFrame::Polars(sample_polars(df, &keys, limit)?),
+        };
+        Ok(Self { columns, data })
+    }
+}
+
+#[cfg(not(feature = "df-polars"))]
+fn sample_native(
+    df: crate::frame::native::LazyFrame,
+    keys: &[&str],
+    limit: u32,
+) -> Result<crate::frame::native::DataFrame> {
+    let df = df
+        .limit(limit as usize)
+        .collect()
+        .map_err(|error| ::anyhow::anyhow!("failed to collect function outputs: {error}"))?;
+
+    // NOTE: the edge features are prefixed with their sides, e.g. `src.capacity`
+    let columns: Vec<_> = df
+        .column_names()
+        .filter(|&name| {
+            let feature = name
+                .split_once('.')
+                .map(|(_, feature)| feature)
+                .unwrap_or(name);
+            keys.contains(&name) || keys.contains(&feature)
+        })
+        .collect();
+
+    df.select(&columns)
+        .map_err(|error| ::anyhow::anyhow!("failed to select function outputs: {error}"))
+}
+
+#[cfg(feature = "df-polars")]
+fn sample_polars(
+    df: ::pl::lazy::frame::LazyFrame,
+    keys: &[&str],
+    limit: u32,
+) -> Result<::pl::frame::DataFrame> {
+    let df = df
+        .limit(limit)
+        .collect()
+        .map_err(|error| ::anyhow::anyhow!("failed to collect function outputs: {error}"))?;
+
+    // NOTE: the edge features are prefixed with their sides, e.g. `src.capacity`
+    let columns: Vec<_> = df
+        .get_column_names()
+        .into_iter()
+        .filter(|name| {
+            let name = name.as_str();
+            let feature = name
+                .split_once('.')
+                .map(|(_, feature)| feature)
+                .unwrap_or(name);
+            keys.contains(&name) || keys.contains(&feature)
+        })
+        .cloned()
+        .collect();
+
+    df.select(columns)
+        .map_err(|error| ::anyhow::anyhow!("failed to select function outputs: {error}"))
+}
@@ -1,5 +1,6 @@
 pub mod annotation;
 pub mod call;
+pub mod dry_run;
 #[cfg(feature = "function-fake")]
 pub mod fake;
 #[cfg(feature = "function-entrypoint")]
@@ -7,7 +8,7 @@ pub mod service;
 pub mod spawn;
 pub mod webhook;
 
-use kube::{CustomResource, CustomResourceExt};
+use kube::{CustomResource, CustomResourceExt, ResourceExt};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -42,6 +43,19 @@ pub struct NetworkFunctionSpec {
     pub template: NetworkFunctionTemplate,
 }
 
+impl NetworkFunctionCrd {
+    /// The annotation which excludes the function from the live pipeline,
+    /// so that it can be evaluated only with the dry-run requests.
+    pub const ANNOTATION_DRY_RUN: &'static str = "kubegraph.ulagbulag.io/dry-run";
+
+    pub fn is_dry_run(&self) -> bool {
+        self.annotations()
+            .get(Self::ANNOTATION_DRY_RUN)
+            .map(|value| value == "true")
+            .unwrap_or_default()
+    }
+}
+
 impl NetworkResource for NetworkFunctionCrd {
     type Filter = ();
 
@@ -0,0 +1,278 @@
+use std::{collections::BTreeMap, fmt};
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use k8s_openapi::api::{
+    authentication::v1::{TokenReview, TokenReviewSpec, TokenReviewStatus, UserInfo},
+    authorization::v1::{
+        ResourceAttributes, SubjectAccessReview, SubjectAccessReviewSpec, SubjectAccessReviewStatus,
+    },
+};
+use kube::{api::PostParams, core::ObjectMeta, Api, Client};
+use tracing::{instrument, Level};
+
+use crate::frame::LazyFrame;
+
+use super::{Graph, GraphData, GraphFilter, GraphProjection, GraphScope, NetworkGraphDB};
+
+/// Reviews the graph requests with the Kubernetes RBAC.
+///
+/// The graphs are treated as a virtual namespaced resource (`graphs.kubegraph.ulagbulag.io`),
+/// so that the tenants can be granted with the plain `Role`s.
+#[derive(Clone)]
+pub enum NetworkGraphAuthorizer {
+    /// Allow all requests, e.g. for the trusted in-cluster clients.
+    Disabled,
+    /// Authenticate the bearer tokens (`TokenReview`) and authorize the subjects
+    /// per namespace (`SubjectAccessReview`).
+    Kubernetes { kube: Client },
+}
+
+impl NetworkGraphAuthorizer {
+    pub const API_GROUP: &'static str = "kubegraph.ulagbulag.io";
+    pub const RESOURCE: &'static str = "graphs";
+
+    pub async fn try_new(enabled: bool) -> Result<Self> {
+        if enabled {
+            Client::try_default()
+                .await
+                .map(|kube| Self::Kubernetes { kube })
+                .map_err(|error| anyhow!("failed to load kubernetes client: {error}"))
+        } else {
+            Ok(Self::Disabled)
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip_all, err(Display))]
+    pub async fn authenticate(&self, token: Option<&str>) -> Result<GraphSubject> {
+        let kube = match self {
+            Self::Disabled => return Ok(GraphSubject::default()),
+            Self::Kubernetes { kube } => kube,
+        };
+        let token = token.ok_or_else(|| anyhow!("missing bearer token"))?;
+
+        let api = Api::<TokenReview>::all(kube.clone());
+        let pp = PostParams::default();
+        let review = TokenReview {
+            metadata: ObjectMeta::default(),
+            spec: TokenReviewSpec {
+                audiences: None,
+                token: Some(token.into()),
+            },
+            status: None,
+        };
+        let TokenReviewStatus {
+            authenticated,
+            error,
+            user,
+            ..
+        } = api
+            .create(&pp, &review)
+            .await
+            .map_err(|error| anyhow!("failed to review token: {error}"))?
+            .status
+            .unwrap_or_default();
+
+        match user {
+            Some(user) if authenticated == Some(true) => Ok(user.into()),
+            _ => bail!(
+                "unauthenticated: {error}",
+                error = error.as_deref().unwrap_or("invalid token"),
+            ),
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    pub async fn authorize(
+        &self,
+        subject: &GraphSubject,
+        namespace: &str,
+        verb: GraphAccessVerb,
+    ) -> Result<()> {
+        let kube = match self {
+            Self::Disabled => return Ok(()),
+            Self::Kubernetes { kube } => kube,
+        };
+
+        let api = Api::<SubjectAccessReview>::all(kube.clone());
+        let pp = PostParams::default();
+        let review = SubjectAccessReview {
+            metadata: ObjectMeta::default(),
+            spec: SubjectAccessReviewSpec {
+                extra: Some(subject.extra.clone()),
+                groups: Some(subject.groups.clone()),
+                non_resource_attributes: None,
+                resource_attributes: Some(ResourceAttributes {
+                    group: Some(Self::API_GROUP.into()),
+                    // NOTE: an empty namespace means all namespaces
+                    namespace: Some(namespace.into()).filter(|namespace| !namespace.is_empty()),
+                    resource: Some(Self::RESOURCE.into()),
+                    verb: Some(verb.to_string()),
+                    ..Default::default()
+                }),
+                uid: subject.uid.clone(),
+                user: Some(subject.user.clone()),
+            },
+            status: None,
+        };
+        let SubjectAccessReviewStatus {
+            allowed, reason, ..
+        } = api
+            .create(&pp, &review)
+            .await
+            .map_err(|error| anyhow!("failed to review subject access: {error}"))?
+            .status
+            .unwrap_or_default();
+
+        if allowed {
+            Ok(())
+        } else {
+            let user = &subject.user;
+            let namespace = if namespace.is_empty() { "*" } else { namespace };
+            bail!(
+                "forbidden: {user:?} cannot {verb} graphs in namespace {namespace:?}{reason}",
+                reason = reason
+                    .map(|reason| format!(": {reason}"))
+                    .unwrap_or_default(),
+            )
+        }
+    }
+}
+
+/// The authenticated identity of a graph client.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GraphSubject {
+    pub extra: BTreeMap<String, Vec<String>>,
+    pub groups: Vec<String>,
+    pub uid: Option<String>,
+    pub user: String,
+}
+
+impl From<UserInfo> for GraphSubject {
+    fn from(value: UserInfo) -> Self {
+        let UserInfo {
+            extra,
+            groups,
+            uid,
+            username,
+        } = value;
+
+        Self {
+            extra: extra.unwrap_or_default(),
+            groups: groups.unwrap_or_default(),
+            uid,
+            user: username.unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GraphAccessVerb {
+    Create,
+    Delete,
+    Get,
+    List,
+    Watch,
+}
+
+impl fmt::Display for GraphAccessVerb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Create => f.write_str("create"),
+            Self::Delete => f.write_str("delete"),
+            Self::Get => f.write_str("get"),
+            Self::List => f.write_str("list"),
+            Self::Watch => f.write_str("watch"),
+        }
+    }
+}
+
+/// A [`NetworkGraphDB`] which is accessible only in the namespaces authorized to the subject.
+pub struct AuthorizedNetworkGraphDB<'a, DB>
+where
+    DB: ?Sized,
+{
+    pub authorizer: &'a NetworkGraphAuthorizer,
+    pub graph_db: &'a DB,
+    pub subject: GraphSubject,
+}
+
+#[async_trait]
+impl<'a, DB> NetworkGraphDB for AuthorizedNetworkGraphDB<'a, DB>
+where
+    DB: ?Sized + NetworkGraphDB,
+{
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn get(&self, scope: &GraphScope) -> Result<Option<Graph<GraphData<LazyFrame>>>> {
+        self.authorizer
+            .authorize(&self.subject, &scope.namespace, GraphAccessVerb::Get)
+            .await?;
+        self.graph_db.get(scope).await
+    }
+
+    #[instrument(level = Level::INFO, skip(self, graph))]
+    async fn insert(&self, graph: Graph<GraphData<LazyFrame>>) -> Result<()> {
+        self.authorizer
+            .authorize(
+                &self.subject,
+                &graph.scope.namespace,
+                GraphAccessVerb::Create,
+            )
+            .await?;
+        self.graph_db.insert(graph).await
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn list(&self, filter: &GraphFilter) -> Result<Vec<Graph<GraphData<LazyFrame>>>> {
+        self.authorizer
+            .authorize(&self.subject, &filter.namespace, GraphAccessVerb::List)
+            .await?;
+        self.graph_db.list(filter).await
+    }
+
+    #[instrument(level = Level::INFO, skip(self, projection))]
+    async fn get_projected(
+        &self,
+        scope: &GraphScope,
+        projection: &GraphProjection,
+    ) -> Result<Option<Graph<GraphData<LazyFrame>>>> {
+        self.authorizer
+            .authorize(&self.subject, &scope.namespace, GraphAccessVerb::Get)
+            .await?;
+        self.graph_db.get_projected(scope, projection).await
+    }
+
+    #[instrument(level = Level::INFO, skip(self, projection))]
+    async fn list_projected(
+        &self,
+        filter: &GraphFilter,
+        projection: &GraphProjection,
+    ) -> Result<Vec<Graph<GraphData<LazyFrame>>>> {
+        self.authorizer
+            .authorize(&self.subject, &filter.namespace, GraphAccessVerb::List)
+            .await?;
+        self.graph_db.list_projected(filter, projection).await
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn remove(&self, scope: GraphScope) -> Result<()> {
+        self.authorizer
+            .authorize(&self.subject, &scope.namespace, GraphAccessVerb::Delete)
+            .await?;
+        self.graph_db.remove(scope).await
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn close(&self) -> Result<()> {
+        // NOTE: the inner graph db is shared with the other subjects
+        Ok(())
+    }
+}
+
+/// Parse the bearer token from the `Authorization` header.
+pub fn parse_bearer_token(header: &str) -> Option<&str> {
+    header
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
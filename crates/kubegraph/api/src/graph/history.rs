@@ -0,0 +1,48 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::frame::{DataFrame, LazyFrame};
+
+use super::{Graph, GraphData, GraphScope};
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphSnapshot<T> {
+    pub graph: Graph<T>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl GraphSnapshot<GraphData<LazyFrame>> {
+    pub async fn collect(self) -> Result<GraphSnapshot<GraphData<DataFrame>>> {
+        let Self { graph, timestamp } = self;
+        Ok(GraphSnapshot {
+            graph: graph.collect().await?,
+            timestamp,
+        })
+    }
+}
+
+/// An append-only store of the graph snapshots, for time-travel queries.
+#[async_trait]
+pub trait NetworkGraphHistoryDB
+where
+    Self: Sync,
+{
+    /// Return the latest snapshot recorded at or before the given timestamp.
+    async fn get_at(
+        &self,
+        scope: &GraphScope,
+        timestamp: DateTime<Utc>,
+    ) -> Result<Option<GraphSnapshot<GraphData<LazyFrame>>>>;
+
+    /// Return all snapshots recorded within the given time range, in order.
+    async fn range(
+        &self,
+        scope: &GraphScope,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<GraphSnapshot<GraphData<LazyFrame>>>>;
+}
@@ -0,0 +1,268 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{connector::NetworkConnectorCrd, frame::DataFrame};
+
+use super::{Graph, GraphData, GraphScope};
+
+/// The leading bytes of the graph interchange format.
+const MAGIC: &[u8; 5] = b"KGIPC";
+
+/// The version of the graph interchange format.
+const VERSION: u8 = 1;
+
+/// The envelope of the graph, which is followed by the edges and the nodes frames in order.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphIpcHeader<M> {
+    #[serde(default)]
+    connector: Option<Arc<NetworkConnectorCrd>>,
+    metadata: M,
+    scope: GraphScope,
+    edges: GraphIpcFrame,
+    nodes: GraphIpcFrame,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphIpcFrame {
+    format: GraphIpcFormat,
+    len: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum GraphIpcFormat {
+    Empty,
+    /// NOTE: Arrow IPC is not available on the native dataframe, so it is encoded in JSON
+    Json,
+    ArrowIpc,
+}
+
+impl<M> Graph<GraphData<DataFrame>, M>
+where
+    M: Clone + Serialize,
+{
+    /// Encode the graph into a compact binary format, which keeps the column types.
+    pub fn to_ipc_bytes(&self) -> Result<Vec<u8>> {
+        let Self {
+            connector,
+            data: GraphData { edges, nodes },
+            metadata,
+            scope,
+        } = self;
+
+        let edges = encode_frame(edges)
+            .map_err(|error| anyhow!("failed to encode graph edges ({scope}): {error}"))?;
+        let nodes = encode_frame(nodes)
+            .map_err(|error| anyhow!("failed to encode graph nodes ({scope}): {error}"))?;
+
+        let header = GraphIpcHeader {
+            connector: connector.clone(),
+            metadata: metadata.clone(),
+            scope: scope.clone(),
+            edges: edges.0,
+            nodes: nodes.0,
+        };
+        let header = ::serde_json::to_vec(&header)
+            .map_err(|error| anyhow!("failed to encode graph header ({scope}): {error}"))?;
+        let header_len = u32::try_from(header.len())
+            .map_err(|_| anyhow!("too large graph header ({scope}): {}", header.len()))?;
+
+        let mut buf =
+            Vec::with_capacity(MAGIC.len() + 1 + 4 + header.len() + edges.1.len() + nodes.1.len());
+        buf.extend_from_slice(MAGIC);
+        buf.push(VERSION);
+        buf.extend_from_slice(&header_len.to_le_bytes());
+        buf.extend_from_slice(&header);
+        buf.extend_from_slice(&edges.1);
+        buf.extend_from_slice(&nodes.1);
+        Ok(buf)
+    }
+}
+
+impl<M> Graph<GraphData<DataFrame>, M>
+where
+    M: DeserializeOwned,
+{
+    /// Decode the graph, which is encoded by [`Graph::to_ipc_bytes`].
+    pub fn from_ipc_bytes(bytes: &[u8]) -> Result<Self> {
+        let bytes = bytes
+            .strip_prefix(MAGIC.as_slice())
+            .ok_or_else(|| anyhow!("invalid graph format: magic mismatch"))?;
+        let (&version, bytes) = bytes
+            .split_first()
+            .ok_or_else(|| anyhow!("invalid graph format: missing version"))?;
+        if version != VERSION {
+            bail!("unsupported graph format version: {version}");
+        }
+
+        let (header_len, bytes) = split_at(bytes, 4, "header length")?;
+        let header_len = u32::from_le_bytes(header_len.try_into().unwrap()) as usize;
+        let (header, bytes) = split_at(bytes, header_len, "header")?;
+        let GraphIpcHeader {
+            connector,
+            metadata,
+            scope,
+            edges,
+            nodes,
+        } = ::serde_json::from_slice::<GraphIpcHeader<M>>(header)
+            .map_err(|error| anyhow!("failed to decode graph header: {error}"))?;
+
+        let (edges_bytes, bytes) = split_at(bytes, edges.len as usize, "edges")?;
+        let (nodes_bytes, bytes) = split_at(bytes, nodes.len as usize, "nodes")?;
+        if !bytes.is_empty() {
+            bail!(
+                "invalid graph format ({scope}): trailing {} bytes",
+                bytes.len()
+            );
+        }
+
+        let edges = decode_frame(edges.format, edges_bytes)
+            .map_err(|error| anyhow!("failed to decode graph edges ({scope}): {error}"))?;
+        let nodes = decode_frame(nodes.format, nodes_bytes)
+            .map_err(|error| anyhow!("failed to decode graph nodes ({scope}): {error}"))?;
+
+        Ok(Self {
+            connector,
+            data: GraphData { edges, nodes },
+            metadata,
+            scope,
+        })
+    }
+}
+
+fn split_at<'a>(bytes: &'a [u8], len: usize, name: &str) -> Result<(&'a [u8], &'a [u8])> {
+    if bytes.len() < len {
+        bail!(
+            "invalid graph format: truncated {name}: expected {len} bytes, but given {}",
+            bytes.len(),
+        );
+    }
+    Ok(bytes.split_at(len))
+}
+
+fn encode_frame(df: &DataFrame) -> Result<(GraphIpcFrame, Vec<u8>)> {
+    let (format, buf) = match df {
+        DataFrame::Empty => (GraphIpcFormat::Empty, Vec::default()),
+        #[cfg(not(feature = "df-polars"))]
+        DataFrame::Native(df) => (GraphIpcFormat::Json, ::serde_json::to_vec(df)?),
+        #[cfg(feature = "df-polars")]
+        DataFrame::Polars(df) => {
+            use pl::prelude::{IpcWriter, SerWriter};
+
+            let mut buf = Vec::default();
+            IpcWriter::new(&mut buf).finish(&mut df.clone())?;
+            (GraphIpcFormat::ArrowIpc, buf)
+        }
+    };
+
+    let frame = GraphIpcFrame {
+        format,
+        len: buf.len() as u64,
+    };
+    Ok((frame, buf))
+}
+
+fn decode_frame(format: GraphIpcFormat, bytes: &[u8]) -> Result<DataFrame> {
+    match format {
+        GraphIpcFormat::Empty => Ok(DataFrame::Empty),
+        #[cfg(not(feature = "df-polars"))]
+        GraphIpcFormat::Json => ::serde_json::from_slice(bytes)
+            .map(DataFrame::Native)
+            .map_err(Into::into),
+        #[cfg(not(feature = "df-polars"))]
+        GraphIpcFormat::ArrowIpc => bail!("Arrow IPC frames require the polars dataframe"),
+        #[cfg(feature = "df-polars")]
+        GraphIpcFormat::Json => bail!("JSON frames require the native dataframe"),
+        #[cfg(feature = "df-polars")]
+        GraphIpcFormat::ArrowIpc => {
+            use pl::prelude::{IpcReader, SerReader};
+
+            IpcReader::new(::std::io::Cursor::new(bytes))
+                .finish()
+                .map(DataFrame::Polars)
+                .map_err(Into::into)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::GraphMetadata;
+
+    fn sample_scope() -> GraphScope {
+        GraphScope {
+            namespace: "default".into(),
+            name: "sample".into(),
+        }
+    }
+
+    #[cfg(not(feature = "df-polars"))]
+    fn sample_frame() -> DataFrame {
+        use crate::frame::native::{Column, DataFrame as NativeDataFrame, Value};
+
+        let df = NativeDataFrame::new(vec![
+            Column {
+                name: "name".into(),
+                values: vec![Value::String("a".into()), Value::String("b".into())],
+            },
+            Column {
+                name: "capacity".into(),
+                values: vec![Value::Number(1.5), Value::Null],
+            },
+        ])
+        .expect("failed to create a sample dataframe");
+        DataFrame::Native(df)
+    }
+
+    #[cfg(feature = "df-polars")]
+    fn sample_frame() -> DataFrame {
+        let df = pl::df!(
+            "name" => ["a", "b"],
+            "capacity" => [Some(1.5), None],
+        )
+        .expect("failed to create a sample dataframe");
+        DataFrame::Polars(df)
+    }
+
+    #[test]
+    fn round_trip() {
+        let graph = Graph {
+            connector: None,
+            data: GraphData {
+                edges: DataFrame::Empty,
+                nodes: sample_frame(),
+            },
+            metadata: GraphMetadata::default(),
+            scope: sample_scope(),
+        };
+
+        let bytes = graph.to_ipc_bytes().expect("failed to encode");
+        let decoded = Graph::<GraphData<DataFrame>, GraphMetadata>::from_ipc_bytes(&bytes)
+            .expect("failed to decode");
+
+        assert_eq!(decoded.data, graph.data);
+        assert_eq!(decoded.scope, graph.scope);
+    }
+
+    #[test]
+    fn reject_truncated() {
+        let graph = Graph {
+            connector: None,
+            data: GraphData {
+                edges: sample_frame(),
+                nodes: sample_frame(),
+            },
+            metadata: GraphMetadata::default(),
+            scope: sample_scope(),
+        };
+
+        let bytes = graph.to_ipc_bytes().expect("failed to encode");
+        let truncated = &bytes[..bytes.len() - 1];
+        assert!(Graph::<GraphData<DataFrame>, GraphMetadata>::from_ipc_bytes(truncated).is_err());
+    }
+}
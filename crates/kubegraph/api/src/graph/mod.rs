@@ -1,3 +1,8 @@
+pub mod auth;
+pub mod history;
+mod ipc;
+#[cfg(all(feature = "petgraph", not(feature = "df-polars")))]
+mod native;
 #[cfg(feature = "df-polars")]
 pub mod polars;
 
@@ -13,7 +18,7 @@ use tracing::{instrument, Level};
 
 use crate::{
     connector::NetworkConnectorCrd,
-    frame::{DataFrame, LazyFrame},
+    frame::{DataFrame, LazyFrame, LazySlice},
     function::FunctionMetadata,
     vm::{Feature, Number},
 };
@@ -107,11 +112,107 @@ where
 
     async fn list(&self, filter: &GraphFilter) -> Result<Vec<Graph<GraphData<LazyFrame>>>>;
 
+    /// Get the graph with only the projected columns and rows.
+    ///
+    /// The default implementation pushes the projection into the lazy plans;
+    /// the backends may override it to skip loading the unused data at all.
+    async fn get_projected(
+        &self,
+        scope: &GraphScope,
+        projection: &GraphProjection,
+    ) -> Result<Option<Graph<GraphData<LazyFrame>>>> {
+        self.get(scope)
+            .await?
+            .map(|graph| projection.apply(graph))
+            .transpose()
+    }
+
+    /// List the graphs with only the projected columns and rows.
+    async fn list_projected(
+        &self,
+        filter: &GraphFilter,
+        projection: &GraphProjection,
+    ) -> Result<Vec<Graph<GraphData<LazyFrame>>>> {
+        self.list(filter)
+            .await?
+            .into_iter()
+            .map(|graph| projection.apply(graph))
+            .collect()
+    }
+
     async fn remove(&self, scope: GraphScope) -> Result<()>;
 
     async fn close(&self) -> Result<()>;
 }
 
+/// The columns and rows to load from the graphs.
+#[derive(Clone, Default)]
+pub struct GraphProjection {
+    pub edges: GraphFrameProjection,
+    pub nodes: GraphFrameProjection,
+}
+
+impl GraphProjection {
+    pub fn is_all(&self) -> bool {
+        self.edges.is_all() && self.nodes.is_all()
+    }
+
+    pub fn apply<M>(
+        &self,
+        graph: Graph<GraphData<LazyFrame>, M>,
+    ) -> Result<Graph<GraphData<LazyFrame>, M>> {
+        if self.is_all() {
+            return Ok(graph);
+        }
+
+        let Graph {
+            connector,
+            data: GraphData { edges, nodes },
+            metadata,
+            scope,
+        } = graph;
+
+        Ok(Graph {
+            connector,
+            data: GraphData {
+                edges: self.edges.apply(edges)?,
+                nodes: self.nodes.apply(nodes)?,
+            },
+            metadata,
+            scope,
+        })
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct GraphFrameProjection {
+    /// The columns to select; all columns if not given
+    pub columns: Option<Vec<String>>,
+    /// The predicate of the rows, which may refer to the unselected columns
+    pub filter: Option<LazySlice>,
+}
+
+impl GraphFrameProjection {
+    pub fn is_all(&self) -> bool {
+        self.columns.is_none() && self.filter.is_none()
+    }
+
+    pub fn apply(&self, mut df: LazyFrame) -> Result<LazyFrame> {
+        if matches!(df, LazyFrame::Empty) {
+            return Ok(df);
+        }
+
+        // NOTE: filter first, so that the predicate can refer to any column
+        if let Some(filter) = self.filter.clone() {
+            df.apply_filter(filter)?;
+        }
+        if let Some(columns) = &self.columns {
+            df.select_columns(columns)?;
+        }
+        Ok(df)
+    }
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(transparent)]
 pub struct GraphEdges<T>(pub(crate) T);
@@ -175,6 +276,10 @@ impl FromIterator<Self> for GraphEdges<LazyFrame> {
 
         match iter.peek() {
             Some(Self(LazyFrame::Empty)) | None => Self(LazyFrame::Empty),
+            #[cfg(not(feature = "df-polars"))]
+            Some(Self(LazyFrame::Native(_))) => iter
+                .filter_map(|Self(edges)| edges.try_into_native().ok().map(GraphEdges))
+                .collect(),
             #[cfg(feature = "df-polars")]
             Some(Self(LazyFrame::Polars(_))) => iter
                 .filter_map(|Self(edges)| edges.try_into_polars().ok().map(GraphEdges))
@@ -220,6 +325,15 @@ where
 
         let name_map = match nodes {
             LazyFrame::Empty => GraphNameMap::default(),
+            #[cfg(not(feature = "df-polars"))]
+            LazyFrame::Native(df) => self::native::transform_petgraph_nodes(
+                &mut graph, &metadata, df,
+            )
+            .map_err(|error| {
+                ::anyhow::anyhow!(
+                    "failed to transform native nodes dataframe into petgraph: {error}"
+                )
+            })?,
             #[cfg(feature = "df-polars")]
             LazyFrame::Polars(df) => self::polars::transform_petgraph_nodes(
                 &mut graph, &metadata, df,
@@ -232,6 +346,15 @@ where
         };
         match edges {
             LazyFrame::Empty => (),
+            #[cfg(not(feature = "df-polars"))]
+            LazyFrame::Native(df) => {
+                self::native::transform_petgraph_edges(&mut graph, &metadata, name_map, df)
+                    .map_err(|error| {
+                        ::anyhow::anyhow!(
+                            "failed to transform native edges dataframe into petgraph: {error}"
+                        )
+                    })?
+            }
             #[cfg(feature = "df-polars")]
             LazyFrame::Polars(df) => {
                 self::polars::transform_petgraph_edges(&mut graph, &metadata, name_map, df)
@@ -401,6 +524,7 @@ where
         let mut values = vec![
             self.capacity().into(),
             self.connector().into(),
+            self.direction().into(),
             self.flow().into(),
             self.function().into(),
             self.interval_ms().into(),
@@ -416,10 +540,11 @@ where
         values
     }
 
-    fn all_cores(&self) -> [&str; 10] {
+    fn all_cores(&self) -> [&str; 11] {
         [
             self.capacity(),
             self.connector(),
+            self.direction(),
             self.flow(),
             self.function(),
             self.interval_ms(),
@@ -475,6 +600,13 @@ where
             .unwrap_or(GraphMetadataStandard::DEFAULT_CONNECTOR)
     }
 
+    fn direction(&self) -> &str {
+        self.extras()
+            .and_then(|extras| extras.get("direction"))
+            .map(|value| value.as_str())
+            .unwrap_or(GraphMetadataStandard::DEFAULT_DIRECTION)
+    }
+
     fn flow(&self) -> &str {
         self.extras()
             .and_then(|extras| extras.get("flow"))
@@ -517,6 +649,7 @@ where
         GraphMetadataPinned {
             capacity: self.capacity().into(),
             connector: self.connector().into(),
+            direction: self.direction().into(),
             flow: self.flow().into(),
             function: self.function().into(),
             interval_ms: self.interval_ms().into(),
@@ -538,7 +671,7 @@ impl GraphMetadataExt for GraphMetadata {
         }
     }
 
-    fn all_cores(&self) -> [&str; 10] {
+    fn all_cores(&self) -> [&str; 11] {
         match self {
             GraphMetadata::Raw(m) => m.all_cores(),
             GraphMetadata::Pinned(m) => m.all_cores(),
@@ -586,6 +719,14 @@ impl GraphMetadataExt for GraphMetadata {
         }
     }
 
+    fn direction(&self) -> &str {
+        match self {
+            GraphMetadata::Raw(m) => m.direction(),
+            GraphMetadata::Pinned(m) => GraphMetadataExt::direction(m),
+            GraphMetadata::Standard(m) => GraphMetadataExt::direction(m),
+        }
+    }
+
     fn flow(&self) -> &str {
         match self {
             GraphMetadata::Raw(m) => m.flow(),
@@ -726,6 +867,20 @@ impl GraphMetadataExt for GraphMetadataRaw {
 }
 
 impl GraphMetadataRaw {
+    #[cfg(not(feature = "df-polars"))]
+    pub fn from_native(df: &crate::frame::native::DataFrame) -> Self {
+        let mut metadata = Self::default();
+        for column in df.columns() {
+            let key = &column.name;
+            if column.values.iter().all(|value| value.is_null()) {
+                continue;
+            }
+
+            metadata.insert(key.clone(), key.clone());
+        }
+        metadata
+    }
+
     #[cfg(feature = "df-polars")]
     pub fn from_polars(df: &::pl::frame::DataFrame) -> Self {
         let mut metadata = Self::default();
@@ -794,6 +949,8 @@ where
 
     fn connector(&self) -> &str;
 
+    fn direction(&self) -> &str;
+
     fn flow(&self) -> &str;
 
     fn function(&self) -> &str;
@@ -840,6 +997,10 @@ where
         GraphMetadataPinnedExt::connector(self)
     }
 
+    fn direction(&self) -> &str {
+        GraphMetadataPinnedExt::direction(self)
+    }
+
     fn flow(&self) -> &str {
         GraphMetadataPinnedExt::flow(self)
     }
@@ -882,6 +1043,10 @@ where
                 GraphMetadataStandard::DEFAULT_CONNECTOR.into(),
                 self.connector().into(),
             ),
+            (
+                GraphMetadataStandard::DEFAULT_DIRECTION.into(),
+                self.direction().into(),
+            ),
             (
                 GraphMetadataStandard::DEFAULT_FLOW.into(),
                 self.flow().into(),
@@ -922,6 +1087,9 @@ pub struct GraphMetadataPinned {
     #[serde(default = "GraphMetadataPinned::default_connector")]
     #[validate(length(min = 1))]
     pub connector: String,
+    #[serde(default = "GraphMetadataPinned::default_direction")]
+    #[validate(length(min = 1))]
+    pub direction: String,
     #[serde(default = "GraphMetadataPinned::default_flow")]
     #[validate(length(min = 1))]
     pub flow: String,
@@ -959,6 +1127,7 @@ impl Default for GraphMetadataPinned {
         Self {
             capacity: Self::default_capacity(),
             connector: Self::default_connector(),
+            direction: Self::default_direction(),
             flow: Self::default_flow(),
             function: Self::default_function(),
             interval_ms: Self::default_interval_ms(),
@@ -980,6 +1149,10 @@ impl GraphMetadataPinned {
         GraphMetadataStandard::DEFAULT_CONNECTOR.into()
     }
 
+    pub fn default_direction() -> String {
+        GraphMetadataStandard::DEFAULT_DIRECTION.into()
+    }
+
     pub fn default_flow() -> String {
         GraphMetadataStandard::DEFAULT_FLOW.into()
     }
@@ -1022,6 +1195,10 @@ impl GraphMetadataPinnedExt for GraphMetadataPinned {
         &self.connector
     }
 
+    fn direction(&self) -> &str {
+        &self.direction
+    }
+
     fn flow(&self) -> &str {
         &self.flow
     }
@@ -1075,6 +1252,7 @@ pub struct GraphMetadataStandard {}
 impl GraphMetadataStandard {
     pub const DEFAULT_CAPACITY: &'static str = "capacity";
     pub const DEFAULT_CONNECTOR: &'static str = "connector";
+    pub const DEFAULT_DIRECTION: &'static str = "direction";
     pub const DEFAULT_FLOW: &'static str = "flow";
     pub const DEFAULT_FUNCTION: &'static str = "function";
     pub const DEFAULT_INTERVAL_MS: &'static str = "le";
@@ -1100,6 +1278,10 @@ impl GraphMetadataPinnedExt for GraphMetadataStandard {
         Self::DEFAULT_CONNECTOR
     }
 
+    fn direction(&self) -> &str {
+        Self::DEFAULT_DIRECTION
+    }
+
     fn flow(&self) -> &str {
         Self::DEFAULT_FLOW
     }
@@ -1223,6 +1405,45 @@ pub enum GraphDataType {
     Node,
 }
 
+/// The direction of the flows on an edge, stored in the `direction` column.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum GraphEdgeDirection {
+    /// The flows go only from `src` to `sink`
+    #[default]
+    Directed,
+    /// The flows go both ways; the solvers mirror the edge from `sink` to `src`
+    Undirected,
+}
+
+impl fmt::Display for GraphEdgeDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl GraphEdgeDirection {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Directed => "directed",
+            Self::Undirected => "undirected",
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct GraphEntry {
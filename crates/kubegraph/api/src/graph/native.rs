@@ -0,0 +1,51 @@
+use crate::frame::native::{LazyFrame, Value};
+
+pub(super) fn transform_petgraph_edges<M>(
+    graph: &mut ::petgraph::stable_graph::StableDiGraph<super::GraphEntry, super::GraphEntry>,
+    metadata: &M,
+    name_map: super::GraphNameMap,
+    lf: LazyFrame,
+) -> ::anyhow::Result<()>
+where
+    M: super::GraphMetadataExt,
+{
+    let df = lf.collect()?;
+
+    for data in df.entries() {
+        let src = data.get_as_petgraph(&name_map, metadata.src())?;
+        let sink = data.get_as_petgraph(&name_map, metadata.sink())?;
+        graph.add_edge(src, sink, data);
+    }
+    Ok(())
+}
+
+pub(super) fn transform_petgraph_nodes<M>(
+    graph: &mut ::petgraph::stable_graph::StableDiGraph<super::GraphEntry, super::GraphEntry>,
+    metadata: &M,
+    lf: LazyFrame,
+) -> ::anyhow::Result<super::GraphNameMap>
+where
+    M: super::GraphMetadataExt,
+{
+    let df = lf.collect()?;
+
+    for data in df.entries() {
+        graph.add_node(data);
+    }
+
+    let name_key = metadata.name();
+    let name = df
+        .column(name_key)
+        .ok_or_else(|| ::anyhow::anyhow!("failed to get native nodes column ({name_key})"))?;
+
+    Ok(name
+        .values
+        .iter()
+        .filter_map(|name| match name {
+            Value::String(value) => Some(value.clone()),
+            _ => None,
+        })
+        .enumerate()
+        .map(|(index, name)| (name, index))
+        .collect())
+}
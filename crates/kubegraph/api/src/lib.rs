@@ -1,18 +1,27 @@
 #[cfg(feature = "df-polars")]
 extern crate polars as pl;
 
+pub mod alert;
+pub mod analyzer;
+pub mod circuit;
 pub mod component;
 pub mod connector;
 pub mod dependency;
+pub mod expansion;
 pub mod frame;
 pub mod function;
 pub mod graph;
 pub mod market;
+pub mod metrics;
 pub mod ops;
+pub mod partition;
 pub mod problem;
 pub mod query;
+pub mod recorder;
 pub mod resource;
 pub mod runner;
+pub mod scheduler;
+pub mod simulation;
 pub mod solver;
 pub mod trader;
 pub mod visualizer;
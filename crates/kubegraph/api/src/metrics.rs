@@ -0,0 +1,134 @@
+use std::{sync::LazyLock, time::Duration};
+
+use opentelemetry::{
+    global,
+    metrics::{Counter, Gauge, Histogram},
+    KeyValue,
+};
+use tracing::warn;
+
+use crate::{
+    frame::LazyFrame,
+    graph::{GraphData, GraphMetadataPinnedExt, GraphScope},
+};
+
+/// The instruments of the optimization loop, which are shared by all components.
+///
+/// NOTE: the instruments are bound to the global meter provider when they are first used.
+pub static METRICS: LazyLock<NetworkMetrics> = LazyLock::new(NetworkMetrics::new);
+
+pub struct NetworkMetrics {
+    connector_poll_duration: Histogram<f64>,
+    graph_edges: Gauge<u64>,
+    graph_nodes: Gauge<u64>,
+    runner_actions: Counter<u64>,
+    solver_duration: Histogram<f64>,
+    solver_objective: Gauge<f64>,
+    vm_steps: Counter<u64>,
+}
+
+impl NetworkMetrics {
+    fn new() -> Self {
+        let meter = global::meter("kubegraph");
+        Self {
+            connector_poll_duration: meter
+                .f64_histogram("kubegraph_connector_poll_duration")
+                .with_description("The elapsed time of pulling graphs from a connector")
+                .with_unit("s")
+                .build(),
+            graph_edges: meter
+                .u64_gauge("kubegraph_graph_edges")
+                .with_description("The number of the edges of a graph")
+                .build(),
+            graph_nodes: meter
+                .u64_gauge("kubegraph_graph_nodes")
+                .with_description("The number of the nodes of a graph")
+                .build(),
+            runner_actions: meter
+                .u64_counter("kubegraph_runner_actions")
+                .with_description("The number of the actions taken by a runner")
+                .build(),
+            solver_duration: meter
+                .f64_histogram("kubegraph_solver_duration")
+                .with_description("The elapsed time of solving a problem")
+                .with_unit("s")
+                .build(),
+            solver_objective: meter
+                .f64_gauge("kubegraph_solver_objective")
+                .with_description("The total cost of the edge flows of the last solution")
+                .build(),
+            vm_steps: meter
+                .u64_counter("kubegraph_vm_steps")
+                .with_description("The number of the optimization loop iterations of a problem")
+                .build(),
+        }
+    }
+
+    pub fn record_connector_poll(&self, connector: &str, elapsed: Duration, success: bool) {
+        self.connector_poll_duration.record(
+            elapsed.as_secs_f64(),
+            &[
+                KeyValue::new("connector", connector.to_string()),
+                KeyValue::new("success", success),
+            ],
+        )
+    }
+
+    pub fn record_graph(&self, scope: &GraphScope, data: &GraphData<LazyFrame>) {
+        let attributes = scope_attributes(scope);
+        match data.edges.count_rows() {
+            Ok(len) => self.graph_edges.record(len as u64, &attributes),
+            Err(error) => warn!("failed to measure graph edges: {scope}: {error}"),
+        }
+        match data.nodes.count_rows() {
+            Ok(len) => self.graph_nodes.record(len as u64, &attributes),
+            Err(error) => warn!("failed to measure graph nodes: {scope}: {error}"),
+        }
+    }
+
+    pub fn record_runner_action(&self, scope: &GraphScope, kind: &str, result: &'static str) {
+        let mut attributes = scope_attributes(scope).to_vec();
+        attributes.push(KeyValue::new("kind", kind.to_string()));
+        attributes.push(KeyValue::new("result", result));
+        self.runner_actions.add(1, &attributes)
+    }
+
+    pub fn record_solution<M>(
+        &self,
+        scope: &GraphScope,
+        elapsed: Duration,
+        solution: Option<&GraphData<LazyFrame>>,
+        metadata: &M,
+    ) where
+        M: GraphMetadataPinnedExt,
+    {
+        let attributes = scope_attributes(scope);
+        let mut attributes_with_result = attributes.to_vec();
+        attributes_with_result.push(KeyValue::new("success", solution.is_some()));
+        self.solver_duration
+            .record(elapsed.as_secs_f64(), &attributes_with_result);
+
+        // NOTE: an empty solution has no feasible edges to be measured
+        let edges = match solution {
+            Some(GraphData { edges, nodes: _ }) if !matches!(edges, LazyFrame::Empty) => edges,
+            _ => return,
+        };
+        match edges.flow_cost(metadata) {
+            Ok(cost) => self.solver_objective.record(cost, &attributes),
+            Err(error) => warn!("failed to measure solver objective: {scope}: {error}"),
+        }
+    }
+
+    pub fn record_vm_step(&self, scope: &GraphScope, state: &'static str) {
+        let mut attributes = scope_attributes(scope).to_vec();
+        attributes.push(KeyValue::new("state", state));
+        self.vm_steps.add(1, &attributes)
+    }
+}
+
+fn scope_attributes(scope: &GraphScope) -> [KeyValue; 2] {
+    [
+        KeyValue::new("namespace", scope.namespace.clone()),
+        KeyValue::new("name", scope.name.clone()),
+    ]
+}
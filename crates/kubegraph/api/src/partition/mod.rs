@@ -0,0 +1,233 @@
+#[cfg(feature = "df-polars")]
+mod polars;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+use clap::ValueEnum;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    frame::LazyFrame,
+    graph::{GraphData, GraphMetadataPinnedExt},
+};
+
+/// The policy of the edges crossing the partitions.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    ValueEnum,
+)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase")]
+pub enum NetworkPartitionCrossEdges {
+    /// Drop the cross edges, so that no flows are assigned to them
+    #[default]
+    Drop,
+    /// Merge the partitions connected by the cross edges into a single sub-problem
+    Aggregate,
+    /// Replicate the sink of each cross edge into the partition of its source
+    Boundary,
+}
+
+/// A splitter of large graphs into the weakly-connected sub-problems, grouped by a node column
+/// (e.g. `namespace`, `zone` or a label).
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkPartitioner {
+    pub key: String,
+    #[serde(default)]
+    pub cross_edges: NetworkPartitionCrossEdges,
+}
+
+impl NetworkPartitioner {
+    /// The column of the partition id, which is attached to the partitioned nodes and edges.
+    pub const KEY_PARTITION: &'static str = "__partition";
+    /// The column of the partition id of the edge sinks.
+    pub const KEY_PARTITION_SINK: &'static str = "__partition_sink";
+    /// The column marking the replicated boundary nodes.
+    pub const KEY_BOUNDARY: &'static str = "__boundary";
+
+    /// Split the graph into the sub-problems, which can be solved independently.
+    pub fn split<M>(
+        &self,
+        graph: GraphData<LazyFrame>,
+        metadata: &M,
+    ) -> Result<Vec<GraphData<LazyFrame>>>
+    where
+        M: GraphMetadataPinnedExt,
+    {
+        match graph {
+            GraphData {
+                edges: LazyFrame::Empty,
+                nodes: LazyFrame::Empty,
+            } => Ok(vec![graph]),
+            #[cfg(not(feature = "df-polars"))]
+            GraphData { .. } => {
+                let _ = metadata;
+                ::anyhow::bail!("partitioning is not supported by the native dataframe backend")
+            }
+            #[cfg(feature = "df-polars")]
+            GraphData { edges, nodes } => self::polars::split(
+                self,
+                GraphData {
+                    edges: edges.try_into_polars()?,
+                    nodes: nodes.try_into_polars()?,
+                },
+                metadata,
+            )
+            .map(|graphs| graphs.into_iter().map(Into::into).collect()),
+        }
+    }
+
+    /// Stitch the solved sub-problems into a graph, dropping the boundary nodes.
+    pub fn stitch(&self, graphs: Vec<GraphData<LazyFrame>>) -> Result<GraphData<LazyFrame>> {
+        let graphs: Vec<_> = graphs
+            .into_iter()
+            .filter(|graph| {
+                !matches!(
+                    graph,
+                    GraphData {
+                        edges: LazyFrame::Empty,
+                        nodes: LazyFrame::Empty,
+                    },
+                )
+            })
+            .collect();
+        if graphs.is_empty() {
+            return Ok(GraphData {
+                edges: LazyFrame::Empty,
+                nodes: LazyFrame::Empty,
+            });
+        }
+
+        #[cfg(not(feature = "df-polars"))]
+        {
+            ::anyhow::bail!("partitioning is not supported by the native dataframe backend")
+        }
+
+        #[cfg(feature = "df-polars")]
+        {
+            let graphs = graphs
+                .into_iter()
+                .map(|GraphData { edges, nodes }| {
+                    Ok(GraphData {
+                        edges: edges.try_into_polars()?,
+                        nodes: nodes.try_into_polars()?,
+                    })
+                })
+                .collect::<Result<_>>()?;
+            self::polars::stitch(graphs).map(Into::into)
+        }
+    }
+}
+
+/// The partitions of the nodes, given by their indices.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct NetworkPartitionAssignment {
+    /// The number of the partitions
+    len: usize,
+    /// The partition id of each node
+    owners: Vec<usize>,
+    /// The replicated nodes and their partition ids
+    boundaries: BTreeSet<(usize, usize)>,
+}
+
+/// Group the nodes into the weakly-connected components of the edges within the same key.
+fn assign(
+    keys: &[Option<&str>],
+    edges: &[(usize, usize)],
+    cross_edges: NetworkPartitionCrossEdges,
+) -> NetworkPartitionAssignment {
+    fn find(parents: &mut [usize], mut node: usize) -> usize {
+        while parents[node] != node {
+            parents[node] = parents[parents[node]];
+            node = parents[node];
+        }
+        node
+    }
+
+    let mut parents: Vec<_> = (0..keys.len()).collect();
+    for &(src, sink) in edges {
+        let is_cross = keys[src] != keys[sink];
+        if is_cross && cross_edges != NetworkPartitionCrossEdges::Aggregate {
+            continue;
+        }
+
+        let src = find(&mut parents, src);
+        let sink = find(&mut parents, sink);
+        if src != sink {
+            parents[sink] = src;
+        }
+    }
+
+    // compact the partition ids in order of the nodes
+    let mut ids = BTreeMap::default();
+    let owners: Vec<_> = (0..keys.len())
+        .map(|node| {
+            let root = find(&mut parents, node);
+            let len = ids.len();
+            *ids.entry(root).or_insert(len)
+        })
+        .collect();
+
+    let boundaries = match cross_edges {
+        NetworkPartitionCrossEdges::Boundary => edges
+            .iter()
+            .filter(|&&(src, sink)| owners[src] != owners[sink])
+            .map(|&(src, sink)| (sink, owners[src]))
+            .collect(),
+        NetworkPartitionCrossEdges::Aggregate | NetworkPartitionCrossEdges::Drop => {
+            BTreeSet::default()
+        }
+    };
+
+    NetworkPartitionAssignment {
+        len: ids.len(),
+        owners,
+        boundaries,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEYS: &[Option<&str>] = &[Some("a"), Some("a"), Some("b"), Some("b"), Some("a"), None];
+    const EDGES: &[(usize, usize)] = &[(0, 1), (1, 2), (2, 3)];
+
+    #[test]
+    fn assign_drop() {
+        let assignment = assign(KEYS, EDGES, NetworkPartitionCrossEdges::Drop);
+        assert_eq!(assignment.len, 4);
+        assert_eq!(assignment.owners, vec![0, 0, 1, 1, 2, 3]);
+        assert!(assignment.boundaries.is_empty());
+    }
+
+    #[test]
+    fn assign_aggregate() {
+        let assignment = assign(KEYS, EDGES, NetworkPartitionCrossEdges::Aggregate);
+        assert_eq!(assignment.len, 3);
+        assert_eq!(assignment.owners, vec![0, 0, 0, 0, 1, 2]);
+        assert!(assignment.boundaries.is_empty());
+    }
+
+    #[test]
+    fn assign_boundary() {
+        let assignment = assign(KEYS, EDGES, NetworkPartitionCrossEdges::Boundary);
+        assert_eq!(assignment.len, 4);
+        assert_eq!(assignment.owners, vec![0, 0, 1, 1, 2, 3]);
+        assert_eq!(assignment.boundaries, [(2, 0)].into_iter().collect());
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use pl::{
+    datatypes::DataType,
+    frame::DataFrame,
+    lazy::{
+        dsl,
+        frame::{IntoLazy, LazyFrame},
+    },
+    prelude::{Column, JoinArgs, JoinType, UnionArgs},
+};
+
+use crate::{
+    frame::polars::get_column,
+    graph::{GraphData, GraphMetadataPinnedExt},
+};
+
+use super::{NetworkPartitionCrossEdges, NetworkPartitioner};
+
+pub(super) fn split<M>(
+    partitioner: &NetworkPartitioner,
+    graph: GraphData<LazyFrame>,
+    metadata: &M,
+) -> Result<Vec<GraphData<LazyFrame>>>
+where
+    M: GraphMetadataPinnedExt,
+{
+    let GraphData { edges, nodes } = graph;
+    let NetworkPartitioner { key, cross_edges } = partitioner;
+
+    let key_name = metadata.name();
+    let key_sink = metadata.sink();
+    let key_src = metadata.src();
+    let key_boundary = NetworkPartitioner::KEY_BOUNDARY;
+    let key_partition = NetworkPartitioner::KEY_PARTITION;
+    let key_partition_sink = NetworkPartitioner::KEY_PARTITION_SINK;
+
+    // Step 1. Collect the node keys and the edge topology
+    let node_keys = nodes
+        .clone()
+        .select([
+            dsl::col(key_name).cast(DataType::String),
+            dsl::col(key).cast(DataType::String).alias(key_partition),
+        ])
+        .collect()
+        .map_err(|error| anyhow!("failed to collect node partition keys: {error}"))?;
+    let names = get_column(&node_keys, "node", "name", key_name, None)?;
+    let names: Vec<_> = names.str()?.into_iter().collect();
+    let keys = get_column(&node_keys, "node", "partition", key_partition, None)?;
+    let keys: Vec<_> = keys.str()?.into_iter().collect();
+
+    let indices: HashMap<_, _> = names
+        .iter()
+        .enumerate()
+        .filter_map(|(index, name)| Some((name.as_ref()?.to_string(), index)))
+        .collect();
+
+    let topology = edges
+        .clone()
+        .select([
+            dsl::col(key_src).cast(DataType::String),
+            dsl::col(key_sink).cast(DataType::String),
+        ])
+        .collect()
+        .map_err(|error| anyhow!("failed to collect edge topology: {error}"))?;
+    let src = get_column(&topology, "edge", "src", key_src, None)?;
+    let sink = get_column(&topology, "edge", "sink", key_sink, None)?;
+    let topology: Vec<_> = src
+        .str()?
+        .into_iter()
+        .zip(sink.str()?)
+        .filter_map(|(src, sink)| Some((*indices.get(src?)?, *indices.get(sink?)?)))
+        .collect();
+
+    // Step 2. Assign the nodes into the partitions
+    let assignment = super::assign(&keys, &topology, *cross_edges);
+
+    // Step 3. Attach the partition ids to the nodes, replicating the boundary nodes
+    let owners = assignment
+        .owners
+        .iter()
+        .enumerate()
+        .map(|(node, &partition)| (node, partition, false));
+    let boundaries = assignment
+        .boundaries
+        .iter()
+        .map(|&(node, partition)| (node, partition, true));
+
+    let mut mapping_names = Vec::default();
+    let mut mapping_partitions = Vec::default();
+    let mut mapping_boundaries = Vec::default();
+    for (node, partition, is_boundary) in owners.chain(boundaries) {
+        mapping_names.push(names[node]);
+        mapping_partitions.push(partition as u64);
+        mapping_boundaries.push(is_boundary);
+    }
+    let mapping = DataFrame::new(vec![
+        Column::new(key_name.into(), mapping_names),
+        Column::new(key_partition.into(), mapping_partitions),
+        Column::new(key_boundary.into(), mapping_boundaries),
+    ])
+    .map_err(|error| anyhow!("failed to build node partitions: {error}"))?
+    .lazy();
+
+    let nodes = nodes
+        .with_column(dsl::col(key_name).cast(DataType::String))
+        .join(
+            mapping.clone(),
+            [dsl::col(key_name)],
+            [dsl::col(key_name)],
+            JoinArgs::new(JoinType::Inner),
+        );
+
+    // Step 4. Attach the partition ids of both sides to the edges
+    let owners = mapping.filter(dsl::col(key_boundary).not());
+    let edges = edges
+        .with_columns([
+            dsl::col(key_src).cast(DataType::String),
+            dsl::col(key_sink).cast(DataType::String),
+        ])
+        .join(
+            owners
+                .clone()
+                .select([dsl::col(key_name).alias(key_src), dsl::col(key_partition)]),
+            [dsl::col(key_src)],
+            [dsl::col(key_src)],
+            JoinArgs::new(JoinType::Inner),
+        )
+        .join(
+            owners.select([
+                dsl::col(key_name).alias(key_sink),
+                dsl::col(key_partition).alias(key_partition_sink),
+            ]),
+            [dsl::col(key_sink)],
+            [dsl::col(key_sink)],
+            JoinArgs::new(JoinType::Inner),
+        );
+
+    // Step 5. Split the graph
+    Ok((0..assignment.len as u64)
+        .map(|partition| {
+            let is_owned = dsl::col(key_partition).eq(dsl::lit(partition));
+            let filter_edges = match cross_edges {
+                NetworkPartitionCrossEdges::Drop => is_owned
+                    .clone()
+                    .and(dsl::col(key_partition_sink).eq(dsl::lit(partition))),
+                // NOTE: the cross edges are kept by the partition of their sources
+                NetworkPartitionCrossEdges::Aggregate | NetworkPartitionCrossEdges::Boundary => {
+                    is_owned.clone()
+                }
+            };
+
+            GraphData {
+                edges: edges.clone().filter(filter_edges),
+                nodes: nodes.clone().filter(is_owned),
+            }
+        })
+        .collect())
+}
+
+pub(super) fn stitch(graphs: Vec<GraphData<LazyFrame>>) -> Result<GraphData<LazyFrame>> {
+    let key_boundary = NetworkPartitioner::KEY_BOUNDARY;
+    let key_partition = NetworkPartitioner::KEY_PARTITION;
+    let key_partition_sink = NetworkPartitioner::KEY_PARTITION_SINK;
+
+    let args = UnionArgs {
+        rechunk: true,
+        to_supertypes: true,
+        ..Default::default()
+    };
+    let (edges, nodes): (Vec<_>, Vec<_>) = graphs
+        .into_iter()
+        .map(|GraphData { edges, nodes }| (edges, nodes))
+        .unzip();
+
+    let edges = dsl::concat_lf_diagonal(edges, args.clone())
+        .map_err(|error| anyhow!("failed to stitch partitioned edges: {error}"))?
+        .drop([key_partition, key_partition_sink]);
+    let nodes = dsl::concat_lf_diagonal(nodes, args)
+        .map_err(|error| anyhow!("failed to stitch partitioned nodes: {error}"))?
+        // NOTE: the boundary nodes are owned by the other partitions
+        .filter(dsl::col(key_boundary).not())
+        .drop([key_boundary, key_partition]);
+
+    Ok(GraphData { edges, nodes })
+}
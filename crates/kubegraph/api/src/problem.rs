@@ -3,8 +3,13 @@ use schemars::JsonSchema;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::{
-    graph::{GraphFilter, GraphMetadataPinned, GraphScope},
+    analyzer::NetworkAnalyzerSpec,
+    expansion::TimeExpansionSpec,
+    graph::{GraphEdgeDirection, GraphFilter, GraphMetadataPinned, GraphScope},
     resource::NetworkResource,
+    runner::RunnerPolicy,
+    scheduler::NetworkSchedulePolicy,
+    simulation::SimulationSpec,
 };
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -64,9 +69,33 @@ pub struct VirtualProblem<M = GraphMetadataPinned> {
     bound = "M: Default + Serialize + DeserializeOwned"
 )]
 pub struct ProblemSpec<M = GraphMetadataPinned> {
+    /// The analyzers to derive the extra columns before solving, in order
+    #[serde(default)]
+    pub analyzers: Vec<NetworkAnalyzerSpec>,
+
+    /// The direction of the edges inferred by the functions.
+    /// The undirected edges are stored only once per node pair.
+    #[serde(default)]
+    pub direction: GraphEdgeDirection,
+
     #[serde(default)]
     pub metadata: M,
 
+    #[serde(default)]
+    pub runner_policy: RunnerPolicy,
+
+    /// When and in which order the problem should be solved
+    #[serde(default)]
+    pub schedule: NetworkSchedulePolicy,
+
+    /// The models to make the trajectories of the simulator runner realistic
+    #[serde(default)]
+    pub simulation: SimulationSpec,
+
+    /// The time steps to plan ahead for the known future demands
+    #[serde(default)]
+    pub time_expansion: Option<TimeExpansionSpec>,
+
     #[serde(default = "ProblemSpec::<M>::default_verbose")]
     pub verbose: bool,
 }
@@ -77,7 +106,13 @@ where
 {
     fn default() -> Self {
         Self {
+            analyzers: Vec::default(),
+            direction: GraphEdgeDirection::default(),
             metadata: M::default(),
+            runner_policy: RunnerPolicy::default(),
+            schedule: NetworkSchedulePolicy::default(),
+            simulation: SimulationSpec::default(),
+            time_expansion: None,
             verbose: Self::default_verbose(),
         }
     }
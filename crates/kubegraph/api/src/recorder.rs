@@ -0,0 +1,304 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use tracing::{instrument, warn, Level};
+
+use crate::{
+    circuit::NetworkCircuitBreaker,
+    frame::{DataFrame, LazyFrame},
+    graph::{GraphData, GraphScope},
+    problem::VirtualProblem,
+    simulation::NetworkSimulationReport,
+    solver::NetworkSolver,
+    vm::solve_over_time,
+};
+
+/// A captured step of the optimize-run loop, which can be replayed later.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkRecord {
+    pub timestamp: DateTime<Utc>,
+    pub problem: VirtualProblem,
+    pub input: GraphData<DataFrame>,
+    /// The solver output, if solved.
+    #[serde(default)]
+    pub output: Option<GraphData<DataFrame>>,
+    pub action: NetworkRecordAction,
+}
+
+/// What the loop has done with the solution.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum NetworkRecordAction {
+    /// The solution has been applied by the runner
+    Applied,
+    /// The solution has been held back by the circuit breaker
+    Paused,
+    /// No feasible functions are found
+    Unsolvable,
+    /// The problem has been registered to the market
+    Trading,
+    Failed {
+        message: String,
+    },
+}
+
+/// Records the steps into a bundle, which is a directory of JSON files per problem.
+///
+/// The files are named after their timestamps, i.e. `<namespace>/<name>/<timestamp>.json`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NetworkRecorder {
+    dir: PathBuf,
+}
+
+impl NetworkRecorder {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    #[instrument(level = Level::INFO, skip(self, record), fields(scope = %record.problem.scope), err(Display))]
+    pub async fn record(&self, record: &NetworkRecord) -> Result<()> {
+        let GraphScope { namespace, name } = &record.problem.scope;
+        let dir = self.dir.join(namespace).join(name);
+        fs::create_dir_all(&dir)
+            .await
+            .map_err(|error| anyhow!("failed to create record directory: {error}"))?;
+
+        let data = ::serde_json::to_vec(record)
+            .map_err(|error| anyhow!("failed to serialize record: {error}"))?;
+        let path = dir.join(format!(
+            "{timestamp}.json",
+            timestamp = record.timestamp.format("%Y%m%dT%H%M%S%.6fZ"),
+        ));
+        fs::write(&path, data)
+            .await
+            .map_err(|error| anyhow!("failed to write record {path:?}: {error}"))
+    }
+
+    /// Load the records of a file or a bundle, in order of their timestamps.
+    #[instrument(level = Level::INFO, err(Display))]
+    pub async fn load(path: &Path) -> Result<Vec<NetworkRecord>> {
+        let mut records = Vec::default();
+        let mut pending = vec![path.to_path_buf()];
+        while let Some(path) = pending.pop() {
+            let metadata = fs::metadata(&path)
+                .await
+                .map_err(|error| anyhow!("failed to read {path:?}: {error}"))?;
+            if metadata.is_dir() {
+                let mut entries = fs::read_dir(&path)
+                    .await
+                    .map_err(|error| anyhow!("failed to read directory {path:?}: {error}"))?;
+                while let Some(entry) = entries
+                    .next_entry()
+                    .await
+                    .map_err(|error| anyhow!("failed to read directory {path:?}: {error}"))?
+                {
+                    pending.push(entry.path());
+                }
+            } else if path.extension().is_some_and(|ext| ext == "json") {
+                let data = fs::read(&path)
+                    .await
+                    .map_err(|error| anyhow!("failed to read record {path:?}: {error}"))?;
+                let record: NetworkRecord = ::serde_json::from_slice(&data)
+                    .map_err(|error| anyhow!("failed to parse record {path:?}: {error}"))?;
+                records.push(record);
+            }
+        }
+
+        records
+            .sort_by(|a, b| (a.timestamp, &a.problem.scope).cmp(&(b.timestamp, &b.problem.scope)));
+        Ok(records)
+    }
+}
+
+/// Captures a step of the loop, doing nothing if the recorder is disabled.
+pub(crate) struct NetworkCapture<'a> {
+    inner: Option<(
+        &'a NetworkRecorder,
+        DateTime<Utc>,
+        VirtualProblem,
+        GraphData<LazyFrame>,
+    )>,
+}
+
+impl<'a> NetworkCapture<'a> {
+    pub(crate) fn new(
+        recorder: Option<&'a NetworkRecorder>,
+        problem: &VirtualProblem,
+        input: &GraphData<LazyFrame>,
+    ) -> Self {
+        Self {
+            inner: recorder.map(|recorder| (recorder, Utc::now(), problem.clone(), input.clone())),
+        }
+    }
+
+    /// Record the step as best-effort, so that the loop is not blocked by the recorder.
+    pub(crate) async fn finish(
+        self,
+        output: Option<&GraphData<LazyFrame>>,
+        action: NetworkRecordAction,
+    ) {
+        let Some((recorder, timestamp, problem, input)) = self.inner else {
+            return;
+        };
+
+        let result = async {
+            let record = NetworkRecord {
+                timestamp,
+                problem,
+                input: input.collect().await?,
+                output: match output {
+                    Some(output) => Some(output.clone().collect().await?),
+                    None => None,
+                },
+                action,
+            };
+            recorder.record(&record).await
+        };
+        if let Err(error) = result.await {
+            warn!("failed to record the step: {error}");
+        }
+    }
+}
+
+/// How the solver is treated while replaying.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum NetworkReplaySolver {
+    /// Reuse the recorded outputs, to replay the decisions after solving
+    #[default]
+    Mock,
+    /// Solve the recorded inputs again, to find the regressions of the solver
+    Rerun,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkReplayRequest {
+    pub records: Vec<NetworkRecord>,
+    #[serde(default)]
+    pub solver: NetworkReplaySolver,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkReplayReport {
+    pub steps: Vec<NetworkReplayStep>,
+}
+
+impl NetworkReplayReport {
+    /// Whether all steps have reproduced the recorded decisions and flows.
+    pub fn is_matched(&self) -> bool {
+        self.steps.iter().all(|step| step.is_matched)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkReplayStep {
+    pub timestamp: DateTime<Utc>,
+    pub scope: GraphScope,
+    pub recorded: NetworkRecordAction,
+    pub replayed: NetworkRecordAction,
+    /// The edges whose flows differ between the recorded and replayed outputs
+    pub diff: NetworkSimulationReport,
+    pub is_matched: bool,
+}
+
+/// Re-execute the recorded steps in order without touching any state.
+///
+/// The runners are not executed; the replayed action is the one the loop would take.
+pub async fn replay<S>(
+    solver: &S,
+    records: Vec<NetworkRecord>,
+    mode: NetworkReplaySolver,
+    is_trading: bool,
+) -> Result<NetworkReplayReport>
+where
+    S: ?Sized + Sync + NetworkSolver<GraphData<LazyFrame>, Output = GraphData<LazyFrame>>,
+{
+    // NOTE: the circuits are replayed from scratch, rather than the shared ones
+    let circuit_breaker = NetworkCircuitBreaker::default();
+
+    let mut steps = Vec::with_capacity(records.len());
+    for record in records {
+        let NetworkRecord {
+            timestamp,
+            problem,
+            input,
+            output,
+            action: recorded,
+        } = record;
+        let metadata = &problem.spec.metadata;
+        let recorded_edges = output
+            .as_ref()
+            .map(|output| output.edges.clone().lazy())
+            .unwrap_or_default();
+
+        // Step 1. Solve edge flows
+        let output = match mode {
+            NetworkReplaySolver::Mock => output.map(|output| Ok(output.lazy())),
+            NetworkReplaySolver::Rerun => {
+                Some(solve_over_time(solver, input.lazy(), &problem.spec).await)
+            }
+        };
+
+        // Step 2. Decide what to do with the solution
+        let (replayed, edges) = match output {
+            // NOTE: the recorded failures are kept as-is without the solver
+            None => (recorded.clone(), LazyFrame::Empty),
+            Some(Err(error)) => {
+                let message = error.to_string();
+                (NetworkRecordAction::Failed { message }, LazyFrame::Empty)
+            }
+            Some(Ok(GraphData { edges, .. })) if matches!(&edges, LazyFrame::Empty) => {
+                if is_trading {
+                    (NetworkRecordAction::Trading, edges)
+                } else {
+                    (NetworkRecordAction::Unsolvable, edges)
+                }
+            }
+            Some(Ok(GraphData { edges, .. })) => {
+                let is_paused = match &problem.spec.runner_policy.circuit_breaker {
+                    Some(policy) => circuit_breaker
+                        .observe(&problem.scope, policy, &edges, metadata)?
+                        .is_open(),
+                    None => false,
+                };
+                if is_paused {
+                    (NetworkRecordAction::Paused, edges)
+                } else {
+                    (NetworkRecordAction::Applied, edges)
+                }
+            }
+        };
+
+        // Step 3. Compare the outputs
+        let diff = NetworkSimulationReport::diff(recorded_edges, edges, metadata)?;
+        steps.push(NetworkReplayStep {
+            timestamp,
+            is_matched: recorded == replayed && diff.flows.is_empty(),
+            scope: problem.scope,
+            recorded,
+            replayed,
+            diff,
+        });
+    }
+    Ok(NetworkReplayReport { steps })
+}
@@ -2,8 +2,8 @@ use async_trait::async_trait;
 use kube::Client;
 
 use crate::{
-    connector::NetworkConnectorCrd, function::NetworkFunctionCrd, graph::GraphScope,
-    problem::NetworkProblemCrd,
+    alert::NetworkAlertRuleCrd, connector::NetworkConnectorCrd, function::NetworkFunctionCrd,
+    graph::GraphScope, problem::NetworkProblemCrd,
 };
 
 #[async_trait]
@@ -11,6 +11,7 @@ pub trait NetworkResourceCollectionDB<T>
 where
     Self: Sync
         + NetworkResourceClient
+        + NetworkResourceDB<NetworkAlertRuleCrd>
         + NetworkResourceDB<NetworkConnectorCrd>
         + NetworkResourceDB<NetworkFunctionCrd>
         + NetworkResourceDB<NetworkProblemCrd>,
@@ -21,6 +22,7 @@ where
 impl<DB, T> NetworkResourceCollectionDB<T> for DB where
     Self: Sync
         + NetworkResourceClient
+        + NetworkResourceDB<NetworkAlertRuleCrd>
         + NetworkResourceDB<NetworkConnectorCrd>
         + NetworkResourceDB<NetworkFunctionCrd>
         + NetworkResourceDB<NetworkProblemCrd>
@@ -1,8 +1,19 @@
-use std::{collections::BTreeMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use kube::Client;
+use chrono::{NaiveTime, Utc};
+use k8s_openapi::api::core::v1::ObjectReference;
+use kube::{
+    runtime::events::{Event, EventType, Recorder, Reporter},
+    Client, CustomResourceExt,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 use crate::{
     connector::NetworkConnectorCrd,
@@ -11,7 +22,7 @@ use crate::{
         GraphData, GraphEdges, GraphMetadataPinned, GraphScope, NetworkGraphDB,
         ScopedNetworkGraphDBContainer,
     },
-    problem::VirtualProblem,
+    problem::{NetworkProblemCrd, VirtualProblem},
 };
 
 #[async_trait]
@@ -34,3 +45,227 @@ where
     pub problem: VirtualProblem<GraphMetadataPinned>,
     pub static_edges: Option<GraphEdges<T>>,
 }
+
+/// Safety guardrails that every runner should consult before acting on the cluster.
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct RunnerPolicy {
+    /// Pause the runners when the consecutive solutions are anomalous
+    #[serde(default)]
+    pub circuit_breaker: Option<RunnerCircuitBreakerPolicy>,
+
+    /// Namespaces that runners should never touch
+    #[serde(default)]
+    pub forbidden_namespaces: BTreeSet<String>,
+
+    /// UTC time windows in which runners are allowed to act; empty means always
+    #[serde(default)]
+    pub maintenance_windows: Vec<RunnerMaintenanceWindow>,
+
+    /// The maximum number of workloads to be moved within a cycle
+    #[serde(default)]
+    pub max_moved_workloads_per_cycle: Option<u64>,
+
+    /// Whether the targets should be annotated with the approval annotation
+    #[serde(default)]
+    pub require_approval: bool,
+}
+
+impl RunnerPolicy {
+    pub const ANNOTATION_APPROVED: &'static str = "kubegraph.ulagbulag.io/approved";
+
+    pub fn begin_cycle(&self) -> RunnerPolicyGuard<'_> {
+        RunnerPolicyGuard {
+            moved_workloads: 0,
+            policy: self,
+        }
+    }
+
+    fn is_in_maintenance_windows(&self, now: NaiveTime) -> bool {
+        self.maintenance_windows.is_empty()
+            || self
+                .maintenance_windows
+                .iter()
+                .any(|window| window.contains(now))
+    }
+}
+
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct RunnerCircuitBreakerPolicy {
+    /// The maximum change of the edge flows between the consecutive solutions, in percent
+    #[serde(default = "RunnerCircuitBreakerPolicy::default_max_flow_change_percent")]
+    pub max_flow_change_percent: u32,
+
+    /// The maximum regression of the objective value (total flow cost)
+    /// between the consecutive solutions, in percent
+    #[serde(default = "RunnerCircuitBreakerPolicy::default_max_objective_regression_percent")]
+    pub max_objective_regression_percent: u32,
+
+    /// The number of the consecutive stable solutions to close the circuit;
+    /// zero means that the circuit is closed only by a human
+    #[serde(default = "RunnerCircuitBreakerPolicy::default_stable_cycles_to_close")]
+    pub stable_cycles_to_close: u32,
+}
+
+impl Default for RunnerCircuitBreakerPolicy {
+    fn default() -> Self {
+        Self {
+            max_flow_change_percent: Self::default_max_flow_change_percent(),
+            max_objective_regression_percent: Self::default_max_objective_regression_percent(),
+            stable_cycles_to_close: Self::default_stable_cycles_to_close(),
+        }
+    }
+}
+
+impl RunnerCircuitBreakerPolicy {
+    const fn default_max_flow_change_percent() -> u32 {
+        50
+    }
+
+    const fn default_max_objective_regression_percent() -> u32 {
+        20
+    }
+
+    const fn default_stable_cycles_to_close() -> u32 {
+        3
+    }
+}
+
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct RunnerMaintenanceWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl RunnerMaintenanceWindow {
+    fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            // the window wraps around midnight
+            self.start <= time || time < self.end
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum RunnerPolicyViolation {
+    #[error("namespace {0:?} is forbidden")]
+    ForbiddenNamespace(String),
+    #[error("exceeded the maximum moved workloads per cycle ({0})")]
+    MaxMovedWorkloads(u64),
+    #[error("out of the maintenance windows")]
+    OutOfMaintenanceWindows,
+    #[error("the target is not approved")]
+    NotApproved,
+}
+
+impl RunnerPolicyViolation {
+    pub const fn reason(&self) -> &'static str {
+        match self {
+            Self::ForbiddenNamespace(_) => "ForbiddenNamespace",
+            Self::MaxMovedWorkloads(_) => "MaxMovedWorkloads",
+            Self::OutOfMaintenanceWindows => "OutOfMaintenanceWindows",
+            Self::NotApproved => "NotApproved",
+        }
+    }
+}
+
+pub struct RunnerPolicyGuard<'a> {
+    moved_workloads: u64,
+    policy: &'a RunnerPolicy,
+}
+
+impl RunnerPolicyGuard<'_> {
+    pub fn check_cycle(&self, namespace: &str) -> Result<(), RunnerPolicyViolation> {
+        if self.policy.forbidden_namespaces.contains(namespace) {
+            return Err(RunnerPolicyViolation::ForbiddenNamespace(namespace.into()));
+        }
+        if !self.policy.is_in_maintenance_windows(Utc::now().time()) {
+            return Err(RunnerPolicyViolation::OutOfMaintenanceWindows);
+        }
+        Ok(())
+    }
+
+    pub fn check_action(
+        &mut self,
+        namespace: Option<&str>,
+        annotations: Option<&BTreeMap<String, String>>,
+        moved_workloads: u64,
+    ) -> Result<(), RunnerPolicyViolation> {
+        if let Some(namespace) = namespace {
+            if self.policy.forbidden_namespaces.contains(namespace) {
+                return Err(RunnerPolicyViolation::ForbiddenNamespace(namespace.into()));
+            }
+        }
+
+        if self.policy.require_approval {
+            let approved = annotations
+                .and_then(|annotations| annotations.get(RunnerPolicy::ANNOTATION_APPROVED))
+                .map(|value| value == "true")
+                .unwrap_or_default();
+            if !approved {
+                return Err(RunnerPolicyViolation::NotApproved);
+            }
+        }
+
+        let moved_workloads = self.moved_workloads + moved_workloads;
+        if let Some(max) = self.policy.max_moved_workloads_per_cycle {
+            if moved_workloads > max {
+                return Err(RunnerPolicyViolation::MaxMovedWorkloads(max));
+            }
+        }
+        self.moved_workloads = moved_workloads;
+        Ok(())
+    }
+}
+
+/// Report a suppressed action to the problem as a kubernetes warning event.
+pub async fn report_policy_violation(
+    kube: &Client,
+    scope: &GraphScope,
+    action: &str,
+    violation: &RunnerPolicyViolation,
+) -> Result<()> {
+    let event = Event {
+        type_: EventType::Warning,
+        reason: violation.reason().into(),
+        note: Some(format!("Suppressed {action}: {violation}")),
+        action: "Suppress".into(),
+        secondary: None,
+    };
+    publish_problem_event(kube, scope, &event)
+        .await
+        .map_err(|error| anyhow!("failed to report policy violation: {error}"))
+}
+
+/// Publish a kubernetes event to the problem.
+pub(crate) async fn publish_problem_event(
+    kube: &Client,
+    scope: &GraphScope,
+    event: &Event,
+) -> Result<(), ::kube::Error> {
+    let reporter = Reporter {
+        controller: "kubegraph-runner".into(),
+        instance: None,
+    };
+    let recorder = Recorder::new(kube.clone(), reporter);
+
+    let api_resource = NetworkProblemCrd::api_resource();
+    let reference = ObjectReference {
+        api_version: Some(api_resource.api_version),
+        kind: Some(api_resource.kind),
+        name: Some(scope.name.clone()),
+        namespace: Some(scope.namespace.clone()),
+        ..Default::default()
+    };
+    recorder.publish(event, &reference).await
+}
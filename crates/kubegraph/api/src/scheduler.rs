@@ -0,0 +1,302 @@
+use std::{
+    cmp::Reverse,
+    collections::{hash_map::RandomState, BTreeMap, BTreeSet},
+    hash::BuildHasher,
+    sync::{LazyLock, Mutex},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, TimeDelta, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::{graph::GraphScope, problem::VirtualProblem};
+
+/// The schedules of the problems, which are shared by the optimization loop.
+pub static SCHEDULER: LazyLock<NetworkScheduler> = LazyLock::new(NetworkScheduler::default);
+
+/// When and in which order a problem should be solved.
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkSchedulePolicy {
+    /// The problems in the same namespace whose outputs are consumed by this problem,
+    /// which are solved before this problem within a cycle
+    #[serde(default)]
+    pub depends_on: BTreeSet<String>,
+
+    /// The interval between the solves, in seconds; every cycle of the VM if not given
+    #[serde(default)]
+    pub interval_seconds: Option<u64>,
+
+    /// The maximum random delay added to each interval, in seconds
+    #[serde(default)]
+    pub jitter_seconds: u64,
+
+    /// The problems with higher priorities are solved first within the same dependency level
+    #[serde(default)]
+    pub priority: i32,
+}
+
+impl NetworkSchedulePolicy {
+    fn next_run(&self, scope: &GraphScope, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let interval = self.interval_seconds.map(Duration::from_secs);
+        let jitter = match self.jitter_seconds {
+            0 => None,
+            jitter => {
+                let jitter_ms = jitter.saturating_mul(1_000);
+                let seed = RandomState::new().hash_one(scope);
+                Some(Duration::from_millis(seed % (jitter_ms + 1)))
+            }
+        };
+        if interval.is_none() && jitter.is_none() {
+            return None;
+        }
+
+        let delay = interval.unwrap_or_default() + jitter.unwrap_or_default();
+        TimeDelta::from_std(delay)
+            .ok()
+            .and_then(|delay| now.checked_add_signed(delay))
+    }
+}
+
+/// The schedule of a problem.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkScheduleStatus {
+    /// The dependency level, where the problems of the lower levels are solved first
+    pub level: usize,
+    pub priority: i32,
+    pub last_run: Option<DateTime<Utc>>,
+    /// The time the problem becomes due; every cycle of the VM if missing
+    pub next_run: Option<DateTime<Utc>>,
+}
+
+impl NetworkScheduleStatus {
+    fn is_due(&self, now: DateTime<Utc>) -> bool {
+        !self.next_run.is_some_and(|next_run| next_run > now)
+    }
+}
+
+/// Decides which problems are due in each cycle of the VM, and in which order.
+#[derive(Default)]
+pub struct NetworkScheduler {
+    states: Mutex<BTreeMap<GraphScope, NetworkScheduleStatus>>,
+}
+
+impl NetworkScheduler {
+    /// Pick the problems due at the time, grouped into the batches to be solved in order.
+    ///
+    /// The problems of a batch can be solved concurrently.
+    pub fn schedule<M>(
+        &self,
+        problems: Vec<VirtualProblem<M>>,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Vec<VirtualProblem<M>>>> {
+        let levels = levels(
+            &problems
+                .iter()
+                .map(|problem| (&problem.scope, &problem.spec.schedule.depends_on))
+                .collect::<Vec<_>>(),
+        );
+
+        let mut states = self.lock()?;
+        // NOTE: forget the schedules of the removed problems
+        let scopes: BTreeSet<_> = problems.iter().map(|problem| &problem.scope).collect();
+        states.retain(|scope, _| scopes.contains(scope));
+
+        let mut problems: Vec<_> = problems
+            .into_iter()
+            .zip(levels)
+            .filter_map(|(problem, level)| {
+                let state = states.entry(problem.scope.clone()).or_default();
+                state.level = level;
+                state.priority = problem.spec.schedule.priority;
+                if state.is_due(now) {
+                    Some(((level, Reverse(state.priority)), problem))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        problems.sort_by_key(|(key, _)| *key);
+
+        let mut batches: Vec<(_, Vec<_>)> = Vec::default();
+        for (key, problem) in problems {
+            match batches.last_mut() {
+                Some((last, batch)) if *last == key => batch.push(problem),
+                _ => batches.push((key, vec![problem])),
+            }
+        }
+        Ok(batches.into_iter().map(|(_, batch)| batch).collect())
+    }
+
+    /// Reserve the next run of the solved problem.
+    pub fn complete(
+        &self,
+        scope: &GraphScope,
+        policy: &NetworkSchedulePolicy,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        let mut states = self.lock()?;
+        let state = states.entry(scope.clone()).or_default();
+        state.last_run = Some(now);
+        state.next_run = policy.next_run(scope, now);
+        Ok(())
+    }
+
+    /// Return the time until the earliest reserved run, if any.
+    pub fn wait_time(&self, now: DateTime<Utc>) -> Result<Option<Duration>> {
+        Ok(self
+            .lock()?
+            .values()
+            .filter_map(|state| state.next_run)
+            .min()
+            .map(|next_run| (next_run - now).to_std().unwrap_or_default()))
+    }
+
+    /// Return the schedules of the problems in the namespace.
+    pub fn list(&self, namespace: &str) -> Result<BTreeMap<String, NetworkScheduleStatus>> {
+        Ok(self
+            .lock()?
+            .iter()
+            .filter(|(scope, _)| scope.namespace == namespace)
+            .map(|(scope, state)| (scope.name.clone(), state.clone()))
+            .collect())
+    }
+
+    fn lock(
+        &self,
+    ) -> Result<::std::sync::MutexGuard<'_, BTreeMap<GraphScope, NetworkScheduleStatus>>> {
+        self.states
+            .lock()
+            .map_err(|error| anyhow!("failed to lock schedules: {error}"))
+    }
+}
+
+/// Assign the dependency levels, so that each problem is placed above all its dependencies.
+///
+/// NOTE: the missing dependencies are ignored, and the cyclic ones are placed at the top.
+fn levels(problems: &[(&GraphScope, &BTreeSet<String>)]) -> Vec<usize> {
+    let indices: BTreeMap<_, _> = problems
+        .iter()
+        .enumerate()
+        .map(|(index, (scope, _))| (*scope, index))
+        .collect();
+    let dependencies: Vec<Vec<_>> = problems
+        .iter()
+        .enumerate()
+        .map(|(index, (scope, depends_on))| {
+            depends_on
+                .iter()
+                .filter_map(|name| {
+                    let dependency = GraphScope {
+                        namespace: scope.namespace.clone(),
+                        name: name.clone(),
+                    };
+                    indices.get(&dependency).copied()
+                })
+                .filter(|&dependency| dependency != index)
+                .collect()
+        })
+        .collect();
+
+    let mut levels = vec![None; problems.len()];
+    loop {
+        let ready: Vec<_> = (0..problems.len())
+            .filter(|&index| levels[index].is_none())
+            .filter(|&index| {
+                dependencies[index]
+                    .iter()
+                    .all(|&dependency| levels[dependency].is_some())
+            })
+            .collect();
+        if ready.is_empty() {
+            break;
+        }
+
+        for index in ready {
+            levels[index] = Some(
+                dependencies[index]
+                    .iter()
+                    .filter_map(|&dependency| levels[dependency])
+                    .map(|level| level + 1)
+                    .max()
+                    .unwrap_or_default(),
+            );
+        }
+    }
+
+    let top = levels
+        .iter()
+        .flatten()
+        .max()
+        .map(|level| level + 1)
+        .unwrap_or_default();
+    levels
+        .into_iter()
+        .enumerate()
+        .map(|(index, level)| {
+            level.unwrap_or_else(|| {
+                warn!("cyclic dependencies are detected: {}", problems[index].0);
+                top
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope(name: &str) -> GraphScope {
+        GraphScope {
+            namespace: "default".into(),
+            name: name.into(),
+        }
+    }
+
+    fn depends_on(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|&name| name.into()).collect()
+    }
+
+    #[test]
+    fn levels_in_dependency_order() {
+        let scopes = [scope("a"), scope("b"), scope("c"), scope("d")];
+        let dependencies = [
+            depends_on(&["b", "c"]),
+            depends_on(&["c", "missing"]),
+            depends_on(&[]),
+            depends_on(&["d"]),
+        ];
+        let problems: Vec<_> = scopes.iter().zip(&dependencies).collect();
+        assert_eq!(levels(&problems), vec![2, 1, 0, 0]);
+    }
+
+    #[test]
+    fn levels_with_cycles() {
+        let scopes = [scope("a"), scope("b"), scope("c")];
+        let dependencies = [depends_on(&["b"]), depends_on(&["a"]), depends_on(&[])];
+        let problems: Vec<_> = scopes.iter().zip(&dependencies).collect();
+        assert_eq!(levels(&problems), vec![1, 1, 0]);
+    }
+
+    #[test]
+    fn next_run_with_jitter() {
+        let now = Utc::now();
+        let policy = NetworkSchedulePolicy {
+            interval_seconds: Some(10),
+            jitter_seconds: 5,
+            ..Default::default()
+        };
+        let next_run = policy.next_run(&scope("a"), now).unwrap();
+        assert!(next_run >= now + TimeDelta::seconds(10));
+        assert!(next_run <= now + TimeDelta::seconds(15));
+
+        let policy = NetworkSchedulePolicy::default();
+        assert_eq!(policy.next_run(&scope("a"), now), None);
+    }
+}
@@ -0,0 +1,312 @@
+#[cfg(feature = "df-polars")]
+mod polars;
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    frame::LazyFrame,
+    graph::{GraphData, GraphMetadataPinned, GraphMetadataPinnedExt},
+    problem::VirtualProblem,
+};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkSimulationRequest {
+    pub problem: VirtualProblem<GraphMetadataPinned>,
+    #[serde(default)]
+    pub overrides: Vec<NetworkSimulationOverride>,
+}
+
+/// A hypothetical change to be applied to the graph before solving.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum NetworkSimulationOverride {
+    /// Remove the node and all of its edges
+    RemoveNode { name: String },
+    /// Remove the edge
+    RemoveEdge { src: String, sink: String },
+    /// Multiply the node column (e.g. `supply`) by the factor
+    ScaleNode {
+        name: String,
+        key: String,
+        factor: f64,
+    },
+    /// Multiply the edge column (e.g. `capacity`) by the factor
+    ScaleEdge {
+        src: String,
+        sink: String,
+        key: String,
+        factor: f64,
+    },
+}
+
+impl NetworkSimulationOverride {
+    pub fn apply<M>(
+        &self,
+        graph: GraphData<LazyFrame>,
+        metadata: &M,
+    ) -> Result<GraphData<LazyFrame>>
+    where
+        M: GraphMetadataPinnedExt,
+    {
+        match graph {
+            GraphData {
+                edges: LazyFrame::Empty,
+                nodes: LazyFrame::Empty,
+            } => Ok(graph),
+            #[cfg(not(feature = "df-polars"))]
+            GraphData { .. } => {
+                ::anyhow::bail!("simulations are not supported by the native dataframe backend")
+            }
+            #[cfg(feature = "df-polars")]
+            GraphData { edges, nodes } => Ok(self::polars::apply(
+                self,
+                GraphData {
+                    edges: edges.try_into_polars()?,
+                    nodes: nodes.try_into_polars()?,
+                },
+                metadata,
+            )
+            .into()),
+        }
+    }
+}
+
+/// The models of the simulator runner, which perturb the flows before applying them.
+///
+/// All models are disabled by default, so that the flows are applied as they are.
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationSpec {
+    /// The maximum variation of the edge capacity per cycle, in percent;
+    /// the flows beyond the varied capacity are clipped
+    #[serde(default)]
+    pub capacity_jitter_percent: u32,
+
+    /// The number of the cycles for the flows to arrive at the sinks
+    #[serde(default)]
+    pub delay_cycles: u32,
+
+    /// The edge column of the per-edge delay in cycles, which overrides `delayCycles`
+    #[serde(default)]
+    pub delay_key: Option<String>,
+
+    /// The probability of each flow to fail and not to be applied, in percent
+    #[serde(default)]
+    pub failure_percent: u32,
+
+    /// The seed of the random models, to reproduce the trajectories
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+impl SimulationSpec {
+    /// Return `true` if the flows are applied as they are.
+    pub fn is_ideal(&self) -> bool {
+        self.capacity_jitter_percent == 0
+            && self.delay_cycles == 0
+            && self.delay_key.is_none()
+            && self.failure_percent == 0
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkSimulationReport {
+    /// The edges whose flows differ between the current and hypothetical allocations
+    pub flows: Vec<NetworkSimulationFlowDiff>,
+}
+
+impl NetworkSimulationReport {
+    pub fn diff<M>(current: LazyFrame, hypothetical: LazyFrame, metadata: &M) -> Result<Self>
+    where
+        M: GraphMetadataPinnedExt,
+    {
+        let current = collect_flows(current, metadata)?;
+        let mut hypothetical = collect_flows(hypothetical, metadata)?;
+
+        let mut flows: Vec<_> = current
+            .into_iter()
+            .map(|((src, sink), current)| {
+                let hypothetical = hypothetical
+                    .remove(&(src.clone(), sink.clone()))
+                    .unwrap_or_default();
+                NetworkSimulationFlowDiff {
+                    src,
+                    sink,
+                    current,
+                    hypothetical,
+                }
+            })
+            .collect();
+        flows.extend(hypothetical.into_iter().map(|((src, sink), hypothetical)| {
+            NetworkSimulationFlowDiff {
+                src,
+                sink,
+                current: 0.0,
+                hypothetical,
+            }
+        }));
+        flows.retain(|flow| flow.current != flow.hypothetical);
+
+        Ok(Self { flows })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkDrainRequest {
+    pub problem: VirtualProblem<GraphMetadataPinned>,
+    /// The name of the node to be drained
+    pub node: String,
+}
+
+/// The impact of draining a node, which is simulated by removing it from the graph.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkDrainReport {
+    pub node: String,
+    /// The flows through the node, which should be moved to the other nodes
+    pub evicted: Vec<NetworkSimulationFlowDiff>,
+    /// The other flows which are changed to take over the evicted ones
+    pub moved: Vec<NetworkSimulationFlowDiff>,
+    /// The amount of the inbound flows of the node which cannot be placed anywhere else
+    pub unplaced: f64,
+    /// The edges whose hypothetical flows exceed their capacities
+    pub violations: Vec<NetworkDrainViolation>,
+    /// Whether the node can be drained without losing any flow nor violating the capacities
+    pub is_safe: bool,
+}
+
+impl NetworkDrainReport {
+    pub fn analyze<M>(
+        node: String,
+        current: LazyFrame,
+        hypothetical: LazyFrame,
+        metadata: &M,
+    ) -> Result<Self>
+    where
+        M: GraphMetadataPinnedExt,
+    {
+        let violations = collect_violations(hypothetical.clone(), metadata)?;
+        let NetworkSimulationReport { flows } =
+            NetworkSimulationReport::diff(current, hypothetical, metadata)?;
+
+        let (evicted, moved): (Vec<_>, Vec<_>) = flows
+            .into_iter()
+            .partition(|flow| flow.src == node || flow.sink == node);
+
+        // NOTE: the relocated amount is approximated by the increased flows of the other sinks
+        let hosted: f64 = evicted
+            .iter()
+            .filter(|flow| flow.sink == node)
+            .map(|flow| flow.current)
+            .sum();
+        let relocated: f64 = moved
+            .iter()
+            .map(|flow| (flow.hypothetical - flow.current).max(0.0))
+            .sum();
+        let unplaced = (hosted - relocated).max(0.0);
+
+        Ok(Self {
+            is_safe: violations.is_empty() && unplaced <= f64::EPSILON,
+            node,
+            evicted,
+            moved,
+            unplaced,
+            violations,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkDrainViolation {
+    pub src: String,
+    pub sink: String,
+    pub flow: f64,
+    pub capacity: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkSimulationFlowDiff {
+    pub src: String,
+    pub sink: String,
+    pub current: f64,
+    pub hypothetical: f64,
+}
+
+pub(crate) fn collect_flows<M>(
+    edges: LazyFrame,
+    metadata: &M,
+) -> Result<BTreeMap<(String, String), f64>>
+where
+    M: GraphMetadataPinnedExt,
+{
+    match edges {
+        LazyFrame::Empty => Ok(BTreeMap::default()),
+        #[cfg(not(feature = "df-polars"))]
+        LazyFrame::Native(edges) => collect_flows_native(edges, metadata),
+        #[cfg(feature = "df-polars")]
+        LazyFrame::Polars(edges) => self::polars::collect_flows(edges, metadata),
+    }
+}
+
+fn collect_violations<M>(edges: LazyFrame, metadata: &M) -> Result<Vec<NetworkDrainViolation>>
+where
+    M: GraphMetadataPinnedExt,
+{
+    match edges {
+        LazyFrame::Empty => Ok(Vec::default()),
+        #[cfg(not(feature = "df-polars"))]
+        LazyFrame::Native(_) => {
+            ::anyhow::bail!("drain analyses are not supported by the native dataframe backend")
+        }
+        #[cfg(feature = "df-polars")]
+        LazyFrame::Polars(edges) => self::polars::collect_violations(edges, metadata),
+    }
+}
+
+#[cfg(not(feature = "df-polars"))]
+fn collect_flows_native<M>(
+    edges: crate::frame::native::LazyFrame,
+    metadata: &M,
+) -> Result<BTreeMap<(String, String), f64>>
+where
+    M: GraphMetadataPinnedExt,
+{
+    use crate::frame::native::Value;
+
+    let edges = edges
+        .collect()
+        .map_err(|error| ::anyhow::anyhow!("failed to collect edge flows: {error}"))?;
+
+    let get_column = |key: &str| {
+        edges
+            .column(key)
+            .map(|column| column.values.as_slice())
+            .ok_or_else(|| ::anyhow::anyhow!("failed to get edge {key} column"))
+    };
+    let src = get_column(metadata.src())?;
+    let sink = get_column(metadata.sink())?;
+    let flow = get_column(metadata.flow())?;
+
+    Ok(src
+        .iter()
+        .zip(sink)
+        .zip(flow)
+        .filter_map(|((src, sink), flow)| match (src, sink, flow) {
+            (Value::String(src), Value::String(sink), Value::Number(flow)) => {
+                Some(((src.clone(), sink.clone()), *flow))
+            }
+            _ => None,
+        })
+        .collect())
+}
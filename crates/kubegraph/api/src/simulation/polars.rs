@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use pl::{datatypes::DataType, lazy::dsl, lazy::frame::LazyFrame};
+
+use crate::{
+    frame::polars::get_column,
+    graph::{GraphData, GraphMetadataPinnedExt},
+};
+
+use super::{NetworkDrainViolation, NetworkSimulationOverride};
+
+pub(super) fn apply<M>(
+    value: &NetworkSimulationOverride,
+    graph: GraphData<LazyFrame>,
+    metadata: &M,
+) -> GraphData<LazyFrame>
+where
+    M: GraphMetadataPinnedExt,
+{
+    let GraphData { edges, nodes } = graph;
+
+    let key_name = metadata.name();
+    let key_sink = metadata.sink();
+    let key_src = metadata.src();
+
+    let is_edge = |src: &str, sink: &str| {
+        dsl::col(key_src)
+            .eq(dsl::lit(src))
+            .and(dsl::col(key_sink).eq(dsl::lit(sink)))
+    };
+    let scale = |filter: dsl::Expr, key: &str, factor: f64| {
+        dsl::when(filter)
+            .then(dsl::col(key) * dsl::lit(factor))
+            .otherwise(dsl::col(key))
+            .alias(key)
+    };
+
+    match value {
+        NetworkSimulationOverride::RemoveNode { name } => GraphData {
+            edges: edges.filter(
+                dsl::col(key_src)
+                    .neq(dsl::lit(name.as_str()))
+                    .and(dsl::col(key_sink).neq(dsl::lit(name.as_str()))),
+            ),
+            nodes: nodes.filter(dsl::col(key_name).neq(dsl::lit(name.as_str()))),
+        },
+        NetworkSimulationOverride::RemoveEdge { src, sink } => GraphData {
+            edges: edges.filter(is_edge(src, sink).not()),
+            nodes,
+        },
+        NetworkSimulationOverride::ScaleNode { name, key, factor } => GraphData {
+            edges,
+            nodes: nodes.with_column(scale(
+                dsl::col(key_name).eq(dsl::lit(name.as_str())),
+                key,
+                *factor,
+            )),
+        },
+        NetworkSimulationOverride::ScaleEdge {
+            src,
+            sink,
+            key,
+            factor,
+        } => GraphData {
+            edges: edges.with_column(scale(is_edge(src, sink), key, *factor)),
+            nodes,
+        },
+    }
+}
+
+pub(super) fn collect_flows<M>(
+    edges: LazyFrame,
+    metadata: &M,
+) -> Result<BTreeMap<(String, String), f64>>
+where
+    M: GraphMetadataPinnedExt,
+{
+    let key_flow = metadata.flow();
+    let key_sink = metadata.sink();
+    let key_src = metadata.src();
+
+    let edges = edges
+        .select([
+            dsl::col(key_src),
+            dsl::col(key_sink),
+            dsl::col(key_flow).cast(DataType::Float64),
+        ])
+        .collect()
+        .map_err(|error| anyhow!("failed to collect edge flows: {error}"))?;
+
+    let src = get_column(&edges, "edge", "src", key_src, Some(&DataType::String))?;
+    let sink = get_column(&edges, "edge", "sink", key_sink, Some(&DataType::String))?;
+    let flow = get_column(&edges, "edge", "flow", key_flow, None)?;
+
+    Ok(src
+        .str()?
+        .into_iter()
+        .zip(sink.str()?)
+        .zip(flow.f64()?)
+        .filter_map(|((src, sink), flow)| Some(((src?.into(), sink?.into()), flow?)))
+        .collect())
+}
+
+pub(super) fn collect_violations<M>(
+    edges: LazyFrame,
+    metadata: &M,
+) -> Result<Vec<NetworkDrainViolation>>
+where
+    M: GraphMetadataPinnedExt,
+{
+    let key_capacity = metadata.capacity();
+    let key_flow = metadata.flow();
+    let key_sink = metadata.sink();
+    let key_src = metadata.src();
+
+    // NOTE: the edges without capacities are unbounded
+    let edges = edges
+        .select([
+            dsl::col(key_src),
+            dsl::col(key_sink),
+            dsl::col(key_flow).cast(DataType::Float64),
+            dsl::col(key_capacity).cast(DataType::Float64),
+        ])
+        .filter(dsl::col(key_flow).gt(dsl::col(key_capacity)))
+        .collect()
+        .map_err(|error| anyhow!("failed to collect edge capacity violations: {error}"))?;
+
+    let src = get_column(&edges, "edge", "src", key_src, Some(&DataType::String))?;
+    let sink = get_column(&edges, "edge", "sink", key_sink, Some(&DataType::String))?;
+    let flow = get_column(&edges, "edge", "flow", key_flow, None)?;
+    let capacity = get_column(&edges, "edge", "capacity", key_capacity, None)?;
+
+    Ok(src
+        .str()?
+        .into_iter()
+        .zip(sink.str()?)
+        .zip(flow.f64()?)
+        .zip(capacity.f64()?)
+        .filter_map(|(((src, sink), flow), capacity)| {
+            Some(NetworkDrainViolation {
+                src: src?.into(),
+                sink: sink?.into(),
+                flow: flow?,
+                capacity: capacity?,
+            })
+        })
+        .collect())
+}
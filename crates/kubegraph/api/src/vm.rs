@@ -1,5 +1,5 @@
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     fmt,
     ops::{Add, Div, Mul, Neg, Not, Sub},
     str::FromStr,
@@ -10,6 +10,7 @@ use std::{
 use anyhow::{anyhow, bail, Result};
 use ark_core::signal::FunctionSignal;
 use async_trait::async_trait;
+use chrono::Utc;
 use clap::Parser;
 use duration_string::DurationString;
 use futures::{stream::FuturesUnordered, TryStreamExt};
@@ -21,20 +22,34 @@ use tokio::time::{sleep, Instant};
 use tracing::{error, info, instrument, warn, Level};
 
 use crate::{
+    alert::{NetworkAlertRuleCrd, ALERT_MANAGER},
+    analyzer::{NetworkAnalyzer, NetworkAnalyzerPipeline},
+    circuit::{NetworkCircuitBreaker, CIRCUIT_BREAKER},
     component::{NetworkComponent, NetworkComponentExt},
     dependency::{
         NetworkDependencyPipeline, NetworkDependencyPipelineTemplate, NetworkDependencySolver,
         NetworkDependencySolverSpec,
     },
     frame::LazyFrame,
+    function::{
+        dry_run::{NetworkFunctionDryRunReport, NetworkFunctionDryRunRequest},
+        NetworkFunctionCrd,
+    },
     graph::{
-        Graph, GraphData, GraphFilter, GraphMetadata, GraphScope, NetworkGraphDB,
-        NetworkGraphDBExt, ScopedNetworkGraphDBContainer,
+        Graph, GraphData, GraphFilter, GraphMetadata, GraphMetadataPinned, GraphScope,
+        NetworkGraphDB, NetworkGraphDBExt, ScopedNetworkGraphDBContainer,
     },
+    metrics::METRICS,
     ops::{And, Eq, Ge, Gt, Le, Lt, Max, Min, Ne, Or},
     problem::{NetworkProblemCrd, ProblemSpec, VirtualProblem},
+    recorder::{
+        NetworkCapture, NetworkRecord, NetworkRecordAction, NetworkRecorder, NetworkReplayReport,
+        NetworkReplaySolver,
+    },
     resource::{NetworkResourceClient, NetworkResourceCollectionDB, NetworkResourceDB},
     runner::{NetworkRunner, NetworkRunnerContext},
+    scheduler::SCHEDULER,
+    simulation::{NetworkDrainReport, NetworkSimulationOverride, NetworkSimulationReport},
     solver::NetworkSolver,
     trader::{NetworkTrader, NetworkTraderContext},
     visualizer::{NetworkVisualizer, NetworkVisualizerExt},
@@ -165,6 +180,12 @@ where
                 }
             };
 
+            // NOTE: wake up earlier if any problem becomes due before the next cycle
+            let interval = match SCHEDULER.wait_time(Utc::now())? {
+                Some(wait_time) => interval.min(wait_time),
+                None => interval,
+            };
+
             let elapsed = instant.elapsed() + Duration::from_micros(500);
             if elapsed < interval {
                 sleep(interval - elapsed).await;
@@ -183,13 +204,40 @@ where
             return Ok(self::sealed::NetworkVirtualMachineState::Ready);
         }
 
-        // Apply it
-        problems
-            .into_iter()
-            .map(|problem| self.step_with_custom_problem(state, problem))
-            .collect::<FuturesUnordered<_>>()
-            .try_collect()
-            .await
+        // Pick the problems due now, in order of their dependencies and priorities
+        let batches = SCHEDULER.schedule(problems, Utc::now())?;
+        if batches.is_empty() {
+            return Ok(state);
+        }
+
+        // Apply them
+        let mut next_state = self::sealed::NetworkVirtualMachineState::default();
+        for problems in batches {
+            let batch_state: self::sealed::NetworkVirtualMachineState = problems
+                .into_iter()
+                .map(|problem| async move {
+                    let scope = problem.scope.clone();
+                    let schedule = problem.spec.schedule.clone();
+                    let result = self.step_with_custom_problem(state, problem).await;
+                    let name = match &result {
+                        Ok(state) => state.name(),
+                        Err(_) => "Failed",
+                    };
+                    METRICS.record_vm_step(&scope, name);
+
+                    // NOTE: the pending problems are not solved yet
+                    if result.is_ok() && state != self::sealed::NetworkVirtualMachineState::Pending
+                    {
+                        SCHEDULER.complete(&scope, &schedule, Utc::now())?;
+                    }
+                    result
+                })
+                .collect::<FuturesUnordered<_>>()
+                .try_collect()
+                .await?;
+            next_state = next_state.min(batch_state);
+        }
+        Ok(next_state)
     }
 
     #[instrument(level = Level::INFO, skip(self, state))]
@@ -232,15 +280,43 @@ where
             },
             None => return Ok(self::sealed::NetworkVirtualMachineState::Empty),
         };
+        // NOTE: the alert rules are evaluated on each graph refresh
+        if let Err(error) = self.alert(&problem, &data).await {
+            warn!(
+                "failed to evaluate alert rules: {scope}: {error}",
+                scope = &problem.scope,
+            );
+        }
+        let capture = NetworkCapture::new(self.recorder(), &problem, &data);
 
         // Step 3. Solve edge flows
-        let data = self.solver().solve(data, &problem.spec).await?;
+        let instant = Instant::now();
+        let result = solve_over_time(self.solver(), data, &problem.spec).await;
+        METRICS.record_solution(
+            &problem.scope,
+            instant.elapsed(),
+            result.as_ref().ok(),
+            &problem.spec.metadata,
+        );
+        let data = match result {
+            Ok(data) => data,
+            Err(error) => {
+                let message = error.to_string();
+                capture
+                    .finish(None, NetworkRecordAction::Failed { message })
+                    .await;
+                return Err(error);
+            }
+        };
 
         // Step 4. Register to the market if no feasible functions are found
         if matches!(&data.edges, LazyFrame::Empty) {
             info!("No feasible functions are found: {scope}");
             if self.trader().is_enabled() {
                 info!("Registering the problem to the market: {scope}");
+                capture
+                    .finish(Some(&data), NetworkRecordAction::Trading)
+                    .await;
                 let ctx = NetworkTraderContext {
                     functions,
                     graph: data,
@@ -251,26 +327,67 @@ where
                 info!("Registered the problem to the market: {scope}");
                 return Ok(self::sealed::NetworkVirtualMachineState::Trading);
             } else {
+                capture
+                    .finish(Some(&data), NetworkRecordAction::Unsolvable)
+                    .await;
                 return Ok(self::sealed::NetworkVirtualMachineState::Completed);
             }
         }
 
-        // Step 5. Apply edges to real-world (or simulator)
-        let runner_ctx = NetworkRunnerContext {
-            connectors,
-            functions,
-            kube: self.resource_db().kube(),
-            graph: data.clone(),
-            graph_db: ScopedNetworkGraphDBContainer {
-                inner: self.graph_db(),
-                scope: &scope,
-            },
-            problem,
-            static_edges,
+        // Step 5. Check whether the solution is stable enough to be applied
+        let is_paused = match &problem.spec.runner_policy.circuit_breaker {
+            Some(policy) => {
+                let decision = CIRCUIT_BREAKER.observe(
+                    &problem.scope,
+                    policy,
+                    &data.edges,
+                    &problem.spec.metadata,
+                )?;
+                if let Err(error) = decision
+                    .report(self.resource_db().kube(), &problem.scope)
+                    .await
+                {
+                    warn!("{error}");
+                }
+                decision.is_open()
+            }
+            None => false,
+        };
+
+        // Step 6. Apply edges to real-world (or simulator)
+        let action = if is_paused {
+            info!(
+                "The circuit is open; skipping the runner: {scope}",
+                scope = &problem.scope,
+            );
+            NetworkRecordAction::Paused
+        } else {
+            let runner_ctx = NetworkRunnerContext {
+                connectors,
+                functions,
+                kube: self.resource_db().kube(),
+                graph: data.clone(),
+                graph_db: ScopedNetworkGraphDBContainer {
+                    inner: self.graph_db(),
+                    scope: &scope,
+                },
+                problem,
+                static_edges,
+            };
+            match self.runner().execute(runner_ctx).await {
+                Ok(()) => NetworkRecordAction::Applied,
+                Err(error) => {
+                    let message = error.to_string();
+                    capture
+                        .finish(Some(&data), NetworkRecordAction::Failed { message })
+                        .await;
+                    return Err(error);
+                }
+            }
         };
-        self.runner().execute(runner_ctx).await?;
+        capture.finish(Some(&data), action).await;
 
-        // Step 6. Visualize the outputs
+        // Step 7. Visualize the outputs
         let graph = Graph {
             connector,
             data,
@@ -281,9 +398,94 @@ where
         Ok(self::sealed::NetworkVirtualMachineState::Completed)
     }
 
+    /// Solve the problem against a hypothetical graph without touching any state,
+    /// and return the diff between the current and hypothetical allocations.
+    #[instrument(level = Level::INFO, skip(self, problem, overrides))]
+    async fn simulate(
+        &self,
+        problem: VirtualProblem,
+        overrides: Vec<NetworkSimulationOverride>,
+    ) -> Result<NetworkSimulationReport> {
+        // Step 1. Pull & Convert graphs
+        let data = match self.pull_graph(&problem).await? {
+            Some(NetworkDependencyPipeline {
+                connectors: _,
+                functions: _,
+                template:
+                    NetworkDependencyPipelineTemplate {
+                        graph: Graph { data, .. },
+                        static_edges: _,
+                    },
+            }) => data,
+            None => return Ok(NetworkSimulationReport::default()),
+        };
+
+        // Step 2. Apply the hypothetical changes
+        let metadata = &problem.spec.metadata;
+        let hypothetical = overrides
+            .iter()
+            .try_fold(data.clone(), |data, value| value.apply(data, metadata))?;
+
+        // Step 3. Solve edge flows of both graphs
+        let current = solve_over_time(self.solver(), data, &problem.spec).await?;
+        let hypothetical = solve_over_time(self.solver(), hypothetical, &problem.spec).await?;
+
+        // Step 4. Compare the allocations
+        NetworkSimulationReport::diff(current.edges, hypothetical.edges, metadata)
+    }
+
+    /// Solve the problem against the graph without the node, and report whether
+    /// the node can be drained safely.
+    #[instrument(level = Level::INFO, skip(self, problem))]
+    async fn drain(&self, problem: VirtualProblem, node: String) -> Result<NetworkDrainReport> {
+        // Step 1. Pull & Convert graphs
+        let data = match self.pull_graph(&problem).await? {
+            Some(NetworkDependencyPipeline {
+                connectors: _,
+                functions: _,
+                template:
+                    NetworkDependencyPipelineTemplate {
+                        graph: Graph { data, .. },
+                        static_edges: _,
+                    },
+            }) => data,
+            None => {
+                return Ok(NetworkDrainReport {
+                    node,
+                    is_safe: true,
+                    ..Default::default()
+                })
+            }
+        };
+
+        // Step 2. Remove the node
+        let metadata = &problem.spec.metadata;
+        let hypothetical = NetworkSimulationOverride::RemoveNode { name: node.clone() }
+            .apply(data.clone(), metadata)?;
+
+        // Step 3. Solve edge flows of both graphs
+        let current = solve_over_time(self.solver(), data, &problem.spec).await?;
+        let hypothetical = solve_over_time(self.solver(), hypothetical, &problem.spec).await?;
+
+        // Step 4. Analyze the impact
+        NetworkDrainReport::analyze(node, current.edges, hypothetical.edges, metadata)
+    }
+
+    /// Re-execute the recorded steps in order without touching any state,
+    /// and report the decisions which differ from the recorded ones.
+    #[instrument(level = Level::INFO, skip(self, records))]
+    async fn replay(
+        &self,
+        records: Vec<NetworkRecord>,
+        solver: NetworkReplaySolver,
+    ) -> Result<NetworkReplayReport> {
+        let is_trading = self.trader().is_enabled();
+        crate::recorder::replay(self.solver(), records, solver, is_trading).await
+    }
+
     #[instrument(level = Level::INFO, skip(self))]
     async fn pull_problems(&self) -> Result<Vec<VirtualProblem>> {
-        Ok(self
+        let problems: Vec<_> = self
             .resource_db()
             .list(())
             .await
@@ -291,13 +493,74 @@ where
             .into_iter()
             .map(|cr: NetworkProblemCrd| {
                 let scope = GraphScope::from_resource(&cr);
-                VirtualProblem {
+                let reset = cr
+                    .metadata
+                    .annotations
+                    .as_ref()
+                    .and_then(|annotations| {
+                        annotations.get(NetworkCircuitBreaker::ANNOTATION_RESET)
+                    })
+                    .map(String::as_str);
+                CIRCUIT_BREAKER.reset(&scope, reset)?;
+
+                Ok(VirtualProblem {
                     filter: GraphFilter::all(scope.namespace.clone()),
                     scope,
                     spec: cr.spec,
-                }
+                })
             })
-            .collect())
+            .collect::<Result<_>>()?;
+
+        // NOTE: forget the circuits of the removed problems
+        let scopes: BTreeSet<_> = problems
+            .iter()
+            .filter(|problem| problem.spec.runner_policy.circuit_breaker.is_some())
+            .map(|problem| &problem.scope)
+            .collect();
+        CIRCUIT_BREAKER.retain(&scopes)?;
+
+        // NOTE: forget the alerts of the removed rules and problems
+        let rules: Vec<NetworkAlertRuleCrd> = self.resource_db().list(()).await.unwrap_or_default();
+        let rules = rules.iter().map(GraphScope::from_resource).collect();
+        let scopes = problems.iter().map(|problem| &problem.scope).collect();
+        ALERT_MANAGER.retain(&rules, &scopes)?;
+        Ok(problems)
+    }
+
+    /// Evaluate the alert rules against the refreshed graph of the problem,
+    /// and report the fired or resolved alerts.
+    #[instrument(level = Level::INFO, skip(self, problem, data))]
+    async fn alert(&self, problem: &VirtualProblem, data: &GraphData<LazyFrame>) -> Result<()> {
+        let rules = self
+            .resource_db()
+            .list(())
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|cr: NetworkAlertRuleCrd| (GraphScope::from_resource(&cr), cr.spec))
+            .filter(|(rule, spec)| {
+                rule.namespace == problem.scope.namespace && spec.matches(&problem.scope)
+            });
+
+        let now = Utc::now();
+        for (rule, spec) in rules {
+            let breaches = match spec.evaluate(data, &problem.spec.metadata) {
+                Ok(breaches) => breaches,
+                Err(error) => {
+                    warn!("failed to evaluate the alert rule: {rule}: {error}");
+                    continue;
+                }
+            };
+            for alert in ALERT_MANAGER.observe(&rule, &problem.scope, &spec, breaches, now)? {
+                if let Err(error) = alert
+                    .report(self.resource_db().kube(), &rule, &spec, &problem.scope)
+                    .await
+                {
+                    warn!("{error}");
+                }
+            }
+        }
+        Ok(())
     }
 
     #[instrument(level = Level::INFO, skip(self, problem))]
@@ -306,24 +569,23 @@ where
         problem: &VirtualProblem,
     ) -> Result<Option<NetworkDependencyPipeline<Graph<GraphData<LazyFrame>>>>> {
         let VirtualProblem {
-            filter,
+            filter: _,
             scope,
-            spec: ProblemSpec {
-                metadata,
-                verbose: _,
-            },
+            spec:
+                ProblemSpec {
+                    analyzers,
+                    direction: _,
+                    metadata,
+                    runner_policy: _,
+                    schedule: _,
+                    simulation: _,
+                    time_expansion: _,
+                    verbose: _,
+                },
         } = problem;
 
         // Step 1. Collect all graphs
-        let graphs = match self
-            .graph_db()
-            .get_global_namespaced(&scope.namespace)
-            .await?
-        {
-            // If there is a global graph, use this
-            Some(graph) => vec![graph],
-            None => self.graph_db().list(filter).await?,
-        };
+        let graphs = self.pull_graphs(problem).await?;
         if graphs.is_empty() {
             return Ok(None);
         }
@@ -344,6 +606,7 @@ where
             .await
             .unwrap_or_default()
             .into_iter()
+            .filter(|cr: &NetworkFunctionCrd| !cr.is_dry_run())
             .map(|cr| (GraphScope::from_resource(&cr), cr))
             .collect();
 
@@ -360,6 +623,13 @@ where
             .build_pipeline(problem, spec)
             .await?;
 
+        // Step 5. Derive the extra columns
+        let data = analyzers
+            .iter()
+            .cloned()
+            .collect::<NetworkAnalyzerPipeline>()
+            .analyze(data, metadata)?;
+
         Ok(Some(NetworkDependencyPipeline {
             connectors,
             functions,
@@ -378,6 +648,71 @@ where
         }))
     }
 
+    #[instrument(level = Level::INFO, skip(self, problem))]
+    async fn pull_graphs(
+        &self,
+        problem: &VirtualProblem,
+    ) -> Result<Vec<Graph<GraphData<LazyFrame>>>> {
+        let VirtualProblem {
+            filter,
+            scope,
+            spec: _,
+        } = problem;
+
+        match self
+            .graph_db()
+            .get_global_namespaced(&scope.namespace)
+            .await?
+        {
+            // If there is a global graph, use this
+            Some(graph) => Ok(vec![graph]),
+            None => self.graph_db().list(filter).await,
+        }
+    }
+
+    /// Evaluate the function against the current graph without committing the outputs,
+    /// and return the sampled rows of the derived columns.
+    #[instrument(level = Level::INFO, skip(self, request))]
+    async fn dry_run_function(
+        &self,
+        request: NetworkFunctionDryRunRequest,
+    ) -> Result<NetworkFunctionDryRunReport> {
+        let NetworkFunctionDryRunRequest {
+            problem,
+            function: name,
+            limit,
+        } = request;
+
+        // Step 1. Find the function
+        let scope = GraphScope {
+            namespace: problem.scope.namespace.clone(),
+            name,
+        };
+        let function = self
+            .resource_db()
+            .list(())
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .find(|cr: &NetworkFunctionCrd| GraphScope::from_resource(cr) == scope)
+            .ok_or_else(|| anyhow!("no such function: {scope}"))?;
+
+        // Step 2. Pull graphs
+        let graphs = self.pull_graphs(&problem).await?;
+        if graphs.is_empty() {
+            bail!("no graphs to evaluate the function: {scope}");
+        }
+
+        // Step 3. Evaluate the function
+        let output = self
+            .dependency_solver()
+            .dry_run(&problem, function, graphs)
+            .await?;
+
+        // Step 4. Sample the outputs
+        NetworkFunctionDryRunReport::sample(output, &problem.spec.metadata, limit)
+    }
+
     #[instrument(level = Level::INFO, skip(self))]
     async fn close(&self) -> Result<()> {
         self.graph_db().close().await?;
@@ -403,6 +738,18 @@ mod sealed {
         Completed,
     }
 
+    impl NetworkVirtualMachineState {
+        pub(super) const fn name(&self) -> &'static str {
+            match self {
+                Self::Pending => "Pending",
+                Self::Ready => "Ready",
+                Self::Empty => "Empty",
+                Self::Trading => "Trading",
+                Self::Completed => "Completed",
+            }
+        }
+    }
+
     impl Extend<Self> for NetworkVirtualMachineState {
         fn extend<T: IntoIterator<Item = Self>>(&mut self, iter: T) {
             *self = iter.into_iter().min().unwrap_or(*self)
@@ -410,6 +757,25 @@ mod sealed {
     }
 }
 
+/// Solve the graph, planning ahead over the time steps if requested.
+pub(crate) async fn solve_over_time<S>(
+    solver: &S,
+    graph: GraphData<LazyFrame>,
+    problem: &ProblemSpec<GraphMetadataPinned>,
+) -> Result<GraphData<LazyFrame>>
+where
+    S: ?Sized + Sync + NetworkSolver<GraphData<LazyFrame>, Output = GraphData<LazyFrame>>,
+{
+    match &problem.time_expansion {
+        Some(spec) => {
+            let graph = spec.expand(graph, &problem.metadata)?;
+            let graph = solver.solve(graph, problem).await?;
+            spec.collapse(graph, &problem.metadata)
+        }
+        None => solver.solve(graph, problem).await,
+    }
+}
+
 #[async_trait]
 pub trait NetworkVirtualMachine
 where
@@ -443,6 +809,11 @@ where
 
     fn visualizer(&self) -> &<Self as NetworkVirtualMachine>::Visualizer;
 
+    /// Return the recorder capturing the steps of the loop, if enabled.
+    fn recorder(&self) -> Option<&NetworkRecorder> {
+        None
+    }
+
     fn fallback_policy(&self) -> NetworkFallbackPolicy {
         NetworkFallbackPolicy::default()
     }
@@ -495,6 +866,10 @@ where
         <T as NetworkVirtualMachine>::visualizer(&**self)
     }
 
+    fn recorder(&self) -> Option<&NetworkRecorder> {
+        <T as NetworkVirtualMachine>::recorder(&**self)
+    }
+
     fn fallback_policy(&self) -> NetworkFallbackPolicy {
         <T as NetworkVirtualMachine>::fallback_policy(&**self)
     }
@@ -624,6 +999,9 @@ pub enum Stmt {
     DefineLocalValue {
         value: Option<Number>,
     },
+    DefineLocalString {
+        value: String,
+    },
     BinaryExpr {
         lhs: Value,
         rhs: Value,
@@ -637,6 +1015,11 @@ pub enum Stmt {
         op: FunctionExpr,
         args: Vec<Value>,
     },
+    ConditionExpr {
+        r#if: Value,
+        then: Value,
+        r#else: Value,
+    },
 }
 
 impl From<Value> for Stmt {
@@ -669,9 +1052,11 @@ impl Stmt {
             Stmt::DefineLocalFeature { value: None } => None,
             Stmt::DefineLocalValue { value: Some(value) } => Some(Value::Number(*value)),
             Stmt::DefineLocalValue { value: None } => None,
+            Stmt::DefineLocalString { .. } => None,
             Stmt::BinaryExpr { .. } => None,
             Stmt::UnaryExpr { .. } => None,
             Stmt::FunctionExpr { .. } => None,
+            Stmt::ConditionExpr { .. } => None,
         }
     }
 }
@@ -853,6 +1238,19 @@ impl_expr_function_builtin!(impl Max(max) for self as Number -> Number);
 impl_expr_function_builtin!(impl Min(min) for self as Number -> Number);
 
 impl Value {
+    /// Select `then` if the condition holds, or `else` otherwise.
+    pub fn select(self, then: Self, r#else: Self) -> Result<Stmt> {
+        match self.to_feature()? {
+            Some(r#if) if r#if.into_inner() => Ok(then.into()),
+            Some(_) => Ok(r#else.into()),
+            None => Ok(Stmt::ConditionExpr {
+                r#if: self,
+                then,
+                r#else,
+            }),
+        }
+    }
+
     // fn is_feature(&self) -> bool {
     //     matches!(self, Self::Feature(_))
     // }
@@ -1090,6 +1488,16 @@ pub enum FunctionExpr {
 pub enum BuiltInFunctionExpr {
     Max,
     Min,
+    /// Test whether the strings contain the regex pattern
+    Matches,
+    /// Sum up the values of all rows
+    Sum,
+    /// Sum up the values per group
+    SumOver,
+    /// Find the maximum value per group
+    MaxOver,
+    /// Find the minimum value per group
+    MinOver,
 }
 
 #[derive(
@@ -0,0 +1,172 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use ark_core::tracer;
+use clap::{value_parser, ArgAction, Parser, Subcommand, ValueEnum};
+use kubegraph_api::{
+    analyzer::{NetworkAnalyzer, NetworkAnalyzerPipeline},
+    frame::LazyFrame,
+    graph::{GraphData, GraphMetadataPinned},
+    problem::ProblemSpec,
+};
+use tokio::fs;
+use tracing::{info, instrument, Level};
+
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+pub(crate) struct Args {
+    #[command(flatten)]
+    common: ArgsCommon,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+impl Args {
+    pub(crate) async fn run(self) -> Result<()> {
+        self.common.run();
+        self.command.run().await
+    }
+}
+
+#[derive(Parser)]
+pub(crate) struct ArgsCommon {
+    /// Turn debugging information on
+    #[arg(short, long, global = true, env = "KUBEGRAPH_DEBUG", action = ArgAction::Count)]
+    #[arg(value_parser = value_parser!(u8).range(..=3))]
+    debug: u8,
+}
+
+impl ArgsCommon {
+    fn run(self) {
+        tracer::init_once_with_level_int(self.debug, false)
+    }
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub(crate) enum Command {
+    /// Solve a problem on the local node/edge files, without a Kubernetes cluster
+    Solve(SolveArgs),
+}
+
+impl Command {
+    async fn run(self) -> Result<()> {
+        match self {
+            Self::Solve(args) => args.run().await,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Parser)]
+pub(crate) struct SolveArgs {
+    /// The edges file (.csv, .parquet)
+    #[arg(long, env = "KUBEGRAPH_EDGES", value_name = "PATH")]
+    edges: PathBuf,
+
+    /// The nodes file (.csv, .parquet)
+    #[arg(long, env = "KUBEGRAPH_NODES", value_name = "PATH")]
+    nodes: PathBuf,
+
+    /// The problem spec file in YAML; the default problem is used if not given
+    #[arg(short, long, env = "KUBEGRAPH_PROBLEM", value_name = "PATH")]
+    problem: Option<PathBuf>,
+
+    /// The solver to optimize the graph
+    #[arg(long, env = "KUBEGRAPH_SOLVER", value_enum, default_value_t = SolverKind::default())]
+    solver: SolverKind,
+
+    /// Export the optimized edges into the file (.csv, .parquet)
+    #[arg(long, value_name = "PATH")]
+    output_edges: Option<PathBuf>,
+
+    /// Export the optimized nodes into the file (.csv, .parquet)
+    #[arg(long, value_name = "PATH")]
+    output_nodes: Option<PathBuf>,
+}
+
+impl SolveArgs {
+    #[instrument(level = Level::INFO, skip(self), err(Display))]
+    async fn run(self) -> Result<()> {
+        // Step 1. Load the problem
+        let problem: ProblemSpec<GraphMetadataPinned> = match &self.problem {
+            Some(path) => {
+                let data = fs::read(path).await.map_err(|error| {
+                    anyhow!("failed to read problem ({}): {error}", path.display())
+                })?;
+                ::serde_yaml::from_slice(&data).map_err(|error| {
+                    anyhow!("failed to parse problem ({}): {error}", path.display())
+                })?
+            }
+            None => ProblemSpec::default(),
+        };
+
+        // Step 2. Load the graph
+        let graph = GraphData {
+            edges: super::frame::load(&self.edges)?,
+            nodes: super::frame::load(&self.nodes)?,
+        };
+
+        // Step 3. Derive the extra columns
+        let graph = problem
+            .analyzers
+            .iter()
+            .cloned()
+            .collect::<NetworkAnalyzerPipeline>()
+            .analyze(graph.into(), &problem.metadata)?;
+
+        // Step 4. Optimize the graph, planning ahead over the time steps if requested
+        info!("Solving the graph with {solver:?}", solver = self.solver);
+        let graph = match &problem.time_expansion {
+            Some(spec) => {
+                let graph = spec.expand(graph, &problem.metadata)?;
+                let graph = self.solver.solve(graph, &problem).await?;
+                spec.collapse(graph, &problem.metadata)?
+            }
+            None => self.solver.solve(graph, &problem).await?,
+        };
+        let GraphData { edges, nodes } = graph;
+        let mut edges = edges.try_into_polars()?.collect()?;
+        let mut nodes = nodes.try_into_polars()?.collect()?;
+
+        // Step 5. Print or export the optimized graph
+        match &self.output_edges {
+            Some(path) => super::frame::save(path, &mut edges)?,
+            None => println!("{edges}"),
+        }
+        match &self.output_nodes {
+            Some(path) => super::frame::save(path, &mut nodes)?,
+            None => println!("{nodes}"),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum SolverKind {
+    #[default]
+    Ortools,
+}
+
+impl SolverKind {
+    async fn solve(
+        self,
+        graph: GraphData<LazyFrame>,
+        problem: &ProblemSpec<GraphMetadataPinned>,
+    ) -> Result<GraphData<LazyFrame>> {
+        match self {
+            #[cfg(feature = "solver-ortools")]
+            Self::Ortools => {
+                use kubegraph_api::solver::NetworkSolver;
+
+                ::kubegraph_solver_ortools::NetworkSolver::default()
+                    .solve(graph, problem)
+                    .await
+            }
+            #[cfg(not(feature = "solver-ortools"))]
+            Self::Ortools => {
+                let _ = (graph, problem);
+                ::anyhow::bail!("ortools solver is not enabled in this build")
+            }
+        }
+    }
+}
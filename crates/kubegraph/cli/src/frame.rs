@@ -0,0 +1,60 @@
+use std::{fs::File, path::Path};
+
+use anyhow::{anyhow, bail, Result};
+use pl::{
+    frame::DataFrame,
+    prelude::{
+        CsvReadOptions, CsvWriter, IntoLazy, LazyFrame, ParquetReader, ParquetWriter, SerReader,
+        SerWriter,
+    },
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum FileFormat {
+    Csv,
+    Parquet,
+}
+
+impl FileFormat {
+    fn from_path(path: &Path) -> Result<Self> {
+        match path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase())
+            .as_deref()
+        {
+            Some("csv") => Ok(Self::Csv),
+            Some("parquet") => Ok(Self::Parquet),
+            Some(_) | None => bail!(
+                "unsupported file format; expected .csv or .parquet: {}",
+                path.display(),
+            ),
+        }
+    }
+}
+
+pub(crate) fn load(path: &Path) -> Result<LazyFrame> {
+    let df = match FileFormat::from_path(path)? {
+        FileFormat::Csv => CsvReadOptions::default()
+            .with_has_header(true)
+            .try_into_reader_with_file_path(Some(path.into()))
+            .and_then(|reader| reader.finish()),
+        FileFormat::Parquet => File::open(path)
+            .map_err(Into::into)
+            .and_then(|file| ParquetReader::new(file).finish()),
+    }
+    .map_err(|error| anyhow!("failed to load {}: {error}", path.display()))?;
+    Ok(df.lazy())
+}
+
+pub(crate) fn save(path: &Path, df: &mut DataFrame) -> Result<()> {
+    let format = FileFormat::from_path(path)?;
+    let file = File::create(path)
+        .map_err(|error| anyhow!("failed to create {}: {error}", path.display()))?;
+
+    match format {
+        FileFormat::Csv => CsvWriter::new(file).finish(df),
+        FileFormat::Parquet => ParquetWriter::new(file).finish(df).map(|_| ()),
+    }
+    .map_err(|error| anyhow!("failed to save {}: {error}", path.display()))
+}
@@ -0,0 +1,11 @@
+extern crate polars as pl;
+
+mod args;
+mod frame;
+
+#[tokio::main]
+async fn main() -> ::anyhow::Result<()> {
+    use clap::Parser;
+
+    self::args::Args::parse().run().await
+}
@@ -10,7 +10,8 @@ use kubegraph_api::{
     dependency::{NetworkDependencyPipelineTemplate, NetworkDependencySolverSpec},
     frame::LazyFrame,
     function::{
-        FunctionMetadata, NetworkFunctionCrd, NetworkFunctionKind, NetworkFunctionTemplate,
+        dry_run::NetworkFunctionDryRunOutput, FunctionMetadata, NetworkFunctionCrd,
+        NetworkFunctionKind, NetworkFunctionTemplate,
     },
     graph::{GraphData, GraphEdges, GraphMetadataExt, GraphScope},
     problem::VirtualProblem,
@@ -172,6 +173,52 @@ impl ::kubegraph_api::dependency::NetworkDependencySolver for NetworkDependencyG
             static_edges: Some(static_edges),
         })
     }
+
+    #[instrument(level = Level::INFO, skip(self, problem, function, graphs))]
+    async fn dry_run(
+        &self,
+        problem: &VirtualProblem,
+        function: NetworkFunctionCrd,
+        graphs: Vec<::kubegraph_api::graph::Graph<GraphData<LazyFrame>>>,
+    ) -> Result<NetworkFunctionDryRunOutput> {
+        let function = Function::new(function, problem)?;
+
+        // Step 1. Collect all nodes
+        let nodes = graphs
+            .into_iter()
+            .map(
+                |::kubegraph_api::graph::Graph {
+                     connector: _,
+                     data:
+                         GraphData {
+                             edges: _,
+                             mut nodes,
+                         },
+                     metadata: _,
+                     scope,
+                 }| {
+                    // Mark the connector
+                    nodes.alias_nodes(&problem.spec.metadata, &scope)?;
+                    Ok(GraphEdges::new(nodes))
+                },
+            )
+            .collect::<Result<GraphEdges<_>>>()?;
+
+        // Step 2. Evaluate the function
+        let data = function
+            .infer(
+                problem,
+                &function.metadata(),
+                nodes.into_inner(),
+                function.infer_type(),
+            )?
+            .into_inner();
+
+        Ok(NetworkFunctionDryRunOutput {
+            columns: function.provided.clone(),
+            data,
+        })
+    }
 }
 
 trait GraphPipelineBuilder {
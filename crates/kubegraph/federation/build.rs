@@ -0,0 +1,19 @@
+use tonic_build::manual::{Builder, Method, Service};
+
+fn main() {
+    let service = Service::builder()
+        .name("NetworkGraphFederation")
+        .package("kubegraph.federation")
+        .method(
+            Method::builder()
+                .name("push")
+                .route_name("Push")
+                .input_type("crate::proto::PushGraphsRequest")
+                .output_type("crate::proto::PushGraphsResponse")
+                .codec_path("::tonic::codec::ProstCodec")
+                .build(),
+        )
+        .build();
+
+    Builder::new().compile(&[service]);
+}
@@ -0,0 +1,77 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use futures::{stream::FuturesUnordered, TryStreamExt};
+use kubegraph_api::{
+    frame::DataFrame,
+    graph::{Graph, GraphData, GraphFilter, NetworkGraphDB},
+};
+use tokio::time::sleep;
+use tonic::transport::Channel;
+use tracing::{debug, instrument, warn, Level};
+
+use crate::{
+    proto::{network_graph_federation_client::NetworkGraphFederationClient, PushGraphsRequest},
+    NetworkFederationArgs,
+};
+
+pub(crate) async fn loop_forever<DB>(graph_db: DB, args: NetworkFederationArgs)
+where
+    DB: NetworkGraphDB,
+{
+    let interval = args.interval();
+
+    let mut client = None;
+    loop {
+        if let Err(error) = try_push(&graph_db, &args, &mut client).await {
+            warn!("failed to push graphs to the federation aggregator: {error}");
+
+            // reconnect on the next push
+            client = None;
+        }
+        sleep(interval).await;
+    }
+}
+
+#[instrument(level = Level::INFO, skip_all)]
+async fn try_push<DB>(
+    graph_db: &DB,
+    args: &NetworkFederationArgs,
+    client: &mut Option<NetworkGraphFederationClient<Channel>>,
+) -> Result<()>
+where
+    DB: NetworkGraphDB,
+{
+    // Step 1. Collect all local graphs
+    let timestamp = Utc::now().timestamp_millis();
+    let graphs: Vec<Graph<GraphData<DataFrame>>> = graph_db
+        .list(&GraphFilter::all(String::default()))
+        .await?
+        .into_iter()
+        .map(|graph| graph.collect())
+        .collect::<FuturesUnordered<_>>()
+        .try_collect()
+        .await?;
+
+    let request = PushGraphsRequest {
+        cluster: args.federation_cluster_name.clone(),
+        timestamp,
+        graphs: ::serde_json::to_vec(&graphs)?,
+    };
+
+    // Step 2. Connect to the aggregator
+    let client = match client {
+        Some(client) => client,
+        None => client.insert(
+            NetworkGraphFederationClient::connect(args.federation_aggregator_endpoint.clone())
+                .await
+                .map_err(|error| anyhow!("failed to connect to the aggregator: {error}"))?,
+        ),
+    };
+
+    // Step 3. Push the graphs
+    client
+        .push(request)
+        .await
+        .map(|_| debug!("Pushed {} graphs to the aggregator", graphs.len()))
+        .map_err(|error| anyhow!("failed to push graphs: {error}"))
+}
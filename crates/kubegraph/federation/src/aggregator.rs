@@ -0,0 +1,210 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    net::SocketAddr,
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Result};
+use ark_core::signal::FunctionSignal;
+use kubegraph_api::{
+    frame::DataFrame,
+    graph::{Graph, GraphData, GraphScope, NetworkGraphDB},
+};
+use tokio::{
+    sync::Mutex,
+    time::{sleep, Instant},
+};
+use tonic::{transport::Server, Request, Response, Status};
+use tracing::{error, info, instrument, warn, Level};
+
+use crate::{
+    proto::{
+        network_graph_federation_server::{NetworkGraphFederation, NetworkGraphFederationServer},
+        PushGraphsRequest, PushGraphsResponse,
+    },
+    NetworkFederationArgs,
+};
+
+pub(crate) async fn loop_forever<DB>(
+    signal: FunctionSignal,
+    graph_db: DB,
+    args: NetworkFederationArgs,
+) where
+    DB: 'static + Send + Sync + Clone + NetworkGraphDB,
+{
+    let addr: SocketAddr = match args.federation_bind_addr.parse() {
+        Ok(addr) => addr,
+        Err(error) => {
+            signal
+                .panic(anyhow!("failed to parse federation bind address: {error}"))
+                .await
+        }
+    };
+
+    let aggregator = NetworkFederationAggregator {
+        clusters: Arc::default(),
+        graph_db,
+    };
+
+    let interval = args.interval();
+    let reaper = ::tokio::spawn({
+        let aggregator = aggregator.clone();
+        async move { aggregator.reap_forever(&args).await }
+    });
+
+    loop {
+        info!("Starting federation aggregator server on {addr}...");
+        let server = Server::builder()
+            .add_service(NetworkGraphFederationServer::new(aggregator.clone()))
+            .serve(addr)
+            .await;
+
+        match server {
+            Ok(()) => break,
+            Err(error) => {
+                error!("failed to operate federation aggregator server: {error}");
+                sleep(interval).await;
+            }
+        }
+    }
+    reaper.abort();
+}
+
+#[derive(Clone)]
+struct NetworkFederationAggregator<DB> {
+    clusters: Arc<Mutex<BTreeMap<String, NetworkFederationClusterState>>>,
+    graph_db: DB,
+}
+
+impl<DB> NetworkFederationAggregator<DB>
+where
+    DB: NetworkGraphDB,
+{
+    const SEPARATOR: &'static str = ":";
+
+    /// Prefix the graph scope with the cluster name, so that the graphs of
+    /// different clusters never collide with each other.
+    fn federated_scope(cluster: &str, scope: GraphScope) -> GraphScope {
+        let GraphScope { namespace, name } = scope;
+        GraphScope {
+            namespace,
+            name: format!("{cluster}{sep}{name}", sep = Self::SEPARATOR),
+        }
+    }
+
+    async fn reap_forever(&self, args: &NetworkFederationArgs) {
+        let interval = args.interval();
+        let timeout = args.stale_timeout();
+
+        loop {
+            if let Err(error) = self.reap(timeout).await {
+                warn!("failed to drop stale federated graphs: {error}");
+            }
+            sleep(interval).await;
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn reap(&self, timeout: ::std::time::Duration) -> Result<()> {
+        let mut clusters = self.clusters.lock().await;
+
+        let stale_clusters: Vec<_> = clusters
+            .iter()
+            .filter(|(_, state)| state.last_seen.elapsed() >= timeout)
+            .map(|(cluster, _)| cluster.clone())
+            .collect();
+
+        for cluster in stale_clusters {
+            warn!("Dropping stale federated cluster: {cluster}");
+            if let Some(state) = clusters.remove(&cluster) {
+                for scope in state.scopes {
+                    self.graph_db.remove(scope).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[instrument(level = Level::INFO, skip(self, graphs))]
+    async fn insert(
+        &self,
+        cluster: String,
+        timestamp: i64,
+        graphs: Vec<Graph<GraphData<DataFrame>>>,
+    ) -> Result<(), Status> {
+        let mut clusters = self.clusters.lock().await;
+
+        // Reject out-of-order snapshots, which may be sent by a delayed agent
+        // or a duplicated agent sharing the same cluster name
+        if let Some(state) = clusters.get(&cluster) {
+            if timestamp <= state.timestamp {
+                return Err(Status::failed_precondition(format!(
+                    "outdated snapshot of cluster {cluster:?}: {timestamp} <= {}",
+                    state.timestamp,
+                )));
+            }
+        }
+
+        let mut scopes = BTreeSet::default();
+        for graph in graphs {
+            let mut graph = graph.lazy();
+            graph.scope = Self::federated_scope(&cluster, graph.scope);
+            scopes.insert(graph.scope.clone());
+
+            self.graph_db
+                .insert(graph)
+                .await
+                .map_err(|error| Status::internal(error.to_string()))?;
+        }
+
+        // Drop the graphs which are removed from the cluster
+        if let Some(state) = clusters.get(&cluster) {
+            for scope in state.scopes.difference(&scopes) {
+                self.graph_db
+                    .remove(scope.clone())
+                    .await
+                    .map_err(|error| Status::internal(error.to_string()))?;
+            }
+        }
+
+        clusters.insert(
+            cluster,
+            NetworkFederationClusterState {
+                last_seen: Instant::now(),
+                scopes,
+                timestamp,
+            },
+        );
+        Ok(())
+    }
+}
+
+#[::tonic::async_trait]
+impl<DB> NetworkGraphFederation for NetworkFederationAggregator<DB>
+where
+    DB: 'static + Send + Sync + NetworkGraphDB,
+{
+    async fn push(
+        &self,
+        request: Request<PushGraphsRequest>,
+    ) -> Result<Response<PushGraphsResponse>, Status> {
+        let PushGraphsRequest {
+            cluster,
+            timestamp,
+            graphs,
+        } = request.into_inner();
+
+        let graphs = ::serde_json::from_slice(&graphs)
+            .map_err(|error| Status::invalid_argument(format!("invalid graphs: {error}")))?;
+
+        self.insert(cluster, timestamp, graphs)
+            .await
+            .map(|()| Response::new(PushGraphsResponse {}))
+    }
+}
+
+struct NetworkFederationClusterState {
+    last_seen: Instant,
+    scopes: BTreeSet<GraphScope>,
+    timestamp: i64,
+}
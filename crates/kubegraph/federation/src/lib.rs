@@ -0,0 +1,183 @@
+mod agent;
+mod aggregator;
+mod proto;
+
+use std::time::Duration;
+
+use ark_core::signal::FunctionSignal;
+use clap::{Parser, ValueEnum};
+use kubegraph_api::graph::NetworkGraphDB;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use tracing::info;
+
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema, Parser,
+)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkFederationArgs {
+    #[arg(
+        long,
+        env = "KUBEGRAPH_FEDERATION_MODE",
+        value_enum,
+        value_name = "MODE",
+        default_value_t = NetworkFederationMode::default(),
+    )]
+    #[serde(default)]
+    pub federation_mode: NetworkFederationMode,
+
+    /// The aggregator endpoint to push the graphs (agent mode only)
+    #[arg(
+        long,
+        env = "KUBEGRAPH_FEDERATION_AGGREGATOR_ENDPOINT",
+        value_name = "URL",
+        default_value_t = NetworkFederationArgs::default_aggregator_endpoint(),
+    )]
+    #[serde(default = "NetworkFederationArgs::default_aggregator_endpoint")]
+    pub federation_aggregator_endpoint: String,
+
+    /// The address to listen the agents (aggregator mode only)
+    #[arg(
+        long,
+        env = "KUBEGRAPH_FEDERATION_BIND_ADDR",
+        value_name = "ADDR",
+        default_value_t = NetworkFederationArgs::default_bind_addr(),
+    )]
+    #[serde(default = "NetworkFederationArgs::default_bind_addr")]
+    pub federation_bind_addr: String,
+
+    /// The unique name of this cluster, used to prefix the graph scopes
+    #[arg(
+        long,
+        env = "KUBEGRAPH_FEDERATION_CLUSTER_NAME",
+        value_name = "NAME",
+        default_value_t = NetworkFederationArgs::default_cluster_name(),
+    )]
+    #[serde(default = "NetworkFederationArgs::default_cluster_name")]
+    pub federation_cluster_name: String,
+
+    #[arg(
+        long,
+        env = "KUBEGRAPH_FEDERATION_INTERVAL_MS",
+        value_name = "MILLISECONDS",
+        default_value_t = NetworkFederationArgs::default_interval_ms(),
+    )]
+    #[serde(default = "NetworkFederationArgs::default_interval_ms")]
+    pub federation_interval_ms: u64,
+
+    /// The duration after which the graphs of a silent cluster are dropped
+    #[arg(
+        long,
+        env = "KUBEGRAPH_FEDERATION_STALE_TIMEOUT_MS",
+        value_name = "MILLISECONDS",
+        default_value_t = NetworkFederationArgs::default_stale_timeout_ms(),
+    )]
+    #[serde(default = "NetworkFederationArgs::default_stale_timeout_ms")]
+    pub federation_stale_timeout_ms: u64,
+}
+
+impl Default for NetworkFederationArgs {
+    fn default() -> Self {
+        Self {
+            federation_mode: NetworkFederationMode::default(),
+            federation_aggregator_endpoint: Self::default_aggregator_endpoint(),
+            federation_bind_addr: Self::default_bind_addr(),
+            federation_cluster_name: Self::default_cluster_name(),
+            federation_interval_ms: Self::default_interval_ms(),
+            federation_stale_timeout_ms: Self::default_stale_timeout_ms(),
+        }
+    }
+}
+
+impl NetworkFederationArgs {
+    fn default_aggregator_endpoint() -> String {
+        "http://kubegraph-federation.kubegraph.svc:50051".into()
+    }
+
+    fn default_bind_addr() -> String {
+        "0.0.0.0:50051".into()
+    }
+
+    fn default_cluster_name() -> String {
+        "default".into()
+    }
+
+    const fn default_interval_ms() -> u64 {
+        5_000
+    }
+
+    const fn default_stale_timeout_ms() -> u64 {
+        30_000
+    }
+
+    const fn interval(&self) -> Duration {
+        Duration::from_millis(self.federation_interval_ms)
+    }
+
+    const fn stale_timeout(&self) -> Duration {
+        Duration::from_millis(self.federation_stale_timeout_ms)
+    }
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    ValueEnum,
+)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "kebab-case")]
+pub enum NetworkFederationMode {
+    #[default]
+    Disabled,
+    /// Push the local graphs to the aggregator
+    Agent,
+    /// Collect the graphs from the agents
+    Aggregator,
+}
+
+pub struct NetworkFederationWorker {
+    inner: Option<JoinHandle<()>>,
+}
+
+impl NetworkFederationWorker {
+    pub fn spawn<DB>(signal: &FunctionSignal, graph_db: DB, args: NetworkFederationArgs) -> Self
+    where
+        DB: 'static + Send + Sync + Clone + NetworkGraphDB,
+    {
+        Self {
+            inner: match args.federation_mode {
+                NetworkFederationMode::Disabled => None,
+                NetworkFederationMode::Agent => {
+                    info!("Starting federation agent...");
+                    Some(::tokio::spawn(self::agent::loop_forever(graph_db, args)))
+                }
+                NetworkFederationMode::Aggregator => {
+                    info!("Starting federation aggregator...");
+                    Some(::tokio::spawn(self::aggregator::loop_forever(
+                        signal.clone(),
+                        graph_db,
+                        args,
+                    )))
+                }
+            },
+        }
+    }
+
+    pub fn abort(&self) {
+        if let Some(inner) = &self.inner {
+            inner.abort()
+        }
+    }
+}
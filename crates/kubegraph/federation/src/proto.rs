@@ -0,0 +1,22 @@
+include!(concat!(
+    env!("OUT_DIR"),
+    "/kubegraph.federation.NetworkGraphFederation.rs"
+));
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PushGraphsRequest {
+    /// The unique name of the source cluster
+    #[prost(string, tag = "1")]
+    pub cluster: String,
+
+    /// The snapshot time of the graphs in milliseconds
+    #[prost(int64, tag = "2")]
+    pub timestamp: i64,
+
+    /// The JSON-encoded graphs
+    #[prost(bytes = "vec", tag = "3")]
+    pub graphs: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PushGraphsResponse {}
@@ -6,9 +6,10 @@ use anyhow::{anyhow, Result};
 use ark_core::{env::infer, signal::FunctionSignal};
 use futures::TryFutureExt;
 use kubegraph_api::{
-    graph::NetworkGraphDB,
+    graph::{auth::NetworkGraphAuthorizer, NetworkGraphDB},
     vm::{NetworkFallbackPolicy, NetworkVirtualMachine},
 };
+use prometheus::Registry;
 use tokio::time::sleep;
 use tracing::{error, info, instrument, warn, Level};
 
@@ -18,9 +19,14 @@ async fn health() -> impl Responder {
     HttpResponse::Ok().json("healthy")
 }
 
-pub async fn loop_forever(signal: FunctionSignal, vm: impl NetworkVirtualMachine) {
+pub async fn loop_forever(
+    signal: FunctionSignal,
+    vm: crate::vm::NetworkVirtualMachine,
+    registry: Registry,
+) {
+    let registry = Data::new(registry);
     loop {
-        if let Err(error) = try_loop_forever(&vm).await {
+        if let Err(error) = try_loop_forever(&vm, &registry).await {
             error!("failed to operate http server: {error}");
 
             match vm.fallback_policy() {
@@ -38,21 +44,46 @@ pub async fn loop_forever(signal: FunctionSignal, vm: impl NetworkVirtualMachine
     }
 }
 
-async fn try_loop_forever(vm: &impl NetworkVirtualMachine) -> Result<()> {
+async fn try_loop_forever(
+    vm: &crate::vm::NetworkVirtualMachine,
+    registry: &Data<Registry>,
+) -> Result<()> {
     info!("Starting http server...");
 
     // Initialize pipe
     let addr =
         infer::<_, SocketAddr>("BIND_ADDR").unwrap_or_else(|_| "0.0.0.0:80".parse().unwrap());
 
+    let authorizer =
+        NetworkGraphAuthorizer::try_new(infer("GRAPH_DB_RBAC_ENABLED").unwrap_or_default()).await?;
+    let authorizer = Data::new(authorizer);
     let graph_db: Box<dyn Send + NetworkGraphDB> = Box::new(vm.graph_db().clone());
     let graph_db = Data::new(graph_db);
+    let registry = Data::clone(registry);
+    let vm = Data::new(vm.clone());
 
     // Create a http server
     let server = HttpServer::new(move || {
-        let app = App::new().app_data(Data::clone(&graph_db));
+        let app = App::new()
+            .app_data(Data::clone(&authorizer))
+            .app_data(Data::clone(&graph_db))
+            .app_data(Data::clone(&registry))
+            .app_data(Data::clone(&vm));
+        #[cfg(feature = "visualizer-web")]
+        let app = app
+            .service(crate::routes::visualizer::index)
+            .service(crate::routes::visualizer::list)
+            .service(crate::routes::visualizer::get);
         let app = app
             .service(health)
+            .service(crate::metrics::get)
+            .service(crate::routes::function::post)
+            .service(crate::routes::history::get)
+            .service(crate::routes::history::range)
+            .service(crate::routes::schedule::list)
+            .service(crate::routes::simulation::drain)
+            .service(crate::routes::simulation::post)
+            .service(crate::routes::simulation::replay)
             .service(crate::routes::graph::get)
             .service(crate::routes::graph::post);
         app.wrap(middleware::NormalizePath::new(
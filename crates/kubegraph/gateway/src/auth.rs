@@ -0,0 +1,41 @@
+use actix_web::{http::header::AUTHORIZATION, HttpRequest, HttpResponse};
+use anyhow::Result;
+use ark_core::result::Result as HttpResult;
+use kubegraph_api::graph::{
+    auth::{parse_bearer_token, AuthorizedNetworkGraphDB, GraphSubject, NetworkGraphAuthorizer},
+    NetworkGraphDB,
+};
+
+/// Authenticate the client with the bearer token of the request.
+pub async fn authenticate(
+    request: &HttpRequest,
+    authorizer: &NetworkGraphAuthorizer,
+) -> Result<GraphSubject> {
+    let token = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_bearer_token);
+
+    authorizer.authenticate(token).await
+}
+
+/// Scope the graph db to the namespaces authorized to the client.
+pub async fn authorize<'a, DB>(
+    request: &HttpRequest,
+    authorizer: &'a NetworkGraphAuthorizer,
+    graph_db: &'a DB,
+) -> Result<AuthorizedNetworkGraphDB<'a, DB>>
+where
+    DB: ?Sized + NetworkGraphDB,
+{
+    Ok(AuthorizedNetworkGraphDB {
+        authorizer,
+        graph_db,
+        subject: authenticate(request, authorizer).await?,
+    })
+}
+
+pub fn unauthorized(error: ::anyhow::Error) -> HttpResponse {
+    HttpResponse::Unauthorized().json(HttpResult::<()>::Err(error.to_string()))
+}
@@ -0,0 +1,45 @@
+use std::net::SocketAddr;
+
+use anyhow::Result;
+use ark_core::{env::infer, signal::FunctionSignal};
+use kubegraph_api::{
+    graph::auth::NetworkGraphAuthorizer,
+    vm::{NetworkFallbackPolicy, NetworkVirtualMachine},
+};
+use kubegraph_graph_grpc::NetworkGraphDBServer;
+use tokio::time::sleep;
+use tracing::{error, info, warn};
+
+pub async fn loop_forever(signal: FunctionSignal, vm: crate::vm::NetworkVirtualMachine) {
+    loop {
+        if let Err(error) = try_loop_forever(&vm).await {
+            error!("failed to operate grpc server: {error}");
+
+            match vm.fallback_policy() {
+                NetworkFallbackPolicy::Interval { interval } => {
+                    warn!("restarting grpc server in {interval:?}...");
+                    sleep(interval).await;
+                    info!("Restarted grpc server");
+                }
+                NetworkFallbackPolicy::Never => {
+                    signal.terminate_on_panic();
+                    break;
+                }
+            }
+        }
+    }
+}
+
+async fn try_loop_forever(vm: &crate::vm::NetworkVirtualMachine) -> Result<()> {
+    info!("Starting grpc server...");
+
+    let addr = infer::<_, SocketAddr>("GRPC_BIND_ADDR")
+        .unwrap_or_else(|_| "0.0.0.0:50052".parse().unwrap());
+
+    let authorizer =
+        NetworkGraphAuthorizer::try_new(infer("GRAPH_DB_RBAC_ENABLED").unwrap_or_default()).await?;
+
+    NetworkGraphDBServer::new(vm.graph_db().clone(), authorizer)
+        .serve(addr)
+        .await
+}
@@ -1,4 +1,8 @@
 mod actix;
+mod auth;
+#[cfg(feature = "graph-grpc")]
+mod grpc;
+mod metrics;
 mod routes;
 mod vm;
 
@@ -7,11 +11,14 @@ use tokio::spawn;
 
 #[tokio::main]
 async fn main() {
+    let registry = self::metrics::init_once();
+
     self::vm::NetworkVirtualMachine::main(|signal, vm| {
-        vec![spawn(crate::actix::loop_forever(
-            signal.clone(),
-            vm.clone(),
-        ))]
+        vec![
+            spawn(crate::actix::loop_forever(signal.clone(), vm.clone(), registry)),
+            #[cfg(feature = "graph-grpc")]
+            spawn(crate::grpc::loop_forever(signal.clone(), vm.clone())),
+        ]
     })
     .await
 }
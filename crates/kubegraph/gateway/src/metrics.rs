@@ -0,0 +1,29 @@
+use actix_web::{get, web::Data, HttpResponse, Responder};
+use opentelemetry::global;
+use opentelemetry_sdk::metrics::SdkMeterProvider;
+use prometheus::{Registry, TextEncoder, TEXT_FORMAT};
+use tracing::{instrument, Level};
+
+/// Register a global meter provider which is exported in the Prometheus format.
+///
+/// NOTE: it should be called before any instruments are created.
+pub fn init_once() -> Registry {
+    let registry = Registry::new();
+    let exporter = ::opentelemetry_prometheus::exporter()
+        .with_registry(registry.clone())
+        .build()
+        .expect("failed to init a prometheus exporter");
+
+    let provider = SdkMeterProvider::builder().with_reader(exporter).build();
+    global::set_meter_provider(provider);
+    registry
+}
+
+#[instrument(level = Level::INFO, skip(registry))]
+#[get("/metrics")]
+async fn get(registry: Data<Registry>) -> impl Responder {
+    match TextEncoder::new().encode_to_string(&registry.gather()) {
+        Ok(metrics) => HttpResponse::Ok().content_type(TEXT_FORMAT).body(metrics),
+        Err(error) => HttpResponse::InternalServerError().body(error.to_string()),
+    }
+}
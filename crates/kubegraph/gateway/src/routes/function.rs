@@ -0,0 +1,19 @@
+use actix_web::{
+    post,
+    web::{Data, Json},
+    HttpResponse, Responder,
+};
+use ark_core::result::Result;
+use kubegraph_api::{
+    function::dry_run::NetworkFunctionDryRunRequest, vm::NetworkVirtualMachineExt,
+};
+use tracing::{instrument, Level};
+
+#[instrument(level = Level::INFO, skip(vm, request))]
+#[post("/_dryrun")]
+pub async fn post(
+    vm: Data<crate::vm::NetworkVirtualMachine>,
+    Json(request): Json<NetworkFunctionDryRunRequest>,
+) -> impl Responder {
+    HttpResponse::Ok().json(Result::from(vm.dry_run_function(request).await))
+}
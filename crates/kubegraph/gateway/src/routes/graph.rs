@@ -1,27 +1,77 @@
 use actix_web::{
     get, post,
-    web::{Data, Json, Path},
-    HttpResponse, Responder,
+    web::{Data, Json, Path, Query},
+    HttpRequest, HttpResponse, Responder,
 };
 use ark_core::result::Result;
 use futures::{stream::FuturesUnordered, TryFutureExt, TryStreamExt};
 use kubegraph_api::{
     frame::DataFrame,
-    graph::{Graph, GraphData, GraphFilter, NetworkGraphDB},
+    graph::{
+        auth::NetworkGraphAuthorizer, Graph, GraphData, GraphFilter, GraphFrameProjection,
+        GraphProjection, NetworkGraphDB,
+    },
 };
+use serde::{Deserialize, Serialize};
 use tracing::{instrument, Level};
 
-#[instrument(level = Level::INFO, skip(graph_db))]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphQuery {
+    /// The comma-separated edge columns to load; all columns if not given
+    #[serde(default)]
+    edge_columns: Option<String>,
+    /// The comma-separated node columns to load; all columns if not given
+    #[serde(default)]
+    node_columns: Option<String>,
+}
+
+impl GraphQuery {
+    fn to_projection(&self) -> GraphProjection {
+        fn parse_columns(columns: Option<&String>) -> Option<Vec<String>> {
+            columns.map(|columns| {
+                columns
+                    .split(',')
+                    .map(|column| column.trim())
+                    .filter(|column| !column.is_empty())
+                    .map(Into::into)
+                    .collect()
+            })
+        }
+
+        GraphProjection {
+            edges: GraphFrameProjection {
+                columns: parse_columns(self.edge_columns.as_ref()),
+                filter: None,
+            },
+            nodes: GraphFrameProjection {
+                columns: parse_columns(self.node_columns.as_ref()),
+                filter: None,
+            },
+        }
+    }
+}
+
+#[instrument(level = Level::INFO, skip(request, authorizer, graph_db))]
 #[get("/{namespace}")]
 pub async fn get(
+    request: HttpRequest,
     namespace: Path<String>,
+    Query(query): Query<GraphQuery>,
+    authorizer: Data<NetworkGraphAuthorizer>,
     graph_db: Data<Box<dyn Send + NetworkGraphDB>>,
 ) -> impl Responder {
+    let graph_db = match crate::auth::authorize(&request, &authorizer, &**graph_db).await {
+        Ok(graph_db) => graph_db,
+        Err(error) => return crate::auth::unauthorized(error),
+    };
+
     let filter = GraphFilter::all(namespace.into_inner());
+    let projection = query.to_projection();
 
     HttpResponse::Ok().json(Result::from(
         graph_db
-            .list(&filter)
+            .list_projected(&filter, &projection)
             .and_then(|graph| {
                 graph
                     .into_iter()
@@ -34,10 +84,12 @@ pub async fn get(
     ))
 }
 
-#[instrument(level = Level::INFO, skip(graph_db, graph))]
+#[instrument(level = Level::INFO, skip(request, authorizer, graph_db, graph))]
 #[post("/{namespace}")]
 pub async fn post(
+    request: HttpRequest,
     namespace: Path<String>,
+    authorizer: Data<NetworkGraphAuthorizer>,
     graph_db: Data<Box<dyn Send + NetworkGraphDB>>,
     Json(graph): Json<Graph<GraphData<DataFrame>>>,
 ) -> impl Responder {
@@ -45,5 +97,10 @@ pub async fn post(
         return HttpResponse::Ok().json(Result::Ok(()));
     }
 
+    let graph_db = match crate::auth::authorize(&request, &authorizer, &**graph_db).await {
+        Ok(graph_db) => graph_db,
+        Err(error) => return crate::auth::unauthorized(error),
+    };
+
     HttpResponse::Ok().json(Result::from(graph_db.insert(graph.lazy()).await))
 }
@@ -0,0 +1,101 @@
+use actix_web::{
+    get,
+    web::{Data, Path, Query},
+    HttpRequest, HttpResponse, Responder,
+};
+use ark_core::result::Result;
+use chrono::{DateTime, Utc};
+use futures::{stream::FuturesUnordered, TryFutureExt, TryStreamExt};
+use kubegraph_api::{
+    graph::{
+        auth::{GraphAccessVerb, NetworkGraphAuthorizer},
+        history::NetworkGraphHistoryDB,
+        GraphScope,
+    },
+    vm::NetworkVirtualMachine,
+};
+use serde::Deserialize;
+use tracing::{instrument, Level};
+
+#[derive(Debug, Deserialize)]
+pub struct GetQuery {
+    #[serde(default = "Utc::now")]
+    at: DateTime<Utc>,
+}
+
+#[instrument(level = Level::INFO, skip(request, authorizer, vm))]
+#[get("/_history/{namespace}/{name}")]
+pub async fn get(
+    request: HttpRequest,
+    path: Path<(String, String)>,
+    query: Query<GetQuery>,
+    authorizer: Data<NetworkGraphAuthorizer>,
+    vm: Data<crate::vm::NetworkVirtualMachine>,
+) -> impl Responder {
+    let (namespace, name) = path.into_inner();
+    let scope = GraphScope { namespace, name };
+
+    if let Err(error) = authorize(&request, &authorizer, &scope).await {
+        return crate::auth::unauthorized(error);
+    }
+
+    HttpResponse::Ok().json(Result::from(
+        vm.graph_db()
+            .get_at(&scope, query.at)
+            .and_then(|snapshot| async move {
+                match snapshot {
+                    Some(snapshot) => snapshot.collect().await.map(Some),
+                    None => Ok(None),
+                }
+            })
+            .await,
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RangeQuery {
+    from: DateTime<Utc>,
+    #[serde(default = "Utc::now")]
+    to: DateTime<Utc>,
+}
+
+#[instrument(level = Level::INFO, skip(request, authorizer, vm))]
+#[get("/_history/{namespace}/{name}/range")]
+pub async fn range(
+    request: HttpRequest,
+    path: Path<(String, String)>,
+    query: Query<RangeQuery>,
+    authorizer: Data<NetworkGraphAuthorizer>,
+    vm: Data<crate::vm::NetworkVirtualMachine>,
+) -> impl Responder {
+    let (namespace, name) = path.into_inner();
+    let scope = GraphScope { namespace, name };
+
+    if let Err(error) = authorize(&request, &authorizer, &scope).await {
+        return crate::auth::unauthorized(error);
+    }
+
+    HttpResponse::Ok().json(Result::from(
+        vm.graph_db()
+            .range(&scope, query.from, query.to)
+            .and_then(|snapshots| {
+                snapshots
+                    .into_iter()
+                    .map(|snapshot| snapshot.collect())
+                    .collect::<FuturesUnordered<_>>()
+                    .try_collect::<Vec<_>>()
+            })
+            .await,
+    ))
+}
+
+async fn authorize(
+    request: &HttpRequest,
+    authorizer: &NetworkGraphAuthorizer,
+    scope: &GraphScope,
+) -> ::anyhow::Result<()> {
+    let subject = crate::auth::authenticate(request, authorizer).await?;
+    authorizer
+        .authorize(&subject, &scope.namespace, GraphAccessVerb::Get)
+        .await
+}
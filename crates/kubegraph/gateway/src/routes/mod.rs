@@ -1 +1,7 @@
+pub mod function;
 pub mod graph;
+pub mod history;
+pub mod schedule;
+pub mod simulation;
+#[cfg(feature = "visualizer-web")]
+pub mod visualizer;
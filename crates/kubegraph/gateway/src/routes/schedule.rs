@@ -0,0 +1,38 @@
+use actix_web::{
+    get,
+    web::{Data, Path},
+    HttpRequest, HttpResponse, Responder,
+};
+use ark_core::result::Result;
+use kubegraph_api::{
+    graph::auth::{GraphAccessVerb, NetworkGraphAuthorizer},
+    scheduler::SCHEDULER,
+};
+use tracing::{instrument, Level};
+
+#[instrument(level = Level::INFO, skip(request, authorizer))]
+#[get("/_schedule/{namespace}")]
+pub async fn list(
+    request: HttpRequest,
+    namespace: Path<String>,
+    authorizer: Data<NetworkGraphAuthorizer>,
+) -> impl Responder {
+    let namespace = namespace.into_inner();
+
+    if let Err(error) = authorize(&request, &authorizer, &namespace).await {
+        return crate::auth::unauthorized(error);
+    }
+
+    HttpResponse::Ok().json(Result::from(SCHEDULER.list(&namespace)))
+}
+
+async fn authorize(
+    request: &HttpRequest,
+    authorizer: &NetworkGraphAuthorizer,
+    namespace: &str,
+) -> ::anyhow::Result<()> {
+    let subject = crate::auth::authenticate(request, authorizer).await?;
+    authorizer
+        .authorize(&subject, namespace, GraphAccessVerb::Get)
+        .await
+}
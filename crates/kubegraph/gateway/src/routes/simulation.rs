@@ -0,0 +1,45 @@
+use actix_web::{
+    post,
+    web::{Data, Json},
+    HttpResponse, Responder,
+};
+use ark_core::result::Result;
+use kubegraph_api::{
+    recorder::NetworkReplayRequest,
+    simulation::{NetworkDrainRequest, NetworkSimulationRequest},
+    vm::NetworkVirtualMachineExt,
+};
+use tracing::{instrument, Level};
+
+#[instrument(level = Level::INFO, skip(vm, request))]
+#[post("/_simulate")]
+pub async fn post(
+    vm: Data<crate::vm::NetworkVirtualMachine>,
+    Json(request): Json<NetworkSimulationRequest>,
+) -> impl Responder {
+    let NetworkSimulationRequest { problem, overrides } = request;
+
+    HttpResponse::Ok().json(Result::from(vm.simulate(problem, overrides).await))
+}
+
+#[instrument(level = Level::INFO, skip(vm, request))]
+#[post("/_drain")]
+pub async fn drain(
+    vm: Data<crate::vm::NetworkVirtualMachine>,
+    Json(request): Json<NetworkDrainRequest>,
+) -> impl Responder {
+    let NetworkDrainRequest { problem, node } = request;
+
+    HttpResponse::Ok().json(Result::from(vm.drain(problem, node).await))
+}
+
+#[instrument(level = Level::INFO, skip(vm, request))]
+#[post("/_replay")]
+pub async fn replay(
+    vm: Data<crate::vm::NetworkVirtualMachine>,
+    Json(request): Json<NetworkReplayRequest>,
+) -> impl Responder {
+    let NetworkReplayRequest { records, solver } = request;
+
+    HttpResponse::Ok().json(Result::from(vm.replay(records, solver).await))
+}
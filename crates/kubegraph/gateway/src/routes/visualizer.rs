@@ -0,0 +1,86 @@
+use actix_web::{
+    get,
+    http::header::ContentType,
+    web::{Data, Path},
+    HttpRequest, HttpResponse, Responder,
+};
+use ark_core::result::Result;
+use kubegraph_api::graph::{
+    auth::{GraphAccessVerb, NetworkGraphAuthorizer},
+    GraphScope,
+};
+use tracing::{instrument, Level};
+
+#[instrument(level = Level::INFO)]
+#[get("/_visualizer")]
+pub async fn index() -> impl Responder {
+    HttpResponse::Ok()
+        .content_type(ContentType::html())
+        .body(::kubegraph_visualizer_web::INDEX_HTML)
+}
+
+#[instrument(level = Level::INFO, skip(request, authorizer, vm))]
+#[get("/_visualizer/api/{namespace}")]
+pub async fn list(
+    request: HttpRequest,
+    namespace: Path<String>,
+    authorizer: Data<NetworkGraphAuthorizer>,
+    vm: Data<crate::vm::NetworkVirtualMachine>,
+) -> impl Responder {
+    let namespace = namespace.into_inner();
+
+    if let Err(error) = authorize(&request, &authorizer, &namespace, GraphAccessVerb::List).await {
+        return crate::auth::unauthorized(error);
+    }
+
+    let scopes = match vm.visualizer_web() {
+        Some(visualizer) => visualizer.list(&namespace).await,
+        None => return disabled(),
+    };
+    HttpResponse::Ok().json(Result::Ok(scopes))
+}
+
+#[instrument(level = Level::INFO, skip(request, authorizer, vm))]
+#[get("/_visualizer/api/{namespace}/{name}")]
+pub async fn get(
+    request: HttpRequest,
+    path: Path<(String, String)>,
+    authorizer: Data<NetworkGraphAuthorizer>,
+    vm: Data<crate::vm::NetworkVirtualMachine>,
+) -> impl Responder {
+    let (namespace, name) = path.into_inner();
+    let scope = GraphScope { namespace, name };
+
+    if let Err(error) = authorize(
+        &request,
+        &authorizer,
+        &scope.namespace,
+        GraphAccessVerb::Get,
+    )
+    .await
+    {
+        return crate::auth::unauthorized(error);
+    }
+
+    let scope = match vm.visualizer_web() {
+        Some(visualizer) => visualizer.get(&scope).await,
+        None => return disabled(),
+    };
+    HttpResponse::Ok().json(Result::Ok(scope))
+}
+
+async fn authorize(
+    request: &HttpRequest,
+    authorizer: &NetworkGraphAuthorizer,
+    namespace: &str,
+    verb: GraphAccessVerb,
+) -> ::anyhow::Result<()> {
+    let subject = crate::auth::authenticate(request, authorizer).await?;
+    authorizer.authorize(&subject, namespace, verb).await
+}
+
+fn disabled() -> HttpResponse {
+    HttpResponse::NotFound().json(Result::<()>::Err(
+        "web visualizer is disabled; set KUBEGRAPH_VISUALIZER=web".into(),
+    ))
+}
@@ -0,0 +1,24 @@
+use tonic_build::manual::{Builder, Method, Service};
+
+fn main() {
+    let method = |name: &str, route_name: &str| {
+        Method::builder()
+            .name(name)
+            .route_name(route_name)
+            .input_type(format!("crate::proto::{route_name}Request"))
+            .output_type(format!("crate::proto::{route_name}Response"))
+            .codec_path("::tonic::codec::ProstCodec")
+    };
+
+    let service = Service::builder()
+        .name("GraphService")
+        .package("kubegraph.graph")
+        .method(method("get", "Get").build())
+        .method(method("insert", "Insert").build())
+        .method(method("list", "List").build())
+        .method(method("remove", "Remove").build())
+        .method(method("watch", "Watch").server_streaming().build())
+        .build();
+
+    Builder::new().compile(&[service]);
+}
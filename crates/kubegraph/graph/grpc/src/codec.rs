@@ -0,0 +1,105 @@
+use std::io::Cursor;
+
+use anyhow::{anyhow, Result};
+use kubegraph_api::{
+    frame::{DataFrame, LazyFrame},
+    graph::{Graph, GraphData, GraphFilter, GraphScope},
+};
+use pl::prelude::{IpcReader, IpcWriter, SerReader, SerWriter};
+
+use crate::proto;
+
+impl From<GraphScope> for proto::GraphScope {
+    fn from(scope: GraphScope) -> Self {
+        let GraphScope { namespace, name } = scope;
+        Self { namespace, name }
+    }
+}
+
+impl From<proto::GraphScope> for GraphScope {
+    fn from(scope: proto::GraphScope) -> Self {
+        let proto::GraphScope { namespace, name } = scope;
+        Self { namespace, name }
+    }
+}
+
+impl From<GraphFilter> for proto::GraphFilter {
+    fn from(filter: GraphFilter) -> Self {
+        let GraphFilter { namespace, name } = filter;
+        Self { namespace, name }
+    }
+}
+
+impl From<proto::GraphFilter> for GraphFilter {
+    fn from(filter: proto::GraphFilter) -> Self {
+        let proto::GraphFilter { namespace, name } = filter;
+        Self { namespace, name }
+    }
+}
+
+pub(crate) async fn encode_graph(graph: Graph<GraphData<LazyFrame>>) -> Result<proto::Graph> {
+    let Graph {
+        connector,
+        data: GraphData { edges, nodes },
+        metadata,
+        scope,
+    } = graph.collect().await?;
+
+    Ok(proto::Graph {
+        scope: Some(scope.into()),
+        connector: connector
+            .map(|connector| ::serde_json::to_vec(&connector))
+            .transpose()?,
+        metadata: ::serde_json::to_vec(&metadata)?,
+        edges: encode_frame(edges)?,
+        nodes: encode_frame(nodes)?,
+    })
+}
+
+pub(crate) fn decode_graph(graph: proto::Graph) -> Result<Graph<GraphData<LazyFrame>>> {
+    let proto::Graph {
+        scope,
+        connector,
+        metadata,
+        edges,
+        nodes,
+    } = graph;
+
+    Ok(Graph {
+        connector: connector
+            .map(|connector| ::serde_json::from_slice(&connector))
+            .transpose()?,
+        data: GraphData {
+            edges: decode_frame(edges)?,
+            nodes: decode_frame(nodes)?,
+        },
+        metadata: ::serde_json::from_slice(&metadata)?,
+        scope: scope
+            .map(Into::into)
+            .ok_or_else(|| anyhow!("missing graph scope"))?,
+    })
+}
+
+fn encode_frame(df: DataFrame) -> Result<Vec<u8>> {
+    let mut df = match df {
+        DataFrame::Empty => return Ok(Vec::default()),
+        DataFrame::Polars(df) => df,
+    };
+
+    let mut buf = Vec::default();
+    IpcWriter::new(&mut buf)
+        .finish(&mut df)
+        .map(|()| buf)
+        .map_err(|error| anyhow!("failed to encode graph frame: {error}"))
+}
+
+fn decode_frame(buf: Vec<u8>) -> Result<LazyFrame> {
+    if buf.is_empty() {
+        return Ok(LazyFrame::Empty);
+    }
+
+    IpcReader::new(Cursor::new(buf))
+        .finish()
+        .map(|df| DataFrame::Polars(df).lazy())
+        .map_err(|error| anyhow!("failed to decode graph frame: {error}"))
+}
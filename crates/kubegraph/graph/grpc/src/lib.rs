@@ -0,0 +1,241 @@
+extern crate polars as pl;
+
+mod codec;
+mod proto;
+mod server;
+
+pub use self::server::NetworkGraphDBServer;
+
+use std::{path::PathBuf, time::Duration};
+
+use anyhow::{anyhow, Result};
+use ark_core::signal::FunctionSignal;
+use async_trait::async_trait;
+use clap::Parser;
+use futures::{Stream, StreamExt};
+use kubegraph_api::{
+    component::NetworkComponent,
+    frame::LazyFrame,
+    graph::{Graph, GraphData, GraphFilter, GraphScope},
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tonic::{
+    service::{interceptor::InterceptedService, Interceptor},
+    transport::{Channel, Endpoint},
+    Request, Status,
+};
+use tracing::{info, instrument, Level};
+
+use crate::{
+    codec::{decode_graph, encode_graph},
+    proto::{
+        graph_service_client::GraphServiceClient, GetRequest, GetResponse, InsertRequest,
+        ListRequest, ListResponse, RemoveRequest, WatchRequest, WatchResponse,
+    },
+};
+
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema, Parser,
+)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkGraphDBArgs {
+    /// The endpoint of the remote graph db server
+    #[arg(
+        long,
+        env = "KUBEGRAPH_GRAPH_DB_GRPC_ENDPOINT",
+        value_name = "URL",
+        default_value_t = NetworkGraphDBArgs::default_graph_db_grpc_endpoint(),
+    )]
+    #[serde(default = "NetworkGraphDBArgs::default_graph_db_grpc_endpoint")]
+    pub graph_db_grpc_endpoint: String,
+
+    /// The bearer token file to be authorized by the remote graph db server
+    #[arg(
+        long,
+        env = "KUBEGRAPH_GRAPH_DB_GRPC_TOKEN_PATH",
+        value_name = "PATH",
+        default_value_t = NetworkGraphDBArgs::default_graph_db_grpc_token_path(),
+    )]
+    #[serde(default = "NetworkGraphDBArgs::default_graph_db_grpc_token_path")]
+    pub graph_db_grpc_token_path: String,
+}
+
+impl Default for NetworkGraphDBArgs {
+    fn default() -> Self {
+        Self {
+            graph_db_grpc_endpoint: Self::default_graph_db_grpc_endpoint(),
+            graph_db_grpc_token_path: Self::default_graph_db_grpc_token_path(),
+        }
+    }
+}
+
+impl NetworkGraphDBArgs {
+    fn default_graph_db_grpc_endpoint() -> String {
+        "http://kubegraph.kubegraph.svc:50052".into()
+    }
+
+    fn default_graph_db_grpc_token_path() -> String {
+        "/var/run/secrets/kubernetes.io/serviceaccount/token".into()
+    }
+}
+
+/// A remote graph db client, connected to a [`NetworkGraphDBServer`].
+#[derive(Clone)]
+pub struct NetworkGraphDB {
+    client: GraphServiceClient<InterceptedService<Channel, TokenInterceptor>>,
+}
+
+#[async_trait]
+impl NetworkComponent for NetworkGraphDB {
+    type Args = NetworkGraphDBArgs;
+
+    #[instrument(level = Level::INFO)]
+    async fn try_new(args: <Self as NetworkComponent>::Args, _: &FunctionSignal) -> Result<Self> {
+        let NetworkGraphDBArgs {
+            graph_db_grpc_endpoint,
+            graph_db_grpc_token_path,
+        } = args;
+
+        info!("Connecting to remote graph db...");
+        let channel = Endpoint::from_shared(graph_db_grpc_endpoint)
+            .map_err(|error| anyhow!("invalid graph db endpoint: {error}"))?
+            .connect_lazy();
+
+        let interceptor = TokenInterceptor {
+            path: graph_db_grpc_token_path.into(),
+        };
+
+        Ok(Self {
+            client: GraphServiceClient::with_interceptor(channel, interceptor),
+        })
+    }
+}
+
+#[async_trait]
+impl ::kubegraph_api::graph::NetworkGraphDB for NetworkGraphDB {
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn get(&self, scope: &GraphScope) -> Result<Option<Graph<GraphData<LazyFrame>>>> {
+        let request = GetRequest {
+            scope: Some(scope.clone().into()),
+        };
+
+        let GetResponse { graph } = self
+            .client
+            .clone()
+            .get(request)
+            .await
+            .map_err(|error| anyhow!("failed to get graph: {error}"))?
+            .into_inner();
+        graph.map(decode_graph).transpose()
+    }
+
+    #[instrument(level = Level::INFO, skip(self, graph))]
+    async fn insert(&self, graph: Graph<GraphData<LazyFrame>>) -> Result<()> {
+        let request = InsertRequest {
+            graph: Some(encode_graph(graph).await?),
+        };
+
+        self.client
+            .clone()
+            .insert(request)
+            .await
+            .map(|_| ())
+            .map_err(|error| anyhow!("failed to insert graph: {error}"))
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn list(&self, filter: &GraphFilter) -> Result<Vec<Graph<GraphData<LazyFrame>>>> {
+        let request = ListRequest {
+            filter: Some(filter.clone().into()),
+        };
+
+        let ListResponse { graphs } = self
+            .client
+            .clone()
+            .list(request)
+            .await
+            .map_err(|error| anyhow!("failed to list graphs: {error}"))?
+            .into_inner();
+        graphs.into_iter().map(decode_graph).collect()
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn remove(&self, scope: GraphScope) -> Result<()> {
+        let request = RemoveRequest {
+            scope: Some(scope.into()),
+        };
+
+        self.client
+            .clone()
+            .remove(request)
+            .await
+            .map(|_| ())
+            .map_err(|error| anyhow!("failed to remove graph: {error}"))
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn close(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl NetworkGraphDB {
+    /// Subscribe the changes of the graphs, polled by the server on every interval.
+    #[instrument(level = Level::INFO, skip(self))]
+    pub async fn watch(
+        &self,
+        filter: &GraphFilter,
+        interval: Duration,
+    ) -> Result<impl Stream<Item = Result<NetworkGraphEvent>>> {
+        let request = WatchRequest {
+            filter: Some(filter.clone().into()),
+            interval_ms: interval.as_millis().try_into().unwrap_or(u64::MAX),
+        };
+
+        let stream = self
+            .client
+            .clone()
+            .watch(request)
+            .await
+            .map_err(|error| anyhow!("failed to watch graphs: {error}"))?
+            .into_inner();
+
+        Ok(stream.map(|response| {
+            let WatchResponse { scope, graph } =
+                response.map_err(|error| anyhow!("failed to watch graphs: {error}"))?;
+            match graph {
+                Some(graph) => decode_graph(graph).map(NetworkGraphEvent::Updated),
+                None => scope
+                    .map(|scope| NetworkGraphEvent::Removed(scope.into()))
+                    .ok_or_else(|| anyhow!("missing graph scope")),
+            }
+        }))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum NetworkGraphEvent {
+    Updated(Graph<GraphData<LazyFrame>>),
+    Removed(GraphScope),
+}
+
+/// Attaches the bearer token to the requests, if available.
+#[derive(Clone, Debug)]
+struct TokenInterceptor {
+    path: PathBuf,
+}
+
+impl Interceptor for TokenInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        // NOTE: the projected service account tokens are rotated, so load it on every request
+        if let Ok(token) = ::std::fs::read_to_string(&self.path) {
+            let value = format!("Bearer {}", token.trim())
+                .parse()
+                .map_err(|_| Status::unauthenticated("invalid bearer token"))?;
+            request.metadata_mut().insert("authorization", value);
+        }
+        Ok(request)
+    }
+}
@@ -0,0 +1,103 @@
+include!(concat!(env!("OUT_DIR"), "/kubegraph.graph.GraphService.rs"));
+
+#[derive(Clone, PartialEq, Eq, ::prost::Message)]
+pub struct GraphScope {
+    #[prost(string, tag = "1")]
+    pub namespace: String,
+
+    #[prost(string, tag = "2")]
+    pub name: String,
+}
+
+#[derive(Clone, PartialEq, Eq, ::prost::Message)]
+pub struct GraphFilter {
+    #[prost(string, tag = "1")]
+    pub namespace: String,
+
+    #[prost(string, optional, tag = "2")]
+    pub name: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Eq, ::prost::Message)]
+pub struct Graph {
+    #[prost(message, optional, tag = "1")]
+    pub scope: Option<GraphScope>,
+
+    /// The JSON-encoded connector
+    #[prost(bytes = "vec", optional, tag = "2")]
+    pub connector: Option<Vec<u8>>,
+
+    /// The JSON-encoded metadata
+    #[prost(bytes = "vec", tag = "3")]
+    pub metadata: Vec<u8>,
+
+    /// The Arrow IPC-encoded edges; empty if the frame is empty
+    #[prost(bytes = "vec", tag = "4")]
+    pub edges: Vec<u8>,
+
+    /// The Arrow IPC-encoded nodes; empty if the frame is empty
+    #[prost(bytes = "vec", tag = "5")]
+    pub nodes: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Eq, ::prost::Message)]
+pub struct GetRequest {
+    #[prost(message, optional, tag = "1")]
+    pub scope: Option<GraphScope>,
+}
+
+#[derive(Clone, PartialEq, Eq, ::prost::Message)]
+pub struct GetResponse {
+    #[prost(message, optional, tag = "1")]
+    pub graph: Option<Graph>,
+}
+
+#[derive(Clone, PartialEq, Eq, ::prost::Message)]
+pub struct InsertRequest {
+    #[prost(message, optional, tag = "1")]
+    pub graph: Option<Graph>,
+}
+
+#[derive(Clone, PartialEq, Eq, ::prost::Message)]
+pub struct InsertResponse {}
+
+#[derive(Clone, PartialEq, Eq, ::prost::Message)]
+pub struct ListRequest {
+    #[prost(message, optional, tag = "1")]
+    pub filter: Option<GraphFilter>,
+}
+
+#[derive(Clone, PartialEq, Eq, ::prost::Message)]
+pub struct ListResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub graphs: Vec<Graph>,
+}
+
+#[derive(Clone, PartialEq, Eq, ::prost::Message)]
+pub struct RemoveRequest {
+    #[prost(message, optional, tag = "1")]
+    pub scope: Option<GraphScope>,
+}
+
+#[derive(Clone, PartialEq, Eq, ::prost::Message)]
+pub struct RemoveResponse {}
+
+#[derive(Clone, PartialEq, Eq, ::prost::Message)]
+pub struct WatchRequest {
+    #[prost(message, optional, tag = "1")]
+    pub filter: Option<GraphFilter>,
+
+    /// The polling interval of the server in milliseconds
+    #[prost(uint64, tag = "2")]
+    pub interval_ms: u64,
+}
+
+#[derive(Clone, PartialEq, Eq, ::prost::Message)]
+pub struct WatchResponse {
+    #[prost(message, optional, tag = "1")]
+    pub scope: Option<GraphScope>,
+
+    /// The updated graph; the graph is removed if not given
+    #[prost(message, optional, tag = "2")]
+    pub graph: Option<Graph>,
+}
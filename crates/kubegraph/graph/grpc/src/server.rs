@@ -0,0 +1,253 @@
+use std::{collections::BTreeMap, net::SocketAddr, time::Duration};
+
+use anyhow::{anyhow, Result};
+use kubegraph_api::graph::{
+    auth::{parse_bearer_token, GraphAccessVerb, NetworkGraphAuthorizer},
+    GraphFilter, GraphScope, NetworkGraphDB,
+};
+use tokio::{
+    sync::mpsc::{self, Sender},
+    time::sleep,
+};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{metadata::MetadataMap, transport::Server, Request, Response, Status};
+use tracing::{info, instrument, Level};
+
+use crate::{
+    codec::{decode_graph, encode_graph},
+    proto::{
+        self,
+        graph_service_server::{GraphService, GraphServiceServer},
+        GetRequest, GetResponse, InsertRequest, InsertResponse, ListRequest, ListResponse,
+        RemoveRequest, RemoveResponse, WatchRequest, WatchResponse,
+    },
+};
+
+/// A gRPC server exposing any [`NetworkGraphDB`] to the remote clients.
+///
+/// Each request is authorized in the namespace of its graph scope (or filter).
+#[derive(Clone)]
+pub struct NetworkGraphDBServer<DB> {
+    authorizer: NetworkGraphAuthorizer,
+    graph_db: DB,
+}
+
+impl<DB> NetworkGraphDBServer<DB>
+where
+    DB: 'static + Send + Sync + Clone + NetworkGraphDB,
+{
+    const MIN_WATCH_INTERVAL_MS: u64 = 100;
+    const WATCH_BUFFER_SIZE: usize = 16;
+
+    pub const fn new(graph_db: DB, authorizer: NetworkGraphAuthorizer) -> Self {
+        Self {
+            authorizer,
+            graph_db,
+        }
+    }
+
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        info!("Starting graph db server on {addr}...");
+        Server::builder()
+            .add_service(GraphServiceServer::new(self))
+            .serve(addr)
+            .await
+            .map_err(|error| anyhow!("failed to operate graph db server: {error}"))
+    }
+
+    async fn authorize(
+        &self,
+        metadata: &MetadataMap,
+        namespace: &str,
+        verb: GraphAccessVerb,
+    ) -> Result<(), Status> {
+        let token = metadata
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_bearer_token);
+
+        let subject = self
+            .authorizer
+            .authenticate(token)
+            .await
+            .map_err(|error| Status::unauthenticated(error.to_string()))?;
+        self.authorizer
+            .authorize(&subject, namespace, verb)
+            .await
+            .map_err(|error| Status::permission_denied(error.to_string()))
+    }
+}
+
+#[::tonic::async_trait]
+impl<DB> GraphService for NetworkGraphDBServer<DB>
+where
+    DB: 'static + Send + Sync + Clone + NetworkGraphDB,
+{
+    async fn get(&self, request: Request<GetRequest>) -> Result<Response<GetResponse>, Status> {
+        let (metadata, _, GetRequest { scope }) = request.into_parts();
+        let scope: GraphScope = scope
+            .map(Into::into)
+            .ok_or_else(|| Status::invalid_argument("missing graph scope"))?;
+        self.authorize(&metadata, &scope.namespace, GraphAccessVerb::Get)
+            .await?;
+
+        let graph = match self.graph_db.get(&scope).await.map_err(internal)? {
+            Some(graph) => Some(encode_graph(graph).await.map_err(internal)?),
+            None => None,
+        };
+        Ok(Response::new(GetResponse { graph }))
+    }
+
+    async fn insert(
+        &self,
+        request: Request<InsertRequest>,
+    ) -> Result<Response<InsertResponse>, Status> {
+        let (metadata, _, InsertRequest { graph }) = request.into_parts();
+        let graph = graph
+            .ok_or_else(|| Status::invalid_argument("missing graph"))
+            .and_then(|graph| {
+                decode_graph(graph)
+                    .map_err(|error| Status::invalid_argument(format!("invalid graph: {error}")))
+            })?;
+        self.authorize(&metadata, &graph.scope.namespace, GraphAccessVerb::Create)
+            .await?;
+
+        self.graph_db
+            .insert(graph)
+            .await
+            .map(|()| Response::new(InsertResponse {}))
+            .map_err(internal)
+    }
+
+    async fn list(&self, request: Request<ListRequest>) -> Result<Response<ListResponse>, Status> {
+        let (metadata, _, ListRequest { filter }) = request.into_parts();
+        let filter = parse_filter(filter);
+        self.authorize(&metadata, &filter.namespace, GraphAccessVerb::List)
+            .await?;
+
+        let mut graphs = Vec::default();
+        for graph in self.graph_db.list(&filter).await.map_err(internal)? {
+            graphs.push(encode_graph(graph).await.map_err(internal)?);
+        }
+        Ok(Response::new(ListResponse { graphs }))
+    }
+
+    async fn remove(
+        &self,
+        request: Request<RemoveRequest>,
+    ) -> Result<Response<RemoveResponse>, Status> {
+        let (metadata, _, RemoveRequest { scope }) = request.into_parts();
+        let scope: GraphScope = scope
+            .map(Into::into)
+            .ok_or_else(|| Status::invalid_argument("missing graph scope"))?;
+        self.authorize(&metadata, &scope.namespace, GraphAccessVerb::Delete)
+            .await?;
+
+        self.graph_db
+            .remove(scope)
+            .await
+            .map(|()| Response::new(RemoveResponse {}))
+            .map_err(internal)
+    }
+
+    type WatchStream = ReceiverStream<Result<WatchResponse, Status>>;
+
+    async fn watch(
+        &self,
+        request: Request<WatchRequest>,
+    ) -> Result<Response<Self::WatchStream>, Status> {
+        let (
+            metadata,
+            _,
+            WatchRequest {
+                filter,
+                interval_ms,
+            },
+        ) = request.into_parts();
+        let filter = parse_filter(filter);
+        self.authorize(&metadata, &filter.namespace, GraphAccessVerb::Watch)
+            .await?;
+
+        let interval = Duration::from_millis(interval_ms.max(Self::MIN_WATCH_INTERVAL_MS));
+
+        let (tx, rx) = mpsc::channel(Self::WATCH_BUFFER_SIZE);
+        ::tokio::spawn(watch_forever(self.graph_db.clone(), filter, interval, tx));
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+async fn watch_forever<DB>(
+    graph_db: DB,
+    filter: GraphFilter,
+    interval: Duration,
+    tx: Sender<Result<WatchResponse, Status>>,
+) where
+    DB: NetworkGraphDB,
+{
+    let mut last_graphs = BTreeMap::default();
+    loop {
+        let events = match poll(&graph_db, &filter, &mut last_graphs).await {
+            Ok(events) => events,
+            Err(error) => {
+                let _ = tx.send(Err(internal(error))).await;
+                break;
+            }
+        };
+
+        for event in events {
+            if tx.send(Ok(event)).await.is_err() {
+                // the client has been disconnected
+                return;
+            }
+        }
+        if tx.is_closed() {
+            break;
+        }
+        sleep(interval).await;
+    }
+}
+
+/// Compare the current graphs with the last ones and collect the changes.
+#[instrument(level = Level::DEBUG, skip(graph_db, last_graphs))]
+async fn poll<DB>(
+    graph_db: &DB,
+    filter: &GraphFilter,
+    last_graphs: &mut BTreeMap<GraphScope, proto::Graph>,
+) -> Result<Vec<WatchResponse>>
+where
+    DB: NetworkGraphDB,
+{
+    let mut graphs = BTreeMap::default();
+    for graph in graph_db.list(filter).await? {
+        graphs.insert(graph.scope.clone(), encode_graph(graph).await?);
+    }
+
+    let removed = last_graphs
+        .keys()
+        .filter(|scope| !graphs.contains_key(scope))
+        .map(|scope| WatchResponse {
+            scope: Some(scope.clone().into()),
+            graph: None,
+        });
+    let updated = graphs
+        .iter()
+        .filter(|(scope, graph)| last_graphs.get(scope) != Some(graph))
+        .map(|(scope, graph)| WatchResponse {
+            scope: Some(scope.clone().into()),
+            graph: Some(graph.clone()),
+        });
+    let events = removed.chain(updated).collect();
+
+    *last_graphs = graphs;
+    Ok(events)
+}
+
+fn parse_filter(filter: Option<proto::GraphFilter>) -> GraphFilter {
+    filter
+        .map(Into::into)
+        .unwrap_or_else(|| GraphFilter::all(String::default()))
+}
+
+fn internal(error: ::anyhow::Error) -> Status {
+    Status::internal(error.to_string())
+}
@@ -0,0 +1,244 @@
+extern crate polars as pl;
+
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, bail, Result};
+use ark_core::signal::FunctionSignal;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use kubegraph_api::{
+    component::NetworkComponent,
+    connector::NetworkConnectorCrd,
+    frame::{DataFrame, LazyFrame},
+    graph::{history::GraphSnapshot, Graph, GraphData, GraphMetadata, GraphScope},
+};
+use pl::prelude::{ParquetReader, ParquetWriter, SerReader};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument, Level};
+
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    Parser,
+)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkGraphHistoryDBArgs {
+    /// The root directory of the parquet partitions; the history is disabled if not given
+    #[arg(long, env = "KUBEGRAPH_GRAPH_HISTORY_PATH", value_name = "PATH")]
+    #[serde(default)]
+    pub graph_history_path: Option<String>,
+}
+
+/// An append-only graph history, stored as parquet partitions.
+///
+/// Each snapshot is stored in `{root}/{namespace}/{name}/{timestamp_ms}/`,
+/// containing `nodes.parquet`, `edges.parquet` and `metadata.json`.
+#[derive(Clone)]
+pub struct NetworkGraphHistoryDB {
+    root: Option<Arc<PathBuf>>,
+}
+
+#[async_trait]
+impl NetworkComponent for NetworkGraphHistoryDB {
+    type Args = NetworkGraphHistoryDBArgs;
+
+    #[instrument(level = Level::INFO)]
+    async fn try_new(args: <Self as NetworkComponent>::Args, _: &FunctionSignal) -> Result<Self> {
+        let NetworkGraphHistoryDBArgs { graph_history_path } = args;
+
+        let root = match graph_history_path {
+            Some(path) => {
+                info!("Loading graph history db...");
+                let path = PathBuf::from(path);
+                fs::create_dir_all(&path)
+                    .map_err(|error| anyhow!("failed to create graph history db: {error}"))?;
+                Some(Arc::new(path))
+            }
+            None => None,
+        };
+        Ok(Self { root })
+    }
+}
+
+impl NetworkGraphHistoryDB {
+    const FILE_EDGES: &'static str = "edges.parquet";
+    const FILE_METADATA: &'static str = "metadata.json";
+    const FILE_NODES: &'static str = "nodes.parquet";
+
+    pub const fn is_enabled(&self) -> bool {
+        self.root.is_some()
+    }
+
+    fn root(&self) -> Result<&Path> {
+        match &self.root {
+            Some(root) => Ok(root),
+            None => bail!("graph history db is disabled"),
+        }
+    }
+
+    fn scope_dir(root: &Path, scope: &GraphScope) -> PathBuf {
+        let GraphScope { namespace, name } = scope;
+        root.join(namespace).join(name)
+    }
+
+    /// Append a snapshot of the graph, timestamped now.
+    #[instrument(level = Level::INFO, skip(self, graph))]
+    pub async fn record(&self, graph: Graph<GraphData<LazyFrame>>) -> Result<()> {
+        let root = match &self.root {
+            Some(root) => root,
+            None => return Ok(()),
+        };
+
+        let timestamp = Utc::now();
+        let Graph {
+            connector,
+            data: GraphData { edges, nodes },
+            metadata,
+            scope,
+        } = graph.collect().await?;
+
+        let dir = Self::scope_dir(root, &scope).join(timestamp.timestamp_millis().to_string());
+        fs::create_dir_all(&dir)
+            .map_err(|error| anyhow!("failed to create graph snapshot directory: {error}"))?;
+
+        write_parquet(&dir.join(Self::FILE_EDGES), edges)?;
+        write_parquet(&dir.join(Self::FILE_NODES), nodes)?;
+
+        let metadata = GraphSnapshotMetadata {
+            connector,
+            metadata,
+        };
+        fs::write(
+            dir.join(Self::FILE_METADATA),
+            ::serde_json::to_vec(&metadata)?,
+        )
+        .map_err(|error| anyhow!("failed to write graph snapshot metadata: {error}"))
+    }
+
+    fn list_timestamps(&self, scope: &GraphScope) -> Result<Vec<DateTime<Utc>>> {
+        let dir = Self::scope_dir(self.root()?, scope);
+        if !dir.exists() {
+            return Ok(Vec::default());
+        }
+
+        let mut timestamps: Vec<_> = fs::read_dir(&dir)
+            .map_err(|error| anyhow!("failed to list graph snapshots: {error}"))?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+            .filter_map(DateTime::from_timestamp_millis)
+            .collect();
+        timestamps.sort();
+        Ok(timestamps)
+    }
+
+    fn load(
+        &self,
+        scope: &GraphScope,
+        timestamp: DateTime<Utc>,
+    ) -> Result<GraphSnapshot<GraphData<LazyFrame>>> {
+        let dir =
+            Self::scope_dir(self.root()?, scope).join(timestamp.timestamp_millis().to_string());
+
+        let GraphSnapshotMetadata {
+            connector,
+            metadata,
+        } = fs::read(dir.join(Self::FILE_METADATA))
+            .map_err(|error| anyhow!("failed to read graph snapshot metadata: {error}"))
+            .and_then(|bytes| ::serde_json::from_slice(&bytes).map_err(Into::into))?;
+
+        Ok(GraphSnapshot {
+            graph: Graph {
+                connector,
+                data: GraphData {
+                    edges: read_parquet(&dir.join(Self::FILE_EDGES))?,
+                    nodes: read_parquet(&dir.join(Self::FILE_NODES))?,
+                },
+                metadata,
+                scope: scope.clone(),
+            },
+            timestamp,
+        })
+    }
+}
+
+#[async_trait]
+impl ::kubegraph_api::graph::history::NetworkGraphHistoryDB for NetworkGraphHistoryDB {
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn get_at(
+        &self,
+        scope: &GraphScope,
+        timestamp: DateTime<Utc>,
+    ) -> Result<Option<GraphSnapshot<GraphData<LazyFrame>>>> {
+        self.list_timestamps(scope)?
+            .into_iter()
+            .take_while(|&recorded| recorded <= timestamp)
+            .last()
+            .map(|recorded| self.load(scope, recorded))
+            .transpose()
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn range(
+        &self,
+        scope: &GraphScope,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<GraphSnapshot<GraphData<LazyFrame>>>> {
+        self.list_timestamps(scope)?
+            .into_iter()
+            .filter(|&recorded| from <= recorded && recorded <= to)
+            .map(|recorded| self.load(scope, recorded))
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphSnapshotMetadata {
+    #[serde(default)]
+    connector: Option<Arc<NetworkConnectorCrd>>,
+    metadata: GraphMetadata,
+}
+
+fn read_parquet(path: &Path) -> Result<LazyFrame> {
+    if !path.exists() {
+        return Ok(LazyFrame::Empty);
+    }
+
+    let file = File::open(path)
+        .map_err(|error| anyhow!("failed to open graph snapshot {path:?}: {error}"))?;
+    ParquetReader::new(file)
+        .finish()
+        .map(|df| DataFrame::Polars(df).lazy())
+        .map_err(|error| anyhow!("failed to read graph snapshot {path:?}: {error}"))
+}
+
+fn write_parquet(path: &Path, df: DataFrame) -> Result<()> {
+    let mut df = match df {
+        DataFrame::Empty => return Ok(()),
+        DataFrame::Polars(df) => df,
+    };
+
+    let file = File::create(path)
+        .map_err(|error| anyhow!("failed to create graph snapshot {path:?}: {error}"))?;
+    ParquetWriter::new(file)
+        .finish(&mut df)
+        .map(|_| ())
+        .map_err(|error| anyhow!("failed to write graph snapshot {path:?}: {error}"))
+}
@@ -1,3 +1,4 @@
+pub mod alert;
 pub mod connector;
 pub mod function;
 pub mod problem;
@@ -10,6 +10,7 @@ pub(crate) mod consts {
 #[tokio::main]
 async fn main() {
     join!(
+        self::ctx::alert::Ctx::spawn_crd(),
         self::ctx::connector::Ctx::spawn_crd(),
         self::ctx::function::Ctx::spawn_crd(),
         self::ctx::problem::Ctx::spawn_crd(),
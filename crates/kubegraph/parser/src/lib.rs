@@ -53,11 +53,20 @@ pub enum Expr {
         op: FunctionExpr,
         args: Vec<Expr>,
     },
+    //
+    // conditional
+    //
+    Condition {
+        r#if: Box<Expr>,
+        then: Box<Expr>,
+        r#else: Box<Expr>,
+    },
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub enum Value {
     Number(Number),
+    String(String),
     Variable(Literal),
 }
 
@@ -0,0 +1,447 @@
+use std::{collections::BTreeMap, fmt};
+
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use k8s_openapi::{
+    api::{
+        apps::v1::Deployment,
+        batch::v1::{Job, JobSpec},
+        core::v1::{Container, EnvVar, Node, PodSpec, PodTemplateSpec, Taint},
+    },
+    apimachinery::pkg::apis::meta::v1::ObjectMeta,
+};
+use kube::{
+    api::{Patch, PatchParams, PostParams},
+    Api, Client,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::explain::NetworkRunnerExplanation;
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    ValueEnum,
+)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "kebab-case")]
+pub enum NetworkRunnerActionKind {
+    /// Move replicas between the deployments named by the graph nodes
+    #[default]
+    ScaleDeployment,
+    /// Label the kubernetes nodes named by the graph nodes with their in/out flows
+    LabelNode,
+    /// Taint the kubernetes nodes named by the graph nodes which have out flows
+    TaintNode,
+    /// Create a migration job per each edge flow
+    MigrationJob,
+}
+
+impl fmt::Display for NetworkRunnerActionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.name().fmt(f)
+    }
+}
+
+impl NetworkRunnerActionKind {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::ScaleDeployment => "scale-deployment",
+            Self::LabelNode => "label-node",
+            Self::TaintNode => "taint-node",
+            Self::MigrationJob => "migration-job",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkFlow {
+    pub src: String,
+    pub sink: String,
+    pub flow: f64,
+    #[serde(default)]
+    pub capacity: Option<f64>,
+    #[serde(default)]
+    pub unit_cost: Option<f64>,
+}
+
+impl NetworkFlow {
+    /// Return the cost of the flow, which contributes to the objective value.
+    pub fn cost(&self) -> f64 {
+        self.flow * self.unit_cost.unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum NetworkRunnerAction {
+    ScaleDeployment {
+        namespace: String,
+        name: String,
+        delta: i64,
+    },
+    LabelNode {
+        name: String,
+        labels: BTreeMap<String, String>,
+    },
+    TaintNode {
+        name: String,
+        effect: String,
+        sink: String,
+    },
+    MigrationJob {
+        namespace: String,
+        image: String,
+        flow: NetworkFlow,
+    },
+}
+
+impl fmt::Display for NetworkRunnerAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ScaleDeployment {
+                namespace,
+                name,
+                delta,
+            } => write!(f, "deployment/{namespace}/{name} ({delta:+})"),
+            Self::LabelNode { name, labels } => write!(f, "node/{name} {labels:?}"),
+            Self::TaintNode { name, effect, sink } => {
+                write!(f, "node/{name} ({effect} -> {sink})")
+            }
+            Self::MigrationJob {
+                namespace,
+                image: _,
+                flow: NetworkFlow {
+                    src, sink, flow, ..
+                },
+            } => write!(f, "job/{namespace} ({src} -> {sink}: {flow})"),
+        }
+    }
+}
+
+impl NetworkRunnerAction {
+    pub const LABEL_INFLOW: &'static str = "kubegraph.ulagbulag.io/inflow";
+    pub const LABEL_OUTFLOW: &'static str = "kubegraph.ulagbulag.io/outflow";
+    pub const TAINT_KEY: &'static str = "kubegraph.ulagbulag.io/outflow";
+    pub const TAINT_EFFECT: &'static str = "PreferNoSchedule";
+
+    pub fn from_flows(
+        kind: NetworkRunnerActionKind,
+        namespace: &str,
+        image: &str,
+        flows: Vec<NetworkFlow>,
+    ) -> Vec<Self> {
+        match kind {
+            NetworkRunnerActionKind::ScaleDeployment => {
+                let mut deltas: BTreeMap<String, i64> = BTreeMap::default();
+                for NetworkFlow {
+                    src, sink, flow, ..
+                } in flows
+                {
+                    let flow = flow.round() as i64;
+                    *deltas.entry(src).or_default() -= flow;
+                    *deltas.entry(sink).or_default() += flow;
+                }
+
+                deltas
+                    .into_iter()
+                    .filter(|(_, delta)| *delta != 0)
+                    .map(|(name, delta)| Self::ScaleDeployment {
+                        namespace: namespace.into(),
+                        name,
+                        delta,
+                    })
+                    .collect()
+            }
+            NetworkRunnerActionKind::LabelNode => {
+                let mut inflows: BTreeMap<String, f64> = BTreeMap::default();
+                let mut outflows: BTreeMap<String, f64> = BTreeMap::default();
+                for NetworkFlow {
+                    src, sink, flow, ..
+                } in flows
+                {
+                    *outflows.entry(src).or_default() += flow;
+                    *inflows.entry(sink).or_default() += flow;
+                }
+
+                let mut labels: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::default();
+                for (name, flow) in inflows {
+                    labels
+                        .entry(name)
+                        .or_default()
+                        .insert(Self::LABEL_INFLOW.into(), flow.round().to_string());
+                }
+                for (name, flow) in outflows {
+                    labels
+                        .entry(name)
+                        .or_default()
+                        .insert(Self::LABEL_OUTFLOW.into(), flow.round().to_string());
+                }
+
+                labels
+                    .into_iter()
+                    .map(|(name, labels)| Self::LabelNode { name, labels })
+                    .collect()
+            }
+            NetworkRunnerActionKind::TaintNode => flows
+                .into_iter()
+                .map(|NetworkFlow { src, sink, .. }| Self::TaintNode {
+                    name: src,
+                    effect: Self::TAINT_EFFECT.into(),
+                    sink,
+                })
+                .collect(),
+            NetworkRunnerActionKind::MigrationJob => flows
+                .into_iter()
+                .map(|flow| Self::MigrationJob {
+                    namespace: namespace.into(),
+                    image: image.into(),
+                    flow,
+                })
+                .collect(),
+        }
+    }
+
+    pub const fn kind(&self) -> NetworkRunnerActionKind {
+        match self {
+            Self::ScaleDeployment { .. } => NetworkRunnerActionKind::ScaleDeployment,
+            Self::LabelNode { .. } => NetworkRunnerActionKind::LabelNode,
+            Self::TaintNode { .. } => NetworkRunnerActionKind::TaintNode,
+            Self::MigrationJob { .. } => NetworkRunnerActionKind::MigrationJob,
+        }
+    }
+
+    pub fn namespace(&self) -> Option<&str> {
+        match self {
+            Self::ScaleDeployment { namespace, .. } | Self::MigrationJob { namespace, .. } => {
+                Some(namespace)
+            }
+            Self::LabelNode { .. } | Self::TaintNode { .. } => None,
+        }
+    }
+
+    /// Return the number of workloads which would be moved by this action.
+    pub fn moved_workloads(&self) -> u64 {
+        match self {
+            Self::ScaleDeployment { delta, .. } => (*delta).max(0) as u64,
+            Self::LabelNode { .. } => 0,
+            Self::TaintNode { .. } => 1,
+            Self::MigrationJob { flow, .. } => flow.flow.round().max(0.0) as u64,
+        }
+    }
+
+    /// Explain the action with the flows which have made it.
+    pub fn explain(&self, run_id: &str, flows: &[NetworkFlow]) -> NetworkRunnerExplanation {
+        let flows = flows.iter().filter(|flow| match self {
+            Self::ScaleDeployment { name, .. } | Self::LabelNode { name, .. } => {
+                &flow.src == name || &flow.sink == name
+            }
+            Self::TaintNode { name, sink, .. } => &flow.src == name && &flow.sink == sink,
+            Self::MigrationJob { flow: target, .. } => {
+                flow.src == target.src && flow.sink == target.sink
+            }
+        });
+        NetworkRunnerExplanation::from_flows(run_id, flows)
+    }
+
+    /// Fetch the annotations of the target object, if it exists.
+    pub async fn target_annotations(
+        &self,
+        kube: &Client,
+    ) -> Result<Option<BTreeMap<String, String>>> {
+        match self {
+            Self::ScaleDeployment {
+                namespace, name, ..
+            } => Api::<Deployment>::namespaced(kube.clone(), namespace)
+                .get_opt(name)
+                .await
+                .map(|object| object.and_then(|object| object.metadata.annotations))
+                .map_err(|error| anyhow!("failed to get deployment {name:?}: {error}")),
+            Self::LabelNode { name, .. } | Self::TaintNode { name, .. } => {
+                Api::<Node>::all(kube.clone())
+                    .get_opt(name)
+                    .await
+                    .map(|object| object.and_then(|object| object.metadata.annotations))
+                    .map_err(|error| anyhow!("failed to get node {name:?}: {error}"))
+            }
+            Self::MigrationJob { .. } => Ok(None),
+        }
+    }
+
+    pub async fn apply(
+        &self,
+        kube: &Client,
+        dry_run: bool,
+        explanation: &NetworkRunnerExplanation,
+    ) -> Result<()> {
+        let pp = PatchParams {
+            dry_run,
+            ..Default::default()
+        };
+        let annotations: BTreeMap<_, _> = [(
+            NetworkRunnerExplanation::ANNOTATION.to_string(),
+            explanation.to_annotation(),
+        )]
+        .into_iter()
+        .collect();
+
+        match self {
+            Self::ScaleDeployment {
+                namespace,
+                name,
+                delta,
+            } => {
+                let api = Api::<Deployment>::namespaced(kube.clone(), namespace);
+                let replicas = api
+                    .get(name)
+                    .await
+                    .map_err(|error| anyhow!("failed to get deployment {name:?}: {error}"))?
+                    .spec
+                    .and_then(|spec| spec.replicas)
+                    .unwrap_or_default();
+
+                let patch = Patch::Merge(json!({
+                    "metadata": {
+                        "annotations": annotations,
+                    },
+                    "spec": {
+                        "replicas": (i64::from(replicas) + delta).max(0),
+                    },
+                }));
+                api.patch(name, &pp, &patch)
+                    .await
+                    .map(|_| ())
+                    .map_err(|error| anyhow!("failed to scale deployment {name:?}: {error}"))
+            }
+            Self::LabelNode { name, labels } => {
+                let api = Api::<Node>::all(kube.clone());
+                let patch = Patch::Merge(json!({
+                    "metadata": {
+                        "annotations": annotations,
+                        "labels": labels,
+                    },
+                }));
+                api.patch(name, &pp, &patch)
+                    .await
+                    .map(|_| ())
+                    .map_err(|error| anyhow!("failed to label node {name:?}: {error}"))
+            }
+            Self::TaintNode { name, effect, sink } => {
+                let api = Api::<Node>::all(kube.clone());
+                let mut taints = api
+                    .get(name)
+                    .await
+                    .map_err(|error| anyhow!("failed to get node {name:?}: {error}"))?
+                    .spec
+                    .and_then(|spec| spec.taints)
+                    .unwrap_or_default();
+
+                taints.retain(|taint| taint.key != Self::TAINT_KEY);
+                taints.push(Taint {
+                    effect: effect.clone(),
+                    key: Self::TAINT_KEY.into(),
+                    time_added: None,
+                    value: Some(sink.clone()),
+                });
+
+                let patch = Patch::Merge(json!({
+                    "metadata": {
+                        "annotations": annotations,
+                    },
+                    "spec": {
+                        "taints": taints,
+                    },
+                }));
+                api.patch(name, &pp, &patch)
+                    .await
+                    .map(|_| ())
+                    .map_err(|error| anyhow!("failed to taint node {name:?}: {error}"))
+            }
+            Self::MigrationJob {
+                namespace,
+                image,
+                flow: NetworkFlow {
+                    src, sink, flow, ..
+                },
+            } => {
+                let api = Api::<Job>::namespaced(kube.clone(), namespace);
+                let pp = PostParams {
+                    dry_run,
+                    ..Default::default()
+                };
+
+                let labels: BTreeMap<_, _> = [
+                    (
+                        "kubegraph.ulagbulag.io/runner".to_string(),
+                        "kubernetes".to_string(),
+                    ),
+                    ("kubegraph.ulagbulag.io/src".into(), src.clone()),
+                    ("kubegraph.ulagbulag.io/sink".into(), sink.clone()),
+                ]
+                .into_iter()
+                .collect();
+                let env = [
+                    ("KUBEGRAPH_MIGRATION_SRC", src.clone()),
+                    ("KUBEGRAPH_MIGRATION_SINK", sink.clone()),
+                    ("KUBEGRAPH_MIGRATION_FLOW", flow.to_string()),
+                ]
+                .into_iter()
+                .map(|(name, value)| EnvVar {
+                    name: name.into(),
+                    value: Some(value),
+                    value_from: None,
+                })
+                .collect();
+
+                let job = Job {
+                    metadata: ObjectMeta {
+                        annotations: Some(annotations),
+                        generate_name: Some("kubegraph-migration-".into()),
+                        labels: Some(labels.clone()),
+                        namespace: Some(namespace.clone()),
+                        ..Default::default()
+                    },
+                    spec: Some(JobSpec {
+                        backoff_limit: Some(0),
+                        template: PodTemplateSpec {
+                            metadata: Some(ObjectMeta {
+                                labels: Some(labels),
+                                ..Default::default()
+                            }),
+                            spec: Some(PodSpec {
+                                containers: vec![Container {
+                                    name: "migration".into(),
+                                    image: Some(image.clone()),
+                                    env: Some(env),
+                                    ..Default::default()
+                                }],
+                                restart_policy: Some("Never".into()),
+                                ..Default::default()
+                            }),
+                        },
+                        ttl_seconds_after_finished: Some(3600),
+                        ..Default::default()
+                    }),
+                    status: None,
+                };
+                api.create(&pp, &job)
+                    .await
+                    .map(|_| ())
+                    .map_err(|error| anyhow!("failed to create migration job: {error}"))
+            }
+        }
+    }
+}
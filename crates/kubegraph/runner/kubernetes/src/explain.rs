@@ -0,0 +1,91 @@
+use std::cmp::Ordering;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::action::NetworkFlow;
+
+/// A compact explanation of why the runner has acted on an object.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkRunnerExplanation {
+    /// The id of the solve run which made the decision
+    pub run_id: String,
+    /// The contribution to the objective value (total flow cost)
+    pub objective: f64,
+    /// The constraints which forced the decision, the most binding first
+    pub constraints: Vec<String>,
+}
+
+impl NetworkRunnerExplanation {
+    pub const ANNOTATION: &'static str = "kubegraph.ulagbulag.io/explanation";
+
+    const MAX_CONSTRAINTS: usize = 3;
+
+    pub fn from_flows<'a>(run_id: &str, flows: impl IntoIterator<Item = &'a NetworkFlow>) -> Self {
+        let mut objective = 0.0;
+        let mut constraints = vec![];
+        for flow in flows {
+            objective += flow.cost();
+            constraints.push(NetworkFlowConstraint::new(flow));
+        }
+
+        // Prefer the saturated edges, and then the larger flows
+        constraints.sort_by(|a, b| {
+            b.saturated
+                .cmp(&a.saturated)
+                .then_with(|| b.flow.partial_cmp(&a.flow).unwrap_or(Ordering::Equal))
+        });
+
+        Self {
+            run_id: run_id.into(),
+            objective,
+            constraints: constraints
+                .into_iter()
+                .take(Self::MAX_CONSTRAINTS)
+                .map(|constraint| constraint.description)
+                .collect(),
+        }
+    }
+
+    pub fn to_annotation(&self) -> String {
+        ::serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+struct NetworkFlowConstraint {
+    description: String,
+    flow: f64,
+    saturated: bool,
+}
+
+impl NetworkFlowConstraint {
+    fn new(flow: &NetworkFlow) -> Self {
+        let NetworkFlow {
+            src,
+            sink,
+            flow,
+            capacity,
+            unit_cost,
+        } = flow;
+
+        let saturated = capacity
+            .map(|capacity| *flow >= capacity - f64::EPSILON)
+            .unwrap_or_default();
+        let description = match (saturated, capacity, unit_cost) {
+            (true, Some(capacity), _) => {
+                format!("capacity {src} -> {sink} saturated ({flow}/{capacity})")
+            }
+            (_, _, Some(unit_cost)) => {
+                format!("flow {src} -> {sink} ({flow}) at unit cost {unit_cost}")
+            }
+            (_, _, None) => format!("flow {src} -> {sink} ({flow})"),
+        };
+
+        Self {
+            description,
+            flow: *flow,
+            saturated,
+        }
+    }
+}
@@ -0,0 +1,254 @@
+#[cfg(feature = "df-polars")]
+extern crate polars as pl;
+
+mod action;
+mod explain;
+mod limiter;
+#[cfg(feature = "df-polars")]
+mod polars;
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use ark_core::signal::FunctionSignal;
+use async_trait::async_trait;
+use chrono::Utc;
+use clap::{ArgAction, Parser};
+use kube::{
+    api::{Patch, PatchParams},
+    Api, Client,
+};
+use kubegraph_api::{
+    component::NetworkComponent,
+    frame::LazyFrame,
+    graph::{GraphScope, NetworkGraphDB},
+    metrics::METRICS,
+    problem::NetworkProblemCrd,
+    runner::{report_policy_violation, NetworkRunnerContext},
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::Mutex;
+use tracing::{error, info, instrument, warn, Level};
+
+pub use self::{
+    action::{NetworkFlow, NetworkRunnerAction, NetworkRunnerActionKind},
+    explain::NetworkRunnerExplanation,
+};
+
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema, Parser,
+)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkRunnerArgs {
+    #[arg(
+        long,
+        env = "KUBEGRAPH_RUNNER_KUBERNETES_ACTION",
+        value_enum,
+        value_name = "KIND",
+        default_value_t = NetworkRunnerActionKind::default(),
+    )]
+    #[serde(default)]
+    pub kubernetes_action: NetworkRunnerActionKind,
+
+    #[arg(long, env = "KUBEGRAPH_RUNNER_KUBERNETES_DRY_RUN", action = ArgAction::SetTrue)]
+    #[serde(default)]
+    pub kubernetes_dry_run: bool,
+
+    /// The maximum number of actions per kind within a minute; `0` means unlimited
+    #[arg(
+        long,
+        env = "KUBEGRAPH_RUNNER_KUBERNETES_MAX_ACTIONS_PER_MINUTE",
+        value_name = "COUNT",
+        default_value_t = NetworkRunnerArgs::default_max_actions_per_minute(),
+    )]
+    #[serde(default = "NetworkRunnerArgs::default_max_actions_per_minute")]
+    pub kubernetes_max_actions_per_minute: u32,
+
+    #[arg(
+        long,
+        env = "KUBEGRAPH_RUNNER_KUBERNETES_MIGRATION_IMAGE",
+        value_name = "IMAGE",
+        default_value_t = NetworkRunnerArgs::default_migration_image(),
+    )]
+    #[serde(default = "NetworkRunnerArgs::default_migration_image")]
+    pub kubernetes_migration_image: String,
+}
+
+impl Default for NetworkRunnerArgs {
+    fn default() -> Self {
+        Self {
+            kubernetes_action: NetworkRunnerActionKind::default(),
+            kubernetes_dry_run: false,
+            kubernetes_max_actions_per_minute: Self::default_max_actions_per_minute(),
+            kubernetes_migration_image: Self::default_migration_image(),
+        }
+    }
+}
+
+impl NetworkRunnerArgs {
+    const fn default_max_actions_per_minute() -> u32 {
+        10
+    }
+
+    fn default_migration_image() -> String {
+        "quay.io/ulagbulag/openark:latest".into()
+    }
+}
+
+#[derive(Clone)]
+pub struct NetworkRunner {
+    args: NetworkRunnerArgs,
+    limiter: Arc<Mutex<self::limiter::NetworkRunnerRateLimiter>>,
+}
+
+#[async_trait]
+impl NetworkComponent for NetworkRunner {
+    type Args = NetworkRunnerArgs;
+
+    #[instrument(level = Level::INFO)]
+    async fn try_new(args: <Self as NetworkComponent>::Args, _: &FunctionSignal) -> Result<Self> {
+        let limiter =
+            self::limiter::NetworkRunnerRateLimiter::new(args.kubernetes_max_actions_per_minute);
+
+        Ok(Self {
+            args,
+            limiter: Arc::new(Mutex::new(limiter)),
+        })
+    }
+}
+
+#[async_trait]
+impl<DB> ::kubegraph_api::runner::NetworkRunner<DB, LazyFrame> for NetworkRunner
+where
+    DB: NetworkGraphDB,
+{
+    #[instrument(level = Level::INFO, skip(self, ctx))]
+    async fn execute<'a>(&self, ctx: NetworkRunnerContext<'a, DB, LazyFrame>) -> Result<()> {
+        let NetworkRunnerContext {
+            connectors: _,
+            functions: _,
+            graph,
+            graph_db: _,
+            kube,
+            problem,
+            static_edges: _,
+        } = ctx;
+
+        // Step 1. Collect the optimized edge flows
+        let flows: Vec<NetworkFlow> = match graph.edges {
+            LazyFrame::Empty => return Ok(()),
+            #[cfg(feature = "df-polars")]
+            LazyFrame::Polars(edges) => self::polars::collect_flows(edges, &problem.spec.metadata)?,
+        };
+        if flows.is_empty() {
+            return Ok(());
+        }
+
+        // Step 2. Translate the flows into actions
+        let run_id = format!(
+            "{name}-{timestamp}",
+            name = &problem.scope.name,
+            timestamp = Utc::now().timestamp_millis(),
+        );
+        let actions = NetworkRunnerAction::from_flows(
+            self.args.kubernetes_action,
+            &problem.scope.namespace,
+            &self.args.kubernetes_migration_image,
+            flows.clone(),
+        );
+
+        // Step 3. Apply the actions to the cluster
+        let dry_run = self.args.kubernetes_dry_run;
+        let mut policy = problem.spec.runner_policy.begin_cycle();
+        if let Err(violation) = policy.check_cycle(&problem.scope.namespace) {
+            warn!("Skipped kubernetes runner by policy: {violation}");
+            return report_policy_violation(kube, &problem.scope, "kubernetes runner", &violation)
+                .await;
+        }
+
+        for action in actions {
+            let kind = action.kind();
+
+            let annotations = match action.target_annotations(kube).await {
+                Ok(annotations) => annotations,
+                Err(error) => {
+                    error!("failed to apply {kind} action: {error}");
+                    METRICS.record_runner_action(&problem.scope, kind.name(), "Failed");
+                    continue;
+                }
+            };
+            if let Err(violation) = policy.check_action(
+                action.namespace(),
+                annotations.as_ref(),
+                action.moved_workloads(),
+            ) {
+                warn!("Skipped {kind} action by policy: {action}: {violation}");
+                METRICS.record_runner_action(&problem.scope, kind.name(), "Forbidden");
+                let action = format!("{kind} action ({action})");
+                if let Err(error) =
+                    report_policy_violation(kube, &problem.scope, &action, &violation).await
+                {
+                    error!("{error}");
+                }
+                continue;
+            }
+
+            if !self.limiter.lock().await.try_acquire(kind) {
+                warn!("Skipped {kind} action by rate limit: {action}");
+                METRICS.record_runner_action(&problem.scope, kind.name(), "Throttled");
+                continue;
+            }
+
+            let explanation = action.explain(&run_id, &flows);
+            let result = match action.apply(kube, dry_run, &explanation).await {
+                Ok(()) if dry_run => {
+                    info!("Applied {kind} action (dry-run): {action}");
+                    "DryRun"
+                }
+                Ok(()) => {
+                    info!("Applied {kind} action: {action}");
+                    "Applied"
+                }
+                Err(error) => {
+                    error!("failed to apply {kind} action: {error}");
+                    "Failed"
+                }
+            };
+            METRICS.record_runner_action(&problem.scope, kind.name(), result);
+        }
+
+        // Step 4. Explain the whole solution on the problem
+        let explanation = NetworkRunnerExplanation::from_flows(&run_id, &flows);
+        if let Err(error) = annotate_problem(kube, &problem.scope, &explanation, dry_run).await {
+            error!("{error}");
+        }
+        Ok(())
+    }
+}
+
+async fn annotate_problem(
+    kube: &Client,
+    scope: &GraphScope,
+    explanation: &NetworkRunnerExplanation,
+    dry_run: bool,
+) -> Result<()> {
+    let api = Api::<NetworkProblemCrd>::namespaced(kube.clone(), &scope.namespace);
+    let pp = PatchParams {
+        dry_run,
+        ..Default::default()
+    };
+    let patch = Patch::Merge(json!({
+        "metadata": {
+            "annotations": {
+                NetworkRunnerExplanation::ANNOTATION: explanation.to_annotation(),
+            },
+        },
+    }));
+    api.patch(&scope.name, &pp, &patch)
+        .await
+        .map(|_| ())
+        .map_err(|error| anyhow!("failed to annotate problem {:?}: {error}", &scope.name))
+}
@@ -0,0 +1,47 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+use crate::action::NetworkRunnerActionKind;
+
+pub(crate) struct NetworkRunnerRateLimiter {
+    history: BTreeMap<NetworkRunnerActionKind, VecDeque<Instant>>,
+    max_actions: u32,
+}
+
+impl NetworkRunnerRateLimiter {
+    const WINDOW: Duration = Duration::from_secs(60);
+
+    pub(crate) fn new(max_actions: u32) -> Self {
+        Self {
+            history: BTreeMap::default(),
+            max_actions,
+        }
+    }
+
+    pub(crate) fn try_acquire(&mut self, kind: NetworkRunnerActionKind) -> bool {
+        if self.max_actions == 0 {
+            return true;
+        }
+
+        let now = Instant::now();
+        let history = self.history.entry(kind).or_default();
+        while history
+            .front()
+            .map(|&last| now.duration_since(last) >= Self::WINDOW)
+            .unwrap_or_default()
+        {
+            history.pop_front();
+        }
+
+        if history.len() < self.max_actions as usize {
+            history.push_back(now);
+            true
+        } else {
+            false
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use anyhow::{anyhow, Result};
+use kubegraph_api::{frame::polars::get_column, graph::GraphMetadataPinnedExt};
+use pl::{datatypes::DataType, lazy::dsl, lazy::frame::LazyFrame};
+
+use crate::action::NetworkFlow;
+
+pub(crate) fn collect_flows<M>(edges: LazyFrame, metadata: &M) -> Result<Vec<NetworkFlow>>
+where
+    M: GraphMetadataPinnedExt,
+{
+    let key_capacity = metadata.capacity();
+    let key_flow = metadata.flow();
+    let key_sink = metadata.sink();
+    let key_src = metadata.src();
+    let key_unit_cost = metadata.unit_cost();
+
+    let edges = edges
+        .filter(dsl::col(key_flow).cast(DataType::Float64).gt(dsl::lit(0.0)))
+        .collect()
+        .map_err(|error| anyhow!("failed to collect edge flows: {error}"))?;
+
+    let src = get_column(&edges, "edge", "src", key_src, Some(&DataType::String))?;
+    let sink = get_column(&edges, "edge", "sink", key_sink, Some(&DataType::String))?;
+    let flow = get_column(&edges, "edge", "flow", key_flow, Some(&DataType::Float64))?;
+
+    // The optional columns to explain the flows
+    let capacity = get_column(
+        &edges,
+        "edge",
+        "capacity",
+        key_capacity,
+        Some(&DataType::Float64),
+    )
+    .ok();
+    let unit_cost = get_column(
+        &edges,
+        "edge",
+        "unit_cost",
+        key_unit_cost,
+        Some(&DataType::Float64),
+    )
+    .ok();
+    let get_value = |column: Option<&pl::series::Series>, index: usize| {
+        column
+            .and_then(|column| column.f64().ok())
+            .and_then(|column| column.get(index))
+    };
+
+    Ok(src
+        .str()?
+        .into_iter()
+        .zip(sink.str()?)
+        .zip(flow.f64()?)
+        .enumerate()
+        .filter_map(|(index, ((src, sink), flow))| {
+            Some(NetworkFlow {
+                src: src?.into(),
+                sink: sink?.into(),
+                flow: flow?,
+                capacity: get_value(capacity.as_ref(), index),
+                unit_cost: get_value(unit_cost.as_ref(), index),
+            })
+        })
+        .collect())
+}
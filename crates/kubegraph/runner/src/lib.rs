@@ -3,15 +3,17 @@ extern crate polars as pl;
 
 #[cfg(feature = "df-polars")]
 mod polars;
+#[cfg(feature = "df-polars")]
+mod simulation;
 
 use anyhow::{bail, Result};
 use async_trait::async_trait;
 use kubegraph_api::{
     frame::LazyFrame,
     graph::{GraphData, GraphEdges, NetworkGraphDB},
-    runner::NetworkRunnerContext,
+    runner::{report_policy_violation, NetworkRunnerContext},
 };
-use tracing::{instrument, Level};
+use tracing::{instrument, warn, Level};
 
 #[derive(Clone, Debug, Default)]
 pub struct NetworkRunner {}
@@ -33,6 +35,17 @@ where
             static_edges,
         } = ctx;
 
+        if let Err(violation) = problem
+            .spec
+            .runner_policy
+            .begin_cycle()
+            .check_cycle(&problem.scope.namespace)
+        {
+            warn!("Skipped simulator runner by policy: {violation}");
+            return report_policy_violation(kube, &problem.scope, "simulator runner", &violation)
+                .await;
+        }
+
         match (graph, static_edges.map(GraphEdges::into_inner)) {
             (
                 GraphData {
@@ -37,20 +37,29 @@ where
             problem:
                 VirtualProblem {
                     filter: _,
-                    scope: _,
+                    scope,
                     spec:
                         ProblemSpec {
+                            analyzers: _,
+                            direction: _,
                             metadata,
+                            runner_policy: _,
+                            schedule: _,
+                            simulation,
+                            time_expansion: _,
                             verbose: _,
                         },
                 },
             static_edges,
         } = ctx;
 
-        // Step 2. Disaggregate nodes by connector
+        // Step 2. Perturb the flows with the simulation models
+        let edges = crate::simulation::simulate(&scope, &simulation, &metadata, edges)?;
+
+        // Step 3. Disaggregate nodes by connector
         let all_nodes = collect_by_connectors(connectors, &metadata, &nodes);
 
-        // Step 3. Disaggregate edges by function
+        // Step 4. Disaggregate edges by function
         let all_functions = all_nodes.flat_map(|nodes| {
             collect_by_functions(
                 &graph_db,
@@ -62,7 +71,7 @@ where
             )
         });
 
-        // Step 4. Spawn all tasks
+        // Step 5. Spawn all tasks
         all_functions
             .collect::<FuturesUnordered<_>>()
             .try_collect()
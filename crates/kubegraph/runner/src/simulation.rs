@@ -0,0 +1,167 @@
+use std::{
+    collections::BTreeMap,
+    sync::{LazyLock, Mutex},
+};
+
+use anyhow::{anyhow, Result};
+use kubegraph_api::{
+    frame::polars::get_column,
+    graph::{GraphMetadataPinnedExt, GraphScope},
+    simulation::SimulationSpec,
+};
+use pl::{
+    datatypes::DataType,
+    lazy::frame::{IntoLazy, LazyFrame},
+    prelude::NamedFrom,
+    series::Series,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use tracing::warn;
+
+/// The states of the simulated problems, which are kept across the cycles.
+static STATES: LazyLock<Mutex<BTreeMap<GraphScope, SimulationState>>> =
+    LazyLock::new(Default::default);
+
+#[derive(Default)]
+struct SimulationState {
+    cycle: u64,
+    in_flight: Vec<InFlightFlow>,
+}
+
+/// A delayed flow, which arrives at the sink after the remaining cycles.
+struct InFlightFlow {
+    src: String,
+    sink: String,
+    flow: i64,
+    remaining_cycles: u32,
+}
+
+/// Perturb the edge flows with the simulation models of the problem.
+///
+/// The failed flows are dropped, the flows beyond the jittered capacities are clipped,
+/// and the delayed flows are replaced by the ones which have arrived in this cycle.
+pub(crate) fn simulate<M>(
+    scope: &GraphScope,
+    spec: &SimulationSpec,
+    metadata: &M,
+    edges: LazyFrame,
+) -> Result<LazyFrame>
+where
+    M: GraphMetadataPinnedExt,
+{
+    let mut states = STATES
+        .lock()
+        .map_err(|error| anyhow!("failed to lock simulation states: {error}"))?;
+    if spec.is_ideal() {
+        // NOTE: the in-flight flows are dropped when the models are disabled
+        states.remove(scope);
+        return Ok(edges);
+    }
+    let state = states.entry(scope.clone()).or_default();
+    state.cycle += 1;
+
+    let key_capacity = metadata.capacity();
+    let key_flow = metadata.flow();
+    let key_sink = metadata.sink();
+    let key_src = metadata.src();
+
+    // Step 1. Collect the edges
+    let mut edges = edges
+        .collect()
+        .map_err(|error| anyhow!("failed to collect edges: {error}"))?;
+    let src = get_column(&edges, "edge", "src", key_src, Some(&DataType::String))?;
+    let sink = get_column(&edges, "edge", "sink", key_sink, Some(&DataType::String))?;
+    let flow = get_column(&edges, "edge", "flow", key_flow, Some(&DataType::Int64))?;
+    let capacity = if spec.capacity_jitter_percent > 0 {
+        let dtype = Some(&DataType::Int64);
+        Some(get_column(&edges, "edge", "capacity", key_capacity, dtype)?)
+    } else {
+        None
+    };
+    let delay = match spec.delay_key.as_deref() {
+        Some(key) => Some(get_column(
+            &edges,
+            "edge",
+            "delay",
+            key,
+            Some(&DataType::Int64),
+        )?),
+        None => None,
+    };
+    let capacities = capacity.as_ref().map(|column| column.i64()).transpose()?;
+    let delays = delay.as_ref().map(|column| column.i64()).transpose()?;
+
+    // Step 2. Release the delayed flows which have arrived
+    let mut arrived: BTreeMap<(String, String), i64> = BTreeMap::default();
+    state.in_flight.retain_mut(|in_flight| {
+        in_flight.remaining_cycles = in_flight.remaining_cycles.saturating_sub(1);
+        if in_flight.remaining_cycles == 0 {
+            let key = (in_flight.src.clone(), in_flight.sink.clone());
+            *arrived.entry(key).or_default() += in_flight.flow;
+            false
+        } else {
+            true
+        }
+    });
+
+    // Step 3. Perturb the new flows
+    let mut rng = match spec.seed {
+        Some(seed) => StdRng::seed_from_u64(seed.wrapping_add(state.cycle)),
+        None => StdRng::from_entropy(),
+    };
+    let failure_percent = spec.failure_percent.min(100);
+    let jitter = spec.capacity_jitter_percent.min(100) as f64 / 100.0;
+
+    let mut flows = Vec::with_capacity(edges.height());
+    for (index, ((src, sink), flow)) in src
+        .str()?
+        .into_iter()
+        .zip(sink.str()?)
+        .zip(flow.i64()?)
+        .enumerate()
+    {
+        let pair = src.zip(sink);
+        let mut flow = flow.unwrap_or_default();
+
+        if flow > 0 && failure_percent > 0 && rng.gen_ratio(failure_percent, 100) {
+            flow = 0;
+        }
+        if let Some(capacity) = capacities.and_then(|capacities| capacities.get(index)) {
+            if flow > 0 {
+                let capacity = capacity as f64 * (1.0 + rng.gen_range(-jitter..=jitter));
+                flow = flow.min(capacity.max(0.0) as i64);
+            }
+        }
+
+        let delay = delays
+            .and_then(|delays| delays.get(index))
+            .map(|delay| delay.clamp(0, u32::MAX as i64) as u32)
+            .unwrap_or(spec.delay_cycles);
+        if let Some((src, sink)) = pair.filter(|_| flow > 0 && delay > 0) {
+            state.in_flight.push(InFlightFlow {
+                src: src.into(),
+                sink: sink.into(),
+                flow,
+                remaining_cycles: delay,
+            });
+            flow = 0;
+        }
+
+        let arrived_flow = pair
+            .and_then(|(src, sink)| arrived.remove(&(src.into(), sink.into())))
+            .unwrap_or_default();
+        flows.push(flow + arrived_flow);
+    }
+    if !arrived.is_empty() {
+        warn!(
+            "Dropped the delayed flows of the removed edges: {scope}: {len}",
+            len = arrived.len(),
+        );
+    }
+
+    // Step 4. Replace the flows
+    edges
+        .with_column(Series::new(key_flow.into(), flows))
+        .map_err(|error| anyhow!("failed to replace edge flow column: {error}"))?;
+    Ok(edges.lazy())
+}
@@ -1,7 +1,7 @@
 use anyhow::{anyhow, bail, Result};
 use async_trait::async_trait;
 use kubegraph_api::{
-    frame::polars::{find_indices, get_column},
+    frame::polars::{find_indices, get_column, mirror_undirected_edges},
     graph::{GraphData, GraphMetadataPinned, GraphMetadataPinnedExt},
     problem::ProblemSpec,
 };
@@ -46,7 +46,16 @@ impl ::kubegraph_api::solver::NetworkSolver<GraphData<LazyFrame>> for super::Net
         graph: GraphData<LazyFrame>,
         problem: &ProblemSpec<GraphMetadataPinned>,
     ) -> Result<Self::Output> {
-        let ProblemSpec { metadata, verbose } = problem;
+        let ProblemSpec {
+            analyzers: _,
+            direction: _,
+            metadata,
+            runner_policy: _,
+            schedule: _,
+            simulation: _,
+            time_expansion: _,
+            verbose,
+        } = problem;
         let key_capacity = metadata.capacity();
         let key_flow = metadata.flow();
         let key_name = metadata.name();
@@ -60,6 +69,7 @@ impl ::kubegraph_api::solver::NetworkSolver<GraphData<LazyFrame>> for super::Net
             edges: src_edges,
             nodes: src_nodes,
         } = graph;
+        let src_edges = mirror_undirected_edges(src_edges, metadata)?;
         let edges = src_edges
             .clone()
             .select([
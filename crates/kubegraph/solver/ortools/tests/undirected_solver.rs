@@ -0,0 +1,78 @@
+extern crate polars as pl;
+
+use kubegraph_api::{graph::GraphData, problem::ProblemSpec, solver::NetworkSolver as _};
+use kubegraph_solver_ortools::NetworkSolver;
+use pl::{
+    df,
+    frame::DataFrame,
+    lazy::{dsl, frame::IntoLazy},
+};
+
+#[::tokio::test]
+async fn solver_undirected() {
+    // Step 1. Define edges (reversed to the supply)
+    let edges = df!(
+        "src"       => [  1],
+        "sink"      => [  0],
+        "capacity"  => [ 20],
+        "unit_cost" => [  1],
+        "direction" => ["undirected"],
+    )
+    .expect("failed to create edges dataframe");
+
+    // Step 2. Define edges
+    let nodes = df!(
+        "name"      => [  0,   1],
+        "capacity"  => [ 20,  10],
+        "supply"    => [ 20,   0],
+        "unit_cost" => [  5,   0],
+    )
+    .expect("failed to create nodes dataframe");
+
+    // Step 3. Define a graph
+    let graph = GraphData { edges, nodes };
+
+    // Step 4. Define a problem
+    let problem = ProblemSpec {
+        verbose: true,
+        ..Default::default()
+    };
+
+    // Step 5. Define a solver
+    let solver = NetworkSolver::default();
+
+    // Step 6. Optimize the graph
+    let optimized_graph: GraphData<DataFrame> = solver
+        .solve(graph, &problem)
+        .await
+        .expect("failed to optimize the graph")
+        .try_into()
+        .expect("failed to collect graph");
+    let GraphData {
+        edges: optimized_edges,
+        nodes: optimized_nodes,
+    } = optimized_graph;
+
+    println!();
+    println!("{}", &optimized_nodes);
+    println!("{}", &optimized_edges);
+
+    let get_arc_flow = |src, sink| -> u64 {
+        optimized_edges
+            .clone()
+            .lazy()
+            .filter(dsl::col("src").eq(src).and(dsl::col("sink").eq(sink)))
+            .collect()
+            .expect("failed to search an edge")
+            .column("flow")
+            .unwrap()
+            .get(0)
+            .expect("no such edge")
+            .try_extract()
+            .expect("failed to extract edge flow value")
+    };
+
+    // The flow goes through the mirrored edge
+    assert_eq!(get_arc_flow(0, 1), 10);
+    assert_eq!(get_arc_flow(1, 0), 0);
+}
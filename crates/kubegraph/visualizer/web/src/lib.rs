@@ -0,0 +1,281 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
+};
+
+use anyhow::Result;
+use ark_core::signal::FunctionSignal;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use kubegraph_api::{
+    component::NetworkComponent,
+    frame::LazyFrame,
+    graph::{
+        Graph, GraphData, GraphEntry, GraphMetadataExt, GraphMetadataPinned,
+        GraphMetadataPinnedExt, GraphScope,
+    },
+    visualizer::NetworkVisualizerEvent,
+};
+use petgraph::stable_graph::StableDiGraph;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::{instrument, Level};
+
+/// The static page of the web visualizer.
+pub const INDEX_HTML: &str = include_str!("../assets/index.html");
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    Parser,
+)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkVisualizerArgs {
+    /// The maximum number of the solver objectives and runner actions to keep per graph
+    #[arg(
+        long,
+        env = "KUBEGRAPH_VISUALIZER_WEB_HISTORY",
+        value_name = "COUNT",
+        default_value_t = NetworkVisualizerArgs::default_visualizer_web_history(),
+    )]
+    #[serde(default = "NetworkVisualizerArgs::default_visualizer_web_history")]
+    pub visualizer_web_history: usize,
+}
+
+impl Default for NetworkVisualizerArgs {
+    fn default() -> Self {
+        Self {
+            visualizer_web_history: Self::default_visualizer_web_history(),
+        }
+    }
+}
+
+impl NetworkVisualizerArgs {
+    const fn default_visualizer_web_history() -> usize {
+        256
+    }
+}
+
+/// A visualizer which keeps the latest graphs in memory,
+/// so that they can be served to the browsers by the gateway.
+#[derive(Clone)]
+pub struct NetworkVisualizer {
+    args: NetworkVisualizerArgs,
+    scopes: Arc<RwLock<BTreeMap<GraphScope, NetworkVisualizerScope>>>,
+}
+
+#[async_trait]
+impl NetworkComponent for NetworkVisualizer {
+    type Args = NetworkVisualizerArgs;
+
+    #[instrument(level = Level::INFO)]
+    async fn try_new(
+        args: <Self as NetworkComponent>::Args,
+        signal: &FunctionSignal,
+    ) -> Result<Self> {
+        let _ = signal;
+        Ok(Self {
+            args,
+            scopes: Arc::default(),
+        })
+    }
+}
+
+#[async_trait]
+impl ::kubegraph_api::visualizer::NetworkVisualizer for NetworkVisualizer {
+    #[instrument(level = Level::INFO, skip(self, graph))]
+    async fn replace_graph<M>(&self, graph: Graph<GraphData<LazyFrame>, M>) -> Result<()>
+    where
+        M: Send + Clone + GraphMetadataExt,
+    {
+        let timestamp = Utc::now();
+        let scope = graph.scope.clone();
+        let metadata = graph.metadata.to_pinned();
+        let objective = graph.data.edges.flow_cost(&metadata).ok();
+
+        let graph: ::petgraph::Graph<_, _> =
+            StableDiGraph::<GraphEntry, GraphEntry>::try_from(graph)?.into();
+        let (nodes, edges) = graph.into_nodes_edges();
+        let graph = NetworkVisualizerGraph {
+            metadata,
+            nodes: nodes.into_iter().map(|node| node.weight).collect(),
+            edges: edges.into_iter().map(|edge| edge.weight).collect(),
+        };
+
+        self.scopes
+            .write()
+            .await
+            .entry(scope)
+            .or_insert_with(|| NetworkVisualizerScope::new(graph.clone(), timestamp))
+            .update(
+                graph,
+                objective,
+                timestamp,
+                self.args.visualizer_web_history,
+            );
+        Ok(())
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn call(&self, event: NetworkVisualizerEvent) -> Result<()> {
+        let _ = event;
+        Ok(())
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn close(&self) -> Result<()> {
+        self.scopes.write().await.clear();
+        Ok(())
+    }
+}
+
+impl NetworkVisualizer {
+    /// List the visualized graphs in the given namespace.
+    pub async fn list(&self, namespace: &str) -> Vec<GraphScope> {
+        self.scopes
+            .read()
+            .await
+            .keys()
+            .filter(|scope| scope.namespace == namespace)
+            .cloned()
+            .collect()
+    }
+
+    /// Get the latest graph, and the histories of its solutions.
+    pub async fn get(&self, scope: &GraphScope) -> Option<NetworkVisualizerScope> {
+        self.scopes.read().await.get(scope).cloned()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkVisualizerScope {
+    pub actions: VecDeque<NetworkVisualizerAction>,
+    pub graph: NetworkVisualizerGraph,
+    pub objectives: VecDeque<NetworkVisualizerObjective>,
+    pub updated: DateTime<Utc>,
+    #[serde(skip)]
+    flows: BTreeMap<(String, String), f64>,
+}
+
+impl NetworkVisualizerScope {
+    fn new(graph: NetworkVisualizerGraph, timestamp: DateTime<Utc>) -> Self {
+        Self {
+            actions: VecDeque::default(),
+            graph,
+            objectives: VecDeque::default(),
+            updated: timestamp,
+            flows: BTreeMap::default(),
+        }
+    }
+
+    fn update(
+        &mut self,
+        graph: NetworkVisualizerGraph,
+        objective: Option<f64>,
+        timestamp: DateTime<Utc>,
+        limit: usize,
+    ) {
+        // NOTE: the graphs are not solved yet if no flows are found
+        let flows = graph.flows();
+        if !flows.is_empty() {
+            if let Some(value) = objective {
+                push_bounded(
+                    &mut self.objectives,
+                    NetworkVisualizerObjective { timestamp, value },
+                    limit,
+                );
+            }
+
+            let mut keys: Vec<_> = self.flows.keys().chain(flows.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let (src, sink) = key;
+                let from = self.flows.get(key).copied().unwrap_or_default();
+                let to = flows.get(key).copied().unwrap_or_default();
+                if (to - from).abs() > f64::EPSILON {
+                    push_bounded(
+                        &mut self.actions,
+                        NetworkVisualizerAction {
+                            timestamp,
+                            src: src.clone(),
+                            sink: sink.clone(),
+                            from,
+                            to,
+                        },
+                        limit,
+                    );
+                }
+            }
+            self.flows = flows;
+        }
+
+        self.graph = graph;
+        self.updated = timestamp;
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkVisualizerGraph {
+    pub metadata: GraphMetadataPinned,
+    pub nodes: Vec<GraphEntry>,
+    pub edges: Vec<GraphEntry>,
+}
+
+impl NetworkVisualizerGraph {
+    fn flows(&self) -> BTreeMap<(String, String), f64> {
+        let key_flow = self.metadata.flow();
+        let key_sink = self.metadata.sink();
+        let key_src = self.metadata.src();
+
+        self.edges
+            .iter()
+            .filter_map(|edge| {
+                let src = edge.others.get(key_src)?.as_string()?;
+                let sink = edge.others.get(key_sink)?.as_string()?;
+                let flow = edge.others.get(key_flow)?.as_number()?;
+                Some(((src.clone(), sink.clone()), flow.into_inner()))
+            })
+            .collect()
+    }
+}
+
+/// An objective value (total flow cost) of the solved problem.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkVisualizerObjective {
+    pub timestamp: DateTime<Utc>,
+    pub value: f64,
+}
+
+/// A change of the edge flow, which is applied by the runner.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkVisualizerAction {
+    pub timestamp: DateTime<Utc>,
+    pub src: String,
+    pub sink: String,
+    pub from: f64,
+    pub to: f64,
+}
+
+fn push_bounded<T>(items: &mut VecDeque<T>, item: T, limit: usize) {
+    items.push_back(item);
+    while items.len() > limit {
+        items.pop_front();
+    }
+}
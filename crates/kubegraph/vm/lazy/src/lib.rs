@@ -127,6 +127,7 @@ mod impl_call {
                     }
                     Stmt::DefineLocalFeature { value } => Variable::Feature(value),
                     Stmt::DefineLocalValue { value } => Variable::Number(value),
+                    Stmt::DefineLocalString { value } => Variable::String(value),
                     Stmt::BinaryExpr { lhs, rhs, op } => {
                         let lhs = stack.fetch(lhs);
                         let rhs = stack.fetch(rhs);
@@ -141,6 +142,12 @@ mod impl_call {
                             VariableVec(args.into_iter().map(|arg| stack.fetch(arg)).collect());
                         args.execute_expr_function(op)?
                     }
+                    Stmt::ConditionExpr { r#if, then, r#else } => {
+                        let r#if = stack.fetch(r#if);
+                        let then = stack.fetch(then);
+                        let r#else = stack.fetch(r#else);
+                        r#if.select(then, r#else, &heap.edges)?
+                    }
                 };
 
                 // fetch from heap
@@ -195,6 +202,7 @@ mod impl_call {
         fn get_feature(&self, key: &str) -> Result<Variable> {
             match self.get_unchecked(key)? {
                 Variable::Number(_) => bail!("unexpected value: {key:?}"),
+                Variable::String(_) => bail!("unexpected string: {key:?}"),
                 value => Ok(value),
             }
         }
@@ -202,6 +210,7 @@ mod impl_call {
         fn get_number(&self, key: &str) -> Result<Variable> {
             match self.get_unchecked(key)? {
                 Variable::Feature(_) => bail!("unexpected feature: {key:?}"),
+                Variable::String(_) => bail!("unexpected string: {key:?}"),
                 value => Ok(value),
             }
         }
@@ -227,6 +236,9 @@ mod impl_call {
                     self.edges.fill_column_with_value(&key, *value)?;
                 }
                 Variable::Number(None) => error_undefined_number()?,
+                Variable::String(value) => {
+                    self.edges.fill_column_with_string(&key, value.clone())?;
+                }
             }
             self.variables.insert(key, value);
             Ok(())
@@ -269,13 +281,7 @@ mod impl_call {
         fn pop_slice(&mut self, edges: &LazyFrame) -> Result<LazySlice> {
             self.0
                 .pop()
-                .map(|value| match value {
-                    Variable::LazySlice(value) => Ok(value),
-                    Variable::Feature(Some(value)) => value.try_into_lazy_slice(edges),
-                    Variable::Feature(None) => error_undefined_feature(),
-                    Variable::Number(Some(value)) => value.try_into_lazy_slice(edges),
-                    Variable::Number(None) => error_undefined_number(),
-                })
+                .map(|value| value.try_into_lazy_slice(edges))
                 .unwrap_or_else(|| edges.all())
         }
     }
@@ -285,6 +291,7 @@ mod impl_call {
         LazySlice(LazySlice),
         Feature(Option<Feature>),
         Number(Option<Number>),
+        String(String),
     }
 
     impl From<LazySlice> for Variable {
@@ -323,6 +330,32 @@ mod impl_call {
     }
 
     impl Variable {
+        fn try_into_lazy_slice(self, edges: &LazyFrame) -> Result<LazySlice> {
+            match self {
+                Variable::LazySlice(value) => Ok(value),
+                Variable::Feature(Some(value)) => value.try_into_lazy_slice(edges),
+                Variable::Feature(None) => error_undefined_feature(),
+                Variable::Number(Some(value)) => value.try_into_lazy_slice(edges),
+                Variable::Number(None) => error_undefined_number(),
+                Variable::String(value) => value.try_into_lazy_slice(edges),
+            }
+        }
+
+        fn select(self, then: Self, r#else: Self, edges: &LazyFrame) -> Result<Self> {
+            match self {
+                Variable::LazySlice(r#if) => {
+                    let then = then.try_into_lazy_slice(edges)?;
+                    let r#else = r#else.try_into_lazy_slice(edges)?;
+                    Ok(Variable::LazySlice(r#if.select(then, r#else)))
+                }
+                Variable::Feature(Some(r#if)) if r#if.into_inner() => Ok(then),
+                Variable::Feature(Some(_)) => Ok(r#else),
+                Variable::Feature(None) => error_undefined_feature(),
+                Variable::Number(_) => error_unexpected_type_number(),
+                Variable::String(_) => error_unexpected_type_string(),
+            }
+        }
+
         fn execute_expr_unary(self, op: UnaryExpr) -> Result<Self> {
             match op {
                 UnaryExpr::Neg => self.neg(),
@@ -331,6 +364,10 @@ mod impl_call {
         }
 
         fn execute_expr_binary(self, op: BinaryExpr, rhs: Self) -> Result<Self> {
+            if matches!(self, Variable::String(_)) || matches!(rhs, Variable::String(_)) {
+                return self.execute_expr_binary_string(op, rhs);
+            }
+
             match op {
                 BinaryExpr::Add => self.add(rhs),
                 BinaryExpr::Sub => self.sub(rhs),
@@ -346,6 +383,27 @@ mod impl_call {
                 BinaryExpr::Or => self.or(rhs),
             }
         }
+
+        fn execute_expr_binary_string(self, op: BinaryExpr, rhs: Self) -> Result<Self> {
+            match (op, self, rhs) {
+                (BinaryExpr::Eq, Variable::LazySlice(lhs), Variable::String(rhs))
+                | (BinaryExpr::Eq, Variable::String(rhs), Variable::LazySlice(lhs)) => {
+                    Ok(Variable::LazySlice(lhs.eq(rhs)))
+                }
+                (BinaryExpr::Eq, Variable::String(lhs), Variable::String(rhs)) => {
+                    Ok(Variable::Feature(Some(Feature::new(lhs == rhs))))
+                }
+                (BinaryExpr::Ne, Variable::LazySlice(lhs), Variable::String(rhs))
+                | (BinaryExpr::Ne, Variable::String(rhs), Variable::LazySlice(lhs)) => {
+                    Ok(Variable::LazySlice(lhs.ne(rhs)))
+                }
+                (BinaryExpr::Ne, Variable::String(lhs), Variable::String(rhs)) => {
+                    Ok(Variable::Feature(Some(Feature::new(lhs != rhs))))
+                }
+                (BinaryExpr::Eq | BinaryExpr::Ne, _, _) => error_unexpected_type_string(),
+                (op, _, _) => bail!("unsupported string operator: {op:?}"),
+            }
+        }
     }
 
     struct VariableVec(Vec<Variable>);
@@ -362,6 +420,38 @@ mod impl_call {
             match op {
                 BuiltInFunctionExpr::Max => self.max(),
                 BuiltInFunctionExpr::Min => self.min(),
+                BuiltInFunctionExpr::Matches => match <[_; 2]>::try_from(self.0) {
+                    Ok([Variable::LazySlice(src), Variable::String(pattern)]) => {
+                        Ok(Variable::LazySlice(src.matches(&pattern)))
+                    }
+                    _ => bail!("matches expects a column and a string pattern"),
+                },
+                BuiltInFunctionExpr::Sum => match <[_; 1]>::try_from(self.0) {
+                    Ok([Variable::LazySlice(src)]) => Ok(Variable::LazySlice(src.sum())),
+                    _ => bail!("sum expects a column"),
+                },
+                BuiltInFunctionExpr::SumOver => {
+                    self.execute_expr_function_builtin_over(op, LazySlice::sum_over)
+                }
+                BuiltInFunctionExpr::MaxOver => {
+                    self.execute_expr_function_builtin_over(op, LazySlice::max_over)
+                }
+                BuiltInFunctionExpr::MinOver => {
+                    self.execute_expr_function_builtin_over(op, LazySlice::min_over)
+                }
+            }
+        }
+
+        fn execute_expr_function_builtin_over(
+            self,
+            op: BuiltInFunctionExpr,
+            f: impl FnOnce(LazySlice, LazySlice) -> LazySlice,
+        ) -> Result<Variable> {
+            match <[_; 2]>::try_from(self.0) {
+                Ok([Variable::LazySlice(src), Variable::LazySlice(group)]) => {
+                    Ok(Variable::LazySlice(f(src, group)))
+                }
+                _ => bail!("{op:?} expects a column and a group column"),
             }
         }
     }
@@ -377,6 +467,7 @@ mod impl_call {
                         Variable::Feature(Some(src)) => Ok(Variable::Feature(Some(src.not()))),
                         Variable::Feature(None) => error_undefined_feature(),
                         Variable::Number(_) => error_unexpected_type_number(),
+                        Variable::String(_) => error_unexpected_type_string(),
                     }
                 }
             }
@@ -391,6 +482,7 @@ mod impl_call {
                         Variable::Feature(_) => error_unexpected_type_feature(),
                         Variable::Number(Some(src)) => Ok(Variable::Number(Some(src.neg()))),
                         Variable::Number(None) => error_undefined_number(),
+                        Variable::String(_) => error_unexpected_type_string(),
                     }
                 }
             }
@@ -412,6 +504,7 @@ mod impl_call {
                             Variable::Feature(Some(rhs)) => Ok(Variable::LazySlice(lhs.$fn(rhs))),
                             Variable::Feature(None) => error_undefined_feature(),
                             Variable::Number(_) => error_unexpected_type_number(),
+                            Variable::String(_) => error_unexpected_type_string(),
                         },
                         Variable::Feature(Some(lhs)) => match rhs {
                             Variable::LazySlice(rhs) => Ok(Variable::LazySlice(lhs.$fn(rhs))),
@@ -420,9 +513,11 @@ mod impl_call {
                             }
                             Variable::Feature(None) => error_undefined_feature(),
                             Variable::Number(_) => error_unexpected_type_number(),
+                            Variable::String(_) => error_unexpected_type_string(),
                         },
                         Variable::Feature(None) => error_undefined_feature(),
                         Variable::Number(_) => error_unexpected_type_number(),
+                        Variable::String(_) => error_unexpected_type_string(),
                     }
                 }
             }
@@ -438,6 +533,7 @@ mod impl_call {
                             Variable::Feature(_) => error_unexpected_type_feature(),
                             Variable::Number(Some(rhs)) => Ok(Variable::LazySlice(lhs.$fn(rhs))),
                             Variable::Number(None) => error_undefined_number(),
+                            Variable::String(_) => error_unexpected_type_string(),
                         },
                         Variable::Feature(_) => error_unexpected_type_feature(),
                         Variable::Number(Some(lhs)) => match rhs {
@@ -447,8 +543,10 @@ mod impl_call {
                                 Ok(Variable::Feature(Some(lhs.$fn(rhs))))
                             }
                             Variable::Number(None) => error_undefined_number(),
+                            Variable::String(_) => error_unexpected_type_string(),
                         },
                         Variable::Number(None) => error_undefined_number(),
+                        Variable::String(_) => error_unexpected_type_string(),
                     }
                 }
             }
@@ -464,6 +562,7 @@ mod impl_call {
                             Variable::Feature(_) => error_unexpected_type_feature(),
                             Variable::Number(Some(rhs)) => Ok(Variable::LazySlice(lhs.$fn(rhs))),
                             Variable::Number(None) => error_undefined_number(),
+                            Variable::String(_) => error_unexpected_type_string(),
                         },
                         Variable::Feature(_) => error_unexpected_type_feature(),
                         Variable::Number(Some(lhs)) => match rhs {
@@ -471,8 +570,10 @@ mod impl_call {
                             Variable::Feature(_) => error_unexpected_type_feature(),
                             Variable::Number(Some(rhs)) => Ok(Variable::Number(Some(lhs.$fn(rhs)))),
                             Variable::Number(None) => error_undefined_number(),
+                            Variable::String(_) => error_unexpected_type_string(),
                         },
                         Variable::Number(None) => error_undefined_number(),
+                        Variable::String(_) => error_unexpected_type_string(),
                     }
                 }
             }
@@ -488,6 +589,7 @@ mod impl_call {
                             Variable::Feature(_) => error_unexpected_type_feature(),
                             Variable::Number(Some(rhs)) => Ok(Variable::LazySlice(lhs.$fn(rhs))),
                             Variable::Number(None) => error_undefined_number(),
+                            Variable::String(_) => error_unexpected_type_string(),
                         },
                         Variable::Feature(_) => error_unexpected_type_feature(),
                         Variable::Number(Some(lhs)) => match rhs {
@@ -497,8 +599,10 @@ mod impl_call {
                                 Ok(Variable::Number(Some(lhs.$fn(rhs)?)))
                             }
                             Variable::Number(None) => error_undefined_number(),
+                            Variable::String(_) => error_unexpected_type_string(),
                         },
                         Variable::Number(None) => error_undefined_number(),
+                        Variable::String(_) => error_unexpected_type_string(),
                     }
                 }
             }
@@ -547,6 +651,7 @@ mod impl_call {
                                 Variable::Feature(_) => error_unexpected_type_feature(),
                                 Variable::Number(Some(arg)) => Ok(LazySliceOrScalar::Scalar(arg)),
                                 Variable::Number(None) => error_undefined_number(),
+                                Variable::String(_) => error_unexpected_type_string(),
                             })
                             .collect::<Result<Vec<_>>>()?;
 
@@ -583,6 +688,10 @@ mod impl_call {
     fn error_unexpected_type_number<T>() -> Result<T> {
         error_unexpected_type("number")
     }
+
+    fn error_unexpected_type_string<T>() -> Result<T> {
+        error_unexpected_type("string")
+    }
 }
 
 mod impl_execute {
@@ -658,6 +767,13 @@ mod impl_execute {
         fn execute_get_local_value(&mut self, value: Value) -> Result<RefValue> {
             match value {
                 Value::Number(data) => Ok(RefValue::Number(data)),
+                Value::String(data) => {
+                    let ins = Instruction {
+                        name: None,
+                        stmt: LazyStmt::DefineLocalString { value: data },
+                    };
+                    Ok(self.execute_register_instruction(ins))
+                }
                 Value::Variable(name) => self.execute_get_local_value_by_name(&name.0),
             }
         }
@@ -686,6 +802,9 @@ mod impl_execute {
                 Expr::Unary { value, op } => self.execute_expr_unary(op, *value)?,
                 Expr::Binary { lhs, rhs, op } => self.execute_expr_binary(op, *lhs, *rhs)?,
                 Expr::Function { op, args } => self.execute_expr_function(op, args)?,
+                Expr::Condition { r#if, then, r#else } => {
+                    self.execute_expr_condition(*r#if, *then, *r#else)?
+                }
             };
 
             match stmt.to_value() {
@@ -834,6 +953,18 @@ mod impl_execute {
             lhs.or(rhs)
         }
 
+        fn execute_expr_condition(
+            &mut self,
+            r#if: Expr,
+            then: Expr,
+            r#else: Expr,
+        ) -> Result<LazyStmt> {
+            let r#if = self.execute_expr(r#if)?;
+            let then = self.execute_expr(then)?;
+            let r#else = self.execute_expr(r#else)?;
+            r#if.select(then, r#else)
+        }
+
         fn execute_expr_function(&mut self, op: FunctionExpr, args: Vec<Expr>) -> Result<LazyStmt> {
             let args = args
                 .into_iter()
@@ -854,6 +985,33 @@ mod impl_execute {
             match op {
                 BuiltInFunctionExpr::Max => self.execute_expr_function_builtin_max(args),
                 BuiltInFunctionExpr::Min => self.execute_expr_function_builtin_min(args),
+                BuiltInFunctionExpr::Matches
+                | BuiltInFunctionExpr::SumOver
+                | BuiltInFunctionExpr::MaxOver
+                | BuiltInFunctionExpr::MinOver => {
+                    self.execute_expr_function_builtin_lazy(op, args, 2)
+                }
+                BuiltInFunctionExpr::Sum => self.execute_expr_function_builtin_lazy(op, args, 1),
+            }
+        }
+
+        /// Defer the function to the call time, as it depends on the rows.
+        fn execute_expr_function_builtin_lazy(
+            &mut self,
+            op: BuiltInFunctionExpr,
+            args: Vec<RefValue>,
+            num_args: usize,
+        ) -> Result<LazyStmt> {
+            if args.len() == num_args {
+                Ok(LazyStmt::FunctionExpr {
+                    op: FunctionExpr::BuiltIn(op),
+                    args,
+                })
+            } else {
+                bail!(
+                    "{op:?} expects {num_args} arguments, but given {len}",
+                    len = args.len(),
+                )
             }
         }
 
@@ -967,4 +1125,45 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn lazy_condition_with_placeholder() {
+        let mut vm = LazyVirtualMachine::default();
+        vm.execute_register_value("a".into(), None);
+
+        let input = "b = if a > 1 then 2 else 3;";
+        vm.execute_script(input).expect("failed to compile");
+
+        let script = vm.dump_script();
+
+        assert_eq!(
+            script.code,
+            &[
+                Instruction {
+                    name: Some("a".into()),
+                    stmt: Stmt::DefineLocalValue { value: None },
+                },
+                Instruction {
+                    name: None,
+                    stmt: Stmt::BinaryExpr {
+                        lhs: Value::Variable(0),
+                        rhs: Value::Number(Number::new(1.)),
+                        op: BinaryExpr::Gt,
+                    },
+                },
+                Instruction {
+                    name: None,
+                    stmt: Stmt::ConditionExpr {
+                        r#if: Value::Variable(1),
+                        then: Value::Number(Number::new(2.)),
+                        r#else: Value::Number(Number::new(3.)),
+                    },
+                },
+                Instruction {
+                    name: Some("b".into()),
+                    stmt: Stmt::Identity { index: 2 },
+                },
+            ]
+        );
+    }
 }
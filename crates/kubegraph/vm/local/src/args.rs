@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use clap::Parser;
 use kubegraph_api::{
     component::NetworkComponent,
@@ -14,6 +16,11 @@ pub struct NetworkArgs {
     #[serde(default)]
     pub dependency_graph: <<crate::NetworkVirtualMachine as NetworkVirtualMachine>::DependencySolver as NetworkComponent>::Args,
 
+    #[cfg(feature = "federation")]
+    #[command(flatten)]
+    #[serde(default)]
+    pub federation: ::kubegraph_federation::NetworkFederationArgs,
+
     #[command(flatten)]
     #[serde(default)]
     pub graph_db: <<crate::NetworkVirtualMachine as NetworkVirtualMachine>::GraphDB as NetworkComponent>::Args,
@@ -43,7 +50,7 @@ pub struct NetworkArgs {
     pub vm: NetworkVirtualMachineArgs,
 }
 
-#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, Parser)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, Parser)]
 #[clap(rename_all = "kebab-case")]
 #[serde(rename_all = "camelCase")]
 pub struct NetworkVirtualMachineArgs {
@@ -64,4 +71,9 @@ pub struct NetworkVirtualMachineArgs {
     )]
     #[serde(default)]
     pub restart_policy: NetworkVirtualMachineRestartPolicy,
+
+    /// Record the steps of the optimize-run loop into the directory, to be replayed later.
+    #[arg(long, env = "KUBEGRAPH_VM_RECORD_DIR", value_name = "PATH")]
+    #[serde(default)]
+    pub record_dir: Option<PathBuf>,
 }
@@ -1,11 +1,12 @@
 use anyhow::Result;
 use ark_core::signal::FunctionSignal;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use clap::{Parser, ValueEnum};
 use kubegraph_api::{
     component::NetworkComponent,
     frame::LazyFrame,
-    graph::{Graph, GraphData, GraphFilter, GraphScope},
+    graph::{history::GraphSnapshot, Graph, GraphData, GraphFilter, GraphProjection, GraphScope},
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -38,6 +39,16 @@ pub struct NetworkGraphDBArgs {
     #[serde(default)]
     pub graph_db: NetworkGraphDBType,
 
+    #[cfg(feature = "graph-grpc")]
+    #[command(flatten)]
+    #[serde(default)]
+    pub grpc: <::kubegraph_graph_grpc::NetworkGraphDB as NetworkComponent>::Args,
+
+    #[cfg(feature = "graph-history")]
+    #[command(flatten)]
+    #[serde(default)]
+    pub history: <::kubegraph_graph_history::NetworkGraphHistoryDB as NetworkComponent>::Args,
+
     #[cfg(feature = "graph-local")]
     #[command(flatten)]
     #[serde(default)]
@@ -67,6 +78,8 @@ pub struct NetworkGraphDBArgs {
 #[clap(rename_all = "kebab-case")]
 #[serde(rename_all = "kebab-case")]
 pub enum NetworkGraphDBType {
+    #[cfg(feature = "graph-grpc")]
+    Grpc,
     #[cfg(feature = "graph-local")]
     Local,
     #[cfg(feature = "graph-memory")]
@@ -75,7 +88,16 @@ pub enum NetworkGraphDBType {
 }
 
 #[derive(Clone)]
-pub enum NetworkGraphDB {
+pub struct NetworkGraphDB {
+    backend: NetworkGraphDBBackend,
+    #[cfg(feature = "graph-history")]
+    history: ::kubegraph_graph_history::NetworkGraphHistoryDB,
+}
+
+#[derive(Clone)]
+enum NetworkGraphDBBackend {
+    #[cfg(feature = "graph-grpc")]
+    Grpc(::kubegraph_graph_grpc::NetworkGraphDB),
     #[cfg(feature = "graph-local")]
     Local(::kubegraph_graph_local::NetworkGraphDB),
     #[cfg(feature = "graph-memory")]
@@ -93,22 +115,37 @@ impl NetworkComponent for NetworkGraphDB {
     ) -> Result<Self> {
         let NetworkGraphDBArgs {
             graph_db,
+            #[cfg(feature = "graph-grpc")]
+            grpc,
+            #[cfg(feature = "graph-history")]
+            history,
             #[cfg(feature = "graph-local")]
             local,
             #[cfg(feature = "graph-memory")]
             memory,
         } = args;
 
-        match graph_db {
+        let backend = match graph_db {
+            #[cfg(feature = "graph-grpc")]
+            NetworkGraphDBType::Grpc => NetworkGraphDBBackend::Grpc(
+                ::kubegraph_graph_grpc::NetworkGraphDB::try_new(grpc, signal).await?,
+            ),
             #[cfg(feature = "graph-local")]
-            NetworkGraphDBType::Local => Ok(Self::Local(
+            NetworkGraphDBType::Local => NetworkGraphDBBackend::Local(
                 ::kubegraph_graph_local::NetworkGraphDB::try_new(local, signal).await?,
-            )),
+            ),
             #[cfg(feature = "graph-memory")]
-            NetworkGraphDBType::Memory => Ok(Self::Memory(
+            NetworkGraphDBType::Memory => NetworkGraphDBBackend::Memory(
                 ::kubegraph_graph_memory::NetworkGraphDB::try_new(memory, signal).await?,
-            )),
-        }
+            ),
+        };
+
+        Ok(Self {
+            backend,
+            #[cfg(feature = "graph-history")]
+            history: ::kubegraph_graph_history::NetworkGraphHistoryDB::try_new(history, signal)
+                .await?,
+        })
     }
 }
 
@@ -116,51 +153,155 @@ impl NetworkComponent for NetworkGraphDB {
 impl ::kubegraph_api::graph::NetworkGraphDB for NetworkGraphDB {
     #[instrument(level = Level::INFO, skip(self))]
     async fn get(&self, scope: &GraphScope) -> Result<Option<Graph<GraphData<LazyFrame>>>> {
-        match self {
+        match &self.backend {
+            #[cfg(feature = "graph-grpc")]
+            NetworkGraphDBBackend::Grpc(runtime) => runtime.get(scope).await,
             #[cfg(feature = "graph-local")]
-            Self::Local(runtime) => runtime.get(scope).await,
+            NetworkGraphDBBackend::Local(runtime) => runtime.get(scope).await,
             #[cfg(feature = "graph-memory")]
-            Self::Memory(runtime) => runtime.get(scope).await,
+            NetworkGraphDBBackend::Memory(runtime) => runtime.get(scope).await,
         }
     }
 
     #[instrument(level = Level::INFO, skip(self, graph))]
     async fn insert(&self, graph: Graph<GraphData<LazyFrame>>) -> Result<()> {
-        match self {
+        #[cfg(feature = "graph-history")]
+        self.history.record(graph.clone()).await?;
+
+        match &self.backend {
+            #[cfg(feature = "graph-grpc")]
+            NetworkGraphDBBackend::Grpc(runtime) => runtime.insert(graph).await,
             #[cfg(feature = "graph-local")]
-            Self::Local(runtime) => runtime.insert(graph).await,
+            NetworkGraphDBBackend::Local(runtime) => runtime.insert(graph).await,
             #[cfg(feature = "graph-memory")]
-            Self::Memory(runtime) => runtime.insert(graph).await,
+            NetworkGraphDBBackend::Memory(runtime) => runtime.insert(graph).await,
         }
     }
 
     #[instrument(level = Level::INFO, skip(self))]
     async fn list(&self, filter: &GraphFilter) -> Result<Vec<Graph<GraphData<LazyFrame>>>> {
-        match self {
+        match &self.backend {
+            #[cfg(feature = "graph-grpc")]
+            NetworkGraphDBBackend::Grpc(runtime) => runtime.list(filter).await,
+            #[cfg(feature = "graph-local")]
+            NetworkGraphDBBackend::Local(runtime) => runtime.list(filter).await,
+            #[cfg(feature = "graph-memory")]
+            NetworkGraphDBBackend::Memory(runtime) => runtime.list(filter).await,
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip(self, projection))]
+    async fn get_projected(
+        &self,
+        scope: &GraphScope,
+        projection: &GraphProjection,
+    ) -> Result<Option<Graph<GraphData<LazyFrame>>>> {
+        match &self.backend {
+            #[cfg(feature = "graph-grpc")]
+            NetworkGraphDBBackend::Grpc(runtime) => runtime.get_projected(scope, projection).await,
+            #[cfg(feature = "graph-local")]
+            NetworkGraphDBBackend::Local(runtime) => runtime.get_projected(scope, projection).await,
+            #[cfg(feature = "graph-memory")]
+            NetworkGraphDBBackend::Memory(runtime) => {
+                runtime.get_projected(scope, projection).await
+            }
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip(self, projection))]
+    async fn list_projected(
+        &self,
+        filter: &GraphFilter,
+        projection: &GraphProjection,
+    ) -> Result<Vec<Graph<GraphData<LazyFrame>>>> {
+        match &self.backend {
+            #[cfg(feature = "graph-grpc")]
+            NetworkGraphDBBackend::Grpc(runtime) => {
+                runtime.list_projected(filter, projection).await
+            }
             #[cfg(feature = "graph-local")]
-            Self::Local(runtime) => runtime.list(filter).await,
+            NetworkGraphDBBackend::Local(runtime) => {
+                runtime.list_projected(filter, projection).await
+            }
             #[cfg(feature = "graph-memory")]
-            Self::Memory(runtime) => runtime.list(filter).await,
+            NetworkGraphDBBackend::Memory(runtime) => {
+                runtime.list_projected(filter, projection).await
+            }
         }
     }
 
     #[instrument(level = Level::INFO, skip(self))]
     async fn remove(&self, scope: GraphScope) -> Result<()> {
-        match self {
+        match &self.backend {
+            #[cfg(feature = "graph-grpc")]
+            NetworkGraphDBBackend::Grpc(runtime) => runtime.remove(scope).await,
             #[cfg(feature = "graph-local")]
-            Self::Local(runtime) => runtime.remove(scope).await,
+            NetworkGraphDBBackend::Local(runtime) => runtime.remove(scope).await,
             #[cfg(feature = "graph-memory")]
-            Self::Memory(runtime) => runtime.remove(scope).await,
+            NetworkGraphDBBackend::Memory(runtime) => runtime.remove(scope).await,
         }
     }
 
     #[instrument(level = Level::INFO, skip(self))]
     async fn close(&self) -> Result<()> {
-        match self {
+        match &self.backend {
+            #[cfg(feature = "graph-grpc")]
+            NetworkGraphDBBackend::Grpc(runtime) => runtime.close().await,
             #[cfg(feature = "graph-local")]
-            Self::Local(runtime) => runtime.close().await,
+            NetworkGraphDBBackend::Local(runtime) => runtime.close().await,
             #[cfg(feature = "graph-memory")]
-            Self::Memory(runtime) => runtime.close().await,
+            NetworkGraphDBBackend::Memory(runtime) => runtime.close().await,
+        }
+    }
+}
+
+#[async_trait]
+impl ::kubegraph_api::graph::history::NetworkGraphHistoryDB for NetworkGraphDB {
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn get_at(
+        &self,
+        scope: &GraphScope,
+        timestamp: DateTime<Utc>,
+    ) -> Result<Option<GraphSnapshot<GraphData<LazyFrame>>>> {
+        #[cfg(feature = "graph-history")]
+        {
+            ::kubegraph_api::graph::history::NetworkGraphHistoryDB::get_at(
+                &self.history,
+                scope,
+                timestamp,
+            )
+            .await
+        }
+
+        #[cfg(not(feature = "graph-history"))]
+        {
+            let _ = (scope, timestamp);
+            ::anyhow::bail!("graph history db is not supported")
+        }
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn range(
+        &self,
+        scope: &GraphScope,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<GraphSnapshot<GraphData<LazyFrame>>>> {
+        #[cfg(feature = "graph-history")]
+        {
+            ::kubegraph_api::graph::history::NetworkGraphHistoryDB::range(
+                &self.history,
+                scope,
+                from,
+                to,
+            )
+            .await
+        }
+
+        #[cfg(not(feature = "graph-history"))]
+        {
+            let _ = (scope, from, to);
+            ::anyhow::bail!("graph history db is not supported")
         }
     }
 }
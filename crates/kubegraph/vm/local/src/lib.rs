@@ -16,6 +16,7 @@ use async_trait::async_trait;
 use clap::Parser;
 use kubegraph_api::{
     component::NetworkComponent,
+    recorder::NetworkRecorder,
     vm::{NetworkFallbackPolicy, NetworkVirtualMachineExt, NetworkVirtualMachineRestartPolicy},
 };
 use tokio::{sync::Mutex, task::JoinHandle};
@@ -25,7 +26,10 @@ use tracing::{instrument, Level};
 pub struct NetworkVirtualMachine {
     dependency_graph: self::dependency::NetworkDependencyGraph,
     args: self::args::NetworkVirtualMachineArgs,
+    #[cfg(feature = "federation")]
+    federation_worker: Arc<Mutex<Option<::kubegraph_federation::NetworkFederationWorker>>>,
     graph_db: self::graph::NetworkGraphDB,
+    recorder: Option<NetworkRecorder>,
     resource_db: self::resource::NetworkResourceDB,
     resource_worker: Arc<Mutex<Option<self::resource::NetworkResourceWorker>>>,
     runner: self::runner::NetworkRunner,
@@ -47,6 +51,8 @@ impl NetworkComponent for NetworkVirtualMachine {
         // Step 1. Initialize components
         let self::args::NetworkArgs {
             dependency_graph,
+            #[cfg(feature = "federation")]
+            federation,
             graph_db,
             resource_db,
             runner,
@@ -56,12 +62,15 @@ impl NetworkComponent for NetworkVirtualMachine {
             vm,
         } = args;
         let vm = Self {
+            recorder: vm.record_dir.clone().map(NetworkRecorder::new),
             args: vm,
             dependency_graph: self::dependency::NetworkDependencyGraph::try_new(
                 dependency_graph,
                 signal,
             )
             .await?,
+            #[cfg(feature = "federation")]
+            federation_worker: Arc::new(Mutex::new(None)),
             graph_db: self::graph::NetworkGraphDB::try_new(graph_db, signal).await?,
             resource_db: self::resource::NetworkResourceDB::try_new(resource_db, signal).await?,
             resource_worker: Arc::new(Mutex::new(None)),
@@ -81,6 +90,14 @@ impl NetworkComponent for NetworkVirtualMachine {
             .lock()
             .await
             .replace(NetworkVirtualMachineRunner::spawn(signal, vm.clone()));
+        #[cfg(feature = "federation")]
+        vm.federation_worker.lock().await.replace(
+            ::kubegraph_federation::NetworkFederationWorker::spawn(
+                signal,
+                vm.graph_db.clone(),
+                federation,
+            ),
+        );
         Ok(vm)
     }
 }
@@ -125,6 +142,10 @@ impl ::kubegraph_api::vm::NetworkVirtualMachine for NetworkVirtualMachine {
         &self.visualizer
     }
 
+    fn recorder(&self) -> Option<&NetworkRecorder> {
+        self.recorder.as_ref()
+    }
+
     fn fallback_policy(&self) -> NetworkFallbackPolicy {
         self.args.fallback_policy
     }
@@ -135,6 +156,10 @@ impl ::kubegraph_api::vm::NetworkVirtualMachine for NetworkVirtualMachine {
 
     #[instrument(level = Level::INFO, skip(self))]
     async fn close_workers(&self) -> Result<()> {
+        #[cfg(feature = "federation")]
+        if let Some(worker) = self.federation_worker.lock().await.take() {
+            worker.abort();
+        }
         if let Some(worker) = self.resource_worker.lock().await.take() {
             worker.abort();
         }
@@ -145,6 +170,14 @@ impl ::kubegraph_api::vm::NetworkVirtualMachine for NetworkVirtualMachine {
     }
 }
 
+impl NetworkVirtualMachine {
+    /// Return the web visualizer, if enabled.
+    #[cfg(feature = "visualizer-web")]
+    pub const fn visualizer_web(&self) -> Option<&::kubegraph_visualizer_web::NetworkVisualizer> {
+        self.visualizer.as_web()
+    }
+}
+
 struct NetworkVirtualMachineRunner {
     inner: JoinHandle<()>,
 }
@@ -230,7 +263,9 @@ mod tests {
             },
             spec: NetworkConnectorSpec {
                 kind: NetworkConnectorKind::Unknown {},
+                poll_interval_secs: None,
             },
+            status: None,
         };
         let scope = GraphScope::from_resource(&connector);
         let graph = Graph {
@@ -364,7 +399,9 @@ mod tests {
             },
             spec: NetworkConnectorSpec {
                 kind: NetworkConnectorKind::Unknown {},
+                poll_interval_secs: None,
             },
+            status: None,
         };
         let scope = GraphScope::from_resource(&connector);
         let graph = Graph {
@@ -7,6 +7,7 @@ use clap::Parser;
 use futures::{stream::FuturesUnordered, StreamExt};
 use kube::Client;
 use kubegraph_api::{
+    alert::NetworkAlertRuleCrd,
     component::NetworkComponent,
     connector::{NetworkConnectorCrd, NetworkConnectorExt, NetworkConnectorType},
     function::NetworkFunctionCrd,
@@ -68,6 +69,24 @@ impl ::kubegraph_api::resource::NetworkResourceClient for NetworkResourceDB {
     }
 }
 
+#[async_trait]
+impl ::kubegraph_api::resource::NetworkResourceDB<NetworkAlertRuleCrd> for NetworkResourceDB {
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn delete(&self, key: &GraphScope) {
+        self.inner.lock().await.delete_alert_rule(key)
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn insert(&self, object: NetworkAlertRuleCrd) {
+        self.inner.lock().await.insert_alert_rule(object)
+    }
+
+    #[instrument(level = Level::INFO, skip(self))]
+    async fn list(&self, (): ()) -> Option<Vec<NetworkAlertRuleCrd>> {
+        Some(self.inner.lock().await.list_alert_rules())
+    }
+}
+
 #[async_trait]
 impl ::kubegraph_api::resource::NetworkResourceDB<NetworkConnectorCrd> for NetworkResourceDB {
     #[instrument(level = Level::INFO, skip(self))]
@@ -124,12 +143,29 @@ impl ::kubegraph_api::resource::NetworkResourceDB<NetworkProblemCrd> for Network
 
 #[derive(Default)]
 struct LocalResourceDB {
+    alert_rules: BTreeMap<GraphScope, NetworkAlertRuleCrd>,
     connectors: BTreeMap<GraphScope, NetworkConnectorCrd>,
     connectors_has_updated: BTreeMap<NetworkConnectorType, bool>,
     functions: BTreeMap<GraphScope, NetworkFunctionCrd>,
     problems: BTreeMap<GraphScope, NetworkProblemCrd>,
 }
 
+impl LocalResourceDB {
+    fn delete_alert_rule(&mut self, key: &GraphScope) {
+        self.alert_rules.remove(&key);
+    }
+
+    fn insert_alert_rule(&mut self, object: NetworkAlertRuleCrd) {
+        let key = GraphScope::from_resource(&object);
+
+        self.alert_rules.insert(key, object);
+    }
+
+    fn list_alert_rules(&self) -> Vec<NetworkAlertRuleCrd> {
+        self.alert_rules.values().cloned().collect()
+    }
+}
+
 impl LocalResourceDB {
     fn delete_connector(&mut self, key: &GraphScope) {
         let removed_object = self.connectors.remove(&key);
@@ -204,6 +240,7 @@ impl LocalResourceDB {
 }
 
 pub(crate) struct NetworkResourceWorker {
+    alert_rule_reloader: NetworkResourceReloader<NetworkAlertRuleCrd>,
     connector_db: NetworkConnectorDBWorker,
     connector_reloader: NetworkResourceReloader<NetworkConnectorCrd>,
     function_reloader: NetworkResourceReloader<NetworkFunctionCrd>,
@@ -216,6 +253,7 @@ impl NetworkResourceWorker {
         vm: &(impl 'static + Clone + NetworkVirtualMachine),
     ) -> Result<Self> {
         Ok(Self {
+            alert_rule_reloader: NetworkResourceReloader::spawn(signal.clone(), vm),
             connector_db: NetworkConnectorDBWorker::spawn(vm),
             connector_reloader: NetworkResourceReloader::spawn(signal.clone(), vm),
             function_reloader: NetworkResourceReloader::spawn(signal.clone(), vm),
@@ -224,6 +262,7 @@ impl NetworkResourceWorker {
     }
 
     pub(crate) fn abort(&self) {
+        self.alert_rule_reloader.abort();
         self.connector_db.abort();
         self.connector_reloader.abort();
         self.function_reloader.abort();
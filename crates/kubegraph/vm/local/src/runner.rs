@@ -1 +1,117 @@
-pub type NetworkRunner = ::kubegraph_runner::NetworkRunner;
+use anyhow::Result;
+use ark_core::signal::FunctionSignal;
+use async_trait::async_trait;
+use clap::{Parser, ValueEnum};
+use kubegraph_api::{
+    component::NetworkComponent, frame::LazyFrame, graph::NetworkGraphDB,
+    runner::NetworkRunnerContext,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{instrument, Level};
+
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    Parser,
+)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkRunnerArgs {
+    #[arg(
+        long,
+        env = "KUBEGRAPH_RUNNER",
+        value_enum,
+        value_name = "IMPL",
+        default_value_t = NetworkRunnerType::default(),
+    )]
+    #[serde(default)]
+    pub runner: NetworkRunnerType,
+
+    #[cfg(feature = "runner-kubernetes")]
+    #[command(flatten)]
+    #[serde(default)]
+    pub kubernetes: <::kubegraph_runner_kubernetes::NetworkRunner as NetworkComponent>::Args,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    ValueEnum,
+)]
+#[clap(rename_all = "kebab-case")]
+#[serde(rename_all = "kebab-case")]
+pub enum NetworkRunnerType {
+    #[default]
+    Simulator,
+    #[cfg(feature = "runner-kubernetes")]
+    Kubernetes,
+}
+
+#[derive(Clone)]
+pub enum NetworkRunner {
+    Simulator(::kubegraph_runner::NetworkRunner),
+    #[cfg(feature = "runner-kubernetes")]
+    Kubernetes(::kubegraph_runner_kubernetes::NetworkRunner),
+}
+
+#[async_trait]
+impl NetworkComponent for NetworkRunner {
+    type Args = NetworkRunnerArgs;
+
+    #[instrument(level = Level::INFO, skip(signal))]
+    async fn try_new(
+        args: <Self as NetworkComponent>::Args,
+        signal: &FunctionSignal,
+    ) -> Result<Self> {
+        let NetworkRunnerArgs {
+            runner,
+            #[cfg(feature = "runner-kubernetes")]
+            kubernetes,
+        } = args;
+
+        match runner {
+            NetworkRunnerType::Simulator => Ok(Self::Simulator(
+                ::kubegraph_runner::NetworkRunner::try_new(Default::default(), signal).await?,
+            )),
+            #[cfg(feature = "runner-kubernetes")]
+            NetworkRunnerType::Kubernetes => Ok(Self::Kubernetes(
+                ::kubegraph_runner_kubernetes::NetworkRunner::try_new(kubernetes, signal).await?,
+            )),
+        }
+    }
+}
+
+#[async_trait]
+impl<DB> ::kubegraph_api::runner::NetworkRunner<DB, LazyFrame> for NetworkRunner
+where
+    DB: NetworkGraphDB,
+{
+    #[instrument(level = Level::INFO, skip(self, ctx))]
+    async fn execute<'a>(&self, ctx: NetworkRunnerContext<'a, DB, LazyFrame>) -> Result<()> {
+        match self {
+            Self::Simulator(runtime) => runtime.execute(ctx).await,
+            #[cfg(feature = "runner-kubernetes")]
+            Self::Kubernetes(runtime) => runtime.execute(ctx).await,
+        }
+    }
+}
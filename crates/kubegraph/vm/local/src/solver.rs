@@ -2,10 +2,12 @@ use anyhow::Result;
 use ark_core::signal::FunctionSignal;
 use async_trait::async_trait;
 use clap::{Parser, ValueEnum};
+use futures::future::try_join_all;
 use kubegraph_api::{
     component::NetworkComponent,
     frame::LazyFrame,
     graph::{GraphData, GraphMetadataPinned},
+    partition::{NetworkPartitionCrossEdges, NetworkPartitioner},
     problem::ProblemSpec,
 };
 use schemars::JsonSchema;
@@ -13,7 +15,6 @@ use serde::{Deserialize, Serialize};
 use tracing::{instrument, Level};
 
 #[derive(
-    Copy,
     Clone,
     Debug,
     Default,
@@ -30,6 +31,21 @@ use tracing::{instrument, Level};
 #[clap(rename_all = "kebab-case")]
 #[serde(rename_all = "camelCase")]
 pub struct NetworkSolverArgs {
+    /// The node column to split the graph into the sub-problems by, e.g. `namespace` or `zone`
+    #[arg(long, env = "KUBEGRAPH_PARTITION_KEY", value_name = "COLUMN")]
+    #[serde(default)]
+    pub partition_key: Option<String>,
+
+    #[arg(
+        long,
+        env = "KUBEGRAPH_PARTITION_CROSS_EDGES",
+        value_enum,
+        value_name = "POLICY",
+        default_value_t = NetworkPartitionCrossEdges::default(),
+    )]
+    #[serde(default)]
+    pub partition_cross_edges: NetworkPartitionCrossEdges,
+
     #[arg(
         long,
         env = "KUBEGRAPH_SOLVER",
@@ -72,7 +88,13 @@ pub enum NetworkSolverType {
 }
 
 #[derive(Clone)]
-pub enum NetworkSolver {
+pub struct NetworkSolver {
+    backend: NetworkSolverBackend,
+    partitioner: Option<NetworkPartitioner>,
+}
+
+#[derive(Clone)]
+enum NetworkSolverBackend {
     Disabled,
     #[cfg(feature = "solver-ortools")]
     Ortools(::kubegraph_solver_ortools::NetworkSolver),
@@ -88,21 +110,31 @@ impl NetworkComponent for NetworkSolver {
         signal: &FunctionSignal,
     ) -> Result<Self> {
         let NetworkSolverArgs {
+            partition_key,
+            partition_cross_edges,
             solver,
             #[cfg(feature = "solver-ortools")]
             ortools,
         } = args;
 
-        match solver {
+        let backend = match solver {
             NetworkSolverType::Disabled => {
                 let _ = signal;
-                Ok(Self::Disabled)
+                NetworkSolverBackend::Disabled
             }
             #[cfg(feature = "solver-ortools")]
-            NetworkSolverType::Ortools => Ok(Self::Ortools(
+            NetworkSolverType::Ortools => NetworkSolverBackend::Ortools(
                 ::kubegraph_solver_ortools::NetworkSolver::try_new(ortools, signal).await?,
-            )),
-        }
+            ),
+        };
+
+        Ok(Self {
+            backend,
+            partitioner: partition_key.map(|key| NetworkPartitioner {
+                key,
+                cross_edges: partition_cross_edges,
+            }),
+        })
     }
 }
 
@@ -116,13 +148,42 @@ impl ::kubegraph_api::solver::NetworkSolver<GraphData<LazyFrame>> for NetworkSol
         graph: GraphData<LazyFrame>,
         problem: &ProblemSpec<GraphMetadataPinned>,
     ) -> Result<Self::Output> {
+        let partitioner = match &self.partitioner {
+            Some(partitioner) if !matches!(self.backend, NetworkSolverBackend::Disabled) => {
+                partitioner
+            }
+            _ => return self.backend.solve(graph, problem).await,
+        };
+
+        // solve the weakly-connected sub-problems in parallel
+        let graphs = partitioner.split(graph, &problem.metadata)?;
+        let graphs = try_join_all(
+            graphs
+                .into_iter()
+                .map(|graph| self.backend.solve(graph, problem)),
+        )
+        .await?;
+        partitioner.stitch(graphs)
+    }
+}
+
+impl NetworkSolverBackend {
+    async fn solve(
+        &self,
+        graph: GraphData<LazyFrame>,
+        problem: &ProblemSpec<GraphMetadataPinned>,
+    ) -> Result<GraphData<LazyFrame>> {
         match self {
             Self::Disabled => {
                 let _ = problem;
                 Ok(graph)
             }
             #[cfg(feature = "solver-ortools")]
-            Self::Ortools(runtime) => runtime.solve(graph, problem).await,
+            Self::Ortools(runtime) => {
+                use kubegraph_api::solver::NetworkSolver;
+
+                runtime.solve(graph, problem).await
+            }
         }
     }
 }
@@ -44,6 +44,11 @@ pub struct NetworkVisualizerArgs {
     #[command(flatten)]
     #[serde(default)]
     pub egui: <::kubegraph_visualizer_egui::NetworkVisualizer as NetworkComponent>::Args,
+
+    #[cfg(feature = "visualizer-web")]
+    #[command(flatten)]
+    #[serde(default)]
+    pub web: <::kubegraph_visualizer_web::NetworkVisualizer as NetworkComponent>::Args,
 }
 
 #[derive(
@@ -69,6 +74,8 @@ pub enum NetworkVisualizerType {
     #[cfg(feature = "visualizer-egui")]
     #[default]
     Egui,
+    #[cfg(feature = "visualizer-web")]
+    Web,
 }
 
 #[derive(Clone)]
@@ -76,6 +83,8 @@ pub enum NetworkVisualizer {
     Disabled,
     #[cfg(feature = "visualizer-egui")]
     Egui(::kubegraph_visualizer_egui::NetworkVisualizer),
+    #[cfg(feature = "visualizer-web")]
+    Web(::kubegraph_visualizer_web::NetworkVisualizer),
 }
 
 #[async_trait]
@@ -91,6 +100,8 @@ impl NetworkComponent for NetworkVisualizer {
             visualizer,
             #[cfg(feature = "visualizer-egui")]
             egui,
+            #[cfg(feature = "visualizer-web")]
+            web,
         } = args;
 
         match visualizer {
@@ -102,6 +113,10 @@ impl NetworkComponent for NetworkVisualizer {
             NetworkVisualizerType::Egui => Ok(Self::Egui(
                 ::kubegraph_visualizer_egui::NetworkVisualizer::try_new(egui, signal).await?,
             )),
+            #[cfg(feature = "visualizer-web")]
+            NetworkVisualizerType::Web => Ok(Self::Web(
+                ::kubegraph_visualizer_web::NetworkVisualizer::try_new(web, signal).await?,
+            )),
         }
     }
 }
@@ -120,6 +135,8 @@ impl ::kubegraph_api::visualizer::NetworkVisualizer for NetworkVisualizer {
             }
             #[cfg(feature = "visualizer-egui")]
             Self::Egui(runtime) => runtime.replace_graph(graph).await,
+            #[cfg(feature = "visualizer-web")]
+            Self::Web(runtime) => runtime.replace_graph(graph).await,
         }
     }
 
@@ -132,6 +149,8 @@ impl ::kubegraph_api::visualizer::NetworkVisualizer for NetworkVisualizer {
             }
             #[cfg(feature = "visualizer-egui")]
             Self::Egui(runtime) => runtime.call(event).await,
+            #[cfg(feature = "visualizer-web")]
+            Self::Web(runtime) => runtime.call(event).await,
         }
     }
 
@@ -141,6 +160,18 @@ impl ::kubegraph_api::visualizer::NetworkVisualizer for NetworkVisualizer {
             Self::Disabled => Ok(()),
             #[cfg(feature = "visualizer-egui")]
             Self::Egui(runtime) => runtime.close().await,
+            #[cfg(feature = "visualizer-web")]
+            Self::Web(runtime) => runtime.close().await,
+        }
+    }
+}
+
+impl NetworkVisualizer {
+    #[cfg(feature = "visualizer-web")]
+    pub(crate) const fn as_web(&self) -> Option<&::kubegraph_visualizer_web::NetworkVisualizer> {
+        match self {
+            Self::Web(runtime) => Some(runtime),
+            _ => None,
         }
     }
 }
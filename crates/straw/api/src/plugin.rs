@@ -616,6 +616,12 @@ context_env_fields! {
         pub storage_name: String,
 
         optional: {
+            #[
+                env("PIPE_DLQ_MAX_ATTEMPTS"),
+                default(None),
+            ]
+            pub dlq_max_attempts: Option<u32>,
+
             #[
                 env("PIPE_ENCODER"),
                 default(None),
@@ -1,10 +1,12 @@
 pub mod display;
+pub mod usage_report;
 pub mod user;
 pub mod user_auth;
 pub mod user_auth_binding;
 pub mod user_box_binding;
 pub mod user_box_quota;
 pub mod user_box_quota_binding;
+pub mod user_quota;
 pub mod user_role;
 pub mod user_role_binding;
 pub mod user_session;
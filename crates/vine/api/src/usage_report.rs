@@ -0,0 +1,222 @@
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, Utc};
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema, CustomResource)]
+#[kube(
+    group = "vine.ulagbulag.io",
+    version = "v1alpha1",
+    kind = "UsageReport",
+    root = "UsageReportCrd",
+    status = "UsageReportStatus",
+    shortname = "ur",
+    printcolumn = r#"{
+        "name": "user",
+        "type": "string",
+        "description": "user name",
+        "jsonPath": ".spec.userName"
+    }"#,
+    printcolumn = r#"{
+        "name": "period",
+        "type": "string",
+        "description": "aggregation period",
+        "jsonPath": ".spec.period"
+    }"#,
+    printcolumn = r#"{
+        "name": "begin",
+        "type": "date",
+        "description": "beginning of the period",
+        "jsonPath": ".spec.begin"
+    }"#,
+    printcolumn = r#"{
+        "name": "session-seconds",
+        "type": "integer",
+        "description": "total duration of the sessions",
+        "jsonPath": ".status.total.sessionSeconds"
+    }"#,
+    printcolumn = r#"{
+        "name": "gpu-seconds",
+        "type": "integer",
+        "description": "total reserved GPU time",
+        "jsonPath": ".status.total.gpuSeconds"
+    }"#,
+    printcolumn = r#"{
+        "name": "updated-at",
+        "type": "date",
+        "description": "updated time",
+        "jsonPath": ".status.lastUpdated"
+    }"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReportSpec {
+    pub user_name: String,
+    pub period: UsageReportPeriod,
+    /// The beginning of the period (inclusive).
+    pub begin: DateTime<Utc>,
+    /// The end of the period (exclusive).
+    pub end: DateTime<Utc>,
+}
+
+impl UsageReportCrd {
+    /// The label whether the report has the sessions which are not finished yet.
+    pub const LABEL_ACTIVE: &'static str = "vine.ulagbulag.io/usage.active";
+    /// The label of the month of the report, e.g. `2024-01`.
+    pub const LABEL_MONTH: &'static str = "vine.ulagbulag.io/usage.month";
+    pub const LABEL_PERIOD: &'static str = "vine.ulagbulag.io/usage.period";
+    pub const LABEL_USER_NAME: &'static str = "vine.ulagbulag.io/usage.user";
+
+    pub fn name_with(user_name: &str, period: UsageReportPeriod, begin: DateTime<Utc>) -> String {
+        match period {
+            UsageReportPeriod::Daily => format!("{user_name}-daily-{}", begin.format("%Y%m%d")),
+            UsageReportPeriod::Monthly => format!("{user_name}-monthly-{}", begin.format("%Y%m")),
+        }
+    }
+
+    pub fn month_with(begin: DateTime<Utc>) -> String {
+        begin.format("%Y-%m").to_string()
+    }
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum UsageReportPeriod {
+    Daily,
+    Monthly,
+}
+
+impl UsageReportPeriod {
+    /// Return the period `[begin, end)` which contains the given timestamp.
+    pub fn range(&self, timestamp: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+        let date = timestamp.date_naive();
+        let begin = match self {
+            Self::Daily => date,
+            Self::Monthly => NaiveDate::from_ymd_opt(date.year(), date.month(), 1).unwrap(),
+        };
+        let end = match self {
+            Self::Daily => begin + Duration::days(1),
+            Self::Monthly => begin + Months::new(1),
+        };
+        (
+            begin.and_hms_opt(0, 0, 0).unwrap().and_utc(),
+            end.and_hms_opt(0, 0, 0).unwrap().and_utc(),
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReportStatus {
+    /// The sessions within the period, which are recorded only in the daily reports.
+    #[serde(default)]
+    pub sessions: Vec<UsageSessionRecord>,
+    #[serde(default)]
+    pub total: UsageSummary,
+    pub last_updated: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageSessionRecord {
+    pub node_name: String,
+    /// The time when the session was started, which identifies the session with the node name.
+    pub started_at: DateTime<Utc>,
+    /// The beginning of the session within the period.
+    pub begin: DateTime<Utc>,
+    /// The end of the session within the period, or the last seen time if it is still active.
+    pub end: DateTime<Utc>,
+    #[serde(default)]
+    pub active: bool,
+    /// The reserved CPU of the node, in millicores.
+    #[serde(default)]
+    pub cpu_millicores: u64,
+    /// The reserved GPUs of the node.
+    #[serde(default)]
+    pub gpus: u64,
+}
+
+impl UsageSessionRecord {
+    pub fn duration_seconds(&self) -> u64 {
+        (self.end - self.begin).num_seconds().max(0) as u64
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageSummary {
+    /// The number of the sessions, which are counted in each day they span.
+    pub sessions: u64,
+    pub session_seconds: u64,
+    pub cpu_core_seconds: u64,
+    pub gpu_seconds: u64,
+}
+
+impl UsageSummary {
+    pub fn from_sessions(sessions: &[UsageSessionRecord]) -> Self {
+        sessions.iter().fold(Self::default(), |total, session| {
+            let seconds = session.duration_seconds();
+            Self {
+                sessions: total.sessions + 1,
+                session_seconds: total.session_seconds + seconds,
+                cpu_core_seconds: total.cpu_core_seconds + session.cpu_millicores * seconds / 1000,
+                gpu_seconds: total.gpu_seconds + session.gpus * seconds,
+            }
+        })
+    }
+}
+
+impl ::core::ops::Add for UsageSummary {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            sessions: self.sessions + rhs.sessions,
+            session_seconds: self.session_seconds + rhs.session_seconds,
+            cpu_core_seconds: self.cpu_core_seconds + rhs.cpu_core_seconds,
+            gpu_seconds: self.gpu_seconds + rhs.gpu_seconds,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReportQuery {
+    #[serde(default)]
+    pub period: Option<UsageReportPeriod>,
+    /// The month of the reports, e.g. `2024-01`.
+    #[serde(default)]
+    pub month: Option<String>,
+    /// The user of the reports, which is allowed only for the administrators.
+    #[serde(default)]
+    pub user_name: Option<String>,
+}
+
+impl UsageReportQuery {
+    pub fn to_label_selector(&self, user_name: Option<&str>) -> String {
+        let mut selectors = vec![];
+        if let Some(period) = self.period {
+            selectors.push(format!("{}={period}", UsageReportCrd::LABEL_PERIOD));
+        }
+        if let Some(month) = self.month.as_deref() {
+            selectors.push(format!("{}={month}", UsageReportCrd::LABEL_MONTH));
+        }
+        if let Some(user_name) = user_name.or(self.user_name.as_deref()) {
+            selectors.push(format!("{}={user_name}", UsageReportCrd::LABEL_USER_NAME));
+        }
+        selectors.join(",")
+    }
+}
@@ -0,0 +1,139 @@
+use chrono::{DateTime, Utc};
+use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+/// The quota of the user, which is named after the user.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, CustomResource)]
+#[kube(
+    group = "vine.ulagbulag.io",
+    version = "v1alpha1",
+    kind = "UserQuota",
+    root = "UserQuotaCrd",
+    status = "UserQuotaStatus",
+    shortname = "uq",
+    printcolumn = r#"{
+        "name": "state",
+        "type": "string",
+        "description": "state of the quota",
+        "jsonPath": ".status.state"
+    }"#,
+    printcolumn = r#"{
+        "name": "limit",
+        "type": "string",
+        "description": "limit of the home volume",
+        "jsonPath": ".spec.home.limit"
+    }"#,
+    printcolumn = r#"{
+        "name": "used",
+        "type": "integer",
+        "description": "used bytes of the home volume",
+        "jsonPath": ".status.home.used"
+    }"#,
+    printcolumn = r#"{
+        "name": "updated-at",
+        "type": "date",
+        "description": "updated time",
+        "jsonPath": ".status.lastUpdated"
+    }"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct UserQuotaSpec {
+    pub home: UserQuotaHomeSpec,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UserQuotaHomeSpec {
+    /// The maximum size of the home volume, e.g. `100Gi`.
+    pub limit: Quantity,
+    /// The usage ratios (in percent) to warn the user of.
+    #[serde(default = "UserQuotaHomeSpec::default_warning_thresholds")]
+    pub warning_thresholds: Vec<u8>,
+    #[serde(default)]
+    pub storage: UserQuotaStorageSpec,
+}
+
+impl UserQuotaHomeSpec {
+    fn default_warning_thresholds() -> Vec<u8> {
+        vec![80, 95]
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum UserQuotaStorageSpec {
+    /// The `RemoteOwned` home volume claim, which is resized up to the limit.
+    Pvc {},
+    /// The dash model storing the home, whose bucket quota is set to the limit.
+    Dash { model: String },
+}
+
+impl Default for UserQuotaStorageSpec {
+    fn default() -> Self {
+        Self::Pvc {}
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UserQuotaStatus {
+    #[serde(default)]
+    pub state: UserQuotaState,
+    #[serde(default)]
+    pub home: UserQuotaUsage,
+    /// The error message, if the usage cannot be measured or the limit cannot be applied.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub last_updated: Option<DateTime<Utc>>,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UserQuotaUsage {
+    /// The limit in bytes.
+    #[serde(default)]
+    pub limit: Option<u64>,
+    /// The measured size in bytes.
+    #[serde(default)]
+    pub used: Option<u64>,
+    /// The last threshold (in percent) which the user has been warned of.
+    #[serde(default)]
+    pub warned_threshold: Option<u8>,
+}
+
+impl UserQuotaUsage {
+    /// Return the usage in percent, if measured.
+    pub fn percent(&self) -> Option<u64> {
+        match (self.limit, self.used) {
+            (Some(limit), Some(used)) if limit > 0 => Some(used.saturating_mul(100) / limit),
+            _ => None,
+        }
+    }
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    Default,
+    EnumString,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum UserQuotaState {
+    #[default]
+    Pending,
+    Ok,
+    Warning,
+    Exceeded,
+}
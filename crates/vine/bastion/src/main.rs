@@ -43,6 +43,7 @@ async fn main() {
             let app = app
                 .service(health)
                 .service(crate::routes::auth::get)
+                .service(crate::routes::r#box::login::get_any)
                 .service(crate::routes::r#box::login::get)
                 .service(crate::routes::install_os::get)
                 .service(crate::routes::reserved::get)
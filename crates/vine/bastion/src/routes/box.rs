@@ -53,6 +53,38 @@ pub mod login {
         }
     }
 
+    #[instrument(level = Level::INFO, skip(request, client, tera))]
+    #[get("/box/login")]
+    pub async fn get_any(
+        request: HttpRequest,
+        client: Data<Client>,
+        tera: Data<Tera>,
+    ) -> impl Responder {
+        match match ::vine_rbac::auth::get_user_name(&request) {
+            Ok(user_name) => {
+                const LOGOUT_ON_FAILED: bool = false;
+                ::vine_rbac::login::execute_any(&client, &user_name, LOGOUT_ON_FAILED).await
+            }
+            Err(response) => Ok(response.into()),
+        } {
+            Ok(UserSessionResponse::Accept { .. }) => Redirect::to("../")
+                .temporary()
+                .respond_to(&request)
+                .map_into_boxed_body(),
+            Ok(UserSessionResponse::Error(error)) => {
+                warn!("denied to login: {error}");
+                create_error_html(tera, error)
+            }
+            Err(error) => {
+                error!("failed to login: {error}");
+                create_error_html(
+                    tera,
+                    "Internal server error. Please contact the administrator.",
+                )
+            }
+        }
+    }
+
     fn create_error_html(tera: Data<Tera>, error: impl ToString) -> HttpResponse {
         #[derive(Serialize)]
         struct Value {
@@ -1,3 +1,5 @@
+pub mod usage_report;
 pub mod user_auth;
 pub mod user_box_binding;
+pub mod user_quota;
 pub mod user_session;
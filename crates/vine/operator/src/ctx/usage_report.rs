@@ -0,0 +1,294 @@
+use std::{collections::BTreeSet, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use ark_api::NamespaceAny;
+use ark_core_k8s::manager::Manager;
+use async_trait::async_trait;
+use k8s_openapi::{
+    api::core::v1::Node,
+    apimachinery::pkg::api::resource::Quantity,
+    chrono::{DateTime, Utc},
+    serde_json::json,
+};
+use kube::{
+    api::{ListParams, Patch, PatchParams},
+    runtime::controller::Action,
+    Api, Client, CustomResourceExt, Error, ResourceExt,
+};
+use tracing::{instrument, warn, Level};
+use vine_api::usage_report::{
+    UsageReportCrd, UsageReportPeriod, UsageReportSpec, UsageReportStatus, UsageSessionRecord,
+    UsageSummary,
+};
+
+#[derive(Default)]
+pub struct Ctx {}
+
+#[async_trait]
+impl ::ark_core_k8s::manager::Ctx for Ctx {
+    type Data = Node;
+
+    const NAME: &'static str = crate::consts::NAME;
+    const NAMESPACE: &'static str = ::vine_api::consts::NAMESPACE;
+    // NOTE: the session usage is accounted with this resolution
+    const FALLBACK: Duration = Duration::from_secs(60); // 1 minute
+
+    #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any()), err(Display))]
+    async fn reconcile(
+        manager: Arc<Manager<Self>>,
+        data: Arc<<Self as ::ark_core_k8s::manager::Ctx>::Data>,
+    ) -> Result<Action, Error>
+    where
+        Self: Sized,
+    {
+        if let Err(e) = Self::account(&manager.kube, &data, Utc::now()).await {
+            let name = data.name_any();
+            warn!("failed to account the session usage: {name:?}: {e}");
+        }
+
+        // If no events were received, check back after a few minutes
+        Ok(Action::requeue(
+            <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+        ))
+    }
+}
+
+impl Ctx {
+    /// Record the session of the node into the daily report, and aggregate the monthly report.
+    ///
+    /// NOTE: the sessions are recorded only in the days when the operator has been running.
+    #[instrument(level = Level::INFO, skip_all, fields(name = %node.name_any()), err(Display))]
+    async fn account(kube: &Client, node: &Node, now: DateTime<Utc>) -> Result<()> {
+        let api = Api::<UsageReportCrd>::all(kube.clone());
+        let node_name = node.name_any();
+        let session = node
+            .get_session_ref()
+            .ok()
+            .filter(|session| session.is_started().unwrap_or_default())
+            .and_then(|session| Some((session.user_name.into_owned(), session.timestamp?)));
+
+        let mut updated = BTreeSet::default();
+
+        // close the records which are finished or passed the day
+        let lp = ListParams::default().labels(&format!("{}=true", UsageReportCrd::LABEL_ACTIVE));
+        for report in api.list(&lp).await?.items {
+            let mut sessions = report
+                .status
+                .as_ref()
+                .map(|status| status.sessions.clone())
+                .unwrap_or_default();
+
+            let mut changed = false;
+            for record in sessions
+                .iter_mut()
+                .filter(|record| record.active && record.node_name == node_name)
+            {
+                let is_same_session = session.as_ref().is_some_and(|(user_name, started_at)| {
+                    user_name == &report.spec.user_name && started_at == &record.started_at
+                });
+                if is_same_session && now < report.spec.end {
+                    continue;
+                }
+
+                // NOTE: the finished sessions keep their last seen time
+                if is_same_session {
+                    record.end = report.spec.end;
+                }
+                record.active = false;
+                changed = true;
+            }
+
+            if changed {
+                Self::update_daily_report(&api, &report, sessions, now).await?;
+                updated.insert((report.spec.user_name.clone(), report.spec.begin));
+            }
+        }
+
+        // record the current session
+        if let Some((user_name, started_at)) = session {
+            let (cpu_millicores, gpus) = get_reserved_resources(node);
+            let report =
+                Self::get_or_create_report(&api, &user_name, UsageReportPeriod::Daily, now).await?;
+            let record = UsageSessionRecord {
+                node_name,
+                started_at,
+                begin: started_at.max(report.spec.begin),
+                end: now,
+                active: true,
+                cpu_millicores,
+                gpus,
+            };
+
+            let mut sessions = report
+                .status
+                .as_ref()
+                .map(|status| status.sessions.clone())
+                .unwrap_or_default();
+            match sessions.iter_mut().find(|last| {
+                last.node_name == record.node_name && last.started_at == record.started_at
+            }) {
+                Some(last) => *last = record,
+                None => sessions.push(record),
+            }
+
+            Self::update_daily_report(&api, &report, sessions, now).await?;
+            updated.insert((user_name, report.spec.begin));
+        }
+
+        // aggregate the daily reports
+        for (user_name, timestamp) in updated {
+            Self::update_monthly_report(&api, &user_name, timestamp, now).await?;
+        }
+        Ok(())
+    }
+
+    #[instrument(level = Level::INFO, skip(api, now), err(Display))]
+    async fn get_or_create_report(
+        api: &Api<UsageReportCrd>,
+        user_name: &str,
+        period: UsageReportPeriod,
+        now: DateTime<Utc>,
+    ) -> Result<UsageReportCrd, Error> {
+        let (begin, end) = period.range(now);
+        let name = UsageReportCrd::name_with(user_name, period, begin);
+        if let Some(report) = api.get_opt(&name).await? {
+            return Ok(report);
+        }
+
+        let crd = UsageReportCrd::api_resource();
+        let patch = Patch::Apply(json!({
+            "apiVersion": crd.api_version,
+            "kind": crd.kind,
+            "metadata": {
+                "name": &name,
+                "labels": {
+                    UsageReportCrd::LABEL_ACTIVE: "false",
+                    UsageReportCrd::LABEL_MONTH: UsageReportCrd::month_with(begin),
+                    UsageReportCrd::LABEL_PERIOD: period.to_string(),
+                    UsageReportCrd::LABEL_USER_NAME: user_name,
+                },
+            },
+            "spec": UsageReportSpec {
+                user_name: user_name.into(),
+                period,
+                begin,
+                end,
+            },
+        }));
+        let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME).force();
+        api.patch(&name, &pp, &patch).await
+    }
+
+    #[instrument(level = Level::INFO, skip_all, fields(name = %report.name_any()), err(Display))]
+    async fn update_daily_report(
+        api: &Api<UsageReportCrd>,
+        report: &UsageReportCrd,
+        sessions: Vec<UsageSessionRecord>,
+        now: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        let name = report.name_any();
+        let active = sessions.iter().any(|record| record.active).to_string();
+
+        // NOTE: the resource version is given, as the sessions of a user are recorded concurrently
+        let crd = UsageReportCrd::api_resource();
+        let patch = Patch::Merge(json!({
+            "apiVersion": crd.api_version,
+            "kind": crd.kind,
+            "metadata": {
+                "resourceVersion": report.resource_version(),
+            },
+            "status": UsageReportStatus {
+                total: UsageSummary::from_sessions(&sessions),
+                sessions,
+                last_updated: Some(now),
+            },
+        }));
+        let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME);
+        api.patch_status(&name, &pp, &patch).await?;
+
+        if report.labels().get(UsageReportCrd::LABEL_ACTIVE) != Some(&active) {
+            let patch = Patch::Merge(json!({
+                "metadata": {
+                    "labels": {
+                        UsageReportCrd::LABEL_ACTIVE: active,
+                    },
+                },
+            }));
+            api.patch(&name, &pp, &patch).await?;
+        }
+        Ok(())
+    }
+
+    #[instrument(level = Level::INFO, skip(api, now), err(Display))]
+    async fn update_monthly_report(
+        api: &Api<UsageReportCrd>,
+        user_name: &str,
+        timestamp: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        let report =
+            Self::get_or_create_report(api, user_name, UsageReportPeriod::Monthly, timestamp)
+                .await?;
+
+        let lp = ListParams::default().labels(&format!(
+            "{}={},{}={},{}={user_name}",
+            UsageReportCrd::LABEL_MONTH,
+            UsageReportCrd::month_with(report.spec.begin),
+            UsageReportCrd::LABEL_PERIOD,
+            UsageReportPeriod::Daily,
+            UsageReportCrd::LABEL_USER_NAME,
+        ));
+        let total = api
+            .list(&lp)
+            .await?
+            .items
+            .into_iter()
+            .filter_map(|report| report.status)
+            .fold(UsageSummary::default(), |total, status| {
+                total + status.total
+            });
+
+        let crd = UsageReportCrd::api_resource();
+        let patch = Patch::Merge(json!({
+            "apiVersion": crd.api_version,
+            "kind": crd.kind,
+            "status": UsageReportStatus {
+                sessions: Vec::default(),
+                total,
+                last_updated: Some(now),
+            },
+        }));
+        let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME);
+        api.patch_status(&report.name_any(), &pp, &patch).await?;
+        Ok(())
+    }
+}
+
+/// Return the reserved CPU (in millicores) and GPUs of the node, as a session occupies the whole node.
+fn get_reserved_resources(node: &Node) -> (u64, u64) {
+    let allocatable = node
+        .status
+        .as_ref()
+        .and_then(|status| status.allocatable.as_ref());
+
+    let cpu_millicores = allocatable
+        .and_then(|allocatable| allocatable.get("cpu"))
+        .and_then(parse_millicores)
+        .unwrap_or_default();
+    let gpus = allocatable
+        .and_then(|allocatable| allocatable.get("nvidia.com/gpu"))
+        .and_then(|quantity| quantity.0.parse().ok())
+        .unwrap_or_default();
+    (cpu_millicores, gpus)
+}
+
+fn parse_millicores(quantity: &Quantity) -> Option<u64> {
+    match quantity.0.strip_suffix('m') {
+        Some(millicores) => millicores.parse().ok(),
+        None => quantity
+            .0
+            .parse::<f64>()
+            .ok()
+            .map(|cores| (cores * 1000.0) as u64),
+    }
+}
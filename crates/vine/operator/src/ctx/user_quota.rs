@@ -0,0 +1,342 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::{anyhow, bail, Result};
+use ark_core_k8s::manager::Manager;
+use async_trait::async_trait;
+use byte_unit::Byte;
+use dash_api::{model_storage_binding::ModelStorageBindingCrd, storage::ModelStorageCrd};
+use k8s_openapi::{
+    api::core::v1::{Node, PersistentVolumeClaim},
+    apimachinery::pkg::api::resource::Quantity,
+    chrono::Utc,
+    serde_json::{json, Value},
+};
+use kube::{
+    api::{GetParams, ListParams, Patch, PatchParams},
+    core::Request,
+    runtime::controller::Action,
+    Api, Client, CustomResourceExt, Error, ResourceExt,
+};
+use tracing::{info, instrument, warn, Level};
+use vine_api::{
+    user::UserCrd,
+    user_quota::{
+        UserQuotaCrd, UserQuotaHomeSpec, UserQuotaState, UserQuotaStatus, UserQuotaStorageSpec,
+        UserQuotaUsage,
+    },
+};
+use vine_session::{
+    batch::{BatchCommandArgs, BatchCommandUsers},
+    snapshot::HOME_PVC_NAME,
+};
+
+#[derive(Default)]
+pub struct Ctx {}
+
+#[async_trait]
+impl ::ark_core_k8s::manager::Ctx for Ctx {
+    type Data = UserQuotaCrd;
+
+    const NAME: &'static str = crate::consts::NAME;
+    const NAMESPACE: &'static str = ::vine_api::consts::NAMESPACE;
+    const FALLBACK: Duration = Duration::from_secs(300); // 5 minutes
+
+    #[instrument(level = Level::INFO, skip_all, fields(name = %data.name_any()), err(Display))]
+    async fn reconcile(
+        manager: Arc<Manager<Self>>,
+        data: Arc<<Self as ::ark_core_k8s::manager::Ctx>::Data>,
+    ) -> Result<Action, Error>
+    where
+        Self: Sized,
+    {
+        let user_name = data.name_any();
+        let last = data.status.clone().unwrap_or_default();
+
+        let status = match apply_home(&manager.kube, &user_name, &data.spec.home).await {
+            Ok((limit, used)) => {
+                let home = UserQuotaUsage {
+                    limit: Some(limit),
+                    // NOTE: the usage is kept while the user is not logged in
+                    used: used.or(last.home.used),
+                    warned_threshold: last.home.warned_threshold,
+                };
+                let home = warn_user(&manager.kube, &user_name, &data.spec.home, home).await;
+                UserQuotaStatus {
+                    state: get_state(&data.spec.home, &home),
+                    home,
+                    message: None,
+                    last_updated: Some(Utc::now()),
+                }
+            }
+            Err(error) => {
+                warn!("failed to apply the home quota: {user_name:?}: {error}");
+                UserQuotaStatus {
+                    message: Some(error.to_string()),
+                    last_updated: Some(Utc::now()),
+                    ..last
+                }
+            }
+        };
+
+        let api = Api::<UserQuotaCrd>::all(manager.kube.clone());
+        let crd = UserQuotaCrd::api_resource();
+        let patch = Patch::Merge(json!({
+            "apiVersion": crd.api_version,
+            "kind": crd.kind,
+            "status": status,
+        }));
+        let pp = PatchParams::apply(<Self as ::ark_core_k8s::manager::Ctx>::NAME);
+        api.patch_status(&user_name, &pp, &patch).await?;
+
+        // If no events were received, check back after a few minutes
+        Ok(Action::requeue(
+            <Self as ::ark_core_k8s::manager::Ctx>::FALLBACK,
+        ))
+    }
+}
+
+/// Apply the limit to the underlying storage, and return the limit and the measured usage in bytes.
+#[instrument(level = Level::INFO, skip(kube, spec), err(Display))]
+async fn apply_home(
+    kube: &Client,
+    user_name: &str,
+    spec: &UserQuotaHomeSpec,
+) -> Result<(u64, Option<u64>)> {
+    let limit = parse_bytes(&spec.limit)?;
+    let namespace = UserCrd::user_namespace_with(user_name);
+
+    let used = match &spec.storage {
+        UserQuotaStorageSpec::Pvc {} => {
+            apply_home_pvc(kube, &namespace, &spec.limit, limit).await?;
+            get_home_pvc_usage(kube, user_name, &namespace).await?
+        }
+        UserQuotaStorageSpec::Dash { model } => {
+            apply_home_dash(kube, &namespace, model, &spec.limit).await?
+        }
+    };
+    Ok((limit, used))
+}
+
+#[instrument(level = Level::INFO, skip(kube, quantity), err(Display))]
+async fn apply_home_pvc(
+    kube: &Client,
+    namespace: &str,
+    quantity: &Quantity,
+    limit: u64,
+) -> Result<()> {
+    let api = Api::<PersistentVolumeClaim>::namespaced(kube.clone(), namespace);
+    let pvc = match api.get_opt(HOME_PVC_NAME).await? {
+        Some(pvc) => pvc,
+        // NOTE: the home volume is created on the first login
+        None => return Ok(()),
+    };
+
+    let requested = pvc
+        .spec
+        .as_ref()
+        .and_then(|spec| spec.resources.as_ref())
+        .and_then(|resources| resources.requests.as_ref())
+        .and_then(|requests| requests.get("storage"))
+        .map(parse_bytes)
+        .transpose()?;
+
+    match requested {
+        Some(requested) if requested < limit => {
+            info!("resizing the home volume: {namespace}/{HOME_PVC_NAME} => {quantity:?}");
+            let patch = Patch::Merge(json!({
+                "spec": {
+                    "resources": {
+                        "requests": {
+                            "storage": quantity,
+                        },
+                    },
+                },
+            }));
+            let pp = PatchParams::apply(crate::consts::NAME);
+            api.patch(HOME_PVC_NAME, &pp, &patch)
+                .await
+                .map(|_| ())
+                .map_err(|error| anyhow!("failed to resize the home volume: {error}"))
+        }
+        // NOTE: the volumes cannot be shrunk, so the usage is reported instead
+        Some(_) | None => Ok(()),
+    }
+}
+
+/// Measure the home volume from the kubelet of the nodes which the user is logged in.
+#[instrument(level = Level::INFO, skip(kube), err(Display))]
+async fn get_home_pvc_usage(
+    kube: &Client,
+    user_name: &str,
+    namespace: &str,
+) -> Result<Option<u64>> {
+    let api = Api::<Node>::all(kube.clone());
+    let lp = ListParams::default().labels(&format!(
+        "{}={user_name}",
+        ::ark_api::consts::LABEL_BIND_BY_USER,
+    ));
+
+    for node in api.list_metadata(&lp).await?.items {
+        let node_name = node.name_any();
+        let request = Request::new("/api/v1/nodes")
+            .get(
+                &format!("{node_name}/proxy/stats/summary"),
+                &GetParams::default(),
+            )
+            .map_err(|error| anyhow!("failed to build a kubelet request: {error}"))?;
+        let summary: Value = match kube.request(request).await {
+            Ok(summary) => summary,
+            Err(error) => {
+                warn!("failed to get the kubelet stats ({node_name}): {error}");
+                continue;
+            }
+        };
+
+        let used = summary["pods"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|pod| pod["volume"].as_array())
+            .flatten()
+            .find(|volume| {
+                volume["pvcRef"]["name"] == HOME_PVC_NAME
+                    && volume["pvcRef"]["namespace"] == namespace
+            })
+            .and_then(|volume| volume["usedBytes"].as_u64());
+        if used.is_some() {
+            return Ok(used);
+        }
+    }
+    Ok(None)
+}
+
+/// Set the quotas of the model storage bindings, and return the measured size of the model.
+#[instrument(level = Level::INFO, skip(kube, quantity), err(Display))]
+async fn apply_home_dash(
+    kube: &Client,
+    namespace: &str,
+    model: &str,
+    quantity: &Quantity,
+) -> Result<Option<u64>> {
+    let api = Api::<ModelStorageBindingCrd>::namespaced(kube.clone(), namespace);
+    let lp = ListParams::default();
+    let bindings: Vec<_> = api
+        .list(&lp)
+        .await?
+        .items
+        .into_iter()
+        .filter(|binding| binding.spec.model == model)
+        .collect();
+    if bindings.is_empty() {
+        bail!("home model is not bound yet: {namespace}/{model}")
+    }
+
+    let storage_api = Api::<ModelStorageCrd>::namespaced(kube.clone(), namespace);
+    let pp = PatchParams::apply(crate::consts::NAME);
+    let mut used = None;
+    for binding in bindings {
+        let name = binding.name_any();
+        let quota = binding
+            .spec
+            .resources
+            .as_ref()
+            .and_then(|resources| resources.requests.as_ref())
+            .and_then(|requests| requests.get("storage"));
+        if quota != Some(quantity) {
+            info!("updating the home quota: {namespace}/{name} => {quantity:?}");
+            let patch = Patch::Merge(json!({
+                "spec": {
+                    "resources": {
+                        "requests": {
+                            "storage": quantity,
+                        },
+                    },
+                },
+            }));
+            api.patch(&name, &pp, &patch).await.map_err(|error| {
+                anyhow!("failed to update the home quota ({namespace}/{name}): {error}")
+            })?;
+        }
+
+        // NOTE: the models are measured by the dash operator
+        let bytes = storage_api
+            .get_opt(binding.spec.storage.target())
+            .await?
+            .and_then(|storage| storage.status)
+            .and_then(|status| status.usage)
+            .and_then(|usage| usage.models.get(model).and_then(|usage| usage.bytes));
+        if let Some(bytes) = bytes {
+            let bytes = bytes.try_into().unwrap_or(u64::MAX);
+            used = Some(used.map_or(bytes, |used: u64| used.max(bytes)));
+        }
+    }
+    Ok(used)
+}
+
+/// Warn the logged-in user once per threshold, and return the updated usage.
+async fn warn_user(
+    kube: &Client,
+    user_name: &str,
+    spec: &UserQuotaHomeSpec,
+    mut home: UserQuotaUsage,
+) -> UserQuotaUsage {
+    let threshold = get_threshold(spec, &home);
+    if threshold <= home.warned_threshold {
+        // NOTE: rearm the warnings when the usage has been decreased
+        home.warned_threshold = threshold;
+        return home;
+    }
+
+    let percent = home.percent().unwrap_or_default();
+    let limit = &spec.limit.0;
+    let text = if percent >= 100 {
+        format!("Your home directory is full ({percent}% of {limit}). Please clean up your files.")
+    } else {
+        format!("Your home directory is almost full ({percent}% of {limit}).")
+    };
+    let command = vec![format!(
+        "zenity --warning --title='Home Directory Quota' --text='{text}'"
+    )];
+
+    let args = BatchCommandArgs {
+        command,
+        terminal: false,
+        users: BatchCommandUsers::List(vec![user_name]),
+        wait: false,
+    };
+    match args.exec(kube).await {
+        // NOTE: the user is warned again on the next login
+        Ok(0) => (),
+        Ok(_) => home.warned_threshold = threshold,
+        Err(error) => warn!("failed to warn the user of the home quota: {user_name:?}: {error}"),
+    }
+    home
+}
+
+/// Return the highest threshold (in percent) which the usage has reached.
+fn get_threshold(spec: &UserQuotaHomeSpec, home: &UserQuotaUsage) -> Option<u8> {
+    let percent = home.percent()?;
+    spec.warning_thresholds
+        .iter()
+        .copied()
+        .chain(Some(100))
+        .filter(|&threshold| percent >= threshold as u64)
+        .max()
+}
+
+fn get_state(spec: &UserQuotaHomeSpec, home: &UserQuotaUsage) -> UserQuotaState {
+    match get_threshold(spec, home) {
+        _ if home.used.is_none() => UserQuotaState::Pending,
+        Some(100..) => UserQuotaState::Exceeded,
+        Some(_) => UserQuotaState::Warning,
+        None => UserQuotaState::Ok,
+    }
+}
+
+fn parse_bytes(quantity: &Quantity) -> Result<u64> {
+    quantity
+        .0
+        .parse::<Byte>()
+        .map(|bytes| bytes.as_u64())
+        .map_err(|error| anyhow!("failed to parse the quantity {quantity:?}: {error}"))
+}
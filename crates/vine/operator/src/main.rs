@@ -12,8 +12,10 @@ pub(crate) mod consts {
 #[tokio::main]
 async fn main() {
     join!(
+        self::ctx::usage_report::Ctx::spawn(),
         self::ctx::user_auth::Ctx::spawn_crd(),
         self::ctx::user_box_binding::Ctx::spawn_crd(),
+        self::ctx::user_quota::Ctx::spawn_crd(),
         self::ctx::user_session::Ctx::spawn(),
     );
 }
@@ -11,6 +11,14 @@ where
 {
     app.service(crate::routes::desktop::batch::post_exec_broadcast)
         .service(crate::routes::desktop::single::post_exec)
+        .service(crate::routes::quota::get)
+        .service(crate::routes::quota::list)
         .service(crate::routes::session::list)
+        .service(crate::routes::snapshot::create)
+        .service(crate::routes::snapshot::delete)
+        .service(crate::routes::snapshot::list)
+        .service(crate::routes::snapshot::restore)
+        .service(crate::routes::usage::get)
+        .service(crate::routes::usage::list)
         .service(crate::routes::user::get)
 }
@@ -1,3 +1,6 @@
 pub mod desktop;
+pub mod quota;
 pub mod session;
+pub mod snapshot;
+pub mod usage;
 pub mod user;
@@ -0,0 +1,40 @@
+use actix_web::{get, web::Data, HttpRequest, HttpResponse, Responder};
+use ark_core::result::Result;
+use kube::{api::ListParams, Api, Client};
+use tracing::{instrument, warn, Level};
+use vine_api::{user_quota::UserQuotaCrd, user_session::UserSession};
+use vine_rbac::auth::AuthUserSession;
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[get("/user/quota")]
+pub async fn get(request: HttpRequest, kube: Data<Client>) -> impl Responder {
+    let kube = kube.as_ref().clone();
+    let session = match UserSession::from_request(&kube, &request).await {
+        Ok(session) => session,
+        Err(error) => {
+            warn!("{error}");
+            return HttpResponse::from(Result::<()>::Err(error.to_string()));
+        }
+    };
+
+    // NOTE: the quota is named after the user
+    let api = Api::<UserQuotaCrd>::all(kube);
+    HttpResponse::from(Result::from(api.get_opt(&session.user_name).await))
+}
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[get("/batch/user/quota")]
+pub async fn list(request: HttpRequest, kube: Data<Client>) -> impl Responder {
+    let kube = kube.as_ref().clone();
+    if let Err(error) = UserSession::from_request(&kube, &request)
+        .await
+        .and_then(|session| session.assert_admin())
+    {
+        warn!("{error}");
+        return HttpResponse::from(Result::<()>::Err(error.to_string()));
+    };
+
+    let lp = ListParams::default();
+    let api = Api::<UserQuotaCrd>::all(kube);
+    HttpResponse::from(Result::from(api.list(&lp).await.map(|quotas| quotas.items)))
+}
@@ -0,0 +1,84 @@
+use actix_web::{
+    delete, get, post,
+    web::{Data, Path, Query},
+    HttpRequest, HttpResponse, Responder,
+};
+use ark_core::result::Result;
+use kube::Client;
+use serde::Deserialize;
+use tracing::{instrument, warn, Level};
+use vine_api::user_session::UserSession;
+use vine_rbac::auth::AuthUserSession;
+use vine_session::snapshot::SessionSnapshotManager;
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotQuery {
+    #[serde(default)]
+    class_name: Option<String>,
+}
+
+async fn get_manager(
+    request: &HttpRequest,
+    kube: Data<Client>,
+) -> ::core::result::Result<SessionSnapshotManager, HttpResponse> {
+    let kube = kube.as_ref().clone();
+    match UserSession::from_request(&kube, request).await {
+        Ok(session) => Ok(SessionSnapshotManager::new(kube, session.user_name)),
+        Err(error) => {
+            warn!("{error}");
+            Err(HttpResponse::from(Result::<()>::Err(error.to_string())))
+        }
+    }
+}
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[get("/user/snapshot")]
+pub async fn list(request: HttpRequest, kube: Data<Client>) -> impl Responder {
+    match get_manager(&request, kube).await {
+        Ok(manager) => HttpResponse::from(Result::from(manager.list().await)),
+        Err(response) => response,
+    }
+}
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[post("/user/snapshot/{name}")]
+pub async fn create(
+    request: HttpRequest,
+    kube: Data<Client>,
+    name: Path<String>,
+    Query(query): Query<SnapshotQuery>,
+) -> impl Responder {
+    match get_manager(&request, kube).await {
+        Ok(manager) => HttpResponse::from(Result::from(
+            manager.create(&name, query.class_name.as_deref()).await,
+        )),
+        Err(response) => response,
+    }
+}
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[delete("/user/snapshot/{name}")]
+pub async fn delete(
+    request: HttpRequest,
+    kube: Data<Client>,
+    name: Path<String>,
+) -> impl Responder {
+    match get_manager(&request, kube).await {
+        Ok(manager) => HttpResponse::from(Result::from(manager.delete(&name).await)),
+        Err(response) => response,
+    }
+}
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[post("/user/snapshot/{name}/restore")]
+pub async fn restore(
+    request: HttpRequest,
+    kube: Data<Client>,
+    name: Path<String>,
+) -> impl Responder {
+    match get_manager(&request, kube).await {
+        Ok(manager) => HttpResponse::from(Result::from(manager.restore(&name).await)),
+        Err(response) => response,
+    }
+}
@@ -0,0 +1,59 @@
+use actix_web::{
+    get,
+    web::{Data, Query},
+    HttpRequest, HttpResponse, Responder,
+};
+use ark_core::result::Result;
+use kube::{api::ListParams, Api, Client};
+use tracing::{instrument, warn, Level};
+use vine_api::{
+    usage_report::{UsageReportCrd, UsageReportQuery},
+    user_session::UserSession,
+};
+use vine_rbac::auth::AuthUserSession;
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[get("/user/usage")]
+pub async fn get(
+    request: HttpRequest,
+    kube: Data<Client>,
+    Query(query): Query<UsageReportQuery>,
+) -> impl Responder {
+    let kube = kube.as_ref().clone();
+    let session = match UserSession::from_request(&kube, &request).await {
+        Ok(session) => session,
+        Err(error) => {
+            warn!("{error}");
+            return HttpResponse::from(Result::<()>::Err(error.to_string()));
+        }
+    };
+
+    let lp = ListParams::default().labels(&query.to_label_selector(Some(&session.user_name)));
+    let api = Api::<UsageReportCrd>::all(kube);
+    HttpResponse::from(Result::from(
+        api.list(&lp).await.map(|reports| reports.items),
+    ))
+}
+
+#[instrument(level = Level::INFO, skip(request, kube))]
+#[get("/batch/user/usage")]
+pub async fn list(
+    request: HttpRequest,
+    kube: Data<Client>,
+    Query(query): Query<UsageReportQuery>,
+) -> impl Responder {
+    let kube = kube.as_ref().clone();
+    if let Err(error) = UserSession::from_request(&kube, &request)
+        .await
+        .and_then(|session| session.assert_admin())
+    {
+        warn!("{error}");
+        return HttpResponse::from(Result::<()>::Err(error.to_string()));
+    };
+
+    let lp = ListParams::default().labels(&query.to_label_selector(None));
+    let api = Api::<UsageReportCrd>::all(kube);
+    HttpResponse::from(Result::from(
+        api.list(&lp).await.map(|reports| reports.items),
+    ))
+}
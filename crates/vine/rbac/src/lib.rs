@@ -4,4 +4,5 @@ pub mod auth;
 pub mod login;
 pub mod logout;
 mod node_selector;
+mod placement;
 mod session;
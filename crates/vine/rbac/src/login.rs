@@ -1,7 +1,7 @@
 use anyhow::Result;
-use kube::Client;
+use kube::{Client, ResourceExt};
 use tracing::{instrument, Level};
-use vine_api::user_auth::UserSessionResponse;
+use vine_api::user_auth::{UserSessionError, UserSessionResponse};
 
 #[instrument(level = Level::INFO, skip(client), err(Display))]
 pub async fn execute(
@@ -23,3 +23,31 @@ pub async fn execute(
     )
     .await
 }
+
+/// Login to one of the available boxes, in the order of the session placement.
+#[instrument(level = Level::INFO, skip(client), err(Display))]
+pub async fn execute_any(
+    client: &Client,
+    user_name: &str,
+    logout_on_failed: bool,
+) -> Result<UserSessionResponse> {
+    let candidates = super::placement::list_candidates(client, user_name).await?;
+    let candidates = super::placement::schedule(client, candidates).await;
+
+    let mut response = UserSessionResponse::Error(UserSessionError::NodeNotFound);
+    for node in candidates {
+        response = execute(client, &node.name_any(), user_name, logout_on_failed).await?;
+        match &response {
+            // try the next box only if this box is not available
+            UserSessionResponse::Error(
+                UserSessionError::AlreadyLoggedInByUser { .. }
+                | UserSessionError::NodeNotFound
+                | UserSessionError::NodeNotInCluster
+                | UserSessionError::NodeReserved
+                | UserSessionError::QuotaMismatched,
+            ) => continue,
+            _ => break,
+        }
+    }
+    Ok(response)
+}
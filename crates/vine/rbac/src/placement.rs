@@ -0,0 +1,176 @@
+use anyhow::Result;
+use k8s_openapi::api::core::v1::Node;
+use kube::{api::ListParams, Api, Client, ResourceExt};
+use tracing::{instrument, Level};
+use vine_session::AllocationState;
+
+mod consts {
+    pub const LABEL_ROLE: &str = "node-role.kubernetes.io/kiss";
+    pub const ROLE_DESKTOP: &str = "Desktop";
+}
+
+/// Return the desktop nodes which the user can log in to.
+///
+/// If the user already has a session, only that node is returned.
+#[instrument(level = Level::INFO, skip(client), err(Display))]
+pub(crate) async fn list_candidates(client: &Client, user_name: &str) -> Result<Vec<Node>> {
+    let api = Api::<Node>::all(client.clone());
+    let lp = ListParams::default().labels(&format!(
+        "{key}={value}",
+        key = self::consts::LABEL_ROLE,
+        value = self::consts::ROLE_DESKTOP,
+    ));
+
+    let mut candidates = Vec::default();
+    for node in api.list(&lp).await?.items {
+        if node.spec.as_ref().and_then(|spec| spec.unschedulable) == Some(true) {
+            continue;
+        }
+
+        match ::vine_session::is_allocable(node.labels(), None, user_name) {
+            AllocationState::AllocatedByMyself => return Ok(vec![node]),
+            AllocationState::NotAllocated => candidates.push(node),
+            AllocationState::AllocatedByOtherNode { .. }
+            | AllocationState::AllocatedByOtherUser { .. } => continue,
+        }
+    }
+    Ok(candidates)
+}
+
+/// Sort the candidate nodes by preference.
+///
+/// The node picked by the kubegraph solver comes first if available,
+/// and the others follow the default order (by name).
+#[cfg_attr(not(feature = "kubegraph"), allow(unused_variables))]
+#[instrument(level = Level::INFO, skip(client, candidates))]
+pub(crate) async fn schedule(client: &Client, mut candidates: Vec<Node>) -> Vec<Node> {
+    candidates.sort_by_key(|node| node.name_any());
+
+    #[cfg(feature = "kubegraph")]
+    if candidates.len() > 1 {
+        match self::kubegraph::solve(client, &candidates).await {
+            Ok(Some(node_name)) => {
+                if let Some(index) = candidates
+                    .iter()
+                    .position(|node| node.name_any() == node_name)
+                {
+                    let node = candidates.remove(index);
+                    candidates.insert(0, node);
+                }
+            }
+            Ok(None) => ::tracing::info!(
+                "no session placement has been found; falling back to the default scheduler"
+            ),
+            Err(error) => ::tracing::warn!(
+                "failed to solve session placement; falling back to the default scheduler: {error}"
+            ),
+        }
+    }
+
+    candidates
+}
+
+#[cfg(feature = "kubegraph")]
+mod kubegraph {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use anyhow::{anyhow, Result};
+    use k8s_openapi::api::core::v1::Node;
+    use kiss_api::r#box::{BoxCrd, BoxGroupRole};
+    use kube::{api::ListParams, Api, Client, ResourceExt};
+    use kubegraph_api::{
+        frame::polars::get_column,
+        graph::{GraphData, GraphMetadataPinnedExt},
+        problem::ProblemSpec,
+        solver::NetworkSolver as _,
+    };
+    use kubegraph_solver_ortools::NetworkSolver;
+    use polars::{df, lazy::dsl};
+    use tracing::{instrument, Level};
+
+    /// The virtual node which supplies the session to one of the candidates
+    const NODE_STORAGE: &str = "storage";
+
+    /// The network distance from the user's storage, used as the unit cost of the session flow
+    const DISTANCE_SAME_RACK: i64 = 1;
+    const DISTANCE_OTHER_RACK: i64 = 10;
+    const DISTANCE_UNKNOWN: i64 = 100;
+
+    /// Express the candidate nodes and the session demand as a network problem,
+    /// and return the node which minimizes the network distance to the user's storage.
+    #[instrument(level = Level::INFO, skip(client, candidates), err(Display))]
+    pub(super) async fn solve(client: &Client, candidates: &[Node]) -> Result<Option<String>> {
+        let names: Vec<_> = candidates.iter().map(|node| node.name_any()).collect();
+        let distances = get_distances(client, &names).await?;
+
+        // Step 1. Define nodes: the storage supplies a session, and each candidate can take one
+        let nodes = df!(
+            "name" => ::std::iter::once(NODE_STORAGE)
+                .chain(names.iter().map(String::as_str))
+                .collect::<Vec<_>>(),
+            "capacity" => ::std::iter::once(0i64)
+                .chain(names.iter().map(|_| 1))
+                .collect::<Vec<_>>(),
+            "supply" => ::std::iter::once(1i64)
+                .chain(names.iter().map(|_| 0))
+                .collect::<Vec<_>>(),
+            "unit_cost" => vec![0i64; names.len() + 1],
+        )
+        .map_err(|error| anyhow!("failed to define session placement nodes: {error}"))?;
+
+        // Step 2. Define edges: from the storage to each candidate
+        let edges = df!(
+            "src" => vec![NODE_STORAGE; names.len()],
+            "sink" => &names,
+            "capacity" => vec![1i64; names.len()],
+            "unit_cost" => &distances,
+        )
+        .map_err(|error| anyhow!("failed to define session placement edges: {error}"))?;
+
+        // Step 3. Solve the problem
+        let problem = ProblemSpec::default();
+        let GraphData { edges, nodes: _ } = NetworkSolver::default()
+            .solve(GraphData { edges, nodes }, &problem)
+            .await?;
+
+        // Step 4. Find the candidate which takes the session
+        let key_flow = problem.metadata.flow();
+        let key_sink = problem.metadata.sink();
+        let edges = edges
+            .filter(dsl::col(key_flow).gt(dsl::lit(0i64)))
+            .collect()
+            .map_err(|error| anyhow!("failed to collect session placement: {error}"))?;
+        let sink = get_column(&edges, "edge", "sink", key_sink, None)?;
+        let sink = sink
+            .str()
+            .map_err(|error| anyhow!("failed to parse session placement: {error}"))?;
+        Ok(sink.into_iter().flatten().next().map(Into::into))
+    }
+
+    /// Return the network distance between each node and the nearest storage box, by racks.
+    async fn get_distances(client: &Client, names: &[String]) -> Result<Vec<i64>> {
+        let api = Api::<BoxCrd>::all(client.clone());
+        let lp = ListParams::default();
+        let boxes = api.list(&lp).await?.items;
+
+        let racks: BTreeMap<_, _> = boxes
+            .iter()
+            .filter_map(|r#box| Some((r#box.name_any(), r#box.spec.rack.as_ref()?.name.as_str())))
+            .collect();
+        let racks_storage: BTreeSet<_> = boxes
+            .iter()
+            .filter(|r#box| r#box.spec.group.role == BoxGroupRole::Storage)
+            .filter_map(|r#box| r#box.spec.rack.as_ref())
+            .map(|rack| rack.name.as_str())
+            .collect();
+
+        Ok(names
+            .iter()
+            .map(|name| match racks.get(name) {
+                Some(rack) if racks_storage.contains(rack) => DISTANCE_SAME_RACK,
+                Some(_) if !racks_storage.is_empty() => DISTANCE_OTHER_RACK,
+                _ => DISTANCE_UNKNOWN,
+            })
+            .collect())
+    }
+}
@@ -4,6 +4,8 @@ pub mod batch;
 pub mod exec;
 #[cfg(feature = "shell")]
 pub mod shell;
+#[cfg(feature = "snapshot")]
+pub mod snapshot;
 
 use std::{collections::BTreeMap, fmt, fs, path::PathBuf, time::Duration};
 
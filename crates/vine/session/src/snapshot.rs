@@ -0,0 +1,271 @@
+//! Named snapshots of the user's remote home volume, backed by CSI volume snapshots.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Utc};
+use k8s_openapi::{
+    api::core::v1::{
+        PersistentVolumeClaim, PersistentVolumeClaimSpec, TypedLocalObjectReference,
+        VolumeResourceRequirements,
+    },
+    apimachinery::pkg::api::resource::Quantity,
+};
+use kube::{
+    api::{DeleteParams, ListParams, PostParams},
+    core::{ApiResource, DynamicObject, GroupVersionKind, ObjectMeta},
+    Api, Client, ResourceExt,
+};
+use maplit::btreemap;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::{info, instrument, Level};
+use vine_api::user::UserCrd;
+
+/// The name of the home volume claim which can be snapshotted.
+///
+/// Only the `RemoteOwned` home volumes are provisioned by the CSI drivers.
+pub const HOME_PVC_NAME: &str = "desktop-remote-owned";
+
+pub const LABEL_SNAPSHOT_USER: &str = "vine.ulagbulag.io/snapshot-user";
+
+const SNAPSHOT_API_GROUP: &str = "snapshot.storage.k8s.io";
+const SNAPSHOT_KIND: &str = "VolumeSnapshot";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSnapshot {
+    pub name: String,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub ready: bool,
+    #[serde(default)]
+    pub restore_size: Option<String>,
+}
+
+impl From<DynamicObject> for SessionSnapshot {
+    fn from(object: DynamicObject) -> Self {
+        let status = object.data.get("status");
+        Self {
+            name: object.name_any(),
+            created_at: object.metadata.creation_timestamp.map(|time| time.0),
+            ready: status
+                .and_then(|status| status.get("readyToUse"))
+                .and_then(|value| value.as_bool())
+                .unwrap_or_default(),
+            restore_size: status
+                .and_then(|status| status.get("restoreSize"))
+                .and_then(|value| value.as_str())
+                .map(Into::into),
+        }
+    }
+}
+
+pub struct SessionSnapshotManager {
+    kube: Client,
+    namespace: String,
+    user_name: String,
+}
+
+impl SessionSnapshotManager {
+    const TIMEOUT_HOME_DELETION: Duration = Duration::from_secs(60);
+
+    pub fn new(kube: Client, user_name: String) -> Self {
+        Self {
+            kube,
+            namespace: UserCrd::user_namespace_with(&user_name),
+            user_name,
+        }
+    }
+
+    fn api(&self) -> Api<DynamicObject> {
+        let gvk = GroupVersionKind::gvk(SNAPSHOT_API_GROUP, "v1", SNAPSHOT_KIND);
+        let ar = ApiResource::from_gvk_with_plural(&gvk, "volumesnapshots");
+        Api::namespaced_with(self.kube.clone(), &self.namespace, &ar)
+    }
+
+    /// Take a snapshot of the current home volume.
+    #[instrument(level = Level::INFO, skip(self), fields(user_name = %self.user_name), err(Display))]
+    pub async fn create(&self, name: &str, class_name: Option<&str>) -> Result<SessionSnapshot> {
+        let api = Api::<PersistentVolumeClaim>::namespaced(self.kube.clone(), &self.namespace);
+        if api.get_opt(HOME_PVC_NAME).await?.is_none() {
+            bail!("no remote home volume to snapshot: {}", &self.user_name)
+        }
+
+        let pp = PostParams {
+            field_manager: Some(crate::consts::NAME.into()),
+            ..Default::default()
+        };
+        let snapshot: DynamicObject = ::serde_json::from_value(json!({
+            "apiVersion": format!("{SNAPSHOT_API_GROUP}/v1"),
+            "kind": SNAPSHOT_KIND,
+            "metadata": {
+                "name": name,
+                "namespace": &self.namespace,
+                "labels": {
+                    "app": "desktop",
+                    LABEL_SNAPSHOT_USER: &self.user_name,
+                },
+            },
+            "spec": {
+                "source": {
+                    "persistentVolumeClaimName": HOME_PVC_NAME,
+                },
+                "volumeSnapshotClassName": class_name,
+            },
+        }))?;
+
+        self.api()
+            .create(&pp, &snapshot)
+            .await
+            .map(Into::into)
+            .map_err(|error| anyhow!("failed to create a snapshot ({name}): {error}"))
+    }
+
+    /// List the snapshots of the user, in the order of creation.
+    #[instrument(level = Level::INFO, skip(self), fields(user_name = %self.user_name), err(Display))]
+    pub async fn list(&self) -> Result<Vec<SessionSnapshot>> {
+        let lp = ListParams::default().labels(&format!(
+            "{LABEL_SNAPSHOT_USER}={user_name}",
+            user_name = &self.user_name,
+        ));
+        let mut snapshots: Vec<SessionSnapshot> = self
+            .api()
+            .list(&lp)
+            .await
+            .map_err(|error| anyhow!("failed to list snapshots: {error}"))?
+            .items
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        snapshots.sort_by_key(|snapshot| snapshot.created_at);
+        Ok(snapshots)
+    }
+
+    #[instrument(level = Level::INFO, skip(self), fields(user_name = %self.user_name), err(Display))]
+    pub async fn delete(&self, name: &str) -> Result<()> {
+        let dp = DeleteParams::background();
+        self.get(name).await?;
+        self.api()
+            .delete(name, &dp)
+            .await
+            .map(|_| ())
+            .map_err(|error| anyhow!("failed to delete a snapshot ({name}): {error}"))
+    }
+
+    /// Replace the home volume with a new one restored from the snapshot.
+    ///
+    /// The user should be logged out, so that the next session is launched on the restored volume.
+    #[instrument(level = Level::INFO, skip(self), fields(user_name = %self.user_name), err(Display))]
+    pub async fn restore(&self, name: &str) -> Result<()> {
+        let snapshot = self.get(name).await?;
+        if !snapshot.ready {
+            bail!("snapshot is not ready: {name}")
+        }
+
+        let user = Api::<UserCrd>::all(self.kube.clone())
+            .get(&self.user_name)
+            .await
+            .map_err(|error| anyhow!("failed to get user ({}): {error}", &self.user_name))?;
+        if user
+            .labels()
+            .get(::ark_api::consts::LABEL_BIND_STATUS)
+            .map(|value| value == "true")
+            .unwrap_or_default()
+        {
+            bail!("cannot restore the home volume while the session is running: {name}")
+        }
+
+        let api = Api::<PersistentVolumeClaim>::namespaced(self.kube.clone(), &self.namespace);
+        let last_spec = match api.get_opt(HOME_PVC_NAME).await? {
+            Some(pvc) => {
+                info!("replacing the home volume with the snapshot: {name}");
+                self.delete_home(&api).await?;
+                pvc.spec
+            }
+            None => None,
+        };
+
+        let resources = last_spec
+            .as_ref()
+            .and_then(|spec| spec.resources.clone())
+            .or_else(|| {
+                snapshot
+                    .restore_size
+                    .as_ref()
+                    .map(|size| VolumeResourceRequirements {
+                        requests: Some(btreemap! {
+                            "storage".into() => Quantity(size.clone()),
+                        }),
+                        ..Default::default()
+                    })
+            });
+        if resources.is_none() {
+            bail!("failed to infer the home volume size of the snapshot: {name}")
+        }
+
+        let pp = PostParams {
+            field_manager: Some(crate::consts::NAME.into()),
+            ..Default::default()
+        };
+        let pvc = PersistentVolumeClaim {
+            metadata: ObjectMeta {
+                name: Some(HOME_PVC_NAME.into()),
+                namespace: Some(self.namespace.clone()),
+                labels: Some(btreemap! {
+                    "app".into() => "desktop".into(),
+                    "local".into() => "false".into(),
+                    "shared".into() => "false".into(),
+                }),
+                ..Default::default()
+            },
+            spec: Some(PersistentVolumeClaimSpec {
+                access_modes: Some(vec!["ReadWriteOnce".into()]),
+                data_source: Some(TypedLocalObjectReference {
+                    api_group: Some(SNAPSHOT_API_GROUP.into()),
+                    kind: SNAPSHOT_KIND.into(),
+                    name: name.into(),
+                }),
+                resources,
+                storage_class_name: last_spec.and_then(|spec| spec.storage_class_name),
+                ..Default::default()
+            }),
+            status: None,
+        };
+        api.create(&pp, &pvc)
+            .await
+            .map(|_| ())
+            .map_err(|error| anyhow!("failed to restore the home volume ({name}): {error}"))
+    }
+
+    async fn get(&self, name: &str) -> Result<SessionSnapshot> {
+        match self.api().get_opt(name).await? {
+            Some(snapshot)
+                if snapshot.labels().get(LABEL_SNAPSHOT_USER) == Some(&self.user_name) =>
+            {
+                Ok(snapshot.into())
+            }
+            Some(_) | None => bail!("no such snapshot: {name}"),
+        }
+    }
+
+    async fn delete_home(&self, api: &Api<PersistentVolumeClaim>) -> Result<()> {
+        let dp = DeleteParams::foreground();
+        api.delete(HOME_PVC_NAME, &dp)
+            .await
+            .map_err(|error| anyhow!("failed to delete the home volume: {error}"))?;
+
+        // Wait until the volume is released
+        let interval = Duration::from_secs(1);
+        let mut elapsed = Duration::ZERO;
+        while api.get_opt(HOME_PVC_NAME).await?.is_some() {
+            if elapsed >= Self::TIMEOUT_HOME_DELETION {
+                bail!("timeout while deleting the home volume: {HOME_PVC_NAME}")
+            }
+            ::tokio::time::sleep(interval).await;
+            elapsed += interval;
+        }
+        Ok(())
+    }
+}
@@ -192,6 +192,11 @@ WantedBy=multi-user.target
 EOF
 ln -sf /etc/systemd/system/notify-new-box.service /etc/systemd/system/multi-user.target.wants/notify-new-box.service
 
+## KISS Enrollment Token
+mkdir -p /etc/kiss
+(grep -oP 'kiss\.enrollment_token=\K\S+' /proc/cmdline || true) >/etc/kiss/enrollment-token
+chmod 400 /etc/kiss/enrollment-token
+
 ## KISS Notifier Script
 cat <<EOF >/usr/local/bin/notify-new-box.sh
 #!/bin/bash
@@ -204,10 +209,12 @@ set -e -o pipefail
 
 # Collect node info
 ADDRESS="\$(ip route get 1.1.1.1 | grep -oP 'src \K\d+(\.\d+){3}' | head -1)"
+ARCH="\$(uname -m)"
 UUID="\$(cat /sys/class/dmi/id/product_uuid)"
+TOKEN="\$(cat /etc/kiss/enrollment-token 2>/dev/null || true)"
 
 # Submit to KISS Cluster
-exec curl --retry 5 --retry-delay 5 "http://gateway.kiss.svc.ops.openark/new?address=\${ADDRESS}&uuid=\${UUID}"
+exec curl --retry 5 --retry-delay 5 "http://gateway.kiss.svc.ops.openark/new?address=\${ADDRESS}&arch=\${ARCH}&uuid=\${UUID}&token=\${TOKEN}"
 EOF
 chmod 550 /usr/local/bin/notify-new-box.sh
 
@@ -0,0 +1,79 @@
+#!/bin/bash
+# Copyright (c) 2024 Ho Kim (ho.kim@ulagbulag.io). All rights reserved.
+# Use of this source code is governed by a GPL-3-style license that can be
+# found in the LICENSE file.
+
+# Prehibit errors
+set -e -o pipefail
+
+# Check bucket
+if [ "x${BACKUP_BUCKET}" == "x" ]; then
+    echo "Skipping backup job: Bucket is not set"
+    exit 0
+fi
+
+BACKUP_NAME="$(date -u +'%Y-%m-%dT%H-%M-%SZ')"
+BACKUP_DIR="./backup/${BACKUP_NAME}"
+BACKUP_URL="s3://${BACKUP_BUCKET}/${BACKUP_PREFIX}"
+
+function s3() {
+    aws --endpoint-url "${BACKUP_ENDPOINT}" s3 "$@"
+}
+
+mkdir -p "${BACKUP_DIR}/manifests"
+
+# Take an etcd snapshot
+if [ "x${BACKUP_ETCD}" == "xtrue" ]; then
+    echo "Taking an etcd snapshot..."
+    ETCDCTL_API=3 etcdctl \
+        --endpoints "https://${NODE_IP}:2379" \
+        --cacert "${ETCD_CERTS_DIR}/ca.pem" \
+        --cert "${ETCD_CERTS_DIR}/node-${NODE_NAME}.pem" \
+        --key "${ETCD_CERTS_DIR}/node-${NODE_NAME}-key.pem" \
+        snapshot save "${BACKUP_DIR}/etcd.db"
+
+    # Verify the snapshot integrity
+    echo "Verifying the etcd snapshot..."
+    etcdutl snapshot status "${BACKUP_DIR}/etcd.db" --write-out=table
+fi
+
+# Collect the custom resources
+for group in ${BACKUP_GROUPS//,/ }; do
+    echo "Collecting the custom resources: ${group}"
+    for resource in $(
+        kubectl api-resources --api-group="${group}" --verbs=list --output=name
+    ); do
+        kubectl get "${resource}" --all-namespaces --output=yaml \
+            >"${BACKUP_DIR}/manifests/${resource}.yaml"
+    done
+done
+
+# Pack the backup
+tar -czf "./backup/${BACKUP_NAME}.tar.gz" -C "./backup" "${BACKUP_NAME}"
+(cd "./backup" && sha256sum "${BACKUP_NAME}.tar.gz" >"${BACKUP_NAME}.tar.gz.sha256")
+
+# Verify the archive integrity
+(cd "./backup" && sha256sum -c "${BACKUP_NAME}.tar.gz.sha256")
+tar -tzf "./backup/${BACKUP_NAME}.tar.gz" >/dev/null
+
+# Upload
+echo "Uploading the backup: ${BACKUP_URL}/${BACKUP_NAME}.tar.gz"
+s3 cp "./backup/${BACKUP_NAME}.tar.gz.sha256" "${BACKUP_URL}/${BACKUP_NAME}.tar.gz.sha256"
+s3 cp "./backup/${BACKUP_NAME}.tar.gz" "${BACKUP_URL}/${BACKUP_NAME}.tar.gz"
+
+# Rotate the old backups
+if [ "${BACKUP_RETENTION:-0}" -gt 0 ]; then
+    s3 ls "${BACKUP_URL}/" |
+        awk '{print $4}' |
+        { grep -E '\.tar\.gz$' || true; } |
+        sort -r |
+        tail -n "+$((BACKUP_RETENTION + 1))" |
+        while read -r name; do
+            echo "Removing the old backup: ${name}"
+            s3 rm "${BACKUP_URL}/${name}"
+            s3 rm "${BACKUP_URL}/${name}.sha256" || true
+        done
+fi
+
+# Cleanup
+rm -rf "./backup"
@@ -0,0 +1,71 @@
+#!/bin/bash
+# Copyright (c) 2022 Ho Kim (ho.kim@ulagbulag.io). All rights reserved.
+# Use of this source code is governed by a GPL-3-style license that can be
+# found in the LICENSE file.
+
+# Prehibit errors
+set -e
+
+# Configure default environment variables
+GIT_COMMIT_INTERVAL_SECONDS="${GIT_COMMIT_INTERVAL_SECONDS:-60}"
+GIT_SIGNING_KEY_PATH="${GIT_SIGNING_KEY_PATH:-/root/.ssh/id_ed25519_signing}"
+INVENTORY_PATH="${INVENTORY_PATH:-/inventory}"
+
+# Check repository
+if [ "x${GIT_REPOSITORY}" == "x" ]; then
+    echo "Skipping inventory export: Git repository is not set"
+    exec sleep infinity
+fi
+
+# Configure git
+git config --global user.email "${GIT_USER_EMAIL}"
+git config --global user.name "${GIT_USER_NAME}"
+
+# Configure commit signing
+if [ -s "${GIT_SIGNING_KEY_PATH}" ]; then
+    echo "Enabling commit signing"
+    git config --global gpg.format ssh
+    git config --global user.signingkey "${GIT_SIGNING_KEY_PATH}"
+    git config --global commit.gpgsign true
+fi
+
+# Download repository
+rm -rf "./inventory"
+git clone "${GIT_REPOSITORY}" "./inventory"
+cd "./inventory"
+
+# Checkout branch
+if git ls-remote --exit-code --heads "origin" "${GIT_BRANCH}" >/dev/null; then
+    git switch "${GIT_BRANCH}"
+else
+    # Create an empty branch
+    git switch --orphan "${GIT_BRANCH}"
+fi
+
+# Export the inventory in batches
+while true; do
+    # Sync with the other writers (e.g. the snapshot job)
+    if git ls-remote --exit-code --heads "origin" "${GIT_BRANCH}" >/dev/null; then
+        git fetch "origin" "${GIT_BRANCH}"
+        git reset --hard "origin/${GIT_BRANCH}"
+    fi
+
+    # Dump the rendered inventory
+    mkdir -p "./kiss/inventory"
+    for file in "inventory.yaml" "hosts.yaml"; do
+        if [ -f "${INVENTORY_PATH}/${file}" ]; then
+            cp "${INVENTORY_PATH}/${file}" "./kiss/inventory/${file}"
+        fi
+    done
+
+    # Commit and push only if changed
+    git add --force "./kiss/inventory"
+    if ! git diff --cached --quiet; then
+        git commit --message "Automatic Upload of Inventory ($(date -u +'%Y-%m-%dT%H:%M:%SZ'))"
+        git push --set-upstream "origin" "${GIT_BRANCH}" ||
+            echo "Failed to push the inventory; retrying on the next batch"
+    fi
+
+    # Batch the changes until the next interval
+    sleep "${GIT_COMMIT_INTERVAL_SECONDS}"
+done
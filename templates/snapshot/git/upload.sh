@@ -21,7 +21,8 @@ git clone "${GIT_REPOSITORY}" "./snapshot"
 cd "./snapshot"
 
 # Checkout branch
-if $(git branch --list | grep "^\*\? *${GIT_BRANCH} *\$" >/dev/null); then
+# NOTE: the remote branch is checked, so that the inventory history is preserved
+if git ls-remote --exit-code --heads "origin" "${GIT_BRANCH}" >/dev/null; then
     git switch "${GIT_BRANCH}"
 else
     # Create an empty branch